@@ -0,0 +1,150 @@
+// Polls the admin API and renders a quick overview of the instance.
+// The admin key stays in this tab's sessionStorage.
+(function () {
+  'use strict';
+
+  const REFRESH_MS = 5000;
+  const KEY_STORAGE = 'x402-admin-key';
+
+  // Counters summed across labels from /metrics
+  const METRICS = [
+    ['Verify requests', 'x402_verify_requests_total'],
+    ['Settle requests', 'x402_settle_requests_total'],
+    ['Verification failures', 'x402_verification_failure_total'],
+    ['Dedup hits', 'x402_dedup_hits_total'],
+    ['Webhook deliveries', 'x402_webhook_deliveries_total'],
+    ['Fee payer spend today (lamports)', 'x402_fee_payer_spend_today_lamports'],
+    ['Audit queue depth', 'x402_audit_queue_depth'],
+    ['Cache hit ratio', 'x402_cache_hit_ratio'],
+  ];
+
+  const $ = (id) => document.getElementById(id);
+
+  function adminKey() {
+    return sessionStorage.getItem(KEY_STORAGE) || '';
+  }
+
+  async function fetchAdmin(path, asText) {
+    const headers = {};
+    if (adminKey()) headers['X-Admin-Key'] = adminKey();
+    const response = await fetch(path, { headers });
+    if (!response.ok) throw new Error(path + ': HTTP ' + response.status);
+    return asText ? response.text() : response.json();
+  }
+
+  function cell(text, className) {
+    const td = document.createElement('td');
+    td.textContent = text === undefined || text === null ? '' : String(text);
+    if (className) td.className = className;
+    return td;
+  }
+
+  function renderList(element, entries) {
+    element.replaceChildren();
+    for (const [label, value] of entries) {
+      const dt = document.createElement('dt');
+      const dd = document.createElement('dd');
+      dt.textContent = label;
+      dd.textContent = value === undefined || value === null ? '–' : String(value);
+      element.append(dt, dd);
+    }
+  }
+
+  // Sum of every sample of each metric in the Prometheus text format
+  function parseMetrics(text) {
+    const sums = {};
+    for (const line of text.split('\n')) {
+      if (!line || line.startsWith('#')) continue;
+      const match = line.match(/^([a-zA-Z_:][a-zA-Z0-9_:]*)(\{[^}]*\})?\s+(\S+)/);
+      if (!match) continue;
+      const value = Number(match[3]);
+      if (Number.isNaN(value)) continue;
+      sums[match[1]] = (sums[match[1]] || 0) + value;
+    }
+    return sums;
+  }
+
+  function renderStats(stats) {
+    renderList($('stats'), [
+      ['Version', stats.version],
+      ['Network', stats.network],
+      ['Cache entries', stats.cache_stats.entries],
+      ['Cache size', stats.cache_stats.size],
+    ]);
+  }
+
+  function renderMetrics(text) {
+    const sums = parseMetrics(text);
+    renderList($('metrics'), METRICS.map(([label, name]) => {
+      const value = sums[name];
+      return [label, value === undefined ? undefined : Math.round(value * 1000) / 1000];
+    }));
+  }
+
+  function renderWebhooks(status) {
+    const rows = $('webhooks');
+    rows.replaceChildren();
+    if (!status) {
+      $('webhooks-pending').textContent = 'Webhooks are not configured.';
+      return;
+    }
+    for (const endpoint of status.endpoints) {
+      const status = endpoint.status;
+      const tr = document.createElement('tr');
+      tr.append(
+        cell(endpoint.name),
+        cell(endpoint.enabled ? 'yes' : 'no'),
+        cell(status.delivered, 'ok'),
+        cell(status.failed, status.consecutive_failures > 0 ? 'bad' : ''),
+        cell(status.last_error, 'error'),
+      );
+      rows.append(tr);
+    }
+    $('webhooks-pending').textContent = status.pending + ' deliveries pending';
+  }
+
+  function renderAudit(page) {
+    const rows = $('audit');
+    rows.replaceChildren();
+    for (const event of page.events) {
+      const tr = document.createElement('tr');
+      tr.append(
+        cell(new Date(event.timestamp).toLocaleString()),
+        cell(event.event_type),
+        cell(event.network),
+        cell(event.payer),
+        cell(event.amount),
+        cell(event.tenant),
+        cell(event.error, 'error'),
+      );
+      rows.append(tr);
+    }
+  }
+
+  async function refresh() {
+    const errors = [];
+    const run = (promise, render) => promise.then(render).catch((e) => errors.push(e.message));
+
+    await Promise.all([
+      run(fetchAdmin('/admin/stats'), renderStats),
+      run(fetchAdmin('/metrics', true), renderMetrics),
+      // 501 when webhooks aren't enabled
+      run(fetchAdmin('/admin/webhooks/status').catch(() => null), renderWebhooks),
+      run(fetchAdmin('/admin/audit?limit=20'), renderAudit),
+    ]);
+
+    $('error').hidden = errors.length === 0;
+    $('error').textContent = errors.join(' · ');
+    $('updated').textContent = 'Updated ' + new Date().toLocaleTimeString();
+  }
+
+  $('admin-key').value = adminKey();
+  $('auth').addEventListener('submit', (event) => {
+    event.preventDefault();
+    sessionStorage.setItem(KEY_STORAGE, $('admin-key').value.trim());
+    refresh();
+  });
+
+  refresh();
+  setInterval(refresh, REFRESH_MS);
+})();
@@ -0,0 +1,24 @@
+//! Generates the C header for `src/ffi.rs` with cbindgen into `OUT_DIR`.
+//! The copy checked in at `include/x402_facilitator.h` is compared against
+//! it by a unit test in `src/ffi.rs`, so the header can't drift from the
+//! exported functions.
+
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let crate_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is set by cargo"));
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).expect("Failed to read cbindgen.toml");
+
+    // Only the FFI module: the rest of the crate has no C API
+    let bindings = cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir.join("src/ffi.rs"))
+        .generate()
+        .expect("Failed to generate the C header from src/ffi.rs");
+
+    bindings.write_to_file(out_dir.join("x402_facilitator.h"));
+}
@@ -34,6 +34,34 @@ class CVerifyResult(ctypes.Structure):
     ]
 
 
+class SettleResult:
+    """Result of payment settlement"""
+    def __init__(self, success: bool, transaction: Optional[str] = None, payer: Optional[str] = None,
+                 error_message: Optional[str] = None, receipt: Optional[Dict[str, Any]] = None):
+        self.success = success
+        self.transaction = transaction
+        self.payer = payer
+        self.error_message = error_message
+        self.receipt = receipt
+    
+    def __repr__(self):
+        if self.success:
+            return f"SettleResult(success=True, transaction='{self.transaction}')"
+        else:
+            return f"SettleResult(success=False, error='{self.error_message}')"
+
+
+class CSettleResult(ctypes.Structure):
+    """C-compatible settlement result structure"""
+    _fields_ = [
+        ("success", ctypes.c_bool),
+        ("transaction", ctypes.c_char_p),
+        ("payer", ctypes.c_char_p),
+        ("error_message", ctypes.c_char_p),
+        ("receipt_json", ctypes.c_char_p),
+    ]
+
+
 class X402Facilitator:
     """
     Python wrapper for x402 Rust Facilitator
@@ -138,6 +166,22 @@ class X402Facilitator:
         # x402_free_result
         self.lib.x402_free_result.argtypes = [CVerifyResult]
         self.lib.x402_free_result.restype = None
+        
+        # x402_context_new / x402_context_free
+        self.lib.x402_context_new.argtypes = [ctypes.c_char_p, ctypes.c_char_p, ctypes.c_char_p]
+        self.lib.x402_context_new.restype = ctypes.c_void_p
+        self.lib.x402_context_free.argtypes = [ctypes.c_void_p]
+        self.lib.x402_context_free.restype = None
+        
+        # x402_verify_payment_full
+        self.lib.x402_verify_payment_full.argtypes = [ctypes.c_void_p, ctypes.c_char_p, ctypes.c_char_p]
+        self.lib.x402_verify_payment_full.restype = CVerifyResult
+        
+        # x402_settle_payment / x402_free_settle_result
+        self.lib.x402_settle_payment.argtypes = [ctypes.c_void_p, ctypes.c_char_p, ctypes.c_char_p]
+        self.lib.x402_settle_payment.restype = CSettleResult
+        self.lib.x402_free_settle_result.argtypes = [CSettleResult]
+        self.lib.x402_free_settle_result.restype = None
     
     def version(self) -> str:
         """Get library version"""
@@ -180,6 +224,62 @@ class X402Facilitator:
         self.lib.x402_free_result(c_result)
         
         return result
+    
+    def connect(self, rpc_url: str, network: str, fee_payer_private_key: str):
+        """
+        Create a context for full verification and settlement
+        
+        Args:
+            rpc_url: Solana RPC endpoint
+            network: "solana", "solana-devnet" or "solana-testnet"
+            fee_payer_private_key: Base58 fee payer keypair
+        """
+        self.close()
+        self.context = self.lib.x402_context_new(
+            rpc_url.encode('utf-8'),
+            network.encode('utf-8'),
+            fee_payer_private_key.encode('utf-8'),
+        )
+        if not self.context:
+            raise RuntimeError("Failed to create x402 context (see logs)")
+    
+    def close(self):
+        """Release the context created by connect()"""
+        if getattr(self, 'context', None):
+            self.lib.x402_context_free(self.context)
+            self.context = None
+    
+    def verify_full(self, payment: Dict[str, Any], requirements: Dict[str, Any]) -> VerifyResult:
+        """Verify a payment with RPC checks (requires connect())"""
+        c_result = self.lib.x402_verify_payment_full(
+            getattr(self, 'context', None),
+            json.dumps(payment).encode('utf-8'),
+            json.dumps(requirements).encode('utf-8'),
+        )
+        result = VerifyResult(
+            c_result.is_valid,
+            c_result.error_message.decode('utf-8') if c_result.error_message else None,
+            c_result.payer.decode('utf-8') if c_result.payer else None,
+        )
+        self.lib.x402_free_result(c_result)
+        return result
+    
+    def settle(self, payment: Dict[str, Any], requirements: Dict[str, Any]) -> SettleResult:
+        """Settle a payment on-chain (requires connect())"""
+        c_result = self.lib.x402_settle_payment(
+            getattr(self, 'context', None),
+            json.dumps(payment).encode('utf-8'),
+            json.dumps(requirements).encode('utf-8'),
+        )
+        result = SettleResult(
+            c_result.success,
+            c_result.transaction.decode('utf-8') if c_result.transaction else None,
+            c_result.payer.decode('utf-8') if c_result.payer else None,
+            c_result.error_message.decode('utf-8') if c_result.error_message else None,
+            json.loads(c_result.receipt_json) if c_result.receipt_json else None,
+        )
+        self.lib.x402_free_settle_result(c_result)
+        return result
 
 
 # Example usage
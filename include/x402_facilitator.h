@@ -0,0 +1,297 @@
+/* x402 facilitator C API (see src/ffi.rs) */
+
+#ifndef X402_FACILITATOR_H
+#define X402_FACILITATOR_H
+
+/* Generated by build.rs with cbindgen; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Facilitator instance for full verification and settlement
+ *
+ * Owns the configuration (RPC client, caches, dedup window, fee payer) and
+ * the Tokio runtime the blocking x402_* calls run on. Created by
+ * x402_context_new(), released by x402_context_free().
+ */
+typedef struct X402Context X402Context;
+
+/**
+ * C-compatible verification result structure
+ *
+ * This struct is guaranteed to have a stable memory layout (repr(C))
+ * so it can be safely passed across FFI boundaries.
+ */
+typedef struct CVerifyResult {
+  /**
+   * Whether the payment is valid
+   */
+  bool is_valid;
+  /**
+   * Error message (NULL if valid)
+   * Caller must free with x402_free_string()
+   */
+  char *error_message;
+  /**
+   * Payer address (NULL if invalid)
+   * Caller must free with x402_free_string()
+   */
+  char *payer;
+} CVerifyResult;
+
+/**
+ * C-compatible settlement result structure
+ *
+ * All strings are owned by the caller; free the whole result with
+ * x402_free_settle_result().
+ */
+typedef struct CSettleResult {
+  /**
+   * Whether the payment was settled
+   */
+  bool success;
+  /**
+   * Transaction signature (NULL if not submitted)
+   */
+  char *transaction;
+  /**
+   * Payer address (NULL if unknown)
+   */
+  char *payer;
+  /**
+   * Error reason (NULL on success)
+   */
+  char *error_message;
+  /**
+   * Signed receipt as JSON (NULL if none was issued)
+   */
+  char *receipt_json;
+} CSettleResult;
+
+/**
+ * Completion callback for x402_settle_payment_async()
+ *
+ * Receives the result and the caller's `user_data`. The callback owns the
+ * result and must free it with x402_free_settle_result(). Nullable, so a
+ * NULL function pointer from C is rejected instead of being called.
+ */
+typedef void (*X402SettleCallback)(struct CSettleResult result, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Initialize the FFI library
+ *
+ * Call this once before using any other functions.
+ * Safe to call multiple times.
+ *
+ * # Returns
+ * 0 on success, non-zero on error
+ */
+int32_t x402_init(void);
+
+/**
+ * Free a C string allocated by Rust
+ *
+ * Must be called for every string returned by x402_* functions.
+ *
+ * # Safety
+ * - Caller must ensure the pointer was allocated by Rust
+ * - Must not use the pointer after calling this function
+ * - Safe to call with NULL pointer (no-op)
+ */
+void x402_free_string(char *s);
+
+/**
+ * Free a CVerifyResult structure
+ *
+ * Frees all strings within the result.
+ *
+ * # Safety
+ * - Caller must ensure the result was returned by x402_verify_payment
+ * - Must not use the result after calling this function
+ */
+void x402_free_result(struct CVerifyResult result);
+
+/**
+ * Verify a payment from C-compatible JSON strings
+ *
+ * This is a simplified verification that checks basic structure.
+ * For full verification with RPC calls, use x402_verify_payment_full().
+ *
+ * # Parameters
+ * - `payment_json`: JSON string of PaymentPayload
+ * - `requirements_json`: JSON string of PaymentRequirements
+ *
+ * # Returns
+ * CVerifyResult with is_valid and either error_message or payer
+ *
+ * # Memory Management
+ * Caller must call x402_free_result() to free the returned result.
+ *
+ * # Safety
+ * - Caller must ensure strings are valid UTF-8 and NULL-terminated
+ * - Caller must not modify strings during function execution
+ * - Returned strings must be freed with x402_free_string()
+ *
+ * # Example (Python with ctypes)
+ * ```python
+ * lib = ctypes.CDLL("libx402_facilitator.so")
+ * result = lib.x402_verify_payment(payment.encode(), requirements.encode())
+ * if result.is_valid:
+ *     print(f"Payer: {result.payer.decode()}")
+ * lib.x402_free_result(result)
+ * ```
+ */
+struct CVerifyResult x402_verify_payment(const char *payment_json, const char *requirements_json);
+
+/**
+ * Create a facilitator context
+ *
+ * # Parameters
+ * - `rpc_url`: Solana RPC endpoint (http:// or https://)
+ * - `network`: "solana", "solana-devnet" or "solana-testnet"
+ * - `fee_payer_private_key`: Base58 fee payer keypair
+ *
+ * # Returns
+ * Context handle, or NULL on error (details are logged)
+ *
+ * # Memory Management
+ * Caller must call x402_context_free() to release the context.
+ *
+ * # Safety
+ * - Caller must ensure strings are valid UTF-8 and NULL-terminated
+ */
+struct X402Context *x402_context_new(const char *rpc_url,
+                                     const char *network,
+                                     const char *fee_payer_private_key);
+
+/**
+ * Free a context created by x402_context_new()
+ *
+ * Waits (up to 2 minutes) for settlements started with
+ * x402_settle_payment_async() to finish and run their callbacks.
+ *
+ * # Safety
+ * - Caller must ensure the pointer was returned by x402_context_new
+ * - Must not use the context after calling this function, or while
+ *   another thread is using it
+ * - Must not be called from a settlement callback
+ * - Safe to call with NULL pointer (no-op)
+ */
+void x402_context_free(struct X402Context *context);
+
+/**
+ * Fully verify a payment (blocking)
+ *
+ * Runs the same checks as `POST /verify`, including the RPC account and
+ * mint lookups and the duplicate transaction check.
+ *
+ * # Parameters
+ * - `context`: Handle from x402_context_new()
+ * - `payment_json`: JSON string of PaymentPayload
+ * - `requirements_json`: JSON string of PaymentRequirements
+ *
+ * # Returns
+ * CVerifyResult with is_valid and either error_message or payer
+ *
+ * # Memory Management
+ * Caller must call x402_free_result() to free the returned result.
+ *
+ * # Safety
+ * - `context` must be NULL or a live handle from x402_context_new
+ * - Caller must ensure strings are valid UTF-8 and NULL-terminated
+ */
+struct CVerifyResult x402_verify_payment_full(const struct X402Context *context,
+                                              const char *payment_json,
+                                              const char *requirements_json);
+
+/**
+ * Settle a payment on-chain (blocking)
+ *
+ * Runs the same flow as `POST /settle`: verification, fee payer signing,
+ * submission and confirmation.
+ *
+ * # Parameters
+ * - `context`: Handle from x402_context_new()
+ * - `payment_json`: JSON string of PaymentPayload
+ * - `requirements_json`: JSON string of PaymentRequirements
+ *
+ * # Returns
+ * CSettleResult with success and either the transaction or error_message
+ *
+ * # Memory Management
+ * Caller must call x402_free_settle_result() to free the returned result.
+ *
+ * # Safety
+ * - `context` must be NULL or a live handle from x402_context_new
+ * - Caller must ensure strings are valid UTF-8 and NULL-terminated
+ */
+struct CSettleResult x402_settle_payment(const struct X402Context *context,
+                                         const char *payment_json,
+                                         const char *requirements_json);
+
+/**
+ * Settle a payment on-chain without blocking
+ *
+ * Runs the same flow as x402_settle_payment() on the context's runtime and
+ * invokes `callback` exactly once with the result, from a runtime thread
+ * (argument errors are reported through the callback too).
+ *
+ * # Parameters
+ * - `context`: Handle from x402_context_new()
+ * - `payment_json`: JSON string of PaymentPayload
+ * - `requirements_json`: JSON string of PaymentRequirements
+ * - `callback`: Called on completion
+ * - `user_data`: Passed through to `callback` untouched
+ *
+ * # Returns
+ * 0 if the settlement was started, -1 if `context` or `callback` is NULL
+ * (the callback is then never invoked)
+ *
+ * # Safety
+ * - `context` must be NULL or a live handle from x402_context_new
+ * - Caller must ensure strings are valid UTF-8 and NULL-terminated; they
+ *   are copied before this function returns
+ * - `user_data` must stay valid until the callback runs
+ * - The callback must not call x402_context_free() on its own context
+ */
+int32_t x402_settle_payment_async(const struct X402Context *context,
+                                  const char *payment_json,
+                                  const char *requirements_json,
+                                  X402SettleCallback callback,
+                                  void *user_data);
+
+/**
+ * Free a CSettleResult structure
+ *
+ * Frees all strings within the result.
+ *
+ * # Safety
+ * - Caller must ensure the result was returned by x402_settle_payment or
+ *   passed to an X402SettleCallback
+ * - Must not use the result after calling this function
+ */
+void x402_free_settle_result(struct CSettleResult result);
+
+/**
+ * Get the library version
+ *
+ * Returns a static string (does not need to be freed).
+ *
+ * # Returns
+ * Version string in format "major.minor.patch"
+ */
+const char *x402_version(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* X402_FACILITATOR_H */
@@ -0,0 +1,260 @@
+use futures::StreamExt;
+use moka::sync::Cache;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::cache::AccountCache;
+
+/// How long lookups of an unwatched account are remembered
+const LOOKUP_WINDOW: Duration = Duration::from_secs(600);
+
+/// Account subscription settings
+///
+/// Configuration:
+/// * `SOLANA_WS_URL` - RPC PubSub endpoint (enables the watcher)
+/// * `ACCOUNT_WATCH_THRESHOLD` - Lookups within 10 minutes before an account
+///   is watched (default: 5)
+/// * `ACCOUNT_WATCH_MAX_ACCOUNTS` - Most accounts watched at once (default: 100)
+#[derive(Debug, Clone)]
+pub struct AccountWatchConfig {
+    pub ws_url: String,
+    pub threshold: u32,
+    pub max_accounts: usize,
+}
+
+impl AccountWatchConfig {
+    /// Returns `None` when `SOLANA_WS_URL` is not set
+    pub fn from_env() -> Option<Self> {
+        let ws_url = std::env::var("SOLANA_WS_URL").ok().filter(|url| !url.is_empty())?;
+
+        Some(Self {
+            ws_url,
+            threshold: std::env::var("ACCOUNT_WATCH_THRESHOLD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5),
+            max_accounts: std::env::var("ACCOUNT_WATCH_MAX_ACCOUNTS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(100),
+        })
+    }
+}
+
+/// Keeps frequently verified accounts fresh in the [`AccountCache`]
+///
+/// Accounts looked up `threshold` times get an `accountSubscribe`
+/// subscription; updates replace the cached account and closed accounts are
+/// invalidated, so the cache TTL isn't the only freshness guarantee.
+/// Updates older than the last applied slot are ignored.
+#[derive(Clone)]
+pub struct AccountWatcher {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    config: AccountWatchConfig,
+    cache: AccountCache,
+    runtime: tokio::runtime::Handle,
+    /// Recent lookups of accounts not watched yet
+    lookups: Cache<Pubkey, u32>,
+    watched: Mutex<HashSet<Pubkey>>,
+    /// Shared PubSub connection, re-established after it drops
+    client: tokio::sync::Mutex<Option<Arc<PubsubClient>>>,
+}
+
+impl std::fmt::Debug for AccountWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccountWatcher")
+            .field("ws_url", &self.inner.config.ws_url)
+            .field("watched", &self.watched_count())
+            .finish()
+    }
+}
+
+impl AccountWatcher {
+    /// Create a watcher updating `cache`; subscriptions run on `runtime`
+    pub fn new(config: AccountWatchConfig, cache: AccountCache, runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                lookups: Cache::builder()
+                    .max_capacity(10_000)
+                    .time_to_live(LOOKUP_WINDOW)
+                    .build(),
+                config,
+                cache,
+                runtime,
+                watched: Mutex::new(HashSet::new()),
+                client: tokio::sync::Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Load watcher settings from environment (`SOLANA_WS_URL`)
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn from_env(cache: &AccountCache) -> Option<Self> {
+        let config = AccountWatchConfig::from_env()?;
+        let runtime = match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => runtime,
+            Err(_) => {
+                tracing::warn!("⚠️  Account watcher needs a Tokio runtime; disabled");
+                return None;
+            }
+        };
+
+        tracing::info!(
+            "👀 Watching up to {} hot accounts via {}",
+            config.max_accounts,
+            config.ws_url
+        );
+        Some(Self::new(config, cache.clone(), runtime))
+    }
+
+    /// Number of accounts currently subscribed
+    pub fn watched_count(&self) -> usize {
+        self.inner.watched.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_watched(&self, pubkey: &Pubkey) -> bool {
+        self.inner.watched.lock().unwrap_or_else(|e| e.into_inner()).contains(pubkey)
+    }
+
+    /// Record a lookup of `pubkey`, subscribing once it is looked up often
+    ///
+    /// Cheap and non-blocking; safe to call from the Rayon batch verifier.
+    pub fn observe(&self, pubkey: &Pubkey) {
+        if self.is_watched(pubkey) {
+            return;
+        }
+
+        let lookups = self.inner.lookups.get(pubkey).unwrap_or(0) + 1;
+        if lookups < self.inner.config.threshold {
+            self.inner.lookups.insert(*pubkey, lookups);
+            return;
+        }
+
+        {
+            let mut watched = self.inner.watched.lock().unwrap_or_else(|e| e.into_inner());
+            if watched.len() >= self.inner.config.max_accounts || !watched.insert(*pubkey) {
+                return;
+            }
+        }
+        self.inner.lookups.invalidate(pubkey);
+
+        let watcher = self.clone();
+        let pubkey = *pubkey;
+        self.inner.runtime.spawn(async move {
+            if let Err(e) = watcher.watch(pubkey).await {
+                tracing::warn!("⚠️  Account subscription for {} ended: {:#}", pubkey, e);
+            }
+            watcher.unwatch(&pubkey);
+        });
+    }
+
+    fn unwatch(&self, pubkey: &Pubkey) {
+        self.inner.watched.lock().unwrap_or_else(|e| e.into_inner()).remove(pubkey);
+    }
+
+    async fn client(&self) -> anyhow::Result<Arc<PubsubClient>> {
+        let mut client = self.inner.client.lock().await;
+        if let Some(client) = client.as_ref() {
+            return Ok(client.clone());
+        }
+
+        let connected = Arc::new(PubsubClient::new(&self.inner.config.ws_url).await?);
+        *client = Some(connected.clone());
+        Ok(connected)
+    }
+
+    /// Forget a dropped connection so the next subscription reconnects
+    async fn disconnect(&self, dropped: &Arc<PubsubClient>) {
+        let mut client = self.inner.client.lock().await;
+        if client.as_ref().is_some_and(|client| Arc::ptr_eq(client, dropped)) {
+            *client = None;
+        }
+    }
+
+    async fn watch(&self, pubkey: Pubkey) -> anyhow::Result<()> {
+        let client = self.client().await?;
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..Default::default()
+        };
+
+        let (mut updates, unsubscribe) = match client.account_subscribe(&pubkey, Some(config)).await {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                self.disconnect(&client).await;
+                return Err(e.into());
+            }
+        };
+        tracing::debug!("👀 Subscribed to account {}", pubkey);
+
+        let mut last_slot = 0;
+        while let Some(update) = updates.next().await {
+            if update.context.slot < last_slot {
+                continue;
+            }
+            last_slot = update.context.slot;
+
+            match update.value.decode::<Account>() {
+                Some(account) if account.lamports > 0 => self.inner.cache.store(pubkey, account),
+                // Closed (e.g. a rotated token account) or undecodable
+                _ => self.inner.cache.invalidate(&pubkey).await,
+            }
+        }
+
+        // The stream only ends when the connection drops
+        unsubscribe().await;
+        self.disconnect(&client).await;
+        self.inner.cache.invalidate(&pubkey).await;
+        anyhow::bail!("PubSub connection closed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_accounts_watched_after_threshold() {
+        let config = AccountWatchConfig {
+            // Nothing listens here; subscriptions fail and are released
+            ws_url: "ws://127.0.0.1:1".to_string(),
+            threshold: 3,
+            max_accounts: 1,
+        };
+        let watcher = AccountWatcher::new(config, AccountCache::new(10, 30), tokio::runtime::Handle::current());
+
+        let hot = Pubkey::new_unique();
+        watcher.observe(&hot);
+        watcher.observe(&hot);
+        assert!(!watcher.is_watched(&hot));
+
+        watcher.observe(&hot);
+        assert!(watcher.is_watched(&hot));
+
+        // At capacity
+        let other = Pubkey::new_unique();
+        for _ in 0..3 {
+            watcher.observe(&other);
+        }
+        assert!(!watcher.is_watched(&other));
+
+        // The failed subscription frees its slot
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while watcher.watched_count() > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+}
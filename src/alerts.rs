@@ -0,0 +1,87 @@
+//! Operational webhooks: incidents rather than payments
+//!
+//! Sent to the webhook endpoints subscribed to them (e.g.
+//! `WEBHOOK_EVENTS=fee_payer.*,rpc.*`), and to the tenant's endpoint when a
+//! tenant's request caused them. Each is sent at most once per subject per
+//! `WEBHOOK_ALERT_COOLDOWN_SECONDS` (see [`crate::webhooks::AlertThrottle`]).
+//! `rpc.unhealthy` comes from the [`crate::rpc_health`] monitor.
+
+use serde_json::json;
+
+use crate::config::Config;
+use crate::tenants::Tenant;
+use crate::webhooks::{dispatch_alert, WebhookEvent};
+
+/// `fee_payer.low_balance` if `lamports` is below the configured minimum
+pub fn fee_payer_balance(config: &Config, lamports: u64) {
+    let Some(webhook) = &config.webhook else { return };
+    let min_balance_lamports = config.settings.load().min_fee_payer_balance_lamports;
+    if lamports >= min_balance_lamports {
+        return;
+    }
+
+    let fee_payer = config.fee_payer_signer.pubkey().to_string();
+    dispatch_alert(
+        webhook,
+        config.tenant.as_deref(),
+        WebhookEvent::FeePayerLowBalance,
+        &fee_payer,
+        json!({
+            "fee_payer": fee_payer,
+            "balance_lamports": lamports,
+            "min_balance_lamports": min_balance_lamports,
+            "network": config.network,
+        }),
+    );
+}
+
+/// Look up the fee payer's balance in the background and alert if it's low
+/// (after a settlement spent from it)
+pub fn check_fee_payer_balance(config: &Config) {
+    if config.webhook.is_none() {
+        return;
+    }
+    let config = config.clone();
+    tokio::spawn(async move {
+        let rpc = config.rpc_client.clone();
+        let fee_payer = config.fee_payer_signer.pubkey();
+        match tokio::task::spawn_blocking(move || rpc.get_account(&fee_payer)).await {
+            Ok(Ok(account)) => fee_payer_balance(&config, account.map_or(0, |account| account.lamports)),
+            Ok(Err(e)) => tracing::debug!("Fee payer balance lookup failed: {}", e),
+            Err(e) => tracing::error!("Fee payer balance lookup panicked: {}", e),
+        }
+    });
+}
+
+/// `dedup.replay_detected` for a transaction seen before
+pub fn replay_detected(config: &Config, network: &str, transaction_data: &str) {
+    let Some(webhook) = &config.webhook else { return };
+    let hash = config.transaction_dedup.hash_transaction(transaction_data);
+    dispatch_alert(
+        webhook,
+        config.tenant.as_deref(),
+        WebhookEvent::ReplayDetected,
+        &hash,
+        json!({ "transaction_hash": hash, "network": network }),
+    );
+}
+
+/// `rate_limit.exceeded` for a client over quota on `path`
+///
+/// `tenant` is the tenant the request came in for, which the config passed
+/// to middleware doesn't carry yet.
+pub fn rate_limit_exceeded(config: &Config, tenant: Option<&Tenant>, client: &str, path: &str, retry_after_seconds: u64) {
+    let Some(webhook) = &config.webhook else { return };
+    dispatch_alert(
+        webhook,
+        tenant,
+        WebhookEvent::RateLimitExceeded,
+        client,
+        json!({
+            "client": client,
+            "path": path,
+            "retry_after_seconds": retry_after_seconds,
+            "network": config.network,
+        }),
+    );
+}
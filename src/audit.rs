@@ -1,8 +1,16 @@
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{oneshot, Notify};
 use chrono::{DateTime, Utc};
 
+use crate::metrics::AuditMetrics;
+
+#[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+use crate::audit_store::{AuditStore, AUDIT_BATCH_SIZE};
+
 /// Audit event types for compliance and debugging
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -19,6 +27,8 @@ pub enum AuditEventType {
     SettlementSuccess,
     /// Settlement failed
     SettlementFailed,
+    /// One transaction submission attempt during settlement (incl. retries)
+    SettlementAttempt,
     /// Duplicate transaction detected (replay attack)
     DuplicateDetected,
     /// Payment expired
@@ -51,6 +61,9 @@ pub struct AuditEvent {
     /// Network (solana, solana-devnet, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network: Option<String>,
+    /// Tenant whose request caused the event (see [`crate::tenants`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
     /// Amount in lamports (if applicable)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub amount: Option<u64>,
@@ -75,6 +88,7 @@ impl AuditEvent {
             transaction_signature: None,
             payer: None,
             network: None,
+            tenant: None,
             amount: None,
             recipient: None,
             error: None,
@@ -100,6 +114,12 @@ impl AuditEvent {
         self
     }
 
+    /// Builder method to add tenant
+    pub fn with_tenant(mut self, tenant: String) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+
     /// Builder method to add amount
     pub fn with_amount(mut self, amount: u64) -> Self {
         self.amount = Some(amount);
@@ -125,43 +145,293 @@ impl AuditEvent {
     }
 }
 
+/// Filters for reading persisted audit events
+///
+/// Deserializable from query strings, e.g.
+/// `?event_type=settlement_success&from=2024-01-01T00:00:00Z&limit=50`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub event_type: Option<AuditEventType>,
+    pub payer: Option<String>,
+    pub network: Option<String>,
+    pub tenant: Option<String>,
+    /// Inclusive lower bound on the event timestamp
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on the event timestamp
+    pub to: Option<DateTime<Utc>>,
+    /// Page size (default 100, at most 1000)
+    pub limit: Option<u32>,
+    /// Number of matching events to skip (newest first)
+    pub offset: Option<u64>,
+}
+
+impl AuditQuery {
+    pub const DEFAULT_LIMIT: u32 = 100;
+    pub const MAX_LIMIT: u32 = 1000;
+
+    /// Page size, clamped to `1..=MAX_LIMIT`
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT).clamp(1, Self::MAX_LIMIT)
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset.unwrap_or(0)
+    }
+}
+
+/// Messages handled by the audit writer task
+enum AuditMessage {
+    Event(Box<AuditEvent>),
+    /// Acknowledged once every event queued before it has been written
+    Flush(oneshot::Sender<()>),
+}
+
+/// Default number of queued audit events before the oldest are dropped
+pub const DEFAULT_AUDIT_QUEUE_CAPACITY: usize = 10_000;
+
+/// Most events kept in memory while the audit database is unreachable
+#[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+const MAX_PENDING_EVENTS: usize = 10_000;
+
+/// Events received per writer wake-up when no database is configured
+#[cfg(not(any(feature = "audit-sqlite", feature = "audit-postgres")))]
+const AUDIT_BATCH_SIZE: usize = 64;
+
+#[cfg(not(any(feature = "audit-sqlite", feature = "audit-postgres")))]
+type AuditStore = std::convert::Infallible;
+
+/// Bounded queue between [`AuditLogger`] and the writer task
+///
+/// Logging never blocks: when the queue is full the oldest queued event is
+/// dropped (and counted in `x402_audit_events_dropped_total`). Flush markers
+/// are never dropped, so `flush` callers still get their answer.
+struct AuditQueue {
+    messages: Mutex<VecDeque<AuditMessage>>,
+    notify: Notify,
+    capacity: usize,
+    closed: AtomicBool,
+    metrics: AuditMetrics,
+}
+
+impl AuditQueue {
+    fn new(capacity: usize) -> Self {
+        let metrics = AuditMetrics::new();
+        metrics.queue_capacity.set(capacity as i64);
+
+        Self {
+            messages: Mutex::new(VecDeque::new()),
+            notify: Notify::new(),
+            capacity,
+            closed: AtomicBool::new(false),
+            metrics,
+        }
+    }
+
+    fn push(&self, message: AuditMessage) {
+        let mut messages = self.messages.lock().unwrap_or_else(|e| e.into_inner());
+
+        if messages.len() >= self.capacity {
+            if let Some(oldest) = messages.iter().position(|m| matches!(m, AuditMessage::Event(_))) {
+                messages.remove(oldest);
+                self.metrics.events_dropped.inc();
+            }
+        }
+
+        messages.push_back(message);
+        self.metrics.queue_depth.set(messages.len() as i64);
+        drop(messages);
+
+        self.notify.notify_one();
+    }
+
+    /// Wait for up to `max` messages; `None` once closed and drained
+    async fn pop_batch(&self, max: usize) -> Option<Vec<AuditMessage>> {
+        loop {
+            {
+                let mut messages = self.messages.lock().unwrap_or_else(|e| e.into_inner());
+                if !messages.is_empty() {
+                    let n = messages.len().min(max);
+                    let batch = messages.drain(..n).collect();
+                    self.metrics.queue_depth.set(messages.len() as i64);
+                    return Some(batch);
+                }
+                if self.closed.load(Ordering::Acquire) {
+                    return None;
+                }
+            }
+
+            self.notify.notified().await;
+        }
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.notify.notify_one();
+    }
+}
+
+/// Producer handle; closes the queue once the last logger clone is dropped
+struct AuditSender(Arc<AuditQueue>);
+
+impl Drop for AuditSender {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+/// State of the audit writer task
+///
+/// Every event goes to the `audit` log target immediately; with a database
+/// configured it is also buffered and inserted in batches.
+struct AuditWriter {
+    store: Option<AuditStore>,
+    pending: Vec<AuditEvent>,
+}
+
+impl AuditWriter {
+    fn new(store: Option<AuditStore>) -> Self {
+        Self {
+            store,
+            pending: Vec::new(),
+        }
+    }
+
+    fn write(&mut self, event: AuditEvent) {
+        // Log as structured JSON
+        let json = serde_json::to_string(&event).unwrap_or_else(|e| {
+            format!(r#"{{"error":"Failed to serialize audit event: {}"}}"#, e)
+        });
+
+        // Write to stdout (can be captured by logging infrastructure)
+        tracing::info!(target: "audit", "{}", json);
+
+        if self.store.is_some() {
+            self.pending.push(event);
+        }
+    }
+
+    /// Insert buffered events into the database, if configured
+    ///
+    /// Failed batches stay buffered and are retried on the next call; they
+    /// are already in the audit log either way.
+    #[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+    async fn persist(&mut self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if self.pending.is_empty() {
+            return;
+        }
+
+        if let Err(e) = store.insert_batch(&self.pending).await {
+            tracing::error!("Failed to persist {} audit events: {:#}", self.pending.len(), e);
+
+            // Don't buffer without bound while the database is down
+            if self.pending.len() > MAX_PENDING_EVENTS {
+                let dropped = self.pending.len() - MAX_PENDING_EVENTS;
+                self.pending.drain(..dropped);
+                AuditMetrics::new().events_dropped.inc_by(dropped as u64);
+                tracing::error!("Dropped {} oldest unpersisted audit events", dropped);
+            }
+            return;
+        }
+
+        self.pending.clear();
+    }
+
+    #[cfg(not(any(feature = "audit-sqlite", feature = "audit-postgres")))]
+    async fn persist(&mut self) {}
+}
+
 /// Audit logger - async writer to file/database
 #[derive(Clone)]
 pub struct AuditLogger {
-    sender: Arc<mpsc::UnboundedSender<AuditEvent>>,
+    sender: Arc<AuditSender>,
+    /// Tenant stamped on events without one
+    tenant: Option<Arc<str>>,
+    #[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+    store: Option<AuditStore>,
 }
 
 impl AuditLogger {
     /// Create a new audit logger
     pub fn new() -> Self {
-        let (tx, mut rx) = mpsc::unbounded_channel::<AuditEvent>();
+        Self::with_capacity(DEFAULT_AUDIT_QUEUE_CAPACITY)
+    }
+
+    /// Create an audit logger queueing at most `capacity` unwritten events
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::spawn(None, capacity)
+    }
+
+    /// Create an audit logger that also persists events to `store`
+    #[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+    pub fn with_store(store: AuditStore, capacity: usize) -> Self {
+        Self::spawn(Some(store), capacity)
+    }
+
+    fn spawn(store: Option<AuditStore>, capacity: usize) -> Self {
+        let queue = Arc::new(AuditQueue::new(capacity.max(1)));
 
         // Spawn background task to write audit logs
+        let writer_queue = queue.clone();
+        #[allow(clippy::clone_on_copy)] // `Infallible` without a database feature
+        let writer_store = store.clone();
         tokio::spawn(async move {
-            while let Some(event) = rx.recv().await {
-                // Log as structured JSON
-                let json = serde_json::to_string(&event).unwrap_or_else(|e| {
-                    format!(r#"{{"error":"Failed to serialize audit event: {}"}}"#, e)
-                });
-                
-                // Write to stdout (can be captured by logging infrastructure)
-                // In production, could write to file, database, or external service
-                tracing::info!(target: "audit", "{}", json);
+            let mut writer = AuditWriter::new(writer_store);
+
+            while let Some(messages) = writer_queue.pop_batch(AUDIT_BATCH_SIZE).await {
+                for message in messages {
+                    match message {
+                        AuditMessage::Event(event) => writer.write(*event),
+                        AuditMessage::Flush(ack) => {
+                            writer.persist().await;
+                            let _ = ack.send(());
+                        }
+                    }
+                }
+
+                writer.persist().await;
             }
         });
 
         tracing::info!("📋 Audit logging initialized");
 
         Self {
-            sender: Arc::new(tx),
+            sender: Arc::new(AuditSender(queue)),
+            tenant: None,
+            #[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+            store,
         }
     }
 
+    /// Database the events are persisted to, if any
+    #[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+    pub fn store(&self) -> Option<&AuditStore> {
+        self.store.as_ref()
+    }
+
+    /// Logger writing to the same queue that attributes events to `tenant`
+    pub fn for_tenant(&self, tenant: Option<&str>) -> Self {
+        Self { tenant: tenant.map(Arc::from), ..self.clone() }
+    }
+
     /// Log an audit event (non-blocking)
-    pub fn log(&self, event: AuditEvent) {
-        if let Err(e) = self.sender.send(event) {
-            tracing::error!("Failed to send audit event: {}", e);
+    pub fn log(&self, mut event: AuditEvent) {
+        if event.tenant.is_none() {
+            event.tenant = self.tenant.as_deref().map(str::to_string);
         }
+        self.sender.0.push(AuditMessage::Event(Box::new(event)));
+    }
+
+    /// Wait until all previously logged events have been written, up to `timeout`
+    ///
+    /// Returns `false` if the writer did not catch up in time.
+    pub async fn flush(&self, timeout: Duration) -> bool {
+        let (ack_tx, ack_rx) = oneshot::channel();
+        self.sender.0.push(AuditMessage::Flush(ack_tx));
+
+        matches!(tokio::time::timeout(timeout, ack_rx).await, Ok(Ok(())))
     }
 
     /// Log a verification request
@@ -226,6 +496,36 @@ impl AuditLogger {
         self.log(event);
     }
 
+    /// Log one settlement submission attempt and its RPC error, if any
+    pub fn log_settlement_attempt(
+        &self,
+        network: &str,
+        payer: Option<&str>,
+        request_id: Option<&str>,
+        attempt: &crate::solana::submitter::SubmissionAttempt,
+    ) {
+        let mut event = AuditEvent::new(AuditEventType::SettlementAttempt)
+            .with_network(network.to_string())
+            .with_metadata(serde_json::json!({
+                "request_id": request_id,
+                "attempt": attempt.attempt,
+                "max_attempts": attempt.max_attempts,
+                "backoff_ms": attempt.backoff.map(|backoff| backoff.as_millis() as u64),
+            }));
+
+        if let Some(p) = payer {
+            event = event.with_payer(p.to_string());
+        }
+        if let Some(signature) = &attempt.signature {
+            event = event.with_transaction(signature.to_string());
+        }
+        if let Some(error) = &attempt.error {
+            event = event.with_error(error.clone());
+        }
+
+        self.log(event);
+    }
+
     /// Log a duplicate transaction detection (replay attack)
     pub fn log_duplicate_detected(&self, network: &str, transaction: &str) {
         let event = AuditEvent::new(AuditEventType::DuplicateDetected)
@@ -246,6 +546,18 @@ impl AuditLogger {
         self.log(event);
     }
 
+    /// Log a request rejected by the rate limiter
+    pub fn log_rate_limit_exceeded(&self, network: &str, client: &str, path: &str) {
+        let event = AuditEvent::new(AuditEventType::RateLimitExceeded)
+            .with_network(network.to_string())
+            .with_metadata(serde_json::json!({
+                "client": client,
+                "path": path,
+            }));
+        
+        self.log(event);
+    }
+
     /// Log server startup
     pub fn log_server_started(&self, port: u16, network: &str) {
         let event = AuditEvent::new(AuditEventType::ServerStarted)
@@ -257,6 +569,17 @@ impl AuditLogger {
         self.log(event);
     }
 
+    /// Log a runtime configuration change (e.g. key rotation)
+    pub fn log_config_changed(&self, setting: &str, details: serde_json::Value) {
+        let event = AuditEvent::new(AuditEventType::ConfigChanged)
+            .with_metadata(serde_json::json!({
+                "setting": setting,
+                "details": details,
+            }));
+        
+        self.log(event);
+    }
+
     /// Log server shutdown
     pub fn log_server_stopped(&self) {
         let event = AuditEvent::new(AuditEventType::ServerStopped);
@@ -309,6 +632,35 @@ mod tests {
         // Give the background task time to process
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     }
-}
 
+    #[tokio::test]
+    async fn test_audit_queue_drops_oldest_event_when_full() {
+        let queue = AuditQueue::new(2);
+        let events: Vec<_> = (0..3)
+            .map(|_| AuditEvent::new(AuditEventType::SettlementSuccess))
+            .collect();
+
+        let (ack_tx, _ack_rx) = oneshot::channel();
+        queue.push(AuditMessage::Flush(ack_tx));
+        for event in &events {
+            queue.push(AuditMessage::Event(Box::new(event.clone())));
+        }
+
+        // The flush marker survives; the oldest event makes room for the newest
+        let batch = queue.pop_batch(10).await.unwrap();
+        assert_eq!(batch.len(), 2);
+        assert!(matches!(batch[0], AuditMessage::Flush(_)));
+        assert!(matches!(&batch[1], AuditMessage::Event(event) if event.id == events[2].id));
 
+        queue.close();
+        assert!(queue.pop_batch(10).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_audit_logger_flush() {
+        let logger = AuditLogger::new();
+        logger.log_server_stopped();
+
+        assert!(logger.flush(Duration::from_secs(1)).await);
+    }
+}
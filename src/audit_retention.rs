@@ -0,0 +1,439 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::{write::GzEncoder, Compression};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::audit::AuditEvent;
+use crate::audit_store::AuditStore;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Expired events archived and deleted per round trip
+const PRUNE_BATCH_SIZE: u32 = 1000;
+
+/// Timeout for a single archive upload
+const ARCHIVE_UPLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Retention policy for persisted audit events
+///
+/// Events older than `max_age` are deleted by a background task. With an
+/// archive sink configured they are written there first, and only deleted
+/// once the archive write succeeded.
+#[derive(Debug)]
+pub struct RetentionConfig {
+    pub max_age: chrono::Duration,
+    /// How often the pruning task runs
+    pub interval: Duration,
+    pub archive: Option<ArchiveSink>,
+}
+
+impl RetentionConfig {
+    /// Load retention settings from environment
+    ///
+    /// Returns `Ok(None)` when `AUDIT_RETENTION_DAYS` is not set (events are
+    /// kept forever).
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(days) = std::env::var("AUDIT_RETENTION_DAYS").ok().filter(|days| !days.is_empty()) else {
+            return Ok(None);
+        };
+        let days: i64 = days
+            .parse()
+            .ok()
+            .filter(|days| *days > 0)
+            .with_context(|| format!("Invalid AUDIT_RETENTION_DAYS '{}'", days))?;
+
+        Ok(Some(Self {
+            max_age: chrono::Duration::days(days),
+            interval: Duration::from_secs(
+                std::env::var("AUDIT_PRUNE_INTERVAL_SECONDS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(3600),
+            ),
+            archive: ArchiveSink::from_env()?,
+        }))
+    }
+}
+
+/// Destination for expired audit events, as gzipped NDJSON files
+#[derive(Debug)]
+pub enum ArchiveSink {
+    /// Local (or mounted) directory
+    Directory(PathBuf),
+    /// S3-compatible bucket
+    S3(S3Archive),
+}
+
+impl ArchiveSink {
+    /// `AUDIT_ARCHIVE_DIR` or `AUDIT_ARCHIVE_S3_BUCKET`; at most one may be set
+    pub fn from_env() -> Result<Option<Self>> {
+        let dir = std::env::var("AUDIT_ARCHIVE_DIR").ok().filter(|dir| !dir.is_empty());
+        let bucket = std::env::var("AUDIT_ARCHIVE_S3_BUCKET").ok().filter(|bucket| !bucket.is_empty());
+
+        match (dir, bucket) {
+            (Some(_), Some(_)) => bail!("Set only one of AUDIT_ARCHIVE_DIR and AUDIT_ARCHIVE_S3_BUCKET"),
+            (Some(dir), None) => Ok(Some(Self::Directory(PathBuf::from(dir)))),
+            (None, Some(bucket)) => Ok(Some(Self::S3(S3Archive::from_env(bucket)?))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Write one archive file named `name`
+    pub async fn write(&self, name: &str, contents: Vec<u8>) -> Result<()> {
+        match self {
+            Self::Directory(dir) => {
+                tokio::fs::create_dir_all(dir)
+                    .await
+                    .with_context(|| format!("Failed to create archive directory {}", dir.display()))?;
+
+                // Write then rename, so readers never see a partial archive
+                let path = dir.join(name);
+                let partial = dir.join(format!(".{}.partial", name));
+                tokio::fs::write(&partial, contents)
+                    .await
+                    .with_context(|| format!("Failed to write {}", partial.display()))?;
+                tokio::fs::rename(&partial, &path)
+                    .await
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                Ok(())
+            }
+            Self::S3(s3) => s3.put(name, contents).await,
+        }
+    }
+}
+
+/// AWS credentials used for SigV4 request signing
+#[derive(Clone)]
+struct AwsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+/// Uploads archives to an S3-compatible bucket (AWS, MinIO, R2, ...)
+///
+/// Configuration:
+/// * `AUDIT_ARCHIVE_S3_BUCKET` - bucket name
+/// * `AUDIT_ARCHIVE_S3_PREFIX` - key prefix (default: `audit/`)
+/// * `AUDIT_ARCHIVE_S3_ENDPOINT` - endpoint override for non-AWS storage
+/// * `AWS_REGION` / `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN`
+///
+/// Uses path-style URLs (`{endpoint}/{bucket}/{key}`), which all
+/// S3-compatible stores accept.
+pub struct S3Archive {
+    client: Client,
+    endpoint: String,
+    bucket: String,
+    prefix: String,
+    region: String,
+    credentials: AwsCredentials,
+}
+
+impl std::fmt::Debug for S3Archive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3Archive")
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl S3Archive {
+    fn from_env(bucket: String) -> Result<Self> {
+        let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        let endpoint = std::env::var("AUDIT_ARCHIVE_S3_ENDPOINT")
+            .unwrap_or_else(|_| format!("https://s3.{}.amazonaws.com", region));
+
+        let credentials = AwsCredentials {
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID")
+                .map_err(|_| anyhow!("AWS_ACCESS_KEY_ID must be set for S3 audit archival"))?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY")
+                .map_err(|_| anyhow!("AWS_SECRET_ACCESS_KEY must be set for S3 audit archival"))?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        };
+
+        Ok(Self {
+            client: Client::builder()
+                .timeout(ARCHIVE_UPLOAD_TIMEOUT)
+                .build()
+                .context("Failed to build S3 HTTP client")?,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket,
+            prefix: std::env::var("AUDIT_ARCHIVE_S3_PREFIX").unwrap_or_else(|_| "audit/".to_string()),
+            region,
+            credentials,
+        })
+    }
+
+    async fn put(&self, name: &str, contents: Vec<u8>) -> Result<()> {
+        let path = format!("/{}/{}{}", self.bucket, self.prefix, name);
+        let url = reqwest::Url::parse(&format!("{}{}", self.endpoint, path))
+            .context("Invalid AUDIT_ARCHIVE_S3_ENDPOINT")?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => bail!("AUDIT_ARCHIVE_S3_ENDPOINT has no host"),
+        };
+
+        let mut request = self
+            .client
+            .put(url.clone())
+            .header("content-type", "application/gzip");
+        for (name, value) in self.sign_request(&host, url.path(), &contents, Utc::now()) {
+            request = request.header(name, value);
+        }
+
+        let response = request
+            .body(contents)
+            .send()
+            .await
+            .context("S3 archive upload failed")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            bail!("S3 archive upload returned {}: {}", status, body);
+        }
+
+        Ok(())
+    }
+
+    /// Build SigV4 headers for a PUT of `body` to `path`
+    ///
+    /// `path` must already be URI-encoded (as produced by [`reqwest::Url`]).
+    fn sign_request(&self, host: &str, path: &str, body: &[u8], now: DateTime<Utc>) -> Vec<(String, String)> {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(body));
+
+        // Canonical headers must be lowercase and sorted by name
+        let mut headers = vec![
+            ("host".to_string(), host.to_string()),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(k, v)| format!("{}:{}\n", k, v.trim()))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "PUT\n{}\n\n{}\n{}\n{}",
+            path, canonical_headers, signed_headers, payload_hash
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let secret = format!("AWS4{}", self.credentials.secret_access_key);
+        let k_date = hmac_sha256(secret.as_bytes(), date.as_bytes());
+        let k_region = hmac_sha256(&k_date, self.region.as_bytes());
+        let k_service = hmac_sha256(&k_region, b"s3");
+        let k_signing = hmac_sha256(&k_service, b"aws4_request");
+        let signature = hex::encode(hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.credentials.access_key_id, scope, signed_headers, signature
+        );
+
+        let mut result: Vec<(String, String)> = headers
+            .into_iter()
+            .filter(|(k, _)| k != "host")
+            .collect();
+        result.push(("authorization".to_string(), authorization));
+        result
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Gzipped NDJSON, one event per line
+fn encode_archive(events: &[AuditEvent]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    for event in events {
+        serde_json::to_writer(&mut encoder, event)?;
+        encoder.write_all(b"\n")?;
+    }
+
+    Ok(encoder.finish()?)
+}
+
+/// Archive file name covering the events' time range
+///
+/// The random suffix keeps names unique when several replicas prune.
+fn archive_name(events: &[AuditEvent]) -> String {
+    let format = |timestamp: &DateTime<Utc>| timestamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let first = events.first().map(|event| format(&event.timestamp)).unwrap_or_default();
+    let last = events.last().map(|event| format(&event.timestamp)).unwrap_or_default();
+
+    format!("audit-{}-{}-{}.ndjson.gz", first, last, &uuid::Uuid::new_v4().simple().to_string()[..8])
+}
+
+/// Archive (if configured) and delete events older than the retention window
+///
+/// Returns the number of events deleted. Stops at the first archive failure
+/// so nothing is deleted without being archived.
+pub async fn prune_once(store: &AuditStore, config: &RetentionConfig) -> Result<u64> {
+    let cutoff = Utc::now() - config.max_age;
+    let mut deleted = 0;
+
+    loop {
+        let events = store.expired(cutoff, PRUNE_BATCH_SIZE).await?;
+        if events.is_empty() {
+            break;
+        }
+
+        if let Some(archive) = &config.archive {
+            let name = archive_name(&events);
+            archive
+                .write(&name, encode_archive(&events)?)
+                .await
+                .with_context(|| format!("Failed to archive {} audit events", events.len()))?;
+        }
+
+        let ids: Vec<_> = events.iter().map(|event| event.id.clone()).collect();
+        deleted += store.delete(&ids).await?;
+
+        if events.len() < PRUNE_BATCH_SIZE as usize {
+            break;
+        }
+    }
+
+    Ok(deleted)
+}
+
+/// Spawn a background task enforcing the retention policy
+pub fn spawn_pruner(store: AuditStore, config: RetentionConfig) {
+    tracing::info!(
+        "🧹 Pruning audit events older than {} days every {}s{}",
+        config.max_age.num_days(),
+        config.interval.as_secs(),
+        if config.archive.is_some() { " (archiving first)" } else { "" }
+    );
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(config.interval);
+
+        loop {
+            ticker.tick().await;
+
+            match prune_once(&store, &config).await {
+                Ok(0) => {}
+                Ok(deleted) => tracing::info!("🧹 Pruned {} expired audit events", deleted),
+                Err(e) => tracing::error!("Audit retention pass failed: {:#}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEventType;
+    use chrono::TimeZone;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn test_s3_archive() -> S3Archive {
+        S3Archive {
+            client: Client::new(),
+            endpoint: "https://s3.us-east-1.amazonaws.com".to_string(),
+            bucket: "audit-archive".to_string(),
+            prefix: "audit/".to_string(),
+            region: "us-east-1".to_string(),
+            credentials: AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+                session_token: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_encode_archive_is_gzipped_ndjson() {
+        let events = vec![
+            AuditEvent::new(AuditEventType::SettlementSuccess),
+            AuditEvent::new(AuditEventType::SettlementFailed),
+        ];
+
+        let mut ndjson = String::new();
+        GzDecoder::new(encode_archive(&events).unwrap().as_slice())
+            .read_to_string(&mut ndjson)
+            .unwrap();
+
+        let lines: Vec<_> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let decoded: AuditEvent = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(decoded.id, events[1].id);
+    }
+
+    #[test]
+    fn test_s3_sigv4_headers() {
+        let archive = test_s3_archive();
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        let headers = archive.sign_request("s3.us-east-1.amazonaws.com", "/audit-archive/audit/a.ndjson.gz", b"data", now);
+        let auth = &headers.iter().find(|(k, _)| k == "authorization").unwrap().1;
+
+        assert!(auth.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240102/us-east-1/s3/aws4_request"));
+        assert!(auth.contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date"));
+        assert!(headers
+            .iter()
+            .any(|(k, v)| k == "x-amz-content-sha256" && *v == hex::encode(Sha256::digest(b"data"))));
+    }
+
+    #[cfg(feature = "audit-sqlite")]
+    #[tokio::test]
+    async fn test_prune_archives_then_deletes_expired_events() {
+        let path = std::env::temp_dir().join(format!("audit-{}.db", uuid::Uuid::new_v4()));
+        let store = AuditStore::connect_lazy(&format!("sqlite://{}?mode=rwc", path.display())).unwrap();
+        let archive_dir = std::env::temp_dir().join(format!("audit-archive-{}", uuid::Uuid::new_v4()));
+
+        let mut old = AuditEvent::new(AuditEventType::SettlementSuccess);
+        old.timestamp = Utc::now() - chrono::Duration::days(40);
+        let recent = AuditEvent::new(AuditEventType::SettlementSuccess);
+        store.insert_batch(&[old.clone(), recent.clone()]).await.unwrap();
+
+        let config = RetentionConfig {
+            max_age: chrono::Duration::days(30),
+            interval: Duration::from_secs(3600),
+            archive: Some(ArchiveSink::Directory(archive_dir.clone())),
+        };
+        assert_eq!(prune_once(&store, &config).await.unwrap(), 1);
+
+        let remaining = store.query(&Default::default()).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, recent.id);
+
+        let archives: Vec<_> = std::fs::read_dir(&archive_dir).unwrap().collect();
+        assert_eq!(archives.len(), 1);
+
+        let _ = std::fs::remove_dir_all(archive_dir);
+        let _ = std::fs::remove_file(path);
+    }
+}
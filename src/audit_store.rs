@@ -0,0 +1,507 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{ColumnIndex, Decode, Row, Type};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use crate::audit::{AuditEvent, AuditEventType, AuditQuery};
+
+/// Maximum number of events written per INSERT statement
+///
+/// Keeps the number of bound parameters (11 per event) well below SQLite's
+/// limit.
+pub const AUDIT_BATCH_SIZE: usize = 64;
+
+/// Columns of `audit_events`, in bind order
+const COLUMNS: [&str; 11] = [
+    "id",
+    "event_type",
+    "timestamp",
+    "transaction_signature",
+    "payer",
+    "network",
+    "amount",
+    "recipient",
+    "error",
+    "metadata",
+    "tenant",
+];
+
+/// Schema shared by SQLite and Postgres
+///
+/// Timestamps are stored as RFC 3339 UTC strings (sortable as text) and
+/// metadata as serialized JSON, so both backends use the same column types.
+const SCHEMA: [&str; 3] = [
+    r#"CREATE TABLE IF NOT EXISTS audit_events (
+        id TEXT PRIMARY KEY,
+        event_type TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        transaction_signature TEXT,
+        payer TEXT,
+        network TEXT,
+        amount BIGINT,
+        recipient TEXT,
+        error TEXT,
+        metadata TEXT,
+        tenant TEXT
+    )"#,
+    "CREATE INDEX IF NOT EXISTS audit_events_timestamp_idx ON audit_events (timestamp)",
+    "CREATE INDEX IF NOT EXISTS audit_events_event_type_idx ON audit_events (event_type)",
+];
+
+/// URL schemes of the backends compiled in, for error messages
+const SUPPORTED_BACKENDS: &str = if cfg!(all(feature = "audit-sqlite", feature = "audit-postgres")) {
+    "sqlite:// or postgres:// databases"
+} else if cfg!(feature = "audit-sqlite") {
+    "sqlite:// databases"
+} else {
+    "postgres:// databases"
+};
+
+/// Adds the `tenant` column to tables created before it existed
+const ADD_TENANT_COLUMN: &str = "ALTER TABLE audit_events ADD COLUMN tenant TEXT";
+
+/// Connection pool of the backend selected by the URL scheme
+#[derive(Clone)]
+enum StorePool {
+    #[cfg(feature = "audit-sqlite")]
+    Sqlite(sqlx::SqlitePool),
+    #[cfg(feature = "audit-postgres")]
+    Postgres(sqlx::PgPool),
+}
+
+/// Evaluate `$body` with `$pool` bound to the store's concrete pool
+///
+/// The body is compiled once per enabled backend, so queries are typed
+/// against that backend's driver.
+macro_rules! with_pool {
+    ($store:expr, |$pool:ident| $body:expr) => {
+        match &$store.pool {
+            #[cfg(feature = "audit-sqlite")]
+            StorePool::Sqlite($pool) => $body,
+            #[cfg(feature = "audit-postgres")]
+            StorePool::Postgres($pool) => $body,
+        }
+    };
+}
+
+/// Durable storage for audit events (SQLite or Postgres)
+///
+/// Used by the [`crate::audit::AuditLogger`] writer task in addition to the
+/// `audit` log target, so events survive pod rotation.
+#[derive(Clone)]
+pub struct AuditStore {
+    pool: StorePool,
+    /// Set once the schema exists; retried on the next insert until then
+    schema: Arc<OnceCell<()>>,
+}
+
+impl std::fmt::Debug for AuditStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditStore").finish_non_exhaustive()
+    }
+}
+
+impl AuditStore {
+    /// Create a store for `url` (`sqlite://...` or `postgres://...`)
+    ///
+    /// Connections are opened lazily, so an unreachable database does not
+    /// prevent startup; [`AuditStore::migrate`] reports it instead. Each
+    /// backend needs its feature (`audit-sqlite`, `audit-postgres`).
+    pub fn connect_lazy(url: &str) -> Result<Self> {
+        let pool = match url.split_once(':').map(|(scheme, _)| scheme) {
+            #[cfg(feature = "audit-sqlite")]
+            Some("sqlite") => StorePool::Sqlite(
+                sqlx::sqlite::SqlitePoolOptions::new()
+                    .max_connections(4)
+                    .connect_lazy(url)
+                    .context("Invalid AUDIT_DATABASE_URL")?,
+            ),
+            #[cfg(feature = "audit-postgres")]
+            Some("postgres" | "postgresql") => StorePool::Postgres(
+                sqlx::postgres::PgPoolOptions::new()
+                    .max_connections(4)
+                    .connect_lazy(url)
+                    .context("Invalid AUDIT_DATABASE_URL")?,
+            ),
+            _ => anyhow::bail!(
+                "Unsupported AUDIT_DATABASE_URL; this build stores audit events in {}",
+                SUPPORTED_BACKENDS
+            ),
+        };
+
+        Ok(Self {
+            pool,
+            schema: Arc::new(OnceCell::new()),
+        })
+    }
+
+    /// Load audit storage settings from environment
+    ///
+    /// Returns `Ok(None)` when `AUDIT_DATABASE_URL` is not set.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(url) = std::env::var("AUDIT_DATABASE_URL").ok().filter(|url| !url.is_empty()) else {
+            return Ok(None);
+        };
+
+        let store = Self::connect_lazy(&url)?;
+        tracing::info!("🗄️  Persisting audit events to database");
+
+        Ok(Some(store))
+    }
+
+    /// Create the `audit_events` table and indexes if missing
+    pub async fn migrate(&self) -> Result<()> {
+        with_pool!(self, |pool| {
+            for statement in SCHEMA {
+                sqlx::query(statement)
+                    .execute(pool)
+                    .await
+                    .context("Failed to create audit_events schema")?;
+            }
+
+            // SQLite has no ADD COLUMN IF NOT EXISTS, so probe for it instead
+            if sqlx::query("SELECT tenant FROM audit_events LIMIT 0").execute(pool).await.is_err() {
+                sqlx::query(ADD_TENANT_COLUMN)
+                    .execute(pool)
+                    .await
+                    .context("Failed to add the tenant column to audit_events")?;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Insert events, [`AUDIT_BATCH_SIZE`] rows per statement
+    ///
+    /// Creates the schema first if needed. Events already stored (same `id`)
+    /// are skipped, so a failed batch can be retried safely.
+    pub async fn insert_batch(&self, events: &[AuditEvent]) -> Result<()> {
+        self.schema.get_or_try_init(|| self.migrate()).await?;
+
+        for chunk in events.chunks(AUDIT_BATCH_SIZE) {
+            let sql = insert_sql(chunk.len());
+            with_pool!(self, |pool| {
+                let mut query = sqlx::query(&sql);
+
+                for event in chunk {
+                    query = query
+                        .bind(event.id.clone())
+                        .bind(event_type_name(&event.event_type)?)
+                        .bind(format_timestamp(&event.timestamp))
+                        .bind(event.transaction_signature.clone())
+                        .bind(event.payer.clone())
+                        .bind(event.network.clone())
+                        // Amounts above i64::MAX can't be represented in BIGINT
+                        .bind(event.amount.and_then(|amount| i64::try_from(amount).ok()))
+                        .bind(event.recipient.clone())
+                        .bind(event.error.clone())
+                        .bind(event.metadata.as_ref().map(|metadata| metadata.to_string()))
+                        .bind(event.tenant.clone());
+                }
+
+                query.execute(pool).await.context("Failed to insert audit events")?;
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Read events matching `query`, newest first
+    pub async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEvent>> {
+        self.schema.get_or_try_init(|| self.migrate()).await?;
+
+        let mut conditions = Vec::new();
+        let mut binds = Vec::new();
+
+        if let Some(event_type) = &query.event_type {
+            binds.push(event_type_name(event_type)?);
+            conditions.push(format!("event_type = ${}", binds.len()));
+        }
+        if let Some(payer) = &query.payer {
+            binds.push(payer.clone());
+            conditions.push(format!("payer = ${}", binds.len()));
+        }
+        if let Some(network) = &query.network {
+            binds.push(network.clone());
+            conditions.push(format!("network = ${}", binds.len()));
+        }
+        if let Some(tenant) = &query.tenant {
+            binds.push(tenant.clone());
+            conditions.push(format!("tenant = ${}", binds.len()));
+        }
+        if let Some(from) = &query.from {
+            binds.push(format_timestamp(from));
+            conditions.push(format!("timestamp >= ${}", binds.len()));
+        }
+        if let Some(to) = &query.to {
+            binds.push(format_timestamp(to));
+            conditions.push(format!("timestamp < ${}", binds.len()));
+        }
+
+        let mut sql = format!("SELECT {} FROM audit_events", COLUMNS.join(", "));
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(&format!(
+            " ORDER BY timestamp DESC, id DESC LIMIT {} OFFSET {}",
+            query.limit(),
+            query.offset()
+        ));
+
+        with_pool!(self, |pool| {
+            let mut statement = sqlx::query(&sql);
+            for value in binds {
+                statement = statement.bind(value);
+            }
+
+            let rows = statement
+                .fetch_all(pool)
+                .await
+                .context("Failed to query audit events")?;
+
+            rows.iter().map(event_from_row).collect()
+        })
+    }
+
+    /// Oldest events with a timestamp before `cutoff`, up to `limit`
+    pub async fn expired(&self, cutoff: DateTime<Utc>, limit: u32) -> Result<Vec<AuditEvent>> {
+        self.schema.get_or_try_init(|| self.migrate()).await?;
+
+        let sql = format!(
+            "SELECT {} FROM audit_events WHERE timestamp < $1 ORDER BY timestamp, id LIMIT {}",
+            COLUMNS.join(", "),
+            limit
+        );
+
+        with_pool!(self, |pool| {
+            let rows = sqlx::query(&sql)
+                .bind(format_timestamp(&cutoff))
+                .fetch_all(pool)
+                .await
+                .context("Failed to query expired audit events")?;
+
+            rows.iter().map(event_from_row).collect()
+        })
+    }
+
+    /// Delete events by ID, returning the number of rows removed
+    pub async fn delete(&self, ids: &[String]) -> Result<u64> {
+        let mut deleted = 0;
+
+        for chunk in ids.chunks(AUDIT_BATCH_SIZE * COLUMNS.len()) {
+            let placeholders = (1..=chunk.len())
+                .map(|i| format!("${}", i))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let sql = format!("DELETE FROM audit_events WHERE id IN ({})", placeholders);
+
+            deleted += with_pool!(self, |pool| {
+                let mut statement = sqlx::query(&sql);
+                for id in chunk {
+                    statement = statement.bind(id.clone());
+                }
+
+                statement
+                    .execute(pool)
+                    .await
+                    .context("Failed to delete audit events")?
+                    .rows_affected()
+            });
+        }
+
+        Ok(deleted)
+    }
+}
+
+/// Stored name of an event type (its serde name, e.g. `settlement_success`)
+fn event_type_name(event_type: &AuditEventType) -> Result<String> {
+    serde_json::to_value(event_type)?
+        .as_str()
+        .map(str::to_string)
+        .context("Audit event type is not a string")
+}
+
+/// Fixed-width RFC 3339 so text comparison matches time order
+fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+fn event_from_row<'r, R>(row: &'r R) -> Result<AuditEvent>
+where
+    R: Row,
+    &'static str: ColumnIndex<R>,
+    String: Decode<'r, R::Database> + Type<R::Database>,
+    i64: Decode<'r, R::Database> + Type<R::Database>,
+{
+    let event_type: String = row.try_get("event_type")?;
+    let timestamp: String = row.try_get("timestamp")?;
+    let amount: Option<i64> = row.try_get("amount")?;
+    let metadata: Option<String> = row.try_get("metadata")?;
+
+    Ok(AuditEvent {
+        id: row.try_get("id")?,
+        event_type: serde_json::from_value(serde_json::Value::String(event_type))
+            .context("Unknown audit event type")?,
+        timestamp: DateTime::parse_from_rfc3339(&timestamp)
+            .context("Invalid audit event timestamp")?
+            .with_timezone(&Utc),
+        transaction_signature: row.try_get("transaction_signature")?,
+        payer: row.try_get("payer")?,
+        network: row.try_get("network")?,
+        tenant: row.try_get("tenant")?,
+        amount: amount.and_then(|amount| u64::try_from(amount).ok()),
+        recipient: row.try_get("recipient")?,
+        error: row.try_get("error")?,
+        metadata: metadata
+            .map(|metadata| serde_json::from_str(&metadata))
+            .transpose()
+            .context("Invalid audit event metadata")?,
+    })
+}
+
+/// Multi-row INSERT for `rows` events
+///
+/// Uses numbered `$n` placeholders, which both SQLite and Postgres accept.
+fn insert_sql(rows: usize) -> String {
+    let values = (0..rows)
+        .map(|row| {
+            let placeholders = (1..=COLUMNS.len())
+                .map(|column| format!("${}", row * COLUMNS.len() + column))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({})", placeholders)
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "INSERT INTO audit_events ({}) VALUES {} ON CONFLICT (id) DO NOTHING",
+        COLUMNS.join(", "),
+        values
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_sql_numbers_placeholders_per_row() {
+        let sql = insert_sql(2);
+        assert!(sql.contains("($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11), ($12,"));
+        assert!(sql.ends_with("$22) ON CONFLICT (id) DO NOTHING"));
+    }
+
+    #[cfg(feature = "audit-sqlite")]
+    fn sqlite_store() -> (AuditStore, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("audit-{}.db", uuid::Uuid::new_v4()));
+        let store = AuditStore::connect_lazy(&format!("sqlite://{}?mode=rwc", path.display())).unwrap();
+        (store, path)
+    }
+
+    #[cfg(feature = "audit-sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_round_trip() {
+        let (store, path) = sqlite_store();
+
+        let event = AuditEvent::new(AuditEventType::SettlementSuccess)
+            .with_network("solana-devnet".to_string())
+            .with_payer("payer".to_string())
+            .with_amount(1_000_000)
+            .with_metadata(serde_json::json!({ "port": 8080 }));
+
+        store.insert_batch(std::slice::from_ref(&event)).await.unwrap();
+        // Re-inserting the same event is a no-op
+        store.insert_batch(&[event]).await.unwrap();
+
+        let pool = match &store.pool {
+            StorePool::Sqlite(pool) => pool,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        };
+        let rows = sqlx::query("SELECT event_type, amount, metadata FROM audit_events")
+            .fetch_all(pool)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get::<String, _>("event_type"), "settlement_success");
+        assert_eq!(rows[0].get::<i64, _>("amount"), 1_000_000);
+        assert_eq!(rows[0].get::<String, _>("metadata"), r#"{"port":8080}"#);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[cfg(feature = "audit-sqlite")]
+    #[tokio::test]
+    async fn test_query_filters_and_pagination() {
+        let (store, path) = sqlite_store();
+
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let events: Vec<_> = (0..5i64)
+            .map(|i| {
+                let mut event = AuditEvent::new(if i % 2 == 0 {
+                    AuditEventType::SettlementSuccess
+                } else {
+                    AuditEventType::SettlementFailed
+                })
+                .with_network("solana-devnet".to_string())
+                .with_payer(format!("payer-{}", i % 2));
+                event.timestamp = start + chrono::Duration::minutes(i);
+                if i == 3 {
+                    event = event.with_tenant("acme".to_string());
+                }
+                event
+            })
+            .collect();
+        store.insert_batch(&events).await.unwrap();
+
+        let successes = store
+            .query(&AuditQuery {
+                event_type: Some(AuditEventType::SettlementSuccess),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(successes.len(), 3);
+        // Newest first
+        assert_eq!(successes[0].id, events[4].id);
+
+        let window = store
+            .query(&AuditQuery {
+                payer: Some("payer-1".to_string()),
+                from: Some(start + chrono::Duration::minutes(1)),
+                to: Some(start + chrono::Duration::minutes(3)),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(window.len(), 1);
+        assert_eq!(window[0].id, events[1].id);
+
+        let tenant = store
+            .query(&AuditQuery {
+                tenant: Some("acme".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(tenant.len(), 1);
+        assert_eq!(tenant[0].id, events[3].id);
+        assert_eq!(tenant[0].tenant.as_deref(), Some("acme"));
+
+        let page = store
+            .query(&AuditQuery {
+                limit: Some(2),
+                offset: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let ids: Vec<_> = page.iter().map(|event| event.id.as_str()).collect();
+        assert_eq!(ids, vec![events[2].id.as_str(), events[1].id.as_str()]);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
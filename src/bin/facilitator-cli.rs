@@ -1,7 +1,34 @@
-use clap::{Parser, Subcommand};
-use anyhow::Result;
+use clap::{Args, Parser, Subcommand};
+use anyhow::{Context, Result};
+use axum::extract::State;
 use solana_sdk::signer::{keypair::Keypair, Signer};
 use solana_client::rpc_client::RpcClient;
+use x402_facilitator::receipts::SignedReceipt;
+use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+use x402_facilitator::solana::decoder::{
+    decode_versioned_transaction_from_base64, encode_transaction_to_base64, to_legacy_transaction,
+};
+use x402_facilitator::solana::verifier::{
+    compute_budget_program_id, parse_transfer_instruction, verify_compute_limit_instruction,
+    verify_compute_price_instruction, verify_create_ata_funder, verify_create_ata_instruction, verify_fee_payer_position,
+    verify_fee_payer_safety, verify_instruction_count, verify_message_header, verify_signer_count,
+    DEFAULT_REQUIRED_SIGNERS,
+};
+use x402_facilitator::config::Config;
+use x402_facilitator::handlers;
+use x402_facilitator::middleware::msgpack::Negotiated;
+use x402_facilitator::types::requests::{
+    ExtraFields, PaymentPayload, PaymentRequirements, SettleRequest, SvmPayload, VerifyRequest,
+};
+use x402_facilitator::types::responses::VerifyResponse;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::str::FromStr;
+use solana_sdk::{
+    instruction::CompiledInstruction, message::Message, pubkey::Pubkey, sanitize::Sanitize, signature::Signature,
+    transaction::Transaction,
+};
+use x402_facilitator::error::VerificationError;
+use x402_facilitator::webhooks::{signed_request, WebhookEvent, WebhookPayload};
 
 #[derive(Parser)]
 #[command(name = "facilitator-cli")]
@@ -44,6 +71,488 @@ enum Commands {
         #[arg(short, long, default_value = "https://api.devnet.solana.com")]
         rpc: String,
     },
+
+    /// Verify a signed payment receipt offline
+    VerifyReceipt {
+        /// Receipt JSON file (as returned by /settle or /receipt)
+        file: String,
+
+        /// Facilitator fee payer public key (base58)
+        #[arg(short, long)]
+        facilitator: String,
+    },
+
+    /// Decode a payment transaction and run the verifier's offline checks
+    Decode {
+        /// Base64 transaction (the `payload.transaction` field)
+        #[arg(long)]
+        base64: String,
+
+        /// Payment requirements JSON file, for the checks that need them
+        #[arg(short, long)]
+        requirements: Option<String>,
+    },
+
+    /// Verify a payment via a facilitator's /verify, or in-process
+    Verify(PaymentArgs),
+
+    /// Settle a payment via a facilitator's /settle, or in-process
+    Settle(PaymentArgs),
+
+    /// Build and sign a test payment with matching requirements
+    GenPayment(GenPaymentArgs),
+
+    /// Watch a fee payer balance; exits non-zero once it is below threshold
+    Monitor {
+        /// Public key (base58)
+        #[arg(long)]
+        pubkey: String,
+
+        /// Minimum balance in SOL
+        #[arg(long, default_value_t = 0.5)]
+        threshold: f64,
+
+        /// Seconds between checks; without it the balance is checked once
+        #[arg(long)]
+        interval: Option<u64>,
+
+        /// RPC URL
+        #[arg(short, long, default_value = "https://api.devnet.solana.com")]
+        rpc: String,
+    },
+
+    /// Load-test /verify (or /verify/batch) and report latency percentiles
+    Bench(BenchArgs),
+
+    /// Keypair utilities
+    Key {
+        #[command(subcommand)]
+        command: KeyCommands,
+    },
+
+    /// Derive an associated token account, check it exists, optionally create it
+    Ata {
+        /// Wallet owning the token account
+        #[arg(long)]
+        owner: String,
+
+        /// Token mint (SPL Token or Token-2022)
+        #[arg(long)]
+        mint: String,
+
+        /// Create the account if it doesn't exist
+        #[arg(long)]
+        create: bool,
+
+        /// Keypair file paying for the account (required with --create)
+        #[arg(long)]
+        payer_keypair: Option<String>,
+
+        /// RPC URL
+        #[arg(short, long, default_value = "https://api.devnet.solana.com")]
+        rpc: String,
+    },
+
+    /// Send a signed sample webhook to a receiver and report its response
+    WebhookTest {
+        /// Receiver URL
+        #[arg(long)]
+        url: String,
+
+        /// Signing secret shared with the receiver
+        #[arg(long)]
+        secret: String,
+
+        /// Event to send (e.g. settlement.success, verification.failure)
+        #[arg(long, default_value = "settlement.success")]
+        event: String,
+    },
+
+    /// Request a devnet/testnet SOL airdrop
+    Airdrop {
+        /// Public key (base58)
+        #[arg(long)]
+        pubkey: String,
+
+        /// Amount in SOL
+        #[arg(long, default_value_t = 1.0)]
+        sol: f64,
+
+        /// RPC URL
+        #[arg(short, long, default_value = "https://api.devnet.solana.com")]
+        rpc: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeyCommands {
+    /// Convert a keypair between Solana JSON array and base58
+    ///
+    /// JSON input (a keypair file or inline array) becomes base58, base58
+    /// becomes a JSON array.
+    Convert {
+        /// Keypair file, JSON array or base58 private key
+        input: String,
+
+        /// Write the result here instead of printing it
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    /// Facilitator base URL
+    #[arg(short, long, default_value = "http://localhost:3000")]
+    url: String,
+
+    /// `/verify` request body JSON file (as printed by `gen-payment`)
+    #[arg(short, long)]
+    payload: String,
+
+    /// Target requests per second
+    #[arg(long, default_value_t = 100)]
+    rps: u32,
+
+    /// Test duration in seconds
+    #[arg(short, long, default_value_t = 30)]
+    duration: u64,
+
+    /// Most requests in flight; ticks finding none free are skipped
+    #[arg(short, long, default_value_t = 64)]
+    concurrency: usize,
+
+    /// Send batches of this many payments to /verify/batch instead
+    #[arg(short, long)]
+    batch: Option<usize>,
+
+    /// Bearer token for facilitators that require authentication
+    #[arg(long)]
+    token: Option<String>,
+}
+
+#[derive(Args)]
+struct GenPaymentArgs {
+    /// Amount in the mint's base units
+    #[arg(long)]
+    amount: u64,
+
+    /// Token mint (SPL Token or Token-2022)
+    #[arg(long)]
+    mint: String,
+
+    /// Recipient wallet
+    #[arg(long)]
+    pay_to: String,
+
+    /// Keypair file of the paying wallet
+    #[arg(long)]
+    payer_keypair: String,
+
+    /// Facilitator fee payer public key (`extra.feePayer`)
+    #[arg(long)]
+    fee_payer: String,
+
+    /// RPC URL, for the mint, the recipient's token account and a blockhash
+    #[arg(long, default_value = "https://api.devnet.solana.com")]
+    rpc: String,
+
+    #[arg(long, default_value = "solana-devnet")]
+    network: String,
+
+    #[arg(long, default_value = "/test")]
+    resource: String,
+
+    /// Write the payment payload JSON here
+    #[arg(long)]
+    payload_out: Option<String>,
+
+    /// Write the payment requirements JSON here
+    #[arg(long)]
+    requirements_out: Option<String>,
+
+    /// POST the payment to this facilitator's /verify
+    #[arg(long)]
+    verify_url: Option<String>,
+
+    /// Bearer token for facilitators that require authentication
+    #[arg(long)]
+    token: Option<String>,
+}
+
+#[derive(Args)]
+struct PaymentArgs {
+    /// Payment payload JSON file (`paymentPayload`)
+    #[arg(short, long)]
+    payload: String,
+
+    /// Payment requirements JSON file (`paymentRequirements`)
+    #[arg(short, long)]
+    requirements: String,
+
+    /// Facilitator base URL; without it the payment is handled locally
+    /// using the configuration from the environment
+    #[arg(short, long)]
+    url: Option<String>,
+
+    /// Bearer token for facilitators that require authentication
+    #[arg(long)]
+    token: Option<String>,
+
+    /// .env file loaded for local handling
+    #[arg(short, long, default_value = ".env")]
+    env_file: String,
+}
+
+impl PaymentArgs {
+    fn read(&self) -> Result<(PaymentPayload, PaymentRequirements)> {
+        let payload = std::fs::read_to_string(&self.payload)
+            .with_context(|| format!("Failed to read {}", self.payload))?;
+        let requirements = std::fs::read_to_string(&self.requirements)
+            .with_context(|| format!("Failed to read {}", self.requirements))?;
+
+        Ok((
+            serde_json::from_str(&payload).context("Invalid payment payload")?,
+            serde_json::from_str(&requirements).context("Invalid payment requirements")?,
+        ))
+    }
+
+    /// Config for in-process handling
+    fn local_config(&self) -> Result<Config> {
+        // A missing file is fine when the environment is already set
+        dotenvy::from_filename(&self.env_file).ok();
+        Config::from_env()
+    }
+
+}
+
+/// Event data shaped like what the handlers send
+fn sample_webhook_data(event: &WebhookEvent) -> serde_json::Value {
+    let payer = Pubkey::new_unique().to_string();
+    match event {
+        WebhookEvent::VerificationSuccess => serde_json::json!({
+            "payer": payer,
+            "network": "solana-devnet",
+        }),
+        WebhookEvent::VerificationFailure => serde_json::json!({
+            "reason": "invalid_exact_svm_payload_transaction_amount_mismatch",
+            "network": "solana-devnet",
+        }),
+        WebhookEvent::SettlementSuccess => serde_json::json!({
+            "signature": Signature::new_unique().to_string(),
+            "payer": payer,
+            "network": "solana-devnet",
+        }),
+        WebhookEvent::SettlementFailure => serde_json::json!({
+            "error": "Transaction simulation failed",
+            "payer": payer,
+            "network": "solana-devnet",
+        }),
+        WebhookEvent::FeePayerLowBalance => serde_json::json!({
+            "fee_payer": payer,
+            "balance_lamports": 1_000_000,
+            "min_balance_lamports": 5_000_000,
+            "network": "solana-devnet",
+        }),
+        WebhookEvent::RpcUnhealthy => serde_json::json!({
+            "endpoint": "api.devnet.solana.com",
+            "error": "RPC request timed out",
+            "slot_lag": null,
+        }),
+        WebhookEvent::RateLimitExceeded => serde_json::json!({
+            "client": "ip:203.0.113.7",
+            "path": "/verify",
+            "retry_after_seconds": 1,
+            "network": "solana-devnet",
+        }),
+        WebhookEvent::ReplayDetected => serde_json::json!({
+            "transaction_hash": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+            "network": "solana-devnet",
+        }),
+        WebhookEvent::Test => serde_json::json!({ "endpoint": "facilitator-cli" }),
+    }
+}
+
+fn sol_to_lamports(sol: f64) -> u64 {
+    (sol * 1_000_000_000.0) as u64
+}
+
+fn lamports_to_sol(lamports: u64) -> f64 {
+    lamports as f64 / 1_000_000_000.0
+}
+
+/// POST `body` to `path` on the facilitator at `url`
+async fn post_json<B: serde::Serialize, R: serde::de::DeserializeOwned>(
+    url: &str,
+    path: &str,
+    token: Option<&str>,
+    body: &B,
+) -> Result<R> {
+    let mut request = reqwest::Client::new()
+        .post(format!("{}{}", url.trim_end_matches('/'), path))
+        .json(body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("{} returned {}: {}", path, status, response.text().await.unwrap_or_default());
+    }
+    Ok(response.json().await?)
+}
+
+/// Drive requests at a fixed rate (open loop) and print the latency report
+async fn run_bench(args: BenchArgs) -> Result<()> {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    let request: VerifyRequest = serde_json::from_str(
+        &std::fs::read_to_string(&args.payload).with_context(|| format!("Failed to read {}", args.payload))?,
+    )
+    .context("Invalid /verify request body")?;
+    if args.rps == 0 || args.concurrency == 0 {
+        anyhow::bail!("--rps and --concurrency must be positive");
+    }
+
+    let (path, body) = match args.batch {
+        Some(size) => ("/verify/batch", serde_json::to_vec(&vec![&request; size])?),
+        None => ("/verify", serde_json::to_vec(&request)?),
+    };
+    let url = format!("{}{}", args.url.trim_end_matches('/'), path);
+
+    println!(
+        "🏋️  Benchmarking {} at {} req/s for {}s (concurrency {})...\n",
+        url, args.rps, args.duration, args.concurrency
+    );
+
+    let client = reqwest::Client::new();
+    let permits = Arc::new(tokio::sync::Semaphore::new(args.concurrency));
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / args.rps as f64));
+    let mut requests = tokio::task::JoinSet::new();
+    let mut skipped = 0u64;
+
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(args.duration);
+    while Instant::now() < deadline {
+        ticker.tick().await;
+        let Ok(permit) = permits.clone().try_acquire_owned() else {
+            skipped += 1;
+            continue;
+        };
+
+        let mut request = client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if let Some(token) = &args.token {
+            request = request.bearer_auth(token);
+        }
+        requests.spawn(async move {
+            let sent = Instant::now();
+            let ok = matches!(request.send().await, Ok(response) if response.status().is_success());
+            drop(permit);
+            (sent.elapsed(), ok)
+        });
+    }
+
+    let mut latencies = Vec::new();
+    let mut errors = 0u64;
+    while let Some(result) = requests.join_next().await {
+        let (latency, ok) = result?;
+        if ok {
+            latencies.push(latency);
+        } else {
+            errors += 1;
+        }
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+    latencies.sort();
+
+    let percentile = |p: f64| -> String {
+        match latencies.len() {
+            0 => "-".to_string(),
+            n => format!("{:.2?}", latencies[((n - 1) as f64 * p).round() as usize]),
+        }
+    };
+
+    println!("📊 Results");
+    println!("   Succeeded:  {}", latencies.len());
+    println!("   Failed:     {}", errors);
+    println!("   Skipped:    {} (concurrency limit reached)", skipped);
+    println!("   Throughput: {:.1} req/s", latencies.len() as f64 / elapsed);
+    if let Some(size) = args.batch {
+        println!("              {:.1} payments/s", (latencies.len() * size) as f64 / elapsed);
+    }
+    println!("   p50:        {}", percentile(0.50));
+    println!("   p90:        {}", percentile(0.90));
+    println!("   p99:        {}", percentile(0.99));
+    println!("   max:        {}", percentile(1.0));
+
+    Ok(())
+}
+
+/// Read a keypair file: Solana CLI JSON, or the raw bytes `generate-key` writes
+fn read_keypair(path: &str) -> Result<Keypair> {
+    if let Ok(keypair) = solana_sdk::signature::read_keypair_file(path) {
+        return Ok(keypair);
+    }
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    Keypair::from_bytes(&bytes).map_err(|e| anyhow::anyhow!("Invalid keypair file {}: {}", path, e))
+}
+
+/// Build and sign a test payment, returning the `/verify` request for it
+fn generate_payment(args: &GenPaymentArgs, payer: &Keypair) -> Result<VerifyRequest> {
+    let client = RpcClient::new(args.rpc.clone());
+    let mint = Pubkey::from_str(&args.mint).context("Invalid mint pubkey")?;
+    let pay_to = Pubkey::from_str(&args.pay_to).context("Invalid payTo pubkey")?;
+
+    let token_program = client.get_account(&mint).context("Failed to fetch mint")?.owner;
+    let decimals = client.get_token_supply(&mint).context("Failed to fetch mint decimals")?.decimals;
+    let destination = get_associated_token_address_with_program_id(&pay_to, &mint, &token_program);
+    let create_destination_ata = client
+        .get_account_with_commitment(&destination, client.commitment())?
+        .value
+        .is_none();
+
+    let payment_requirements = PaymentRequirements {
+        scheme: "exact".to_string(),
+        network: args.network.clone(),
+        max_amount_required: args.amount.to_string(),
+        asset: mint.to_string(),
+        pay_to: pay_to.to_string(),
+        resource: args.resource.clone(),
+        description: "Test payment".to_string(),
+        mime_type: "application/json".to_string(),
+        max_timeout_seconds: 60,
+        output_schema: None,
+        extra: ExtraFields::new(args.fee_payer.clone()),
+    };
+
+    let recent_blockhash = client.get_latest_blockhash().context("Failed to fetch blockhash")?;
+    let mut transaction = build_payment_transaction(&PaymentTransaction {
+        requirements: &payment_requirements,
+        payer: payer.pubkey(),
+        decimals,
+        token_program,
+        create_destination_ata,
+        recent_blockhash,
+    })?;
+    // The facilitator's signature is added at settlement
+    transaction.try_partial_sign(&[payer], recent_blockhash)?;
+
+    let payment_payload = PaymentPayload {
+        x402_version: 1,
+        scheme: "exact".to_string(),
+        network: args.network.clone(),
+        payload: SvmPayload {
+            transaction: encode_transaction_to_base64(&transaction)?,
+        },
+        timestamp: Some(chrono::Utc::now().timestamp() as u64),
+    };
+
+    Ok(VerifyRequest { payment_payload, payment_requirements, dry_run: false })
 }
 
 #[tokio::main]
@@ -181,16 +690,485 @@ async fn main() -> Result<()> {
             
             match client.get_balance(&pubkey) {
                 Ok(balance) => {
-                    let sol = balance as f64 / 1_000_000_000.0;
-                    println!("✅ Balance: {} lamports ({:.9} SOL)", balance, sol);
+                    println!("✅ Balance: {} lamports ({:.9} SOL)", balance, lamports_to_sol(balance));
                 }
                 Err(e) => {
                     println!("❌ Failed to get balance: {}", e);
                 }
             }
         }
+
+        Commands::VerifyReceipt { file, facilitator } => {
+            println!("🧾 Verifying receipt {}...\n", file);
+
+            let facilitator = match solana_sdk::pubkey::Pubkey::try_from(facilitator.as_str()) {
+                Ok(pk) => pk,
+                Err(e) => {
+                    println!("❌ Invalid public key: {}", e);
+                    return Ok(());
+                }
+            };
+
+            let receipt: SignedReceipt = serde_json::from_str(&std::fs::read_to_string(&file)?)?;
+
+            match receipt.verify(&facilitator) {
+                Ok(()) => {
+                    println!("✅ Receipt is valid");
+                    println!("   Transaction: {}", receipt.receipt.transaction);
+                    println!("   Amount:      {} ({})", receipt.receipt.amount, receipt.receipt.asset);
+                    println!("   Payer:       {}", receipt.receipt.payer);
+                    println!("   Payee:       {}", receipt.receipt.payee);
+                }
+                Err(e) => println!("❌ Receipt is invalid: {}", e),
+            }
+        }
+
+        Commands::Decode { base64, requirements } => {
+            println!("🔍 Decoding transaction...\n");
+
+            let requirements: Option<PaymentRequirements> = match requirements {
+                Some(path) => Some(serde_json::from_str(&std::fs::read_to_string(&path)?)?),
+                None => None,
+            };
+
+            let versioned = match decode_versioned_transaction_from_base64(base64.trim()) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    println!("❌ {}", e);
+                    return Ok(());
+                }
+            };
+            let tx = match to_legacy_transaction(&versioned) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    println!("❌ Unsupported transaction: {}", e);
+                    return Ok(());
+                }
+            };
+            // Out-of-range account indexes would make the checks below panic
+            if let Err(e) = tx.sanitize() {
+                println!("❌ Malformed transaction: {}", e);
+                return Ok(());
+            }
+
+            print_transaction(&tx);
+            print_signatures(&tx);
+            print_checks(&tx, requirements.as_ref());
+        }
+
+        Commands::Verify(args) => {
+            let (payment_payload, payment_requirements) = args.read()?;
+            let request = VerifyRequest { payment_payload, payment_requirements, dry_run: false };
+
+            let response = match &args.url {
+                Some(url) => post_json(url, "/verify", args.token.as_deref(), &request).await?,
+                None => handlers::verify::verify(State(args.local_config()?), None, None, Negotiated(request)).await.0,
+            };
+
+            // JSON on stdout and the exit status, for scripts
+            println!("{}", serde_json::to_string_pretty(&response)?);
+            if !response.is_valid {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Settle(args) => {
+            let (payment_payload, payment_requirements) = args.read()?;
+            let request = SettleRequest { payment_payload, payment_requirements };
+
+            let response = match &args.url {
+                Some(url) => post_json(url, "/settle", args.token.as_deref(), &request).await?,
+                None => handlers::settle::settle(State(args.local_config()?), None, None, Negotiated(request)).await.0,
+            };
+
+            println!("{}", serde_json::to_string_pretty(&response)?);
+            if !response.success {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::GenPayment(args) => {
+            let payer = read_keypair(&args.payer_keypair)?;
+            let request = generate_payment(&args, &payer)?;
+
+            if let Some(path) = &args.payload_out {
+                std::fs::write(path, serde_json::to_string_pretty(&request.payment_payload)?)?;
+                eprintln!("💾 Payment payload saved to: {}", path);
+            }
+            if let Some(path) = &args.requirements_out {
+                std::fs::write(path, serde_json::to_string_pretty(&request.payment_requirements)?)?;
+                eprintln!("💾 Payment requirements saved to: {}", path);
+            }
+            // The `/verify` request body, ready for curl
+            println!("{}", serde_json::to_string_pretty(&request)?);
+
+            if let Some(url) = &args.verify_url {
+                let response: VerifyResponse = post_json(url, "/verify", args.token.as_deref(), &request).await?;
+                eprintln!("{}", serde_json::to_string_pretty(&response)?);
+                if !response.is_valid {
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Monitor { pubkey, threshold, interval, rpc } => {
+            let pubkey = Pubkey::from_str(&pubkey).context("Invalid public key")?;
+            let threshold_lamports = sol_to_lamports(threshold);
+            let client = RpcClient::new(rpc);
+
+            println!("👀 Monitoring {} (threshold {} SOL)...\n", pubkey, threshold);
+
+            loop {
+                match client.get_balance(&pubkey) {
+                    Ok(balance) if balance < threshold_lamports => {
+                        println!("🚨 Balance low: {:.9} SOL (threshold {} SOL)", lamports_to_sol(balance), threshold);
+                        std::process::exit(1);
+                    }
+                    Ok(balance) => println!("✅ Balance: {:.9} SOL", lamports_to_sol(balance)),
+                    // Transient RPC errors only fail one-shot checks
+                    Err(e) if interval.is_some() => println!("⚠️  Failed to get balance: {}", e),
+                    Err(e) => {
+                        println!("❌ Failed to get balance: {}", e);
+                        std::process::exit(2);
+                    }
+                }
+
+                match interval {
+                    Some(seconds) => tokio::time::sleep(std::time::Duration::from_secs(seconds)).await,
+                    None => break,
+                }
+            }
+        }
+
+        Commands::Bench(args) => run_bench(args).await?,
+
+        Commands::Key { command: KeyCommands::Convert { input, output } } => {
+            let input = match std::fs::read_to_string(&input) {
+                Ok(contents) => contents,
+                Err(_) => input,
+            };
+            let input = input.trim();
+
+            let converted = if input.starts_with('[') {
+                let bytes: Vec<u8> = serde_json::from_str(input).context("Invalid JSON keypair")?;
+                let keypair = Keypair::from_bytes(&bytes).map_err(|e| anyhow::anyhow!("Invalid keypair: {}", e))?;
+                eprintln!("🔑 {} (JSON → base58)", keypair.pubkey());
+                keypair.to_base58_string()
+            } else {
+                let bytes = bs58::decode(input).into_vec().context("Invalid base58 keypair")?;
+                let keypair = Keypair::from_bytes(&bytes).map_err(|e| anyhow::anyhow!("Invalid keypair: {}", e))?;
+                eprintln!("🔑 {} (base58 → JSON)", keypair.pubkey());
+                serde_json::to_string(&keypair.to_bytes().to_vec())?
+            };
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, converted)?;
+                    eprintln!("💾 Keypair saved to: {}", path);
+                }
+                None => println!("{}", converted),
+            }
+        }
+
+        Commands::Ata { owner, mint, create, payer_keypair, rpc } => {
+            let owner = Pubkey::from_str(&owner).context("Invalid owner pubkey")?;
+            let mint = Pubkey::from_str(&mint).context("Invalid mint pubkey")?;
+            let client = RpcClient::new_with_commitment(rpc, solana_sdk::commitment_config::CommitmentConfig::confirmed());
+
+            let token_program = client.get_account(&mint).context("Failed to fetch mint")?.owner;
+            let ata = get_associated_token_address_with_program_id(&owner, &mint, &token_program);
+
+            println!("🏦 Associated token account");
+            println!("   Owner:   {}", owner);
+            println!("   Mint:    {} ({})", mint, program_name(&token_program));
+            println!("   Address: {}", ata);
+
+            if client.get_account_with_commitment(&ata, client.commitment())?.value.is_some() {
+                println!("\n✅ Account exists");
+                return Ok(());
+            }
+            println!("\n⚠️  Account does not exist");
+            if !create {
+                println!("💡 To create it, use: --create --payer-keypair <path>");
+                std::process::exit(1);
+            }
+
+            let payer = read_keypair(&payer_keypair.context("--create needs --payer-keypair")?)?;
+            let instruction = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &payer.pubkey(),
+                &owner,
+                &mint,
+                &token_program,
+            );
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer],
+                client.get_latest_blockhash()?,
+            );
+
+            match client.send_and_confirm_transaction(&transaction) {
+                Ok(signature) => println!("✅ Created: {}", signature),
+                Err(e) => {
+                    println!("❌ Failed to create account: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::WebhookTest { url, secret, event } => {
+            let event: WebhookEvent = event.parse()?;
+            let payload = WebhookPayload::new(event.clone(), sample_webhook_data(&event));
+            let payload_json = serde_json::to_string(&payload)?;
+
+            println!("🪝 Sending {} to {}...\n", payload.event, url);
+            println!("{}\n", serde_json::to_string_pretty(&payload)?);
+
+            let request = signed_request(&reqwest::Client::new(), &url, &payload_json, &secret)
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            let started = std::time::Instant::now();
+            let response = match request.timeout(std::time::Duration::from_secs(10)).send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    println!("❌ Delivery failed: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let elapsed = started.elapsed();
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+
+            if status.is_success() {
+                println!("✅ Receiver responded {} in {:.2?}", status, elapsed);
+            } else {
+                println!("❌ Receiver responded {} in {:.2?}", status, elapsed);
+            }
+            if !body.is_empty() {
+                println!("   {}", body);
+            }
+            if !status.is_success() {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Airdrop { pubkey, sol, rpc } => {
+            if rpc.contains("mainnet") {
+                println!("❌ Airdrops are only available on devnet and testnet");
+                std::process::exit(1);
+            }
+
+            let pubkey = Pubkey::from_str(&pubkey).context("Invalid public key")?;
+            let client = RpcClient::new_with_commitment(rpc, solana_sdk::commitment_config::CommitmentConfig::confirmed());
+
+            println!("🪂 Requesting {} SOL for {}...", sol, pubkey);
+            let signature = match client.request_airdrop(&pubkey, sol_to_lamports(sol)) {
+                Ok(signature) => signature,
+                Err(e) => {
+                    println!("❌ Airdrop failed: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            print!("   Confirming {}... ", signature);
+            match client.poll_for_signature(&signature) {
+                Ok(()) => println!("✅"),
+                Err(e) => {
+                    println!("❌ {}", e);
+                    std::process::exit(1);
+                }
+            }
+
+            let balance = client.get_balance(&pubkey)?;
+            println!("\n✅ Balance: {:.9} SOL", lamports_to_sol(balance));
+        }
     }
 
     Ok(())
 }
 
+/// Human-readable name of well-known programs
+fn program_name(program_id: &Pubkey) -> &'static str {
+    if *program_id == compute_budget_program_id() {
+        "Compute Budget"
+    } else if *program_id == spl_token::ID {
+        "SPL Token"
+    } else if *program_id == spl_token_2022::ID {
+        "SPL Token-2022"
+    } else if *program_id == spl_associated_token_account::ID {
+        "Associated Token Account"
+    } else if *program_id == solana_sdk::system_program::ID {
+        "System"
+    } else {
+        "Unknown"
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+/// Decoded instruction arguments, for the instructions payments use
+fn describe_instruction(program_id: &Pubkey, data: &[u8]) -> String {
+    let discriminator = data.first().copied();
+
+    if *program_id == compute_budget_program_id() {
+        match discriminator {
+            Some(2) => match data.get(1..5) {
+                Some(units) => format!("SetComputeUnitLimit {} units", u32::from_le_bytes(units.try_into().unwrap())),
+                None => "SetComputeUnitLimit (truncated)".to_string(),
+            },
+            Some(3) => match read_u64(data, 1) {
+                Some(price) => format!("SetComputeUnitPrice {} micro-lamports", price),
+                None => "SetComputeUnitPrice (truncated)".to_string(),
+            },
+            _ => "Unknown compute budget instruction".to_string(),
+        }
+    } else if *program_id == spl_token::ID || *program_id == spl_token_2022::ID {
+        match (discriminator, read_u64(data, 1), data.get(9)) {
+            (Some(12), Some(amount), Some(decimals)) => {
+                format!("TransferChecked amount {} (decimals {})", amount, decimals)
+            }
+            (Some(3), Some(amount), _) => format!("Transfer amount {}", amount),
+            _ => "Other token instruction".to_string(),
+        }
+    } else if *program_id == spl_associated_token_account::ID {
+        match discriminator {
+            None | Some(0) => "Create".to_string(),
+            Some(1) => "CreateIdempotent".to_string(),
+            _ => "Other associated token account instruction".to_string(),
+        }
+    } else {
+        "-".to_string()
+    }
+}
+
+fn print_instruction(index: usize, instruction: &CompiledInstruction, message: &Message) {
+    let program_id = &message.account_keys[instruction.program_id_index as usize];
+    let discriminator = instruction
+        .data
+        .first()
+        .map(|d| d.to_string())
+        .unwrap_or_else(|| "none".to_string());
+
+    println!("   #{} {} ({})", index, program_name(program_id), program_id);
+    println!("      Discriminator: {}", discriminator);
+    println!("      Decoded:       {}", describe_instruction(program_id, &instruction.data));
+    for &account in &instruction.accounts {
+        let account = account as usize;
+        let flags = match (message.is_signer(account), message.is_maybe_writable(account, None)) {
+            (true, true) => "signer, writable",
+            (true, false) => "signer",
+            (false, true) => "writable",
+            (false, false) => "readonly",
+        };
+        println!("      - {} [{}]", message.account_keys[account], flags);
+    }
+}
+
+fn print_transaction(tx: &Transaction) {
+    let message = &tx.message;
+    println!("📦 Transaction");
+    println!("   Fee payer:  {}", message.account_keys[0]);
+    println!("   Blockhash:  {}", message.recent_blockhash);
+    println!("   Accounts:   {}", message.account_keys.len());
+    println!();
+
+    println!("📋 Instructions ({})", message.instructions.len());
+    for (index, instruction) in message.instructions.iter().enumerate() {
+        print_instruction(index, instruction, message);
+    }
+    println!();
+}
+
+fn print_signatures(tx: &Transaction) {
+    let valid = tx.verify_with_results();
+
+    println!("✍️  Signatures");
+    for (index, signature) in tx.signatures.iter().enumerate() {
+        let signer = tx
+            .message
+            .account_keys
+            .get(index)
+            .map(|key| key.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let status = if *signature == Signature::default() {
+            "⏳ missing"
+        } else if valid.get(index).copied().unwrap_or(false) {
+            "✅ valid"
+        } else {
+            "❌ invalid"
+        };
+        println!("   {} {}", status, signer);
+    }
+    println!();
+}
+
+fn print_check(name: &str, result: Result<(), VerificationError>) {
+    match result {
+        Ok(()) => println!("   ✅ {}", name),
+        Err(e) => println!("   ❌ {} ({})", name, e.as_str()),
+    }
+}
+
+/// The verifier's instruction checks, in `/verify` order
+///
+/// Account existence checks need RPC and are not run.
+fn print_checks(tx: &Transaction, requirements: Option<&PaymentRequirements>) {
+    let message = &tx.message;
+    println!("🧪 Verifier checks");
+
+    print_check("Message header", verify_message_header(tx));
+    print_check("Signer count", verify_signer_count(tx, DEFAULT_REQUIRED_SIGNERS));
+    let has_create_ata = match verify_instruction_count(tx) {
+        Ok(has_create_ata) => {
+            print_check("Instruction count", Ok(()));
+            has_create_ata
+        }
+        Err(e) => {
+            // The remaining checks locate instructions by position
+            print_check("Instruction count", Err(e));
+            return;
+        }
+    };
+    print_check(
+        "Compute limit instruction",
+        verify_compute_limit_instruction(&message.instructions[0], message),
+    );
+    print_check(
+        "Compute price instruction",
+        verify_compute_price_instruction(&message.instructions[1], message),
+    );
+
+    let Some(requirements) = requirements else {
+        println!("   ⏭️  Fee payer, ATA creation and transfer checks need --requirements");
+        return;
+    };
+    let fee_payer = match requirements.extra.fee_payer.parse::<Pubkey>() {
+        Ok(fee_payer) => fee_payer,
+        Err(e) => {
+            println!("   ❌ Invalid feePayer in requirements: {}", e);
+            return;
+        }
+    };
+
+    print_check("Fee payer position", verify_fee_payer_position(tx, &fee_payer));
+    print_check("Fee payer safety", verify_fee_payer_safety(tx, &fee_payer));
+    if has_create_ata {
+        print_check(
+            "Create ATA instruction",
+            verify_create_ata_instruction(&message.instructions[2], message, requirements),
+        );
+        print_check(
+            "Create ATA funder",
+            verify_create_ata_funder(&message.instructions[2], message, &fee_payer, false).map(|_| ()),
+        );
+    }
+
+    let transfer_idx = if has_create_ata { 3 } else { 2 };
+    print_check(
+        "Transfer instruction",
+        parse_transfer_instruction(&message.instructions[transfer_idx], message, requirements, &fee_payer, false)
+            .map(|_| ()),
+    );
+}
+
@@ -1,12 +1,21 @@
-use moka::future::Cache;
+use anyhow::{Context, Result};
+use moka::sync::Cache;
 use solana_sdk::{account::Account, pubkey::Pubkey};
+use spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Account cache with TTL (Time To Live)
 /// Caches Solana account data to reduce RPC calls
+///
+/// Lookups never block, so the cache is shared by the async handlers and
+/// the batch verifier ([`AccountCache::lookup`] / [`AccountCache::store`]).
 #[derive(Clone)]
 pub struct AccountCache {
     cache: Cache<Pubkey, Account>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
 }
 
 impl AccountCache {
@@ -27,22 +36,49 @@ impl AccountCache {
             ttl_seconds
         );
 
-        Self { cache }
+        Self {
+            cache,
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        }
     }
 
     /// Get an account from cache
     pub async fn get(&self, pubkey: &Pubkey) -> Option<Account> {
-        self.cache.get(pubkey).await
+        self.lookup(pubkey)
     }
 
     /// Insert an account into cache
     pub async fn insert(&self, pubkey: Pubkey, account: Account) {
-        self.cache.insert(pubkey, account).await;
+        self.store(pubkey, account);
     }
 
     /// Invalidate a specific account
     pub async fn invalidate(&self, pubkey: &Pubkey) {
-        self.cache.invalidate(pubkey).await;
+        self.cache.invalidate(pubkey);
+    }
+
+    /// Invalidate every cached account
+    pub fn invalidate_all(&self) {
+        self.cache.invalidate_all();
+    }
+
+    /// Whether an account is cached (without counting a hit or miss)
+    pub fn contains(&self, pubkey: &Pubkey) -> bool {
+        self.cache.contains_key(pubkey)
+    }
+
+    /// Get an account from cache (synchronous)
+    pub fn lookup(&self, pubkey: &Pubkey) -> Option<Account> {
+        let account = self.cache.get(pubkey);
+        let counter = if account.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        account
+    }
+
+    /// Insert an account into cache (synchronous)
+    pub fn store(&self, pubkey: Pubkey, account: Account) {
+        self.cache.insert(pubkey, account);
     }
 
     /// Get cache statistics
@@ -50,6 +86,8 @@ impl AccountCache {
         CacheStats {
             entry_count: self.cache.entry_count(),
             weighted_size: self.cache.weighted_size(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
         }
     }
 }
@@ -59,6 +97,130 @@ impl AccountCache {
 pub struct CacheStats {
     pub entry_count: u64,
     pub weighted_size: u64,
+    /// Lookups served from the cache since startup
+    pub hits: u64,
+    /// Lookups that fell through to RPC since startup
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups served from the cache (0 before any lookup)
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// Token mint details needed during verification
+#[derive(Debug, Clone, PartialEq)]
+pub struct MintInfo {
+    pub decimals: u8,
+    /// Owning token program (SPL Token or Token-2022)
+    pub token_program: Pubkey,
+    /// Token-2022 extensions enabled on the mint (empty for SPL Token)
+    pub extensions: Vec<ExtensionType>,
+}
+
+impl MintInfo {
+    /// Parse a mint account of either token program
+    pub fn from_account(account: &Account) -> Result<Self> {
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data)
+            .context("Account is not a token mint")?;
+
+        Ok(Self {
+            decimals: mint.base.decimals,
+            token_program: account.owner,
+            extensions: mint.get_extension_types().context("Invalid mint extensions")?,
+        })
+    }
+}
+
+/// Mint cache with a long TTL
+///
+/// Mints effectively never change, so they are kept apart from the
+/// short-lived ATA entries in [`AccountCache`] rather than competing with
+/// them for capacity.
+#[derive(Clone)]
+pub struct MintCache {
+    cache: Cache<Pubkey, MintInfo>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl MintCache {
+    /// Create a new mint cache
+    ///
+    /// # Arguments
+    /// * `max_capacity` - Maximum number of mints to cache
+    /// * `ttl_seconds` - Time to live for cached mints in seconds
+    pub fn new(max_capacity: u64, ttl_seconds: u64) -> Self {
+        let cache = Cache::builder()
+            .max_capacity(max_capacity)
+            .time_to_live(Duration::from_secs(ttl_seconds))
+            .build();
+
+        tracing::info!(
+            "Created mint cache: capacity={}, ttl={}s",
+            max_capacity,
+            ttl_seconds
+        );
+
+        Self {
+            cache,
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Get a mint from cache
+    pub fn lookup(&self, mint: &Pubkey) -> Option<MintInfo> {
+        let info = self.cache.get(mint);
+        let counter = if info.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        info
+    }
+
+    /// Insert a mint into cache
+    pub fn store(&self, mint: Pubkey, info: MintInfo) {
+        self.cache.insert(mint, info);
+    }
+
+    /// Invalidate a specific mint
+    pub fn invalidate(&self, mint: &Pubkey) {
+        self.cache.invalidate(mint);
+    }
+
+    /// Invalidate every cached mint
+    pub fn invalidate_all(&self) {
+        self.cache.invalidate_all();
+    }
+
+    /// Whether a mint is cached (without counting a hit or miss)
+    pub fn contains(&self, mint: &Pubkey) -> bool {
+        self.cache.contains_key(mint)
+    }
+
+    /// Get cache statistics
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entry_count: self.cache.entry_count(),
+            weighted_size: self.cache.weighted_size(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl std::fmt::Debug for MintCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MintCache")
+            .field("entry_count", &self.cache.entry_count())
+            .finish()
+    }
 }
 
 impl std::fmt::Debug for AccountCache {
@@ -118,7 +280,7 @@ mod tests {
         
         // Just verify we can call stats without panicking
         let stats = cache.stats();
-        assert!(stats.entry_count >= 0);
+        assert_eq!(stats.entry_count, 0);
 
         // Add some entries
         let pubkey = Pubkey::new_unique();
@@ -130,5 +292,65 @@ mod tests {
         // Stats API works (exact counts may be eventually consistent)
         let _stats = cache.stats();
     }
+
+    fn mint_account(decimals: u8) -> Account {
+        use solana_sdk::program_pack::Pack;
+
+        let mint = spl_token::state::Mint {
+            decimals,
+            is_initialized: true,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(mint, &mut data).unwrap();
+
+        Account {
+            lamports: 1_461_600,
+            data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_mint_info_from_account() {
+        let info = MintInfo::from_account(&mint_account(6)).unwrap();
+        assert_eq!(info.decimals, 6);
+        assert_eq!(info.token_program, spl_token::ID);
+        assert!(info.extensions.is_empty());
+
+        assert!(MintInfo::from_account(&Account::default()).is_err());
+    }
+
+    #[test]
+    fn test_mint_cache() {
+        let cache = MintCache::new(10, 3600);
+        let mint = Pubkey::new_unique();
+        assert!(cache.lookup(&mint).is_none());
+
+        cache.store(mint, MintInfo::from_account(&mint_account(9)).unwrap());
+        assert_eq!(cache.lookup(&mint).unwrap().decimals, 9);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_ratio() {
+        let cache = AccountCache::new(100, 30);
+        assert_eq!(cache.stats().hit_ratio(), 0.0);
+
+        let pubkey = Pubkey::new_unique();
+        assert!(cache.get(&pubkey).await.is_none());
+        cache.insert(pubkey, Account::default()).await;
+        for _ in 0..3 {
+            assert!(cache.get(&pubkey).await.is_some());
+        }
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (3, 1));
+        assert_eq!(stats.hit_ratio(), 0.75);
+    }
 }
 
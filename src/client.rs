@@ -0,0 +1,294 @@
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::types::requests::{
+    BatchVerifyRequest, HeaderVerifyRequest, PaymentRequirements, SettleRequest, VerifyRequest,
+};
+use crate::types::responses::{BatchVerifyResponse, SettleResponse, SupportedResponse, VerifyResponse};
+
+/// Errors from [`FacilitatorClient`]
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// Connection failure, timeout or undecodable response
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Rejected by the rate limiter, after the last retry
+    #[error("Rate limited by facilitator")]
+    RateLimited {
+        /// From the `Retry-After` header
+        retry_after: Option<Duration>,
+    },
+
+    /// Non-success response (the `error` field of the body, if any)
+    #[error("Facilitator returned {status}: {message}")]
+    Api { status: StatusCode, message: String },
+}
+
+impl ClientError {
+    /// Whether the request may be sent again
+    fn is_retryable(&self) -> bool {
+        match self {
+            ClientError::Http(e) => e.is_connect() || e.is_timeout(),
+            ClientError::RateLimited { .. } => true,
+            ClientError::Api { status, .. } => status.is_server_error(),
+        }
+    }
+
+    /// Whether the facilitator never handled the request, so even a
+    /// settlement can safely be sent again
+    fn is_unhandled(&self) -> bool {
+        match self {
+            ClientError::Http(e) => e.is_connect(),
+            ClientError::RateLimited { .. } => true,
+            ClientError::Api { status, .. } => *status == StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+/// Client for the facilitator's payment API
+///
+/// Verification requests are retried on connection errors, timeouts, rate
+/// limiting and 5xx responses. Settlements are only retried when the
+/// facilitator can't have started them (connection refused, 429, 503), so a
+/// payment is never submitted twice by the client.
+///
+/// ```no_run
+/// # async fn example(request: x402_facilitator::types::requests::VerifyRequest) -> anyhow::Result<()> {
+/// use x402_facilitator::client::FacilitatorClient;
+///
+/// let client = FacilitatorClient::new("https://facilitator.example.com/v1").with_token("...");
+/// let response = client.verify(&request).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FacilitatorClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+impl FacilitatorClient {
+    /// Client for the facilitator at `base_url` (including any `/v1` prefix)
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: None,
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(200),
+        }
+    }
+
+    /// Send `Authorization: Bearer <token>` (for JWT-protected facilitators)
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Retries after the first attempt (default 3), with exponential backoff
+    /// starting at `backoff` (default 200ms)
+    pub fn with_retries(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Use a preconfigured reqwest client (proxies, TLS, timeouts)
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// `POST /verify`
+    pub async fn verify(&self, request: &VerifyRequest) -> Result<VerifyResponse, ClientError> {
+        self.post("/verify", request, ClientError::is_retryable).await
+    }
+
+    /// `POST /verify?mode=diagnostic`: every failed check is listed in
+    /// `failures`, and the transaction can still be settled afterwards
+    pub async fn diagnose(&self, request: &VerifyRequest) -> Result<VerifyResponse, ClientError> {
+        self.post("/verify?mode=diagnostic", request, ClientError::is_retryable).await
+    }
+
+    /// `POST /verify` with `dryRun`: a full verification that doesn't mark
+    /// the transaction as seen or produce alerting metrics, audit events or
+    /// webhooks (for synthetic checks and integration tests)
+    pub async fn verify_dry_run(&self, request: &VerifyRequest) -> Result<VerifyResponse, ClientError> {
+        let request = VerifyRequest { dry_run: true, ..request.clone() };
+        self.post("/verify", &request, ClientError::is_retryable).await
+    }
+
+    /// `POST /verify/header`, with a client's `X-PAYMENT` header value as is
+    pub async fn verify_header(
+        &self,
+        payment_header: &str,
+        requirements: &PaymentRequirements,
+    ) -> Result<VerifyResponse, ClientError> {
+        let request = HeaderVerifyRequest {
+            payment_header: Some(payment_header.to_string()),
+            payment_requirements: requirements.clone(),
+            dry_run: false,
+        };
+        self.post("/verify/header", &request, ClientError::is_retryable).await
+    }
+
+    /// `POST /verify/batch`; responses are in request order
+    pub async fn verify_batch(&self, requests: &[VerifyRequest]) -> Result<Vec<VerifyResponse>, ClientError> {
+        self.post("/verify/batch", requests, ClientError::is_retryable).await
+    }
+
+    /// `POST /verify/batch?envelope=true`: results carry their request's
+    /// index and `id`, with a summary of the batch
+    pub async fn verify_batch_envelope(
+        &self,
+        requests: &[BatchVerifyRequest],
+    ) -> Result<BatchVerifyResponse, ClientError> {
+        self.post("/verify/batch?envelope=true", requests, ClientError::is_retryable).await
+    }
+
+    /// `POST /settle`
+    pub async fn settle(&self, request: &SettleRequest) -> Result<SettleResponse, ClientError> {
+        self.post("/settle", request, ClientError::is_unhandled).await
+    }
+
+    /// `GET /supported`
+    pub async fn supported(&self) -> Result<SupportedResponse, ClientError> {
+        self.with_retries_for(ClientError::is_retryable, || self.http.get(self.url("/supported")))
+            .await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn post<B: Serialize + ?Sized, R: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+        retry_if: fn(&ClientError) -> bool,
+    ) -> Result<R, ClientError> {
+        let url = self.url(path);
+        self.with_retries_for(retry_if, || self.http.post(&url).json(body)).await
+    }
+
+    async fn with_retries_for<R: DeserializeOwned>(
+        &self,
+        retry_if: fn(&ClientError) -> bool,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<R, ClientError> {
+        let mut attempt = 0;
+        loop {
+            let error = match self.send(request()).await {
+                Ok(response) => return Ok(response),
+                Err(e) => e,
+            };
+            if attempt >= self.max_retries || !retry_if(&error) {
+                return Err(error);
+            }
+
+            // Honor Retry-After, otherwise back off exponentially
+            let backoff = match &error {
+                ClientError::RateLimited { retry_after: Some(retry_after) } => *retry_after,
+                _ => self.retry_backoff * 2u32.pow(attempt),
+            };
+            tracing::debug!("Facilitator request failed ({}), retrying in {:?}", error, backoff);
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    async fn send<R: DeserializeOwned>(&self, mut request: reqwest::RequestBuilder) -> Result<R, ClientError> {
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_secs);
+            return Err(ClientError::RateLimited { retry_after });
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let message = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|json| json.get("error").and_then(|e| e.as_str()).map(str::to_string))
+                .unwrap_or(body);
+            return Err(ClientError::Api { status, message });
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode as AxumStatus, routing::get, Json, Router};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    async fn serve(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_supported_retries_server_errors() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let router = Router::new().route(
+            "/supported",
+            get(move || {
+                let counter = counter.clone();
+                async move {
+                    if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return Err((AxumStatus::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": "boom" }))));
+                    }
+                    Ok(Json(serde_json::json!({
+                        "schemes": [{ "scheme": "exact", "networks": ["solana-devnet"] }]
+                    })))
+                }
+            }),
+        );
+
+        let client = FacilitatorClient::new(serve(router).await).with_retries(2, Duration::from_millis(1));
+        let supported = client.supported().await.unwrap();
+
+        assert_eq!(supported.schemes[0].scheme, "exact");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_api_error_message() {
+        let router = Router::new().route(
+            "/supported",
+            get(|| async { (AxumStatus::BAD_REQUEST, Json(serde_json::json!({ "error": "nope" }))) }),
+        );
+
+        let client = FacilitatorClient::new(serve(router).await);
+        match client.supported().await {
+            Err(ClientError::Api { status, message }) => {
+                assert_eq!(status, StatusCode::BAD_REQUEST);
+                assert_eq!(message, "nope");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -2,45 +2,132 @@ use anyhow::Result;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
 use std::sync::Arc;
-use crate::audit::AuditLogger;
-use crate::cache::AccountCache;
-use crate::dedup::TransactionDedup;
+use crate::audit::{AuditLogger, DEFAULT_AUDIT_QUEUE_CAPACITY};
+use crate::account_watch::AccountWatcher;
+use crate::cache::{AccountCache, MintCache};
+use crate::dedup::{DedupSnapshots, TransactionDedup};
+use crate::events::EventBus;
 use crate::metrics::AppMetrics;
+use crate::parallel::VerifyPool;
+use crate::middleware::admin_auth::AdminAuth;
+use crate::middleware::auth::JwtAuth;
+use crate::middleware::in_flight::InFlightRequests;
 use crate::middleware::rate_limit::RateLimitState;
+use crate::reload::{RuntimeSettings, SharedSettings};
+use crate::rpc_health::{RpcHealthConfig, RpcHealthMonitor};
+use crate::settlements::SettlementHistory;
+use crate::spend::SpendTracker;
+use crate::subscriptions::SubscriptionLedger;
+use crate::solana::rpc::{InstrumentedRpc, SolanaRpc};
+use crate::networks::NetworkAdapters;
+use crate::pricing::PriceOracle;
+use crate::rent_budget::AtaRentBudget;
+use crate::schemes::SchemeRegistry;
+use crate::tenants::{Tenant, Tenants};
+use crate::solana::signer::{signer_from_env, FeePayerSigner, LocalKeypairSigner};
 use crate::webhooks::WebhookConfig;
 
 #[derive(Clone)]
 pub struct Config {
     pub solana_rpc_url: String,
     pub fee_payer_private_key: String,
+    pub fee_payer_signer: Arc<dyn FeePayerSigner>,
     pub network: String,
     pub port: u16,
-    pub rpc_client: Arc<RpcClient>,
+    pub rpc_client: Arc<dyn SolanaRpc>,
+    /// Health, slot lag and latency of the RPC endpoints (`GET /admin/rpc`)
+    pub rpc_health: RpcHealthMonitor,
     pub account_cache: AccountCache,
+    pub mint_cache: MintCache,
+    /// Subscriptions keeping frequently verified accounts fresh
+    pub account_watcher: Option<AccountWatcher>,
     pub metrics: AppMetrics,
     pub rate_limiter: Option<RateLimitState>,
     pub webhook: Option<WebhookConfig>,
     pub transaction_dedup: TransactionDedup,
-    pub payment_expiry_seconds: u64,
+    /// Settings that can be reloaded at runtime (see [`crate::reload`])
+    pub settings: SharedSettings,
+    pub max_body_bytes: usize,
+    pub max_batch_body_bytes: usize,
+    pub max_batch_size: usize,
+    /// Verify payments without RPC lookups (`OFFLINE_VERIFY`); settlement is refused
+    pub offline_verify: bool,
+    /// Accept plain SPL Token `Transfer` payments besides `TransferChecked`
+    /// (`ALLOW_UNCHECKED_TRANSFER`)
+    pub allow_unchecked_transfer: bool,
+    /// Sign verify and settle responses with the fee payer key
+    /// (`SIGN_RESPONSES`, see [`crate::middleware::response_signature`])
+    pub sign_responses: bool,
+    /// Rent the fee payer may spend funding recipients' token accounts
+    /// (`FEE_PAYER_ATA_RENT_BUDGET_LAMPORTS`)
+    pub ata_rent_budget: AtaRentBudget,
+    /// USD prices of assets, for requirements priced in USD (`PRICE_ORACLE`)
+    pub price_oracle: PriceOracle,
+    /// What the fee payer spends, against its daily budget
+    /// (`FEE_PAYER_DAILY_BUDGET_LAMPORTS`)
+    pub fee_spend: SpendTracker,
+    /// Bounds batch and stream verification (`VERIFY_THREADS`)
+    pub verify_pool: VerifyPool,
+    /// Verifiers of the supported payment schemes
+    pub schemes: SchemeRegistry,
+    /// Settlement steps of each network family
+    pub networks: NetworkAdapters,
+    /// Merchants and their API keys (`TENANTS_FILE`)
+    pub tenants: Tenants,
+    /// Tenant of the request being served (see [`Config::for_tenant`])
+    pub tenant: Option<Arc<Tenant>>,
     pub audit_logger: AuditLogger,
+    pub jwt_auth: Option<JwtAuth>,
+    pub admin_auth: Option<AdminAuth>,
+    pub in_flight: InFlightRequests,
+    /// Live verify/settle/dedup events for `GET /ws`
+    pub events: EventBus,
+    /// Settlement history for `GET /settlements`
+    pub settlements: SettlementHistory,
+    /// Last settled payment of each subscription (`SUBSCRIPTIONS_PATH`)
+    pub subscriptions: SubscriptionLedger,
 }
 
-// Manual Debug implementation since RpcClient doesn't implement Debug
+// Manual Debug implementation since the RPC client doesn't implement Debug
 impl std::fmt::Debug for Config {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Config")
             .field("solana_rpc_url", &self.solana_rpc_url)
             .field("fee_payer_private_key", &"[REDACTED]")
+            .field("fee_payer_signer", &format!("{} ({})", self.fee_payer_signer.backend(), self.fee_payer_signer.pubkey()))
             .field("network", &self.network)
             .field("port", &self.port)
-            .field("rpc_client", &"Arc<RpcClient>")
+            .field("rpc_client", &"Arc<dyn SolanaRpc>")
+            .field("rpc_health", &self.rpc_health)
             .field("account_cache", &self.account_cache)
+            .field("mint_cache", &self.mint_cache)
+            .field("account_watcher", &self.account_watcher)
             .field("metrics", &"AppMetrics")
             .field("rate_limiter", &self.rate_limiter.is_some())
             .field("webhook", &self.webhook.is_some())
             .field("transaction_dedup", &"TransactionDedup")
-            .field("payment_expiry_seconds", &self.payment_expiry_seconds)
+            .field("settings", &self.settings.load())
+            .field("max_body_bytes", &self.max_body_bytes)
+            .field("max_batch_body_bytes", &self.max_batch_body_bytes)
+            .field("max_batch_size", &self.max_batch_size)
+            .field("offline_verify", &self.offline_verify)
+            .field("allow_unchecked_transfer", &self.allow_unchecked_transfer)
+            .field("sign_responses", &self.sign_responses)
+            .field("ata_rent_budget", &self.ata_rent_budget.daily_lamports())
+            .field("price_oracle", &self.price_oracle.enabled())
+            .field("fee_spend", &self.fee_spend.daily_budget_lamports())
+            .field("verify_pool", &self.verify_pool.limit())
+            .field("schemes", &self.schemes)
+            .field("networks", &self.networks)
+            .field("tenants", &self.tenants.len())
+            .field("tenant", &self.tenant.as_ref().map(|tenant| &tenant.id))
             .field("audit_logger", &"AuditLogger")
+            .field("jwt_auth", &self.jwt_auth)
+            .field("admin_auth", &self.admin_auth)
+            .field("in_flight", &self.in_flight.requests.count())
+            .field("events", &self.events.subscribers())
+            .field("settlements", &self.settlements.is_enabled())
+            .field("subscriptions", &self.subscriptions.len())
             .finish()
     }
 }
@@ -52,14 +139,18 @@ impl Config {
         let solana_rpc_url = std::env::var("SOLANA_RPC_URL")
             .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
 
-        // Create shared RPC client for connection pooling
-        let rpc_client = Arc::new(RpcClient::new_with_commitment(
-            solana_rpc_url.clone(),
-            CommitmentConfig::confirmed(),
+        // Create shared RPC client for connection pooling, with its calls
+        // counted in the metrics
+        let metrics = AppMetrics::new();
+        let rpc_client: Arc<dyn SolanaRpc> = Arc::new(InstrumentedRpc::new(
+            Arc::new(RpcClient::new_with_commitment(solana_rpc_url.clone(), CommitmentConfig::confirmed())),
+            metrics.clone(),
         ));
 
         tracing::info!("✅ Created shared RPC client for: {}", solana_rpc_url);
 
+        let rpc_health = RpcHealthMonitor::from_config(&solana_rpc_url, rpc_client.clone(), &RpcHealthConfig::from_env());
+
         // Create account cache with configurable parameters
         let cache_size = std::env::var("CACHE_SIZE")
             .ok()
@@ -73,29 +164,48 @@ impl Config {
 
         let account_cache = AccountCache::new(cache_size, cache_ttl);
 
-        // Initialize metrics
-        let metrics = AppMetrics::new();
+        // Mints rarely change: separate, long-lived cache
+        let mint_cache_size = std::env::var("MINT_CACHE_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(100);
+
+        let mint_cache_ttl = std::env::var("MINT_CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3600);
+
+        let mint_cache = MintCache::new(mint_cache_size, mint_cache_ttl);
 
-        // Initialize rate limiter if configured
+        // Keep hot accounts fresh through PubSub subscriptions (optional)
+        let account_watcher = AccountWatcher::from_env(&account_cache);
+
+        // Shared Redis state for multi-replica deployments
+        #[cfg(feature = "redis")]
+        let redis_store = crate::redis_backend::RedisStore::from_env()?;
+
+        // Reloadable settings: payment expiry, rate limit quotas, mint allowlist,
+        // webhook URL, fee payer balance threshold
+        let settings = RuntimeSettings::from_env()?;
+
+        // Initialize per-client rate limiter if configured
         let rate_limiter = if std::env::var("ENABLE_RATE_LIMIT").unwrap_or_else(|_| "true".to_string()) == "true" {
-            let per_second = std::env::var("RATE_LIMIT_PER_SECOND")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(10);
-            
-            let burst_size = std::env::var("RATE_LIMIT_BURST_SIZE")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(20);
-
-            Some(RateLimitState::new(per_second, burst_size))
+            let limiter = RateLimitState::new(settings.verify_quota, settings.settle_quota);
+
+            #[cfg(feature = "redis")]
+            let limiter = match &redis_store {
+                Some(store) => limiter.with_redis(store.clone()),
+                None => limiter,
+            };
+
+            Some(limiter)
         } else {
             tracing::info!("⚠️  Rate limiting disabled");
             None
         };
 
         // Load webhook configuration
-        let webhook = WebhookConfig::from_env();
+        let webhook = WebhookConfig::from_env()?;
         if webhook.is_some() {
             tracing::info!("🔔 Webhooks enabled");
         }
@@ -113,21 +223,97 @@ impl Config {
 
         let transaction_dedup = TransactionDedup::new(dedup_max_entries, dedup_window_seconds);
 
-        // Payment expiry time
-        let payment_expiry_seconds = std::env::var("PAYMENT_EXPIRY_SECONDS")
+        // Keep the dedup window across restarts (optional)
+        let transaction_dedup = match DedupSnapshots::from_env() {
+            Some(snapshots) => transaction_dedup.with_snapshots(snapshots),
+            None => transaction_dedup,
+        };
+
+        #[cfg(feature = "redis")]
+        let transaction_dedup = match &redis_store {
+            Some(store) => transaction_dedup.with_redis(store.clone()),
+            None => transaction_dedup,
+        };
+
+        tracing::info!("⏰ Payment expiry set to {} seconds", settings.payment_expiry_seconds);
+        if let Some(mints) = &settings.allowed_mints {
+            tracing::info!("🪙 Accepting payments in {} allowlisted mints", mints.len());
+        }
+
+        // Request size limits
+        let max_body_bytes = std::env::var("MAX_BODY_BYTES")
             .ok()
             .and_then(|s| s.parse().ok())
-            .unwrap_or(600); // 10 minutes default
-        
-        tracing::info!("⏰ Payment expiry set to {} seconds", payment_expiry_seconds);
+            .unwrap_or(64 * 1024); // 64 KiB default
+
+        let max_batch_body_bytes = std::env::var("MAX_BATCH_BODY_BYTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(2 * 1024 * 1024); // 2 MiB default
+
+        let max_batch_size = std::env::var("MAX_BATCH_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(100);
+
+        // Structural/cryptographic checks only, e.g. for air-gapped pre-validation
+        let offline_verify = std::env::var("OFFLINE_VERIFY").unwrap_or_default() == "true";
+        if offline_verify {
+            tracing::warn!("📴 Offline verification: account checks are skipped and /settle is disabled");
+        }
+
+        // Some wallets build Transfer, which names no mint or decimals
+        let allow_unchecked_transfer = std::env::var("ALLOW_UNCHECKED_TRANSFER").unwrap_or_default() == "true";
+
+        // Responses resource servers can prove came from this facilitator
+        let sign_responses = std::env::var("SIGN_RESPONSES").unwrap_or_default() == "true";
+        if sign_responses {
+            tracing::info!("✍️  Signing verify and settle responses (X-Facilitator-Signature)");
+        }
+
+        // Payments of batches and streams verified at once (default: one per core)
+        let verify_limit = std::env::var("VERIFY_THREADS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&limit: &usize| limit > 0)
+            .unwrap_or_else(VerifyPool::default_limit);
+        tracing::info!("🧵 Batch payments verified at once: {}", verify_limit);
 
-        // Initialize audit logger
-        let audit_logger = AuditLogger::new();
+        // Initialize audit logger, persisting events if a database is configured
+        let audit_queue_capacity = std::env::var("AUDIT_QUEUE_CAPACITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_AUDIT_QUEUE_CAPACITY);
+
+        #[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+        let audit_logger = match crate::audit_store::AuditStore::from_env()? {
+            Some(store) => AuditLogger::with_store(store, audit_queue_capacity),
+            None => AuditLogger::with_capacity(audit_queue_capacity),
+        };
+
+        #[cfg(not(any(feature = "audit-sqlite", feature = "audit-postgres")))]
+        let audit_logger = AuditLogger::with_capacity(audit_queue_capacity);
+
+        // Fee payer signer (local keypair or remote KMS)
+        let fee_payer_signer = signer_from_env(&audit_logger)?;
+
+        // Optional JWT bearer authentication for payment endpoints
+        let jwt_auth = JwtAuth::from_env();
+        if let Some(auth) = &jwt_auth {
+            tracing::info!("🔐 JWT authentication enabled: {:?}", auth);
+        }
+
+        // Access control for /admin/*
+        let admin_auth = AdminAuth::from_env()?;
+        match &admin_auth {
+            Some(auth) => tracing::info!("🔐 Admin endpoints protected: {:?}", auth),
+            None => tracing::warn!("⚠️  Admin endpoints are unauthenticated; set ADMIN_API_KEY and/or ADMIN_ALLOWED_IPS"),
+        }
 
         let config = Config {
             solana_rpc_url,
-            fee_payer_private_key: std::env::var("FEE_PAYER_PRIVATE_KEY")
-                .expect("FEE_PAYER_PRIVATE_KEY must be set"),
+            fee_payer_private_key: std::env::var("FEE_PAYER_PRIVATE_KEY").unwrap_or_default(),
+            fee_payer_signer,
             network: std::env::var("NETWORK")
                 .unwrap_or_else(|_| "solana-devnet".to_string()),
             port: std::env::var("PORT")
@@ -135,13 +321,36 @@ impl Config {
                 .parse()
                 .expect("PORT must be a valid number"),
             rpc_client,
+            rpc_health,
             account_cache,
+            mint_cache,
+            account_watcher,
             metrics,
             rate_limiter,
             webhook,
             transaction_dedup,
-            payment_expiry_seconds,
+            settings: SharedSettings::new(settings),
+            max_body_bytes,
+            max_batch_body_bytes,
+            max_batch_size,
+            offline_verify,
+            allow_unchecked_transfer,
+            sign_responses,
+            ata_rent_budget: AtaRentBudget::from_env(),
+            price_oracle: PriceOracle::from_env()?,
+            fee_spend: SpendTracker::from_env(),
+            verify_pool: VerifyPool::new(verify_limit),
+            schemes: SchemeRegistry::default(),
+            networks: NetworkAdapters::default(),
+            tenants: Tenants::from_env()?,
+            tenant: None,
             audit_logger,
+            jwt_auth,
+            admin_auth,
+            in_flight: InFlightRequests::new(),
+            events: EventBus::default(),
+            settlements: SettlementHistory::from_env(),
+            subscriptions: SubscriptionLedger::from_env()?,
         };
 
         // Validate configuration
@@ -150,6 +359,85 @@ impl Config {
         Ok(config)
     }
 
+    /// Configuration for embedding the facilitator as a library
+    ///
+    /// Uses the same defaults as [`Config::from_env`] with the optional
+    /// integrations (rate limiting, webhooks, auth, audit persistence,
+    /// settlement history, account watching) turned off.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn new(solana_rpc_url: &str, network: &str, fee_payer_private_key: &str) -> Result<Self> {
+        let fee_payer_signer: Arc<dyn FeePayerSigner> =
+            Arc::new(LocalKeypairSigner::from_base58(fee_payer_private_key)?);
+        let metrics = AppMetrics::new();
+        let rpc_client: Arc<dyn SolanaRpc> = Arc::new(InstrumentedRpc::new(
+            Arc::new(RpcClient::new_with_commitment(solana_rpc_url.to_string(), CommitmentConfig::confirmed())),
+            metrics.clone(),
+        ));
+
+        let config = Config {
+            solana_rpc_url: solana_rpc_url.to_string(),
+            fee_payer_private_key: fee_payer_private_key.to_string(),
+            fee_payer_signer,
+            network: network.to_string(),
+            port: 3000,
+            rpc_health: RpcHealthMonitor::new(solana_rpc_url, rpc_client.clone(), std::time::Duration::from_secs(15)),
+            rpc_client,
+            account_cache: AccountCache::new(1000, 30),
+            mint_cache: MintCache::new(100, 3600),
+            account_watcher: None,
+            metrics,
+            rate_limiter: None,
+            webhook: None,
+            transaction_dedup: TransactionDedup::new(10000, 300),
+            settings: SharedSettings::new(RuntimeSettings::default()),
+            max_body_bytes: 64 * 1024,
+            max_batch_body_bytes: 2 * 1024 * 1024,
+            max_batch_size: 100,
+            offline_verify: false,
+            allow_unchecked_transfer: false,
+            sign_responses: false,
+            ata_rent_budget: AtaRentBudget::default(),
+            price_oracle: PriceOracle::default(),
+            fee_spend: SpendTracker::default(),
+            verify_pool: VerifyPool::default(),
+            schemes: SchemeRegistry::default(),
+            networks: NetworkAdapters::default(),
+            tenants: Tenants::default(),
+            tenant: None,
+            audit_logger: AuditLogger::new(),
+            jwt_auth: None,
+            admin_auth: None,
+            in_flight: InFlightRequests::new(),
+            events: EventBus::default(),
+            settlements: SettlementHistory::disabled(),
+            subscriptions: SubscriptionLedger::new(),
+        };
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// This configuration as seen by a request of `tenant`: settled with
+    /// the tenant's fee payer, if it has one, and audited under its id
+    pub fn for_tenant(&self, tenant: Option<Arc<Tenant>>) -> Config {
+        let Some(tenant) = tenant else {
+            return self.clone();
+        };
+        Config {
+            fee_payer_signer: tenant.fee_payer.clone().unwrap_or_else(|| self.fee_payer_signer.clone()),
+            audit_logger: self.audit_logger.for_tenant(Some(&tenant.id)),
+            tenant: Some(tenant),
+            ..self.clone()
+        }
+    }
+
+    /// Id of the request's tenant, if any
+    pub fn tenant_id(&self) -> Option<&str> {
+        self.tenant.as_ref().map(|tenant| tenant.id.as_str())
+    }
+
     /// Validate configuration
     pub fn validate(&self) -> Result<()> {
         tracing::info!("🔍 Validating configuration...");
@@ -160,9 +448,10 @@ impl Config {
             anyhow::bail!("Invalid RPC URL format: must start with http:// or https://");
         }
 
-        // Validate private key is not empty
-        if self.fee_payer_private_key.is_empty() || 
-           self.fee_payer_private_key == "your_base58_private_key_here" {
+        // Validate private key is not empty (remote signers never see the key)
+        if self.fee_payer_signer.backend() == "local" &&
+           (self.fee_payer_private_key.is_empty() ||
+            self.fee_payer_private_key == "your_base58_private_key_here") {
             anyhow::bail!("Fee payer private key is not configured");
         }
 
@@ -172,8 +461,13 @@ impl Config {
             anyhow::bail!("Invalid network: {} (must be one of: {:?})", self.network, valid_networks);
         }
 
+        // The history lists payers and amounts to whoever can reach it
+        if self.settlements.is_enabled() && self.jwt_auth.is_none() && self.tenants.is_empty() {
+            tracing::warn!("⚠️  Settlement history is public: enable JWT auth or tenants to restrict /settlements and /receipt");
+        }
+
         // Validate port range
-        if self.port < 1024 || self.port > 65535 {
+        if self.port < 1024 {
             tracing::warn!("⚠️  Port {} is outside recommended range (1024-65535)", self.port);
         }
 
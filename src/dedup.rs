@@ -1,16 +1,102 @@
-use moka::sync::Cache;
-use std::time::Duration;
+use anyhow::{Context, Result};
+use moka::{sync::Cache, Expiry};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use sha2::{Sha256, Digest};
+#[cfg(feature = "redis")]
+use crate::redis_backend::RedisDedup;
 
 /// Transaction deduplication cache to prevent replay attacks
 /// 
 /// This cache stores transaction signatures/hashes with a TTL to ensure
 /// that the same transaction cannot be verified or settled multiple times
 /// within a configurable time window.
+///
+/// With the `redis` feature and a Redis backend attached, seen transactions
+/// are shared across replicas; the local cache is kept in sync and used as a
+/// fallback if Redis is unavailable.
+///
+/// With snapshots configured ([`DedupSnapshots`]), the local cache is
+/// periodically written to disk and restored on startup, so the window
+/// survives restarts.
 #[derive(Clone, Debug)]
 pub struct TransactionDedup {
-    pub(crate) cache: Cache<String, ()>,
+    /// Transaction hash -> when it was first seen (Unix milliseconds)
+    pub(crate) cache: Cache<String, u64>,
     window_seconds: u64,
+    snapshots: Option<Arc<DedupSnapshots>>,
+    #[cfg(feature = "redis")]
+    redis: Option<Arc<RedisDedup>>,
+}
+
+/// Expires entries `window` after they were first seen, including entries
+/// restored from a snapshot
+struct SeenWindow {
+    window: Duration,
+}
+
+impl SeenWindow {
+    fn remaining(&self, seen_at: u64) -> Option<Duration> {
+        let age = Duration::from_millis(now_millis().saturating_sub(seen_at));
+        Some(self.window.saturating_sub(age))
+    }
+}
+
+impl Expiry<String, u64> for SeenWindow {
+    fn expire_after_create(&self, _key: &String, seen_at: &u64, _created_at: Instant) -> Option<Duration> {
+        self.remaining(*seen_at)
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        seen_at: &u64,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        self.remaining(*seen_at)
+    }
+}
+
+fn now_millis() -> u64 {
+    chrono::Utc::now().timestamp_millis().max(0) as u64
+}
+
+/// Periodic dedup snapshots on disk
+///
+/// Configuration:
+/// * `DEDUP_SNAPSHOT_PATH` - Snapshot file (enables snapshots)
+/// * `DEDUP_SNAPSHOT_INTERVAL_SECONDS` - Time between snapshots (default: 30)
+#[derive(Debug, Clone)]
+pub struct DedupSnapshots {
+    pub path: PathBuf,
+    pub interval: Duration,
+}
+
+impl DedupSnapshots {
+    /// Returns `None` when `DEDUP_SNAPSHOT_PATH` is not set
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("DEDUP_SNAPSHOT_PATH").ok().filter(|path| !path.is_empty())?;
+        let interval = std::env::var("DEDUP_SNAPSHOT_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(30);
+
+        Some(Self {
+            path: PathBuf::from(path),
+            interval: Duration::from_secs(interval.max(1)),
+        })
+    }
+}
+
+/// On-disk snapshot format
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    /// Transaction hash -> first seen (Unix milliseconds)
+    entries: HashMap<String, u64>,
 }
 
 impl TransactionDedup {
@@ -22,7 +108,7 @@ impl TransactionDedup {
     pub fn new(max_entries: u64, window_seconds: u64) -> Self {
         let cache = Cache::builder()
             .max_capacity(max_entries)
-            .time_to_live(Duration::from_secs(window_seconds))
+            .expire_after(SeenWindow { window: Duration::from_secs(window_seconds) })
             .build();
 
         tracing::info!(
@@ -34,7 +120,37 @@ impl TransactionDedup {
         Self {
             cache,
             window_seconds,
+            snapshots: None,
+            #[cfg(feature = "redis")]
+            redis: None,
+        }
+    }
+
+    /// Persist the cache to disk, restoring the last snapshot now
+    ///
+    /// Entries whose window has passed are dropped on restore; the rest
+    /// expire when they would have without the restart. A missing or
+    /// unreadable snapshot starts an empty cache.
+    pub fn with_snapshots(mut self, snapshots: DedupSnapshots) -> Self {
+        match self.restore(&snapshots.path) {
+            Ok(0) => {}
+            Ok(restored) => tracing::info!(
+                "🔐 Restored {} dedup entries from {}",
+                restored,
+                snapshots.path.display()
+            ),
+            Err(e) => tracing::warn!("⚠️  Could not restore dedup snapshot: {:#}", e),
         }
+
+        self.snapshots = Some(Arc::new(snapshots));
+        self
+    }
+
+    /// Share dedup state across replicas through Redis
+    #[cfg(feature = "redis")]
+    pub fn with_redis(mut self, store: Arc<crate::redis_backend::RedisStore>) -> Self {
+        self.redis = Some(Arc::new(RedisDedup::new(store, self.window_seconds)));
+        self
     }
 
     /// Check if a transaction has already been seen
@@ -42,6 +158,15 @@ impl TransactionDedup {
     /// Returns true if the transaction is a duplicate (already seen within the time window)
     pub fn is_duplicate(&self, transaction_data: &str) -> bool {
         let hash = self.hash_transaction(transaction_data);
+
+        #[cfg(feature = "redis")]
+        if let Some(redis) = &self.redis {
+            match redis.is_duplicate(&hash) {
+                Ok(duplicate) => return duplicate,
+                Err(e) => tracing::warn!("⚠️  Redis dedup unavailable, using local cache: {}", e),
+            }
+        }
+
         self.cache.get(&hash).is_some()
     }
 
@@ -51,7 +176,15 @@ impl TransactionDedup {
     /// processed again within the deduplication window
     pub fn mark_seen(&self, transaction_data: &str) {
         let hash = self.hash_transaction(transaction_data);
-        self.cache.insert(hash, ());
+
+        #[cfg(feature = "redis")]
+        if let Some(redis) = &self.redis {
+            if let Err(e) = redis.mark_seen(&hash) {
+                tracing::warn!("⚠️  Redis dedup unavailable, using local cache: {}", e);
+            }
+        }
+
+        self.cache.insert(hash, now_millis());
     }
 
     /// Check and mark a transaction in one atomic operation
@@ -60,22 +193,37 @@ impl TransactionDedup {
     /// If new, automatically marks it as seen.
     pub fn check_and_mark(&self, transaction_data: &str) -> bool {
         let hash = self.hash_transaction(transaction_data);
+
+        // Shared state wins when available; mirror it locally for stats/fallback
+        #[cfg(feature = "redis")]
+        if let Some(redis) = &self.redis {
+            match redis.check_and_mark(&hash) {
+                Ok(duplicate) => {
+                    if duplicate {
+                        tracing::warn!("🚨 Duplicate transaction detected: {}", hash);
+                    }
+                    self.cache.insert(hash, now_millis());
+                    return duplicate;
+                }
+                Err(e) => tracing::warn!("⚠️  Redis dedup unavailable, using local cache: {}", e),
+            }
+        }
         
         // Check if it exists
         if self.cache.get(&hash).is_some() {
-            tracing::warn!("🚨 Duplicate transaction detected: {}", &hash[..16]);
+            tracing::warn!("🚨 Duplicate transaction detected: {}", hash);
             return true;
         }
 
         // Not a duplicate, mark as seen
-        self.cache.insert(hash, ());
+        self.cache.insert(hash, now_millis());
         false
     }
 
     /// Hash a transaction to create a unique identifier
     /// 
     /// Uses SHA256 to create a deterministic hash of the transaction data
-    fn hash_transaction(&self, transaction_data: &str) -> String {
+    pub fn hash_transaction(&self, transaction_data: &str) -> String {
         let mut hasher = Sha256::new();
         hasher.update(transaction_data.as_bytes());
         let result = hasher.finalize();
@@ -84,24 +232,150 @@ impl TransactionDedup {
 
     /// Get cache statistics for monitoring
     pub fn stats(&self) -> DedupStats {
+        // Flush pending maintenance so the entry count reflects recent inserts
+        self.cache.run_pending_tasks();
+
         DedupStats {
             entry_count: self.cache.entry_count(),
             window_seconds: self.window_seconds,
+            backend: self.backend(),
         }
     }
 
-    /// Clear the cache (useful for testing)
-    #[cfg(test)]
+    /// Storage backend in use ("memory" or "redis")
+    pub fn backend(&self) -> &'static str {
+        #[cfg(feature = "redis")]
+        if self.redis.is_some() {
+            return "redis";
+        }
+
+        "memory"
+    }
+
+    /// Write the cache to the configured snapshot file (no-op without one)
+    pub fn write_snapshot(&self) -> Result<()> {
+        match &self.snapshots {
+            Some(snapshots) => self.save(&snapshots.path).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Write snapshots every interval in the background (no-op without
+    /// snapshots configured)
+    pub fn spawn_snapshotter(&self) {
+        let Some(snapshots) = self.snapshots.clone() else {
+            return;
+        };
+
+        tracing::info!(
+            "💾 Snapshotting dedup cache to {} every {}s",
+            snapshots.path.display(),
+            snapshots.interval.as_secs()
+        );
+
+        let dedup = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(snapshots.interval);
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+
+                let dedup = dedup.clone();
+                let path = snapshots.path.clone();
+                match tokio::task::spawn_blocking(move || dedup.save(&path)).await {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => tracing::error!("Failed to snapshot dedup cache: {:#}", e),
+                    Err(e) => tracing::error!("Dedup snapshot task panicked: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Write live entries to `path` (atomically, via a temporary file)
+    fn save(&self, path: &Path) -> Result<usize> {
+        let snapshot = Snapshot {
+            entries: self.cache.iter().map(|(hash, seen_at)| ((*hash).clone(), seen_at)).collect(),
+        };
+
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&snapshot)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+
+        Ok(snapshot.entries.len())
+    }
+
+    /// Load unexpired entries from `path` (0 if it doesn't exist)
+    fn restore(&self, path: &Path) -> Result<usize> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let snapshot: Snapshot = serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid dedup snapshot {}", path.display()))?;
+
+        let cutoff = now_millis().saturating_sub(self.window_seconds * 1000);
+        let mut restored = 0;
+        for (hash, seen_at) in snapshot.entries {
+            if seen_at > cutoff {
+                self.cache.insert(hash, seen_at);
+                restored += 1;
+            }
+        }
+
+        Ok(restored)
+    }
+
+    /// Forget a transaction hash (as logged on duplicates), e.g. to release
+    /// a transaction marked seen by a failed settle
+    ///
+    /// Returns true if the hash was recorded locally or in Redis.
+    pub fn remove(&self, hash: &str) -> bool {
+        let removed = self.cache.remove(hash).is_some();
+
+        #[cfg(feature = "redis")]
+        let removed = match &self.redis {
+            Some(redis) => match redis.remove(hash) {
+                Ok(shared) => removed | shared,
+                Err(e) => {
+                    tracing::warn!("⚠️  Redis dedup unavailable, only removed locally: {}", e);
+                    removed
+                }
+            },
+            None => removed,
+        };
+
+        removed
+    }
+
+    /// Forget a transaction marked by [`check_and_mark`](Self::check_and_mark),
+    /// e.g. because it failed verification
+    pub fn unmark(&self, transaction_data: &str) -> bool {
+        self.remove(&self.hash_transaction(transaction_data))
+    }
+
+    /// Forget every transaction, locally and in Redis
     pub fn clear(&self) {
         self.cache.invalidate_all();
+
+        #[cfg(feature = "redis")]
+        if let Some(redis) = &self.redis {
+            if let Err(e) = redis.clear() {
+                tracing::warn!("⚠️  Redis dedup unavailable, only cleared locally: {}", e);
+            }
+        }
     }
 }
 
 /// Statistics about the deduplication cache
 #[derive(Debug, Clone)]
 pub struct DedupStats {
+    /// Entries in the local cache (a subset of the shared set when using Redis)
     pub entry_count: u64,
     pub window_seconds: u64,
+    pub backend: &'static str,
 }
 
 #[cfg(test)]
@@ -199,6 +473,66 @@ mod tests {
         assert!(!dedup.is_duplicate("tx1"));
     }
 
+    #[test]
+    fn test_dedup_snapshot_restore() {
+        let path = std::env::temp_dir().join(format!("dedup-{}.json", uuid::Uuid::new_v4()));
+        let snapshots = DedupSnapshots { path: path.clone(), interval: Duration::from_secs(30) };
+
+        let dedup = TransactionDedup::new(1000, 300).with_snapshots(snapshots.clone());
+        dedup.mark_seen("tx1");
+        dedup.write_snapshot().unwrap();
+
+        let restarted = TransactionDedup::new(1000, 300).with_snapshots(snapshots);
+        assert!(restarted.is_duplicate("tx1"));
+        assert!(!restarted.is_duplicate("tx2"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_dedup_restored_entries_keep_their_expiry() {
+        let path = std::env::temp_dir().join(format!("dedup-{}.json", uuid::Uuid::new_v4()));
+        let hash = TransactionDedup::new(10, 2).hash_transaction("tx");
+        let stale = TransactionDedup::new(10, 2).hash_transaction("stale");
+        let entries = HashMap::from([(hash, now_millis() - 1_500), (stale, now_millis() - 2_500)]);
+        std::fs::write(&path, serde_json::to_vec(&Snapshot { entries }).unwrap()).unwrap();
+
+        let dedup = TransactionDedup::new(1000, 2)
+            .with_snapshots(DedupSnapshots { path: path.clone(), interval: Duration::from_secs(30) });
+        assert!(dedup.is_duplicate("tx"));
+        // Its window had already passed
+        assert!(!dedup.is_duplicate("stale"));
+
+        // Half a second of the window was left, not a fresh 2 seconds
+        std::thread::sleep(Duration::from_secs(1));
+        assert!(!dedup.is_duplicate("tx"));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_dedup_remove() {
+        let dedup = TransactionDedup::new(1000, 300);
+
+        dedup.mark_seen("tx1");
+        dedup.mark_seen("tx2");
+
+        assert!(dedup.remove(&dedup.hash_transaction("tx1")));
+        assert!(!dedup.remove(&dedup.hash_transaction("tx1")));
+        assert!(!dedup.is_duplicate("tx1"));
+        assert!(dedup.is_duplicate("tx2"));
+    }
+
+    #[test]
+    fn test_dedup_unmark() {
+        let dedup = TransactionDedup::new(1000, 300);
+
+        assert!(!dedup.check_and_mark("tx"));
+        assert!(dedup.unmark("tx"));
+        assert!(!dedup.check_and_mark("tx"));
+        assert!(dedup.check_and_mark("tx"));
+    }
+
     #[test]
     fn test_dedup_expiry() {
         // Create dedup with 1 second window
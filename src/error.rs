@@ -11,6 +11,21 @@ pub enum AppError {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Not implemented: {0}")]
+    NotImplemented(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     #[error("Internal server error")]
     Internal(#[from] anyhow::Error),
 }
@@ -19,6 +34,11 @@ impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             AppError::Config(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg),
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::NotImplemented(msg) => (StatusCode::NOT_IMPLEMENTED, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
             AppError::Internal(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal server error".to_string(),
@@ -38,6 +58,28 @@ pub enum VerificationError {
     #[error("invalid_network")]
     InvalidNetwork,
 
+    #[error("pay_to_not_allowed")]
+    PayToNotAllowed,
+
+    #[error("fee_payer_not_allowed")]
+    FeePayerNotAllowed,
+
+    #[error("payment_expired")]
+    PaymentExpired,
+
+    #[error("payment_timestamp_in_future")]
+    TimestampInFuture,
+
+    /// `extra` lacks or has an invalid field the scheme requires (with how to fix it)
+    #[error("invalid_extra")]
+    InvalidExtra(String),
+
+    #[error("invalid_exact_svm_payload_transaction_message_header")]
+    InvalidMessageHeader,
+
+    #[error("invalid_exact_svm_payload_transaction_unexpected_signers")]
+    UnexpectedSigners,
+
     #[error("invalid_exact_svm_payload_transaction_instructions_length")]
     InvalidInstructionCount,
 
@@ -56,6 +98,9 @@ pub enum VerificationError {
     #[error("invalid_exact_svm_payload_transaction_fee_payer_transferring_funds")]
     FeePayerTransferringFunds,
 
+    #[error("invalid_exact_svm_payload_transaction_fee_payer_not_first_signer")]
+    FeePayerNotFirstSigner,
+
     #[error("invalid_exact_svm_payload_transaction_amount_mismatch")]
     AmountMismatch,
 
@@ -68,9 +113,18 @@ pub enum VerificationError {
     #[error("invalid_exact_svm_payload_transaction_create_ata_instruction_incorrect_asset")]
     CreateATAIncorrectAsset,
 
+    #[error("invalid_exact_svm_payload_transaction_create_ata_instruction_invalid_funder")]
+    CreateATAInvalidFunder,
+
+    #[error("invalid_exact_svm_payload_transaction_create_ata_rent_budget_exhausted")]
+    AtaRentBudgetExhausted,
+
     #[error("invalid_exact_svm_payload_transaction_transfer_to_incorrect_ata")]
     TransferToIncorrectATA,
 
+    #[error("invalid_exact_svm_payload_transaction_transfer_authority_not_signer")]
+    TransferAuthorityNotSigner,
+
     #[error("invalid_exact_svm_payload_transaction_sender_ata_not_found")]
     SenderATANotFound,
 
@@ -80,6 +134,33 @@ pub enum VerificationError {
     #[error("invalid_exact_svm_payload_transaction_not_a_transfer_instruction")]
     NotATransferInstruction,
 
+    #[error("invalid_exact_svm_payload_transaction_mint_not_found")]
+    MintNotFound,
+
+    #[error("invalid_exact_svm_payload_transaction_decimals_mismatch")]
+    DecimalsMismatch,
+
+    #[error("invalid_exact_svm_payload_transaction_source_mint_mismatch")]
+    SourceMintMismatch,
+
+    /// USD-priced requirements for an asset without a fresh price
+    #[error("usd_price_unavailable")]
+    UsdPriceUnavailable,
+
+    #[error("usd_amount_out_of_bounds")]
+    UsdAmountOutOfBounds,
+
+    #[error("subscription_subscriber_mismatch")]
+    SubscriberMismatch,
+
+    /// Paid outside the current billing window (or before the first one)
+    #[error("subscription_outside_billing_window")]
+    OutsideBillingWindow,
+
+    /// The billing window has a payment settled or being settled
+    #[error("subscription_already_paid")]
+    SubscriptionAlreadyPaid,
+
     #[error("unexpected_verify_error")]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -89,22 +170,86 @@ impl VerificationError {
         match self {
             Self::UnsupportedScheme => "unsupported_scheme",
             Self::InvalidNetwork => "invalid_network",
+            Self::PayToNotAllowed => "pay_to_not_allowed",
+            Self::FeePayerNotAllowed => "fee_payer_not_allowed",
+            Self::PaymentExpired => "payment_expired",
+            Self::TimestampInFuture => "payment_timestamp_in_future",
+            Self::InvalidExtra(_) => "invalid_extra",
+            Self::InvalidMessageHeader => "invalid_exact_svm_payload_transaction_message_header",
+            Self::UnexpectedSigners => "invalid_exact_svm_payload_transaction_unexpected_signers",
             Self::InvalidInstructionCount => "invalid_exact_svm_payload_transaction_instructions_length",
             Self::InvalidComputeLimitInstruction => "invalid_exact_svm_payload_transaction_instructions_compute_limit_instruction",
             Self::InvalidComputePriceInstruction => "invalid_exact_svm_payload_transaction_instructions_compute_price_instruction",
             Self::ComputePriceTooHigh => "invalid_exact_svm_payload_transaction_instructions_compute_price_instruction_too_high",
             Self::FeePayerInInstructionAccounts => "invalid_exact_svm_payload_transaction_fee_payer_included_in_instruction_accounts",
             Self::FeePayerTransferringFunds => "invalid_exact_svm_payload_transaction_fee_payer_transferring_funds",
+            Self::FeePayerNotFirstSigner => "invalid_exact_svm_payload_transaction_fee_payer_not_first_signer",
             Self::AmountMismatch => "invalid_exact_svm_payload_transaction_amount_mismatch",
             Self::InvalidCreateATAInstruction => "invalid_exact_svm_payload_transaction_create_ata_instruction",
             Self::CreateATAIncorrectPayee => "invalid_exact_svm_payload_transaction_create_ata_instruction_incorrect_payee",
             Self::CreateATAIncorrectAsset => "invalid_exact_svm_payload_transaction_create_ata_instruction_incorrect_asset",
+            Self::CreateATAInvalidFunder => "invalid_exact_svm_payload_transaction_create_ata_instruction_invalid_funder",
+            Self::AtaRentBudgetExhausted => "invalid_exact_svm_payload_transaction_create_ata_rent_budget_exhausted",
             Self::TransferToIncorrectATA => "invalid_exact_svm_payload_transaction_transfer_to_incorrect_ata",
+            Self::TransferAuthorityNotSigner => "invalid_exact_svm_payload_transaction_transfer_authority_not_signer",
             Self::SenderATANotFound => "invalid_exact_svm_payload_transaction_sender_ata_not_found",
             Self::ReceiverATANotFound => "invalid_exact_svm_payload_transaction_receiver_ata_not_found",
             Self::NotATransferInstruction => "invalid_exact_svm_payload_transaction_not_a_transfer_instruction",
+            Self::MintNotFound => "invalid_exact_svm_payload_transaction_mint_not_found",
+            Self::DecimalsMismatch => "invalid_exact_svm_payload_transaction_decimals_mismatch",
+            Self::SourceMintMismatch => "invalid_exact_svm_payload_transaction_source_mint_mismatch",
+            Self::UsdPriceUnavailable => "usd_price_unavailable",
+            Self::UsdAmountOutOfBounds => "usd_amount_out_of_bounds",
+            Self::SubscriberMismatch => "subscription_subscriber_mismatch",
+            Self::OutsideBillingWindow => "subscription_outside_billing_window",
+            Self::SubscriptionAlreadyPaid => "subscription_already_paid",
             Self::UnexpectedError(_) => "unexpected_verify_error",
         }
     }
+    /// How a client can fix the payment, for diagnostic verification
+    pub fn hint(&self) -> String {
+        let hint = match self {
+            Self::UnsupportedScheme => "Use the \"exact\" scheme in both the payload and the requirements",
+            Self::InvalidNetwork => "Pay on the requirements' network (solana or solana-devnet)",
+            Self::PayToNotAllowed => "Pay one of the addresses registered for your API key",
+            Self::FeePayerNotAllowed => "Use the fee payer assigned to your API key as extra.feePayer",
+            Self::PaymentExpired => "Build and sign a new payment; this one is older than maxTimeoutSeconds",
+            Self::TimestampInFuture => "Set timestamp to the current Unix time (check the client's clock)",
+            Self::InvalidExtra(hint) => return hint.clone(),
+            Self::InvalidMessageHeader => {
+                "Send a well-formed message: a writable fee payer and one signature per required signer"
+            }
+            Self::UnexpectedSigners => "Require exactly two signatures: the facilitator's fee payer and the paying wallet",
+            Self::InvalidInstructionCount => {
+                "Send 3 instructions (compute limit, compute price, transfer), or 4 with a CreateATA before the transfer"
+            }
+            Self::InvalidComputeLimitInstruction => "Make the first instruction a ComputeBudget SetComputeUnitLimit",
+            Self::InvalidComputePriceInstruction => "Make the second instruction a ComputeBudget SetComputeUnitPrice",
+            Self::ComputePriceTooHigh => "Lower the compute unit price to at most 5 lamports per compute unit",
+            Self::FeePayerInInstructionAccounts => "Don't reference the facilitator's fee payer in any instruction",
+            Self::FeePayerTransferringFunds => "Sign the transfer with the paying wallet, not the fee payer",
+            Self::FeePayerNotFirstSigner => "Make extra.feePayer the transaction's fee payer (its first account key)",
+            Self::AmountMismatch => "Transfer exactly maxAmountRequired of the asset",
+            Self::InvalidCreateATAInstruction => "Create the recipient's account with the associated token program",
+            Self::CreateATAIncorrectPayee => "Create the associated token account of payTo",
+            Self::CreateATAIncorrectAsset => "Create the associated token account for the requirements' asset",
+            Self::CreateATAInvalidFunder => "Fund the new token account from the paying wallet, as a writable signer",
+            Self::AtaRentBudgetExhausted => "Fund the recipient's token account from the paying wallet",
+            Self::TransferToIncorrectATA => "Transfer to the associated token account of payTo for the asset",
+            Self::TransferAuthorityNotSigner => "Make the paying wallet, the transfer's authority, sign the transaction",
+            Self::SenderATANotFound => "Fund the paying wallet's token account for the asset first",
+            Self::ReceiverATANotFound => "Add a CreateATA instruction for the recipient's token account",
+            Self::NotATransferInstruction => "Make the last instruction a TransferChecked of the token program",
+            Self::MintNotFound => "Use an asset that is an existing token mint on this network",
+            Self::DecimalsMismatch => "Use the mint's decimals in the TransferChecked instruction",
+            Self::SourceMintMismatch => "Transfer from a token account of the requirements' asset",
+            Self::UsdPriceUnavailable => "Price the payment in the asset, or in USD only for assets the facilitator has a price feed for",
+            Self::UsdAmountOutOfBounds => "Set maxAmountRequired to extra.usdAmount's worth of the asset at its current price",
+            Self::SubscriberMismatch => "Pay from the wallet named in extra.subscriber",
+            Self::OutsideBillingWindow => "Build and sign a new payment within the current billing period",
+            Self::SubscriptionAlreadyPaid => "Wait for the next billing period; this one is already paid",
+            Self::UnexpectedError(e) => return e.to_string(),
+        };
+        hint.to_string()
+    }
 }
-
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+/// Initialize Sentry error reporting from environment
+///
+/// Returns `None` (reporting disabled) when `SENTRY_DSN` is not set. The
+/// returned guard flushes queued events when dropped, so keep it alive for
+/// the lifetime of the process.
+///
+/// Once initialized, panics and `tracing::error!` events are reported (with
+/// warnings and info logs attached as breadcrumbs), tagged with the request
+/// ID when they happen while handling a request.
+pub fn init_from_env() -> Option<sentry::ClientInitGuard> {
+    let dsn = std::env::var("SENTRY_DSN").ok().filter(|dsn| !dsn.is_empty())?;
+
+    let sample_rate = std::env::var("SENTRY_SAMPLE_RATE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1.0);
+
+    let guard = sentry::init((
+        dsn,
+        sentry::ClientOptions {
+            release: sentry::release_name!(),
+            environment: std::env::var("SENTRY_ENVIRONMENT")
+                .or_else(|_| std::env::var("NETWORK"))
+                .ok()
+                .map(Into::into),
+            sample_rate,
+            attach_stacktrace: true,
+            ..Default::default()
+        },
+    ));
+
+    if !guard.is_enabled() {
+        tracing::warn!("⚠️  Invalid SENTRY_DSN, error reporting disabled");
+        return None;
+    }
+
+    tracing::info!("🚨 Sentry error reporting enabled");
+    Some(guard)
+}
+
+/// Hub for one request, tagged with its request ID
+///
+/// Returns `None` when error reporting is disabled, so requests don't pay
+/// for a hub nobody reads.
+pub fn request_hub(request_id: &str) -> Option<Arc<sentry::Hub>> {
+    let current = sentry::Hub::current();
+    current.client()?;
+
+    let hub = Arc::new(sentry::Hub::new_from_top(current));
+    hub.configure_scope(|scope| scope.set_tag("request_id", request_id));
+    Some(hub)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_hub_disabled_without_client() {
+        assert!(request_hub("req-1").is_none());
+    }
+
+    #[test]
+    fn test_request_id_attached_to_events() {
+        let events = sentry::test::with_captured_events(|| {
+            let hub = request_hub("req-42").expect("test client bound");
+            sentry::Hub::run(hub, || {
+                sentry::capture_message("Settlement failed", sentry::Level::Error);
+            });
+        });
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].tags.get("request_id").map(String::as_str), Some("req-42"));
+    }
+}
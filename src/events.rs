@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before slow subscribers start missing some
+pub const DEFAULT_EVENT_BUFFER: usize = 1024;
+
+/// Kinds of events published on the live feed (`GET /ws`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiveEventKind {
+    /// A payment was verified (successfully or not)
+    Verify,
+    /// A settlement completed or failed
+    Settle,
+    /// A transaction was rejected as a duplicate
+    Dedup,
+}
+
+impl LiveEventKind {
+    pub const ALL: [LiveEventKind; 3] = [LiveEventKind::Verify, LiveEventKind::Settle, LiveEventKind::Dedup];
+}
+
+/// One event on the live feed
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveEvent {
+    pub kind: LiveEventKind,
+    /// Unix timestamp in milliseconds
+    pub timestamp: i64,
+    pub network: String,
+    pub success: bool,
+    /// Event details (payer, signature, failure reason, ...)
+    pub data: serde_json::Value,
+}
+
+/// In-process fan-out of [`LiveEvent`]s to WebSocket subscribers
+///
+/// Publishing never blocks: subscribers that fall more than the buffer
+/// behind miss the oldest events and are told how many.
+#[derive(Clone, Debug)]
+pub struct EventBus {
+    sender: broadcast::Sender<LiveEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publish an event to current subscribers (no-op without any)
+    pub fn publish(&self, kind: LiveEventKind, network: &str, success: bool, data: serde_json::Value) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+
+        let _ = self.sender.send(LiveEvent {
+            kind,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            network: network.to_string(),
+            success,
+            data,
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of connected subscribers
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_BUFFER)
+    }
+}
+
+/// Messages sent by feed clients
+///
+/// `{"type": "subscribe", "events": ["verify", "settle"]}` adds kinds
+/// (all kinds when `events` is omitted), `unsubscribe` removes them.
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        #[serde(default)]
+        events: Option<Vec<LiveEventKind>>,
+    },
+    Unsubscribe {
+        #[serde(default)]
+        events: Option<Vec<LiveEventKind>>,
+    },
+    Ping,
+}
+
+/// Messages sent to feed clients
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage<'a> {
+    /// Current subscriptions, sent after every (un)subscribe
+    Subscribed { events: Vec<LiveEventKind> },
+    Event { event: &'a LiveEvent },
+    /// The client fell behind and `missed` events were dropped
+    Lagged { missed: u64 },
+    Pong,
+    Error { message: String },
+}
+
+/// Event kinds a feed client is subscribed to
+#[derive(Debug, Default)]
+pub struct Subscriptions(HashSet<LiveEventKind>);
+
+impl Subscriptions {
+    /// Apply a subscribe/unsubscribe request
+    pub fn apply(&mut self, message: &ClientMessage) {
+        match message {
+            ClientMessage::Subscribe { events } => {
+                self.0.extend(events.as_deref().unwrap_or(&LiveEventKind::ALL));
+            }
+            ClientMessage::Unsubscribe { events } => match events {
+                Some(events) => self.0.retain(|kind| !events.contains(kind)),
+                None => self.0.clear(),
+            },
+            ClientMessage::Ping => {}
+        }
+    }
+
+    pub fn contains(&self, kind: LiveEventKind) -> bool {
+        self.0.contains(&kind)
+    }
+
+    /// Subscribed kinds in a stable order
+    pub fn kinds(&self) -> Vec<LiveEventKind> {
+        LiveEventKind::ALL.into_iter().filter(|kind| self.0.contains(kind)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_protocol() {
+        let mut subscriptions = Subscriptions::default();
+
+        let subscribe: ClientMessage = serde_json::from_str(r#"{"type": "subscribe", "events": ["settle", "dedup"]}"#).unwrap();
+        subscriptions.apply(&subscribe);
+        assert_eq!(subscriptions.kinds(), vec![LiveEventKind::Settle, LiveEventKind::Dedup]);
+
+        let unsubscribe: ClientMessage = serde_json::from_str(r#"{"type": "unsubscribe", "events": ["dedup"]}"#).unwrap();
+        subscriptions.apply(&unsubscribe);
+        assert!(subscriptions.contains(LiveEventKind::Settle));
+        assert!(!subscriptions.contains(LiveEventKind::Dedup));
+
+        // No list means everything
+        subscriptions.apply(&serde_json::from_str(r#"{"type": "subscribe"}"#).unwrap());
+        assert_eq!(subscriptions.kinds(), LiveEventKind::ALL.to_vec());
+
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"type": "subscribe", "events": ["bogus"]}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_event_bus_fan_out() {
+        let bus = EventBus::new(8);
+
+        // Without subscribers, publishing is a no-op
+        bus.publish(LiveEventKind::Verify, "solana-devnet", true, serde_json::json!({}));
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        assert_eq!(bus.subscribers(), 2);
+
+        bus.publish(LiveEventKind::Settle, "solana-devnet", true, serde_json::json!({ "signature": "abc" }));
+
+        for receiver in [&mut first, &mut second] {
+            let event = receiver.recv().await.unwrap();
+            assert_eq!(event.kind, LiveEventKind::Settle);
+            assert_eq!(event.data["signature"], "abc");
+        }
+    }
+}
@@ -2,9 +2,19 @@
 // Exposes core verification functions to other programming languages
 // Compatible with: Python, Go, Java, Ruby, Node.js (N-API), C, C++, etc.
 
-use std::ffi::{CStr, CString};
+use axum::extract::State;
+use std::ffi::{c_void, CStr, CString};
 use std::os::raw::c_char;
 use std::ptr;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::handlers;
+use crate::middleware::msgpack::Negotiated;
+use crate::types::requests::{PaymentPayload, PaymentRequirements, SettleRequest, VerifyRequest};
+
+/// How long x402_context_free() waits for pending async settlements
+const SETTLEMENT_DRAIN_TIMEOUT: Duration = Duration::from_secs(120);
 
 /// C-compatible verification result structure
 /// 
@@ -51,7 +61,7 @@ pub extern "C" fn x402_init() -> i32 {
 /// - Must not use the pointer after calling this function
 /// - Safe to call with NULL pointer (no-op)
 #[no_mangle]
-pub extern "C" fn x402_free_string(s: *mut c_char) {
+pub unsafe extern "C" fn x402_free_string(s: *mut c_char) {
     if s.is_null() {
         return;
     }
@@ -69,15 +79,17 @@ pub extern "C" fn x402_free_string(s: *mut c_char) {
 /// - Caller must ensure the result was returned by x402_verify_payment
 /// - Must not use the result after calling this function
 #[no_mangle]
-pub extern "C" fn x402_free_result(result: CVerifyResult) {
-    x402_free_string(result.error_message);
-    x402_free_string(result.payer);
+pub unsafe extern "C" fn x402_free_result(result: CVerifyResult) {
+    unsafe {
+        x402_free_string(result.error_message);
+        x402_free_string(result.payer);
+    }
 }
 
 /// Verify a payment from C-compatible JSON strings
 /// 
 /// This is a simplified verification that checks basic structure.
-/// For full verification with RPC calls, use the async Rust API.
+/// For full verification with RPC calls, use x402_verify_payment_full().
 /// 
 /// # Parameters
 /// - `payment_json`: JSON string of PaymentPayload
@@ -103,47 +115,17 @@ pub extern "C" fn x402_free_result(result: CVerifyResult) {
 /// lib.x402_free_result(result)
 /// ```
 #[no_mangle]
-pub extern "C" fn x402_verify_payment(
+pub unsafe extern "C" fn x402_verify_payment(
     payment_json: *const c_char,
     requirements_json: *const c_char,
 ) -> CVerifyResult {
-    // 1. Validate pointers
-    if payment_json.is_null() {
-        return error_result("Null payment pointer");
-    }
-    if requirements_json.is_null() {
-        return error_result("Null requirements pointer");
-    }
-
-    // 2. Convert C strings to Rust strings
-    let payment_str = unsafe {
-        match CStr::from_ptr(payment_json).to_str() {
-            Ok(s) => s,
-            Err(_) => return error_result("Invalid UTF-8 in payment"),
-        }
+    // 1. Parse arguments
+    let (payment, requirements) = match unsafe { parse_request(payment_json, requirements_json) } {
+        Ok(request) => request,
+        Err(e) => return error_result(&e),
     };
 
-    let requirements_str = unsafe {
-        match CStr::from_ptr(requirements_json).to_str() {
-            Ok(s) => s,
-            Err(_) => return error_result("Invalid UTF-8 in requirements"),
-        }
-    };
-
-    // 3. Parse JSON
-    let payment: crate::types::requests::PaymentPayload = 
-        match serde_json::from_str(payment_str) {
-            Ok(p) => p,
-            Err(e) => return error_result(&format!("Payment JSON parse error: {}", e)),
-        };
-
-    let requirements: crate::types::requests::PaymentRequirements = 
-        match serde_json::from_str(requirements_str) {
-            Ok(r) => r,
-            Err(e) => return error_result(&format!("Requirements JSON parse error: {}", e)),
-        };
-
-    // 4. Perform basic validation (without RPC calls)
+    // 2. Perform basic validation (without RPC calls)
     if payment.scheme != requirements.scheme {
         return error_result("Scheme mismatch");
     }
@@ -160,16 +142,15 @@ pub extern "C" fn x402_verify_payment(
         return error_result("Only Solana networks are supported");
     }
 
-    // 5. Decode transaction to extract payer
+    // 3. Decode transaction to extract payer
     let transaction_base64 = &payment.payload.transaction;
     
     match crate::solana::decoder::decode_transaction_from_base64(transaction_base64) {
         Ok(tx) => {
-            // Extract payer (second account key, index 1)
-            let payer = if let Some(payer_key) = tx.message.account_keys.get(1) {
-                payer_key.to_string()
-            } else {
-                "unknown".to_string()
+            // The payer is the signing authority of the transfer
+            let payer = match crate::solana::verifier::transfer_authority(&tx.message) {
+                Ok(authority) => authority.to_string(),
+                Err(e) => return error_result(e.as_str()),
             };
 
             // Success!
@@ -198,6 +179,374 @@ fn error_result(msg: &str) -> CVerifyResult {
     }
 }
 
+/// Parse the payment and requirements JSON arguments
+///
+/// # Safety
+/// Non-NULL pointers must be valid NULL-terminated strings.
+unsafe fn parse_request(
+    payment_json: *const c_char,
+    requirements_json: *const c_char,
+) -> Result<(PaymentPayload, PaymentRequirements), String> {
+    if payment_json.is_null() {
+        return Err("Null payment pointer".to_string());
+    }
+    if requirements_json.is_null() {
+        return Err("Null requirements pointer".to_string());
+    }
+
+    let payment_str = unsafe { CStr::from_ptr(payment_json) }
+        .to_str()
+        .map_err(|_| "Invalid UTF-8 in payment".to_string())?;
+    let requirements_str = unsafe { CStr::from_ptr(requirements_json) }
+        .to_str()
+        .map_err(|_| "Invalid UTF-8 in requirements".to_string())?;
+
+    let payment = serde_json::from_str(payment_str)
+        .map_err(|e| format!("Payment JSON parse error: {}", e))?;
+    let requirements = serde_json::from_str(requirements_str)
+        .map_err(|e| format!("Requirements JSON parse error: {}", e))?;
+
+    Ok((payment, requirements))
+}
+
+/// Convert an optional Rust string into an owned C string (NULL if absent)
+fn into_c_string(s: Option<String>) -> *mut c_char {
+    s.and_then(|s| CString::new(s).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// C-compatible settlement result structure
+///
+/// All strings are owned by the caller; free the whole result with
+/// x402_free_settle_result().
+#[repr(C)]
+pub struct CSettleResult {
+    /// Whether the payment was settled
+    pub success: bool,
+    /// Transaction signature (NULL if not submitted)
+    pub transaction: *mut c_char,
+    /// Payer address (NULL if unknown)
+    pub payer: *mut c_char,
+    /// Error reason (NULL on success)
+    pub error_message: *mut c_char,
+    /// Signed receipt as JSON (NULL if none was issued)
+    pub receipt_json: *mut c_char,
+}
+
+impl CSettleResult {
+    fn error(msg: &str) -> Self {
+        Self {
+            success: false,
+            transaction: ptr::null_mut(),
+            payer: ptr::null_mut(),
+            error_message: into_c_string(Some(msg.to_string())),
+            receipt_json: ptr::null_mut(),
+        }
+    }
+}
+
+/// Facilitator instance for full verification and settlement
+///
+/// Owns the configuration (RPC client, caches, dedup window, fee payer) and
+/// the Tokio runtime the blocking x402_* calls run on. Created by
+/// x402_context_new(), released by x402_context_free().
+pub struct X402Context {
+    config: Config,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl X402Context {
+    fn new(rpc_url: &str, network: &str, fee_payer_private_key: &str) -> anyhow::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("x402-ffi")
+            .enable_all()
+            .build()?;
+        let config = {
+            let _guard = runtime.enter();
+            Config::new(rpc_url, network, fee_payer_private_key)?
+        };
+
+        Ok(Self { config, runtime })
+    }
+
+    /// Run the `POST /settle` flow
+    ///
+    /// Callers track it in `config.in_flight.settlements` so freeing the
+    /// context waits for it.
+    async fn settle(config: Config, request: SettleRequest) -> CSettleResult {
+        let Negotiated(response) = handlers::settle::settle(State(config), None, None, Negotiated(request)).await;
+
+        CSettleResult {
+            success: response.success,
+            transaction: into_c_string(Some(response.transaction).filter(|tx| !tx.is_empty())),
+            payer: into_c_string(response.payer),
+            error_message: into_c_string(response.error_reason),
+            receipt_json: into_c_string(
+                response.receipt.and_then(|receipt| serde_json::to_string(&receipt).ok()),
+            ),
+        }
+    }
+}
+
+impl Drop for X402Context {
+    /// Let pending async settlements finish and run their callbacks
+    fn drop(&mut self) {
+        let settlements = self.config.in_flight.settlements.clone();
+        if settlements.count() > 0
+            && !self.runtime.block_on(settlements.wait_idle(SETTLEMENT_DRAIN_TIMEOUT))
+        {
+            tracing::warn!(
+                "⚠️  Freeing x402 context with {} settlements still in flight",
+                settlements.count()
+            );
+        }
+    }
+}
+
+/// Create a facilitator context
+///
+/// # Parameters
+/// - `rpc_url`: Solana RPC endpoint (http:// or https://)
+/// - `network`: "solana", "solana-devnet" or "solana-testnet"
+/// - `fee_payer_private_key`: Base58 fee payer keypair
+///
+/// # Returns
+/// Context handle, or NULL on error (details are logged)
+///
+/// # Memory Management
+/// Caller must call x402_context_free() to release the context.
+///
+/// # Safety
+/// - Caller must ensure strings are valid UTF-8 and NULL-terminated
+#[no_mangle]
+pub unsafe extern "C" fn x402_context_new(
+    rpc_url: *const c_char,
+    network: *const c_char,
+    fee_payer_private_key: *const c_char,
+) -> *mut X402Context {
+    if rpc_url.is_null() || network.is_null() || fee_payer_private_key.is_null() {
+        tracing::error!("❌ x402_context_new: null argument");
+        return ptr::null_mut();
+    }
+
+    let args = unsafe {
+        (
+            CStr::from_ptr(rpc_url).to_str(),
+            CStr::from_ptr(network).to_str(),
+            CStr::from_ptr(fee_payer_private_key).to_str(),
+        )
+    };
+    let (Ok(rpc_url), Ok(network), Ok(fee_payer_private_key)) = args else {
+        tracing::error!("❌ x402_context_new: invalid UTF-8 argument");
+        return ptr::null_mut();
+    };
+
+    match X402Context::new(rpc_url, network, fee_payer_private_key) {
+        Ok(context) => Box::into_raw(Box::new(context)),
+        Err(e) => {
+            tracing::error!("❌ x402_context_new: {:#}", e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Free a context created by x402_context_new()
+///
+/// Waits (up to 2 minutes) for settlements started with
+/// x402_settle_payment_async() to finish and run their callbacks.
+///
+/// # Safety
+/// - Caller must ensure the pointer was returned by x402_context_new
+/// - Must not use the context after calling this function, or while
+///   another thread is using it
+/// - Must not be called from a settlement callback
+/// - Safe to call with NULL pointer (no-op)
+#[no_mangle]
+pub unsafe extern "C" fn x402_context_free(context: *mut X402Context) {
+    if context.is_null() {
+        return;
+    }
+    unsafe {
+        let _ = Box::from_raw(context);
+    }
+}
+
+/// Fully verify a payment (blocking)
+///
+/// Runs the same checks as `POST /verify`, including the RPC account and
+/// mint lookups and the duplicate transaction check.
+///
+/// # Parameters
+/// - `context`: Handle from x402_context_new()
+/// - `payment_json`: JSON string of PaymentPayload
+/// - `requirements_json`: JSON string of PaymentRequirements
+///
+/// # Returns
+/// CVerifyResult with is_valid and either error_message or payer
+///
+/// # Memory Management
+/// Caller must call x402_free_result() to free the returned result.
+///
+/// # Safety
+/// - `context` must be NULL or a live handle from x402_context_new
+/// - Caller must ensure strings are valid UTF-8 and NULL-terminated
+#[no_mangle]
+pub unsafe extern "C" fn x402_verify_payment_full(
+    context: *const X402Context,
+    payment_json: *const c_char,
+    requirements_json: *const c_char,
+) -> CVerifyResult {
+    let Some(context) = (unsafe { context.as_ref() }) else {
+        return error_result("Null context pointer");
+    };
+    let (payment_payload, payment_requirements) =
+        match unsafe { parse_request(payment_json, requirements_json) } {
+            Ok(request) => request,
+            Err(e) => return error_result(&e),
+        };
+
+    let request = VerifyRequest { payment_payload, payment_requirements, dry_run: false };
+    let Negotiated(response) = context
+        .runtime
+        .block_on(handlers::verify::verify(State(context.config.clone()), None, None, Negotiated(request)));
+
+    CVerifyResult {
+        is_valid: response.is_valid,
+        error_message: into_c_string(response.invalid_reason),
+        payer: into_c_string(response.payer),
+    }
+}
+
+/// Settle a payment on-chain (blocking)
+///
+/// Runs the same flow as `POST /settle`: verification, fee payer signing,
+/// submission and confirmation.
+///
+/// # Parameters
+/// - `context`: Handle from x402_context_new()
+/// - `payment_json`: JSON string of PaymentPayload
+/// - `requirements_json`: JSON string of PaymentRequirements
+///
+/// # Returns
+/// CSettleResult with success and either the transaction or error_message
+///
+/// # Memory Management
+/// Caller must call x402_free_settle_result() to free the returned result.
+///
+/// # Safety
+/// - `context` must be NULL or a live handle from x402_context_new
+/// - Caller must ensure strings are valid UTF-8 and NULL-terminated
+#[no_mangle]
+pub unsafe extern "C" fn x402_settle_payment(
+    context: *const X402Context,
+    payment_json: *const c_char,
+    requirements_json: *const c_char,
+) -> CSettleResult {
+    let Some(context) = (unsafe { context.as_ref() }) else {
+        return CSettleResult::error("Null context pointer");
+    };
+    let (payment_payload, payment_requirements) =
+        match unsafe { parse_request(payment_json, requirements_json) } {
+            Ok(request) => request,
+            Err(e) => return CSettleResult::error(&e),
+        };
+
+    let request = SettleRequest { payment_payload, payment_requirements };
+    let _in_flight = context.config.in_flight.settlements.track();
+    context
+        .runtime
+        .block_on(X402Context::settle(context.config.clone(), request))
+}
+
+/// Completion callback for x402_settle_payment_async()
+///
+/// Receives the result and the caller's `user_data`. The callback owns the
+/// result and must free it with x402_free_settle_result(). Nullable, so a
+/// NULL function pointer from C is rejected instead of being called.
+pub type X402SettleCallback = Option<unsafe extern "C" fn(result: CSettleResult, user_data: *mut c_void)>;
+
+/// Caller data handed back to the callback on a runtime thread
+struct UserData(*mut c_void);
+
+// The caller vouches for `user_data` being usable from another thread
+unsafe impl Send for UserData {}
+
+/// Settle a payment on-chain without blocking
+///
+/// Runs the same flow as x402_settle_payment() on the context's runtime and
+/// invokes `callback` exactly once with the result, from a runtime thread
+/// (argument errors are reported through the callback too).
+///
+/// # Parameters
+/// - `context`: Handle from x402_context_new()
+/// - `payment_json`: JSON string of PaymentPayload
+/// - `requirements_json`: JSON string of PaymentRequirements
+/// - `callback`: Called on completion
+/// - `user_data`: Passed through to `callback` untouched
+///
+/// # Returns
+/// 0 if the settlement was started, -1 if `context` or `callback` is NULL
+/// (the callback is then never invoked)
+///
+/// # Safety
+/// - `context` must be NULL or a live handle from x402_context_new
+/// - Caller must ensure strings are valid UTF-8 and NULL-terminated; they
+///   are copied before this function returns
+/// - `user_data` must stay valid until the callback runs
+/// - The callback must not call x402_context_free() on its own context
+#[no_mangle]
+pub unsafe extern "C" fn x402_settle_payment_async(
+    context: *const X402Context,
+    payment_json: *const c_char,
+    requirements_json: *const c_char,
+    callback: X402SettleCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    let (Some(context), Some(callback)) = (unsafe { context.as_ref() }, callback) else {
+        return -1;
+    };
+    let request = unsafe { parse_request(payment_json, requirements_json) };
+    let user_data = UserData(user_data);
+    let config = context.config.clone();
+    // Tracked before spawning, until the callback has run, so a context freed
+    // right after this returns still waits for it
+    let in_flight = config.in_flight.settlements.track();
+
+    context.runtime.spawn(async move {
+        let result = match request {
+            Ok((payment_payload, payment_requirements)) => {
+                X402Context::settle(config, SettleRequest { payment_payload, payment_requirements }).await
+            }
+            Err(e) => CSettleResult::error(&e),
+        };
+
+        // Capture the whole wrapper, not just its (non-Send) pointer
+        let user_data = user_data;
+        unsafe { callback(result, user_data.0) };
+        drop(in_flight);
+    });
+
+    0
+}
+
+/// Free a CSettleResult structure
+///
+/// Frees all strings within the result.
+///
+/// # Safety
+/// - Caller must ensure the result was returned by x402_settle_payment or
+///   passed to an X402SettleCallback
+/// - Must not use the result after calling this function
+#[no_mangle]
+pub unsafe extern "C" fn x402_free_settle_result(result: CSettleResult) {
+    unsafe {
+        x402_free_string(result.transaction);
+        x402_free_string(result.payer);
+        x402_free_string(result.error_message);
+        x402_free_string(result.receipt_json);
+    }
+}
+
 /// Get the library version
 /// 
 /// Returns a static string (does not need to be freed).
@@ -207,7 +556,7 @@ fn error_result(msg: &str) -> CVerifyResult {
 #[no_mangle]
 pub extern "C" fn x402_version() -> *const c_char {
     // Static string - caller must NOT free this
-    b"2.0.0\0".as_ptr() as *const c_char
+    c"2.0.0".as_ptr()
 }
 
 #[cfg(test)]
@@ -215,6 +564,17 @@ mod tests {
     use super::*;
     use std::ffi::CString;
 
+    #[test]
+    fn test_header_is_current() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/x402_facilitator.h"));
+        let checked_in = include_str!("../include/x402_facilitator.h");
+        assert!(
+            generated == checked_in,
+            "include/x402_facilitator.h is stale; regenerate it with:\n  cp {}/x402_facilitator.h include/",
+            env!("OUT_DIR")
+        );
+    }
+
     #[test]
     fn test_init() {
         let result = x402_init();
@@ -233,17 +593,17 @@ mod tests {
     #[test]
     fn test_free_null_string() {
         // Should not crash
-        x402_free_string(ptr::null_mut());
+        unsafe { x402_free_string(ptr::null_mut()) };
     }
 
     #[test]
     fn test_verify_null_pointers() {
-        let result = x402_verify_payment(ptr::null(), ptr::null());
+        let result = unsafe { x402_verify_payment(ptr::null(), ptr::null()) };
         assert!(!result.is_valid);
         assert!(!result.error_message.is_null());
         
         // Cleanup
-        x402_free_result(result);
+        unsafe { x402_free_result(result) };
     }
 
     #[test]
@@ -251,16 +611,18 @@ mod tests {
         let payment = CString::new("invalid json").unwrap();
         let requirements = CString::new("{}").unwrap();
         
-        let result = x402_verify_payment(
-            payment.as_ptr(),
-            requirements.as_ptr()
-        );
+        let result = unsafe {
+            x402_verify_payment(
+                payment.as_ptr(),
+                requirements.as_ptr()
+            )
+        };
         
         assert!(!result.is_valid);
         assert!(!result.error_message.is_null());
         
         // Cleanup
-        x402_free_result(result);
+        unsafe { x402_free_result(result) };
     }
 
     #[test]
@@ -285,15 +647,142 @@ mod tests {
             "extra": {"fee_payer": "test"}
         }"#).unwrap();
         
-        let result = x402_verify_payment(
-            payment.as_ptr(),
-            requirements.as_ptr()
-        );
+        let result = unsafe {
+            x402_verify_payment(
+                payment.as_ptr(),
+                requirements.as_ptr()
+            )
+        };
         
         assert!(!result.is_valid);
         
         // Cleanup
-        x402_free_result(result);
+        unsafe { x402_free_result(result) };
     }
-}
 
+    #[test]
+    fn test_full_api_null_context() {
+        let payment = CString::new("{}").unwrap();
+        let requirements = CString::new("{}").unwrap();
+
+        let result = unsafe {
+            x402_verify_payment_full(ptr::null(), payment.as_ptr(), requirements.as_ptr())
+        };
+        assert!(!result.is_valid);
+        assert!(!result.error_message.is_null());
+        unsafe { x402_free_result(result) };
+
+        let result = unsafe {
+            x402_settle_payment(ptr::null(), payment.as_ptr(), requirements.as_ptr())
+        };
+        assert!(!result.success);
+        assert!(result.transaction.is_null());
+        assert!(!result.error_message.is_null());
+        unsafe { x402_free_settle_result(result) };
+
+        // Should not crash
+        unsafe { x402_context_free(ptr::null_mut()) };
+    }
+
+    #[test]
+    fn test_context_new_rejects_invalid_key() {
+        let rpc_url = CString::new("http://127.0.0.1:1").unwrap();
+        let network = CString::new("solana-devnet").unwrap();
+        let key = CString::new("not a key").unwrap();
+
+        let context = unsafe { x402_context_new(rpc_url.as_ptr(), network.as_ptr(), key.as_ptr()) };
+        assert!(context.is_null());
+
+        let context = unsafe { x402_context_new(rpc_url.as_ptr(), network.as_ptr(), ptr::null()) };
+        assert!(context.is_null());
+    }
+
+    fn test_context() -> *mut X402Context {
+        use solana_sdk::signature::Keypair;
+
+        // Nothing listens on the RPC port; the health check only warns
+        let rpc_url = CString::new("http://127.0.0.1:1").unwrap();
+        let network = CString::new("solana-devnet").unwrap();
+        let key = CString::new(bs58::encode(Keypair::new().to_bytes()).into_string()).unwrap();
+
+        let context = unsafe { x402_context_new(rpc_url.as_ptr(), network.as_ptr(), key.as_ptr()) };
+        assert!(!context.is_null());
+        context
+    }
+
+    #[test]
+    fn test_verify_full_rejects_invalid_json() {
+        let context = test_context();
+
+        let payment = CString::new("invalid json").unwrap();
+        let requirements = CString::new("{}").unwrap();
+        let result = unsafe {
+            x402_verify_payment_full(context, payment.as_ptr(), requirements.as_ptr())
+        };
+        assert!(!result.is_valid);
+        assert!(!result.error_message.is_null());
+
+        unsafe {
+            x402_free_result(result);
+            x402_context_free(context);
+        }
+    }
+
+    unsafe extern "C" fn send_result(result: CSettleResult, user_data: *mut c_void) {
+        let sender = unsafe { &*(user_data as *const std::sync::mpsc::Sender<(bool, Option<String>)>) };
+        let error = (!result.error_message.is_null())
+            .then(|| unsafe { CStr::from_ptr(result.error_message) }.to_string_lossy().into_owned());
+        sender.send((result.success, error)).unwrap();
+        unsafe { x402_free_settle_result(result) };
+    }
+
+    #[test]
+    fn test_settle_async_reports_through_callback() {
+        let payment = CString::new("invalid json").unwrap();
+        let requirements = CString::new("{}").unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel::<(bool, Option<String>)>();
+        let user_data = &sender as *const _ as *mut c_void;
+
+        // Nothing to call back
+        let status = unsafe {
+            x402_settle_payment_async(ptr::null(), payment.as_ptr(), requirements.as_ptr(), Some(send_result), user_data)
+        };
+        assert_eq!(status, -1);
+
+        let context = test_context();
+        let status = unsafe {
+            x402_settle_payment_async(context, payment.as_ptr(), requirements.as_ptr(), None, user_data)
+        };
+        assert_eq!(status, -1);
+
+        let status = unsafe {
+            x402_settle_payment_async(context, payment.as_ptr(), requirements.as_ptr(), Some(send_result), user_data)
+        };
+        assert_eq!(status, 0);
+
+        let (success, error) = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(!success);
+        assert!(error.unwrap().contains("Payment JSON parse error"));
+
+        unsafe { x402_context_free(context) };
+    }
+
+    #[test]
+    fn test_context_free_waits_for_async_callback() {
+        let payment = CString::new("invalid json").unwrap();
+        let requirements = CString::new("{}").unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel::<(bool, Option<String>)>();
+        let user_data = &sender as *const _ as *mut c_void;
+
+        let context = test_context();
+        let status = unsafe {
+            x402_settle_payment_async(context, payment.as_ptr(), requirements.as_ptr(), Some(send_result), user_data)
+        };
+        assert_eq!(status, 0);
+
+        // Freed straight away: the callback has run by the time this returns
+        unsafe { x402_context_free(context) };
+        let (success, _) = receiver.try_recv().unwrap();
+        assert!(!success);
+    }
+}
@@ -1,18 +1,66 @@
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use crate::audit::{AuditEvent, AuditQuery};
+use crate::cache::MintInfo;
 use crate::config::Config;
+use crate::error::AppError;
+use crate::middleware::rate_limit::RateLimitRoute;
+use crate::rpc_health::RpcHealthSnapshot;
+use crate::solana::rpc::latest_blockhash_age;
+use crate::webhooks::{
+    send_test_webhook, WebhookConfig, WebhookEndpoint, WebhookEndpointSpec, WebhookEndpoints,
+    WebhookTls, DEFAULT_ENDPOINT,
+};
 
 /// Detailed health check with system information
 #[derive(Serialize, Deserialize)]
 pub struct HealthDetail {
+    /// `ok`, or `degraded` when this instance can't settle
     pub status: String,
     pub version: String,
     pub network: String,
     pub rpc_url: String,
     pub rpc_status: String,
+    /// Whether a `/settle` could succeed right now: RPC healthy, fee payer
+    /// funded, a fresh blockhash, and not in offline mode
+    pub can_settle: bool,
+    pub fee_payer: FeePayerHealth,
+    pub blockhash: BlockhashHealth,
     pub features: HealthFeatures,
     pub cache: CacheInfo,
+    pub dedup: DedupInfo,
+    /// Webhook deliveries in progress or queued (`None` without webhooks)
+    pub webhook_queue_depth: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FeePayerHealth {
+    pub pubkey: String,
+    /// Signer backend (`local`, or a KMS)
+    pub signer: String,
+    /// `None` if the balance couldn't be fetched
+    pub balance_lamports: Option<u64>,
+    pub balance_sol: Option<f64>,
+    pub min_balance_lamports: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BlockhashHealth {
+    pub blockhash: Option<String>,
+    /// Blocks since the RPC's latest blockhash was produced; transactions
+    /// are rejected past 150
+    pub age_blocks: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -28,23 +76,78 @@ pub struct CacheInfo {
     pub size: u64,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct DedupInfo {
+    pub entries: u64,
+    pub window_seconds: u64,
+    pub backend: String,
+}
+
+/// Blockhashes older than this are reported as stale (well within the
+/// 150-block validity, leaving clients time to sign and submit)
+const MAX_HEALTHY_BLOCKHASH_AGE: u64 = 100;
+
 /// GET /admin/health - Detailed health check
-pub async fn detailed_health(State(config): State<Config>) -> Json<HealthDetail> {
-    // Check RPC connection
-    let rpc_status = match config.rpc_client.get_health() {
+///
+/// A single probe of whether this instance can settle: RPC health, fee
+/// payer balance, blockhash freshness, plus dedup and webhook queue state.
+pub async fn detailed_health(State(config): State<Config>) -> Result<Json<HealthDetail>, AppError> {
+    let fee_payer = config.fee_payer_signer.pubkey();
+    let rpc = config.rpc_client.clone();
+    let (health, balance, blockhash) = tokio::task::spawn_blocking(move || {
+        (
+            rpc.get_health(),
+            rpc.get_account(&fee_payer).map(|account| account.map_or(0, |account| account.lamports)),
+            latest_blockhash_age(rpc.as_ref()),
+        )
+    })
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Health check failed: {}", e)))?;
+
+    let rpc_status = match &health {
         Ok(_) => "healthy".to_string(),
         Err(e) => format!("unhealthy: {}", e),
     };
+    let min_balance_lamports = config.settings.load().min_fee_payer_balance_lamports;
+    if let Ok(lamports) = &balance {
+        crate::alerts::fee_payer_balance(&config, *lamports);
+    }
+    let blockhash = match blockhash {
+        Ok((blockhash, age)) => BlockhashHealth {
+            blockhash: Some(blockhash.to_string()),
+            age_blocks: Some(age),
+            error: None,
+        },
+        Err(e) => BlockhashHealth { blockhash: None, age_blocks: None, error: Some(e.to_string()) },
+    };
+
+    let can_settle = !config.offline_verify
+        && health.is_ok()
+        && balance.as_ref().is_ok_and(|lamports| *lamports >= min_balance_lamports)
+        && blockhash.age_blocks.is_some_and(|age| age <= MAX_HEALTHY_BLOCKHASH_AGE);
+    if !can_settle {
+        tracing::warn!("🩺 Health check: this instance can't settle right now");
+    }
 
-    // Get cache stats
     let cache_stats = config.account_cache.stats();
+    let dedup_stats = config.transaction_dedup.stats();
 
-    let health = HealthDetail {
-        status: "ok".to_string(),
+    Ok(Json(HealthDetail {
+        status: if can_settle { "ok" } else { "degraded" }.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         network: config.network.clone(),
         rpc_url: config.solana_rpc_url.clone(),
         rpc_status,
+        can_settle,
+        fee_payer: FeePayerHealth {
+            pubkey: fee_payer.to_string(),
+            signer: config.fee_payer_signer.backend().to_string(),
+            balance_sol: balance.as_ref().ok().map(|lamports| *lamports as f64 / LAMPORTS_PER_SOL as f64),
+            min_balance_lamports,
+            error: balance.as_ref().err().map(|e| e.to_string()),
+            balance_lamports: balance.ok(),
+        },
+        blockhash,
         features: HealthFeatures {
             rate_limiting: config.rate_limiter.is_some(),
             caching: true,
@@ -54,9 +157,13 @@ pub async fn detailed_health(State(config): State<Config>) -> Json<HealthDetail>
             entries: cache_stats.entry_count,
             size: cache_stats.weighted_size,
         },
-    };
-
-    Json(health)
+        dedup: DedupInfo {
+            entries: dedup_stats.entry_count,
+            window_seconds: dedup_stats.window_seconds,
+            backend: dedup_stats.backend.to_string(),
+        },
+        webhook_queue_depth: config.webhook.as_ref().map(|webhook| webhook.pending.count()),
+    }))
 }
 
 /// Stats for monitoring
@@ -91,12 +198,50 @@ pub async fn get_stats(State(config): State<Config>) -> Json<Stats> {
     Json(stats)
 }
 
+/// GET /admin/rpc - Health, slot lag and latency of the RPC endpoints
+///
+/// Serves the monitor's last check, or checks now if none ran yet.
+pub async fn rpc_health(State(config): State<Config>) -> Result<Json<RpcHealthSnapshot>, AppError> {
+    if let Some(snapshot) = config.rpc_health.snapshot() {
+        return Ok(Json(snapshot));
+    }
+
+    let monitor = config.rpc_health.clone();
+    let snapshot = tokio::task::spawn_blocking(move || monitor.check())
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("RPC health check failed: {}", e)))?;
+    Ok(Json(snapshot))
+}
+
 /// GET /admin/config - Configuration info (redacted)
 pub async fn get_config(State(config): State<Config>) -> Json<Value> {
     Json(json!({
         "network": config.network,
         "rpc_url": config.solana_rpc_url,
         "port": config.port,
+        "fee_payer": config.fee_payer_signer.pubkey().to_string(),
+        "fee_payer_signer": config.fee_payer_signer.backend(),
+        "rate_limits": config.rate_limiter.as_ref().map(|limiter| json!({
+            "backend": limiter.backend(),
+            "verify": {
+                "per_second": limiter.quota(RateLimitRoute::Verify).per_second,
+                "burst_size": limiter.quota(RateLimitRoute::Verify).burst_size,
+            },
+            "settle": {
+                "per_second": limiter.quota(RateLimitRoute::Settle).per_second,
+                "burst_size": limiter.quota(RateLimitRoute::Settle).burst_size,
+            },
+        })),
+        "dedup_backend": config.transaction_dedup.backend(),
+        "payment_expiry_seconds": config.settings.load().payment_expiry_seconds,
+        "clock_skew_seconds": config.settings.load().clock_skew_seconds,
+        "allowed_mints": config.settings.load().allowed_mints,
+        "min_fee_payer_balance_lamports": config.settings.load().min_fee_payer_balance_lamports,
+        "required_signers": config.settings.load().required_signers,
+        "ata_rent_budget_lamports": config.ata_rent_budget.daily_lamports(),
+        "fee_payer_daily_budget_lamports": config.fee_spend.daily_budget_lamports(),
+        "tenants": config.tenants.len(),
+        "fee_payer_spend_today": config.fee_spend.today(),
         "features": {
             "rate_limiting": config.rate_limiter.is_some(),
             "caching": true,
@@ -105,3 +250,575 @@ pub async fn get_config(State(config): State<Config>) -> Json<Value> {
     }))
 }
 
+
+/// POST /admin/reload - Re-read runtime settings (same as SIGHUP)
+pub async fn reload_config(State(config): State<Config>) -> Result<Json<Value>, AppError> {
+    let changed = crate::reload::reload(&config).map_err(|e| {
+        tracing::warn!("⚠️  Config reload failed, keeping current settings: {:#}", e);
+        AppError::Config(format!("{:#}", e))
+    })?;
+
+    Ok(Json(json!({
+        "reloaded": true,
+        "changed": changed,
+    })))
+}
+
+/// Output format for `/admin/audit`
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditOutput {
+    #[serde(default)]
+    pub format: AuditFormat,
+}
+
+/// GET /admin/audit - Query persisted audit events
+///
+/// Filters: `event_type`, `payer`, `network`, `from`/`to` (RFC 3339), with
+/// `limit`/`offset` pagination and `format=json|csv`. Requires audit event
+/// storage (`AUDIT_DATABASE_URL`).
+pub async fn query_audit(
+    State(config): State<Config>,
+    Query(query): Query<AuditQuery>,
+    Query(output): Query<AuditOutput>,
+) -> Result<Response, AppError> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from >= to {
+            return Err(AppError::BadRequest("'from' must be before 'to'".to_string()));
+        }
+    }
+
+    let events = fetch_audit_events(&config, &query).await?;
+
+    // A full page means there may be more
+    let next_offset = (events.len() as u64 == u64::from(query.limit()))
+        .then(|| query.offset() + events.len() as u64);
+
+    let mut response = match output.format {
+        AuditFormat::Json => Json(json!({
+            "events": events,
+            "limit": query.limit(),
+            "offset": query.offset(),
+            "next_offset": next_offset,
+        }))
+        .into_response(),
+        AuditFormat::Csv => (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"audit.csv\""),
+            ],
+            audit_csv(&events),
+        )
+            .into_response(),
+    };
+
+    if let Some(next_offset) = next_offset {
+        response.headers_mut().insert("x-next-offset", next_offset.into());
+    }
+
+    Ok(response)
+}
+
+#[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+async fn fetch_audit_events(config: &Config, query: &AuditQuery) -> Result<Vec<AuditEvent>, AppError> {
+    let store = config
+        .audit_logger
+        .store()
+        .ok_or_else(|| AppError::NotImplemented("Audit storage is not configured".to_string()))?;
+
+    Ok(store.query(query).await?)
+}
+
+#[cfg(not(any(feature = "audit-sqlite", feature = "audit-postgres")))]
+async fn fetch_audit_events(_config: &Config, _query: &AuditQuery) -> Result<Vec<AuditEvent>, AppError> {
+    Err(AppError::NotImplemented(
+        "Audit storage requires the audit-sqlite or audit-postgres feature".to_string(),
+    ))
+}
+
+fn webhook_config(config: &Config) -> Result<&WebhookConfig, AppError> {
+    config
+        .webhook
+        .as_ref()
+        .ok_or_else(|| AppError::NotImplemented("Webhooks are not enabled".to_string()))
+}
+
+/// Endpoints that may be changed at runtime (requires an endpoints file)
+fn managed_endpoints(config: &Config) -> Result<&WebhookEndpoints, AppError> {
+    let endpoints = &webhook_config(config)?.endpoints;
+    if !endpoints.is_persistent() {
+        return Err(AppError::NotImplemented(
+            "Set WEBHOOK_ENDPOINTS_FILE to manage webhook endpoints at runtime".to_string(),
+        ));
+    }
+    Ok(endpoints)
+}
+
+/// Endpoint details without the secret
+fn endpoint_json(endpoint: &WebhookEndpoint) -> Value {
+    json!({
+        "name": endpoint.name,
+        "url": endpoint.url.get(),
+        "events": endpoint.events.patterns(),
+        "enabled": endpoint.enabled,
+        "tls": endpoint.tls,
+    })
+}
+
+/// GET /admin/webhooks - List webhook endpoints
+///
+/// The default endpoint (`WEBHOOK_URL`) is listed first and can only be
+/// changed through configuration.
+pub async fn list_webhooks(State(config): State<Config>) -> Result<Json<Value>, AppError> {
+    let webhook = webhook_config(&config)?;
+    let endpoints: Vec<_> = std::iter::once(webhook.default_endpoint())
+        .chain(webhook.endpoints.get().iter().cloned())
+        .map(|endpoint| endpoint_json(&endpoint))
+        .collect();
+
+    Ok(Json(json!({
+        "endpoints": endpoints,
+        "editable": webhook.endpoints.is_persistent(),
+    })))
+}
+
+/// GET /admin/webhooks/status - Last delivery outcome per webhook endpoint
+pub async fn webhook_status(State(config): State<Config>) -> Result<Json<Value>, AppError> {
+    let webhook = webhook_config(&config)?;
+    let endpoints: Vec<_> = std::iter::once(webhook.default_endpoint())
+        .chain(webhook.endpoints.get().iter().cloned())
+        .map(|endpoint| {
+            json!({
+                "name": endpoint.name,
+                "enabled": endpoint.enabled,
+                "status": webhook.status.get(&endpoint.name),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "endpoints": endpoints,
+        "pending": webhook.pending.count(),
+    })))
+}
+
+/// POST /admin/webhooks - Register a webhook endpoint
+pub async fn create_webhook(
+    State(config): State<Config>,
+    Json(spec): Json<WebhookEndpointSpec>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    let endpoint = WebhookEndpoint::from_spec(spec).map_err(|e| AppError::BadRequest(format!("{:#}", e)))?;
+
+    managed_endpoints(&config)?
+        .update(|endpoints| {
+            if endpoints.iter().any(|existing| existing.name == endpoint.name) {
+                return Err(AppError::Conflict(format!("Webhook endpoint '{}' already exists", endpoint.name)));
+            }
+            endpoints.push(endpoint.clone());
+            Ok(())
+        })
+        .await?;
+
+    tracing::info!("🔔 Registered webhook endpoint '{}'", endpoint.name);
+    config.audit_logger.log_config_changed(
+        "webhook_endpoints",
+        json!({ "action": "created", "endpoint": endpoint_json(&endpoint) }),
+    );
+
+    Ok((StatusCode::CREATED, Json(endpoint_json(&endpoint))))
+}
+
+/// Changes to a webhook endpoint; omitted fields are left as they are
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateWebhookEndpoint {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+    /// Replaces the endpoint's TLS settings (an empty object clears them)
+    pub tls: Option<WebhookTls>,
+}
+
+/// PATCH /admin/webhooks/{name} - Change a webhook endpoint (e.g. rotate its URL or disable it)
+pub async fn update_webhook(
+    State(config): State<Config>,
+    Path(name): Path<String>,
+    Json(changes): Json<UpdateWebhookEndpoint>,
+) -> Result<Json<Value>, AppError> {
+    if name == DEFAULT_ENDPOINT {
+        return Err(AppError::BadRequest(
+            "The default endpoint is configured by WEBHOOK_URL / WEBHOOK_SECRET".to_string(),
+        ));
+    }
+
+    let updated = managed_endpoints(&config)?
+        .update(|endpoints| {
+            let endpoint = endpoints
+                .iter_mut()
+                .find(|endpoint| endpoint.name == name)
+                .ok_or_else(|| AppError::NotFound(format!("Webhook endpoint '{}' not found", name)))?;
+
+            // Validate the result as a whole, then swap it in
+            let mut spec = WebhookEndpointSpec {
+                name: endpoint.name.clone(),
+                url: endpoint.url.get(),
+                secret: endpoint.secret.get(),
+                events: endpoint.events.patterns().to_vec(),
+                enabled: endpoint.enabled,
+                tls: endpoint.tls.clone(),
+            };
+            if let Some(url) = changes.url {
+                spec.url = url;
+            }
+            if let Some(secret) = changes.secret {
+                spec.secret = secret;
+            }
+            if let Some(events) = changes.events {
+                spec.events = events;
+            }
+            if let Some(enabled) = changes.enabled {
+                spec.enabled = enabled;
+            }
+            if let Some(tls) = changes.tls {
+                spec.tls = Some(tls).filter(|tls| *tls != WebhookTls::default());
+            }
+
+            *endpoint = WebhookEndpoint::from_spec(spec).map_err(|e| AppError::BadRequest(format!("{:#}", e)))?;
+            Ok::<_, AppError>(endpoint.clone())
+        })
+        .await?;
+
+    tracing::info!("🔔 Updated webhook endpoint '{}'", updated.name);
+    config.audit_logger.log_config_changed(
+        "webhook_endpoints",
+        json!({ "action": "updated", "endpoint": endpoint_json(&updated) }),
+    );
+
+    Ok(Json(endpoint_json(&updated)))
+}
+
+/// DELETE /admin/webhooks/{name} - Remove a webhook endpoint
+pub async fn delete_webhook(
+    State(config): State<Config>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AppError> {
+    managed_endpoints(&config)?
+        .update(|endpoints| {
+            let before = endpoints.len();
+            endpoints.retain(|endpoint| endpoint.name != name);
+            if endpoints.len() == before {
+                return Err(AppError::NotFound(format!("Webhook endpoint '{}' not found", name)));
+            }
+            Ok(())
+        })
+        .await?;
+
+    tracing::info!("🔔 Removed webhook endpoint '{}'", name);
+    config.audit_logger.log_config_changed(
+        "webhook_endpoints",
+        json!({ "action": "deleted", "endpoint": { "name": name } }),
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /admin/webhooks/{name}/test - Send a `webhook.test` event to an endpoint
+pub async fn test_webhook(
+    State(config): State<Config>,
+    Path(name): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    let webhook = webhook_config(&config)?;
+    let endpoint = webhook
+        .endpoint(&name)
+        .ok_or_else(|| AppError::NotFound(format!("Webhook endpoint '{}' not found", name)))?;
+
+    let result = send_test_webhook(webhook, &endpoint).await;
+
+    Ok(Json(json!({
+        "endpoint": endpoint.name,
+        "delivered": result.is_ok(),
+        "error": result.err().map(|e| e.to_string()),
+    })))
+}
+
+/// GET /admin/dedup/stats - Transaction dedup cache statistics
+pub async fn dedup_stats(State(config): State<Config>) -> Json<Value> {
+    let stats = config.transaction_dedup.stats();
+
+    Json(json!({
+        "backend": stats.backend,
+        "entries": stats.entry_count,
+        "window_seconds": stats.window_seconds,
+        "replays_rejected": config.metrics.dedup_hits.get(),
+    }))
+}
+
+/// DELETE /admin/dedup/{tx_hash} - Release a transaction marked as seen
+///
+/// `tx_hash` is the SHA-256 (hex) logged when a duplicate is rejected.
+pub async fn delete_dedup_entry(
+    State(config): State<Config>,
+    Path(tx_hash): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let tx_hash = tx_hash.to_ascii_lowercase();
+    if tx_hash.len() != 64 || !tx_hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(AppError::BadRequest("Expected a hex SHA-256 transaction hash".to_string()));
+    }
+
+    if !config.transaction_dedup.remove(&tx_hash) {
+        return Err(AppError::NotFound(format!("Transaction {} is not in the dedup cache", tx_hash)));
+    }
+
+    tracing::info!("🔐 Released dedup entry {}", tx_hash);
+    config.audit_logger.log_config_changed("dedup", json!({ "action": "removed", "hash": tx_hash }));
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /admin/dedup - Forget every transaction marked as seen
+pub async fn clear_dedup(State(config): State<Config>) -> StatusCode {
+    config.transaction_dedup.clear();
+
+    tracing::warn!("🔐 Cleared the dedup cache");
+    config.audit_logger.log_config_changed("dedup", json!({ "action": "cleared" }));
+
+    StatusCode::NO_CONTENT
+}
+
+/// Body of `POST /admin/cache/invalidate`: one account or everything
+#[derive(Debug, Deserialize)]
+pub struct CacheInvalidation {
+    pub pubkey: Option<String>,
+    #[serde(default)]
+    pub all: bool,
+}
+
+/// POST /admin/cache/invalidate - Drop stale accounts and mints from the caches
+pub async fn invalidate_cache(
+    State(config): State<Config>,
+    Json(request): Json<CacheInvalidation>,
+) -> Result<Json<Value>, AppError> {
+    match (request.pubkey, request.all) {
+        (None, true) => {
+            config.account_cache.invalidate_all();
+            config.mint_cache.invalidate_all();
+            tracing::info!("💾 Invalidated the account and mint caches");
+            Ok(Json(json!({ "invalidated": "all" })))
+        }
+        (Some(pubkey), false) => {
+            let key = parse_pubkey(&pubkey)?;
+            let cached = config.account_cache.contains(&key) || config.mint_cache.contains(&key);
+            config.account_cache.invalidate(&key).await;
+            config.mint_cache.invalidate(&key);
+            tracing::info!("💾 Invalidated cached account {}", pubkey);
+            Ok(Json(json!({ "invalidated": pubkey, "was_cached": cached })))
+        }
+        _ => Err(AppError::BadRequest("Specify either 'pubkey' or 'all': true".to_string())),
+    }
+}
+
+/// Most accounts `POST /admin/cache/warm` fetches per request
+pub const MAX_WARM_ACCOUNTS: usize = 1000;
+
+/// Body of `POST /admin/cache/warm`
+#[derive(Debug, Deserialize)]
+pub struct CacheWarmup {
+    pub pubkeys: Vec<String>,
+}
+
+/// POST /admin/cache/warm - Prefetch accounts (e.g. merchant ATAs) into the caches
+///
+/// Mints are stored in the mint cache, everything else in the account cache.
+pub async fn warm_cache(
+    State(config): State<Config>,
+    Json(request): Json<CacheWarmup>,
+) -> Result<Json<Value>, AppError> {
+    if request.pubkeys.len() > MAX_WARM_ACCOUNTS {
+        return Err(AppError::BadRequest(format!(
+            "At most {} accounts can be warmed per request",
+            MAX_WARM_ACCOUNTS
+        )));
+    }
+
+    let pubkeys = request
+        .pubkeys
+        .iter()
+        .map(|pubkey| parse_pubkey(pubkey))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let rpc_client = config.rpc_client.clone();
+    let fetch = pubkeys.clone();
+    let accounts = tokio::task::spawn_blocking(move || {
+        // getMultipleAccounts takes at most 100 keys
+        fetch
+            .chunks(100)
+            .map(|chunk| rpc_client.get_multiple_accounts(chunk))
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("Cache warm-up panicked: {}", e))?
+    .map_err(|e| anyhow::anyhow!("Failed to fetch accounts: {}", e))?;
+
+    let mut warmed = 0;
+    let mut missing = Vec::new();
+    for (pubkey, account) in pubkeys.into_iter().zip(accounts.into_iter().flatten()) {
+        let Some(account) = account else {
+            missing.push(pubkey.to_string());
+            continue;
+        };
+
+        match MintInfo::from_account(&account) {
+            Ok(mint) => config.mint_cache.store(pubkey, mint),
+            Err(_) => config.account_cache.store(pubkey, account),
+        }
+        warmed += 1;
+    }
+
+    tracing::info!("💾 Warmed caches with {} accounts ({} missing)", warmed, missing.len());
+
+    Ok(Json(json!({
+        "requested": request.pubkeys.len(),
+        "warmed": warmed,
+        "missing": missing,
+    })))
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey, AppError> {
+    pubkey
+        .parse()
+        .map_err(|_| AppError::BadRequest(format!("Invalid public key '{}'", pubkey)))
+}
+
+/// Pagination for `/admin/webhooks/dlq`
+#[derive(Debug, Default, Deserialize)]
+pub struct DeadLetterQuery {
+    /// Page size (default 100, at most 1000)
+    pub limit: Option<u32>,
+    pub offset: Option<u64>,
+}
+
+/// GET /admin/webhooks/dlq - Webhook deliveries that exhausted their retries
+///
+/// Requires the durable webhook queue (`WEBHOOK_QUEUE_PATH`).
+pub async fn webhook_dead_letters(
+    State(config): State<Config>,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<Json<Value>, AppError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    let offset = query.offset.unwrap_or(0);
+
+    let dead_letters = fetch_dead_letters(&config, limit, offset).await?;
+
+    Ok(Json(json!({
+        "next_offset": (dead_letters.len() as u32 == limit).then(|| offset + u64::from(limit)),
+        "dead_letters": dead_letters,
+        "limit": limit,
+        "offset": offset,
+    })))
+}
+
+#[cfg(feature = "webhook-queue")]
+async fn fetch_dead_letters(config: &Config, limit: u32, offset: u64) -> Result<Vec<Value>, AppError> {
+    let queue = config
+        .webhook
+        .as_ref()
+        .and_then(|webhook| webhook.queue.as_ref())
+        .ok_or_else(|| AppError::NotImplemented("Webhook queue is not configured".to_string()))?;
+
+    let dead_letters = queue.dead_letters(limit, offset).await?;
+
+    // Show the payload as JSON rather than an escaped string
+    Ok(dead_letters
+        .into_iter()
+        .map(|delivery| {
+            let payload: Value = serde_json::from_str(&delivery.payload).unwrap_or(Value::String(delivery.payload.clone()));
+            json!({
+                "id": delivery.id,
+                "endpoint": delivery.endpoint,
+                "event": delivery.event,
+                "attempts": delivery.attempts,
+                "last_error": delivery.last_error,
+                "created_at": delivery.created_at,
+                "dead_lettered_at": delivery.next_attempt_at,
+                "payload": payload,
+            })
+        })
+        .collect())
+}
+
+#[cfg(not(feature = "webhook-queue"))]
+async fn fetch_dead_letters(_config: &Config, _limit: u32, _offset: u64) -> Result<Vec<Value>, AppError> {
+    Err(AppError::NotImplemented(
+        "Webhook queue requires the webhook-queue feature".to_string(),
+    ))
+}
+
+/// Render events as CSV, one row per event with a header row
+fn audit_csv(events: &[AuditEvent]) -> String {
+    let mut csv = String::from(
+        "id,event_type,timestamp,transaction_signature,payer,network,amount,recipient,error,metadata,tenant\r\n",
+    );
+
+    for event in events {
+        let event_type = serde_json::to_value(&event.event_type)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        let fields = [
+            event.id.clone(),
+            event_type,
+            event.timestamp.to_rfc3339(),
+            event.transaction_signature.clone().unwrap_or_default(),
+            event.payer.clone().unwrap_or_default(),
+            event.network.clone().unwrap_or_default(),
+            event.amount.map(|amount| amount.to_string()).unwrap_or_default(),
+            event.recipient.clone().unwrap_or_default(),
+            event.error.clone().unwrap_or_default(),
+            event.metadata.as_ref().map(|metadata| metadata.to_string()).unwrap_or_default(),
+            event.tenant.clone().unwrap_or_default(),
+        ];
+
+        let row: Vec<_> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// Quote a CSV field when it contains separators, quotes or line breaks
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditEventType;
+
+    #[test]
+    fn test_audit_csv_escapes_fields() {
+        let event = AuditEvent::new(AuditEventType::SettlementFailed)
+            .with_error("RPC error: \"timeout\", retrying".to_string())
+            .with_amount(42);
+
+        let csv = audit_csv(std::slice::from_ref(&event));
+        let lines: Vec<_> = csv.split("\r\n").collect();
+
+        assert!(lines[0].starts_with("id,event_type,timestamp,"));
+        assert!(lines[1].starts_with(&format!("{},settlement_failed,", event.id)));
+        assert!(lines[1].contains(",42,,\"RPC error: \"\"timeout\"\", retrying\","));
+    }
+}
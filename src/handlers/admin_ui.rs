@@ -0,0 +1,63 @@
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use rust_embed::RustEmbed;
+
+/// Static files of the admin dashboard, compiled into the binary
+#[derive(RustEmbed)]
+#[folder = "assets/admin-ui/"]
+struct AdminUiAssets;
+
+/// GET /admin/ui - Admin dashboard
+///
+/// The page holds no data itself: its script asks for the admin key and
+/// polls `/admin/stats`, `/metrics`, `/admin/webhooks/status` and
+/// `/admin/audit` with it, so it is served without admin auth.
+pub async fn index() -> Response {
+    asset("index.html")
+}
+
+/// GET /admin/ui/{path} - Dashboard scripts and styles
+pub async fn asset_file(Path(path): Path<String>) -> Response {
+    asset(&path)
+}
+
+fn asset(path: &str) -> Response {
+    match AdminUiAssets::get(path) {
+        Some(file) => (
+            [
+                (header::CONTENT_TYPE, content_type(path)),
+                (header::CACHE_CONTROL, "no-cache"),
+            ],
+            file.data.into_owned(),
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+fn content_type(path: &str) -> &'static str {
+    match path.rsplit('.').next() {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assets_embedded() {
+        for path in ["index.html", "app.js", "style.css"] {
+            assert!(AdminUiAssets::get(path).is_some(), "{} is not embedded", path);
+        }
+        assert_eq!(content_type("app.js"), "text/javascript; charset=utf-8");
+        assert_eq!(content_type("index.html"), "text/html; charset=utf-8");
+    }
+}
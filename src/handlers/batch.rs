@@ -1,14 +1,53 @@
-// Batch verification handler - leverages parallel processing
-// This endpoint can verify thousands of payments simultaneously,
-// utilizing all CPU cores for maximum throughput.
+// Batch verification handlers - concurrent verification with shared lookups
+// These endpoints verify thousands of payments at once, fetching their
+// accounts together instead of one RPC call per check.
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use futures::{future, Stream, StreamExt};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use utoipa::IntoParams;
 
-use axum::{extract::State, Json};
 use crate::{
     config::Config,
-    parallel::verify_batch_parallel,
-    types::{requests::VerifyRequest, responses::VerifyResponse},
+    error::AppError,
+    middleware::msgpack::Negotiated,
+    parallel::{verify_batch_parallel, verify_in_pool, DEFAULT_BATCH_CONCURRENCY},
+    tenants::Tenant,
+    types::{
+        requests::BatchVerifyRequest,
+        responses::{
+            BatchSummary, BatchVerifyResponse, BatchVerifyResult, CheckFailure, StreamedVerifyResponse,
+            VerifyResponse,
+        },
+    },
 };
 
+/// Content type of /verify/stream bodies
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Payments verified at once by /verify/stream unless `concurrency` is set
+pub const DEFAULT_STREAM_CONCURRENCY: usize = 16;
+
+/// Upper bound of the `concurrency` parameter of /verify/stream
+pub const MAX_STREAM_CONCURRENCY: usize = 64;
+
+/// Query parameters of /verify/batch
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct BatchParams {
+    /// Return a `BatchVerifyResponse` (results with their index and id,
+    /// and a summary) instead of a bare array
+    #[serde(default)]
+    pub envelope: bool,
+}
+
 /// Verify multiple payments in parallel
 /// 
 /// This endpoint is designed for bulk verification scenarios:
@@ -17,9 +56,10 @@ use crate::{
 /// - Analytics systems processing historical payments
 /// - Webhook handlers receiving batched events
 /// 
-/// **Performance:** Utilizes all CPU cores via Rayon's work-stealing thread pool.
-/// - On an 8-core machine: ~8x faster than sequential verification
-/// - Memory efficient: only the results are kept in memory
+/// **Performance:** Accounts are fetched for the whole batch up front,
+/// 100 per `getMultipleAccounts` call, then payments are verified 16 at a time.
+/// - RPC round trips scale with the batch's accounts / 100, not its checks
+/// - Bounded: a large batch can't exhaust the blocking thread pool
 /// - Fault tolerant: individual failures don't block the batch
 /// 
 /// # Example Request
@@ -49,58 +89,247 @@ use crate::{
 ///   }
 /// ]
 /// ```
+///
+/// Items may carry an `id`. With `?envelope=true` each result comes with
+/// its `index` and `id`, payments that couldn't be verified at all have an
+/// `error`, and a `summary` counts valid, invalid and errored payments.
+/// Either way there is exactly one result per request.
 #[utoipa::path(
     post,
     path = "/verify/batch",
-    request_body = Vec<VerifyRequest>,
+    params(BatchParams),
+    request_body = Vec<BatchVerifyRequest>,
     responses(
-        (status = 200, description = "Batch verification results", body = Vec<VerifyResponse>)
+        (status = 200, description = "Batch verification results (a BatchVerifyResponse with `envelope=true`)", body = Vec<VerifyResponse>),
+        (status = 413, description = "Batch exceeds MAX_BATCH_SIZE or body size limit")
     ),
     tag = "Payment"
 )]
 pub async fn verify_batch(
     State(config): State<Config>,
-    Json(requests): Json<Vec<VerifyRequest>>,
-) -> Json<Vec<VerifyResponse>> {
-    let batch_size = requests.len();
+    tenant: Option<Extension<Arc<Tenant>>>,
+    params: Option<Query<BatchParams>>,
+    Negotiated(items): Negotiated<Vec<BatchVerifyRequest>>,
+) -> Result<Response, AppError> {
+    let config = config.for_tenant(tenant.map(|Extension(tenant)| tenant));
+    let batch_size = items.len();
     
     tracing::info!(
         "📦 Received batch verification request for {} payments",
         batch_size
     );
 
-    if batch_size == 0 {
-        return Json(vec![]);
+    if batch_size > config.max_batch_size {
+        tracing::warn!(
+            "🚫 Rejected batch of {} payments (max {})",
+            batch_size,
+            config.max_batch_size
+        );
+        return Err(AppError::PayloadTooLarge(format!(
+            "batch contains {} payments, maximum is {}",
+            batch_size, config.max_batch_size
+        )));
     }
 
-    // Spawn blocking to move to Rayon's thread pool
-    // This prevents blocking Tokio's async runtime
-    let results = tokio::task::spawn_blocking(move || {
-        verify_batch_parallel(&config, requests)
-    })
-    .await
-    .unwrap_or_else(|e| {
-        tracing::error!("Batch verification task panicked: {}", e);
-        // Return empty results on panic
-        vec![]
-    });
+    let envelope = params.is_some_and(|Query(params)| params.envelope);
+    let (ids, requests): (Vec<_>, Vec<_>) = items.into_iter().map(|item| (item.id, item.request)).unzip();
+    let outcomes = if requests.is_empty() {
+        Vec::new()
+    } else {
+        verify_batch_parallel(&config, requests, DEFAULT_BATCH_CONCURRENCY).await
+    };
+
+    let results: Vec<BatchVerifyResult> = outcomes
+        .into_iter()
+        .zip(ids)
+        .enumerate()
+        .map(|(index, (outcome, id))| match outcome {
+            Ok(response) => BatchVerifyResult { index, id, response, error: None },
+            Err(error) => BatchVerifyResult {
+                index,
+                id,
+                response: invalid("unexpected_verify_error", None),
+                error: Some(error),
+            },
+        })
+        .collect();
+    let summary = BatchSummary::of(&results);
 
     tracing::info!(
-        "✅ Batch verification complete: {}/{} valid",
-        results.iter().filter(|r| r.is_valid).count(),
-        batch_size
+        "✅ Batch verification complete: {}/{} valid, {} errors",
+        summary.valid,
+        summary.total,
+        summary.errors
     );
 
-    Json(results)
+    if envelope {
+        return Ok(Negotiated(BatchVerifyResponse { results, summary }).into_response());
+    }
+    let responses: Vec<VerifyResponse> = results.into_iter().map(|result| result.response).collect();
+    Ok(Negotiated(responses).into_response())
+}
+
+/// Query parameters of /verify/stream
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct StreamParams {
+    /// Payments verified at once (default 16, at most 64)
+    pub concurrency: Option<usize>,
+}
+
+/// Verify newline-delimited payments, streaming results as they complete
+///
+/// For reconciliation jobs too large for `/verify/batch`:
+/// - Each line of the body is a `VerifyRequest` (with an optional `id`);
+///   blank lines are skipped
+/// - Each result line is a `VerifyResponse` with the `index` and `id` of its
+///   request, written as soon as it's verified, so results arrive out of order
+/// - Only in-flight payments are held in memory; lines are capped at the
+///   single-request body limit instead of the batch one
+/// - Unparseable lines get an `invalid_request` result and don't stop the stream
+///
+/// # Example Request (`Content-Type: application/x-ndjson`)
+/// ```text
+/// {"payment_payload": { ... }, "payment_requirements": { ... }}
+/// {"payment_payload": { ... }, "payment_requirements": { ... }}
+/// ```
+///
+/// # Example Response
+/// ```text
+/// {"index":1,"isValid":false,"invalidReason":"invalid_exact_svm_payload_transaction_amount_mismatch"}
+/// {"index":0,"isValid":true,"payer":"wallet_address_1"}
+/// ```
+#[utoipa::path(
+    post,
+    path = "/verify/stream",
+    params(StreamParams),
+    request_body(content = String, content_type = "application/x-ndjson", description = "One VerifyRequest per line"),
+    responses(
+        (status = 200, description = "One StreamedVerifyResponse per line, in completion order", body = StreamedVerifyResponse, content_type = "application/x-ndjson")
+    ),
+    tag = "Payment"
+)]
+pub async fn verify_stream(
+    State(config): State<Config>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    Query(params): Query<StreamParams>,
+    body: Body,
+) -> Response {
+    let config = config.for_tenant(tenant.map(|Extension(tenant)| tenant));
+    let concurrency = params
+        .concurrency
+        .unwrap_or(DEFAULT_STREAM_CONCURRENCY)
+        .clamp(1, MAX_STREAM_CONCURRENCY);
+    tracing::info!("📦 Streaming batch verification (concurrency {})", concurrency);
+
+    let max_line_bytes = config.max_body_bytes;
+    let results = lines(body, max_line_bytes)
+        .filter(|line| future::ready(!matches!(line, Ok(line) if line.iter().all(u8::is_ascii_whitespace))))
+        .enumerate()
+        .map(move |(index, line)| {
+            let config = config.clone();
+            async move {
+                let (id, response) = match line.and_then(|line| {
+                    serde_json::from_slice::<BatchVerifyRequest>(&line).map_err(|e| format!("Invalid request: {}", e))
+                }) {
+                    Ok(BatchVerifyRequest { id, request }) => {
+                        let response = verify_in_pool(&config, &request, None)
+                            .await
+                            .unwrap_or_else(|_| invalid("unexpected_verify_error", None));
+                        (id, response)
+                    }
+                    Err(e) => {
+                        tracing::warn!("⚠️  Rejected line {} of verification stream: {}", index, e);
+                        (None, invalid("invalid_request", Some(e)))
+                    }
+                };
+
+                let result = StreamedVerifyResponse { index, id, response };
+                let mut json = serde_json::to_vec(&result).unwrap_or_default();
+                json.push(b'\n');
+                Ok::<_, Infallible>(Bytes::from(json))
+            }
+        })
+        .buffer_unordered(concurrency);
+
+    ([(header::CONTENT_TYPE, NDJSON_CONTENT_TYPE)], Body::from_stream(results)).into_response()
+}
+
+fn invalid(reason: &str, hint: Option<String>) -> VerifyResponse {
+    VerifyResponse {
+        is_valid: false,
+        invalid_reason: Some(reason.to_string()),
+        payer: None,
+        checks_skipped: Vec::new(),
+        failures: hint
+            .map(|hint| CheckFailure {
+                check: "request".to_string(),
+                code: reason.to_string(),
+                hint,
+            })
+            .into_iter()
+            .collect(),
+        details: None,
+    }
+}
+
+/// Lines of `body`, read as it arrives
+///
+/// A line longer than `max_line_bytes` (or a failed read) yields an error
+/// and ends the stream, as the rest of the body can't be split reliably.
+fn lines(body: Body, max_line_bytes: usize) -> impl Stream<Item = Result<Vec<u8>, String>> {
+    let state = (body.into_data_stream(), Vec::new(), false);
+    futures::stream::unfold(state, move |(mut data, mut buffer, mut done)| async move {
+        loop {
+            if let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                return Some((Ok(line), (data, buffer, done)));
+            }
+            if buffer.len() > max_line_bytes {
+                let error = format!("Line exceeds the {} byte limit", max_line_bytes);
+                return Some((Err(error), (data, Vec::new(), true)));
+            }
+            if done {
+                if buffer.is_empty() {
+                    return None;
+                }
+                let line = std::mem::take(&mut buffer);
+                return Some((Ok(line), (data, buffer, done)));
+            }
+            match data.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => {
+                    let error = format!("Failed to read request body: {}", e);
+                    return Some((Err(error), (data, Vec::new(), true)));
+                }
+                None => done = true,
+            }
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::{
-        requests::{PaymentPayload, PaymentRequirements, SvmPayload, ExtraFields},
+        requests::{PaymentPayload, PaymentRequirements, SvmPayload, ExtraFields, VerifyRequest},
     };
 
+    #[tokio::test]
+    async fn test_lines_split_across_chunks() {
+        let chunks: Vec<Result<&'static str, Infallible>> = vec![Ok("{\"a\""), Ok(":1}\n\n{\"b\":2}\n{\"c"), Ok("\":3}")];
+        let body = Body::from_stream(futures::stream::iter(chunks));
+        let split: Vec<Vec<u8>> = lines(body, 64).map(Result::unwrap).collect().await;
+        assert_eq!(
+            split,
+            vec![b"{\"a\":1}\n".to_vec(), b"\n".to_vec(), b"{\"b\":2}\n".to_vec(), b"{\"c\":3}".to_vec()]
+        );
+
+        let body = Body::from("x".repeat(100));
+        let split: Vec<_> = lines(body, 64).collect().await;
+        assert_eq!(split.len(), 1);
+        assert!(split[0].is_err());
+    }
+
     #[test]
     fn test_batch_empty() {
         // Test empty batch
@@ -132,13 +361,12 @@ mod tests {
                 mime_type: "application/json".to_string(),
                 max_timeout_seconds: 30,
                 output_schema: None,
-                extra: ExtraFields {
-                    fee_payer: "fee_payer".to_string(),
-                },
+                extra: ExtraFields::new("fee_payer"),
             },
+            dry_run: false,
         };
 
-        let batch = vec![request.clone(), request.clone()];
+        let batch = [request.clone(), request.clone()];
         assert_eq!(batch.len(), 2);
     }
 }
@@ -3,12 +3,15 @@ use serde_json::{json, Value};
 use crate::config::Config;
 
 /// Health check endpoint
+///
+/// Liveness only, without RPC calls; `/admin/health` reports whether this
+/// instance can settle.
 #[utoipa::path(
     get,
     path = "/health",
     responses(
         (status = 200, description = "Service is healthy", body = Value,
-         example = json!({"status": "ok", "version": "1.0.0"}))
+         example = json!({"status": "ok", "version": "1.0.0", "fee_payer": "FeePayer1111111111111111111111111111111111"}))
     ),
     tag = "Health"
 )]
@@ -18,7 +21,8 @@ pub async fn health_check(State(config): State<Config>) -> Json<Value> {
     
     Json(json!({
         "status": "ok",
-        "version": env!("CARGO_PKG_VERSION")
+        "version": env!("CARGO_PKG_VERSION"),
+        "fee_payer": config.fee_payer_signer.pubkey().to_string(),
     }))
 }
 
@@ -1,7 +1,13 @@
 pub mod admin;
+pub mod admin_ui;
 pub mod batch;
 pub mod health;
+pub mod pubkey;
+pub mod quote;
 pub mod settle;
+pub mod settlements;
 pub mod supported;
+pub mod validate;
 pub mod verify;
+pub mod ws;
 
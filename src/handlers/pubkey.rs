@@ -0,0 +1,21 @@
+use axum::{extract::State, Json};
+use crate::config::Config;
+use crate::middleware::response_signature::RESPONSE_DOMAIN;
+use crate::types::responses::PubkeyResponse;
+
+/// GET /pubkey - Key the facilitator signs responses and receipts with
+#[utoipa::path(
+    get,
+    path = "/pubkey",
+    responses(
+        (status = 200, description = "Facilitator signing key", body = PubkeyResponse)
+    ),
+    tag = "Information"
+)]
+pub async fn pubkey(State(config): State<Config>) -> Json<PubkeyResponse> {
+    Json(PubkeyResponse {
+        pubkey: config.fee_payer_signer.pubkey().to_string(),
+        signs_responses: config.sign_responses,
+        response_domain: hex::encode(RESPONSE_DOMAIN),
+    })
+}
@@ -0,0 +1,94 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::requirements::usdc_mint;
+use crate::solana::builder::{CREATE_ATA_COMPUTE_UNIT_LIMIT, DEFAULT_COMPUTE_UNIT_PRICE, TRANSFER_COMPUTE_UNIT_LIMIT};
+use crate::solana::verifier::{estimate_fee_lamports, DEFAULT_REQUIRED_SIGNERS, MAX_COMPUTE_UNIT_PRICE};
+use crate::types::responses::QuoteResponse;
+
+#[derive(Debug, Deserialize)]
+pub struct QuoteParams {
+    pub network: String,
+    /// Token mint (default: USDC of the network)
+    pub asset: Option<String>,
+    /// Amount in the asset's base units
+    pub amount: Option<String>,
+}
+
+/// GET /quote - The facilitator's current policy for a payment
+///
+/// What resource servers need to build `PaymentRequirements` this
+/// facilitator accepts: its fee payer, the highest compute unit price it
+/// signs for, the network fee it expects to pay, whether it funds the
+/// recipient's token account, and how long payments stay valid.
+#[utoipa::path(
+    get,
+    path = "/quote",
+    params(
+        ("network" = String, Query, description = "Network to pay on, e.g. `solana-devnet`"),
+        ("asset" = Option<String>, Query, description = "Token mint (default: USDC of the network)"),
+        ("amount" = Option<String>, Query, description = "Amount in the asset's base units")
+    ),
+    responses(
+        (status = 200, description = "Current policy", body = QuoteResponse),
+        (status = 400, description = "Unsupported network, asset or amount")
+    ),
+    tag = "Information"
+)]
+pub async fn quote(
+    State(config): State<Config>,
+    Query(params): Query<QuoteParams>,
+) -> Result<Json<QuoteResponse>, AppError> {
+    let settings = config.settings.load();
+
+    if !config.schemes.has_network(&params.network) {
+        return Err(AppError::BadRequest(format!("Network '{}' is not supported", params.network)));
+    }
+
+    let asset = match &params.asset {
+        Some(asset) => Pubkey::from_str(asset)
+            .map_err(|_| AppError::BadRequest(format!("Invalid asset '{}'", asset)))?,
+        None => usdc_mint(&params.network).ok_or_else(|| {
+            AppError::BadRequest(format!("No default asset for network '{}'; set one", params.network))
+        })?,
+    };
+    if !settings.is_mint_allowed(&asset.to_string()) {
+        return Err(AppError::BadRequest(format!("Asset {} is not accepted by this facilitator", asset)));
+    }
+
+    let amount = params
+        .amount
+        .as_deref()
+        .map(|amount| match amount.parse::<u64>() {
+            Ok(amount) if amount > 0 => Ok(amount.to_string()),
+            _ => Err(AppError::BadRequest("amount must be a positive integer".to_string())),
+        })
+        .transpose()?;
+
+    let fee = |unit_limit: u32| {
+        estimate_fee_lamports(DEFAULT_REQUIRED_SIGNERS, unit_limit as u64, DEFAULT_COMPUTE_UNIT_PRICE)
+    };
+    let budget = &config.ata_rent_budget;
+
+    Ok(Json(QuoteResponse {
+        scheme: "exact".to_string(),
+        network: params.network,
+        asset: asset.to_string(),
+        amount,
+        fee_payer: config.fee_payer_signer.pubkey().to_string(),
+        max_compute_unit_price: MAX_COMPUTE_UNIT_PRICE,
+        compute_unit_price: DEFAULT_COMPUTE_UNIT_PRICE,
+        expected_fee_lamports: fee(TRANSFER_COMPUTE_UNIT_LIMIT),
+        expected_fee_with_ata_lamports: fee(CREATE_ATA_COMPUTE_UNIT_LIMIT),
+        ata_creation_subsidized: budget.enabled() && budget.remaining() > 0,
+        max_timeout_seconds: settings.payment_expiry_seconds,
+        clock_skew_seconds: settings.clock_skew_seconds,
+    }))
+}
@@ -1,14 +1,16 @@
-use axum::{extract::State, Json};
-use solana_sdk::signer::Signer;
+use axum::{extract::State, Extension};
+use std::sync::Arc;
 
 use crate::{
     config::Config,
+    events::LiveEventKind,
     handlers::verify::verify,
-    solana::{
-        decoder::decode_transaction_from_base64,
-        signer::{load_keypair_from_base58, sign_transaction_as_fee_payer},
-        submitter::{submit_transaction_with_retries, signature_to_string},
-    },
+    middleware::{msgpack::Negotiated, request_id::RequestId},
+    networks::NetworkContext,
+    receipts::{PaymentReceipt, SignedReceipt},
+    settlements::{SettlementRecord, SettlementStatus},
+    solana::submitter::BlockhashExpired,
+    tenants::Tenant,
     types::{
         requests::SettleRequest,
         responses::SettleResponse,
@@ -27,132 +29,264 @@ use crate::{
 )]
 pub async fn settle(
     State(config): State<Config>,
-    Json(request): Json<SettleRequest>,
-) -> Json<SettleResponse> {
+    request_id: Option<Extension<RequestId>>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    Negotiated(request): Negotiated<SettleRequest>,
+) -> Negotiated<SettleResponse> {
+    // Settled with the tenant's fee payer, if it has one
+    let config = config.for_tenant(tenant.map(|Extension(tenant)| tenant));
     let network = request.payment_requirements.network.clone();
     
     // Record settle request metric
     config.metrics.settle_requests.with_label_values(&[&network, &"attempt".to_string()]).inc();
     
+    // Offline verification can't vouch for the accounts, so don't pay to submit
+    if config.offline_verify {
+        tracing::warn!("📴 Settlement refused: facilitator is verifying offline");
+        return Negotiated(SettleResponse {
+            success: false,
+            network,
+            transaction: String::new(),
+            payer: None,
+            error_reason: Some("offline_mode".to_string()),
+            receipt: None,
+            slot: None,
+            block_time: None,
+            commitment: None,
+        });
+    }
+
+    // The fee payer already spent today's budget
+    if config.fee_spend.is_exhausted() {
+        tracing::warn!("💸 Settlement refused: fee payer daily budget exhausted");
+        return Negotiated(SettleResponse {
+            success: false,
+            network,
+            transaction: String::new(),
+            payer: None,
+            error_reason: Some("fee_budget_exhausted".to_string()),
+            receipt: None,
+            slot: None,
+            block_time: None,
+            commitment: None,
+        });
+    }
+
     // First, verify the transaction
     let verify_request = crate::types::requests::VerifyRequest {
         payment_payload: request.payment_payload.clone(),
         payment_requirements: request.payment_requirements.clone(),
+        dry_run: false,
     };
     
-    let verify_response = verify(State(config.clone()), Json(verify_request)).await.0;
+    let verify_response = verify(State(config.clone()), None, None, Negotiated(verify_request.clone())).await.0;
     
     if !verify_response.is_valid {
-        return Json(SettleResponse {
+        return Negotiated(SettleResponse {
             success: false,
             network,
             transaction: String::new(),
             payer: verify_response.payer,
             error_reason: verify_response.invalid_reason,
+            receipt: None,
+            slot: None,
+            block_time: None,
+            commitment: None,
         });
     }
     
     let payer = verify_response.payer;
+
+    // Claim what the settlement uses up (e.g. a subscription's billing
+    // window), so a concurrent settlement can't use it too
+    let scheme = config.schemes.resolve(&verify_request).ok().cloned();
+    let payer_key = payer.as_deref().unwrap_or_default();
+    if let Some(Err(e)) = scheme.as_ref().map(|scheme| scheme.begin_settlement(&config, &verify_request, payer_key)) {
+        return Negotiated(SettleResponse {
+            success: false,
+            network,
+            transaction: String::new(),
+            payer,
+            error_reason: Some(e.as_str().to_string()),
+            receipt: None,
+            slot: None,
+            block_time: None,
+            commitment: None,
+        });
+    }
     
     // Settle the transaction
-    match settle_transaction(&config, &request).await {
+    let request_id = request_id.map(|Extension(RequestId(id))| id);
+    let result = settle_transaction(&config, &request, payer.as_deref(), request_id.as_deref()).await;
+    if let Some(scheme) = &scheme {
+        scheme.end_settlement(&config, &verify_request, payer_key, result.is_ok());
+    }
+
+    let confirmation = match (&result, config.networks.get(&network)) {
+        (Ok(signature), Some(adapter)) => {
+            let (adapter, config, network, signature) =
+                (adapter.clone(), config.clone(), network.clone(), signature.clone());
+            tokio::task::spawn_blocking(move || adapter.confirm(&NetworkContext::new(&config, &network), &signature))
+                .await
+                .ok()
+                .flatten()
+        }
+        _ => None,
+    };
+
+    // Keep the outcome for reconciliation (`GET /settlements`)
+    let requirements = &request.payment_requirements;
+    let record = SettlementRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        signature: result.as_ref().ok().cloned(),
+        network: network.clone(),
+        payer: payer.clone(),
+        recipient: requirements.pay_to.clone(),
+        amount: requirements.max_amount_required.clone(),
+        mint: requirements.asset.clone(),
+        slot: confirmation.as_ref().map(|confirmation| confirmation.slot),
+        status: if result.is_ok() { SettlementStatus::Success } else { SettlementStatus::Failure },
+        error: result.as_ref().err().map(|e| e.to_string()),
+        created_at: chrono::Utc::now(),
+        tenant: config.tenant_id().map(str::to_string),
+    };
+    let receipt = match &result {
+        Ok(_) => sign_receipt(&config, &record).await,
+        Err(_) => None,
+    };
+    config.settlements.record(record);
+
+    match result {
         Ok(signature) => {
             tracing::info!("Transaction settled successfully: {}", signature);
             config.metrics.settle_requests.with_label_values(&[&network, &"success".to_string()]).inc();
+            config.metrics.record_tenant_request(config.tenant_id(), "settle", "success");
+            config.events.publish(
+                LiveEventKind::Settle,
+                &network,
+                true,
+                serde_json::json!({ "signature": signature, "payer": payer }),
+            );
             
             // Send webhook notification (async, non-blocking)
             if let Some(webhook_config) = &config.webhook {
-                let webhook_config = webhook_config.clone();
-                let sig_clone = signature.clone();
-                let payer_clone = payer.clone();
-                let network_clone = network.clone();
-                tokio::spawn(async move {
-                    let payload = crate::webhooks::WebhookPayload::new(
-                        crate::webhooks::WebhookEvent::SettlementSuccess,
-                        serde_json::json!({
-                            "signature": sig_clone,
-                            "payer": payer_clone,
-                            "network": network_clone,
-                        }),
-                    );
-                    let _ = crate::webhooks::send_webhook(&webhook_config, &payload).await;
-                });
+                let payload = crate::webhooks::WebhookPayload::new(
+                    crate::webhooks::WebhookEvent::SettlementSuccess,
+                    serde_json::json!({
+                        "signature": signature,
+                        "payer": payer,
+                        "network": network,
+                    }),
+                );
+                crate::webhooks::dispatch_webhook_for(webhook_config, config.tenant.as_deref(), payload);
             }
+            crate::alerts::check_fee_payer_balance(&config);
             
-            Json(SettleResponse {
+            Negotiated(SettleResponse {
                 success: true,
                 network,
                 transaction: signature,
                 payer,
                 error_reason: None,
+                receipt,
+                slot: confirmation.as_ref().map(|confirmation| confirmation.slot),
+                block_time: confirmation.as_ref().and_then(|confirmation| confirmation.block_time),
+                commitment: confirmation.and_then(|confirmation| confirmation.commitment),
             })
         }
         Err(e) => {
-            tracing::error!("Settlement failed: {}", e);
+            tracing::error!(network = %network, payer = ?payer, "Settlement failed: {}", e);
             config.metrics.settle_requests.with_label_values(&[&network, &"failure".to_string()]).inc();
+            config.metrics.record_tenant_request(config.tenant_id(), "settle", "failure");
+            config.events.publish(
+                LiveEventKind::Settle,
+                &network,
+                false,
+                serde_json::json!({ "error": e.to_string(), "payer": payer }),
+            );
             
             // Send webhook notification (async, non-blocking)
             if let Some(webhook_config) = &config.webhook {
-                let webhook_config = webhook_config.clone();
-                let error_msg = format!("{}", e);
-                let payer_clone = payer.clone();
-                let network_clone = network.clone();
-                tokio::spawn(async move {
-                    let payload = crate::webhooks::WebhookPayload::new(
-                        crate::webhooks::WebhookEvent::SettlementFailure,
-                        serde_json::json!({
-                            "error": error_msg,
-                            "payer": payer_clone,
-                            "network": network_clone,
-                        }),
-                    );
-                    let _ = crate::webhooks::send_webhook(&webhook_config, &payload).await;
-                });
+                let payload = crate::webhooks::WebhookPayload::new(
+                    crate::webhooks::WebhookEvent::SettlementFailure,
+                    serde_json::json!({
+                        "error": format!("{}", e),
+                        "payer": payer,
+                        "network": network,
+                    }),
+                );
+                crate::webhooks::dispatch_webhook_for(webhook_config, config.tenant.as_deref(), payload);
             }
             
-            Json(SettleResponse {
+            Negotiated(SettleResponse {
                 success: false,
                 network,
                 transaction: String::new(),
                 payer,
-                error_reason: Some(format!("settle_error: {}", e)),
+                error_reason: Some(if e.is::<BlockhashExpired>() {
+                    "blockhash_expired".to_string()
+                } else {
+                    format!("settle_error: {}", e)
+                }),
+                receipt: None,
+                slot: None,
+                block_time: None,
+                commitment: None,
             })
         }
     }
 }
 
-/// Internal settlement logic
+/// Sign the receipt of a successful settlement
+///
+/// The settlement already happened, so failures are logged and the response
+/// goes out without a receipt.
+pub(crate) async fn sign_receipt(config: &Config, record: &SettlementRecord) -> Option<SignedReceipt> {
+    let signer = config.fee_payer_signer.as_ref();
+    let signed = match PaymentReceipt::from_settlement(record, &signer.pubkey()) {
+        Ok(receipt) => receipt.sign(signer).await,
+        Err(e) => Err(e),
+    };
+
+    match signed {
+        Ok(receipt) => Some(receipt),
+        Err(e) => {
+            tracing::warn!("⚠️  Failed to sign receipt for {:?}: {:#}", record.signature, e);
+            None
+        }
+    }
+}
+
+/// Internal settlement logic, through the adapter of the payment's network
+///
+/// Returns the transaction signature.
 async fn settle_transaction(
     config: &Config,
     request: &SettleRequest,
+    payer: Option<&str>,
+    request_id: Option<&str>,
 ) -> Result<String, anyhow::Error> {
+    let network = &request.payment_requirements.network;
+    let adapter = config
+        .networks
+        .get(network)
+        .ok_or_else(|| anyhow::anyhow!("No adapter for network {}", network))?;
+    let ctx = NetworkContext::new(config, network);
+
     // 1. Decode the transaction
-    let mut transaction = decode_transaction_from_base64(
-        &request.payment_payload.payload.transaction
-    )?;
+    let mut transaction = config.metrics.time_step("decode", || adapter.decode(&request.payment_payload))?;
     
     tracing::info!("Decoded transaction for settlement");
     
-    // 2. Load fee payer keypair
-    let fee_payer = load_keypair_from_base58(&config.fee_payer_private_key)?;
-    
-    tracing::info!("Loaded fee payer keypair: {}", fee_payer.pubkey());
-    
-    // 3. Sign the transaction as fee payer
-    sign_transaction_as_fee_payer(&mut transaction, &fee_payer)?;
+    // 2. Sign the transaction as fee payer (local keypair or KMS)
+    config.metrics.time_step_async("sign", adapter.sign(&ctx, &mut transaction)).await?;
     
     tracing::info!("Transaction signed by fee payer");
     
-    // 4. Use shared RPC client (connection pooling)
-    let rpc_client = &config.rpc_client;
-    
-    // 5. Submit transaction with retries (3 attempts, 30 second timeout each)
-    let signature = submit_transaction_with_retries(
-        rpc_client.as_ref(),
-        &transaction,
-        3,  // max retries
-        30, // timeout seconds
-    ).await?;
-    
-    Ok(signature_to_string(&signature))
+    // 3. Submit it with retries, auditing every attempt
+    adapter
+        .submit(&ctx, &transaction, &mut |attempt| {
+            config.audit_logger.log_settlement_attempt(network, payer, request_id, attempt)
+        })
+        .await
 }
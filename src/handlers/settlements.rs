@@ -0,0 +1,175 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::handlers::settle::sign_receipt;
+use crate::middleware::msgpack::Negotiated;
+use crate::receipts::SignedReceipt;
+use crate::settlements::{SettlementQuery, SettlementRecord, SettlementStatus};
+use crate::solana::submitter::{signature_status, string_to_signature};
+use crate::tenants::Tenant;
+use crate::types::responses::SettlementStatusResponse;
+use solana_transaction_status::TransactionConfirmationStatus;
+
+/// GET /settlements - Settlement history, newest first
+///
+/// Filters: `payer`, `recipient`, `status`, `network`, `from`/`to`
+/// (RFC 3339), with `limit`/`offset` pagination. Requires settlement history
+/// (`SETTLEMENT_DB_PATH`). With tenants, only the caller's settlements are
+/// listed.
+pub async fn list_settlements(
+    State(config): State<Config>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    Query(mut query): Query<SettlementQuery>,
+) -> Result<Response, AppError> {
+    query.tenant = tenant.map(|Extension(tenant)| tenant.id.clone());
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from >= to {
+            return Err(AppError::BadRequest("'from' must be before 'to'".to_string()));
+        }
+    }
+
+    let settlements = fetch_settlements(&config, &query).await?;
+
+    // A full page means there may be more
+    let next_offset = (settlements.len() as u64 == u64::from(query.limit()))
+        .then(|| query.offset() + settlements.len() as u64);
+
+    let mut response = Negotiated(json!({
+        "settlements": settlements,
+        "limit": query.limit(),
+        "offset": query.offset(),
+        "next_offset": next_offset,
+    }))
+    .into_response();
+
+    if let Some(next_offset) = next_offset {
+        response.headers_mut().insert("x-next-offset", next_offset.into());
+    }
+
+    Ok(response)
+}
+
+/// GET /settlements/{signature} - One settlement by transaction signature
+///
+/// Another tenant's settlement is reported as not found.
+pub async fn get_settlement(
+    State(config): State<Config>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    Path(signature): Path<String>,
+) -> Result<Negotiated<SettlementRecord>, AppError> {
+    let config = config.for_tenant(tenant.map(|Extension(tenant)| tenant));
+    fetch_settlement(&config, &signature)
+        .await?
+        .map(Negotiated)
+        .ok_or_else(|| AppError::NotFound(format!("Settlement {} not found", signature)))
+}
+
+/// GET /receipt/{signature} - Signed receipt of a successful settlement
+///
+/// Receipts are re-signed from the settlement history; signing is
+/// deterministic, so this returns the receipt given by `/settle` (signed
+/// by the tenant's fee payer, if it has one).
+pub async fn get_receipt(
+    State(config): State<Config>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    Path(signature): Path<String>,
+) -> Result<Negotiated<SignedReceipt>, AppError> {
+    let config = config.for_tenant(tenant.map(|Extension(tenant)| tenant));
+    let record = fetch_settlement(&config, &signature)
+        .await?
+        .filter(|record| record.status == SettlementStatus::Success)
+        .ok_or_else(|| AppError::NotFound(format!("No receipt for {}", signature)))?;
+
+    sign_receipt(&config, &record)
+        .await
+        .map(Negotiated)
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Failed to sign receipt for {}", signature)))
+}
+
+/// GET /settle/{signature}/status - Confirmation status of a settlement
+///
+/// Queries the cluster (`getSignatureStatuses`, including ledger history)
+/// and, when enabled, the settlement history, so resource servers can
+/// confirm finality independently of the `/settle` response. The recorded
+/// settlement is only included for the tenant it belongs to.
+pub async fn settlement_status(
+    State(config): State<Config>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    Path(signature): Path<String>,
+) -> Result<Negotiated<SettlementStatusResponse>, AppError> {
+    let config = config.for_tenant(tenant.map(|Extension(tenant)| tenant));
+    let parsed = string_to_signature(&signature).map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let rpc_client = config.rpc_client.clone();
+    let status = tokio::task::spawn_blocking(move || signature_status(rpc_client.as_ref(), &parsed))
+        .await
+        .map_err(|e| anyhow::anyhow!("Status lookup panicked: {}", e))??;
+
+    let settlement = if config.settlements.is_enabled() {
+        fetch_settlement(&config, &signature).await?
+    } else {
+        None
+    };
+
+    if status.is_none() && settlement.is_none() {
+        return Err(AppError::NotFound(format!("Transaction {} not found", signature)));
+    }
+
+    Ok(Negotiated(SettlementStatusResponse {
+        confirmation_status: status.as_ref().map(|status| {
+            match status.confirmation_status() {
+                TransactionConfirmationStatus::Processed => "processed",
+                TransactionConfirmationStatus::Confirmed => "confirmed",
+                TransactionConfirmationStatus::Finalized => "finalized",
+            }
+            .to_string()
+        }),
+        slot: status.as_ref().map(|status| status.slot).or_else(|| settlement.as_ref().and_then(|record| record.slot)),
+        confirmations: status.as_ref().and_then(|status| status.confirmations),
+        error: status
+            .as_ref()
+            .and_then(|status| status.err.as_ref().map(|e| e.to_string()))
+            .or_else(|| settlement.as_ref().and_then(|record| record.error.clone())),
+        signature,
+        settlement,
+    }))
+}
+
+#[cfg(feature = "settlement-history")]
+fn settlement_store(config: &Config) -> Result<&crate::settlement_store::SettlementStore, AppError> {
+    config
+        .settlements
+        .store()
+        .ok_or_else(|| AppError::NotImplemented("Settlement history is not configured".to_string()))
+}
+
+#[cfg(feature = "settlement-history")]
+async fn fetch_settlements(config: &Config, query: &SettlementQuery) -> Result<Vec<SettlementRecord>, AppError> {
+    Ok(settlement_store(config)?.query(query).await?)
+}
+
+#[cfg(feature = "settlement-history")]
+async fn fetch_settlement(config: &Config, signature: &str) -> Result<Option<SettlementRecord>, AppError> {
+    Ok(settlement_store(config)?.get(signature, config.tenant_id()).await?)
+}
+
+#[cfg(not(feature = "settlement-history"))]
+async fn fetch_settlements(_config: &Config, _query: &SettlementQuery) -> Result<Vec<SettlementRecord>, AppError> {
+    Err(AppError::NotImplemented(
+        "Settlement history requires the settlement-history feature".to_string(),
+    ))
+}
+
+#[cfg(not(feature = "settlement-history"))]
+async fn fetch_settlement(_config: &Config, _signature: &str) -> Result<Option<SettlementRecord>, AppError> {
+    Err(AppError::NotImplemented(
+        "Settlement history requires the settlement-history feature".to_string(),
+    ))
+}
@@ -1,5 +1,6 @@
-use axum::Json;
-use crate::types::responses::{SchemeSupport, SupportedResponse};
+use axum::{extract::State, Json};
+use crate::config::Config;
+use crate::types::responses::SupportedResponse;
 
 /// GET /supported - Returns supported payment schemes and networks
 #[utoipa::path(
@@ -10,14 +11,8 @@ use crate::types::responses::{SchemeSupport, SupportedResponse};
     ),
     tag = "Information"
 )]
-pub async fn supported() -> Json<SupportedResponse> {
+pub async fn supported(State(config): State<Config>) -> Json<SupportedResponse> {
     Json(SupportedResponse {
-        schemes: vec![SchemeSupport {
-            scheme: "exact".to_string(),
-            networks: vec![
-                "solana-devnet".to_string(),
-                "solana".to_string(),
-            ],
-        }],
+        schemes: config.schemes.supported(),
     })
 }
@@ -0,0 +1,290 @@
+use axum::extract::State;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use solana_sdk::{pubkey::Pubkey, sanitize::Sanitize, transaction::Transaction};
+use std::str::FromStr;
+
+use crate::{
+    config::Config,
+    error::VerificationError,
+    middleware::msgpack::Negotiated,
+    rent_budget::AtaRentBudget,
+    requirements::X402_VERSION,
+    solana::{decoder::decode_transaction_from_base64, verifier::*},
+    types::{
+        requests::{PaymentPayload, PaymentRequirements, VerifyRequest},
+        responses::{FieldReport, ValidateResponse},
+    },
+};
+
+const TRANSACTION: &str = "payment_payload.payload.transaction";
+
+/// POST /validate - Check a payment's structure without RPC calls
+///
+/// Lints the envelope (schema, version, scheme, network, addresses, amount)
+/// and the transaction (base64 encoding, instruction layout), reporting
+/// every field. Nothing is looked up on chain and the transaction isn't
+/// marked as seen, so validated payloads can still be verified and settled.
+#[utoipa::path(
+    post,
+    path = "/validate",
+    request_body = VerifyRequest,
+    responses(
+        (status = 200, description = "Per-field validation report", body = ValidateResponse)
+    ),
+    tag = "Payment"
+)]
+pub async fn validate(State(config): State<Config>, Negotiated(body): Negotiated<Value>) -> Negotiated<ValidateResponse> {
+    let mut report = Report::default();
+    validate_request(&config, &body, &mut report);
+
+    tracing::debug!(
+        "🧾 Validated payload: {}/{} fields invalid",
+        report.fields.iter().filter(|field| !field.valid).count(),
+        report.fields.len()
+    );
+
+    Negotiated(ValidateResponse {
+        is_valid: report.fields.iter().all(|field| field.valid),
+        fields: report.fields,
+    })
+}
+
+#[derive(Default)]
+struct Report {
+    fields: Vec<FieldReport>,
+}
+
+impl Report {
+    fn pass(&mut self, field: &str) {
+        self.fields.push(FieldReport {
+            field: field.to_string(),
+            valid: true,
+            code: None,
+            message: None,
+        });
+    }
+
+    fn fail(&mut self, field: &str, code: &str, message: impl Into<String>) {
+        self.fields.push(FieldReport {
+            field: field.to_string(),
+            valid: false,
+            code: Some(code.to_string()),
+            message: Some(message.into()),
+        });
+    }
+
+    /// Record a verifier check of `field`, passing through its value
+    fn check<T>(&mut self, field: &str, result: Result<T, VerificationError>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.pass(field);
+                Some(value)
+            }
+            Err(e) => {
+                self.fail(field, e.as_str(), e.hint());
+                None
+            }
+        }
+    }
+
+    /// Deserialize the object at `field` of `body`
+    fn parse<T: DeserializeOwned>(&mut self, body: &Value, field: &str) -> Option<T> {
+        let Some(value) = body.get(field) else {
+            self.fail(field, "invalid_schema", "missing field");
+            return None;
+        };
+        match serde_json::from_value(value.clone()) {
+            Ok(parsed) => {
+                self.pass(field);
+                Some(parsed)
+            }
+            Err(e) => {
+                self.fail(field, "invalid_schema", e.to_string());
+                None
+            }
+        }
+    }
+
+    fn pubkey(&mut self, field: &str, value: &str) {
+        match Pubkey::from_str(value) {
+            Ok(_) => self.pass(field),
+            Err(_) => self.fail(field, "invalid_pubkey", format!("'{}' is not a base58 Solana address", value)),
+        }
+    }
+}
+
+fn validate_request(config: &Config, body: &Value, report: &mut Report) {
+    let payload: Option<PaymentPayload> = report.parse(body, "payment_payload");
+    let requirements: Option<PaymentRequirements> = report.parse(body, "payment_requirements");
+    let (Some(payload), Some(requirements)) = (payload, requirements) else {
+        return;
+    };
+
+    validate_envelope(config, &payload, &requirements, report);
+
+    let transaction = match decode_transaction_from_base64(&payload.payload.transaction) {
+        Ok(transaction) => transaction,
+        Err(e) => return report.fail(TRANSACTION, "invalid_transaction_encoding", e.to_string()),
+    };
+    // The instruction checks index accounts, so the message must be consistent
+    if let Err(e) = transaction.sanitize() {
+        return report.fail(TRANSACTION, "invalid_transaction_encoding", format!("Malformed transaction: {}", e));
+    }
+    report.pass(TRANSACTION);
+
+    validate_instructions(
+        &transaction,
+        &requirements,
+        config.allow_unchecked_transfer,
+        &config.ata_rent_budget,
+        report,
+    );
+
+    // Signers beyond the fee payer and payer
+    report.check(
+        &format!("{}.header.numRequiredSignatures", TRANSACTION),
+        verify_signer_count(&transaction, config.settings.load().required_signers),
+    );
+}
+
+/// Fields of the payload and requirements, checked like `/verify` does
+fn validate_envelope(
+    config: &Config,
+    payload: &PaymentPayload,
+    requirements: &PaymentRequirements,
+    report: &mut Report,
+) {
+    let settings = config.settings.load();
+
+    if payload.x402_version == X402_VERSION {
+        report.pass("payment_payload.x402Version");
+    } else {
+        report.fail(
+            "payment_payload.x402Version",
+            "unsupported_version",
+            format!("Use x402 version {}", X402_VERSION),
+        );
+    }
+
+    let scheme = if payload.scheme != requirements.scheme || !config.schemes.has_scheme(&payload.scheme) {
+        Err(VerificationError::UnsupportedScheme)
+    } else {
+        Ok(())
+    };
+    report.check("payment_payload.scheme", scheme);
+
+    let network = if payload.network != requirements.network {
+        Err(VerificationError::InvalidNetwork)
+    } else {
+        Ok(())
+    };
+    report.check("payment_payload.network", network);
+
+    let supported = if !config.schemes.has_network(&requirements.network) {
+        Err(VerificationError::InvalidNetwork)
+    } else {
+        Ok(())
+    };
+    report.check("payment_requirements.network", supported);
+
+    if payload.timestamp.is_some() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or_default();
+        report.check(
+            "payment_payload.timestamp",
+            payload.check_timestamp(
+                now,
+                requirements.expiry_seconds(settings.payment_expiry_seconds),
+                settings.clock_skew_seconds,
+            ),
+        );
+    }
+
+    match requirements.max_amount_required.parse::<u64>() {
+        Ok(amount) if amount > 0 => report.pass("payment_requirements.maxAmountRequired"),
+        _ => report.fail(
+            "payment_requirements.maxAmountRequired",
+            "invalid_amount",
+            "Use a positive integer amount in the asset's base units",
+        ),
+    }
+
+    if settings.is_mint_allowed(&requirements.asset) {
+        report.pubkey("payment_requirements.asset", &requirements.asset);
+    } else {
+        report.fail(
+            "payment_requirements.asset",
+            "asset_not_accepted",
+            format!("Asset {} is not accepted by this facilitator", requirements.asset),
+        );
+    }
+    report.pubkey("payment_requirements.payTo", &requirements.pay_to);
+    report.pubkey("payment_requirements.extra.feePayer", &requirements.extra.fee_payer);
+    if let Some(scheme) = config.schemes.get(&requirements.scheme, &requirements.network) {
+        report.check("payment_requirements.extra", scheme.validate_extra(&requirements.extra));
+    }
+}
+
+/// Instruction layout checks of `/verify` (no account lookups)
+fn validate_instructions(
+    transaction: &Transaction,
+    requirements: &PaymentRequirements,
+    allow_unchecked_transfer: bool,
+    ata_rent_budget: &AtaRentBudget,
+    report: &mut Report,
+) {
+    let message = &transaction.message;
+    let field = |index: usize| format!("{}.instructions[{}]", TRANSACTION, index);
+
+    let has_create_ata = report.check(
+        &format!("{}.instructions", TRANSACTION),
+        verify_instruction_count(transaction),
+    );
+    if let Some(instruction) = message.instructions.first() {
+        report.check(&field(0), verify_compute_limit_instruction(instruction, message));
+    }
+    if let Some(instruction) = message.instructions.get(1) {
+        report.check(&field(1), verify_compute_price_instruction(instruction, message));
+    }
+
+    // Checks involving the fee payer need a valid address for it
+    let Ok(fee_payer) = Pubkey::from_str(&requirements.extra.fee_payer) else {
+        return;
+    };
+    report.check(&format!("{}.accountKeys[0]", TRANSACTION), verify_fee_payer_position(transaction, &fee_payer));
+    // With a rent budget the fee payer may fund the recipient's token account
+    let fee_payer_may_fund = ata_rent_budget.enabled();
+    report.check(
+        &format!("{}.accountKeys", TRANSACTION),
+        if fee_payer_may_fund {
+            verify_fee_payer_safety_funding_ata(transaction, &fee_payer)
+        } else {
+            verify_fee_payer_safety(transaction, &fee_payer)
+        },
+    );
+
+    let Some(has_create_ata) = has_create_ata else {
+        return;
+    };
+    if has_create_ata {
+        report.check(&field(2), verify_create_ata_instruction(&message.instructions[2], message, requirements));
+        report.check(
+            &format!("{}.accounts[0]", field(2)),
+            verify_create_ata_funder(&message.instructions[2], message, &fee_payer, fee_payer_may_fund),
+        );
+    }
+    let transfer_idx = if has_create_ata { 3 } else { 2 };
+    report.check(
+        &field(transfer_idx),
+        parse_transfer_instruction(
+            &message.instructions[transfer_idx],
+            message,
+            requirements,
+            &fee_payer,
+            allow_unchecked_transfer,
+        ),
+    );
+}
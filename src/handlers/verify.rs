@@ -1,25 +1,52 @@
-use axum::{extract::State, Json};
-use solana_sdk::pubkey::Pubkey;
-use std::str::FromStr;
+use axum::{extract::{Query, State}, http::HeaderMap, Extension};
+use serde::Deserialize;
+use std::sync::Arc;
 
 use crate::{
     config::Config,
     error::VerificationError,
-    solana::{
-        decoder::decode_transaction_from_base64,
-        verifier::*,
-    },
+    events::LiveEventKind,
+    middleware::msgpack::Negotiated,
+    networks::NetworkContext,
+    schemes::{Diagnosis, Verified, VerifyContext},
+    solana::verifier::PrefetchedAccounts,
+    tenants::Tenant,
     types::{
-        requests::VerifyRequest,
+        requests::{HeaderVerifyRequest, PaymentPayload, PaymentRequirements, VerifyRequest, PAYMENT_HEADER},
         responses::VerifyResponse,
     },
 };
 
+/// How `/verify` reports failures
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifyMode {
+    /// Stop at the first failed check
+    #[default]
+    Standard,
+    /// Run every check and return all failures (a dry run: the
+    /// transaction isn't marked as seen)
+    Diagnostic,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct VerifyParams {
+    #[serde(default)]
+    pub mode: VerifyMode,
+}
+
 /// POST /verify - Verify a payment transaction
+///
+/// With `?mode=diagnostic`, every check runs and `failures` lists each
+/// failed one with its code and a hint. With `dryRun`, the verification has
+/// no side effects. Requests of a tenant are also held to its policy.
 #[utoipa::path(
     post,
     path = "/verify",
     request_body = VerifyRequest,
+    params(
+        ("mode" = Option<String>, Query, description = "`diagnostic` to report every failed check")
+    ),
     responses(
         (status = 200, description = "Verification result", body = VerifyResponse)
     ),
@@ -27,202 +54,368 @@ use crate::{
 )]
 pub async fn verify(
     State(config): State<Config>,
-    Json(request): Json<VerifyRequest>,
-) -> Json<VerifyResponse> {
-    // Record metrics
+    tenant: Option<Extension<Arc<Tenant>>>,
+    params: Option<Query<VerifyParams>>,
+    Negotiated(request): Negotiated<VerifyRequest>,
+) -> Negotiated<VerifyResponse> {
+    let config = config.for_tenant(tenant.map(|Extension(tenant)| tenant));
+
+    if params.is_some_and(|Query(params)| params.mode == VerifyMode::Diagnostic) {
+        record_verify_request(&config, &request);
+        return Negotiated(diagnose_payment(&config, &request));
+    }
+    Negotiated(verify_request(&config, &request, None).await)
+}
+
+/// Verify one payment the way `/verify` does: metrics, audit log, live
+/// events and webhooks included (none of them for dry runs)
+///
+/// Also verifies each payment of `/verify/batch` and `/verify/stream`;
+/// accounts in `prefetched` (fetched for the whole batch) aren't looked up
+/// again.
+pub async fn verify_request(
+    config: &Config,
+    request: &VerifyRequest,
+    prefetched: Option<&PrefetchedAccounts>,
+) -> VerifyResponse {
     let network = &request.payment_payload.network;
-    config.metrics.verify_requests.with_label_values(&[network]).inc();
+    record_verify_request(config, request);
+    if request.dry_run {
+        return dry_run_payment(config, request, prefetched).await;
+    }
     
-    // Update cache size metric
+    // Update cache and dedup gauges
     let stats = config.account_cache.stats();
-    config.metrics.update_cache_size(stats.entry_count);
+    config.metrics.update_cache_stats(&stats, &config.transaction_dedup.stats());
     tracing::debug!("Cache stats: {} entries", stats.entry_count);
     
     // Log verification request
     config.audit_logger.log_verification_request(network, None);
     
     // Perform verification
-    match verify_payment(&config, &request).await {
-        Ok(payer) => {
+    match verify_payment(config, request, prefetched).await {
+        Ok(Verified { payer, checks_skipped, details }) => {
             config.metrics.record_verification_success(network);
+            config.metrics.record_tenant_request(config.tenant_id(), "verify", "success");
             
             // Audit log success
             config.audit_logger.log_verification_success(network, &payer, None);
+            config.events.publish(LiveEventKind::Verify, network, true, serde_json::json!({ "payer": payer }));
             
             // Send webhook notification (async, non-blocking)
             if let Some(webhook_config) = &config.webhook {
-                let webhook_config = webhook_config.clone();
-                let payer_clone = payer.clone();
-                let network_clone = network.clone();
-                tokio::spawn(async move {
-                    let payload = crate::webhooks::WebhookPayload::new(
-                        crate::webhooks::WebhookEvent::VerificationSuccess,
-                        serde_json::json!({
-                            "payer": payer_clone,
-                            "network": network_clone,
-                        }),
-                    );
-                    let _ = crate::webhooks::send_webhook(&webhook_config, &payload).await;
-                });
+                let payload = crate::webhooks::WebhookPayload::new(
+                    crate::webhooks::WebhookEvent::VerificationSuccess,
+                    serde_json::json!({
+                        "payer": payer,
+                        "network": network,
+                    }),
+                );
+                crate::webhooks::dispatch_webhook_for(webhook_config, config.tenant.as_deref(), payload);
             }
             
-            Json(VerifyResponse {
+            VerifyResponse {
                 is_valid: true,
                 invalid_reason: None,
                 payer: Some(payer),
-            })
+                checks_skipped,
+                failures: Vec::new(),
+                details,
+            }
         }
         Err(e) => {
             tracing::warn!("Verification failed: {}", e);
             config.metrics.record_verification_failure(network, e.as_str());
+            config.metrics.record_tenant_request(config.tenant_id(), "verify", "failure");
             
             // Audit log failure
             config.audit_logger.log_verification_failure(network, e.as_str(), None);
+            config.events.publish(LiveEventKind::Verify, network, false, serde_json::json!({ "reason": e.as_str() }));
             
             // Send webhook notification (async, non-blocking)
             if let Some(webhook_config) = &config.webhook {
-                let webhook_config = webhook_config.clone();
-                let reason = e.as_str().to_string();
-                let network_clone = network.clone();
-                tokio::spawn(async move {
-                    let payload = crate::webhooks::WebhookPayload::new(
-                        crate::webhooks::WebhookEvent::VerificationFailure,
-                        serde_json::json!({
-                            "reason": reason,
-                            "network": network_clone,
-                        }),
-                    );
-                    let _ = crate::webhooks::send_webhook(&webhook_config, &payload).await;
-                });
+                let payload = crate::webhooks::WebhookPayload::new(
+                    crate::webhooks::WebhookEvent::VerificationFailure,
+                    serde_json::json!({
+                        "reason": e.as_str(),
+                        "network": network,
+                    }),
+                );
+                crate::webhooks::dispatch_webhook_for(webhook_config, config.tenant.as_deref(), payload);
             }
             
-            Json(VerifyResponse {
+            VerifyResponse {
                 is_valid: false,
                 invalid_reason: Some(e.as_str().to_string()),
                 payer: None,
+                checks_skipped: Vec::new(),
+                failures: Vec::new(),
+                details: None,
+            }
+        }
+    }
+}
+
+/// POST /verify/header - Verify a payment given as its `X-PAYMENT` header
+///
+/// Resource servers can forward the client's header untouched, either as
+/// `payment_header` or as the `X-PAYMENT` header of this request.
+#[utoipa::path(
+    post,
+    path = "/verify/header",
+    request_body = HeaderVerifyRequest,
+    responses(
+        (status = 200, description = "Verification result", body = VerifyResponse)
+    ),
+    tag = "Payment"
+)]
+pub async fn verify_header(
+    State(config): State<Config>,
+    tenant: Option<Extension<Arc<Tenant>>>,
+    headers: HeaderMap,
+    Negotiated(request): Negotiated<HeaderVerifyRequest>,
+) -> Negotiated<VerifyResponse> {
+    let header = request
+        .payment_header
+        .or_else(|| headers.get(PAYMENT_HEADER).and_then(|value| value.to_str().ok()).map(str::to_string));
+    let payload = header
+        .ok_or_else(|| "X-PAYMENT header is required".to_string())
+        .and_then(|header| PaymentPayload::from_header(&header));
+
+    match payload {
+        Ok(payment_payload) => {
+            let request = VerifyRequest {
+                payment_payload,
+                payment_requirements: request.payment_requirements,
+                dry_run: request.dry_run,
+            };
+            verify(State(config), tenant, None, Negotiated(request)).await
+        }
+        Err(e) => {
+            tracing::warn!("Undecodable payment header: {}", e);
+            Negotiated(VerifyResponse {
+                is_valid: false,
+                invalid_reason: Some("invalid_payment_header".to_string()),
+                payer: None,
+                checks_skipped: Vec::new(),
+                failures: Vec::new(),
+                details: None,
             })
         }
     }
 }
 
+/// Count a verification request (dry runs stay out of the counters alerts
+/// watch)
+fn record_verify_request(config: &Config, request: &VerifyRequest) {
+    if !request.dry_run {
+        let network = &request.payment_payload.network;
+        config.metrics.verify_requests.with_label_values(&[network]).inc();
+    }
+}
+
 /// Internal verification logic
 async fn verify_payment(
     config: &Config,
     request: &VerifyRequest,
-) -> Result<String, VerificationError> {
+    prefetched: Option<&PrefetchedAccounts>,
+) -> Result<Verified, VerificationError> {
     let payload = &request.payment_payload;
-    let requirements = &request.payment_requirements;
 
     // 0. Check for duplicate transaction (replay attack prevention)
     let transaction_data = &payload.payload.transaction;
     if config.transaction_dedup.check_and_mark(transaction_data) {
+        config.metrics.record_dedup_hit();
         tracing::warn!("🚨 Duplicate transaction detected - rejecting");
+        config.events.publish(LiveEventKind::Dedup, &payload.network, false, serde_json::json!({}));
+        crate::alerts::replay_detected(config, &payload.network, transaction_data);
         return Err(VerificationError::UnexpectedError(
             anyhow::anyhow!("Transaction has already been processed (replay attack prevented)")
         ));
     }
 
-    // 0.5. Validate payment expiry (if timestamp is provided)
-    if let Some(timestamp) = payload.timestamp {
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| VerificationError::UnexpectedError(anyhow::anyhow!("System time error: {}", e)))?
-            .as_secs();
-        
-        let age_seconds = current_time.saturating_sub(timestamp);
-        
-        if age_seconds > config.payment_expiry_seconds {
-            tracing::warn!(
-                "⏰ Payment expired: age={} seconds, max={} seconds",
-                age_seconds,
-                config.payment_expiry_seconds
-            );
-            return Err(VerificationError::UnexpectedError(
-                anyhow::anyhow!(
-                    "Payment has expired (age: {} seconds, max: {} seconds)",
-                    age_seconds,
-                    config.payment_expiry_seconds
-                )
-            ));
-        }
-        
-        tracing::debug!("✅ Payment age validation passed: {} seconds old", age_seconds);
-    } else {
-        tracing::debug!("⚠️  No timestamp in payload, skipping expiry validation");
+    // Release the transaction if it's rejected, so a retry after fixing
+    // what failed (e.g. creating a token account) isn't taken for a replay
+    let result = check_payment(config, request, prefetched).await;
+    if result.is_err() {
+        config.transaction_dedup.unmark(transaction_data);
     }
+    result
+}
 
-    // 1. Verify scheme and network match
-    if payload.scheme != requirements.scheme || payload.scheme != "exact" {
-        return Err(VerificationError::UnsupportedScheme);
-    }
+/// [`verify_payment`] without side effects, for `dryRun` requests
+///
+/// Replays are still rejected, but the transaction isn't marked as seen,
+/// and no audit events, live events, webhooks or verification metrics
+/// (besides the dry-run count) are produced.
+async fn dry_run_payment(
+    config: &Config,
+    request: &VerifyRequest,
+    prefetched: Option<&PrefetchedAccounts>,
+) -> VerifyResponse {
+    let network = &request.payment_payload.network;
+    let result = if config.transaction_dedup.is_duplicate(&request.payment_payload.payload.transaction) {
+        Err(VerificationError::UnexpectedError(
+            anyhow::anyhow!("Transaction has already been processed (replay attack prevented)")
+        ))
+    } else {
+        check_payment(config, request, prefetched).await
+    };
 
-    if payload.network != requirements.network {
-        return Err(VerificationError::InvalidNetwork);
+    match result {
+        Ok(Verified { payer, checks_skipped, details }) => {
+            tracing::debug!("🧪 Dry-run verification passed");
+            config.metrics.record_verify_dry_run(network, "valid");
+            VerifyResponse {
+                is_valid: true,
+                invalid_reason: None,
+                payer: Some(payer),
+                checks_skipped,
+                failures: Vec::new(),
+                details,
+            }
+        }
+        Err(e) => {
+            tracing::debug!("🧪 Dry-run verification failed: {}", e);
+            config.metrics.record_verify_dry_run(network, "invalid");
+            VerifyResponse {
+                is_valid: false,
+                invalid_reason: Some(e.as_str().to_string()),
+                payer: None,
+                checks_skipped: Vec::new(),
+                failures: Vec::new(),
+                details: None,
+            }
+        }
     }
+}
 
-    // Verify network is supported
-    if requirements.network != "solana" && requirements.network != "solana-devnet" {
-        return Err(VerificationError::InvalidNetwork);
-    }
+/// Checks after deduplication
+async fn check_payment(
+    config: &Config,
+    request: &VerifyRequest,
+    prefetched: Option<&PrefetchedAccounts>,
+) -> Result<Verified, VerificationError> {
+    let requirements = &request.payment_requirements;
 
-    // 2. Decode transaction
-    let transaction = decode_transaction_from_base64(&payload.payload.transaction)
-        .map_err(|_| VerificationError::UnexpectedError(
-            anyhow::anyhow!("Failed to decode transaction")
-        ))?;
+    // Snapshot of reloadable settings, consistent for the whole request
+    let settings = config.settings.load();
 
-    // Get fee payer from requirements
-    let fee_payer = Pubkey::from_str(&requirements.extra.fee_payer)
-        .map_err(|_| VerificationError::UnexpectedError(
-            anyhow::anyhow!("Invalid fee payer pubkey")
-        ))?;
+    // 0.5. Validate payment expiry (if timestamp is provided), within the
+    // requirements' timeout
+    let network = NetworkContext::new(config, &requirements.network);
+    check_payment_age(
+        request,
+        requirements.expiry_seconds(network.payment_expiry_seconds()),
+        settings.clock_skew_seconds,
+    )?;
 
-    // Get payer (client) for response
-    let payer = if let Some(first_key) = transaction.message.account_keys.get(1) {
-        first_key.to_string()
-    } else {
-        "unknown".to_string()
+    // 1. Find the verifier of the scheme and network
+    let scheme = config.schemes.resolve(request)?;
+
+    // Verify the asset is accepted (when a mint allowlist is configured)
+    if !settings.is_mint_allowed(&requirements.asset) {
+        tracing::warn!("🪙 Payment asset {} is not in the mint allowlist", requirements.asset);
+        return Err(asset_not_accepted(requirements));
+    }
+    if let Some(tenant) = &config.tenant {
+        tenant.check(requirements)?;
+    }
+
+    // 2. The scheme's own checks
+    scheme.validate_extra(&requirements.extra)?;
+    scheme.verify(&VerifyContext::new(config).with_prefetched(prefetched), request)
+}
+
+/// Reject payments older than `max_age_seconds` or from the future (if
+/// timestamped), give or take `skew_seconds`
+fn check_payment_age(request: &VerifyRequest, max_age_seconds: u64, skew_seconds: u64) -> Result<(), VerificationError> {
+    let payload = &request.payment_payload;
+    let Some(timestamp) = payload.timestamp else {
+        tracing::debug!("⚠️  No timestamp in payload, skipping expiry validation");
+        return Ok(());
     };
 
-    // 3. Verify instruction count (3 or 4)
-    let has_create_ata = verify_instruction_count(&transaction)?;
+    let current_time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| VerificationError::UnexpectedError(anyhow::anyhow!("System time error: {}", e)))?
+        .as_secs();
 
-    // 4. Verify compute budget instructions
-    verify_compute_limit_instruction(
-        &transaction.message.instructions[0],
-        &transaction.message,
-    )?;
+    if let Err(e) = payload.check_timestamp(current_time, max_age_seconds, skew_seconds) {
+        tracing::warn!(
+            "⏰ Payment timestamp rejected ({}): timestamp={}, now={}, max age={} seconds, skew={} seconds",
+            e.as_str(),
+            timestamp,
+            current_time,
+            max_age_seconds,
+            skew_seconds
+        );
+        return Err(e);
+    }
 
-    verify_compute_price_instruction(
-        &transaction.message.instructions[1],
-        &transaction.message,
-    )?;
+    tracing::debug!("✅ Payment age validation passed: {} seconds old", current_time.saturating_sub(timestamp));
+    Ok(())
+}
 
-    // 5. Verify fee payer safety (not in any instruction accounts)
-    verify_fee_payer_safety(&transaction, &fee_payer)?;
-
-    // 6. Use shared RPC client (connection pooling)
-    let rpc_client = &config.rpc_client;
-
-    // 7. Verify CreateATA instruction (if present)
-    if has_create_ata {
-        verify_create_ata_instruction(
-            &transaction.message.instructions[2],
-            &transaction.message,
-            requirements,
-        )?;
-    }
-
-    // 8. Verify transfer instruction (last instruction)
-    let transfer_idx = if has_create_ata { 3 } else { 2 };
-    verify_transfer_instruction(
-        &transaction.message.instructions[transfer_idx],
-        &transaction.message,
-        requirements,
-        &fee_payer,
-        has_create_ata,
-        rpc_client.as_ref(),
-    )?;
+fn asset_not_accepted(requirements: &PaymentRequirements) -> VerificationError {
+    VerificationError::UnexpectedError(
+        anyhow::anyhow!("Asset {} is not accepted by this facilitator", requirements.asset)
+    )
+}
 
-    Ok(payer)
+/// Every check of [`verify_payment`], collecting all failures
+///
+/// The scheme's checks run with the verifier for the payload's scheme and
+/// network when the requirements name another network, and are skipped when
+/// there is none. Doesn't mark the transaction as seen, so the payment can
+/// still be verified and settled.
+fn diagnose_payment(config: &Config, request: &VerifyRequest) -> VerifyResponse {
+    let payload = &request.payment_payload;
+    let requirements = &request.payment_requirements;
+    let settings = config.settings.load();
+    let mut diagnosis = Diagnosis::default();
+
+    if config.transaction_dedup.is_duplicate(&payload.payload.transaction) {
+        diagnosis.fail(
+            "duplicate",
+            VerificationError::UnexpectedError(anyhow::anyhow!(
+                "Transaction has already been processed; build and sign a new one"
+            )),
+        );
+    }
+    let network = NetworkContext::new(config, &requirements.network);
+    diagnosis.check(
+        "expiry",
+        check_payment_age(
+            request,
+            requirements.expiry_seconds(network.payment_expiry_seconds()),
+            settings.clock_skew_seconds,
+        ),
+    );
+    let scheme = diagnosis
+        .check("scheme_and_network", config.schemes.resolve(request))
+        .or_else(|| config.schemes.get(&payload.scheme, &payload.network));
+    if !settings.is_mint_allowed(&requirements.asset) {
+        diagnosis.fail("asset_allowed", asset_not_accepted(requirements));
+    }
+    if let Some(tenant) = &config.tenant {
+        diagnosis.check("tenant_policy", tenant.check(requirements));
+    }
+
+    if let Some(scheme) = scheme {
+        diagnosis.check("extra", scheme.validate_extra(&requirements.extra));
+        scheme.diagnose(&VerifyContext::new(config), request, &mut diagnosis);
+    }
+
+    tracing::debug!("🩺 Diagnostic verification: {} failed checks", diagnosis.failures.len());
+
+    let failures = diagnosis.failures;
+    VerifyResponse {
+        is_valid: failures.is_empty(),
+        invalid_reason: failures.first().map(|failure| failure.code.clone()),
+        payer: diagnosis.payer,
+        checks_skipped: diagnosis.checks_skipped,
+        failures,
+        details: None,
+    }
 }
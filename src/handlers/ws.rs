@@ -0,0 +1,70 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::config::Config;
+use crate::events::{ClientMessage, ServerMessage, Subscriptions};
+
+/// GET /ws - Live feed of verify, settle and dedup events
+///
+/// Clients start with no subscriptions and send
+/// `{"type": "subscribe", "events": ["verify", "settle", "dedup"]}`
+/// to receive `{"type": "event", "event": {...}}` messages.
+pub async fn live_events(State(config): State<Config>, upgrade: WebSocketUpgrade) -> Response {
+    upgrade.on_upgrade(move |socket| feed(socket, config))
+}
+
+async fn feed(mut socket: WebSocket, config: Config) {
+    let mut events = config.events.subscribe();
+    let mut subscriptions = Subscriptions::default();
+    tracing::debug!("📡 Live event subscriber connected ({} total)", config.events.subscribers());
+
+    loop {
+        let reply = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::Ping) => ServerMessage::Pong,
+                    Ok(message) => {
+                        subscriptions.apply(&message);
+                        ServerMessage::Subscribed { events: subscriptions.kinds() }
+                    }
+                    Err(e) => ServerMessage::Error { message: format!("Invalid message: {}", e) },
+                },
+                Some(Ok(Message::Close(_))) | None => break,
+                // Pings are answered by axum; binary frames are ignored
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    tracing::debug!("Live event subscriber disconnected: {}", e);
+                    break;
+                }
+            },
+            event = events.recv() => match event {
+                Ok(event) if subscriptions.contains(event.kind) => {
+                    if send(&mut socket, &ServerMessage::Event { event: &event }).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => ServerMessage::Lagged { missed },
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        if send(&mut socket, &reply).await.is_err() {
+            break;
+        }
+    }
+
+    tracing::debug!("📡 Live event subscriber disconnected");
+}
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage<'_>) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).expect("live feed messages serialize");
+    socket.send(Message::Text(text)).await
+}
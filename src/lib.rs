@@ -3,15 +3,46 @@
 
 use utoipa::OpenApi;
 
+pub mod account_watch;
+pub mod alerts;
 pub mod audit;
+#[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+pub mod audit_retention;
+#[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+pub mod audit_store;
 pub mod cache;
+#[cfg(feature = "client")]
+pub mod client;
 pub mod config;
 pub mod dedup;
 pub mod error;
+pub mod error_reporting;
+pub mod events;
 pub mod ffi;
+pub mod logging;
 pub mod metrics;
+pub mod networks;
 pub mod parallel;
+pub mod pricing;
+pub mod receipts;
+#[cfg(feature = "redis")]
+pub mod redis_backend;
+pub mod reload;
+pub mod rent_budget;
+pub mod rpc_health;
+pub mod schemes;
+pub mod requirements;
+#[cfg(feature = "settlement-history")]
+pub mod settlement_store;
+pub mod settlements;
+pub mod spend;
+pub mod statsd;
+pub mod subscriptions;
+pub mod tenants;
 pub mod types;
+pub mod vault;
+#[cfg(feature = "webhook-queue")]
+pub mod webhook_queue;
 pub mod webhooks;
 
 // WebAssembly module (only when targeting wasm32)
@@ -20,11 +51,12 @@ pub mod wasm;
 
 // Internal modules needed by server
 pub mod handlers;
-mod solana;
+pub mod solana;
 pub mod middleware;
 
 // Server module needs handlers
 pub mod server;
+pub mod tls;
 
 // Re-export commonly used items
 pub use config::Config;
@@ -36,8 +68,13 @@ pub use error::{AppError, VerificationError};
     paths(
         handlers::health::health_check,
         handlers::supported::supported,
+        handlers::quote::quote,
+        handlers::pubkey::pubkey,
         handlers::verify::verify,
+        handlers::verify::verify_header,
         handlers::batch::verify_batch,
+        handlers::batch::verify_stream,
+        handlers::validate::validate,
         handlers::settle::settle,
     ),
     components(
@@ -47,11 +84,26 @@ pub use error::{AppError, VerificationError};
             types::requests::PaymentRequirements,
             types::requests::ExtraFields,
             types::requests::VerifyRequest,
+            types::requests::BatchVerifyRequest,
+            types::requests::HeaderVerifyRequest,
             types::requests::SettleRequest,
             types::responses::VerifyResponse,
+            types::responses::CheckFailure,
+            types::responses::PaymentDetails,
+            types::responses::StreamedVerifyResponse,
+            types::responses::BatchVerifyResponse,
+            types::responses::BatchVerifyResult,
+            types::responses::BatchSummary,
+            types::responses::ValidateResponse,
+            types::responses::FieldReport,
             types::responses::SettleResponse,
+            types::responses::SettlementStatusResponse,
             types::responses::SupportedResponse,
             types::responses::SchemeSupport,
+            types::responses::QuoteResponse,
+            types::responses::PubkeyResponse,
+            receipts::PaymentReceipt,
+            receipts::SignedReceipt,
         )
     ),
     tags(
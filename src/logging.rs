@@ -0,0 +1,175 @@
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{filter_fn, EnvFilter, LevelFilter, Targets};
+use tracing_subscriber::{Layer, Registry};
+
+/// Target used by [`crate::audit::AuditLogger`] for audit events
+pub const AUDIT_TARGET: &str = "audit";
+
+/// Filter used when `RUST_LOG` is not set
+const DEFAULT_FILTER: &str = "x402_facilitator=info,tower_http=debug";
+
+pub type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Output format for application logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines (default)
+    Pretty,
+    /// One JSON object per line, for log pipelines
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" | "text" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            other => bail!("Invalid LOG_FORMAT '{}' (expected 'pretty' or 'json')", other),
+        }
+    }
+}
+
+/// Logging settings
+///
+/// Logs always go to stdout. When `LOG_DIR` is set they are also written to
+/// rolling files in that directory, with audit events (target `audit`) in a
+/// separate `audit.*.log` file instead of the application log.
+#[derive(Debug, Clone)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    /// Directory for rolling log files (file logging disabled when `None`)
+    pub dir: Option<PathBuf>,
+    pub rotation: Rotation,
+    /// File name prefix of the application log, e.g. `x402-facilitator`
+    pub file_prefix: String,
+}
+
+impl LogConfig {
+    /// Load logging settings from environment
+    pub fn from_env() -> Result<Self> {
+        let format = match std::env::var("LOG_FORMAT") {
+            Ok(format) if !format.is_empty() => format.parse()?,
+            _ => LogFormat::Pretty,
+        };
+
+        let rotation = match std::env::var("LOG_ROTATION") {
+            Ok(rotation) if !rotation.is_empty() => parse_rotation(&rotation)?,
+            _ => Rotation::DAILY,
+        };
+
+        Ok(Self {
+            format,
+            dir: std::env::var("LOG_DIR").ok().filter(|dir| !dir.is_empty()).map(PathBuf::from),
+            rotation,
+            file_prefix: std::env::var("LOG_FILE_PREFIX")
+                .ok()
+                .filter(|prefix| !prefix.is_empty())
+                .unwrap_or_else(|| "x402-facilitator".to_string()),
+        })
+    }
+}
+
+fn parse_rotation(s: &str) -> Result<Rotation> {
+    match s.to_ascii_lowercase().as_str() {
+        "minutely" => Ok(Rotation::MINUTELY),
+        "hourly" => Ok(Rotation::HOURLY),
+        "daily" => Ok(Rotation::DAILY),
+        "never" => Ok(Rotation::NEVER),
+        other => bail!(
+            "Invalid LOG_ROTATION '{}' (expected minutely, hourly, daily or never)",
+            other
+        ),
+    }
+}
+
+/// `RUST_LOG` filter, falling back to the default filter
+pub fn env_filter() -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| DEFAULT_FILTER.into())
+}
+
+/// Build the stdout and (optional) file logging layers
+///
+/// The returned guards flush buffered file output when dropped, so keep them
+/// alive for the lifetime of the process.
+pub fn layers(config: &LogConfig) -> Result<(Vec<BoxedLayer>, Vec<WorkerGuard>)> {
+    let mut layers = vec![fmt_layer(config.format, std::io::stdout, true).with_filter(env_filter()).boxed()];
+    let mut guards = Vec::new();
+
+    if let Some(dir) = &config.dir {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+
+        let (app_writer, guard) = tracing_appender::non_blocking(rolling_appender(config, &config.file_prefix)?);
+        guards.push(guard);
+        layers.push(
+            fmt_layer(config.format, app_writer, false)
+                .with_filter(env_filter())
+                .with_filter(filter_fn(|metadata| metadata.target() != AUDIT_TARGET))
+                .boxed(),
+        );
+
+        // Audit messages are already JSON, so write them as-is, one per line
+        let (audit_writer, guard) = tracing_appender::non_blocking(rolling_appender(config, AUDIT_TARGET)?);
+        guards.push(guard);
+        layers.push(
+            tracing_subscriber::fmt::layer()
+                .with_writer(audit_writer)
+                .with_ansi(false)
+                .without_time()
+                .with_level(false)
+                .with_target(false)
+                .with_filter(Targets::new().with_target(AUDIT_TARGET, LevelFilter::INFO))
+                .boxed(),
+        );
+    }
+
+    Ok((layers, guards))
+}
+
+fn fmt_layer<W>(format: LogFormat, writer: W, ansi: bool) -> BoxedLayer
+where
+    W: for<'writer> tracing_subscriber::fmt::MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(ansi);
+
+    match format {
+        LogFormat::Pretty => layer.boxed(),
+        LogFormat::Json => layer.json().with_current_span(true).with_span_list(false).boxed(),
+    }
+}
+
+fn rolling_appender(config: &LogConfig, prefix: &str) -> Result<RollingFileAppender> {
+    let dir = config.dir.as_deref().context("LOG_DIR not set")?;
+
+    RollingFileAppender::builder()
+        .rotation(config.rotation.clone())
+        .filename_prefix(prefix)
+        .filename_suffix("log")
+        .build(dir)
+        .with_context(|| format!("Failed to open log file in {}", dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_format() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("pretty".parse::<LogFormat>().unwrap(), LogFormat::Pretty);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_parse_rotation() {
+        assert_eq!(parse_rotation("hourly").unwrap(), Rotation::HOURLY);
+        assert_eq!(parse_rotation("Never").unwrap(), Rotation::NEVER);
+        assert!(parse_rotation("weekly").is_err());
+    }
+}
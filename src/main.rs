@@ -1,12 +1,17 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::signal;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tokio::task::JoinHandle;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
 // Use library from lib.rs
-use x402_facilitator::{config, server};
+use x402_facilitator::{config, error_reporting, logging, reload, server, solana, statsd, tls, vault};
 
-/// Graceful shutdown handler
+/// Minimum time allowed for flushing audit events and webhooks on shutdown
+const MIN_FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Wait for a shutdown signal (Ctrl+C or SIGTERM)
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
@@ -35,54 +40,195 @@ async fn shutdown_signal() {
     }
     
     tracing::info!("⏳ Starting graceful shutdown...");
-    tracing::info!("   Waiting for in-flight requests to complete (max 10s)");
-    
-    // Give in-flight requests time to complete
-    tokio::time::sleep(Duration::from_secs(10)).await;
-    
+}
+
+/// Drain in-flight requests, then flush webhooks and audit events
+///
+/// Waits only as long as requests (in particular `/settle`) are still
+/// running, bounded by `timeout`.
+async fn drain(config: &config::Config, server: JoinHandle<()>, timeout: Duration) {
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    tracing::info!(
+        "   Waiting for {} in-flight requests ({} settlements) to complete (max {}s)",
+        config.in_flight.requests.count(),
+        config.in_flight.settlements.count(),
+        timeout.as_secs()
+    );
+
+    if !config.in_flight.requests.wait_idle(timeout).await {
+        tracing::warn!(
+            "⚠️  Shutdown timeout reached with {} requests ({} settlements) still in flight",
+            config.in_flight.requests.count(),
+            config.in_flight.settlements.count()
+        );
+    }
+
+    // Idle keep-alive connections must not hold shutdown past the deadline
+    if tokio::time::timeout_at(deadline, server).await.is_err() {
+        tracing::warn!("⚠️  Closing remaining connections");
+    }
+
+    let flush_timeout = deadline
+        .saturating_duration_since(tokio::time::Instant::now())
+        .max(MIN_FLUSH_TIMEOUT);
+
+    if let Some(webhook) = &config.webhook {
+        if !webhook.flush(flush_timeout).await {
+            tracing::warn!("⚠️  {} webhook deliveries still pending at shutdown", webhook.pending.count());
+        }
+    }
+
+    if let Err(e) = config.transaction_dedup.write_snapshot() {
+        tracing::warn!("⚠️  Failed to snapshot dedup cache: {:#}", e);
+    }
+
+    config.audit_logger.log_server_stopped();
+    if !config.audit_logger.flush(flush_timeout).await {
+        tracing::warn!("⚠️  Audit log may be incomplete: writer did not flush in time");
+    }
+
     tracing::info!("✅ Shutdown complete");
 }
 
 #[tokio::main]
 async fn main() {
-    // Initialize structured logging with environment filter
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "x402_facilitator=info,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize structured logging (stdout, plus rolling files if LOG_DIR is set)
+    let log_config = logging::LogConfig::from_env().expect("Invalid logging configuration");
+    let (mut log_layers, _log_guards) = logging::layers(&log_config).expect("Failed to set up log files");
+    log_layers.push(
+        sentry::integrations::tracing::layer()
+            .with_filter(logging::env_filter())
+            .boxed(),
+    );
+    tracing_subscriber::registry().with(log_layers).init();
+
+    // Report panics and errors to Sentry (optional); flushed when dropped
+    let _sentry = error_reporting::init_from_env();
+
+    // Pull secrets from Vault (if configured) before reading config
+    let vault = vault::VaultConfig::from_env()
+        .expect("Invalid Vault configuration")
+        .map(|vault_config| vault::VaultClient::new(vault_config).expect("Failed to create Vault client"));
+    let vault_secrets = match &vault {
+        Some(client) => Some(client.load_into_env().await.expect("Failed to load secrets from Vault")),
+        None => None,
+    };
 
     // Load config
     let config = config::Config::from_env().expect("Failed to load config");
+    solana::signer::check_signer(config.fee_payer_signer.as_ref())
+        .await
+        .expect("Fee payer signer check failed");
+
+    if let (Some(client), Some(secrets)) = (vault, vault_secrets) {
+        vault::spawn_refresh(client, secrets, config.clone());
+    }
+
+    // Push metrics to a StatsD / DogStatsD agent (optional)
+    if let Some(statsd_config) = statsd::StatsdConfig::from_env() {
+        statsd::spawn_exporter(statsd_config)
+            .await
+            .expect("Failed to start StatsD exporter");
+    }
+
+    // Measure RPC health, slot lag and latency for /admin/rpc and the gauges
+    config.rpc_health.spawn(config.webhook.clone());
+
+    // Fetch USD prices of the PRICE_FEEDS mints (optional)
+    config.price_oracle.spawn_refresher();
+
+    // Snapshot the dedup cache so its window survives restarts (optional)
+    config.transaction_dedup.spawn_snapshotter();
+
+    // Archive and prune persisted audit events past their retention window
+    #[cfg(any(feature = "audit-sqlite", feature = "audit-postgres"))]
+    if let Some(store) = config.audit_logger.store() {
+        use x402_facilitator::audit_retention;
+
+        if let Some(retention) = audit_retention::RetentionConfig::from_env().expect("Invalid audit retention configuration") {
+            audit_retention::spawn_pruner(store.clone(), retention);
+        }
+    }
+
+    // Deliver webhooks from the durable queue (optional)
+    #[cfg(feature = "webhook-queue")]
+    if let Some(webhook) = &config.webhook {
+        if let Some(queue) = &webhook.queue {
+            x402_facilitator::webhook_queue::spawn_dispatcher(queue.clone(), webhook.clone());
+        }
+    }
+
+    // Re-read runtime settings on SIGHUP
+    #[cfg(unix)]
+    reload::spawn_sighup_listener(config.clone());
 
     tracing::info!("🚀 Starting x402 Rust Facilitator v{}", env!("CARGO_PKG_VERSION"));
     tracing::info!("📡 Network: {}", config.network);
     tracing::info!("🔗 RPC: {}", config.solana_rpc_url);
     if config.rate_limiter.is_some() {
-        tracing::info!("🛡️  Rate limiting: enabled");
+        tracing::info!("🛡️  Rate limiting: enabled (per client)");
     } else {
         tracing::info!("⚠️  Rate limiting: disabled");
     }
 
+    // Native TLS (optional)
+    let tls_config = tls::TlsConfig::from_env().expect("Invalid TLS configuration");
+    let scheme = if tls_config.is_some() { "https" } else { "http" };
+
     // Create router
     let app = server::create_router(config.clone());
 
     // Start server
     let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
     tracing::info!("🎧 Listening on {}", addr);
-    tracing::info!("📚 OpenAPI Spec: http://{}:{}/api-docs/openapi.json", addr.ip(), addr.port());
+    tracing::info!("📚 OpenAPI Spec: {}://{}:{}/api-docs/openapi.json", scheme, addr.ip(), addr.port());
     tracing::info!("✅ Server ready! Press Ctrl+C to stop");
 
     let listener = tokio::net::TcpListener::bind(addr)
         .await
         .expect("Failed to bind to address");
 
-    // Serve with graceful shutdown
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .expect("Server failed");
+    let shutdown_timeout = Duration::from_secs(
+        std::env::var("SHUTDOWN_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(30),
+    );
+
+    // Stop accepting connections on signal, and let main know draining began
+    let (signal_tx, signal_rx) = tokio::sync::oneshot::channel::<()>();
+    let shutdown = async move {
+        shutdown_signal().await;
+        let _ = signal_tx.send(());
+    };
+
+    let tls_logger = config.audit_logger.clone();
+    let server = tokio::spawn(async move {
+        match tls_config {
+            Some(tls_config) => {
+                tracing::info!("🔒 TLS enabled with certificate {}", tls_config.cert_path.display());
+                let tls = Arc::new(tls::ReloadableTls::new(tls_config).expect("Failed to load TLS certificate"));
+                tls.spawn_reload_watcher(tls_logger);
+
+                tls::serve(listener, app, tls, shutdown)
+                    .await
+                    .expect("Server failed");
+            }
+            None => {
+                // Serve with graceful shutdown
+                // Peer addresses feed per-client rate limiting
+                axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                    .with_graceful_shutdown(shutdown)
+                    .await
+                    .expect("Server failed");
+            }
+        }
+    });
+
+    // Resolves on a shutdown signal, or if the server exits on its own
+    let _ = signal_rx.await;
+
+    drain(&config, server, shutdown_timeout).await;
 }
 
@@ -1,8 +1,15 @@
 use prometheus::{
-    register_histogram_vec, register_int_counter_vec, register_int_gauge, HistogramVec,
-    IntCounterVec, IntGauge,
+    register_gauge, register_gauge_vec, register_histogram_vec, register_int_counter,
+    register_int_counter_vec, register_int_gauge, register_int_gauge_vec, Gauge, GaugeVec, HistogramVec,
+    IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use lazy_static::lazy_static;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+use crate::cache::CacheStats;
+use crate::dedup::DedupStats;
 
 lazy_static! {
     static ref VERIFY_REQUESTS: IntCounterVec = register_int_counter_vec!(
@@ -40,6 +47,54 @@ lazy_static! {
         "Current number of entries in the account cache"
     ).expect("Failed to register cache_size metric");
 
+    static ref CACHE_HIT_RATIO: Gauge = register_gauge!(
+        "x402_cache_hit_ratio",
+        "Fraction of account cache lookups served from the cache"
+    ).expect("Failed to register cache_hit_ratio metric");
+
+    static ref DEDUP_ENTRIES: IntGauge = register_int_gauge!(
+        "x402_dedup_entries",
+        "Current number of transactions in the local dedup cache"
+    ).expect("Failed to register dedup_entries metric");
+
+    static ref DEDUP_HITS: IntCounter = register_int_counter!(
+        "x402_dedup_hits_total",
+        "Total number of transactions rejected as replays"
+    ).expect("Failed to register dedup_hits metric");
+
+    static ref ATA_RENT_SPONSORED: IntCounter = register_int_counter!(
+        "x402_ata_rent_sponsored_lamports_total",
+        "Total lamports of token account rent charged to the fee payer's budget"
+    ).expect("Failed to register ata_rent_sponsored metric");
+
+    static ref ATA_RENT_BUDGET_REMAINING: IntGauge = register_int_gauge!(
+        "x402_ata_rent_budget_remaining_lamports",
+        "Lamports of today's token account rent budget left"
+    ).expect("Failed to register ata_rent_budget_remaining metric");
+
+    static ref TENANT_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "x402_tenant_requests_total",
+        "Verifications and settlements per tenant",
+        &["tenant", "endpoint", "result"]
+    ).expect("Failed to register tenant_requests metric");
+
+    static ref FEE_PAYER_SPEND: IntCounterVec = register_int_counter_vec!(
+        "x402_fee_payer_spend_lamports_total",
+        "Total lamports the fee payer spent on settlements",
+        &["kind"]
+    ).expect("Failed to register fee_payer_spend metric");
+
+    static ref FEE_PAYER_SPEND_TODAY: IntGauge = register_int_gauge!(
+        "x402_fee_payer_spend_today_lamports",
+        "Lamports the fee payer spent on settlements in the current UTC day"
+    ).expect("Failed to register fee_payer_spend_today metric");
+
+    static ref VERIFY_DRY_RUNS: IntCounterVec = register_int_counter_vec!(
+        "x402_verify_dry_runs_total",
+        "Dry-run verifications, kept out of the verification counters",
+        &["network", "result"]
+    ).expect("Failed to register verify_dry_runs metric");
+
     static ref VERIFICATION_SUCCESS: IntCounterVec = register_int_counter_vec!(
         "x402_verification_success_total",
         "Total number of successful verifications",
@@ -55,10 +110,17 @@ lazy_static! {
     static ref REQUEST_DURATION: HistogramVec = register_histogram_vec!(
         "x402_request_duration_seconds",
         "Request duration in seconds",
-        &["endpoint", "method"],
+        &["endpoint", "method", "status"],
         vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]
     ).expect("Failed to register request_duration metric");
 
+    static ref STEP_DURATION: HistogramVec = register_histogram_vec!(
+        "x402_verification_step_duration_seconds",
+        "Duration of each verification and settlement step in seconds",
+        &["step"],
+        vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]
+    ).expect("Failed to register step_duration metric");
+
     static ref RPC_CALLS: IntCounterVec = register_int_counter_vec!(
         "x402_rpc_calls_total",
         "Total number of RPC calls",
@@ -70,6 +132,65 @@ lazy_static! {
         "Total number of RPC errors",
         &["method", "error_type"]
     ).expect("Failed to register rpc_errors metric");
+
+    static ref RPC_DURATION: HistogramVec = register_histogram_vec!(
+        "x402_rpc_duration_seconds",
+        "RPC call duration in seconds",
+        &["method"],
+        vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]
+    ).expect("Failed to register rpc_duration metric");
+
+    static ref AUDIT_EVENTS_DROPPED: IntCounter = register_int_counter!(
+        "x402_audit_events_dropped_total",
+        "Total number of audit events dropped because the audit pipeline fell behind"
+    ).expect("Failed to register audit_events_dropped metric");
+
+    static ref AUDIT_QUEUE_DEPTH: IntGauge = register_int_gauge!(
+        "x402_audit_queue_depth",
+        "Current number of audit events waiting to be written"
+    ).expect("Failed to register audit_queue_depth metric");
+
+    static ref AUDIT_QUEUE_CAPACITY: IntGauge = register_int_gauge!(
+        "x402_audit_queue_capacity",
+        "Maximum number of audit events queued before the oldest are dropped"
+    ).expect("Failed to register audit_queue_capacity metric");
+
+    static ref WEBHOOK_DELIVERIES: IntCounterVec = register_int_counter_vec!(
+        "x402_webhook_deliveries_total",
+        "Total number of webhook delivery attempts",
+        &["endpoint", "outcome"]
+    ).expect("Failed to register webhook_deliveries metric");
+
+    static ref WEBHOOK_RETRIES: IntCounterVec = register_int_counter_vec!(
+        "x402_webhook_retries_total",
+        "Total number of webhook delivery attempts that were retries",
+        &["endpoint"]
+    ).expect("Failed to register webhook_retries metric");
+
+    static ref WEBHOOK_DELIVERY_DURATION: HistogramVec = register_histogram_vec!(
+        "x402_webhook_delivery_duration_seconds",
+        "Webhook delivery attempt duration in seconds",
+        &["endpoint"],
+        vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]
+    ).expect("Failed to register webhook_delivery_duration metric");
+
+    static ref RPC_ENDPOINT_UP: IntGaugeVec = register_int_gauge_vec!(
+        "x402_rpc_endpoint_up",
+        "Whether the RPC endpoint passed its last health check (1) or not (0)",
+        &["endpoint"]
+    ).expect("Failed to register rpc_endpoint_up metric");
+
+    static ref RPC_SLOT_LAG: IntGaugeVec = register_int_gauge_vec!(
+        "x402_rpc_slot_lag",
+        "Slots the RPC endpoint is behind the most advanced monitored endpoint",
+        &["endpoint"]
+    ).expect("Failed to register rpc_slot_lag metric");
+
+    static ref RPC_LATENCY: GaugeVec = register_gauge_vec!(
+        "x402_rpc_latency_seconds",
+        "Round trip of getSlot at the last RPC health check",
+        &["endpoint"]
+    ).expect("Failed to register rpc_latency metric");
 }
 
 /// Application-specific metrics
@@ -84,17 +205,36 @@ pub struct AppMetrics {
     pub cache_hits: &'static IntCounterVec,
     pub cache_misses: &'static IntCounterVec,
     pub cache_size: &'static IntGauge,
+    pub cache_hit_ratio: &'static Gauge,
+
+    // Dedup metrics
+    pub dedup_entries: &'static IntGauge,
+    pub dedup_hits: &'static IntCounter,
+
+    // Sponsored token account rent
+    pub ata_rent_sponsored: &'static IntCounter,
+    pub ata_rent_budget_remaining: &'static IntGauge,
+
+    // Fee payer spending
+    pub fee_payer_spend: &'static IntCounterVec,
+
+    // Tenants
+    pub tenant_requests: &'static IntCounterVec,
+    pub fee_payer_spend_today: &'static IntGauge,
 
     // Verification metrics
     pub verification_success: &'static IntCounterVec,
     pub verification_failure: &'static IntCounterVec,
+    pub verify_dry_runs: &'static IntCounterVec,
 
     // Latency metrics
     pub request_duration: &'static HistogramVec,
+    pub step_duration: &'static HistogramVec,
 
     // RPC metrics
     pub rpc_calls: &'static IntCounterVec,
     pub rpc_errors: &'static IntCounterVec,
+    pub rpc_duration: &'static HistogramVec,
 }
 
 impl AppMetrics {
@@ -108,11 +248,22 @@ impl AppMetrics {
             cache_hits: &CACHE_HITS,
             cache_misses: &CACHE_MISSES,
             cache_size: &CACHE_SIZE,
+            cache_hit_ratio: &CACHE_HIT_RATIO,
+            dedup_entries: &DEDUP_ENTRIES,
+            dedup_hits: &DEDUP_HITS,
+            ata_rent_sponsored: &ATA_RENT_SPONSORED,
+            ata_rent_budget_remaining: &ATA_RENT_BUDGET_REMAINING,
+            fee_payer_spend: &FEE_PAYER_SPEND,
+            tenant_requests: &TENANT_REQUESTS,
+            fee_payer_spend_today: &FEE_PAYER_SPEND_TODAY,
             verification_success: &VERIFICATION_SUCCESS,
             verification_failure: &VERIFICATION_FAILURE,
+            verify_dry_runs: &VERIFY_DRY_RUNS,
             request_duration: &REQUEST_DURATION,
+            step_duration: &STEP_DURATION,
             rpc_calls: &RPC_CALLS,
             rpc_errors: &RPC_ERRORS,
+            rpc_duration: &RPC_DURATION,
         }
     }
 
@@ -121,6 +272,43 @@ impl AppMetrics {
         self.cache_size.set(size as i64);
     }
 
+    /// Update account cache and dedup gauges from their current stats
+    pub fn update_cache_stats(&self, cache: &CacheStats, dedup: &DedupStats) {
+        self.update_cache_size(cache.entry_count);
+        self.cache_hit_ratio.set(cache.hit_ratio());
+        self.dedup_entries.set(dedup.entry_count as i64);
+    }
+
+    /// Record a transaction rejected as a replay
+    pub fn record_dedup_hit(&self) {
+        self.dedup_hits.inc();
+    }
+
+    /// Record rent the fee payer sponsors and what's left of today's budget
+    pub fn record_ata_rent_sponsored(&self, lamports: u64, remaining: u64) {
+        self.ata_rent_sponsored.inc_by(lamports);
+        self.ata_rent_budget_remaining.set(remaining as i64);
+    }
+
+    /// Record a dry-run verification (`valid` or `invalid`)
+    pub fn record_verify_dry_run(&self, network: &str, result: &str) {
+        self.verify_dry_runs.with_label_values(&[network, result]).inc();
+    }
+
+    /// Record a tenant's verification or settlement (no-op without a tenant)
+    pub fn record_tenant_request(&self, tenant: Option<&str>, endpoint: &str, result: &str) {
+        if let Some(tenant) = tenant {
+            self.tenant_requests.with_label_values(&[tenant, endpoint, result]).inc();
+        }
+    }
+
+    /// Record what a settlement cost the fee payer, and the day's total
+    pub fn record_fee_payer_spend(&self, fee_lamports: u64, rent_lamports: u64, today_lamports: u64) {
+        self.fee_payer_spend.with_label_values(&["fee"]).inc_by(fee_lamports);
+        self.fee_payer_spend.with_label_values(&["rent"]).inc_by(rent_lamports);
+        self.fee_payer_spend_today.set(today_lamports as i64);
+    }
+
     /// Record a cache hit
     pub fn record_cache_hit(&self, account_type: &str) {
         self.cache_hits
@@ -142,6 +330,54 @@ impl AppMetrics {
             .inc();
     }
 
+    /// Record how long a request to `endpoint` (its route template) took
+    pub fn record_request(&self, endpoint: &str, method: &str, status: u16, duration: Duration) {
+        self.request_duration
+            .with_label_values(&[endpoint, method, &status.to_string()])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Record how long one step of verification or settlement took
+    pub fn record_step(&self, step: &str, duration: Duration) {
+        self.step_duration.with_label_values(&[step]).observe(duration.as_secs_f64());
+    }
+
+    /// Run `step` (`decode`, `structure`, `accounts`, `sign`, ...) in its
+    /// own span, recording its duration in the span and the step histogram
+    ///
+    /// Tells CPU-bound checks apart from account lookups (RPC or cache)
+    /// when verification latency moves.
+    pub fn time_step<T>(&self, step: &'static str, f: impl FnOnce() -> T) -> T {
+        let span = tracing::debug_span!("step", step, elapsed_ms = tracing::field::Empty);
+        let start = Instant::now();
+        let result = span.in_scope(f);
+        self.finish_step(&span, step, start.elapsed());
+        result
+    }
+
+    /// [`Self::time_step`] for an async step
+    pub async fn time_step_async<T>(&self, step: &'static str, f: impl Future<Output = T>) -> T {
+        let span = tracing::debug_span!("step", step, elapsed_ms = tracing::field::Empty);
+        let start = Instant::now();
+        let result = f.instrument(span.clone()).await;
+        self.finish_step(&span, step, start.elapsed());
+        result
+    }
+
+    fn finish_step(&self, span: &tracing::Span, step: &str, elapsed: Duration) {
+        span.record("elapsed_ms", elapsed.as_secs_f64() * 1000.0);
+        self.record_step(step, elapsed);
+    }
+
+    /// Record an RPC call, with its error type if it failed
+    pub fn record_rpc_call(&self, method: &str, duration: Duration, error_type: Option<&str>) {
+        self.rpc_calls.with_label_values(&[method]).inc();
+        self.rpc_duration.with_label_values(&[method]).observe(duration.as_secs_f64());
+        if let Some(error_type) = error_type {
+            self.rpc_errors.with_label_values(&[method, error_type]).inc();
+        }
+    }
+
     /// Record verification failure
     pub fn record_verification_failure(&self, network: &str, reason: &str) {
         self.verification_failure
@@ -156,6 +392,105 @@ impl Default for AppMetrics {
     }
 }
 
+/// Audit pipeline metrics, updated by [`crate::audit::AuditLogger`]
+#[derive(Clone)]
+pub struct AuditMetrics {
+    pub events_dropped: &'static IntCounter,
+    pub queue_depth: &'static IntGauge,
+    pub queue_capacity: &'static IntGauge,
+}
+
+impl AuditMetrics {
+    pub fn new() -> Self {
+        Self {
+            events_dropped: &AUDIT_EVENTS_DROPPED,
+            queue_depth: &AUDIT_QUEUE_DEPTH,
+            queue_capacity: &AUDIT_QUEUE_CAPACITY,
+        }
+    }
+}
+
+impl Default for AuditMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Webhook delivery metrics, labelled by endpoint name
+#[derive(Clone)]
+pub struct WebhookMetrics {
+    pub deliveries: &'static IntCounterVec,
+    pub retries: &'static IntCounterVec,
+    pub delivery_duration: &'static HistogramVec,
+}
+
+impl WebhookMetrics {
+    pub fn new() -> Self {
+        Self {
+            deliveries: &WEBHOOK_DELIVERIES,
+            retries: &WEBHOOK_RETRIES,
+            delivery_duration: &WEBHOOK_DELIVERY_DURATION,
+        }
+    }
+
+    /// Record one delivery attempt and how long it took
+    pub fn record_delivery(&self, endpoint: &str, success: bool, duration: std::time::Duration) {
+        let outcome = if success { "success" } else { "failure" };
+        self.deliveries.with_label_values(&[endpoint, outcome]).inc();
+        self.delivery_duration
+            .with_label_values(&[endpoint])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Record that an attempt is a retry of an earlier failure
+    pub fn record_retry(&self, endpoint: &str) {
+        self.retries.with_label_values(&[endpoint]).inc();
+    }
+}
+
+impl Default for WebhookMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// RPC endpoint health gauges, updated by [`crate::rpc_health::RpcHealthMonitor`]
+#[derive(Clone)]
+pub struct RpcHealthMetrics {
+    pub endpoint_up: &'static IntGaugeVec,
+    pub slot_lag: &'static IntGaugeVec,
+    pub latency: &'static GaugeVec,
+}
+
+impl RpcHealthMetrics {
+    pub fn new() -> Self {
+        Self {
+            endpoint_up: &RPC_ENDPOINT_UP,
+            slot_lag: &RPC_SLOT_LAG,
+            latency: &RPC_LATENCY,
+        }
+    }
+
+    /// Record the outcome of checking one endpoint
+    pub fn record(&self, health: &crate::rpc_health::EndpointHealth) {
+        let labels = [health.endpoint.as_str()];
+        let up = health.healthy && health.slot.is_some();
+        self.endpoint_up.with_label_values(&labels).set(up as i64);
+        if let Some(lag) = health.slot_lag {
+            self.slot_lag.with_label_values(&labels).set(lag as i64);
+        }
+        if let Some(latency_ms) = health.latency_ms {
+            self.latency.with_label_values(&labels).set(latency_ms as f64 / 1000.0);
+        }
+    }
+}
+
+impl Default for RpcHealthMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Create Prometheus recorder
 pub fn create_prometheus_handle() -> prometheus::Result<String> {
     use prometheus::Encoder;
@@ -180,6 +515,53 @@ mod tests {
         metrics.update_cache_size(100);
         metrics.record_verification_success("solana-devnet");
         metrics.record_verification_failure("solana-devnet", "invalid_transaction");
+        metrics.record_dedup_hit();
+    }
+
+    #[test]
+    fn test_record_rpc_call() {
+        let metrics = AppMetrics::new();
+        let calls = metrics.rpc_calls.with_label_values(&["getSlot"]).get();
+        let errors = metrics.rpc_errors.with_label_values(&["getSlot", "timeout"]).get();
+
+        metrics.record_rpc_call("getSlot", std::time::Duration::from_millis(12), None);
+        metrics.record_rpc_call("getSlot", std::time::Duration::from_millis(30_000), Some("timeout"));
+        assert_eq!(metrics.rpc_calls.with_label_values(&["getSlot"]).get(), calls + 2);
+        assert_eq!(metrics.rpc_errors.with_label_values(&["getSlot", "timeout"]).get(), errors + 1);
+    }
+
+    #[test]
+    fn test_record_request() {
+        let metrics = AppMetrics::new();
+        let labels = ["/verify", "POST", "200"];
+        let count = metrics.request_duration.with_label_values(&labels).get_sample_count();
+
+        metrics.record_request("/verify", "POST", 200, std::time::Duration::from_millis(8));
+        assert_eq!(metrics.request_duration.with_label_values(&labels).get_sample_count(), count + 1);
+    }
+
+    #[tokio::test]
+    async fn test_time_step() {
+        let metrics = AppMetrics::new();
+        let count = |step: &str| metrics.step_duration.with_label_values(&[step]).get_sample_count();
+        let (decodes, signs) = (count("decode"), count("sign"));
+
+        assert_eq!(metrics.time_step("decode", || 42), 42);
+        assert_eq!(metrics.time_step_async("sign", async { "signed" }).await, "signed");
+        assert_eq!(count("decode"), decodes + 1);
+        assert_eq!(count("sign"), signs + 1);
+    }
+
+    #[test]
+    fn test_cache_stats_gauges() {
+        let metrics = AppMetrics::new();
+        let cache = CacheStats { entry_count: 7, weighted_size: 7, hits: 3, misses: 1 };
+        let dedup = DedupStats { entry_count: 5, window_seconds: 300, backend: "memory" };
+
+        metrics.update_cache_stats(&cache, &dedup);
+        assert_eq!(metrics.cache_size.get(), 7);
+        assert_eq!(metrics.cache_hit_ratio.get(), 0.75);
+        assert_eq!(metrics.dedup_entries.get(), 5);
     }
 }
 
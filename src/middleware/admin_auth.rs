@@ -0,0 +1,226 @@
+use anyhow::{Context, Result};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ipnet::IpNet;
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+use subtle::ConstantTimeEq;
+
+use crate::config::Config;
+
+/// Header carrying the admin API key (`Authorization: Bearer` also works)
+pub const ADMIN_KEY_HEADER: &str = "x-admin-key";
+
+/// Access control for `/admin/*`
+///
+/// Either check can be used on its own; when both are configured a request
+/// must come from an allowed network *and* present the API key.
+#[derive(Clone, Default)]
+pub struct AdminAuth {
+    api_key: Option<String>,
+    allowed_networks: Vec<IpNet>,
+}
+
+impl std::fmt::Debug for AdminAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminAuth")
+            .field("api_key", &self.api_key.as_ref().map(|_| "[REDACTED]"))
+            .field("allowed_networks", &self.allowed_networks)
+            .finish()
+    }
+}
+
+impl AdminAuth {
+    /// Require an API key on admin requests
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Only accept admin requests from these networks
+    pub fn with_allowed_networks(mut self, networks: Vec<IpNet>) -> Self {
+        self.allowed_networks = networks;
+        self
+    }
+
+    /// Load admin auth settings from environment
+    ///
+    /// Returns `Ok(None)` when neither `ADMIN_API_KEY` nor `ADMIN_ALLOWED_IPS`
+    /// is set, and an error when the allowlist cannot be parsed.
+    pub fn from_env() -> Result<Option<Self>> {
+        let api_key = std::env::var("ADMIN_API_KEY").ok().filter(|key| !key.is_empty());
+        let allowed_networks = match std::env::var("ADMIN_ALLOWED_IPS") {
+            Ok(list) => parse_allowlist(&list)?,
+            Err(_) => Vec::new(),
+        };
+
+        if api_key.is_none() && allowed_networks.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self { api_key, allowed_networks }))
+    }
+
+    /// Whether `ip` is inside the allowlist (always true without one)
+    pub fn is_ip_allowed(&self, ip: IpAddr) -> bool {
+        self.allowed_networks.is_empty() || self.allowed_networks.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Whether `provided` matches the configured key (always true without one)
+    pub fn is_key_valid(&self, provided: Option<&str>) -> bool {
+        match (&self.api_key, provided) {
+            (None, _) => true,
+            (Some(expected), Some(provided)) => expected.as_bytes().ct_eq(provided.as_bytes()).into(),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Parse a comma-separated list of CIDR ranges or single addresses
+fn parse_allowlist(list: &str) -> Result<Vec<IpNet>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .parse::<IpNet>()
+                .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                .with_context(|| format!("Invalid entry in ADMIN_ALLOWED_IPS: {}", entry))
+        })
+        .collect()
+}
+
+fn admin_key<B>(req: &axum::http::Request<B>) -> Option<&str> {
+    if let Some(key) = req.headers().get(ADMIN_KEY_HEADER).and_then(|v| v.to_str().ok()) {
+        return Some(key.trim());
+    }
+
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// Middleware guarding the admin route group
+///
+/// A no-op when admin auth is not configured. Requests from outside the
+/// allowlist get a 403, requests without a valid key a 401.
+pub async fn admin_auth_middleware(
+    State(config): State<Config>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(admin_auth) = &config.admin_auth else {
+        return next.run(req).await;
+    };
+
+    // Peer address, not X-Forwarded-For, which any client can set
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    let ip_allowed = match peer {
+        Some(ip) => admin_auth.is_ip_allowed(ip),
+        None => admin_auth.allowed_networks.is_empty(),
+    };
+    if !ip_allowed {
+        tracing::warn!("🔒 Rejected admin request to {} from {:?}", req.uri().path(), peer);
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "client address not allowed" })),
+        )
+            .into_response();
+    }
+
+    if !admin_auth.is_key_valid(admin_key(&req)) {
+        tracing::warn!("🔒 Rejected admin request to {}: invalid or missing API key", req.uri().path());
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid or missing admin API key" })),
+        )
+            .into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Middleware for admin routes that rewrite webhook receivers or dedup state
+///
+/// Other admin routes stay open when admin auth isn't configured, but these
+/// answer 403 until `ADMIN_API_KEY` or `ADMIN_ALLOWED_IPS` is set.
+/// Mounted inside [`admin_auth_middleware`], which checks the credentials.
+pub async fn require_admin_auth(
+    State(config): State<Config>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if config.admin_auth.is_none() {
+        tracing::warn!("🔒 Rejected admin request to {}: admin auth is not configured", req.uri().path());
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "admin auth must be configured (ADMIN_API_KEY or ADMIN_ALLOWED_IPS) to use this endpoint" })),
+        )
+            .into_response();
+    }
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_allowlist() {
+        let networks = parse_allowlist("10.0.0.0/8, 192.168.1.5,,::1").unwrap();
+        assert_eq!(networks.len(), 3);
+
+        let auth = AdminAuth::default().with_allowed_networks(networks);
+        assert!(auth.is_ip_allowed("10.20.30.40".parse().unwrap()));
+        assert!(auth.is_ip_allowed("192.168.1.5".parse().unwrap()));
+        assert!(auth.is_ip_allowed("::1".parse().unwrap()));
+        assert!(!auth.is_ip_allowed("192.168.1.6".parse().unwrap()));
+
+        assert!(parse_allowlist("10.0.0.0/33").is_err());
+        assert!(parse_allowlist("localhost").is_err());
+    }
+
+    #[test]
+    fn test_api_key() {
+        let auth = AdminAuth::default().with_api_key("s3cret");
+        assert!(auth.is_key_valid(Some("s3cret")));
+        assert!(!auth.is_key_valid(Some("s3cre")));
+        assert!(!auth.is_key_valid(None));
+
+        // No key configured: allowlist-only mode
+        assert!(AdminAuth::default().is_key_valid(None));
+    }
+
+    #[test]
+    fn test_admin_key_headers() {
+        let req = axum::http::Request::builder()
+            .header(ADMIN_KEY_HEADER, "from-header")
+            .body(())
+            .unwrap();
+        assert_eq!(admin_key(&req), Some("from-header"));
+
+        let req = axum::http::Request::builder()
+            .header(header::AUTHORIZATION, "Bearer from-bearer")
+            .body(())
+            .unwrap();
+        assert_eq!(admin_key(&req), Some("from-bearer"));
+    }
+
+    #[test]
+    fn test_debug_redacts_key() {
+        let auth = AdminAuth::default().with_api_key("s3cret");
+        assert!(!format!("{:?}", auth).contains("s3cret"));
+    }
+}
@@ -0,0 +1,383 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::config::Config;
+
+/// Minimum time between JWKS refreshes triggered by unknown key IDs
+const JWKS_REFRESH_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Where token signing keys come from
+#[derive(Clone)]
+pub enum JwtKeySource {
+    /// Shared HS256 secret
+    Hs256(DecodingKey),
+    /// RS256 keys published at a JWKS URL
+    Jwks {
+        url: String,
+        keys: Arc<RwLock<JwksCache>>,
+    },
+}
+
+/// Cached keys from the identity provider's JWKS endpoint
+#[derive(Default)]
+pub struct JwksCache {
+    keys: HashMap<String, DecodingKey>,
+    fetched_at: Option<Instant>,
+}
+
+/// Authenticated caller, available to handlers via request extensions
+#[derive(Clone, Debug)]
+pub struct AuthClaims {
+    pub subject: Option<String>,
+    pub scopes: Vec<String>,
+}
+
+impl AuthClaims {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawClaims {
+    sub: Option<String>,
+    /// OAuth2 space-delimited scopes
+    scope: Option<String>,
+    /// Array-style scopes (Azure AD / Okta)
+    scp: Option<Vec<String>>,
+}
+
+impl From<RawClaims> for AuthClaims {
+    fn from(raw: RawClaims) -> Self {
+        let mut scopes: Vec<String> = raw
+            .scope
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        scopes.extend(raw.scp.unwrap_or_default());
+
+        Self {
+            subject: raw.sub,
+            scopes,
+        }
+    }
+}
+
+/// JWT bearer authentication settings
+#[derive(Clone)]
+pub struct JwtAuth {
+    key_source: JwtKeySource,
+    issuer: Option<String>,
+    audience: Option<String>,
+    http: reqwest::Client,
+}
+
+impl std::fmt::Debug for JwtAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = match &self.key_source {
+            JwtKeySource::Hs256(_) => "hs256".to_string(),
+            JwtKeySource::Jwks { url, .. } => format!("jwks ({})", url),
+        };
+        f.debug_struct("JwtAuth")
+            .field("key_source", &source)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .finish()
+    }
+}
+
+impl JwtAuth {
+    /// Create HS256 authentication with a shared secret
+    pub fn hs256(secret: &str) -> Self {
+        Self::with_source(JwtKeySource::Hs256(DecodingKey::from_secret(secret.as_bytes())))
+    }
+
+    /// Create RS256 authentication backed by a JWKS URL
+    pub fn jwks(url: impl Into<String>) -> Self {
+        Self::with_source(JwtKeySource::Jwks {
+            url: url.into(),
+            keys: Arc::new(RwLock::new(JwksCache::default())),
+        })
+    }
+
+    fn with_source(key_source: JwtKeySource) -> Self {
+        Self {
+            key_source,
+            issuer: None,
+            audience: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Require a specific `iss` claim
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Require a specific `aud` claim
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Load JWT settings from environment
+    ///
+    /// `JWT_HS256_SECRET` or `JWT_JWKS_URL` enables authentication;
+    /// `JWT_ISSUER` and `JWT_AUDIENCE` are optional extra checks.
+    pub fn from_env() -> Option<Self> {
+        let auth = if let Ok(secret) = std::env::var("JWT_HS256_SECRET") {
+            Self::hs256(&secret)
+        } else if let Ok(url) = std::env::var("JWT_JWKS_URL") {
+            Self::jwks(url)
+        } else {
+            return None;
+        };
+
+        let auth = match std::env::var("JWT_ISSUER") {
+            Ok(issuer) => auth.with_issuer(issuer),
+            Err(_) => auth,
+        };
+
+        Some(match std::env::var("JWT_AUDIENCE") {
+            Ok(audience) => auth.with_audience(audience),
+            Err(_) => auth,
+        })
+    }
+
+    fn validation(&self, algorithm: Algorithm) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        validation.validate_aud = self.audience.is_some();
+        if let Some(audience) = &self.audience {
+            validation.set_audience(&[audience]);
+        }
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        validation
+    }
+
+    /// Validate a bearer token and return its claims
+    pub async fn authenticate(&self, token: &str) -> Result<AuthClaims, String> {
+        let claims = match &self.key_source {
+            JwtKeySource::Hs256(key) => decode::<RawClaims>(token, key, &self.validation(Algorithm::HS256)),
+            JwtKeySource::Jwks { url, keys } => {
+                let header = decode_header(token).map_err(|e| format!("invalid token header: {}", e))?;
+                let kid = header.kid.ok_or_else(|| "token is missing a key id".to_string())?;
+                let key = self.jwks_key(url, keys, &kid).await?;
+                decode::<RawClaims>(token, &key, &self.validation(Algorithm::RS256))
+            }
+        };
+
+        claims
+            .map(|data| data.claims.into())
+            .map_err(|e| format!("invalid token: {}", e))
+    }
+
+    /// Look up a JWKS key, refreshing the set when the key ID is unknown
+    async fn jwks_key(&self, url: &str, cache: &RwLock<JwksCache>, kid: &str) -> Result<DecodingKey, String> {
+        if let Some(key) = cache.read().await.keys.get(kid) {
+            return Ok(key.clone());
+        }
+
+        let mut cache = cache.write().await;
+        let can_refresh = cache
+            .fetched_at
+            .map(|at| at.elapsed() >= JWKS_REFRESH_COOLDOWN)
+            .unwrap_or(true);
+
+        if can_refresh {
+            let jwks: JwkSet = self
+                .http
+                .get(url)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| format!("failed to fetch JWKS: {}", e))?
+                .json()
+                .await
+                .map_err(|e| format!("invalid JWKS: {}", e))?;
+
+            cache.keys = jwks
+                .keys
+                .iter()
+                .filter_map(|jwk| {
+                    let kid = jwk.common.key_id.clone()?;
+                    DecodingKey::from_jwk(jwk).ok().map(|key| (kid, key))
+                })
+                .collect();
+            cache.fetched_at = Some(Instant::now());
+
+            tracing::info!("🔑 Loaded {} signing keys from JWKS", cache.keys.len());
+        }
+
+        cache
+            .keys
+            .get(kid)
+            .cloned()
+            .ok_or_else(|| format!("unknown signing key: {}", kid))
+    }
+}
+
+/// Scope a route requires, based on its path
+fn required_scope(path: &str) -> Option<&'static str> {
+    if path.ends_with("/settle") {
+        Some("settle")
+    } else if path.contains("/settlements") || path.contains("/settle/") || path.contains("/receipt/") {
+        Some("settlements")
+    } else if path.contains("/verify") || path.ends_with("/validate") {
+        Some("verify")
+    } else {
+        None
+    }
+}
+
+fn auth_error(status: StatusCode, message: &str) -> Response {
+    let mut response = (status, Json(json!({ "error": message }))).into_response();
+    if status == StatusCode::UNAUTHORIZED {
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            header::HeaderValue::from_static("Bearer"),
+        );
+    }
+    response
+}
+
+/// Middleware enforcing JWT bearer authentication on payment routes
+///
+/// A no-op when JWT auth is not configured. `/settle` requires the `settle`
+/// scope, `/verify*` and `/validate` the `verify` scope and `/settlements*` the
+/// `settlements` scope.
+pub async fn jwt_auth_middleware(
+    State(config): State<Config>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(jwt_auth) = &config.jwt_auth else {
+        return next.run(req).await;
+    };
+
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim);
+
+    let Some(token) = token else {
+        return auth_error(StatusCode::UNAUTHORIZED, "missing bearer token");
+    };
+
+    let claims = match jwt_auth.authenticate(token).await {
+        Ok(claims) => claims,
+        Err(e) => {
+            tracing::warn!("🔒 Rejected bearer token: {}", e);
+            return auth_error(StatusCode::UNAUTHORIZED, &e);
+        }
+    };
+
+    if let Some(scope) = required_scope(req.uri().path()) {
+        if !claims.has_scope(scope) {
+            return auth_error(
+                StatusCode::FORBIDDEN,
+                &format!("token is missing required scope: {}", scope),
+            );
+        }
+    }
+
+    req.extensions_mut().insert(claims);
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    fn token(secret: &str, claims: serde_json::Value) -> String {
+        encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+    }
+
+    fn exp() -> u64 {
+        jsonwebtoken::get_current_timestamp() + 300
+    }
+
+    #[tokio::test]
+    async fn test_hs256_scopes() {
+        let auth = JwtAuth::hs256("secret");
+        let claims = auth
+            .authenticate(&token("secret", json!({ "sub": "merchant-1", "scope": "verify settle", "exp": exp() })))
+            .await
+            .unwrap();
+
+        assert_eq!(claims.subject.as_deref(), Some("merchant-1"));
+        assert!(claims.has_scope("settle"));
+        assert!(claims.has_scope("verify"));
+    }
+
+    #[tokio::test]
+    async fn test_scp_array_claim() {
+        let auth = JwtAuth::hs256("secret");
+        let claims = auth
+            .authenticate(&token("secret", json!({ "scp": ["verify"], "exp": exp() })))
+            .await
+            .unwrap();
+
+        assert!(claims.has_scope("verify"));
+        assert!(!claims.has_scope("settle"));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_bad_signature_and_expired() {
+        let auth = JwtAuth::hs256("secret");
+
+        assert!(auth
+            .authenticate(&token("other", json!({ "exp": exp() })))
+            .await
+            .is_err());
+
+        assert!(auth
+            .authenticate(&token("secret", json!({ "exp": 1 })))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_issuer_and_audience() {
+        let auth = JwtAuth::hs256("secret")
+            .with_issuer("https://idp.example.com")
+            .with_audience("x402-facilitator");
+
+        let good = json!({ "iss": "https://idp.example.com", "aud": "x402-facilitator", "exp": exp() });
+        assert!(auth.authenticate(&token("secret", good)).await.is_ok());
+
+        let wrong_aud = json!({ "iss": "https://idp.example.com", "aud": "other", "exp": exp() });
+        assert!(auth.authenticate(&token("secret", wrong_aud)).await.is_err());
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(required_scope("/settle"), Some("settle"));
+        assert_eq!(required_scope("/verify"), Some("verify"));
+        assert_eq!(required_scope("/verify/batch"), Some("verify"));
+        assert_eq!(required_scope("/v1/validate"), Some("verify"));
+        assert_eq!(required_scope("/v1/settlements"), Some("settlements"));
+        assert_eq!(required_scope("/settlements/abc"), Some("settlements"));
+        assert_eq!(required_scope("/v1/receipt/abc"), Some("settlements"));
+        assert_eq!(required_scope("/settle/abc/status"), Some("settlements"));
+        assert_eq!(required_scope("/health"), None);
+    }
+}
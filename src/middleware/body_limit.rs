@@ -0,0 +1,73 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use http_body_util::LengthLimitError;
+use serde_json::json;
+
+use crate::{config::Config, error::AppError};
+
+/// Body size limit of the current route, for extractors reading the body
+/// again (like [`crate::middleware::msgpack::Negotiated`])
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimit(pub usize);
+
+/// Middleware enforcing request body size limits on payment routes
+///
+/// `/verify/batch` uses `max_batch_body_bytes`, everything else
+/// `max_body_bytes`. Oversized bodies are rejected with 413 and a JSON error
+/// before any deserialization happens. `/verify/stream` bodies aren't
+/// buffered; that handler caps each line at `max_body_bytes` instead.
+pub async fn body_limit_middleware(
+    State(config): State<Config>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if req.uri().path().ends_with("/verify/stream") {
+        return next.run(req).await;
+    }
+
+    let limit = if req.uri().path().ends_with("/batch") {
+        config.max_batch_body_bytes
+    } else {
+        config.max_body_bytes
+    };
+
+    // Cheap rejection when the client announces the size up front
+    let declared_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+
+    if declared_length.is_some_and(|length| length > limit) {
+        return too_large(limit);
+    }
+
+    // Chunked or mis-declared bodies are capped while buffering
+    let (mut parts, body) = req.into_parts();
+    let bytes = match axum::body::to_bytes(body, limit).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            if e.into_inner().is::<LengthLimitError>() {
+                return too_large(limit);
+            }
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "Failed to read request body" })),
+            )
+                .into_response();
+        }
+    };
+
+    parts.extensions.insert(BodyLimit(limit));
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
+
+fn too_large(limit: usize) -> Response {
+    AppError::PayloadTooLarge(format!("request body exceeds the {} byte limit", limit)).into_response()
+}
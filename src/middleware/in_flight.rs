@@ -0,0 +1,147 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use crate::config::Config;
+
+/// Counter of outstanding work that can be awaited until it drains
+#[derive(Clone, Default, Debug)]
+pub struct InFlightCounter {
+    count: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+}
+
+/// Decrements its counter when dropped (including on cancellation)
+pub struct InFlightGuard {
+    counter: InFlightCounter,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if self.counter.count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.counter.idle.notify_waiters();
+        }
+    }
+}
+
+impl InFlightCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register one unit of outstanding work
+    pub fn track(&self) -> InFlightGuard {
+        self.count.fetch_add(1, Ordering::AcqRel);
+        InFlightGuard {
+            counter: self.clone(),
+        }
+    }
+
+    /// Number of outstanding units of work
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// Wait until nothing is outstanding, up to `timeout`
+    ///
+    /// Returns `false` if work was still outstanding when the timeout hit.
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, async {
+            loop {
+                let notified = self.idle.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                if self.count() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+}
+
+/// In-flight HTTP requests, with settlements tracked separately
+///
+/// Graceful shutdown waits on these so that a `/settle` which has already
+/// submitted a transaction gets to return its signature to the caller.
+#[derive(Clone, Default, Debug)]
+pub struct InFlightRequests {
+    pub requests: InFlightCounter,
+    pub settlements: InFlightCounter,
+}
+
+impl InFlightRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Middleware counting in-flight requests
+pub async fn in_flight_middleware(
+    State(config): State<Config>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let _request = config.in_flight.requests.track();
+    let _settlement = req
+        .uri()
+        .path()
+        .ends_with("/settle")
+        .then(|| config.in_flight.settlements.track());
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_counter_tracks_guards() {
+        let counter = InFlightCounter::new();
+        let first = counter.track();
+        let second = counter.track();
+        assert_eq!(counter.count(), 2);
+
+        drop(first);
+        assert_eq!(counter.count(), 1);
+        drop(second);
+        assert_eq!(counter.count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_wait_idle_returns_when_drained() {
+        let counter = InFlightCounter::new();
+        let guard = counter.track();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            drop(guard);
+        });
+
+        assert!(counter.wait_idle(Duration::from_secs(5)).await);
+    }
+
+    #[tokio::test]
+    async fn test_wait_idle_times_out() {
+        let counter = InFlightCounter::new();
+        let _guard = counter.track();
+
+        assert!(!counter.wait_idle(Duration::from_millis(50)).await);
+    }
+
+    #[tokio::test]
+    async fn test_wait_idle_when_already_idle() {
+        let counter = InFlightCounter::new();
+        assert!(counter.wait_idle(Duration::from_millis(10)).await);
+    }
+}
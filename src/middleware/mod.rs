@@ -1,3 +1,13 @@
+pub mod admin_auth;
+pub mod auth;
+pub mod body_limit;
+pub mod in_flight;
+pub mod msgpack;
+#[cfg(feature = "payment-required")]
+pub mod payment_required;
 pub mod rate_limit;
 pub mod request_id;
+pub mod request_metrics;
+pub mod response_signature;
+pub mod tenant;
 
@@ -0,0 +1,171 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{config::Config, error::AppError, middleware::body_limit::BodyLimit};
+
+/// Content type of MessagePack bodies
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Media types accepted as MessagePack
+const MSGPACK_TYPES: [&str; 3] = [MSGPACK_CONTENT_TYPE, "application/x-msgpack", "application/vnd.msgpack"];
+
+tokio::task_local! {
+    /// Whether [`Negotiated`] responses of the current request are MessagePack
+    static MSGPACK_RESPONSE: bool;
+}
+
+fn is_msgpack(value: Option<&HeaderValue>) -> bool {
+    value
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| MSGPACK_TYPES.iter().any(|media_type| v.contains(media_type)))
+}
+
+/// Whether the response should be MessagePack: when asked for in `Accept`,
+/// or for MessagePack requests that accept anything
+fn wants_msgpack(headers: &HeaderMap, msgpack_request: bool) -> bool {
+    let accept = headers.get(header::ACCEPT);
+    let accepts_any = match accept.and_then(|v| v.to_str().ok()) {
+        Some(accept) => accept.trim().starts_with("*/*"),
+        None => true,
+    };
+    is_msgpack(accept) || (msgpack_request && accepts_any)
+}
+
+/// Middleware negotiating the response format of the payment routes
+///
+/// Decides from `Accept` (and the request's `Content-Type`) whether the
+/// [`Negotiated`] bodies handlers return are encoded as MessagePack (maps
+/// keyed by field name) or JSON. Error responses stay JSON.
+pub async fn msgpack_middleware(req: Request, next: Next) -> Response {
+    let msgpack_request = is_msgpack(req.headers().get(header::CONTENT_TYPE));
+    let msgpack_response = wants_msgpack(req.headers(), msgpack_request);
+
+    MSGPACK_RESPONSE.scope(msgpack_response, next.run(req)).await
+}
+
+/// JSON or MessagePack body of a payment route
+///
+/// As an extractor, decodes `T` straight from a `Content-Type:
+/// application/msgpack` body, read up to the route's body limit, and from
+/// JSON otherwise. As a response, encodes `T` in the format negotiated by
+/// [`msgpack_middleware`] (JSON outside of it).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Negotiated<T>(pub T);
+
+#[async_trait]
+impl<T> FromRequest<Config> for Negotiated<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, config: &Config) -> Result<Self, Self::Rejection> {
+        if !is_msgpack(req.headers().get(header::CONTENT_TYPE)) {
+            let Json(value) = Json::<T>::from_request(req, config).await.map_err(IntoResponse::into_response)?;
+            return Ok(Self(value));
+        }
+
+        let limit = req
+            .extensions()
+            .get::<BodyLimit>()
+            .map_or(config.max_body_bytes, |BodyLimit(limit)| *limit);
+        let bytes = axum::body::to_bytes(req.into_body(), limit)
+            .await
+            .map_err(|_| AppError::BadRequest("Failed to read request body".to_string()).into_response())?;
+
+        rmp_serde::from_slice(&bytes)
+            .map(Self)
+            .map_err(|e| AppError::BadRequest(format!("Invalid MessagePack body: {}", e)).into_response())
+    }
+}
+
+impl<T> IntoResponse for Negotiated<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        if MSGPACK_RESPONSE.try_with(|msgpack| *msgpack).unwrap_or(false) {
+            match rmp_serde::to_vec_named(&self.0) {
+                Ok(packed) => return ([(header::CONTENT_TYPE, MSGPACK_CONTENT_TYPE)], packed).into_response(),
+                Err(e) => tracing::warn!("Failed to encode response as MessagePack, sending JSON: {}", e),
+            }
+        }
+        Json(self.0).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware, routing::post, Router};
+    use serde::Deserialize;
+    use solana_sdk::signature::Keypair;
+    use tower::ServiceExt;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Echo {
+        is_valid: bool,
+        payer: Option<String>,
+    }
+
+    // Multi-threaded: `Config::new` builds a blocking RPC client
+    fn app() -> Router {
+        let private_key = bs58::encode(Keypair::new().to_bytes()).into_string();
+        let mut config = Config::new("http://127.0.0.1:8899", "solana-devnet", &private_key).unwrap();
+        config.max_body_bytes = 64;
+
+        Router::new()
+            .route("/echo", post(|Negotiated(body): Negotiated<Echo>| async move { Negotiated(body) }))
+            .layer(middleware::from_fn(msgpack_middleware))
+            .with_state(config)
+    }
+
+    async fn send(content_type: &str, accept: Option<&str>, body: Vec<u8>) -> Response {
+        let mut request = Request::post("/echo").header(header::CONTENT_TYPE, content_type);
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+        app().oneshot(request.body(Body::from(body)).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_msgpack_round_trip() {
+        let echo = Echo { is_valid: true, payer: Some("PayerWallet".to_string()) };
+        let body = rmp_serde::to_vec_named(&echo).unwrap();
+        let response = send(MSGPACK_CONTENT_TYPE, None, body.clone()).await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], MSGPACK_CONTENT_TYPE);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(rmp_serde::from_slice::<Echo>(&bytes).unwrap(), echo);
+
+        // An explicit Accept wins over the request format
+        let response = send(MSGPACK_CONTENT_TYPE, Some("application/json"), body).await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_json_request_msgpack_response() {
+        let body = br#"{"isValid":false,"payer":null}"#.to_vec();
+        let response = send("application/json", Some("application/x-msgpack"), body).await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], MSGPACK_CONTENT_TYPE);
+
+        let response = send(MSGPACK_CONTENT_TYPE, None, b"\xc1".to_vec()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_msgpack_body_limit() {
+        // Without the body limit middleware, the configured max_body_bytes applies
+        let echo = Echo { is_valid: true, payer: Some("P".repeat(100)) };
+        let response = send(MSGPACK_CONTENT_TYPE, None, rmp_serde::to_vec_named(&echo).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
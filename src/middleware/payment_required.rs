@@ -0,0 +1,267 @@
+use axum::{
+    extract::{Request, State},
+    http::{header::HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::client::FacilitatorClient;
+use crate::config::Config;
+use crate::handlers;
+use crate::middleware::msgpack::Negotiated;
+use crate::requirements::PaymentRequiredResponse;
+use crate::types::requests::{PaymentPayload, PaymentRequirements, SettleRequest, VerifyRequest};
+use crate::types::responses::{SettleResponse, VerifyResponse};
+
+pub use crate::types::requests::PAYMENT_HEADER;
+
+/// Header carrying the base64-encoded JSON [`SettleResponse`]
+pub const PAYMENT_RESPONSE_HEADER: &str = "x-payment-response";
+
+/// Where payments are verified and settled
+#[derive(Clone)]
+pub enum PaymentVerifier {
+    /// In this process, with this facilitator's configuration (boxed, as
+    /// it's much larger than a client)
+    Local(Box<Config>),
+    /// By a facilitator over HTTP
+    Remote(FacilitatorClient),
+}
+
+impl PaymentVerifier {
+    async fn verify(&self, request: VerifyRequest) -> Result<VerifyResponse, String> {
+        match self {
+            PaymentVerifier::Local(config) => {
+                Ok(handlers::verify::verify(State(Config::clone(config)), None, None, Negotiated(request)).await.0)
+            }
+            PaymentVerifier::Remote(client) => client.verify(&request).await.map_err(|e| e.to_string()),
+        }
+    }
+
+    async fn settle(&self, request: SettleRequest) -> Result<SettleResponse, String> {
+        match self {
+            PaymentVerifier::Local(config) => {
+                Ok(handlers::settle::settle(State(Config::clone(config)), None, None, Negotiated(request)).await.0)
+            }
+            PaymentVerifier::Remote(client) => client.settle(&request).await.map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Payment requirements of the routes a [`payment_required`] layer guards
+#[derive(Clone)]
+pub struct PaymentRequired {
+    pub requirements: PaymentRequirements,
+    pub verifier: PaymentVerifier,
+}
+
+impl PaymentRequired {
+    pub fn new(requirements: PaymentRequirements, verifier: PaymentVerifier) -> Self {
+        Self { requirements, verifier }
+    }
+
+    fn payment_required(&self, error: Option<String>) -> Response {
+        let mut body = PaymentRequiredResponse::new(vec![self.requirements.clone()]);
+        if let Some(error) = error {
+            body = body.with_error(error);
+        }
+        (StatusCode::PAYMENT_REQUIRED, Json(body)).into_response()
+    }
+}
+
+/// Verified payment of the current request, in request extensions
+#[derive(Clone, Debug)]
+pub struct VerifiedPayment {
+    /// Wallet that paid
+    pub payer: String,
+    pub payload: PaymentPayload,
+}
+
+/// Middleware enforcing x402 payments on resource server routes
+///
+/// Requests without `X-PAYMENT` get a 402 with the requirements. Otherwise
+/// the payment is verified, the handler runs with a [`VerifiedPayment`]
+/// extension, and a successful response settles the payment and carries the
+/// result in `X-PAYMENT-RESPONSE`. Handler errors are returned unsettled.
+///
+/// ```no_run
+/// # fn example(paywall: x402_facilitator::middleware::payment_required::PaymentRequired) {
+/// use axum::{middleware, routing::get, Router};
+/// use std::sync::Arc;
+/// use x402_facilitator::middleware::payment_required::payment_required;
+///
+/// let app: Router = Router::new()
+///     .route("/premium", get(|| async { "paid content" }))
+///     .route_layer(middleware::from_fn_with_state(Arc::new(paywall), payment_required));
+/// # }
+/// ```
+pub async fn payment_required(
+    State(paywall): State<Arc<PaymentRequired>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let Some(header) = req.headers().get(PAYMENT_HEADER) else {
+        return paywall.payment_required(None);
+    };
+    let payload = match header
+        .to_str()
+        .map_err(|_| "X-PAYMENT header is not valid base64".to_string())
+        .and_then(PaymentPayload::from_header)
+    {
+        Ok(payload) => payload,
+        Err(e) => return paywall.payment_required(Some(e)),
+    };
+
+    let verify_request = VerifyRequest {
+        payment_payload: payload.clone(),
+        payment_requirements: paywall.requirements.clone(),
+        dry_run: false,
+    };
+    let verified = match paywall.verifier.verify(verify_request).await {
+        Ok(response) => response,
+        Err(e) => return facilitator_error(e),
+    };
+    if !verified.is_valid {
+        return paywall.payment_required(verified.invalid_reason.or(Some("Invalid payment".to_string())));
+    }
+
+    req.extensions_mut().insert(VerifiedPayment {
+        payer: verified.payer.unwrap_or_default(),
+        payload: payload.clone(),
+    });
+    let mut response = next.run(req).await;
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let settle_request = SettleRequest {
+        payment_payload: payload,
+        payment_requirements: paywall.requirements.clone(),
+    };
+    let settled = match paywall.verifier.settle(settle_request).await {
+        Ok(settled) => settled,
+        Err(e) => return facilitator_error(e),
+    };
+    if !settled.success {
+        return paywall.payment_required(settled.error_reason.or(Some("Settlement failed".to_string())));
+    }
+
+    if let Ok(value) = serde_json::to_vec(&settled)
+        .map(|json| general_purpose::STANDARD.encode(json))
+        .map_err(|e| e.to_string())
+        .and_then(|encoded| HeaderValue::from_str(&encoded).map_err(|e| e.to_string()))
+    {
+        response.headers_mut().insert(PAYMENT_RESPONSE_HEADER, value);
+    }
+    response
+}
+
+fn facilitator_error(error: String) -> Response {
+    tracing::error!("❌ Facilitator request failed: {}", error);
+    (
+        StatusCode::BAD_GATEWAY,
+        Json(json!({ "error": "Payment facilitator unavailable" })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requirements::RequirementsBuilder;
+    use crate::types::requests::SvmPayload;
+    use axum::{body::Body, middleware, routing::{get, post}, Extension, Router};
+    use solana_sdk::pubkey::Pubkey;
+    use tower::ServiceExt;
+
+    /// Facilitator accepting every payment
+    async fn facilitator() -> String {
+        let router = Router::new()
+            .route(
+                "/verify",
+                post(|| async { Json(json!({ "isValid": true, "payer": "PayerWallet" })) }),
+            )
+            .route(
+                "/settle",
+                post(|| async {
+                    Json(json!({ "success": true, "network": "solana-devnet", "transaction": "5sig", "payer": "PayerWallet" }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn app(facilitator_url: &str) -> Router {
+        let requirements = RequirementsBuilder::new("solana-devnet", Pubkey::new_unique())
+            .pay_to(Pubkey::new_unique())
+            .amount(1_000)
+            .resource("/premium")
+            .build()
+            .unwrap();
+        let paywall = PaymentRequired::new(
+            requirements,
+            PaymentVerifier::Remote(FacilitatorClient::new(facilitator_url)),
+        );
+
+        Router::new()
+            .route(
+                "/premium",
+                get(|Extension(payment): Extension<VerifiedPayment>| async move { payment.payer }),
+            )
+            .route_layer(middleware::from_fn_with_state(Arc::new(paywall), payment_required))
+    }
+
+    fn payment_header() -> String {
+        let payload = PaymentPayload {
+            x402_version: 1,
+            scheme: "exact".to_string(),
+            network: "solana-devnet".to_string(),
+            payload: SvmPayload { transaction: "AQ==".to_string() },
+            timestamp: None,
+        };
+        general_purpose::STANDARD.encode(serde_json::to_vec(&payload).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_missing_payment_returns_requirements() {
+        let response = app("http://127.0.0.1:1")
+            .oneshot(Request::get("/premium").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYMENT_REQUIRED);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["x402Version"], 1);
+        assert_eq!(body["accepts"][0]["resource"], "/premium");
+
+        let response = app("http://127.0.0.1:1")
+            .oneshot(Request::get("/premium").header(PAYMENT_HEADER, "not base64!").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYMENT_REQUIRED);
+    }
+
+    #[tokio::test]
+    async fn test_paid_request_is_served_and_settled() {
+        let response = app(&facilitator().await)
+            .oneshot(Request::get("/premium").header(PAYMENT_HEADER, payment_header()).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let settled = response.headers().get(PAYMENT_RESPONSE_HEADER).unwrap();
+        let settled: SettleResponse =
+            serde_json::from_slice(&general_purpose::STANDARD.decode(settled.as_bytes()).unwrap()).unwrap();
+        assert_eq!(settled.transaction, "5sig");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"PayerWallet");
+    }
+}
@@ -1,80 +1,401 @@
-use governor::{Quota, RateLimiter, clock::DefaultClock, state::{direct::NotKeyed, InMemoryState}};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use arc_swap::ArcSwap;
+use governor::{
+    clock::{Clock, DefaultClock},
+    state::keyed::DefaultKeyedStateStore,
+    Quota, RateLimiter,
+};
+use std::net::SocketAddr;
 use std::num::NonZeroU32;
 use std::sync::Arc;
+use std::time::Duration;
+use serde_json::json;
 
-pub type DefaultDirectRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+use crate::config::Config;
+use crate::middleware::auth::AuthClaims;
+use crate::middleware::tenant::TENANT_KEY_HEADER;
+use crate::tenants::Tenant;
+#[cfg(feature = "redis")]
+use crate::redis_backend::{RedisRateLimiter, RedisStore};
+
+pub type DefaultKeyedRateLimiter = RateLimiter<String, DefaultKeyedStateStore<String>, DefaultClock>;
+
+/// Number of tracked clients above which idle entries are pruned
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Endpoint groups with independent quotas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitRoute {
+    Verify,
+    Settle,
+}
+
+impl RateLimitRoute {
+    /// Map a request path to its quota group
+    pub fn from_path(path: &str) -> Option<Self> {
+        if path.ends_with("/settle") {
+            Some(Self::Settle)
+        } else if path.contains("/verify") {
+            Some(Self::Verify)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Verify => "verify",
+            Self::Settle => "settle",
+        }
+    }
+}
+
+/// Per-second quota with burst allowance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RateLimitQuota {
+    pub per_second: u32,
+    pub burst_size: u32,
+}
+
+impl RateLimitQuota {
+    fn to_governor(self) -> Quota {
+        let per_second_nz = NonZeroU32::new(self.per_second).unwrap_or(NonZeroU32::new(10).unwrap());
+        let burst_size_nz = NonZeroU32::new(self.burst_size).unwrap_or(NonZeroU32::new(20).unwrap());
+
+        Quota::per_second(per_second_nz).allow_burst(burst_size_nz)
+    }
+}
+
+/// In-memory limiters built for a particular pair of quotas
+struct Limiters {
+    verify: DefaultKeyedRateLimiter,
+    settle: DefaultKeyedRateLimiter,
+    verify_quota: RateLimitQuota,
+    settle_quota: RateLimitQuota,
+}
+
+impl Limiters {
+    fn new(verify_quota: RateLimitQuota, settle_quota: RateLimitQuota) -> Self {
+        Self {
+            verify: RateLimiter::keyed(verify_quota.to_governor()),
+            settle: RateLimiter::keyed(settle_quota.to_governor()),
+            verify_quota,
+            settle_quota,
+        }
+    }
+}
 
 /// Rate limiter state
+///
+/// Limits are tracked per client (tenant API key, JWT subject or IP
+/// address), with separate quotas for `/verify` and `/settle` so a burst of
+/// verifications cannot exhaust a client's settlement budget.
+///
+/// With the `redis` feature and a Redis backend attached, quotas are shared
+/// across replicas and the in-memory limiters only serve as a fallback.
+///
+/// Quotas can be changed at runtime with [`RateLimitState::set_quotas`].
 #[derive(Clone)]
 pub struct RateLimitState {
-    pub limiter: Arc<DefaultDirectRateLimiter>,
+    limiters: Arc<ArcSwap<Limiters>>,
+    #[cfg(feature = "redis")]
+    redis: Option<Arc<RedisRateLimiter>>,
 }
 
 impl RateLimitState {
     /// Create a new rate limiter
-    /// 
+    ///
     /// # Arguments
-    /// * `per_second` - Number of requests allowed per second
-    /// * `burst_size` - Maximum burst size
-    pub fn new(per_second: u32, burst_size: u32) -> Self {
-        let per_second_nz = NonZeroU32::new(per_second).unwrap_or(NonZeroU32::new(10).unwrap());
-        let burst_size_nz = NonZeroU32::new(burst_size).unwrap_or(NonZeroU32::new(20).unwrap());
-        
-        let quota = Quota::per_second(per_second_nz)
-            .allow_burst(burst_size_nz);
-        
-        let limiter = Arc::new(RateLimiter::direct(quota));
-        
+    /// * `verify_quota` - Per-client quota for `/verify` and `/verify/batch`
+    /// * `settle_quota` - Per-client quota for `/settle`
+    pub fn new(verify_quota: RateLimitQuota, settle_quota: RateLimitQuota) -> Self {
         tracing::info!(
-            "✅ Rate limiter initialized: {} req/s, burst {}",
-            per_second,
-            burst_size
+            "✅ Rate limiter initialized: verify {} req/s (burst {}), settle {} req/s (burst {}) per client",
+            verify_quota.per_second,
+            verify_quota.burst_size,
+            settle_quota.per_second,
+            settle_quota.burst_size
         );
 
-        Self { limiter }
+        Self {
+            limiters: Arc::new(ArcSwap::from_pointee(Limiters::new(verify_quota, settle_quota))),
+            #[cfg(feature = "redis")]
+            redis: None,
+        }
+    }
+
+    /// Replace both quotas for all clones of this limiter
+    ///
+    /// In-memory client state starts over under the new quotas; Redis state
+    /// is kept, as the GCRA script reads the quota on every check.
+    pub fn set_quotas(&self, verify_quota: RateLimitQuota, settle_quota: RateLimitQuota) {
+        self.limiters.store(Arc::new(Limiters::new(verify_quota, settle_quota)));
+    }
+
+    /// Share quotas across replicas through Redis
+    #[cfg(feature = "redis")]
+    pub fn with_redis(mut self, store: Arc<RedisStore>) -> Self {
+        self.redis = Some(Arc::new(RedisRateLimiter::new(store)));
+        self
+    }
+
+    /// Storage backend in use ("memory" or "redis")
+    pub fn backend(&self) -> &'static str {
+        #[cfg(feature = "redis")]
+        if self.redis.is_some() {
+            return "redis";
+        }
+
+        "memory"
+    }
+
+    /// Quota applied to a route group
+    pub fn quota(&self, route: RateLimitRoute) -> RateLimitQuota {
+        let limiters = self.limiters.load();
+        match route {
+            RateLimitRoute::Verify => limiters.verify_quota,
+            RateLimitRoute::Settle => limiters.settle_quota,
+        }
+    }
+
+    /// Check if a request from `client` is allowed
+    ///
+    /// Returns how long the client should wait when it is over quota.
+    pub fn check(&self, route: RateLimitRoute, client: &str) -> Result<(), Duration> {
+        #[cfg(feature = "redis")]
+        if let Some(redis) = &self.redis {
+            match redis.check(route, client, self.quota(route)) {
+                Ok(result) => return result,
+                Err(e) => tracing::warn!("⚠️  Redis rate limiter unavailable, using local limits: {}", e),
+            }
+        }
+
+        let limiters = self.limiters.load();
+        let limiter = match route {
+            RateLimitRoute::Verify => &limiters.verify,
+            RateLimitRoute::Settle => &limiters.settle,
+        };
+
+        // Keep memory bounded when many distinct clients show up
+        if limiter.len() > MAX_TRACKED_CLIENTS {
+            limiter.retain_recent();
+            limiter.shrink_to_fit();
+        }
+
+        limiter
+            .check_key(&client.to_string())
+            .map_err(|not_until| not_until.wait_time_from(DefaultClock::default().now()))
+    }
+}
+
+/// Identify the client a request should be rate limited as
+///
+/// Prefers the `X-API-Key` header of a known tenant, then an authenticated
+/// JWT subject, and falls back to the peer IP address. Any other API key is
+/// ignored, so clients can't dodge their quota by sending a fresh one with
+/// each request.
+pub fn client_key<B>(req: &axum::http::Request<B>) -> String {
+    if req.extensions().get::<Arc<Tenant>>().is_some() {
+        if let Some(api_key) = req.headers().get(TENANT_KEY_HEADER).and_then(|v| v.to_str().ok()) {
+            return format!("key:{}", api_key.trim());
+        }
+    }
+
+    if let Some(subject) = req
+        .extensions()
+        .get::<AuthClaims>()
+        .and_then(|claims| claims.subject.as_ref())
+    {
+        return format!("sub:{}", subject);
+    }
+
+    match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+        None => "ip:unknown".to_string(),
     }
-    
-    /// Check if a request is allowed
-    pub fn check(&self) -> bool {
-        self.limiter.check().is_ok()
+}
+
+/// Client key safe to write to logs (API keys are truncated)
+fn redacted_client_key(key: &str) -> String {
+    match key.strip_prefix("key:") {
+        Some(api_key) => format!("key:{}…", api_key.chars().take(4).collect::<String>()),
+        None => key.to_string(),
     }
 }
 
-/// Check rate limit for a specific key
-pub fn check_rate_limit(limiter: &DefaultDirectRateLimiter) -> bool {
-    limiter.check().is_ok()
+/// Middleware enforcing per-client rate limits on payment routes
+///
+/// Tenants with their own quotas are limited as a whole under those;
+/// otherwise a no-op when rate limiting is disabled. Rejected requests get a
+/// 429 with a `Retry-After` header, are recorded as `RateLimitExceeded`
+/// audit events and send a `rate_limit.exceeded` webhook.
+pub async fn rate_limit_middleware(
+    State(config): State<Config>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(route) = RateLimitRoute::from_path(req.uri().path()) else {
+        return next.run(req).await;
+    };
+
+    let tenant = req.extensions().get::<Arc<Tenant>>().cloned();
+    let (limiter, client) = match tenant.as_deref() {
+        Some(Tenant { id, rate_limiter: Some(limiter), .. }) => (limiter, format!("tenant:{}", id)),
+        _ => match &config.rate_limiter {
+            Some(limiter) => (limiter, client_key(&req)),
+            None => return next.run(req).await,
+        },
+    };
+
+    match limiter.check(route, &client) {
+        Ok(()) => next.run(req).await,
+        Err(wait) => {
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            let client = redacted_client_key(&client);
+
+            tracing::warn!("🛡️  Rate limit exceeded for {} on {}", client, req.uri().path());
+            config
+                .audit_logger
+                .for_tenant(tenant.as_ref().map(|tenant| tenant.id.as_str()))
+                .log_rate_limit_exceeded(&config.network, &client, req.uri().path());
+            crate::alerts::rate_limit_exceeded(&config, tenant.as_deref(), &client, req.uri().path(), retry_after);
+
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(json!({
+                    "error": "Rate limit exceeded",
+                    "retry_after_seconds": retry_after,
+                })),
+            )
+                .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from(retry_after));
+            response
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn quota(per_second: u32, burst_size: u32) -> RateLimitQuota {
+        RateLimitQuota { per_second, burst_size }
+    }
+
     #[test]
     fn test_rate_limiter_creation() {
-        let state = RateLimitState::new(10, 20);
-        assert!(state.limiter.check().is_ok());
+        let state = RateLimitState::new(quota(10, 20), quota(5, 10));
+        assert!(state.check(RateLimitRoute::Verify, "ip:127.0.0.1").is_ok());
+        assert!(state.check(RateLimitRoute::Settle, "ip:127.0.0.1").is_ok());
     }
 
     #[test]
     fn test_rate_limiter_allows_within_limit() {
-        let state = RateLimitState::new(10, 10);
-        
+        let state = RateLimitState::new(quota(10, 10), quota(10, 10));
+
         // Should allow up to burst size
         for _ in 0..10 {
-            assert!(state.limiter.check().is_ok());
+            assert!(state.check(RateLimitRoute::Verify, "client").is_ok());
         }
     }
 
     #[test]
     fn test_rate_limiter_blocks_over_limit() {
-        let state = RateLimitState::new(1, 2);
-        
+        let state = RateLimitState::new(quota(1, 2), quota(1, 2));
+
         // Use up the burst
-        assert!(state.limiter.check().is_ok());
-        assert!(state.limiter.check().is_ok());
-        
-        // Should be rate limited
-        assert!(state.limiter.check().is_err());
+        assert!(state.check(RateLimitRoute::Verify, "client").is_ok());
+        assert!(state.check(RateLimitRoute::Verify, "client").is_ok());
+
+        // Should be rate limited, with a wait hint
+        let wait = state.check(RateLimitRoute::Verify, "client").unwrap_err();
+        assert!(wait <= Duration::from_secs(1));
     }
-}
 
+    #[test]
+    fn test_clients_limited_independently() {
+        let state = RateLimitState::new(quota(1, 1), quota(1, 1));
+
+        assert!(state.check(RateLimitRoute::Verify, "noisy").is_ok());
+        assert!(state.check(RateLimitRoute::Verify, "noisy").is_err());
+
+        // Another client is unaffected
+        assert!(state.check(RateLimitRoute::Verify, "quiet").is_ok());
+    }
+
+    #[test]
+    fn test_verify_and_settle_quotas_separate() {
+        let state = RateLimitState::new(quota(1, 1), quota(1, 1));
+
+        assert!(state.check(RateLimitRoute::Verify, "client").is_ok());
+        assert!(state.check(RateLimitRoute::Verify, "client").is_err());
+
+        // Exhausting verify leaves settle untouched
+        assert!(state.check(RateLimitRoute::Settle, "client").is_ok());
+    }
+
+    #[test]
+    fn test_set_quotas_applies_to_clones() {
+        let state = RateLimitState::new(quota(1, 1), quota(1, 1));
+        let shared = state.clone();
+
+        assert!(shared.check(RateLimitRoute::Settle, "client").is_ok());
+        assert!(shared.check(RateLimitRoute::Settle, "client").is_err());
+
+        state.set_quotas(quota(1, 1), quota(5, 3));
+        assert_eq!(shared.quota(RateLimitRoute::Settle), quota(5, 3));
+
+        for _ in 0..3 {
+            assert!(shared.check(RateLimitRoute::Settle, "client").is_ok());
+        }
+        assert!(shared.check(RateLimitRoute::Settle, "client").is_err());
+    }
+
+    #[test]
+    fn test_route_from_path() {
+        assert_eq!(RateLimitRoute::from_path("/verify"), Some(RateLimitRoute::Verify));
+        assert_eq!(RateLimitRoute::from_path("/verify/batch"), Some(RateLimitRoute::Verify));
+        assert_eq!(RateLimitRoute::from_path("/settle"), Some(RateLimitRoute::Settle));
+        assert_eq!(RateLimitRoute::from_path("/health"), None);
+    }
+
+    #[test]
+    fn test_client_key_precedence() {
+        let addr: SocketAddr = "10.0.0.7:5555".parse().unwrap();
+
+        let mut req = axum::http::Request::builder().uri("/verify").body(()).unwrap();
+        req.extensions_mut().insert(ConnectInfo(addr));
+        assert_eq!(client_key(&req), "ip:10.0.0.7");
+
+        req.extensions_mut().insert(AuthClaims {
+            subject: Some("merchant-1".to_string()),
+            scopes: vec![],
+        });
+        assert_eq!(client_key(&req), "sub:merchant-1");
+
+        // An API key no tenant owns doesn't count
+        req.headers_mut().insert("x-api-key", "abc123".parse().unwrap());
+        assert_eq!(client_key(&req), "sub:merchant-1");
+
+        let tenants = crate::tenants::Tenants::from_specs(
+            serde_json::from_value(json!([{ "id": "acme", "api_keys": ["abc123"] }])).unwrap(),
+        )
+        .unwrap();
+        req.extensions_mut().insert(tenants.get("abc123").unwrap().clone());
+        assert_eq!(client_key(&req), "key:abc123");
+    }
+
+    #[test]
+    fn test_redacted_client_key() {
+        assert_eq!(redacted_client_key("key:abc123secret"), "key:abc1…");
+        assert_eq!(redacted_client_key("ip:10.0.0.7"), "ip:10.0.0.7");
+    }
+}
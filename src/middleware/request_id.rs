@@ -4,8 +4,11 @@ use axum::{
     middleware::Next,
     response::Response,
 };
+use sentry::SentryFutureExt;
 use uuid::Uuid;
 
+use crate::error_reporting;
+
 /// Request ID extension that can be extracted in handlers
 #[derive(Clone, Debug)]
 pub struct RequestId(pub String);
@@ -42,8 +45,11 @@ pub async fn request_id_middleware(
 
     tracing::info!("Request started");
 
-    // Process request
-    let mut response = next.run(req).await;
+    // Process request, tagging any reported errors with the request ID
+    let mut response = match error_reporting::request_hub(&request_id) {
+        Some(hub) => next.run(req).bind_hub(hub).await,
+        None => next.run(req).await,
+    };
 
     // Add request ID to response headers
     if let Ok(header_value) = HeaderValue::from_str(&request_id) {
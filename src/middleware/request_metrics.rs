@@ -0,0 +1,36 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+
+use crate::config::Config;
+
+/// Endpoint label of requests that matched no route
+const UNMATCHED: &str = "unmatched";
+
+/// Middleware recording `x402_request_duration_seconds` for every request
+///
+/// Labelled by route template (`/v1/settlements/:signature` rather than
+/// each signature, keeping the label set bounded), method and status code.
+/// Streamed responses are timed up to their headers.
+pub async fn request_metrics_middleware(
+    State(config): State<Config>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let endpoint = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED.to_string());
+    let method = req.method().clone();
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+    config
+        .metrics
+        .record_request(&endpoint, method.as_str(), response.status().as_u16(), start.elapsed());
+    response
+}
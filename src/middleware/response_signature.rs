@@ -0,0 +1,170 @@
+use anyhow::{bail, Context, Result};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+
+use crate::{config::Config, middleware::msgpack::MSGPACK_CONTENT_TYPE};
+
+/// Response header carrying the facilitator's signature of the body
+pub const SIGNATURE_HEADER: &str = "x-facilitator-signature";
+
+/// Prefix of every signed response message
+///
+/// Like [`crate::receipts::RECEIPT_DOMAIN`], the leading `0xff` byte keeps
+/// a response signature from ever doubling as a transaction signature.
+pub const RESPONSE_DOMAIN: &[u8] = b"\xffx402 response v1\n";
+
+/// Bytes covered by a response signature: [`RESPONSE_DOMAIN`] followed by
+/// the body as canonical JSON (object keys sorted, no whitespace)
+///
+/// Re-encoding the body (whitespace, key order, MessagePack) doesn't change
+/// the message, so the signature still checks after proxies touch it.
+pub fn response_message(body: &[u8]) -> Result<Vec<u8>> {
+    let value: Value = serde_json::from_slice(body).context("Response body is not JSON")?;
+    Ok(canonical_message(&value))
+}
+
+/// [`response_message`] of a MessagePack body, as signed when the response
+/// was negotiated as MessagePack
+fn msgpack_response_message(body: &[u8]) -> Result<Vec<u8>> {
+    let value: Value = rmp_serde::from_slice(body).context("Response body is not MessagePack")?;
+    Ok(canonical_message(&value))
+}
+
+fn canonical_message(value: &Value) -> Vec<u8> {
+    let mut message = RESPONSE_DOMAIN.to_vec();
+    write_canonical(value, &mut message);
+    message
+}
+
+fn write_canonical(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push(b'{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                out.extend_from_slice(Value::String(key.clone()).to_string().as_bytes());
+                out.push(b':');
+                write_canonical(&map[key], out);
+            }
+            out.push(b'}');
+        }
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(b']');
+        }
+        other => out.extend_from_slice(other.to_string().as_bytes()),
+    }
+}
+
+/// Check the `X-Facilitator-Signature` of a response body
+///
+/// `facilitator` is the key served by `GET /pubkey`, obtained once and
+/// pinned rather than fetched alongside the response.
+pub fn verify_response_signature(body: &[u8], signature: &str, facilitator: &Pubkey) -> Result<()> {
+    let signature = Signature::from_str(signature).context("Invalid response signature encoding")?;
+    if !signature.verify(facilitator.as_ref(), &response_message(body)?) {
+        bail!("Response signature does not match");
+    }
+    Ok(())
+}
+
+/// Middleware signing verify and settle responses (`SIGN_RESPONSES`)
+///
+/// Adds [`SIGNATURE_HEADER`]: the base58 ed25519 signature of
+/// [`response_message`] by the facilitator's fee payer key, so a resource
+/// server can show downstream systems that a verification or settlement
+/// came from the facilitator. MessagePack bodies are signed as the same
+/// canonical JSON. Responses go out unsigned if signing fails.
+pub async fn response_signature_middleware(State(config): State<Config>, req: Request, next: Next) -> Response {
+    let response = next.run(req).await;
+    if !config.sign_responses {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("⚠️  Failed to read response body for signing: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let msgpack = parts.headers.get(header::CONTENT_TYPE).is_some_and(|v| v == MSGPACK_CONTENT_TYPE);
+    let message = if msgpack {
+        msgpack_response_message(&bytes)
+    } else {
+        response_message(&bytes)
+    };
+    let signed = match message {
+        Ok(message) => config.fee_payer_signer.sign_message(&message).await,
+        Err(e) => Err(e),
+    };
+    match signed.map(|signature| HeaderValue::from_str(&signature.to_string())) {
+        Ok(Ok(value)) => {
+            parts.headers.insert(SIGNATURE_HEADER, value);
+        }
+        Ok(Err(e)) => tracing::warn!("⚠️  Failed to sign response: {}", e),
+        Err(e) => tracing::warn!("⚠️  Failed to sign response: {:#}", e),
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_message_is_canonical() {
+        let compact = response_message(br#"{"isValid":true,"payer":"abc","details":{"b":1,"a":[2,{"d":null,"c":"x"}]}}"#).unwrap();
+        let pretty = response_message(
+            br#"{
+                "payer": "abc",
+                "details": { "a": [2, { "c": "x", "d": null }], "b": 1 },
+                "isValid": true
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(compact, pretty);
+        assert_eq!(
+            &compact[RESPONSE_DOMAIN.len()..],
+            br#"{"details":{"a":[2,{"c":"x","d":null}],"b":1},"isValid":true,"payer":"abc"}"#
+        );
+        assert!(response_message(b"not json").is_err());
+
+        // MessagePack bodies sign the same message
+        let value: Value = serde_json::from_slice(br#"{"isValid":true,"payer":"abc","details":{"b":1,"a":[2,{"d":null,"c":"x"}]}}"#).unwrap();
+        let packed = rmp_serde::to_vec_named(&value).unwrap();
+        assert_eq!(msgpack_response_message(&packed).unwrap(), compact);
+    }
+
+    #[test]
+    fn test_verify_response_signature() {
+        let keypair = Keypair::new();
+        let body = br#"{"isValid":true,"payer":"abc"}"#;
+        let signature = keypair.sign_message(&response_message(body).unwrap()).to_string();
+
+        assert!(verify_response_signature(body, &signature, &keypair.pubkey()).is_ok());
+        assert!(verify_response_signature(br#"{"isValid":false,"payer":"abc"}"#, &signature, &keypair.pubkey()).is_err());
+        assert!(verify_response_signature(body, &signature, &Pubkey::new_unique()).is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+use crate::config::Config;
+
+/// Header carrying a tenant's API key
+pub const TENANT_KEY_HEADER: &str = "x-api-key";
+
+/// Middleware identifying the tenant of payment requests
+///
+/// A no-op without tenants. Otherwise requests need the `X-API-Key` of a
+/// tenant (401 without one), whose [`crate::tenants::Tenant`] is added to
+/// the request extensions for the rate limiter and handlers.
+pub async fn tenant_middleware(
+    State(config): State<Config>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    if config.tenants.is_empty() {
+        return next.run(req).await;
+    }
+
+    let tenant = req
+        .headers()
+        .get(TENANT_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|key| config.tenants.get(key.trim()))
+        .cloned();
+
+    let Some(tenant) = tenant else {
+        tracing::warn!("🏢 Rejected request without a tenant API key on {}", req.uri().path());
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "missing or unknown API key" })),
+        )
+            .into_response();
+    };
+
+    req.extensions_mut().insert(tenant);
+    next.run(req).await
+}
@@ -0,0 +1,150 @@
+//! Chains the facilitator settles on
+//!
+//! A [`NetworkAdapter`] carries a payment through the chain-specific steps
+//! of settlement: decoding the payload's transaction, signing it as fee
+//! payer, submitting it and looking up where it landed. Adapters are
+//! selected by the family of `payment_payload.network` and get the
+//! network's RPC client, signer and expiry through a [`NetworkContext`].
+//! Which checks a payment must pass is up to its scheme (see
+//! [`crate::schemes`]).
+
+use anyhow::Result;
+use async_trait::async_trait;
+use solana_sdk::transaction::Transaction;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::schemes::NetworkFamily;
+use crate::solana::{rpc::SolanaRpc, signer::FeePayerSigner, submitter::SubmissionAttempt};
+use crate::types::requests::PaymentPayload;
+
+pub mod svm;
+
+pub use svm::SvmAdapter;
+
+/// Settings of the network a payment is on
+///
+/// One Solana cluster is configured today, so every SVM network shares the
+/// facilitator's RPC client and signer; per-network overrides belong here.
+#[derive(Clone, Copy)]
+pub struct NetworkContext<'a> {
+    pub network: &'a str,
+    pub config: &'a Config,
+}
+
+impl<'a> NetworkContext<'a> {
+    pub fn new(config: &'a Config, network: &'a str) -> Self {
+        Self { network, config }
+    }
+
+    pub fn rpc(&self) -> &'a dyn SolanaRpc {
+        self.config.rpc_client.as_ref()
+    }
+
+    /// Fee payer signing settlements
+    pub fn signer(&self) -> &'a dyn FeePayerSigner {
+        self.config.fee_payer_signer.as_ref()
+    }
+
+    /// Oldest payment accepted, in seconds (from the reloadable settings)
+    pub fn payment_expiry_seconds(&self) -> u64 {
+        self.config.settings.load().payment_expiry_seconds
+    }
+}
+
+/// A decoded payment transaction of some chain
+#[derive(Debug, Clone)]
+pub enum ChainTransaction {
+    Svm(Transaction),
+}
+
+/// Where a confirmed transaction landed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Confirmation {
+    /// Slot (or block) it landed in
+    pub slot: u64,
+    /// Unix time of that block, if the node knows it
+    pub block_time: Option<i64>,
+    /// Commitment level reached (`processed`, `confirmed` or `finalized`)
+    pub commitment: Option<String>,
+}
+
+/// Settlement steps of one network family
+#[async_trait]
+pub trait NetworkAdapter: Send + Sync {
+    fn family(&self) -> NetworkFamily;
+
+    /// The payload's transaction
+    fn decode(&self, payload: &PaymentPayload) -> Result<ChainTransaction>;
+
+    /// Add the fee payer's signature
+    async fn sign(&self, ctx: &NetworkContext<'_>, transaction: &mut ChainTransaction) -> Result<()>;
+
+    /// Submit the signed transaction and wait until it's confirmed,
+    /// returning its signature
+    ///
+    /// `on_attempt` is called after every submission attempt (its lifetime
+    /// is spelled out, or `#[async_trait]` would pin it to the call's).
+    async fn submit(
+        &self,
+        ctx: &NetworkContext<'_>,
+        transaction: &ChainTransaction,
+        on_attempt: &mut (dyn for<'a> FnMut(&'a SubmissionAttempt) + Send),
+    ) -> Result<String>;
+
+    /// Where a confirmed transaction landed (`None` if unknown)
+    ///
+    /// Blocking (RPC calls).
+    fn confirm(&self, ctx: &NetworkContext<'_>, signature: &str) -> Option<Confirmation>;
+}
+
+/// Network adapters keyed by network family
+#[derive(Clone)]
+pub struct NetworkAdapters {
+    adapters: Arc<BTreeMap<NetworkFamily, Arc<dyn NetworkAdapter>>>,
+}
+
+impl std::fmt::Debug for NetworkAdapters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.adapters.keys()).finish()
+    }
+}
+
+impl Default for NetworkAdapters {
+    fn default() -> Self {
+        Self::empty().with(SvmAdapter)
+    }
+}
+
+impl NetworkAdapters {
+    pub fn empty() -> Self {
+        Self { adapters: Arc::new(BTreeMap::new()) }
+    }
+
+    /// Register `adapter`, replacing any for the same family
+    pub fn with(mut self, adapter: impl NetworkAdapter + 'static) -> Self {
+        Arc::make_mut(&mut self.adapters).insert(adapter.family(), Arc::new(adapter));
+        self
+    }
+
+    /// Adapter of `network` (`None` for unknown networks and families
+    /// without an adapter)
+    pub fn get(&self, network: &str) -> Option<&Arc<dyn NetworkAdapter>> {
+        self.adapters.get(&NetworkFamily::of(network)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adapter_selected_by_network_family() {
+        let adapters = NetworkAdapters::default();
+        assert_eq!(adapters.get("solana").unwrap().family(), NetworkFamily::Svm);
+        assert_eq!(adapters.get("solana-devnet").unwrap().family(), NetworkFamily::Svm);
+        assert!(adapters.get("base").is_none());
+        assert!(adapters.get("unknown").is_none());
+    }
+}
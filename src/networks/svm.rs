@@ -0,0 +1,124 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use solana_transaction_status::TransactionConfirmationStatus;
+
+use super::{ChainTransaction, Confirmation, NetworkAdapter, NetworkContext};
+use crate::events::LiveEventKind;
+use crate::schemes::NetworkFamily;
+use crate::solana::{
+    decoder::decode_transaction_from_base64,
+    signer::sign_transaction_as_fee_payer,
+    verifier::{sponsored_ata_rent, transaction_fee_lamports},
+    submitter::{
+        block_time, confirmed_status, signature_status, signature_to_string, string_to_signature,
+        submit_transaction_with_retries, SubmissionAttempt,
+    },
+};
+use crate::types::requests::PaymentPayload;
+
+/// Submission attempts per settlement
+const MAX_SUBMIT_ATTEMPTS: u32 = 3;
+
+/// Seconds to wait for each attempt to confirm
+const CONFIRM_TIMEOUT_SECONDS: u64 = 30;
+
+/// Solana clusters (`solana`, `solana-devnet`)
+pub struct SvmAdapter;
+
+#[async_trait]
+impl NetworkAdapter for SvmAdapter {
+    fn family(&self) -> NetworkFamily {
+        NetworkFamily::Svm
+    }
+
+    fn decode(&self, payload: &PaymentPayload) -> Result<ChainTransaction> {
+        Ok(ChainTransaction::Svm(decode_transaction_from_base64(&payload.payload.transaction)?))
+    }
+
+    async fn sign(&self, ctx: &NetworkContext<'_>, transaction: &mut ChainTransaction) -> Result<()> {
+        let ChainTransaction::Svm(transaction) = transaction;
+        let fee_payer = ctx.signer();
+        tracing::info!("Using {} fee payer signer: {}", fee_payer.backend(), fee_payer.pubkey());
+
+        sign_transaction_as_fee_payer(transaction, fee_payer).await
+    }
+
+    async fn submit(
+        &self,
+        ctx: &NetworkContext<'_>,
+        transaction: &ChainTransaction,
+        on_attempt: &mut (dyn for<'a> FnMut(&'a SubmissionAttempt) + Send),
+    ) -> Result<String> {
+        let ChainTransaction::Svm(transaction) = transaction;
+        let config = ctx.config;
+
+        // Reject transactions that already landed, e.g. settled by another
+        // facilitator instance (the payload hash dedup only sees this
+        // instance, or replicas sharing its Redis)
+        if let Some(signature) = transaction.signatures.first() {
+            match signature_status(ctx.rpc(), signature) {
+                Ok(Some(_)) => {
+                    tracing::warn!("🚫 Transaction {} already landed on chain", signature);
+                    config.metrics.record_dedup_hit();
+                    config.events.publish(
+                        LiveEventKind::Dedup,
+                        ctx.network,
+                        false,
+                        serde_json::json!({ "signature": signature.to_string() }),
+                    );
+                    anyhow::bail!("Transaction {} was already submitted", signature);
+                }
+                Ok(None) => {}
+                // The cluster rejects already-processed transactions on its own
+                Err(e) => tracing::warn!("Skipping on-chain duplicate check: {}", e),
+            }
+        }
+
+        // Charge token account rent the fee payer sponsors to today's budget
+        // (verification only checked that it fits)
+        let sponsored_rent = sponsored_ata_rent(transaction, &ctx.signer().pubkey());
+        if let Some(rent) = sponsored_rent {
+            let Some(remaining) = config.ata_rent_budget.try_spend(rent) else {
+                anyhow::bail!("Token account rent budget exhausted for today");
+            };
+            tracing::info!("🏦 Fee payer funds a token account: {} lamports ({} left today)", rent, remaining);
+            config.metrics.record_ata_rent_sponsored(rent, remaining);
+        }
+
+        // Submit with retries, waiting for each attempt to confirm
+        let signature = submit_transaction_with_retries(
+            ctx.rpc(),
+            transaction,
+            MAX_SUBMIT_ATTEMPTS,
+            CONFIRM_TIMEOUT_SECONDS,
+            on_attempt,
+        )
+        .await?;
+
+        // Account for what the settlement cost the fee payer
+        let fee = transaction_fee_lamports(transaction);
+        let rent = sponsored_rent.unwrap_or(0);
+        let today = config.fee_spend.record(fee, rent);
+        tracing::info!("💸 Fee payer spent {} lamports ({} today)", fee + rent, today.total_lamports());
+        config.metrics.record_fee_payer_spend(fee, rent, today.total_lamports());
+
+        Ok(signature_to_string(&signature))
+    }
+
+    fn confirm(&self, ctx: &NetworkContext<'_>, signature: &str) -> Option<Confirmation> {
+        let signature = string_to_signature(signature).ok()?;
+        let status = confirmed_status(ctx.rpc(), &signature)?;
+        Some(Confirmation {
+            slot: status.slot,
+            block_time: block_time(ctx.rpc(), status.slot),
+            commitment: status.confirmation_status.map(|commitment| {
+                match commitment {
+                    TransactionConfirmationStatus::Processed => "processed",
+                    TransactionConfirmationStatus::Confirmed => "confirmed",
+                    TransactionConfirmationStatus::Finalized => "finalized",
+                }
+                .to_string()
+            }),
+        })
+    }
+}
@@ -1,213 +1,351 @@
-// Parallel batch verification using Rayon
-// This module enables true multi-threaded verification across all CPU cores
+// Batch verification with bounded async concurrency
+// The accounts of a whole batch are fetched together up front, then the
+// payments are verified a bounded number at a time.
 
-use rayon::prelude::*;
+use futures::{stream, FutureExt, StreamExt};
 use crate::types::{requests::VerifyRequest, responses::VerifyResponse};
 use crate::config::Config;
+use crate::handlers::verify::verify_request;
 use crate::solana::decoder::decode_transaction_from_base64;
 use crate::solana::verifier::*;
-use crate::error::VerificationError;
-use solana_sdk::pubkey::Pubkey;
+use crate::solana::rpc::SolanaRpc;
+use solana_sdk::{pubkey::Pubkey, sanitize::Sanitize};
+use std::collections::HashSet;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 
-/// Verify multiple payments in parallel across all CPU cores
-/// 
-/// This uses Rayon's parallel iterators to distribute verification
-/// work across all available CPU cores, achieving true parallelism.
-/// 
-/// # Example Performance
-/// - Single-threaded: 1000 payments × 5ms = 5000ms
-/// - 8-core parallel: 125 payments/core × 5ms = 625ms (8x faster!)
-pub fn verify_batch_parallel(
+/// Payments verified at once by [`verify_batch_parallel`] in `/verify/batch`
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 16;
+
+/// Accounts per `getMultipleAccounts` call (the RPC limit)
+const MAX_ACCOUNTS_PER_CALL: usize = 100;
+
+/// Payments of batches and streams verified at once (`VERIFY_THREADS`)
+///
+/// Bounds how many payments are verified at once across all batch and
+/// stream requests, so heavy batch load can't crowd out the other
+/// endpoints.
+#[derive(Debug, Clone)]
+pub struct VerifyPool {
+    permits: Arc<Semaphore>,
+    limit: usize,
+}
+
+impl Default for VerifyPool {
+    fn default() -> Self {
+        Self::new(Self::default_limit())
+    }
+}
+
+impl VerifyPool {
+    pub fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            permits: Arc::new(Semaphore::new(limit)),
+            limit,
+        }
+    }
+
+    /// One payment per available core
+    pub fn default_limit() -> usize {
+        std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Payments being verified right now
+    pub fn busy(&self) -> usize {
+        self.limit - self.permits.available_permits()
+    }
+
+    /// Run `verification` once one of the slots is free
+    pub async fn run<F: Future>(&self, verification: F) -> F::Output {
+        // The semaphore is never closed
+        let _permit = self.permits.acquire().await.expect("verify pool closed");
+        verification.await
+    }
+}
+
+/// Verify one payment of a batch or stream with `/verify`'s code
+/// ([`verify_request`]), once the [`VerifyPool`] has a slot free
+///
+/// `Err` when its verification panicked, so one payment can't take the rest
+/// of the batch down with it.
+pub async fn verify_in_pool(
+    config: &Config,
+    request: &VerifyRequest,
+    prefetched: Option<&PrefetchedAccounts>,
+) -> Result<VerifyResponse, String> {
+    let verification = AssertUnwindSafe(verify_request(config, request, prefetched)).catch_unwind();
+    config.verify_pool.run(verification).await.map_err(|_| {
+        tracing::error!("Verification of {} payment panicked", request.payment_payload.network);
+        "verification panicked".to_string()
+    })
+}
+
+/// Verify multiple payments concurrently
+///
+/// Every account the batch needs that isn't cached is fetched first, 100 per
+/// `getMultipleAccounts` call, instead of one `getAccountInfo` per check.
+/// Payments are then verified against those accounts, `concurrency` at a
+/// time and within the [`VerifyPool`], so a large batch neither waits on RPC
+/// latency payment by payment nor crowds out other requests.
+///
+/// There's one result per request, in request order; `Err` when a payment
+/// couldn't be verified at all (its verification panicked).
+pub async fn verify_batch_parallel(
     config: &Config,
     requests: Vec<VerifyRequest>,
-) -> Vec<VerifyResponse> {
+    concurrency: usize,
+) -> Vec<Result<VerifyResponse, String>> {
+    let concurrency = concurrency.max(1);
+    let batch_size = requests.len();
     tracing::info!(
-        "🚀 Starting parallel batch verification for {} requests across {} cores",
-        requests.len(),
-        rayon::current_num_threads()
+        "🚀 Starting batch verification for {} requests ({} at a time)",
+        batch_size,
+        concurrency
     );
 
     let start = std::time::Instant::now();
+    let prefetched = prefetch_accounts(config, &requests, concurrency).await;
 
-    let results: Vec<VerifyResponse> = requests
-        .par_iter()  // Parallel iterator - THIS is the magic!
-        .map(|request| {
-            verify_single_sync(config, request)
-        })
+    // Collected first: a stream over the borrowing closure isn't Send
+    let verifications: Vec<_> = requests
+        .iter()
+        .enumerate()
+        .map(|(index, request)| verify_in_pool(config, request, Some(&prefetched)).map(move |response| (index, response)))
         .collect();
+    let mut results: Vec<(usize, Result<VerifyResponse, String>)> =
+        stream::iter(verifications).buffer_unordered(concurrency).collect().await;
+    results.sort_unstable_by_key(|(index, _)| *index);
 
     let duration = start.elapsed();
-    let per_request = duration.as_micros() as f64 / requests.len() as f64;
+    let per_request = duration.as_micros() as f64 / batch_size.max(1) as f64;
 
     tracing::info!(
         "✅ Batch verification complete: {} requests in {:?} ({:.2}μs per request)",
-        requests.len(),
+        batch_size,
         duration,
         per_request
     );
 
-    results
+    results.into_iter().map(|(_, response)| response).collect()
 }
 
-/// Synchronous version of payment verification
-/// 
-/// This is designed to work with Rayon's thread pool.
-/// It performs all the same checks as the async version,
-/// but in a blocking/synchronous manner.
-fn verify_single_sync(
-    config: &Config,
-    request: &VerifyRequest,
-) -> VerifyResponse {
-    // Record metrics
-    let network = &request.payment_payload.network;
-    config.metrics.verify_requests.with_label_values(&[network]).inc();
-    
-    // Perform verification
-    match verify_payment_sync(config, request) {
-        Ok(payer) => {
-            config.metrics.record_verification_success(network);
-            
-            // Audit log
-            config.audit_logger.log_verification_success(network, &payer, None);
-            
-            VerifyResponse {
-                is_valid: true,
-                invalid_reason: None,
-                payer: Some(payer),
-            }
-        }
-        Err(e) => {
-            tracing::debug!("Verification failed: {}", e);
-            config.metrics.record_verification_failure(network, e.as_str());
-            
-            // Audit log
-            config.audit_logger.log_verification_failure(network, e.as_str(), None);
-            
-            VerifyResponse {
-                is_valid: false,
-                invalid_reason: Some(e.as_str().to_string()),
-                payer: None,
-            }
-        }
+/// Fetch the uncached accounts the requests' transfers will be checked against
+///
+/// Requests that don't parse are skipped (they fail before any lookup), as
+/// is everything when verifying offline.
+async fn prefetch_accounts(config: &Config, requests: &[VerifyRequest], concurrency: usize) -> PrefetchedAccounts {
+    if config.offline_verify || requests.is_empty() {
+        return PrefetchedAccounts::new();
     }
+
+    let plan = LookupPlan::new(requests, |kind, pubkey| match kind {
+        AccountKind::Mint => config.mint_cache.contains(pubkey),
+        _ => config.account_cache.contains(pubkey),
+    });
+
+    tracing::debug!(
+        "📥 Prefetching {} accounts for {} lookups ({} cached)",
+        plan.pubkeys.len(),
+        plan.lookups,
+        plan.cached
+    );
+    plan.fetch(&config.rpc_client, concurrency).await
 }
 
-/// Synchronous verification logic (blocking version)
-fn verify_payment_sync(
-    config: &Config,
-    request: &VerifyRequest,
-) -> Result<String, VerificationError> {
-    let payload = &request.payment_payload;
-    let requirements = &request.payment_requirements;
+/// The account lookups of a batch, planned so each account is fetched once
+///
+/// Payments of a batch mostly share their mint and merchant ATA; the plan
+/// lists every account the batch's transfers need once, without those
+/// already cached, to be fetched together by [`LookupPlan::fetch`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LookupPlan {
+    /// Accounts to fetch, each once, in the order they're first needed
+    pub pubkeys: Vec<Pubkey>,
+    /// Lookups the payments would make one by one
+    pub lookups: usize,
+    /// Of those, lookups served from the caches
+    pub cached: usize,
+}
 
-    // 0. Check for duplicate transaction (replay attack prevention)
-    let transaction_data = &payload.payload.transaction;
-    if config.transaction_dedup.check_and_mark(transaction_data) {
-        return Err(VerificationError::UnexpectedError(
-            anyhow::anyhow!("Transaction has already been processed (replay attack prevented)")
-        ));
-    }
+impl LookupPlan {
+    /// Plan the lookups of `requests`, skipping accounts `is_cached` says
+    /// the caches hold
+    pub fn new<'a>(
+        requests: impl IntoIterator<Item = &'a VerifyRequest>,
+        is_cached: impl Fn(AccountKind, &Pubkey) -> bool,
+    ) -> Self {
+        let mut plan = Self::default();
+        let mut seen = HashSet::new();
 
-    // 0.5. Validate payment expiry (if timestamp is provided)
-    if let Some(timestamp) = payload.timestamp {
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| VerificationError::UnexpectedError(anyhow::anyhow!("System time error: {}", e)))?
-            .as_secs();
-        
-        let age_seconds = current_time.saturating_sub(timestamp);
-        
-        if age_seconds > config.payment_expiry_seconds {
-            return Err(VerificationError::UnexpectedError(
-                anyhow::anyhow!(
-                    "Payment has expired (age: {} seconds, max: {} seconds)",
-                    age_seconds,
-                    config.payment_expiry_seconds
-                )
-            ));
-        }
-    }
+        for request in requests {
+            let Some((transfer, has_create_ata)) = transfer_accounts(request) else {
+                continue;
+            };
+            let mut accounts = vec![(AccountKind::Mint, transfer.mint), (AccountKind::SourceAta, transfer.source)];
+            if !has_create_ata {
+                accounts.push((AccountKind::DestinationAta, transfer.destination));
+            }
 
-    // 1. Verify scheme and network match
-    if payload.scheme != requirements.scheme || payload.scheme != "exact" {
-        return Err(VerificationError::UnsupportedScheme);
-    }
+            for (kind, pubkey) in accounts {
+                plan.lookups += 1;
+                if is_cached(kind, &pubkey) {
+                    plan.cached += 1;
+                } else if seen.insert(pubkey) {
+                    plan.pubkeys.push(pubkey);
+                }
+            }
+        }
 
-    if payload.network != requirements.network {
-        return Err(VerificationError::InvalidNetwork);
+        plan
     }
 
-    // Verify network is supported
-    if requirements.network != "solana" && requirements.network != "solana-devnet" {
-        return Err(VerificationError::InvalidNetwork);
+    /// Fetch the planned accounts, 100 per `getMultipleAccounts` call with
+    /// up to `concurrency` calls in flight
+    ///
+    /// A failed call leaves its accounts out, to be looked up one by one.
+    pub async fn fetch(self, rpc_client: &Arc<dyn SolanaRpc>, concurrency: usize) -> PrefetchedAccounts {
+        // Owned chunks: a closure over borrowed slices makes the future !Send
+        let chunks: Vec<Vec<Pubkey>> = self.pubkeys.chunks(MAX_ACCOUNTS_PER_CALL).map(<[Pubkey]>::to_vec).collect();
+        let calls = chunks.into_iter().map(|chunk| {
+            let rpc_client = rpc_client.clone();
+            async move {
+                let keys = chunk.clone();
+                match tokio::task::spawn_blocking(move || rpc_client.get_multiple_accounts(&keys)).await {
+                    Ok(Ok(accounts)) => chunk.into_iter().zip(accounts).collect(),
+                    Ok(Err(e)) => {
+                        tracing::warn!("⚠️  Failed to prefetch {} accounts: {}", chunk.len(), e);
+                        Vec::new()
+                    }
+                    Err(e) => {
+                        tracing::error!("Account prefetch task panicked: {}", e);
+                        Vec::new()
+                    }
+                }
+            }
+        });
+        let fetched: Vec<Vec<_>> = stream::iter(calls).buffer_unordered(concurrency.max(1)).collect().await;
+        fetched.into_iter().flatten().collect()
     }
+}
 
-    // 2. Decode transaction
-    let transaction = decode_transaction_from_base64(&payload.payload.transaction)
-        .map_err(|_| VerificationError::UnexpectedError(
-            anyhow::anyhow!("Failed to decode transaction")
-        ))?;
-
-    // Get fee payer from requirements
-    let fee_payer = Pubkey::from_str(&requirements.extra.fee_payer)
-        .map_err(|_| VerificationError::UnexpectedError(
-            anyhow::anyhow!("Invalid fee payer pubkey")
-        ))?;
-
-    // Get payer (client) for response
-    let payer = if let Some(first_key) = transaction.message.account_keys.get(1) {
-        first_key.to_string()
-    } else {
-        "unknown".to_string()
-    };
-
-    // 3. Verify instruction count (3 or 4)
-    let has_create_ata = verify_instruction_count(&transaction)?;
-
-    // 4. Verify compute budget instructions
-    verify_compute_limit_instruction(
-        &transaction.message.instructions[0],
-        &transaction.message,
-    )?;
-
-    verify_compute_price_instruction(
-        &transaction.message.instructions[1],
-        &transaction.message,
-    )?;
-
-    // 5. Verify fee payer safety (not in any instruction accounts)
-    verify_fee_payer_safety(&transaction, &fee_payer)?;
-
-    // 6. Use shared RPC client (connection pooling)
-    let rpc_client = &config.rpc_client;
-
-    // 7. Verify CreateATA instruction (if present)
-    if has_create_ata {
-        verify_create_ata_instruction(
-            &transaction.message.instructions[2],
-            &transaction.message,
-            requirements,
-        )?;
-    }
+/// Transfer of a request's transaction, if it's well-formed
+fn transfer_accounts(request: &VerifyRequest) -> Option<(TransferAccounts, bool)> {
+    let requirements = &request.payment_requirements;
+    let transaction = decode_transaction_from_base64(&request.payment_payload.payload.transaction).ok()?;
+    transaction.sanitize().ok()?;
+    let fee_payer = Pubkey::from_str(&requirements.extra.fee_payer).ok()?;
 
-    // 8. Verify transfer instruction (last instruction)
-    let transfer_idx = if has_create_ata { 3 } else { 2 };
-    verify_transfer_instruction(
-        &transaction.message.instructions[transfer_idx],
-        &transaction.message,
-        requirements,
-        &fee_payer,
-        has_create_ata,
-        rpc_client.as_ref(),
-    )?;
-
-    Ok(payer)
+    let has_create_ata = verify_instruction_count(&transaction).ok()?;
+    let transfer_ix = &transaction.message.instructions[if has_create_ata { 3 } else { 2 }];
+    // Plain Transfers too: verification decides whether they're accepted
+    let transfer = parse_transfer_instruction(transfer_ix, &transaction.message, requirements, &fee_payer, true).ok()?;
+    Some((transfer, has_create_ata))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::requirements::RequirementsBuilder;
+    use crate::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use crate::solana::decoder::encode_transaction_to_base64;
+    use crate::solana::rpc::MockRpc;
+    use crate::types::requests::{PaymentPayload, SvmPayload};
+    use solana_sdk::{account::Account, hash::Hash};
+    use spl_associated_token_account::get_associated_token_address;
+
+    /// Payments of `payers` to the same merchant and mint
+    fn payments(payers: &[Pubkey], mint: Pubkey, merchant: Pubkey) -> Vec<VerifyRequest> {
+        let requirements = RequirementsBuilder::new("solana-devnet", Pubkey::new_unique())
+            .asset(mint)
+            .pay_to(merchant)
+            .amount(1_000)
+            .resource("/premium")
+            .build()
+            .unwrap();
+        payers
+            .iter()
+            .map(|payer| {
+                let transaction = build_payment_transaction(&PaymentTransaction {
+                    requirements: &requirements,
+                    payer: *payer,
+                    decimals: 6,
+                    token_program: spl_token::ID,
+                    create_destination_ata: false,
+                    recent_blockhash: Hash::new_unique(),
+                })
+                .unwrap();
+                VerifyRequest {
+                    payment_payload: PaymentPayload {
+                        x402_version: 1,
+                        scheme: "exact".to_string(),
+                        network: "solana-devnet".to_string(),
+                        payload: SvmPayload { transaction: encode_transaction_to_base64(&transaction).unwrap() },
+                        timestamp: None,
+                    },
+                    payment_requirements: requirements.clone(),
+                    dry_run: false,
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_verify_pool_bounds_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let pool = VerifyPool::new(2);
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let tasks = (0..6).map(|_| {
+            pool.run(async {
+                peak.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+            })
+        });
+        futures::future::join_all(tasks).await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(pool.busy(), 0);
+        assert_eq!(VerifyPool::new(0).limit(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lookup_plan_fetches_shared_accounts_once() {
+        let payers: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let (mint, merchant) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let requests = payments(&payers, mint, merchant);
+        let destination = get_associated_token_address(&merchant, &mint);
+
+        // Mint, source and destination per payment; the shared ones once
+        let plan = LookupPlan::new(&requests, |_, _| false);
+        assert_eq!(plan.lookups, 9);
+        assert_eq!(plan.cached, 0);
+        assert_eq!(plan.pubkeys.len(), 5);
+        assert_eq!(plan.pubkeys[..2], [mint, get_associated_token_address(&payers[0], &mint)]);
+        assert_eq!(plan.pubkeys[2], destination);
+
+        // Cached accounts aren't fetched
+        let plan = LookupPlan::new(&requests, |kind, _| kind == AccountKind::Mint);
+        assert_eq!(plan.cached, 3);
+        assert!(!plan.pubkeys.contains(&mint));
+
+        let rpc = MockRpc::new();
+        rpc.set_account(destination, Account { lamports: 1, ..Account::default() });
+        let rpc: Arc<dyn SolanaRpc> = Arc::new(rpc);
+        let fetched = plan.fetch(&rpc, 4).await;
+        assert_eq!(fetched.len(), 4);
+        assert!(fetched[&destination].is_some());
+        assert!(fetched[&get_associated_token_address(&payers[1], &mint)].is_none());
+    }
 
     #[test]
     fn test_parallel_verification_empty() {
@@ -218,11 +356,32 @@ mod tests {
     }
 
     #[test]
-    fn test_rayon_thread_count() {
-        // Verify Rayon is using multiple threads
-        let thread_count = rayon::current_num_threads();
-        println!("Rayon thread pool size: {}", thread_count);
-        assert!(thread_count > 0);
+    fn test_transfer_accounts_skips_malformed() {
+        let mut request: VerifyRequest = serde_json::from_value(serde_json::json!({
+            "payment_payload": {
+                "x402Version": 1,
+                "scheme": "exact",
+                "network": "solana-devnet",
+                "payload": { "transaction": "AQ==" }
+            },
+            "payment_requirements": {
+                "scheme": "exact",
+                "network": "solana-devnet",
+                "maxAmountRequired": "1000000",
+                "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                "payTo": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS",
+                "resource": "/premium",
+                "description": "",
+                "mimeType": "application/json",
+                "maxTimeoutSeconds": 60,
+                "extra": { "feePayer": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS" }
+            }
+        }))
+        .unwrap();
+        assert!(transfer_accounts(&request).is_none());
+
+        request.payment_payload.payload.transaction = "not base64!".to_string();
+        assert!(transfer_accounts(&request).is_none());
     }
 }
 
@@ -0,0 +1,319 @@
+//! USD prices of payment assets
+//!
+//! Requirements may price a payment in USD: with `extra.usdAmount` set,
+//! `maxAmountRequired` must be worth that many dollars at the asset's
+//! current price, within `extra.usdToleranceBps` (default 1%). Resource
+//! servers convert when they quote; the facilitator checks the conversion
+//! wasn't made at a stale or favourable price, which keeps prices stable
+//! for volatile assets.
+//!
+//! Prices of the mints in `PRICE_FEEDS` are fetched in the background from
+//! Pyth or a REST oracle; verification only reads the cache, and a price
+//! older than `PRICE_MAX_AGE_SECONDS` counts as unavailable.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::error::VerificationError;
+use crate::types::requests::ExtraFields;
+
+/// Tolerance of USD amounts without `extra.usdToleranceBps`
+pub const DEFAULT_USD_TOLERANCE_BPS: u64 = 100;
+
+const DEFAULT_PYTH_URL: &str = "https://hermes.pyth.network";
+
+/// Where prices come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriceSource {
+    /// Pyth's Hermes API at `url`; feeds are Pyth price feed ids
+    Pyth { url: String },
+    /// GET of `url` with `{feed}` replaced by the feed id, the price being
+    /// the number (or numeric string) at the JSON `pointer` of the response
+    Rest { url: String, pointer: String },
+}
+
+/// Cached USD prices of the configured mints
+///
+/// Configuration:
+/// * `PRICE_ORACLE` - `pyth` or `rest` (default: none, USD pricing is refused)
+/// * `PRICE_ORACLE_URL` - Hermes endpoint (default: https://hermes.pyth.network),
+///   or the REST URL, with `{feed}` where the feed id goes
+/// * `PRICE_ORACLE_JSON_POINTER` - Price in the REST response (default: `/price`)
+/// * `PRICE_FEEDS` - Comma-separated `mint=feed_id` pairs
+/// * `PRICE_REFRESH_SECONDS` - Seconds between fetches (default: 10)
+/// * `PRICE_MAX_AGE_SECONDS` - Age past which a price is unusable (default: 60)
+#[derive(Debug, Clone)]
+pub struct PriceOracle {
+    source: Option<PriceSource>,
+    /// Feed id of each mint
+    feeds: Arc<HashMap<String, String>>,
+    refresh_interval: Duration,
+    max_age: Duration,
+    prices: Arc<RwLock<HashMap<String, (f64, Instant)>>>,
+}
+
+impl Default for PriceOracle {
+    fn default() -> Self {
+        Self::new(None, HashMap::new(), Duration::from_secs(10), Duration::from_secs(60))
+    }
+}
+
+impl PriceOracle {
+    pub fn new(
+        source: Option<PriceSource>,
+        feeds: HashMap<String, String>,
+        refresh_interval: Duration,
+        max_age: Duration,
+    ) -> Self {
+        Self {
+            source,
+            feeds: Arc::new(feeds),
+            refresh_interval,
+            max_age,
+            prices: Arc::default(),
+        }
+    }
+
+    pub fn from_env() -> Result<Self> {
+        let url = std::env::var("PRICE_ORACLE_URL").ok().filter(|url| !url.is_empty());
+        let source = match std::env::var("PRICE_ORACLE").unwrap_or_default().to_lowercase().as_str() {
+            "" => None,
+            "pyth" => Some(PriceSource::Pyth {
+                url: url.unwrap_or_else(|| DEFAULT_PYTH_URL.to_string()),
+            }),
+            "rest" => Some(PriceSource::Rest {
+                url: url.context("PRICE_ORACLE=rest requires PRICE_ORACLE_URL")?,
+                pointer: std::env::var("PRICE_ORACLE_JSON_POINTER").unwrap_or_else(|_| "/price".to_string()),
+            }),
+            other => bail!("PRICE_ORACLE must be pyth or rest, got {:?}", other),
+        };
+        let feeds = parse_feeds(&std::env::var("PRICE_FEEDS").unwrap_or_default())?;
+        let seconds = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        };
+
+        if source.is_some() {
+            tracing::info!("💱 USD pricing enabled for {} mint(s)", feeds.len());
+        }
+        Ok(Self::new(
+            source,
+            feeds,
+            Duration::from_secs(seconds("PRICE_REFRESH_SECONDS", 10).max(1)),
+            Duration::from_secs(seconds("PRICE_MAX_AGE_SECONDS", 60)),
+        ))
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.source.is_some()
+    }
+
+    /// USD price of one whole token of `mint`, if fetched recently enough
+    pub fn usd_price(&self, mint: &str) -> Option<f64> {
+        let prices = self.prices.read().unwrap_or_else(|e| e.into_inner());
+        prices
+            .get(mint)
+            .filter(|(_, fetched)| fetched.elapsed() <= self.max_age)
+            .map(|(price, _)| *price)
+    }
+
+    /// Record the current price of `mint`
+    pub fn set_price(&self, mint: &str, usd: f64) {
+        let mut prices = self.prices.write().unwrap_or_else(|e| e.into_inner());
+        prices.insert(mint.to_string(), (usd, Instant::now()));
+    }
+
+    /// Fetch the price of every configured mint
+    pub async fn refresh(&self, http: &reqwest::Client) {
+        let Some(source) = &self.source else { return };
+        for (mint, feed) in self.feeds.iter() {
+            match fetch_price(http, source, feed).await {
+                Ok(price) => self.set_price(mint, price),
+                Err(e) => tracing::warn!("⚠️  Failed to fetch the USD price of {}: {:#}", mint, e),
+            }
+        }
+    }
+
+    /// Refresh the prices every interval in the background (no-op without a
+    /// source)
+    pub fn spawn_refresher(&self) {
+        if !self.enabled() {
+            return;
+        }
+        let oracle = self.clone();
+        tokio::spawn(async move {
+            let http = reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default();
+            let mut ticker = tokio::time::interval(oracle.refresh_interval);
+            loop {
+                ticker.tick().await;
+                oracle.refresh(&http).await;
+            }
+        });
+    }
+}
+
+/// `mint=feed_id` pairs, comma-separated
+fn parse_feeds(value: &str) -> Result<HashMap<String, String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((mint, feed)) if !mint.trim().is_empty() && !feed.trim().is_empty() => {
+                Ok((mint.trim().to_string(), feed.trim().to_string()))
+            }
+            _ => bail!("PRICE_FEEDS entries must be mint=feed_id, got {:?}", pair),
+        })
+        .collect()
+}
+
+async fn fetch_price(http: &reqwest::Client, source: &PriceSource, feed: &str) -> Result<f64> {
+    let (url, pointer) = match source {
+        PriceSource::Pyth { url } => (
+            format!("{}/v2/updates/price/latest?ids[]={}&parsed=true", url.trim_end_matches('/'), feed),
+            None,
+        ),
+        PriceSource::Rest { url, pointer } => (url.replace("{feed}", feed), Some(pointer.as_str())),
+    };
+    let body: serde_json::Value = http.get(&url).send().await?.error_for_status()?.json().await?;
+    let price = match pointer {
+        None => pyth_price(&body),
+        Some(pointer) => body.pointer(pointer).and_then(json_number),
+    };
+    price
+        .filter(|price| price.is_finite() && *price > 0.0)
+        .with_context(|| format!("no price in the response of {}", url))
+}
+
+/// Price of the first parsed update of a Hermes response (`price` scaled by
+/// 10^`expo`)
+fn pyth_price(body: &serde_json::Value) -> Option<f64> {
+    let price = body.pointer("/parsed/0/price")?;
+    let mantissa = json_number(price.get("price")?)?;
+    let expo = price.get("expo")?.as_i64()?;
+    Some(mantissa * 10f64.powi(expo as i32))
+}
+
+fn json_number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(number) => number.as_f64(),
+        serde_json::Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+/// A payment priced in USD (`extra.usdAmount`, `extra.usdToleranceBps`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UsdAmount {
+    pub usd: f64,
+    pub tolerance_bps: u64,
+}
+
+impl UsdAmount {
+    /// USD pricing of requirements, if they have any
+    pub fn from_extra(extra: &ExtraFields) -> Result<Option<Self>, VerificationError> {
+        let Some(usd) = extra.get("usdAmount") else {
+            return Ok(None);
+        };
+        let usd = json_number(usd)
+            .filter(|usd| usd.is_finite() && *usd > 0.0)
+            .ok_or_else(|| VerificationError::InvalidExtra("Set extra.usdAmount to a positive decimal, e.g. \"1.50\"".to_string()))?;
+        let tolerance_bps = match extra.get("usdToleranceBps") {
+            None => DEFAULT_USD_TOLERANCE_BPS,
+            Some(bps) => bps.as_u64().filter(|bps| *bps <= 10_000).ok_or_else(|| {
+                VerificationError::InvalidExtra("Set extra.usdToleranceBps to basis points from 0 to 10000".to_string())
+            })?,
+        };
+        Ok(Some(Self { usd, tolerance_bps }))
+    }
+
+    /// Whether `amount` base units (of a mint with `decimals`) are worth
+    /// the USD amount at `usd_price` per token
+    pub fn check(&self, amount: u64, decimals: u8, usd_price: f64) -> Result<(), VerificationError> {
+        let value = amount as f64 / 10f64.powi(decimals as i32) * usd_price;
+        let tolerance = self.usd * self.tolerance_bps as f64 / 10_000.0;
+        // A hair of slack, so amounts exactly on a bound survive float rounding
+        if (value - self.usd).abs() > tolerance + 1e-9 {
+            return Err(VerificationError::UsdAmountOutOfBounds);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn extra(other: serde_json::Value) -> ExtraFields {
+        let mut extra = ExtraFields::new("FeePayer");
+        extra.other = serde_json::from_value(other).unwrap();
+        extra
+    }
+
+    #[test]
+    fn test_usd_amount_from_extra() {
+        assert_eq!(UsdAmount::from_extra(&extra(json!({}))).unwrap(), None);
+        assert_eq!(
+            UsdAmount::from_extra(&extra(json!({ "usdAmount": "1.50" }))).unwrap(),
+            Some(UsdAmount { usd: 1.5, tolerance_bps: DEFAULT_USD_TOLERANCE_BPS })
+        );
+        assert_eq!(
+            UsdAmount::from_extra(&extra(json!({ "usdAmount": 2, "usdToleranceBps": 50 }))).unwrap(),
+            Some(UsdAmount { usd: 2.0, tolerance_bps: 50 })
+        );
+        for invalid in [json!({ "usdAmount": "-1" }), json!({ "usdAmount": "1", "usdToleranceBps": 20_000 })] {
+            assert!(matches!(
+                UsdAmount::from_extra(&extra(invalid)),
+                Err(VerificationError::InvalidExtra(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_usd_amount_bounds() {
+        // $2.00 with 1%: a token at $2 (6 decimals) may be 0.99 to 1.01 tokens
+        let usd = UsdAmount { usd: 2.0, tolerance_bps: 100 };
+        assert!(usd.check(1_000_000, 6, 2.0).is_ok());
+        assert!(usd.check(990_000, 6, 2.0).is_ok());
+        assert!(usd.check(1_010_000, 6, 2.0).is_ok());
+        assert!(matches!(usd.check(980_000, 6, 2.0), Err(VerificationError::UsdAmountOutOfBounds)));
+        assert!(matches!(usd.check(1_000_000, 6, 2.5), Err(VerificationError::UsdAmountOutOfBounds)));
+    }
+
+    #[test]
+    fn test_stale_prices_unavailable() {
+        let oracle = PriceOracle::default();
+        assert_eq!(oracle.usd_price("mint"), None);
+        oracle.set_price("mint", 1.25);
+        assert_eq!(oracle.usd_price("mint"), Some(1.25));
+
+        let oracle = PriceOracle::new(None, HashMap::new(), Duration::from_secs(10), Duration::ZERO);
+        oracle.set_price("mint", 1.25);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(oracle.usd_price("mint"), None);
+    }
+
+    #[test]
+    fn test_parse_feeds() {
+        let feeds = parse_feeds("MintA=feed-a, MintB = feed-b,").unwrap();
+        assert_eq!(feeds.get("MintA").map(String::as_str), Some("feed-a"));
+        assert_eq!(feeds.get("MintB").map(String::as_str), Some("feed-b"));
+        assert!(parse_feeds("MintA").is_err());
+    }
+
+    #[test]
+    fn test_pyth_price() {
+        let body = json!({ "parsed": [{ "id": "ef0d", "price": { "price": "6140993501", "conf": "1", "expo": -8 } }] });
+        let price = pyth_price(&body).unwrap();
+        assert!((price - 61.40993501).abs() < 1e-9);
+        assert_eq!(pyth_price(&json!({ "parsed": [] })), None);
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+use crate::settlements::{SettlementRecord, SettlementStatus};
+use crate::solana::signer::FeePayerSigner;
+
+/// Prefix of every signed receipt message
+///
+/// The leading `0xff` byte can't start a Solana transaction message, so a
+/// receipt signature can never double as a transaction signature.
+pub const RECEIPT_DOMAIN: &[u8] = b"\xffx402 receipt v1\n";
+
+/// Proof that the facilitator settled a payment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentReceipt {
+    /// Transaction signature
+    pub transaction: String,
+    #[schema(example = "solana-devnet")]
+    pub network: String,
+    /// Amount in the asset's base units
+    #[schema(example = "1000000")]
+    pub amount: String,
+    /// Asset (token mint) address
+    pub asset: String,
+    pub payer: String,
+    /// Recipient (`payTo`) address
+    pub payee: String,
+    /// Slot the transaction landed in, when known
+    pub slot: Option<u64>,
+    /// Unix timestamp (seconds) of the settlement
+    pub timestamp: i64,
+    /// Public key that signed the receipt (the facilitator's fee payer)
+    pub facilitator: String,
+}
+
+impl PaymentReceipt {
+    /// Build the receipt of a successful settlement
+    pub fn from_settlement(record: &SettlementRecord, facilitator: &Pubkey) -> Result<Self> {
+        if record.status != SettlementStatus::Success {
+            bail!("Settlement {} did not succeed", record.id);
+        }
+
+        Ok(Self {
+            transaction: record.signature.clone().context("Settlement has no transaction signature")?,
+            network: record.network.clone(),
+            amount: record.amount.clone(),
+            asset: record.mint.clone(),
+            payer: record.payer.clone().unwrap_or_default(),
+            payee: record.recipient.clone(),
+            slot: record.slot,
+            timestamp: record.created_at.timestamp(),
+            facilitator: facilitator.to_string(),
+        })
+    }
+
+    /// Bytes covered by the receipt signature
+    ///
+    /// [`RECEIPT_DOMAIN`] followed by the fields in declaration order, each
+    /// terminated by `\n` (`slot` is empty when unknown), so receipts can be
+    /// checked without a JSON canonicalization step.
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let slot = self.slot.map(|slot| slot.to_string()).unwrap_or_default();
+        let timestamp = self.timestamp.to_string();
+        let fields = [
+            &self.transaction,
+            &self.network,
+            &self.amount,
+            &self.asset,
+            &self.payer,
+            &self.payee,
+            &slot,
+            &timestamp,
+            &self.facilitator,
+        ];
+
+        let mut message = RECEIPT_DOMAIN.to_vec();
+        for field in fields {
+            if field.contains('\n') {
+                bail!("Receipt fields must not contain newlines");
+            }
+            message.extend_from_slice(field.as_bytes());
+            message.push(b'\n');
+        }
+        Ok(message)
+    }
+
+    /// Sign the receipt with the facilitator's key
+    pub async fn sign(self, signer: &dyn FeePayerSigner) -> Result<SignedReceipt> {
+        let signature = signer.sign_message(&self.message()?).await?;
+        Ok(SignedReceipt {
+            receipt: self,
+            signature: signature.to_string(),
+        })
+    }
+}
+
+/// A receipt with the facilitator's detached ed25519 signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SignedReceipt {
+    pub receipt: PaymentReceipt,
+    /// Base58 ed25519 signature over [`PaymentReceipt::message`]
+    pub signature: String,
+}
+
+impl SignedReceipt {
+    /// Check the signature offline
+    ///
+    /// `facilitator` is the key the receipt must be signed by; pass the
+    /// facilitator's published fee payer address rather than trusting the
+    /// key named in the receipt.
+    pub fn verify(&self, facilitator: &Pubkey) -> Result<()> {
+        if self.receipt.facilitator != facilitator.to_string() {
+            bail!("Receipt was issued by {}, not {}", self.receipt.facilitator, facilitator);
+        }
+
+        let signature = Signature::from_str(&self.signature).context("Invalid receipt signature encoding")?;
+        if !signature.verify(facilitator.as_ref(), &self.receipt.message()?) {
+            bail!("Receipt signature does not match");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solana::signer::LocalKeypairSigner;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn receipt(facilitator: &Pubkey) -> PaymentReceipt {
+        PaymentReceipt {
+            transaction: "5j7s6NiJS3JAkvgkoc18WVAsiSaci2pxB2A6ueCJP4tprA2TFg9wSyTLeYouxPBJEMzJinENTkpA52YStRW5Dia7".to_string(),
+            network: "solana-devnet".to_string(),
+            amount: "1000000".to_string(),
+            asset: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            payer: Pubkey::new_unique().to_string(),
+            payee: Pubkey::new_unique().to_string(),
+            slot: Some(123),
+            timestamp: 1_700_000_000,
+            facilitator: facilitator.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_receipt_sign_and_verify() {
+        let keypair = Keypair::new();
+        let facilitator = keypair.pubkey();
+        let signer = LocalKeypairSigner::new(keypair);
+
+        let signed = receipt(&facilitator).sign(&signer).await.unwrap();
+        assert!(signed.verify(&facilitator).is_ok());
+
+        // Signing is deterministic, so re-fetched receipts are identical
+        assert_eq!(signed.receipt.clone().sign(&signer).await.unwrap(), signed);
+
+        // Tampered amount
+        let mut tampered = signed.clone();
+        tampered.receipt.amount = "2000000".to_string();
+        assert!(tampered.verify(&facilitator).is_err());
+
+        // Another facilitator
+        assert!(signed.verify(&Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_receipt_message_rejects_newlines() {
+        let mut receipt = receipt(&Pubkey::new_unique());
+        receipt.network = "solana\ndevnet".to_string();
+        assert!(receipt.message().is_err());
+    }
+}
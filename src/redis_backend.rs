@@ -0,0 +1,301 @@
+use anyhow::{Context, Result};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::middleware::rate_limit::{RateLimitQuota, RateLimitRoute};
+
+/// Timeout for connecting to and talking with Redis
+///
+/// Kept short: every check falls back to in-memory state on failure, so a
+/// slow Redis should never stall request handling for long.
+const REDIS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// GCRA rate limit check, evaluated atomically in Redis
+///
+/// Uses the server clock so replicas with skewed clocks agree. Returns
+/// `{1, 0}` when allowed, `{0, wait_micros}` when over quota.
+const GCRA_SCRIPT: &str = r#"
+local t = redis.call('TIME')
+local now = tonumber(t[1]) * 1000000 + tonumber(t[2])
+local emission = tonumber(ARGV[1])
+local burst = tonumber(ARGV[2])
+local tat = tonumber(redis.call('GET', KEYS[1]) or now)
+if tat < now then tat = now end
+local new_tat = tat + emission
+local allow_at = new_tat - emission * burst
+if allow_at > now then
+  return {0, allow_at - now}
+end
+redis.call('SET', KEYS[1], string.format('%d', new_tat), 'PX', math.ceil((new_tat - now) / 1000))
+return {1, 0}
+"#;
+
+/// Shared Redis connection used by the distributed rate limiter and dedup
+///
+/// Uses a single synchronous connection that is re-established lazily after
+/// errors, matching the blocking RPC client used elsewhere in request paths.
+pub struct RedisStore {
+    client: redis::Client,
+    connection: Mutex<Option<redis::Connection>>,
+    prefix: String,
+}
+
+impl std::fmt::Debug for RedisStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisStore")
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl RedisStore {
+    /// Connect to Redis, failing fast if it is unreachable at startup
+    pub fn new(url: &str, prefix: impl Into<String>) -> Result<Self> {
+        let client = redis::Client::open(url).context("Invalid REDIS_URL")?;
+        let store = Self {
+            client,
+            connection: Mutex::new(None),
+            prefix: prefix.into(),
+        };
+
+        store
+            .with_connection(|conn| redis::cmd("PING").query::<String>(conn))
+            .context("Failed to connect to Redis")?;
+
+        Ok(store)
+    }
+
+    /// Load Redis settings from environment
+    ///
+    /// Returns `Ok(None)` when `REDIS_URL` is not set.
+    pub fn from_env() -> Result<Option<Arc<Self>>> {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return Ok(None);
+        };
+
+        let prefix = std::env::var("REDIS_KEY_PREFIX").unwrap_or_else(|_| "x402".to_string());
+        let store = Self::new(&url, prefix)?;
+
+        tracing::info!("🧱 Connected to Redis for shared rate limits and dedup");
+
+        Ok(Some(Arc::new(store)))
+    }
+
+    fn key(&self, namespace: &str, id: &str) -> String {
+        format!("{}:{}:{}", self.prefix, namespace, id)
+    }
+
+    /// Run a command, reconnecting first if the previous connection failed
+    fn with_connection<T>(
+        &self,
+        f: impl FnOnce(&mut redis::Connection) -> redis::RedisResult<T>,
+    ) -> Result<T> {
+        let mut guard = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+
+        if guard.is_none() {
+            let conn = self.client.get_connection_with_timeout(REDIS_TIMEOUT)?;
+            conn.set_read_timeout(Some(REDIS_TIMEOUT))?;
+            conn.set_write_timeout(Some(REDIS_TIMEOUT))?;
+            *guard = Some(conn);
+        }
+
+        let result = f(guard.as_mut().expect("connection just established"));
+
+        // Drop broken connections so the next call reconnects
+        if let Err(e) = &result {
+            if e.is_io_error() || e.is_connection_dropped() || e.is_timeout() {
+                *guard = None;
+            }
+        }
+
+        Ok(result?)
+    }
+}
+
+/// Transaction dedup shared across replicas
+#[derive(Debug)]
+pub struct RedisDedup {
+    store: Arc<RedisStore>,
+    window_seconds: u64,
+}
+
+impl RedisDedup {
+    pub fn new(store: Arc<RedisStore>, window_seconds: u64) -> Self {
+        Self { store, window_seconds }
+    }
+
+    /// Atomically record a transaction hash; returns true if it was already seen
+    pub fn check_and_mark(&self, hash: &str) -> Result<bool> {
+        let key = self.store.key("dedup", hash);
+        let inserted: Option<String> = self.store.with_connection(|conn| {
+            redis::cmd("SET")
+                .arg(&key)
+                .arg(1)
+                .arg("NX")
+                .arg("EX")
+                .arg(self.window_seconds)
+                .query(conn)
+        })?;
+
+        Ok(inserted.is_none())
+    }
+
+    pub fn is_duplicate(&self, hash: &str) -> Result<bool> {
+        let key = self.store.key("dedup", hash);
+        self.store
+            .with_connection(|conn| redis::cmd("EXISTS").arg(&key).query(conn))
+    }
+
+    pub fn mark_seen(&self, hash: &str) -> Result<()> {
+        let key = self.store.key("dedup", hash);
+        self.store.with_connection(|conn| {
+            redis::cmd("SET")
+                .arg(&key)
+                .arg(1)
+                .arg("EX")
+                .arg(self.window_seconds)
+                .query(conn)
+        })
+    }
+
+    /// Forget a transaction hash; returns true if it was recorded
+    pub fn remove(&self, hash: &str) -> Result<bool> {
+        let key = self.store.key("dedup", hash);
+        let removed: u64 = self
+            .store
+            .with_connection(|conn| redis::cmd("DEL").arg(&key).query(conn))?;
+        Ok(removed > 0)
+    }
+
+    /// Forget every transaction hash; returns how many were removed
+    pub fn clear(&self) -> Result<u64> {
+        let pattern = self.store.key("dedup", "*");
+        let mut cursor = 0u64;
+        let mut removed = 0;
+
+        loop {
+            let (next, keys): (u64, Vec<String>) = self.store.with_connection(|conn| {
+                redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(500)
+                    .query(conn)
+            })?;
+
+            if !keys.is_empty() {
+                let deleted: u64 = self
+                    .store
+                    .with_connection(|conn| redis::cmd("DEL").arg(&keys).query(conn))?;
+                removed += deleted;
+            }
+
+            if next == 0 {
+                return Ok(removed);
+            }
+            cursor = next;
+        }
+    }
+}
+
+/// Per-client rate limiter shared across replicas
+pub struct RedisRateLimiter {
+    store: Arc<RedisStore>,
+    script: redis::Script,
+}
+
+impl std::fmt::Debug for RedisRateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisRateLimiter")
+            .field("store", &self.store)
+            .finish()
+    }
+}
+
+impl RedisRateLimiter {
+    pub fn new(store: Arc<RedisStore>) -> Self {
+        Self {
+            store,
+            script: redis::Script::new(GCRA_SCRIPT),
+        }
+    }
+
+    /// Check a client's quota; the inner result mirrors the in-memory limiter
+    pub fn check(
+        &self,
+        route: RateLimitRoute,
+        client: &str,
+        quota: RateLimitQuota,
+    ) -> Result<std::result::Result<(), Duration>> {
+        let key = self.store.key(&format!("ratelimit:{}", route.as_str()), client);
+        let emission_micros = 1_000_000 / u64::from(quota.per_second.max(1));
+        let burst = quota.burst_size.max(1);
+
+        let (allowed, wait_micros): (i64, i64) = self.store.with_connection(|conn| {
+            self.script
+                .key(&key)
+                .arg(emission_micros)
+                .arg(burst)
+                .invoke(conn)
+        })?;
+
+        if allowed == 1 {
+            Ok(Ok(()))
+        } else {
+            Ok(Err(Duration::from_micros(wait_micros.max(0) as u64)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Connect to the Redis in `REDIS_URL` with a unique prefix, if available
+    fn test_store() -> Option<Arc<RedisStore>> {
+        let url = std::env::var("REDIS_URL").ok()?;
+        let prefix = format!("x402-test-{}", uuid::Uuid::new_v4());
+        Some(Arc::new(RedisStore::new(&url, prefix).expect("REDIS_URL set but unreachable")))
+    }
+
+    #[test]
+    fn test_unreachable_redis_fails_fast() {
+        assert!(RedisStore::new("redis://127.0.0.1:1/", "x402").is_err());
+    }
+
+    #[test]
+    fn test_dedup_shared_between_instances() {
+        let Some(store) = test_store() else { return };
+
+        let replica_a = RedisDedup::new(store.clone(), 60);
+        let replica_b = RedisDedup::new(store, 60);
+
+        assert!(!replica_a.check_and_mark("abc").unwrap());
+        assert!(replica_b.check_and_mark("abc").unwrap());
+        assert!(replica_b.is_duplicate("abc").unwrap());
+        assert!(!replica_b.is_duplicate("def").unwrap());
+    }
+
+    #[test]
+    fn test_rate_limit_shared_between_instances() {
+        let Some(store) = test_store() else { return };
+        let quota = RateLimitQuota { per_second: 1, burst_size: 2 };
+
+        let replica_a = RedisRateLimiter::new(store.clone());
+        let replica_b = RedisRateLimiter::new(store);
+
+        assert!(replica_a.check(RateLimitRoute::Settle, "client", quota).unwrap().is_ok());
+        assert!(replica_b.check(RateLimitRoute::Settle, "client", quota).unwrap().is_ok());
+
+        // Burst used up across both replicas
+        let wait = replica_a
+            .check(RateLimitRoute::Settle, "client", quota)
+            .unwrap()
+            .unwrap_err();
+        assert!(wait <= Duration::from_secs(1));
+
+        // Quotas are per route
+        assert!(replica_b.check(RateLimitRoute::Verify, "client", quota).unwrap().is_ok());
+    }
+}
@@ -0,0 +1,292 @@
+use anyhow::{Context, Result};
+use arc_swap::ArcSwap;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::middleware::rate_limit::RateLimitQuota;
+use crate::solana::verifier::DEFAULT_REQUIRED_SIGNERS;
+
+/// Settings that can be changed without restarting the server
+///
+/// Everything else in [`Config`] (port, RPC endpoint, signer, cache sizes,
+/// whether a feature is enabled at all) is structural and only read at
+/// startup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuntimeSettings {
+    pub payment_expiry_seconds: u64,
+    /// Tolerance for clients whose clock is off, applied to payment
+    /// timestamps both ahead of now and near expiry
+    pub clock_skew_seconds: u64,
+    pub verify_quota: RateLimitQuota,
+    pub settle_quota: RateLimitQuota,
+    /// Token mints accepted as payment assets (`None` accepts any mint)
+    pub allowed_mints: Option<Vec<String>>,
+    pub webhook_url: Option<String>,
+    /// Fee payer balance below which `/admin/health` reports that this
+    /// instance can't settle
+    pub min_fee_payer_balance_lamports: u64,
+    /// Signatures an `exact` payment transaction must require
+    pub required_signers: u8,
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        Self {
+            payment_expiry_seconds: 600, // 10 minutes
+            clock_skew_seconds: 30,
+            verify_quota: RateLimitQuota { per_second: 10, burst_size: 20 },
+            // Settlement spends fee payer SOL, so it gets its own (tighter) quota
+            settle_quota: RateLimitQuota { per_second: 2, burst_size: 5 },
+            allowed_mints: None,
+            webhook_url: None,
+            // About a thousand transaction fees
+            min_fee_payer_balance_lamports: 5_000_000,
+            // Fee payer and payer
+            required_signers: DEFAULT_REQUIRED_SIGNERS,
+        }
+    }
+}
+
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default)
+}
+
+impl RuntimeSettings {
+    /// Load runtime settings from environment
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+
+        let allowed_mints = match std::env::var("ALLOWED_MINTS") {
+            Ok(list) if !list.trim().is_empty() => Some(parse_mint_allowlist(&list)?),
+            _ => None,
+        };
+
+        Ok(Self {
+            payment_expiry_seconds: env_or("PAYMENT_EXPIRY_SECONDS", defaults.payment_expiry_seconds),
+            clock_skew_seconds: env_or("CLOCK_SKEW_SECONDS", defaults.clock_skew_seconds),
+            verify_quota: RateLimitQuota {
+                per_second: env_or("RATE_LIMIT_PER_SECOND", defaults.verify_quota.per_second),
+                burst_size: env_or("RATE_LIMIT_BURST_SIZE", defaults.verify_quota.burst_size),
+            },
+            settle_quota: RateLimitQuota {
+                per_second: env_or("RATE_LIMIT_SETTLE_PER_SECOND", defaults.settle_quota.per_second),
+                burst_size: env_or("RATE_LIMIT_SETTLE_BURST_SIZE", defaults.settle_quota.burst_size),
+            },
+            allowed_mints,
+            webhook_url: std::env::var("WEBHOOK_URL").ok().filter(|url| !url.is_empty()),
+            min_fee_payer_balance_lamports: env_or(
+                "MIN_FEE_PAYER_BALANCE_LAMPORTS",
+                defaults.min_fee_payer_balance_lamports,
+            ),
+            required_signers: env_or("EXACT_REQUIRED_SIGNERS", defaults.required_signers),
+        })
+    }
+
+    /// Whether payments in `mint` are accepted
+    pub fn is_mint_allowed(&self, mint: &str) -> bool {
+        match &self.allowed_mints {
+            Some(mints) => mints.iter().any(|allowed| allowed == mint),
+            None => true,
+        }
+    }
+
+    /// Names of the settings that differ in `other`
+    pub fn changes(&self, other: &Self) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+
+        if self.payment_expiry_seconds != other.payment_expiry_seconds {
+            changed.push("payment_expiry_seconds");
+        }
+        if self.clock_skew_seconds != other.clock_skew_seconds {
+            changed.push("clock_skew_seconds");
+        }
+        if self.verify_quota != other.verify_quota {
+            changed.push("verify_quota");
+        }
+        if self.settle_quota != other.settle_quota {
+            changed.push("settle_quota");
+        }
+        if self.allowed_mints != other.allowed_mints {
+            changed.push("allowed_mints");
+        }
+        if self.webhook_url != other.webhook_url {
+            changed.push("webhook_url");
+        }
+        if self.min_fee_payer_balance_lamports != other.min_fee_payer_balance_lamports {
+            changed.push("min_fee_payer_balance_lamports");
+        }
+        if self.required_signers != other.required_signers {
+            changed.push("required_signers");
+        }
+
+        changed
+    }
+}
+
+/// Parse a comma-separated list of mint addresses
+fn parse_mint_allowlist(list: &str) -> Result<Vec<String>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|mint| !mint.is_empty())
+        .map(|mint| {
+            Pubkey::from_str(mint)
+                .map(|pubkey| pubkey.to_string())
+                .with_context(|| format!("Invalid mint in ALLOWED_MINTS: {}", mint))
+        })
+        .collect()
+}
+
+/// Current runtime settings, shared by all clones of the config
+///
+/// Readers take a snapshot with [`SharedSettings::load`]; a reload swaps the
+/// whole set atomically, so a request never sees a mix of old and new values.
+#[derive(Clone, Debug, Default)]
+pub struct SharedSettings(Arc<ArcSwap<RuntimeSettings>>);
+
+impl SharedSettings {
+    pub fn new(settings: RuntimeSettings) -> Self {
+        Self(Arc::new(ArcSwap::from_pointee(settings)))
+    }
+
+    /// Snapshot of the current settings
+    pub fn load(&self) -> Arc<RuntimeSettings> {
+        self.0.load_full()
+    }
+
+    fn store(&self, settings: RuntimeSettings) {
+        self.0.store(Arc::new(settings));
+    }
+}
+
+/// Apply new runtime settings to the running configuration
+///
+/// Returns the names of settings that changed, and records them as a
+/// `ConfigChanged` audit event.
+pub fn apply(config: &Config, settings: RuntimeSettings) -> Result<Vec<&'static str>> {
+    let current = config.settings.load();
+    let changed = current.changes(&settings);
+    if changed.is_empty() {
+        return Ok(changed);
+    }
+
+    if config.webhook.is_some() && settings.webhook_url.is_none() {
+        anyhow::bail!("WEBHOOK_URL cannot be removed at runtime; set WEBHOOK_ENABLED=false and restart");
+    }
+
+    if let Some(limiter) = &config.rate_limiter {
+        if settings.verify_quota != current.verify_quota || settings.settle_quota != current.settle_quota {
+            limiter.set_quotas(settings.verify_quota, settings.settle_quota);
+        }
+    }
+
+    if let (Some(webhook), Some(url)) = (&config.webhook, &settings.webhook_url) {
+        webhook.url.set(url.clone());
+    }
+
+    config.audit_logger.log_config_changed(
+        "runtime_settings",
+        serde_json::json!({
+            "changed": changed,
+            "settings": settings,
+        }),
+    );
+    config.settings.store(settings);
+
+    Ok(changed)
+}
+
+/// Re-read runtime settings from the environment and `.env`, and apply them
+///
+/// Invalid settings are rejected as a whole, leaving the current ones in place.
+pub fn reload(config: &Config) -> Result<Vec<&'static str>> {
+    dotenvy::dotenv_override().ok();
+    let changed = apply(config, RuntimeSettings::from_env()?)?;
+
+    if changed.is_empty() {
+        tracing::info!("🔄 Runtime settings reloaded: no changes");
+    } else {
+        tracing::info!("🔄 Runtime settings reloaded: changed {}", changed.join(", "));
+    }
+
+    Ok(changed)
+}
+
+/// Reload runtime settings whenever the process receives SIGHUP
+#[cfg(unix)]
+pub fn spawn_sighup_listener(config: Config) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            tracing::warn!("⚠️  Failed to install SIGHUP handler, config reload via signal disabled: {}", e);
+            return;
+        }
+    };
+
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            tracing::info!("🔄 Received SIGHUP, reloading runtime settings");
+            if let Err(e) = reload(&config) {
+                tracing::warn!("⚠️  Config reload failed, keeping current settings: {:#}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    #[test]
+    fn test_parse_mint_allowlist() {
+        let mints = parse_mint_allowlist(&format!(" {} ,,{}", USDC, Pubkey::default())).unwrap();
+        assert_eq!(mints, vec![USDC.to_string(), Pubkey::default().to_string()]);
+
+        assert!(parse_mint_allowlist("not-a-mint").is_err());
+    }
+
+    #[test]
+    fn test_mint_allowed() {
+        let mut settings = RuntimeSettings::default();
+        assert!(settings.is_mint_allowed(USDC));
+
+        settings.allowed_mints = Some(vec![USDC.to_string()]);
+        assert!(settings.is_mint_allowed(USDC));
+        assert!(!settings.is_mint_allowed(&Pubkey::default().to_string()));
+    }
+
+    #[test]
+    fn test_changes() {
+        let current = RuntimeSettings::default();
+        assert!(current.changes(&current.clone()).is_empty());
+
+        let updated = RuntimeSettings {
+            payment_expiry_seconds: 120,
+            settle_quota: RateLimitQuota { per_second: 1, burst_size: 1 },
+            ..current.clone()
+        };
+        assert_eq!(current.changes(&updated), vec!["payment_expiry_seconds", "settle_quota"]);
+    }
+
+    #[test]
+    fn test_shared_settings_swap_visible_to_clones() {
+        let shared = SharedSettings::default();
+        let clone = shared.clone();
+        let before = clone.load();
+
+        shared.store(RuntimeSettings { payment_expiry_seconds: 60, ..RuntimeSettings::default() });
+
+        assert_eq!(clone.load().payment_expiry_seconds, 60);
+        // Snapshots taken earlier are unaffected
+        assert_eq!(before.payment_expiry_seconds, 600);
+    }
+}
@@ -0,0 +1,117 @@
+//! Rent the fee payer may spend creating recipients' token accounts
+//!
+//! The payer funds any associated token account a payment creates. With
+//! `FEE_PAYER_ATA_RENT_BUDGET_LAMPORTS` set, the facilitator's fee payer may
+//! fund it instead, up to that many lamports per UTC day. Rent is charged
+//! to the budget when a settlement is submitted and isn't refunded if it
+//! fails, so the budget errs on the side of spending less.
+
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Daily budget of rent the fee payer sponsors
+#[derive(Debug, Clone, Default)]
+pub struct AtaRentBudget {
+    /// Lamports per UTC day (`None`: the fee payer never funds accounts)
+    daily_lamports: Option<u64>,
+    /// UTC day and the lamports spent on it
+    spent: Arc<Mutex<(u64, u64)>>,
+}
+
+impl AtaRentBudget {
+    pub fn new(daily_lamports: Option<u64>) -> Self {
+        Self { daily_lamports, spent: Arc::default() }
+    }
+
+    /// Budget from `FEE_PAYER_ATA_RENT_BUDGET_LAMPORTS` (disabled if unset)
+    pub fn from_env() -> Self {
+        let daily_lamports = std::env::var("FEE_PAYER_ATA_RENT_BUDGET_LAMPORTS")
+            .ok()
+            .and_then(|value| value.parse().ok());
+        if let Some(lamports) = daily_lamports {
+            tracing::info!("🏦 Fee payer may fund token accounts, up to {} lamports of rent per day", lamports);
+        }
+        Self::new(daily_lamports)
+    }
+
+    /// Whether the fee payer may fund token accounts at all
+    pub fn enabled(&self) -> bool {
+        self.daily_lamports.is_some()
+    }
+
+    pub fn daily_lamports(&self) -> Option<u64> {
+        self.daily_lamports
+    }
+
+    /// Lamports left today
+    pub fn remaining(&self) -> u64 {
+        self.remaining_on(today())
+    }
+
+    /// Charge `lamports` to today's budget, returning what's left (`None`
+    /// if it doesn't fit)
+    pub fn try_spend(&self, lamports: u64) -> Option<u64> {
+        self.try_spend_on(today(), lamports)
+    }
+
+    fn remaining_on(&self, day: u64) -> u64 {
+        let Some(daily) = self.daily_lamports else {
+            return 0;
+        };
+        let (spent_day, spent) = *self.spent.lock().unwrap();
+        if spent_day == day {
+            daily.saturating_sub(spent)
+        } else {
+            daily
+        }
+    }
+
+    fn try_spend_on(&self, day: u64, lamports: u64) -> Option<u64> {
+        let daily = self.daily_lamports?;
+        let mut spent = self.spent.lock().unwrap();
+        if spent.0 != day {
+            *spent = (day, 0);
+        }
+        let total = spent.1.checked_add(lamports).filter(|total| *total <= daily)?;
+        spent.1 = total;
+        Some(daily - total)
+    }
+}
+
+/// Days since the Unix epoch (UTC)
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_caps_daily_spending() {
+        let budget = AtaRentBudget::new(Some(5_000_000));
+        assert_eq!(budget.remaining_on(1), 5_000_000);
+
+        assert_eq!(budget.try_spend_on(1, 2_039_280), Some(2_960_720));
+        assert_eq!(budget.try_spend_on(1, 2_039_280), Some(921_440));
+        assert_eq!(budget.try_spend_on(1, 2_039_280), None);
+        assert_eq!(budget.remaining_on(1), 921_440);
+
+        // A new day starts over
+        assert_eq!(budget.remaining_on(2), 5_000_000);
+        assert_eq!(budget.try_spend_on(2, 2_039_280), Some(2_960_720));
+    }
+
+    #[test]
+    fn test_disabled_budget_never_spends() {
+        let budget = AtaRentBudget::default();
+        assert!(!budget.enabled());
+        assert_eq!(budget.remaining(), 0);
+        assert_eq!(budget.try_spend(1), None);
+    }
+}
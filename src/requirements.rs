@@ -0,0 +1,264 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+use crate::config::Config;
+use crate::types::requests::{ExtraFields, PaymentRequirements};
+
+/// x402 protocol version emitted in 402 responses
+pub const X402_VERSION: u32 = 1;
+
+/// Default `maxTimeoutSeconds` of built requirements
+pub const DEFAULT_MAX_TIMEOUT_SECONDS: u64 = 60;
+
+/// USDC mint on Solana mainnet
+pub const USDC_MAINNET: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+/// USDC mint on Solana devnet
+pub const USDC_DEVNET: &str = "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU";
+
+/// USDC mint of a network (`None` for networks without a known mint)
+pub fn usdc_mint(network: &str) -> Option<Pubkey> {
+    match network {
+        "solana" => Pubkey::from_str(USDC_MAINNET).ok(),
+        "solana-devnet" => Pubkey::from_str(USDC_DEVNET).ok(),
+        _ => None,
+    }
+}
+
+/// Builds [`PaymentRequirements`] for resource servers
+///
+/// `payTo` is the recipient wallet; the verifier derives its associated
+/// token account for the asset. The asset defaults to the network's USDC.
+///
+/// ```no_run
+/// # fn example(config: &x402_facilitator::config::Config, merchant: solana_sdk::pubkey::Pubkey) -> anyhow::Result<()> {
+/// use x402_facilitator::requirements::{PaymentRequiredResponse, RequirementsBuilder};
+///
+/// let requirements = RequirementsBuilder::from_config(config)
+///     .pay_to(merchant)
+///     .amount(10_000)
+///     .resource("/api/premium")
+///     .description("Premium API access")
+///     .build()?;
+/// let body = serde_json::to_string(&PaymentRequiredResponse::new(vec![requirements]))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequirementsBuilder {
+    network: String,
+    fee_payer: Pubkey,
+    asset: Option<Pubkey>,
+    pay_to: Option<Pubkey>,
+    amount: Option<u64>,
+    resource: String,
+    description: String,
+    mime_type: String,
+    max_timeout_seconds: u64,
+    output_schema: Option<serde_json::Value>,
+    extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl RequirementsBuilder {
+    /// Requirements for payments on `network`, settled by `fee_payer`
+    pub fn new(network: impl Into<String>, fee_payer: Pubkey) -> Self {
+        Self {
+            network: network.into(),
+            fee_payer,
+            asset: None,
+            pay_to: None,
+            amount: None,
+            resource: String::new(),
+            description: String::new(),
+            mime_type: "application/json".to_string(),
+            max_timeout_seconds: DEFAULT_MAX_TIMEOUT_SECONDS,
+            output_schema: None,
+            extra: BTreeMap::new(),
+        }
+    }
+
+    /// Requirements settled by this facilitator (its network and fee payer)
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.network.clone(), config.fee_payer_signer.pubkey())
+    }
+
+    /// Token mint to be paid in (default: USDC of the network)
+    pub fn asset(mut self, mint: Pubkey) -> Self {
+        self.asset = Some(mint);
+        self
+    }
+
+    /// Recipient wallet
+    pub fn pay_to(mut self, wallet: Pubkey) -> Self {
+        self.pay_to = Some(wallet);
+        self
+    }
+
+    /// Amount in the asset's base units
+    pub fn amount(mut self, amount: u64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn resource(mut self, resource: impl Into<String>) -> Self {
+        self.resource = resource.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = mime_type.into();
+        self
+    }
+
+    pub fn max_timeout_seconds(mut self, seconds: u64) -> Self {
+        self.max_timeout_seconds = seconds;
+        self
+    }
+
+    pub fn output_schema(mut self, schema: serde_json::Value) -> Self {
+        self.output_schema = Some(schema);
+        self
+    }
+
+    /// Key of `extra` besides the fee payer, e.g. a nonce the scheme requires
+    pub fn extra(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extra.insert(key.into(), value);
+        self
+    }
+
+    /// Build the requirements, checking the fields the verifier relies on
+    pub fn build(&self) -> Result<PaymentRequirements> {
+        let asset = match self.asset {
+            Some(asset) => asset,
+            None => usdc_mint(&self.network)
+                .with_context(|| format!("No default asset for network '{}'; set one", self.network))?,
+        };
+        let pay_to = self.pay_to.context("payTo is required")?;
+        let amount = self.amount.context("amount is required")?;
+        if amount == 0 {
+            bail!("amount must be positive");
+        }
+        if pay_to == self.fee_payer {
+            bail!("payTo must not be the facilitator's fee payer");
+        }
+        if self.resource.is_empty() {
+            bail!("resource is required");
+        }
+        if self.extra.contains_key("feePayer") {
+            bail!("extra.feePayer is the facilitator's fee payer; don't set it");
+        }
+
+        Ok(PaymentRequirements {
+            scheme: "exact".to_string(),
+            network: self.network.clone(),
+            max_amount_required: amount.to_string(),
+            asset: asset.to_string(),
+            pay_to: pay_to.to_string(),
+            resource: self.resource.clone(),
+            description: self.description.clone(),
+            mime_type: self.mime_type.clone(),
+            max_timeout_seconds: self.max_timeout_seconds,
+            output_schema: self.output_schema.clone(),
+            extra: ExtraFields {
+                fee_payer: self.fee_payer.to_string(),
+                other: self.extra.clone(),
+            },
+        })
+    }
+}
+
+/// Body of an HTTP 402 Payment Required response
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequiredResponse {
+    #[schema(example = 1)]
+    pub x402_version: u32,
+
+    /// Why payment is required (or why the last payment was rejected)
+    #[schema(example = "X-PAYMENT header is required")]
+    pub error: String,
+
+    /// Accepted ways to pay
+    pub accepts: Vec<PaymentRequirements>,
+}
+
+impl PaymentRequiredResponse {
+    pub fn new(accepts: Vec<PaymentRequirements>) -> Self {
+        Self {
+            x402_version: X402_VERSION,
+            error: "X-PAYMENT header is required".to_string(),
+            accepts,
+        }
+    }
+
+    /// Report why a submitted payment was rejected (e.g. `invalidReason`)
+    pub fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = error.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_requirements() {
+        let fee_payer = Pubkey::new_unique();
+        let merchant = Pubkey::new_unique();
+
+        let requirements = RequirementsBuilder::new("solana-devnet", fee_payer)
+            .pay_to(merchant)
+            .amount(10_000)
+            .resource("/api/premium")
+            .extra("orderId", serde_json::json!("order-1042"))
+            .build()
+            .unwrap();
+
+        assert_eq!(requirements.asset, USDC_DEVNET);
+        assert_eq!(requirements.pay_to, merchant.to_string());
+        assert_eq!(requirements.max_amount_required, "10000");
+        assert_eq!(requirements.extra.fee_payer, fee_payer.to_string());
+        assert_eq!(requirements.max_timeout_seconds, DEFAULT_MAX_TIMEOUT_SECONDS);
+
+        let body = serde_json::to_value(PaymentRequiredResponse::new(vec![requirements])).unwrap();
+        assert_eq!(body["x402Version"], 1);
+        assert_eq!(body["accepts"][0]["payTo"], merchant.to_string());
+        assert_eq!(body["accepts"][0]["extra"]["feePayer"], fee_payer.to_string());
+        assert_eq!(body["accepts"][0]["extra"]["orderId"], "order-1042");
+    }
+
+    #[test]
+    fn test_build_requirements_rejects_invalid() {
+        let fee_payer = Pubkey::new_unique();
+        let builder = RequirementsBuilder::new("solana-devnet", fee_payer)
+            .amount(10_000)
+            .resource("/api/premium");
+
+        // Missing payTo
+        assert!(builder.build().is_err());
+        // Paying the facilitator
+        assert!(builder.clone().pay_to(fee_payer).build().is_err());
+        // Zero amount
+        assert!(builder.clone().pay_to(Pubkey::new_unique()).amount(0).build().is_err());
+        // Overriding the fee payer through extra
+        let extra_fee_payer = builder.clone().pay_to(Pubkey::new_unique()).extra("feePayer", serde_json::json!("x"));
+        assert!(extra_fee_payer.build().is_err());
+        // No default asset on unknown networks
+        let builder = RequirementsBuilder::new("solana-testnet", fee_payer)
+            .pay_to(Pubkey::new_unique())
+            .amount(1)
+            .resource("/r");
+        assert!(builder.build().is_err());
+        assert!(builder.asset(Pubkey::new_unique()).build().is_ok());
+    }
+}
@@ -0,0 +1,318 @@
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::metrics::RpcHealthMetrics;
+use crate::solana::rpc::SolanaRpc;
+use crate::webhooks::{dispatch_alert, WebhookConfig, WebhookEvent};
+
+/// RPC health monitoring settings
+///
+/// Configuration:
+/// * `RPC_HEALTH_INTERVAL_SECONDS` - Seconds between checks (default: 15)
+/// * `RPC_HEALTH_REFERENCE_URLS` - Comma-separated RPC endpoints checked
+///   alongside `SOLANA_RPC_URL`, so its slot lag can be measured against
+///   another provider (default: none)
+#[derive(Debug, Clone)]
+pub struct RpcHealthConfig {
+    pub interval: Duration,
+    pub reference_urls: Vec<String>,
+}
+
+impl RpcHealthConfig {
+    pub fn from_env() -> Self {
+        let interval = std::env::var("RPC_HEALTH_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(15u64);
+        let reference_urls = std::env::var("RPC_HEALTH_REFERENCE_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        Self {
+            interval: Duration::from_secs(interval.max(1)),
+            reference_urls,
+        }
+    }
+}
+
+/// Health of one RPC endpoint at the last check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    /// Host of the endpoint (paths and queries are left out, as they often
+    /// hold API keys)
+    pub endpoint: String,
+    /// Whether the facilitator sends its requests to this endpoint
+    pub primary: bool,
+    /// `getHealth` succeeded
+    pub healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Confirmed slot (`None` if `getSlot` failed)
+    pub slot: Option<u64>,
+    /// Slots behind the most advanced endpoint
+    pub slot_lag: Option<u64>,
+    /// Round trip of `getSlot` in milliseconds
+    pub latency_ms: Option<u64>,
+}
+
+/// Result of checking every monitored endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcHealthSnapshot {
+    /// Unix timestamp of the check
+    pub checked_at: u64,
+    pub endpoints: Vec<EndpointHealth>,
+}
+
+impl RpcHealthSnapshot {
+    /// The endpoint the facilitator uses
+    pub fn primary(&self) -> Option<&EndpointHealth> {
+        self.endpoints.iter().find(|endpoint| endpoint.primary)
+    }
+}
+
+struct Endpoint {
+    label: String,
+    primary: bool,
+    rpc: Arc<dyn SolanaRpc>,
+}
+
+/// Periodically measures the health, slot lag and latency of the RPC
+/// endpoints
+///
+/// Each check updates the `x402_rpc_*` gauges (labelled by endpoint host)
+/// and the snapshot served by `GET /admin/rpc`, so an RPC provider incident
+/// is easy to tell apart from a facilitator bug.
+#[derive(Clone)]
+pub struct RpcHealthMonitor {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    endpoints: Vec<Endpoint>,
+    interval: Duration,
+    snapshot: RwLock<Option<RpcHealthSnapshot>>,
+    metrics: RpcHealthMetrics,
+}
+
+impl std::fmt::Debug for RpcHealthMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcHealthMonitor")
+            .field("endpoints", &self.inner.endpoints.iter().map(|e| &e.label).collect::<Vec<_>>())
+            .field("interval", &self.inner.interval)
+            .finish()
+    }
+}
+
+impl RpcHealthMonitor {
+    /// Monitor of `rpc` (served at `url`) alone
+    pub fn new(url: &str, rpc: Arc<dyn SolanaRpc>, interval: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                endpoints: vec![Endpoint {
+                    label: endpoint_label(url),
+                    primary: true,
+                    rpc,
+                }],
+                interval,
+                snapshot: RwLock::new(None),
+                metrics: RpcHealthMetrics::new(),
+            }),
+        }
+    }
+
+    /// Monitor of the facilitator's RPC client and the reference endpoints
+    /// of `config`
+    pub fn from_config(url: &str, rpc: Arc<dyn SolanaRpc>, config: &RpcHealthConfig) -> Self {
+        let references = config.reference_urls.iter().map(|reference| {
+            let client: Arc<dyn SolanaRpc> = Arc::new(RpcClient::new_with_commitment(
+                reference.clone(),
+                CommitmentConfig::confirmed(),
+            ));
+            (reference.as_str(), client)
+        });
+        Self::new(url, rpc, config.interval).with_references(references)
+    }
+
+    /// Also check these endpoints, as slot references
+    pub fn with_references<'a>(
+        mut self,
+        references: impl IntoIterator<Item = (&'a str, Arc<dyn SolanaRpc>)>,
+    ) -> Self {
+        let inner = Arc::get_mut(&mut self.inner).expect("references are added before the monitor is shared");
+        for (url, rpc) in references {
+            inner.endpoints.push(Endpoint {
+                label: endpoint_label(url),
+                primary: false,
+                rpc,
+            });
+        }
+        self
+    }
+
+    /// Snapshot of the last check (`None` before the first one)
+    pub fn snapshot(&self) -> Option<RpcHealthSnapshot> {
+        self.inner.snapshot.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Check every endpoint now, updating the gauges and the snapshot
+    ///
+    /// Blocking (RPC calls); run it on the blocking pool.
+    pub fn check(&self) -> RpcHealthSnapshot {
+        let mut endpoints: Vec<EndpointHealth> = self
+            .inner
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let health = endpoint.rpc.get_health();
+                let start = Instant::now();
+                let slot = endpoint.rpc.get_slot();
+                let latency = start.elapsed();
+
+                let error = match (&health, &slot) {
+                    (Err(e), _) | (_, Err(e)) => Some(e.to_string()),
+                    _ => None,
+                };
+                EndpointHealth {
+                    endpoint: endpoint.label.clone(),
+                    primary: endpoint.primary,
+                    healthy: health.is_ok(),
+                    error,
+                    latency_ms: slot.is_ok().then_some(latency.as_millis() as u64),
+                    slot: slot.ok(),
+                    slot_lag: None,
+                }
+            })
+            .collect();
+
+        let highest = endpoints.iter().filter_map(|endpoint| endpoint.slot).max();
+        for endpoint in &mut endpoints {
+            endpoint.slot_lag = endpoint.slot.zip(highest).map(|(slot, highest)| highest - slot);
+            self.inner.metrics.record(endpoint);
+        }
+
+        let snapshot = RpcHealthSnapshot {
+            checked_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|now| now.as_secs())
+                .unwrap_or_default(),
+            endpoints,
+        };
+        *self.inner.snapshot.write().unwrap_or_else(|e| e.into_inner()) = Some(snapshot.clone());
+        snapshot
+    }
+
+    /// Check the endpoints every interval in the background, sending an
+    /// `rpc.unhealthy` webhook when the primary endpoint fails
+    pub fn spawn(&self, webhook: Option<WebhookConfig>) {
+        let monitor = self.clone();
+        tracing::info!(
+            "🩺 Monitoring {} RPC endpoint(s) every {}s",
+            monitor.inner.endpoints.len(),
+            monitor.inner.interval.as_secs()
+        );
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(monitor.inner.interval);
+            let mut was_healthy = true;
+            loop {
+                ticker.tick().await;
+                let check = monitor.clone();
+                let snapshot = match tokio::task::spawn_blocking(move || check.check()).await {
+                    Ok(snapshot) => snapshot,
+                    Err(e) => {
+                        tracing::error!("RPC health check panicked: {}", e);
+                        continue;
+                    }
+                };
+
+                // Log transitions rather than every failed check
+                let Some(primary) = snapshot.primary() else { continue };
+                let healthy = primary.healthy && primary.slot.is_some();
+                if was_healthy && !healthy {
+                    tracing::warn!(
+                        "🩺 RPC endpoint {} is unhealthy: {}",
+                        primary.endpoint,
+                        primary.error.as_deref().unwrap_or("unknown error")
+                    );
+                    if let Some(webhook) = &webhook {
+                        dispatch_alert(
+                            webhook,
+                            None,
+                            WebhookEvent::RpcUnhealthy,
+                            &primary.endpoint,
+                            serde_json::json!({
+                                "endpoint": primary.endpoint,
+                                "error": primary.error,
+                                "slot_lag": primary.slot_lag,
+                            }),
+                        );
+                    }
+                } else if !was_healthy && healthy {
+                    tracing::info!("🩺 RPC endpoint {} recovered", primary.endpoint);
+                }
+                was_healthy = healthy;
+            }
+        });
+    }
+}
+
+/// Host (and port) of an RPC URL, used as the metrics label
+fn endpoint_label(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            let host = url.host_str()?.to_string();
+            Some(match url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host,
+            })
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solana::rpc::MockRpc;
+
+    #[test]
+    fn test_endpoint_label_hides_api_keys() {
+        assert_eq!(endpoint_label("https://mainnet.helius-rpc.com/?api-key=secret"), "mainnet.helius-rpc.com");
+        assert_eq!(endpoint_label("http://127.0.0.1:8899"), "127.0.0.1:8899");
+        assert_eq!(endpoint_label("not a url"), "unknown");
+    }
+
+    #[test]
+    fn test_check_measures_slot_lag() {
+        let primary = Arc::new(MockRpc::new());
+        let reference = Arc::new(MockRpc::new());
+        primary.set_slot(100);
+        reference.set_slot(112);
+
+        let monitor = RpcHealthMonitor::new("https://primary.example", primary.clone(), Duration::from_secs(15))
+            .with_references([("https://reference.example", reference.clone() as Arc<dyn SolanaRpc>)]);
+        assert!(monitor.snapshot().is_none());
+
+        let snapshot = monitor.check();
+        let primary_health = snapshot.primary().unwrap();
+        assert_eq!(primary_health.endpoint, "primary.example");
+        assert!(primary_health.healthy);
+        assert_eq!(primary_health.slot, Some(100));
+        assert_eq!(primary_health.slot_lag, Some(12));
+        assert_eq!(snapshot.endpoints[1].slot_lag, Some(0));
+        assert_eq!(monitor.snapshot(), Some(snapshot));
+
+        primary.fail_health(Some("Node is behind by 12 slots"));
+        let snapshot = monitor.check();
+        let primary_health = snapshot.primary().unwrap();
+        assert!(!primary_health.healthy);
+        assert_eq!(primary_health.error.as_deref(), Some("Node is behind by 12 slots"));
+    }
+}
@@ -0,0 +1,320 @@
+use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
+use std::str::FromStr;
+
+use super::{Diagnosis, NetworkFamily, SchemeVerifier, Verified, VerifyContext};
+use crate::config::Config;
+use crate::error::VerificationError;
+use crate::pricing::UsdAmount;
+use crate::solana::{decoder::decode_transaction_from_base64, verifier::*};
+use crate::types::{
+    requests::{ExtraFields, PaymentRequirements, VerifyRequest},
+    responses::PaymentDetails,
+};
+
+/// The `exact` scheme on Solana: a partially signed SPL Token transfer of
+/// exactly the required amount, with the facilitator as fee payer
+///
+/// The transaction must be 3 or 4 instructions (compute unit limit and
+/// price, optionally creating the destination token account, then the
+/// transfer), and the fee payer may only pay fees. Requirements priced in
+/// USD (`extra.usdAmount`) must also be worth it at the oracle's price.
+pub struct ExactSvmVerifier;
+
+impl SchemeVerifier for ExactSvmVerifier {
+    fn scheme(&self) -> &'static str {
+        "exact"
+    }
+
+    fn family(&self) -> NetworkFamily {
+        NetworkFamily::Svm
+    }
+
+    fn networks(&self) -> &[&'static str] {
+        &["solana-devnet", "solana"]
+    }
+
+    fn validate_extra(&self, extra: &ExtraFields) -> Result<(), VerificationError> {
+        UsdAmount::from_extra(extra).map(|_| ())
+    }
+
+    fn verify(&self, ctx: &VerifyContext<'_>, request: &VerifyRequest) -> Result<Verified, VerificationError> {
+        let config = ctx.config;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+
+        // 1. Decode transaction
+        let transaction = config
+            .metrics
+            .time_step("decode", || decode_transaction_from_base64(&payload.payload.transaction))
+            .map_err(|_| VerificationError::UnexpectedError(
+                anyhow::anyhow!("Failed to decode transaction")
+            ))?;
+
+        // Get fee payer from requirements
+        let fee_payer = Pubkey::from_str(&requirements.extra.fee_payer)
+            .map_err(|_| VerificationError::UnexpectedError(
+                anyhow::anyhow!("Invalid fee payer pubkey")
+            ))?;
+
+        // 2-5. Structural checks (no account lookups)
+        let required_signers = config.settings.load().required_signers;
+        let has_create_ata = config.metrics.time_step("structure", || {
+            // 1.5. Verify the message header and that no unknown co-signers
+            // are required
+            verify_message_header(&transaction)?;
+            verify_signer_count(&transaction, required_signers)?;
+
+            // 2. Verify instruction count (3 or 4)
+            let has_create_ata = verify_instruction_count(&transaction)?;
+
+            // 3. Verify compute budget instructions
+            verify_compute_limit_instruction(
+                &transaction.message.instructions[0],
+                &transaction.message,
+            )?;
+
+            verify_compute_price_instruction(
+                &transaction.message.instructions[1],
+                &transaction.message,
+            )?;
+
+            // 4. Verify fee payer position (pays the fees) and safety (not
+            // in any instruction accounts)
+            verify_fee_payer_position(&transaction, &fee_payer)?;
+            check_fee_payer_safety(config, &transaction, &fee_payer)?;
+
+            // 5. Verify CreateATA instruction (if present) and who funds it
+            if has_create_ata {
+                verify_create_ata_instruction(
+                    &transaction.message.instructions[2],
+                    &transaction.message,
+                    requirements,
+                )?;
+                check_ata_funding(config, &transaction, &fee_payer)?;
+            }
+            Ok::<_, VerificationError>(has_create_ata)
+        })?;
+
+        // 6. Verify transfer instruction (last instruction)
+        let transfer_idx = if has_create_ata { 3 } else { 2 };
+        let transfer_ix = &transaction.message.instructions[transfer_idx];
+
+        // Offline: structure only, the account checks are reported as skipped
+        if config.offline_verify {
+            let transfer = config.metrics.time_step("structure", || {
+                parse_transfer_instruction(
+                    transfer_ix,
+                    &transaction.message,
+                    requirements,
+                    &fee_payer,
+                    config.allow_unchecked_transfer,
+                )
+            })?;
+            let mut checks_skipped = account_checks(has_create_ata, transfer.decimals.is_some());
+            // A plain Transfer doesn't say the mint's decimals
+            match transfer.decimals {
+                Some(decimals) => check_usd_amount(config, requirements, transfer.amount, decimals)?,
+                None if UsdAmount::from_extra(&requirements.extra)?.is_some() => {
+                    checks_skipped.push("usd_amount".to_string())
+                }
+                None => {}
+            }
+            return Ok(Verified {
+                payer: transfer.authority.to_string(),
+                checks_skipped,
+                details: Some(payment_details(&transaction, &transfer, &fee_payer, has_create_ata)),
+            });
+        }
+
+        // 7. Check the accounts through the shared cache, a batch's
+        // prefetched accounts and the RPC client
+        let mut accounts = AccountLookup::from_config(config);
+        if let Some(prefetched) = ctx.prefetched {
+            accounts = accounts.with_prefetched(prefetched);
+        }
+        // The payer is the wallet authorizing the transfer
+        let transfer = config.metrics.time_step("accounts", || {
+            verify_transfer_instruction(
+                transfer_ix,
+                &transaction.message,
+                requirements,
+                &fee_payer,
+                has_create_ata,
+                config.allow_unchecked_transfer,
+                &accounts,
+            )
+        })?;
+        let decimals = match transfer.decimals {
+            Some(decimals) => decimals,
+            None => accounts.mint(&transfer.mint).ok_or(VerificationError::MintNotFound)?.decimals,
+        };
+        check_usd_amount(config, requirements, transfer.amount, decimals)?;
+
+        Ok(Verified {
+            payer: transfer.authority.to_string(),
+            checks_skipped: Vec::new(),
+            details: Some(payment_details(&transaction, &transfer, &fee_payer, has_create_ata)),
+        })
+    }
+
+    fn diagnose(&self, ctx: &VerifyContext<'_>, request: &VerifyRequest, diagnosis: &mut Diagnosis) {
+        let config = ctx.config;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+
+        let fee_payer = diagnosis.check(
+            "fee_payer",
+            Pubkey::from_str(&requirements.extra.fee_payer)
+                .map_err(|_| VerificationError::UnexpectedError(anyhow::anyhow!("Invalid fee payer pubkey"))),
+        );
+        let Some(transaction) = diagnosis.check(
+            "decode",
+            decode_transaction_from_base64(&payload.payload.transaction).map_err(|_| {
+                VerificationError::UnexpectedError(anyhow::anyhow!("Failed to decode transaction"))
+            }),
+        ) else {
+            return;
+        };
+
+        let message = &transaction.message;
+        diagnosis.check("message_header", verify_message_header(&transaction));
+        diagnosis.check(
+            "signer_count",
+            verify_signer_count(&transaction, config.settings.load().required_signers),
+        );
+        diagnosis.payer = transfer_authority(message).ok().map(|authority| authority.to_string());
+
+        let has_create_ata = diagnosis.check("instruction_count", verify_instruction_count(&transaction));
+        if let Some(instruction) = message.instructions.first() {
+            diagnosis.check("compute_limit", verify_compute_limit_instruction(instruction, message));
+        }
+        if let Some(instruction) = message.instructions.get(1) {
+            diagnosis.check("compute_price", verify_compute_price_instruction(instruction, message));
+        }
+        if let Some(fee_payer) = &fee_payer {
+            diagnosis.check("fee_payer_position", verify_fee_payer_position(&transaction, fee_payer));
+            diagnosis.check("fee_payer_safety", check_fee_payer_safety(config, &transaction, fee_payer));
+        }
+
+        // The remaining instructions are only located with a valid count
+        let (Some(has_create_ata), Some(fee_payer)) = (has_create_ata, &fee_payer) else {
+            return;
+        };
+        if has_create_ata {
+            diagnosis.check(
+                "create_ata",
+                verify_create_ata_instruction(&message.instructions[2], message, requirements),
+            );
+            diagnosis.check("create_ata_funder", check_ata_funding(config, &transaction, fee_payer));
+        }
+
+        let transfer_idx = if has_create_ata { 3 } else { 2 };
+        let transfer = diagnosis.check(
+            "transfer",
+            parse_transfer_instruction(
+                &message.instructions[transfer_idx],
+                message,
+                requirements,
+                fee_payer,
+                config.allow_unchecked_transfer,
+            ),
+        );
+
+        if config.offline_verify {
+            let checked = !matches!(transfer, Some(TransferAccounts { decimals: None, .. }));
+            diagnosis.checks_skipped = account_checks(has_create_ata, checked);
+            if let Some(TransferAccounts { amount, decimals: Some(decimals), .. }) = transfer {
+                diagnosis.check("usd_amount", check_usd_amount(config, requirements, amount, decimals));
+            }
+            return;
+        }
+        let Some(transfer) = transfer else {
+            return;
+        };
+
+        let mut accounts = AccountLookup::from_config(config);
+        if let Some(prefetched) = ctx.prefetched {
+            accounts = accounts.with_prefetched(prefetched);
+        }
+        match accounts.mint(&transfer.mint) {
+            None => diagnosis.fail("mint_exists", VerificationError::MintNotFound),
+            Some(mint) if transfer.decimals.is_some_and(|decimals| decimals != mint.decimals) => {
+                diagnosis.fail("mint_decimals", VerificationError::DecimalsMismatch)
+            }
+            Some(mint) => {
+                diagnosis.check("usd_amount", check_usd_amount(config, requirements, transfer.amount, mint.decimals));
+            }
+        }
+        if !accounts.exists(AccountKind::SourceAta, &transfer.source) {
+            diagnosis.fail("source_ata_exists", VerificationError::SenderATANotFound);
+        } else if transfer.decimals.is_none() && accounts.token_account_mint(&transfer.source) != Some(transfer.mint) {
+            diagnosis.fail("source_mint", VerificationError::SourceMintMismatch);
+        }
+        if !has_create_ata && !accounts.exists(AccountKind::DestinationAta, &transfer.destination) {
+            diagnosis.fail("destination_ata_exists", VerificationError::ReceiverATANotFound);
+        }
+    }
+}
+
+/// Fee payer safety, letting the fee payer fund a CreateATA when it has a
+/// rent budget
+fn check_fee_payer_safety(config: &Config, transaction: &Transaction, fee_payer: &Pubkey) -> Result<(), VerificationError> {
+    if config.ata_rent_budget.enabled() {
+        verify_fee_payer_safety_funding_ata(transaction, fee_payer)
+    } else {
+        verify_fee_payer_safety(transaction, fee_payer)
+    }
+}
+
+/// Who funds the CreateATA instruction and, for the fee payer, whether
+/// today's rent budget still covers it
+fn check_ata_funding(config: &Config, transaction: &Transaction, fee_payer: &Pubkey) -> Result<(), VerificationError> {
+    let message = &transaction.message;
+    let budget = &config.ata_rent_budget;
+
+    let funder = verify_create_ata_funder(&message.instructions[2], message, fee_payer, budget.enabled())?;
+    if funder == AtaFunder::FeePayer {
+        let rent = sponsored_ata_rent(transaction, fee_payer).unwrap_or_default();
+        if budget.remaining() < rent {
+            return Err(VerificationError::AtaRentBudgetExhausted);
+        }
+    }
+
+    Ok(())
+}
+
+/// For requirements priced in USD, whether `amount` of the asset is worth
+/// the USD amount at the oracle's current price
+fn check_usd_amount(
+    config: &Config,
+    requirements: &PaymentRequirements,
+    amount: u64,
+    decimals: u8,
+) -> Result<(), VerificationError> {
+    let Some(usd) = UsdAmount::from_extra(&requirements.extra)? else {
+        return Ok(());
+    };
+    let price = config
+        .price_oracle
+        .usd_price(&requirements.asset)
+        .ok_or(VerificationError::UsdPriceUnavailable)?;
+    usd.check(amount, decimals, price)
+}
+
+/// What a checked transfer pays, as reported by `/verify`
+fn payment_details(
+    transaction: &Transaction,
+    transfer: &TransferAccounts,
+    fee_payer: &Pubkey,
+    has_create_ata: bool,
+) -> PaymentDetails {
+    PaymentDetails {
+        amount: transfer.amount.to_string(),
+        asset: transfer.mint.to_string(),
+        destination: transfer.destination.to_string(),
+        payer: transfer.authority.to_string(),
+        fee_payer: fee_payer.to_string(),
+        creates_ata: has_create_ata,
+        estimated_fee_lamports: transaction_fee_lamports(transaction),
+    }
+}
@@ -0,0 +1,343 @@
+//! Payment schemes the facilitator can verify
+//!
+//! Each scheme is a [`SchemeVerifier`] registered in the [`SchemeRegistry`]
+//! under its scheme name and network family. The handlers run the checks
+//! common to every payment (deduplication, expiry, mint allowlist) and hand
+//! the rest to the verifier the registry resolves, so a new scheme is a new
+//! module here plus one [`SchemeRegistry::with`] call.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::error::VerificationError;
+use crate::solana::verifier::PrefetchedAccounts;
+use crate::types::{
+    requests::{ExtraFields, VerifyRequest},
+    responses::{CheckFailure, PaymentDetails, SchemeSupport},
+};
+
+pub mod exact_svm;
+pub mod subscription_svm;
+
+pub use exact_svm::ExactSvmVerifier;
+pub use subscription_svm::SubscriptionSvmVerifier;
+
+/// Chain family of a network, e.g. `solana-devnet` is SVM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NetworkFamily {
+    Svm,
+    Evm,
+}
+
+/// EVM networks named by x402
+const EVM_NETWORKS: [&str; 9] = [
+    "base",
+    "base-sepolia",
+    "avalanche",
+    "avalanche-fuji",
+    "polygon",
+    "polygon-amoy",
+    "iotex",
+    "sei",
+    "sei-testnet",
+];
+
+impl NetworkFamily {
+    /// Family of `network` (`None` if unknown)
+    pub fn of(network: &str) -> Option<Self> {
+        if network == "solana" || network.starts_with("solana-") {
+            Some(Self::Svm)
+        } else if EVM_NETWORKS.contains(&network) {
+            Some(Self::Evm)
+        } else {
+            None
+        }
+    }
+}
+
+/// What a verifier may use besides the request
+pub struct VerifyContext<'a> {
+    pub config: &'a Config,
+    /// Accounts fetched ahead of a batch (SVM)
+    pub prefetched: Option<&'a PrefetchedAccounts>,
+}
+
+impl<'a> VerifyContext<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self { config, prefetched: None }
+    }
+
+    pub fn with_prefetched(mut self, prefetched: Option<&'a PrefetchedAccounts>) -> Self {
+        self.prefetched = prefetched;
+        self
+    }
+}
+
+/// A payment that passed its scheme's checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verified {
+    /// Wallet paying
+    pub payer: String,
+    /// Checks that didn't run (e.g. account lookups when verifying offline)
+    pub checks_skipped: Vec<String>,
+    /// What the payment transfers, for the response
+    pub details: Option<PaymentDetails>,
+}
+
+/// Outcome of running every check of a diagnostic verification
+#[derive(Debug, Default)]
+pub struct Diagnosis {
+    pub failures: Vec<CheckFailure>,
+    pub payer: Option<String>,
+    pub checks_skipped: Vec<String>,
+}
+
+impl Diagnosis {
+    pub fn fail(&mut self, check: &str, error: VerificationError) {
+        self.failures.push(CheckFailure::new(check, &error));
+    }
+
+    /// Record a failed `check`, passing through its value if it succeeded
+    pub fn check<T>(&mut self, check: &str, result: Result<T, VerificationError>) -> Option<T> {
+        result.map_err(|e| self.fail(check, e)).ok()
+    }
+}
+
+/// Verification of one payment scheme on one network family
+///
+/// Runs after the scheme-independent checks; blocking (it may look up
+/// accounts over RPC).
+pub trait SchemeVerifier: Send + Sync {
+    /// Scheme name, as in `PaymentRequirements::scheme`
+    fn scheme(&self) -> &'static str;
+
+    fn family(&self) -> NetworkFamily;
+
+    /// Networks of the family this verifier accepts, as listed by `/supported`
+    fn networks(&self) -> &[&'static str];
+
+    /// Check the scheme's own keys of the requirements' `extra` (e.g. a
+    /// nonce it requires); runs before [`Self::verify`]. Accepts any by default.
+    fn validate_extra(&self, _extra: &ExtraFields) -> Result<(), VerificationError> {
+        Ok(())
+    }
+
+    /// Check `request`, stopping at the first failure
+    fn verify(&self, ctx: &VerifyContext<'_>, request: &VerifyRequest) -> Result<Verified, VerificationError>;
+
+    /// Run every check of [`Self::verify`], recording each failure
+    ///
+    /// Checks that depend on an earlier one (e.g. the instructions on
+    /// decoding the transaction) are left out when it fails.
+    fn diagnose(&self, ctx: &VerifyContext<'_>, request: &VerifyRequest, diagnosis: &mut Diagnosis);
+
+    /// Claim what settling a verified `request` uses up (e.g. a
+    /// subscription's billing window), before it's submitted
+    fn begin_settlement(&self, _config: &Config, _request: &VerifyRequest, _payer: &str) -> Result<(), VerificationError> {
+        Ok(())
+    }
+
+    /// Record the outcome of a settlement [`Self::begin_settlement`] allowed
+    fn end_settlement(&self, _config: &Config, _request: &VerifyRequest, _payer: &str, _settled: bool) {}
+}
+
+/// Scheme and network family a verifier is registered under
+type VerifierKey = (&'static str, NetworkFamily);
+
+/// Scheme verifiers keyed by scheme and network family
+#[derive(Clone)]
+pub struct SchemeRegistry {
+    verifiers: Arc<BTreeMap<VerifierKey, Arc<dyn SchemeVerifier>>>,
+}
+
+impl std::fmt::Debug for SchemeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.verifiers.keys()).finish()
+    }
+}
+
+impl Default for SchemeRegistry {
+    /// The schemes this facilitator ships with
+    fn default() -> Self {
+        Self::empty().with(ExactSvmVerifier).with(SubscriptionSvmVerifier)
+    }
+}
+
+impl SchemeRegistry {
+    pub fn empty() -> Self {
+        Self { verifiers: Arc::new(BTreeMap::new()) }
+    }
+
+    /// Register `verifier`, replacing any for the same scheme and family
+    pub fn with(mut self, verifier: impl SchemeVerifier + 'static) -> Self {
+        Arc::make_mut(&mut self.verifiers).insert((verifier.scheme(), verifier.family()), Arc::new(verifier));
+        self
+    }
+
+    /// Verifier of `scheme` on `network`
+    pub fn get(&self, scheme: &str, network: &str) -> Option<&Arc<dyn SchemeVerifier>> {
+        let family = NetworkFamily::of(network)?;
+        self.verifiers
+            .iter()
+            .find(|((registered, registered_family), _)| *registered == scheme && *registered_family == family)
+            .map(|(_, verifier)| verifier)
+            .filter(|verifier| verifier.networks().contains(&network))
+    }
+
+    /// Whether `scheme` is registered on any network
+    pub fn has_scheme(&self, scheme: &str) -> bool {
+        self.verifiers.keys().any(|(registered, _)| *registered == scheme)
+    }
+
+    /// Whether any scheme is accepted on `network`
+    pub fn has_network(&self, network: &str) -> bool {
+        self.verifiers.values().any(|verifier| verifier.networks().contains(&network))
+    }
+
+    /// Verifier of a request whose payload and requirements agree on a
+    /// registered scheme and network
+    pub fn resolve(&self, request: &VerifyRequest) -> Result<&Arc<dyn SchemeVerifier>, VerificationError> {
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+
+        if payload.scheme != requirements.scheme || !self.has_scheme(&payload.scheme) {
+            return Err(VerificationError::UnsupportedScheme);
+        }
+        if payload.network != requirements.network {
+            return Err(VerificationError::InvalidNetwork);
+        }
+        self.get(&requirements.scheme, &requirements.network)
+            .ok_or(VerificationError::InvalidNetwork)
+    }
+
+    /// Schemes and their networks, for `/supported`
+    pub fn supported(&self) -> Vec<SchemeSupport> {
+        let mut schemes: Vec<SchemeSupport> = Vec::new();
+        for verifier in self.verifiers.values() {
+            let networks = verifier.networks().iter().map(|network| network.to_string());
+            match schemes.iter_mut().find(|support| support.scheme == verifier.scheme()) {
+                Some(support) => support.networks.extend(networks),
+                None => schemes.push(SchemeSupport {
+                    scheme: verifier.scheme().to_string(),
+                    networks: networks.collect(),
+                }),
+            }
+        }
+        schemes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::requests::{PaymentPayload, PaymentRequirements, SvmPayload};
+
+    /// Stand-in for a scheme on another family
+    struct UptoEvm;
+
+    impl SchemeVerifier for UptoEvm {
+        fn scheme(&self) -> &'static str {
+            "upto"
+        }
+
+        fn family(&self) -> NetworkFamily {
+            NetworkFamily::Evm
+        }
+
+        fn networks(&self) -> &[&'static str] {
+            &["base-sepolia"]
+        }
+
+        fn validate_extra(&self, extra: &ExtraFields) -> Result<(), VerificationError> {
+            extra.require_str("nonce").map(|_| ())
+        }
+
+        fn verify(&self, _ctx: &VerifyContext<'_>, _request: &VerifyRequest) -> Result<Verified, VerificationError> {
+            Ok(Verified { payer: "0xpayer".to_string(), checks_skipped: Vec::new(), details: None })
+        }
+
+        fn diagnose(&self, _ctx: &VerifyContext<'_>, _request: &VerifyRequest, _diagnosis: &mut Diagnosis) {}
+    }
+
+    fn request(scheme: &str, network: &str) -> VerifyRequest {
+        let requirements: PaymentRequirements = serde_json::from_value(serde_json::json!({
+            "scheme": scheme,
+            "network": network,
+            "maxAmountRequired": "1000",
+            "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "payTo": "11111111111111111111111111111111",
+            "resource": "/premium",
+            "description": "",
+            "mimeType": "application/json",
+            "maxTimeoutSeconds": 60,
+            "extra": { "feePayer": "11111111111111111111111111111111" }
+        }))
+        .unwrap();
+        VerifyRequest {
+            payment_payload: PaymentPayload {
+                x402_version: 1,
+                scheme: scheme.to_string(),
+                network: network.to_string(),
+                payload: SvmPayload { transaction: String::new() },
+                timestamp: None,
+            },
+            payment_requirements: requirements,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_network_family() {
+        assert_eq!(NetworkFamily::of("solana-devnet"), Some(NetworkFamily::Svm));
+        assert_eq!(NetworkFamily::of("base"), Some(NetworkFamily::Evm));
+        assert_eq!(NetworkFamily::of("bitcoin"), None);
+    }
+
+    #[test]
+    fn test_resolve_by_scheme_and_family() {
+        let registry = SchemeRegistry::default().with(UptoEvm);
+
+        assert_eq!(registry.resolve(&request("exact", "solana")).unwrap().scheme(), "exact");
+        assert_eq!(registry.resolve(&request("upto", "base-sepolia")).unwrap().scheme(), "upto");
+
+        // Known scheme, but not on this network or family
+        let error = |scheme: &str, network: &str| registry.resolve(&request(scheme, network)).err();
+        assert!(matches!(error("exact", "solana-testnet"), Some(VerificationError::InvalidNetwork)));
+        assert!(matches!(error("exact", "base"), Some(VerificationError::InvalidNetwork)));
+        assert!(matches!(error("subscription", "base"), Some(VerificationError::InvalidNetwork)));
+        assert!(matches!(error("deferred", "solana"), Some(VerificationError::UnsupportedScheme)));
+
+        let mut mismatched = request("exact", "solana");
+        mismatched.payment_payload.network = "solana-devnet".to_string();
+        assert!(matches!(registry.resolve(&mismatched).err(), Some(VerificationError::InvalidNetwork)));
+    }
+
+    #[test]
+    fn test_scheme_validates_extra() {
+        let registry = SchemeRegistry::default().with(UptoEvm);
+        let mut upto = request("upto", "base-sepolia");
+        let verifier = registry.resolve(&upto).unwrap();
+        assert!(matches!(
+            verifier.validate_extra(&upto.payment_requirements.extra),
+            Err(VerificationError::InvalidExtra(_))
+        ));
+
+        upto.payment_requirements.extra.other.insert("nonce".to_string(), serde_json::json!("7f3a"));
+        assert!(verifier.validate_extra(&upto.payment_requirements.extra).is_ok());
+
+        // Schemes without requirements accept any extra keys
+        let exact = request("exact", "solana");
+        assert!(registry.resolve(&exact).unwrap().validate_extra(&exact.payment_requirements.extra).is_ok());
+    }
+
+    #[test]
+    fn test_supported_lists_registered_schemes() {
+        let supported = SchemeRegistry::default().with(UptoEvm).supported();
+        assert_eq!(supported.len(), 3);
+        assert_eq!(supported[0].scheme, "exact");
+        assert_eq!(supported[0].networks, vec!["solana-devnet", "solana"]);
+        assert_eq!(supported[1].scheme, "subscription");
+        assert_eq!(supported[2].networks, vec!["base-sepolia"]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{Diagnosis, ExactSvmVerifier, NetworkFamily, SchemeVerifier, Verified, VerifyContext};
+use crate::config::Config;
+use crate::error::VerificationError;
+use crate::subscriptions::BillingWindow;
+use crate::types::requests::{ExtraFields, VerifyRequest};
+
+/// The `subscription` scheme on Solana: an `exact` payment, at most one per
+/// subscriber and resource in each billing window
+///
+/// Requirements name the period (`extra.periodSeconds`, counted from
+/// `extra.periodStart`, default the Unix epoch) and the subscribing wallet
+/// (`extra.subscriber`), which must be the payer. The payment must be made
+/// in the current window, and one already settled or settling in it is
+/// refused (see [`crate::subscriptions`]).
+pub struct SubscriptionSvmVerifier;
+
+/// Billing terms of subscription requirements
+struct Subscription<'a> {
+    subscriber: &'a str,
+    period_seconds: u64,
+    period_start: u64,
+}
+
+impl<'a> Subscription<'a> {
+    fn from_extra(extra: &'a ExtraFields) -> Result<Self, VerificationError> {
+        let subscriber = extra.require_str("subscriber")?;
+        let period_seconds = extra
+            .get("periodSeconds")
+            .and_then(|value| value.as_u64())
+            .filter(|seconds| *seconds > 0)
+            .ok_or_else(|| VerificationError::InvalidExtra("Set extra.periodSeconds to the billing period in seconds".to_string()))?;
+        let period_start = match extra.get("periodStart") {
+            None => 0,
+            Some(value) => value.as_u64().ok_or_else(|| {
+                VerificationError::InvalidExtra("Set extra.periodStart to a Unix timestamp".to_string())
+            })?,
+        };
+        Ok(Self { subscriber, period_seconds, period_start })
+    }
+
+    /// Billing window of `time`
+    fn window(&self, time: u64) -> Result<BillingWindow, VerificationError> {
+        BillingWindow::at(time, self.period_seconds, self.period_start).ok_or(VerificationError::OutsideBillingWindow)
+    }
+}
+
+impl SchemeVerifier for SubscriptionSvmVerifier {
+    fn scheme(&self) -> &'static str {
+        "subscription"
+    }
+
+    fn family(&self) -> NetworkFamily {
+        NetworkFamily::Svm
+    }
+
+    fn networks(&self) -> &[&'static str] {
+        &["solana-devnet", "solana"]
+    }
+
+    fn validate_extra(&self, extra: &ExtraFields) -> Result<(), VerificationError> {
+        ExactSvmVerifier.validate_extra(extra)?;
+        Subscription::from_extra(extra).map(|_| ())
+    }
+
+    fn verify(&self, ctx: &VerifyContext<'_>, request: &VerifyRequest) -> Result<Verified, VerificationError> {
+        let subscription = Subscription::from_extra(&request.payment_requirements.extra)?;
+        let verified = ExactSvmVerifier.verify(ctx, request)?;
+        check_billing_window(ctx.config, request, &subscription, &verified.payer)?;
+        Ok(verified)
+    }
+
+    fn diagnose(&self, ctx: &VerifyContext<'_>, request: &VerifyRequest, diagnosis: &mut Diagnosis) {
+        ExactSvmVerifier.diagnose(ctx, request, diagnosis);
+
+        // Malformed terms are reported by the `extra` check
+        let (Ok(subscription), Some(payer)) =
+            (Subscription::from_extra(&request.payment_requirements.extra), diagnosis.payer.clone())
+        else {
+            return;
+        };
+        diagnosis.check("billing_window", check_billing_window(ctx.config, request, &subscription, &payer));
+    }
+
+    fn begin_settlement(&self, config: &Config, request: &VerifyRequest, payer: &str) -> Result<(), VerificationError> {
+        let subscription = Subscription::from_extra(&request.payment_requirements.extra)?;
+        let window = subscription.window(now())?;
+        config.subscriptions.begin(payer, &request.payment_requirements.resource, &window)
+    }
+
+    fn end_settlement(&self, config: &Config, request: &VerifyRequest, payer: &str, settled: bool) {
+        config
+            .subscriptions
+            .finish(payer, &request.payment_requirements.resource, settled);
+    }
+}
+
+/// The subscriber pays, within the current billing window, which isn't
+/// paid yet
+fn check_billing_window(
+    config: &Config,
+    request: &VerifyRequest,
+    subscription: &Subscription<'_>,
+    payer: &str,
+) -> Result<(), VerificationError> {
+    if payer != subscription.subscriber {
+        return Err(VerificationError::SubscriberMismatch);
+    }
+    let window = subscription.window(now())?;
+    if request.payment_payload.timestamp.is_some_and(|timestamp| !window.contains(timestamp)) {
+        return Err(VerificationError::OutsideBillingWindow);
+    }
+    config.subscriptions.check(payer, &request.payment_requirements.resource, &window)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default()
+}
@@ -1,16 +1,161 @@
-use axum::{routing::{get, post}, Router, middleware, response::IntoResponse, Json};
-use tower_http::trace::TraceLayer;
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    http::HeaderValue,
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post},
+    Extension, Json, Router,
+};
+use tower_http::{
+    compression::CompressionLayer, decompression::RequestDecompressionLayer, trace::TraceLayer,
+};
 use utoipa::OpenApi;
-use crate::{config::Config, handlers, middleware::request_id::request_id_middleware, ApiDoc};
+use crate::{
+    config::Config,
+    handlers,
+    middleware::{
+        admin_auth::{admin_auth_middleware, require_admin_auth},
+        auth::jwt_auth_middleware,
+        body_limit::body_limit_middleware,
+        in_flight::in_flight_middleware,
+        msgpack::msgpack_middleware,
+        rate_limit::rate_limit_middleware,
+        request_id::request_id_middleware,
+        request_metrics::request_metrics_middleware,
+        response_signature::response_signature_middleware,
+        tenant::tenant_middleware,
+    },
+    ApiDoc,
+};
+
+/// Response header naming the API version that served a request
+pub const API_VERSION_HEADER: &str = "x-api-version";
+
+/// Versions of the payment API
+///
+/// Each version is served under its own prefix (`/v1/verify`, ...), and the
+/// unprefixed legacy routes alias [`ApiVersion::LEGACY`]. A breaking payload
+/// change (e.g. x402 v2) gets a new variant with its own handlers in
+/// [`versioned_routes`], leaving existing versions untouched. Handlers can
+/// extract `Extension<ApiVersion>` to tell which version they serve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    /// All served versions
+    pub const ALL: [ApiVersion; 1] = [ApiVersion::V1];
+
+    /// Version served by the unprefixed routes (`/verify`, `/settle`, ...)
+    pub const LEGACY: ApiVersion = ApiVersion::V1;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "v1",
+        }
+    }
+
+    /// Path prefix, e.g. `/v1`
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/v1",
+        }
+    }
+}
+
+/// Routes of one API version (without its prefix)
+fn versioned_routes(version: ApiVersion, config: &Config) -> Router<Config> {
+    // `X-Facilitator-Signature` on verify and settle results (when enabled),
+    // over the canonical JSON of the body, in either response format
+    let sign_responses = middleware::from_fn_with_state(config.clone(), response_signature_middleware);
+
+    let routes = match version {
+        // Payment endpoints (JWT-protected, tenant-scoped and rate limited
+        // when configured). Auth and the tenant lookup run first so limits
+        // can be keyed by the token subject or tenant; body
+        // size limits are enforced by our own middleware (with JSON errors)
+        // instead of axum's default, before MessagePack bodies are decoded.
+        ApiVersion::V1 => Router::new()
+            .route("/verify", post(handlers::verify::verify).route_layer(sign_responses.clone()))
+            .route("/verify/batch", post(handlers::batch::verify_batch).route_layer(sign_responses.clone()))
+            .route("/verify/stream", post(handlers::batch::verify_stream))
+            .route("/verify/header", post(handlers::verify::verify_header).route_layer(sign_responses.clone()))
+            .route("/validate", post(handlers::validate::validate))
+            .route("/settle", post(handlers::settle::settle).route_layer(sign_responses))
+            .route("/settle/:signature/status", get(handlers::settlements::settlement_status))
+            .route("/settlements", get(handlers::settlements::list_settlements))
+            .route("/settlements/:signature", get(handlers::settlements::get_settlement))
+            .route("/receipt/:signature", get(handlers::settlements::get_receipt))
+            .layer(DefaultBodyLimit::disable())
+            .route_layer(middleware::from_fn(msgpack_middleware))
+            .route_layer(middleware::from_fn_with_state(config.clone(), body_limit_middleware))
+            .route_layer(middleware::from_fn_with_state(config.clone(), rate_limit_middleware))
+            .route_layer(middleware::from_fn_with_state(config.clone(), tenant_middleware))
+            .route_layer(middleware::from_fn_with_state(config.clone(), jwt_auth_middleware))
+            // Public, added after the layers above so they don't apply
+            .route("/supported", get(handlers::supported::supported))
+            .route("/quote", get(handlers::quote::quote))
+            .route("/pubkey", get(handlers::pubkey::pubkey)),
+    };
+
+    routes
+        .layer(Extension(version))
+        .layer(middleware::map_response(move |mut response: Response| async move {
+            response
+                .headers_mut()
+                .insert(API_VERSION_HEADER, HeaderValue::from_static(version.as_str()));
+            response
+        }))
+}
 
 pub fn create_router(config: Config) -> Router {
+
+    // Admin endpoints and the live event feed (API key and/or IP allowlist
+    // when configured). Webhook receiver and dedup changes are refused
+    // outright without admin auth.
+    let admin_writes = middleware::from_fn_with_state(config.clone(), require_admin_auth);
+    let admin_routes = Router::new()
+        .route("/admin/health", get(handlers::admin::detailed_health))
+        .route("/admin/stats", get(handlers::admin::get_stats))
+        .route("/admin/config", get(handlers::admin::get_config))
+        .route("/admin/rpc", get(handlers::admin::rpc_health))
+        .route("/admin/reload", post(handlers::admin::reload_config))
+        .route("/admin/audit", get(handlers::admin::query_audit))
+        .route(
+            "/admin/webhooks",
+            get(handlers::admin::list_webhooks)
+                .merge(post(handlers::admin::create_webhook).route_layer(admin_writes.clone())),
+        )
+        .route(
+            "/admin/webhooks/:name",
+            patch(handlers::admin::update_webhook)
+                .delete(handlers::admin::delete_webhook)
+                .route_layer(admin_writes.clone()),
+        )
+        .route("/admin/webhooks/:name/test", post(handlers::admin::test_webhook))
+        .route("/admin/webhooks/dlq", get(handlers::admin::webhook_dead_letters))
+        .route("/admin/webhooks/status", get(handlers::admin::webhook_status))
+        .route("/admin/cache/invalidate", post(handlers::admin::invalidate_cache))
+        .route("/admin/cache/warm", post(handlers::admin::warm_cache))
+        .route("/admin/dedup", delete(handlers::admin::clear_dedup).route_layer(admin_writes.clone()))
+        .route("/admin/dedup/stats", get(handlers::admin::dedup_stats))
+        .route("/admin/dedup/:tx_hash", delete(handlers::admin::delete_dedup_entry).route_layer(admin_writes))
+        .route("/ws", get(handlers::ws::live_events))
+        .route_layer(middleware::from_fn_with_state(config.clone(), admin_auth_middleware));
+
+    // Versioned API (`/v1/...`), plus the unprefixed legacy aliases
+    let api_routes = ApiVersion::ALL
+        .into_iter()
+        .fold(Router::new(), |router, version| {
+            router.nest(version.prefix(), versioned_routes(version, &config))
+        })
+        .merge(versioned_routes(ApiVersion::LEGACY, &config));
+
     Router::new()
         // Core endpoints
         .route("/health", get(handlers::health::health_check))
-        .route("/supported", get(handlers::supported::supported))
-        .route("/verify", post(handlers::verify::verify))
-        .route("/verify/batch", post(handlers::batch::verify_batch))
-        .route("/settle", post(handlers::settle::settle))
+        .merge(api_routes)
         
         // Observability endpoints
         .route("/metrics", get(metrics_handler))
@@ -18,11 +163,19 @@ pub fn create_router(config: Config) -> Router {
         // API Documentation
         .route("/api-docs/openapi.json", get(openapi_json))
         
-        // Admin endpoints
-        .route("/admin/health", get(handlers::admin::detailed_health))
-        .route("/admin/stats", get(handlers::admin::get_stats))
-        .route("/admin/config", get(handlers::admin::get_config))
+        // Admin endpoints, and the dashboard calling them with the admin key
+        .merge(admin_routes)
+        .route("/admin/ui", get(handlers::admin_ui::index))
+        .route("/admin/ui/*path", get(handlers::admin_ui::asset_file))
         
+        // gzip/br responses when accepted; gzip/br request bodies are
+        // decompressed before the body size limits see them
+        .layer(CompressionLayer::new())
+        .layer(RequestDecompressionLayer::new())
+        .layer(middleware::from_fn_with_state(config.clone(), in_flight_middleware))
+        // Times the whole stack below (routing has run, so the route
+        // template is known)
+        .layer(middleware::from_fn_with_state(config.clone(), request_metrics_middleware))
         .layer(middleware::from_fn(request_id_middleware))
         .layer(TraceLayer::new_for_http())
         .with_state(config)
@@ -34,7 +187,12 @@ async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
 }
 
 /// GET /metrics - Prometheus metrics endpoint
-async fn metrics_handler() -> impl IntoResponse {
+async fn metrics_handler(State(config): State<Config>) -> impl IntoResponse {
+    // Gauges sampled from the caches at scrape time
+    config
+        .metrics
+        .update_cache_stats(&config.account_cache.stats(), &config.transaction_dedup.stats());
+
     match crate::metrics::create_prometheus_handle() {
         Ok(metrics) => metrics,
         Err(e) => format!("Error gathering metrics: {}", e),
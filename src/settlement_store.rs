@@ -0,0 +1,292 @@
+use anyhow::{Context, Result};
+use chrono::{TimeZone, Utc};
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::{QueryBuilder, Row};
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+use crate::settlements::{SettlementQuery, SettlementRecord};
+
+const SCHEMA: [&str; 5] = [
+    r#"CREATE TABLE IF NOT EXISTS settlements (
+        id TEXT PRIMARY KEY,
+        signature TEXT,
+        network TEXT NOT NULL,
+        payer TEXT,
+        recipient TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        mint TEXT NOT NULL,
+        slot INTEGER,
+        status TEXT NOT NULL,
+        error TEXT,
+        created_at INTEGER NOT NULL
+    )"#,
+    "CREATE UNIQUE INDEX IF NOT EXISTS settlements_signature_idx ON settlements (signature)",
+    "CREATE INDEX IF NOT EXISTS settlements_payer_idx ON settlements (payer, created_at)",
+    "CREATE INDEX IF NOT EXISTS settlements_recipient_idx ON settlements (recipient, created_at)",
+    "CREATE INDEX IF NOT EXISTS settlements_created_at_idx ON settlements (created_at)",
+];
+
+/// Adds the `tenant` column to tables created before it existed, then
+/// indexes it
+const TENANT_MIGRATION: [&str; 2] = [
+    "ALTER TABLE settlements ADD COLUMN tenant TEXT",
+    "CREATE INDEX IF NOT EXISTS settlements_tenant_idx ON settlements (tenant, created_at)",
+];
+
+const COLUMNS: &str = "id, signature, network, payer, recipient, amount, mint, slot, status, error, created_at, tenant";
+
+/// Settlement history database (SQLite)
+///
+/// Configuration:
+/// * `SETTLEMENT_DB_PATH` - SQLite file (enables the history)
+#[derive(Clone)]
+pub struct SettlementStore {
+    pool: SqlitePool,
+    schema: Arc<OnceCell<()>>,
+}
+
+impl std::fmt::Debug for SettlementStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SettlementStore").finish_non_exhaustive()
+    }
+}
+
+impl SettlementStore {
+    /// Open (creating if needed) the history database at `path`
+    ///
+    /// The file is opened lazily on first use.
+    pub fn open(path: &str) -> Self {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+
+        Self {
+            pool: SqlitePoolOptions::new().max_connections(4).connect_lazy_with(options),
+            schema: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Returns `None` when `SETTLEMENT_DB_PATH` is not set
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("SETTLEMENT_DB_PATH").ok().filter(|path| !path.is_empty())?;
+        tracing::info!("🧾 Settlement history at {}", path);
+        Some(Self::open(&path))
+    }
+
+    async fn ensure_schema(&self) -> Result<()> {
+        self.schema
+            .get_or_try_init(|| async {
+                for statement in SCHEMA {
+                    sqlx::query(statement)
+                        .execute(&self.pool)
+                        .await
+                        .context("Failed to create settlement history schema")?;
+                }
+
+                // SQLite has no ADD COLUMN IF NOT EXISTS, so probe for it instead
+                let has_tenant = sqlx::query("SELECT tenant FROM settlements LIMIT 0").execute(&self.pool).await.is_ok();
+                for statement in TENANT_MIGRATION.iter().skip(usize::from(has_tenant)) {
+                    sqlx::query(statement)
+                        .execute(&self.pool)
+                        .await
+                        .context("Failed to add the tenant column to settlements")?;
+                }
+                Ok::<_, anyhow::Error>(())
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Store a settlement (ignored if its signature is already recorded)
+    pub async fn insert(&self, record: &SettlementRecord) -> Result<()> {
+        self.ensure_schema().await?;
+
+        sqlx::query(&format!(
+            "INSERT INTO settlements ({}) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) \
+             ON CONFLICT DO NOTHING",
+            COLUMNS
+        ))
+        .bind(&record.id)
+        .bind(&record.signature)
+        .bind(&record.network)
+        .bind(&record.payer)
+        .bind(&record.recipient)
+        .bind(&record.amount)
+        .bind(&record.mint)
+        .bind(record.slot.map(|slot| slot as i64))
+        .bind(record.status.as_str())
+        .bind(&record.error)
+        .bind(record.created_at.timestamp_millis())
+        .bind(&record.tenant)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store settlement")?;
+
+        Ok(())
+    }
+
+    /// Settlements matching `query`, newest first
+    pub async fn query(&self, query: &SettlementQuery) -> Result<Vec<SettlementRecord>> {
+        self.ensure_schema().await?;
+
+        let mut sql = QueryBuilder::<Sqlite>::new(format!("SELECT {} FROM settlements WHERE 1 = 1", COLUMNS));
+        if let Some(tenant) = &query.tenant {
+            sql.push(" AND tenant = ").push_bind(tenant.clone());
+        }
+        if let Some(payer) = &query.payer {
+            sql.push(" AND payer = ").push_bind(payer.clone());
+        }
+        if let Some(recipient) = &query.recipient {
+            sql.push(" AND recipient = ").push_bind(recipient.clone());
+        }
+        if let Some(status) = &query.status {
+            sql.push(" AND status = ").push_bind(status.as_str());
+        }
+        if let Some(network) = &query.network {
+            sql.push(" AND network = ").push_bind(network.clone());
+        }
+        if let Some(from) = &query.from {
+            sql.push(" AND created_at >= ").push_bind(from.timestamp_millis());
+        }
+        if let Some(to) = &query.to {
+            sql.push(" AND created_at < ").push_bind(to.timestamp_millis());
+        }
+        sql.push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(i64::from(query.limit()))
+            .push(" OFFSET ")
+            .push_bind(query.offset() as i64);
+
+        let rows = sql
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to query settlements")?;
+
+        rows.iter().map(record_from_row).collect()
+    }
+
+    /// The settlement with transaction `signature`, if it belongs to
+    /// `tenant` (any settlement when `None`)
+    pub async fn get(&self, signature: &str, tenant: Option<&str>) -> Result<Option<SettlementRecord>> {
+        self.ensure_schema().await?;
+
+        let mut sql = QueryBuilder::<Sqlite>::new(format!("SELECT {} FROM settlements WHERE signature = ", COLUMNS));
+        sql.push_bind(signature);
+        if let Some(tenant) = tenant {
+            sql.push(" AND tenant = ").push_bind(tenant);
+        }
+
+        let row = sql
+            .build()
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to query settlements")?;
+
+        row.as_ref().map(record_from_row).transpose()
+    }
+}
+
+fn record_from_row(row: &SqliteRow) -> Result<SettlementRecord> {
+    let created_at: i64 = row.get("created_at");
+
+    Ok(SettlementRecord {
+        id: row.get("id"),
+        signature: row.get("signature"),
+        network: row.get("network"),
+        payer: row.get("payer"),
+        recipient: row.get("recipient"),
+        amount: row.get("amount"),
+        mint: row.get("mint"),
+        slot: row.get::<Option<i64>, _>("slot").map(|slot| slot as u64),
+        status: row.get::<String, _>("status").parse()?,
+        error: row.get("error"),
+        created_at: Utc
+            .timestamp_millis_opt(created_at)
+            .single()
+            .context("Invalid settlement timestamp")?,
+        tenant: row.get("tenant"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settlements::SettlementStatus;
+    use chrono::DateTime;
+
+    fn record(signature: Option<&str>, payer: &str, created_at: DateTime<Utc>) -> SettlementRecord {
+        SettlementRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            signature: signature.map(str::to_string),
+            network: "solana-devnet".to_string(),
+            payer: Some(payer.to_string()),
+            recipient: "merchant".to_string(),
+            amount: "1000000".to_string(),
+            mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+            slot: signature.map(|_| 42),
+            status: if signature.is_some() { SettlementStatus::Success } else { SettlementStatus::Failure },
+            error: None,
+            created_at,
+            tenant: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insert_query_and_get() {
+        let path = std::env::temp_dir().join(format!("settlements-{}.db", uuid::Uuid::new_v4()));
+        let store = SettlementStore::open(path.to_str().unwrap());
+
+        let early = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let late = Utc.with_ymd_and_hms(2024, 2, 1, 0, 0, 0).unwrap();
+        store.insert(&record(Some("sig-a"), "alice", early)).await.unwrap();
+        store.insert(&record(Some("sig-b"), "bob", late)).await.unwrap();
+        store.insert(&record(None, "alice", late)).await.unwrap();
+
+        // Duplicate signatures are ignored
+        store.insert(&record(Some("sig-a"), "alice", late)).await.unwrap();
+
+        let all = store.query(&SettlementQuery::default()).await.unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(all[0].created_at >= all[2].created_at);
+
+        let alice = store
+            .query(&SettlementQuery { payer: Some("alice".to_string()), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(alice.len(), 2);
+
+        let january = store
+            .query(&SettlementQuery { to: Some(late), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(january.len(), 1);
+        assert_eq!(january[0].signature.as_deref(), Some("sig-a"));
+
+        let failed = store
+            .query(&SettlementQuery { status: Some(SettlementStatus::Failure), ..Default::default() })
+            .await
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+
+        let found = store.get("sig-b", None).await.unwrap().unwrap();
+        assert_eq!(found.payer.as_deref(), Some("bob"));
+        assert_eq!(found.slot, Some(42));
+        assert!(store.get("missing", None).await.unwrap().is_none());
+
+        // Tenants only see their own settlements
+        let acme = SettlementRecord { tenant: Some("acme".to_string()), ..record(Some("sig-c"), "carol", late) };
+        store.insert(&acme).await.unwrap();
+        let tenant = |tenant: &str| SettlementQuery { tenant: Some(tenant.to_string()), ..Default::default() };
+        let listed = store.query(&tenant("acme")).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].tenant.as_deref(), Some("acme"));
+        assert!(store.query(&tenant("globex")).await.unwrap().is_empty());
+        assert!(store.get("sig-c", Some("acme")).await.unwrap().is_some());
+        assert!(store.get("sig-c", Some("globex")).await.unwrap().is_none());
+        assert!(store.get("sig-b", Some("acme")).await.unwrap().is_none());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
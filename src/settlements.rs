@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "settlement-history")]
+use crate::settlement_store::SettlementStore;
+
+/// Outcome of a settlement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementStatus {
+    Success,
+    Failure,
+}
+
+impl SettlementStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SettlementStatus::Success => "success",
+            SettlementStatus::Failure => "failure",
+        }
+    }
+}
+
+impl std::str::FromStr for SettlementStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "success" => Ok(SettlementStatus::Success),
+            "failure" => Ok(SettlementStatus::Failure),
+            other => anyhow::bail!("Unknown settlement status '{}'", other),
+        }
+    }
+}
+
+/// One settlement, as kept in the settlement history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementRecord {
+    pub id: String,
+    /// Transaction signature (absent when submission failed)
+    pub signature: Option<String>,
+    pub network: String,
+    pub payer: Option<String>,
+    /// `payTo` of the payment requirements
+    pub recipient: String,
+    /// Amount in the mint's base units
+    pub amount: String,
+    pub mint: String,
+    /// Slot the transaction landed in, when known
+    pub slot: Option<u64>,
+    pub status: SettlementStatus,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Tenant whose request it settled (`None` without tenants)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+/// Filters for `GET /settlements`
+///
+/// Deserializable from query strings, e.g.
+/// `?recipient=8Vzy...&from=2024-01-01T00:00:00Z&limit=50`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SettlementQuery {
+    pub payer: Option<String>,
+    pub recipient: Option<String>,
+    pub status: Option<SettlementStatus>,
+    pub network: Option<String>,
+    /// Inclusive lower bound on the settlement time
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on the settlement time
+    pub to: Option<DateTime<Utc>>,
+    /// Page size (default 100, at most 1000)
+    pub limit: Option<u32>,
+    /// Number of matching settlements to skip (newest first)
+    pub offset: Option<u64>,
+    /// Only settlements of this tenant; set from the caller's API key,
+    /// never from the query string
+    #[serde(skip)]
+    pub tenant: Option<String>,
+}
+
+impl SettlementQuery {
+    pub const DEFAULT_LIMIT: u32 = 100;
+    pub const MAX_LIMIT: u32 = 1000;
+
+    /// Page size, clamped to `1..=MAX_LIMIT`
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(Self::DEFAULT_LIMIT).clamp(1, Self::MAX_LIMIT)
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset.unwrap_or(0)
+    }
+}
+
+/// Records settlements to the history store, when one is configured
+///
+/// Without the `settlement-history` feature (or without
+/// `SETTLEMENT_DB_PATH`) recording is a no-op.
+#[derive(Clone, Debug, Default)]
+pub struct SettlementHistory {
+    #[cfg(feature = "settlement-history")]
+    store: Option<SettlementStore>,
+}
+
+impl SettlementHistory {
+    /// History without storage (recording is a no-op)
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "settlement-history")]
+    pub fn with_store(store: SettlementStore) -> Self {
+        Self { store: Some(store) }
+    }
+
+    /// Load the history store from environment (`SETTLEMENT_DB_PATH`)
+    pub fn from_env() -> Self {
+        #[cfg(feature = "settlement-history")]
+        if let Some(store) = SettlementStore::from_env() {
+            return Self::with_store(store);
+        }
+
+        Self::disabled()
+    }
+
+    /// The underlying store, for queries
+    #[cfg(feature = "settlement-history")]
+    pub fn store(&self) -> Option<&SettlementStore> {
+        self.store.as_ref()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        #[cfg(feature = "settlement-history")]
+        return self.store.is_some();
+
+        #[cfg(not(feature = "settlement-history"))]
+        false
+    }
+
+    /// Persist `record` in the background
+    ///
+    /// Failures are logged, never returned, so recording can't affect the
+    /// settlement response.
+    pub fn record(&self, record: SettlementRecord) {
+        #[cfg(feature = "settlement-history")]
+        if let Some(store) = self.store.clone() {
+            tokio::spawn(async move {
+                if let Err(e) = store.insert(&record).await {
+                    tracing::error!("❌ Failed to record settlement {}: {:#}", record.id, e);
+                }
+            });
+        }
+
+        #[cfg(not(feature = "settlement-history"))]
+        let _ = record;
+    }
+}
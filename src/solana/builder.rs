@@ -0,0 +1,263 @@
+use anyhow::{Context, Result};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use std::str::FromStr;
+
+use crate::solana::verifier::{AccountKind, AccountLookup};
+use crate::types::requests::PaymentRequirements;
+
+/// Compute units requested for a plain transfer
+pub const TRANSFER_COMPUTE_UNIT_LIMIT: u32 = 20_000;
+
+/// Compute units requested when the recipient's token account is created
+pub const CREATE_ATA_COMPUTE_UNIT_LIMIT: u32 = 60_000;
+
+/// Priority fee in micro-lamports per compute unit (the verifier allows up
+/// to 5,000,000)
+pub const DEFAULT_COMPUTE_UNIT_PRICE: u64 = 1;
+
+/// Inputs for [`build_payment_transaction`]
+#[derive(Debug, Clone)]
+pub struct PaymentTransaction<'a> {
+    pub requirements: &'a PaymentRequirements,
+    /// Wallet paying (owner of the source token account)
+    pub payer: Pubkey,
+    /// Decimals of the asset mint
+    pub decimals: u8,
+    /// Program owning the asset mint (SPL Token or Token-2022)
+    pub token_program: Pubkey,
+    /// Create the recipient's associated token account (paid by `payer`)
+    pub create_destination_ata: bool,
+    pub recent_blockhash: Hash,
+}
+
+/// Build the canonical x402 `exact` payment transaction
+///
+/// Instructions, in the order the verifier expects:
+/// 1. SetComputeUnitLimit
+/// 2. SetComputeUnitPrice
+/// 3. CreateAssociatedTokenAccountIdempotent for `payTo` (optional)
+/// 4. TransferChecked of `maxAmountRequired` to the `payTo` token account
+///
+/// The facilitator (`extra.feePayer`) is the fee payer and appears in no
+/// instruction. The transaction is unsigned: the payer's wallet signs it,
+/// the facilitator adds its signature at settlement.
+pub fn build_payment_transaction(params: &PaymentTransaction) -> Result<Transaction> {
+    let requirements = params.requirements;
+    let fee_payer = Pubkey::from_str(&requirements.extra.fee_payer).context("Invalid fee payer pubkey")?;
+    let pay_to = Pubkey::from_str(&requirements.pay_to).context("Invalid payTo pubkey")?;
+    let mint = Pubkey::from_str(&requirements.asset).context("Invalid asset pubkey")?;
+    let amount: u64 = requirements
+        .max_amount_required
+        .parse()
+        .context("Invalid maxAmountRequired")?;
+
+    let source = get_associated_token_address_with_program_id(&params.payer, &mint, &params.token_program);
+    let destination = get_associated_token_address_with_program_id(&pay_to, &mint, &params.token_program);
+
+    let compute_unit_limit = if params.create_destination_ata {
+        CREATE_ATA_COMPUTE_UNIT_LIMIT
+    } else {
+        TRANSFER_COMPUTE_UNIT_LIMIT
+    };
+
+    let mut instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit),
+        ComputeBudgetInstruction::set_compute_unit_price(DEFAULT_COMPUTE_UNIT_PRICE),
+    ];
+
+    if params.create_destination_ata {
+        instructions.push(create_associated_token_account_idempotent(
+            &params.payer,
+            &pay_to,
+            &mint,
+            &params.token_program,
+        ));
+    }
+
+    instructions.push(spl_token_2022::instruction::transfer_checked(
+        &params.token_program,
+        &source,
+        &mint,
+        &destination,
+        &params.payer,
+        &[],
+        amount,
+        params.decimals,
+    )?);
+
+    let message = Message::new_with_blockhash(&instructions, Some(&fee_payer), &params.recent_blockhash);
+    Ok(Transaction::new_unsigned(message))
+}
+
+/// Build the `exact` payment transaction for `requirements` server-side
+///
+/// The mint's decimals and token program come from the verifier's mint
+/// cache, and the `payTo` token account is created in the transaction when
+/// it doesn't exist yet, so the result passes `/verify` as long as `payer`
+/// holds enough of the asset. `payer` still has to sign it.
+pub fn build_exact_payment_transaction(
+    accounts: &AccountLookup,
+    requirements: &PaymentRequirements,
+    payer: &Pubkey,
+    recent_blockhash: Hash,
+) -> Result<Transaction> {
+    let mint = Pubkey::from_str(&requirements.asset).context("Invalid asset pubkey")?;
+    let pay_to = Pubkey::from_str(&requirements.pay_to).context("Invalid payTo pubkey")?;
+    let info = accounts.mint(&mint).context("Asset is not a token mint")?;
+
+    let destination = get_associated_token_address_with_program_id(&pay_to, &mint, &info.token_program);
+    let create_destination_ata = !accounts.exists(AccountKind::DestinationAta, &destination);
+
+    build_payment_transaction(&PaymentTransaction {
+        requirements,
+        payer: *payer,
+        decimals: info.decimals,
+        token_program: info.token_program,
+        create_destination_ata,
+        recent_blockhash,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solana::verifier::*;
+    use crate::types::requests::ExtraFields;
+
+    fn requirements(asset: &Pubkey, fee_payer: &Pubkey) -> PaymentRequirements {
+        PaymentRequirements {
+            scheme: "exact".to_string(),
+            network: "solana-devnet".to_string(),
+            max_amount_required: "1000000".to_string(),
+            asset: asset.to_string(),
+            pay_to: Pubkey::new_unique().to_string(),
+            resource: "/premium".to_string(),
+            description: "Premium".to_string(),
+            mime_type: "application/json".to_string(),
+            max_timeout_seconds: 60,
+            output_schema: None,
+            extra: ExtraFields::new(fee_payer.to_string()),
+        }
+    }
+
+    /// The verifier's instruction checks, as run by `/verify`
+    fn check(tx: &Transaction, requirements: &PaymentRequirements, fee_payer: &Pubkey) -> TransferAccounts {
+        let message = &tx.message;
+        let has_create_ata = verify_instruction_count(tx).unwrap();
+        verify_compute_limit_instruction(&message.instructions[0], message).unwrap();
+        verify_compute_price_instruction(&message.instructions[1], message).unwrap();
+        verify_fee_payer_position(tx, fee_payer).unwrap();
+        verify_fee_payer_safety(tx, fee_payer).unwrap();
+        if has_create_ata {
+            verify_create_ata_instruction(&message.instructions[2], message, requirements).unwrap();
+        }
+
+        let transfer_idx = if has_create_ata { 3 } else { 2 };
+        parse_transfer_instruction(&message.instructions[transfer_idx], message, requirements, fee_payer, false).unwrap()
+    }
+
+    #[test]
+    fn test_built_transactions_pass_verifier() {
+        let fee_payer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let requirements = requirements(&mint, &fee_payer);
+
+        for token_program in [spl_token_program_id(), spl_token_2022_program_id()] {
+            for create_destination_ata in [false, true] {
+                let tx = build_payment_transaction(&PaymentTransaction {
+                    requirements: &requirements,
+                    payer,
+                    decimals: 6,
+                    token_program,
+                    create_destination_ata,
+                    recent_blockhash: Hash::new_unique(),
+                })
+                .unwrap();
+
+                assert_eq!(tx.message.instructions.len(), if create_destination_ata { 4 } else { 3 });
+                // Fee payer first, the paying wallet second
+                assert_eq!(tx.message.account_keys[0], fee_payer);
+                assert_eq!(tx.message.account_keys[1], payer);
+
+                let transfer = check(&tx, &requirements, &fee_payer);
+                assert_eq!(transfer.mint, mint);
+                assert_eq!(transfer.decimals, Some(6));
+                assert_eq!(
+                    transfer.source,
+                    get_associated_token_address_with_program_id(&payer, &mint, &token_program)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_exact_payment_transaction() {
+        use crate::cache::{AccountCache, MintCache, MintInfo};
+        use crate::metrics::AppMetrics;
+        use crate::solana::rpc::MockRpc;
+
+        // Empty RPC: only cached accounts can be found
+        let rpc_client = MockRpc::new();
+        let cache = AccountCache::new(100, 30);
+        let mints = MintCache::new(10, 3600);
+        let metrics = AppMetrics::new();
+        let accounts = AccountLookup {
+            rpc_client: &rpc_client,
+            cache: &cache,
+            mints: &mints,
+            metrics: &metrics,
+            watcher: None,
+            prefetched: None,
+        };
+
+        let fee_payer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let unknown_mint = requirements(&Pubkey::new_unique(), &fee_payer);
+        let requirements = requirements(&mint, &fee_payer);
+        let token_program = spl_token_2022_program_id();
+        mints.store(mint, MintInfo { decimals: 9, token_program, extensions: vec![] });
+
+        // Recipient has no token account yet
+        let tx = build_exact_payment_transaction(&accounts, &requirements, &payer, Hash::new_unique()).unwrap();
+        assert_eq!(tx.message.instructions.len(), 4);
+        assert_eq!(check(&tx, &requirements, &fee_payer).decimals, Some(9));
+
+        let pay_to = Pubkey::from_str(&requirements.pay_to).unwrap();
+        let destination = get_associated_token_address_with_program_id(&pay_to, &mint, &token_program);
+        cache.store(destination, solana_sdk::account::Account::default());
+        let tx = build_exact_payment_transaction(&accounts, &requirements, &payer, Hash::new_unique()).unwrap();
+        assert_eq!(tx.message.instructions.len(), 3);
+        assert_eq!(check(&tx, &requirements, &fee_payer).destination, destination);
+
+        assert!(build_exact_payment_transaction(&accounts, &unknown_mint, &payer, Hash::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_requirements() {
+        let fee_payer = Pubkey::new_unique();
+        let mut requirements = requirements(&Pubkey::new_unique(), &fee_payer);
+        requirements.max_amount_required = "1.5".to_string();
+
+        let params = PaymentTransaction {
+            requirements: &requirements,
+            payer: Pubkey::new_unique(),
+            decimals: 6,
+            token_program: spl_token_program_id(),
+            create_destination_ata: false,
+            recent_blockhash: Hash::new_unique(),
+        };
+        assert!(build_payment_transaction(&params).is_err());
+    }
+}
@@ -1,16 +1,64 @@
 use anyhow::{anyhow, Result};
-use base64::{engine::general_purpose, Engine as _};
+use base64::{
+    alphabet,
+    engine::{general_purpose, DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine as _,
+};
 use solana_sdk::{
+    message::{Message, VersionedMessage},
     pubkey::Pubkey,
     signature::Signature,
-    transaction::Transaction,
+    transaction::{Transaction, VersionedTransaction},
 };
 
-/// Decode a base64-encoded transaction
+/// Base64url, with or without padding
+const URL_SAFE_ANY_PADDING: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Bytes of an encoded transaction, detecting the encoding
+///
+/// Accepts standard base64, base64url (as wallets commonly emit) and base58.
+/// A string can be valid in more than one of them, so the first decoding
+/// that parses as a transaction wins.
+pub fn decode_transaction_bytes(encoded: &str) -> Result<Vec<u8>> {
+    let encoded = encoded.trim();
+    let candidates: Vec<Vec<u8>> = [
+        general_purpose::STANDARD.decode(encoded).ok(),
+        URL_SAFE_ANY_PADDING.decode(encoded).ok(),
+        bs58::decode(encoded).into_vec().ok(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if candidates.is_empty() {
+        return Err(anyhow!("Transaction is not valid base64, base64url or base58"));
+    }
+
+    // Otherwise the first decoding, to report why it isn't a transaction
+    let index = candidates
+        .iter()
+        .position(|bytes| bincode::deserialize::<VersionedTransaction>(bytes).is_ok())
+        .unwrap_or(0);
+    Ok(candidates.into_iter().nth(index).unwrap_or_default())
+}
+
+/// Standard base64 of a transaction sent in another encoding (`None` if it
+/// already is standard base64 or isn't a transaction)
+///
+/// Payloads are normalized on arrival, so deduplication sees one encoding.
+pub fn normalize_transaction_encoding(encoded: &str) -> Option<String> {
+    let bytes = decode_transaction_bytes(encoded).ok()?;
+    bincode::deserialize::<VersionedTransaction>(&bytes).ok()?;
+    let normalized = general_purpose::STANDARD.encode(bytes);
+    (normalized != encoded).then_some(normalized)
+}
+
+/// Decode a transaction (base64, base64url or base58)
 pub fn decode_transaction_from_base64(encoded: &str) -> Result<Transaction> {
-    let bytes = general_purpose::STANDARD
-        .decode(encoded)
-        .map_err(|e| anyhow!("Failed to decode base64: {}", e))?;
+    let bytes = decode_transaction_bytes(encoded)?;
 
     let transaction: Transaction = bincode::deserialize(&bytes)
         .map_err(|e| anyhow!("Failed to deserialize transaction: {}", e))?;
@@ -18,6 +66,51 @@ pub fn decode_transaction_from_base64(encoded: &str) -> Result<Transaction> {
     Ok(transaction)
 }
 
+/// Decode a legacy or v0 transaction (base64, base64url or base58)
+pub fn decode_versioned_transaction_from_base64(encoded: &str) -> Result<VersionedTransaction> {
+    let bytes = decode_transaction_bytes(encoded)?;
+
+    let transaction: VersionedTransaction = bincode::deserialize(&bytes)
+        .map_err(|e| anyhow!("Failed to deserialize transaction: {}", e))?;
+
+    Ok(transaction)
+}
+
+/// Legacy view of a versioned transaction, for running the legacy checks
+///
+/// v0 messages convert only without address lookup tables, since resolving
+/// them needs RPC. The result is for inspection only: its message bytes
+/// differ from the original, so it must never be signed or submitted.
+pub fn to_legacy_transaction(tx: &VersionedTransaction) -> Result<Transaction> {
+    let message = match &tx.message {
+        VersionedMessage::Legacy(message) => message.clone(),
+        VersionedMessage::V0(message) => {
+            if !message.address_table_lookups.is_empty() {
+                return Err(anyhow!("Address lookup tables are not supported"));
+            }
+            Message {
+                header: message.header,
+                account_keys: message.account_keys.clone(),
+                recent_blockhash: message.recent_blockhash,
+                instructions: message.instructions.clone(),
+            }
+        }
+    };
+
+    Ok(Transaction {
+        signatures: tx.signatures.clone(),
+        message,
+    })
+}
+
+/// Encode a transaction as base64 (the `payload.transaction` format)
+pub fn encode_transaction_to_base64(transaction: &Transaction) -> Result<String> {
+    let bytes = bincode::serialize(transaction)
+        .map_err(|e| anyhow!("Failed to serialize transaction: {}", e))?;
+
+    Ok(general_purpose::STANDARD.encode(bytes))
+}
+
 /// Get the fee payer (first signer) from a transaction
 pub fn get_payer_from_transaction(tx: &Transaction) -> String {
     if let Some(first_key) = tx.message.account_keys.first() {
@@ -82,6 +175,31 @@ pub fn is_partially_signed(tx: &Transaction) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_transaction_encodings() {
+        use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        use solana_sdk::{hash::Hash, signature::Keypair, signer::Signer, system_instruction};
+
+        let payer = Keypair::new();
+        let message = Message::new(
+            &[system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1)],
+            Some(&payer.pubkey()),
+        );
+        let tx = Transaction::new(&[&payer], message, Hash::new_unique());
+        let bytes = bincode::serialize(&tx).unwrap();
+        let base64 = encode_transaction_to_base64(&tx).unwrap();
+
+        for encoded in [base64.clone(), URL_SAFE_NO_PAD.encode(&bytes), bs58::encode(&bytes).into_string()] {
+            assert_eq!(decode_transaction_from_base64(&encoded).unwrap(), tx);
+        }
+        assert_eq!(normalize_transaction_encoding(&base64), None);
+        assert_eq!(normalize_transaction_encoding(&bs58::encode(&bytes).into_string()), Some(base64));
+
+        let error = decode_transaction_from_base64("not a transaction!").unwrap_err();
+        assert!(error.to_string().contains("not valid base64, base64url or base58"));
+        assert!(decode_transaction_from_base64("AQID").unwrap_err().to_string().contains("deserialize"));
+    }
+
     #[test]
     fn test_get_payer_from_empty_tx() {
         use solana_sdk::message::Message;
@@ -90,4 +208,40 @@ mod tests {
         let payer = get_payer_from_transaction(&tx);
         assert_eq!(payer, "unknown");
     }
+
+    #[test]
+    fn test_v0_transaction_legacy_view() {
+        use solana_sdk::hash::Hash;
+        use solana_sdk::instruction::{AccountMeta, Instruction};
+        use solana_sdk::message::v0::{self, MessageAddressTableLookup};
+
+        let payer = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+        let v0 = v0::Message::try_compile(&payer, std::slice::from_ref(&instruction), &[], Hash::new_unique()).unwrap();
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(v0.clone()),
+        };
+
+        let encoded = general_purpose::STANDARD.encode(bincode::serialize(&tx).unwrap());
+        let legacy = to_legacy_transaction(&decode_versioned_transaction_from_base64(&encoded).unwrap()).unwrap();
+        let expected = Message::new_with_blockhash(&[instruction], Some(&payer), &v0.recent_blockhash);
+        assert_eq!(legacy.message, expected);
+
+        let mut with_lookup = v0;
+        with_lookup.address_table_lookups.push(MessageAddressTableLookup {
+            account_key: Pubkey::new_unique(),
+            writable_indexes: vec![0],
+            readonly_indexes: vec![],
+        });
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(with_lookup),
+        };
+        assert!(to_legacy_transaction(&tx).is_err());
+    }
 }
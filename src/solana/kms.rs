@@ -0,0 +1,416 @@
+// Remote fee payer signers backed by cloud key management services
+// The private key never leaves the KMS; we only send message bytes to sign.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use super::signer::FeePayerSigner;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Timeout for a single KMS signing request
+const KMS_REQUEST_TIMEOUT_SECONDS: u64 = 10;
+
+/// Parse a 64-byte Ed25519 signature returned by a KMS
+fn signature_from_base64(encoded: &str) -> Result<Signature> {
+    let bytes = general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| anyhow!("KMS returned invalid base64 signature: {}", e))?;
+
+    Signature::try_from(bytes.as_slice())
+        .map_err(|_| anyhow!("KMS returned a {}-byte signature, expected 64", bytes.len()))
+}
+
+/// Reject a KMS signature that doesn't verify against the configured fee payer
+///
+/// A KMS key that doesn't match `FEE_PAYER_PUBKEY` would otherwise produce
+/// transactions the cluster rejects with an opaque signature error.
+fn verify_signature(signature: Signature, pubkey: &Pubkey, message: &[u8], key_setting: &str) -> Result<Signature> {
+    if !signature.verify(pubkey.as_ref(), message) {
+        return Err(anyhow!(
+            "KMS signature doesn't verify against fee payer {}; check {} and FEE_PAYER_PUBKEY",
+            pubkey,
+            key_setting
+        ));
+    }
+    Ok(signature)
+}
+
+fn http_client() -> Result<Client> {
+    Client::builder()
+        .timeout(Duration::from_secs(KMS_REQUEST_TIMEOUT_SECONDS))
+        .build()
+        .map_err(|e| anyhow!("Failed to build KMS HTTP client: {}", e))
+}
+
+/// AWS credentials used for SigV4 request signing
+#[derive(Clone)]
+struct AwsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+/// Fee payer signer using an Ed25519 key stored in AWS KMS
+///
+/// Configuration:
+/// * `AWS_KMS_KEY_ID` - key ID or ARN (key spec `ECC_NIST_EDWARDS25519`)
+/// * `AWS_REGION` - region of the key (default: us-east-1)
+/// * `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN`
+/// * `AWS_KMS_ENDPOINT` - optional endpoint override (e.g. LocalStack)
+pub struct AwsKmsSigner {
+    client: Client,
+    key_id: String,
+    region: String,
+    endpoint: String,
+    credentials: AwsCredentials,
+    pubkey: Pubkey,
+}
+
+impl AwsKmsSigner {
+    /// Create an AWS KMS signer from environment variables
+    pub fn from_env(pubkey: Pubkey) -> Result<Self> {
+        let key_id = std::env::var("AWS_KMS_KEY_ID")
+            .map_err(|_| anyhow!("AWS_KMS_KEY_ID must be set for the aws-kms signer"))?;
+        let region = std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string());
+        let endpoint = std::env::var("AWS_KMS_ENDPOINT")
+            .unwrap_or_else(|_| format!("https://kms.{}.amazonaws.com", region));
+
+        let credentials = AwsCredentials {
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID")
+                .map_err(|_| anyhow!("AWS_ACCESS_KEY_ID must be set for the aws-kms signer"))?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY")
+                .map_err(|_| anyhow!("AWS_SECRET_ACCESS_KEY must be set for the aws-kms signer"))?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        };
+
+        Ok(Self {
+            client: http_client()?,
+            key_id,
+            region,
+            endpoint,
+            credentials,
+            pubkey,
+        })
+    }
+
+    /// Build SigV4 headers for a KMS JSON request
+    fn sign_request(&self, host: &str, target: &str, body: &str, now: chrono::DateTime<chrono::Utc>) -> Vec<(String, String)> {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let content_type = "application/x-amz-json-1.1";
+
+        // Canonical headers must be lowercase and sorted by name
+        let mut headers = vec![
+            ("content-type".to_string(), content_type.to_string()),
+            ("host".to_string(), host.to_string()),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        headers.push(("x-amz-target".to_string(), target.to_string()));
+
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(k, v)| format!("{}:{}\n", k, v.trim()))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(k, _)| k.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+
+        let canonical_request = format!(
+            "POST\n/\n\n{}\n{}\n{}",
+            canonical_headers,
+            signed_headers,
+            hex::encode(Sha256::digest(body.as_bytes()))
+        );
+
+        let scope = format!("{}/{}/kms/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let secret = format!("AWS4{}", self.credentials.secret_access_key);
+        let k_date = hmac_sha256(secret.as_bytes(), date.as_bytes());
+        let k_region = hmac_sha256(&k_date, self.region.as_bytes());
+        let k_service = hmac_sha256(&k_region, b"kms");
+        let k_signing = hmac_sha256(&k_service, b"aws4_request");
+        let signature = hex::encode(hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.credentials.access_key_id, scope, signed_headers, signature
+        );
+
+        let mut result: Vec<(String, String)> = headers
+            .into_iter()
+            .filter(|(k, _)| k != "host")
+            .collect();
+        result.push(("authorization".to_string(), authorization));
+        result
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[async_trait]
+impl FeePayerSigner for AwsKmsSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let body = json!({
+            "KeyId": self.key_id,
+            "Message": general_purpose::STANDARD.encode(message),
+            "MessageType": "RAW",
+            "SigningAlgorithm": "ED25519_SHA_512",
+        })
+        .to_string();
+
+        let host = self
+            .endpoint
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/')
+            .to_string();
+
+        let mut request = self.client.post(&self.endpoint).body(body.clone());
+        for (name, value) in self.sign_request(&host, "TrentService.Sign", &body, chrono::Utc::now()) {
+            request = request.header(name, value);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| anyhow!("AWS KMS request failed: {}", e))?;
+
+        let status = response.status();
+        let payload: Value = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("AWS KMS returned invalid JSON: {}", e))?;
+
+        if !status.is_success() {
+            return Err(anyhow!("AWS KMS Sign failed (HTTP {}): {}", status, payload));
+        }
+
+        let encoded = payload["Signature"]
+            .as_str()
+            .ok_or_else(|| anyhow!("AWS KMS response missing Signature"))?;
+
+        verify_signature(signature_from_base64(encoded)?, &self.pubkey, message, "AWS_KMS_KEY_ID")
+    }
+
+    fn backend(&self) -> &'static str {
+        "aws-kms"
+    }
+}
+
+/// Fee payer signer using an Ed25519 key version in Google Cloud KMS
+///
+/// Configuration:
+/// * `GCP_KMS_KEY_VERSION` - full key version resource name
+///   (`projects/.../locations/.../keyRings/.../cryptoKeys/.../cryptoKeyVersions/N`)
+/// * `GCP_ACCESS_TOKEN` - optional static OAuth token; otherwise a token is
+///   fetched from the GCE/GKE metadata server and refreshed before expiry
+/// * `GCP_KMS_ENDPOINT` - optional endpoint override
+pub struct GcpKmsSigner {
+    client: Client,
+    key_version: String,
+    endpoint: String,
+    static_token: Option<String>,
+    cached_token: Mutex<Option<(String, Instant)>>,
+    pubkey: Pubkey,
+}
+
+/// Metadata server endpoint for the default service account token
+const GCP_METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+
+impl GcpKmsSigner {
+    /// Create a Google Cloud KMS signer from environment variables
+    pub fn from_env(pubkey: Pubkey) -> Result<Self> {
+        let key_version = std::env::var("GCP_KMS_KEY_VERSION")
+            .map_err(|_| anyhow!("GCP_KMS_KEY_VERSION must be set for the gcp-kms signer"))?;
+
+        Ok(Self {
+            client: http_client()?,
+            key_version,
+            endpoint: std::env::var("GCP_KMS_ENDPOINT")
+                .unwrap_or_else(|_| "https://cloudkms.googleapis.com".to_string()),
+            static_token: std::env::var("GCP_ACCESS_TOKEN").ok(),
+            cached_token: Mutex::new(None),
+            pubkey,
+        })
+    }
+
+    /// Get an OAuth access token, refreshing from the metadata server when needed
+    async fn access_token(&self) -> Result<String> {
+        if let Some(token) = &self.static_token {
+            return Ok(token.clone());
+        }
+
+        let mut cached = self.cached_token.lock().await;
+        if let Some((token, expires_at)) = cached.as_ref() {
+            if Instant::now() < *expires_at {
+                return Ok(token.clone());
+            }
+        }
+
+        let response: Value = self
+            .client
+            .get(GCP_METADATA_TOKEN_URL)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to fetch GCP access token: {}", e))?
+            .json()
+            .await
+            .map_err(|e| anyhow!("Invalid GCP token response: {}", e))?;
+
+        let token = response["access_token"]
+            .as_str()
+            .ok_or_else(|| anyhow!("GCP token response missing access_token"))?
+            .to_string();
+        let expires_in = response["expires_in"].as_u64().unwrap_or(300);
+
+        // Refresh a minute early to avoid using a token that expires mid-request
+        let expires_at = Instant::now() + Duration::from_secs(expires_in.saturating_sub(60));
+        *cached = Some((token.clone(), expires_at));
+
+        Ok(token)
+    }
+}
+
+#[async_trait]
+impl FeePayerSigner for GcpKmsSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        let token = self.access_token().await?;
+        let url = format!(
+            "{}/v1/{}:asymmetricSign",
+            self.endpoint.trim_end_matches('/'),
+            self.key_version
+        );
+
+        // Ed25519 keys sign the raw message via the `data` field
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(token)
+            .json(&json!({ "data": general_purpose::STANDARD.encode(message) }))
+            .send()
+            .await
+            .map_err(|e| anyhow!("GCP KMS request failed: {}", e))?;
+
+        let status = response.status();
+        let payload: Value = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("GCP KMS returned invalid JSON: {}", e))?;
+
+        if !status.is_success() {
+            return Err(anyhow!("GCP KMS asymmetricSign failed (HTTP {}): {}", status, payload));
+        }
+
+        let encoded = payload["signature"]
+            .as_str()
+            .ok_or_else(|| anyhow!("GCP KMS response missing signature"))?;
+
+        verify_signature(signature_from_base64(encoded)?, &self.pubkey, message, "GCP_KMS_KEY_VERSION")
+    }
+
+    fn backend(&self) -> &'static str {
+        "gcp-kms"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn test_aws_signer() -> AwsKmsSigner {
+        AwsKmsSigner {
+            client: http_client().unwrap(),
+            key_id: "alias/fee-payer".to_string(),
+            region: "us-east-1".to_string(),
+            endpoint: "https://kms.us-east-1.amazonaws.com".to_string(),
+            credentials: AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+                session_token: None,
+            },
+            pubkey: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_signature_from_base64() {
+        let encoded = general_purpose::STANDARD.encode([7u8; 64]);
+        let signature = signature_from_base64(&encoded).unwrap();
+        assert_eq!(signature.as_ref(), &[7u8; 64]);
+
+        // Wrong length is rejected
+        let short = general_purpose::STANDARD.encode([7u8; 32]);
+        assert!(signature_from_base64(&short).is_err());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let keypair = Keypair::new();
+        let message = b"fee payer message";
+        let signature = keypair.sign_message(message);
+
+        assert!(verify_signature(signature, &keypair.pubkey(), message, "AWS_KMS_KEY_ID").is_ok());
+
+        // A key that doesn't match FEE_PAYER_PUBKEY is rejected
+        let err = verify_signature(signature, &Pubkey::new_unique(), message, "AWS_KMS_KEY_ID").unwrap_err();
+        assert!(err.to_string().contains("AWS_KMS_KEY_ID"));
+
+        // So is a signature over different bytes
+        assert!(verify_signature(signature, &keypair.pubkey(), b"other", "AWS_KMS_KEY_ID").is_err());
+    }
+
+    #[test]
+    fn test_aws_sigv4_headers() {
+        let signer = test_aws_signer();
+        let now = chrono::Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        let headers = signer.sign_request("kms.us-east-1.amazonaws.com", "TrentService.Sign", "{}", now);
+        let auth = &headers.iter().find(|(k, _)| k == "authorization").unwrap().1;
+
+        assert!(auth.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240102/us-east-1/kms/aws4_request"
+        ));
+        assert!(auth.contains("SignedHeaders=content-type;host;x-amz-date;x-amz-target"));
+        assert!(headers.iter().any(|(k, v)| k == "x-amz-date" && v == "20240102T030405Z"));
+
+        // Deterministic for identical input
+        let again = signer.sign_request("kms.us-east-1.amazonaws.com", "TrentService.Sign", "{}", now);
+        assert_eq!(headers, again);
+    }
+}
@@ -1,6 +1,8 @@
+pub mod builder;
 pub mod client;
 pub mod decoder;
+pub mod kms;
+pub mod rpc;
 pub mod signer;
 pub mod submitter;
 pub mod verifier;
-
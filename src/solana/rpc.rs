@@ -0,0 +1,493 @@
+use anyhow::{anyhow, Result};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    clock::MAX_PROCESSING_AGE,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{self, Transaction},
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::metrics::AppMetrics;
+
+/// The Solana RPC calls made by verification, settlement and the admin API
+///
+/// Implemented by [`RpcClient`] and, for tests, by [`MockRpc`]. Calls are
+/// blocking, like [`RpcClient`]'s.
+pub trait SolanaRpc: Send + Sync {
+    /// `None` if the account doesn't exist
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>>;
+
+    /// Accounts in `pubkeys` order (at most 100 per call)
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>>;
+
+    fn send_transaction(&self, transaction: &Transaction) -> Result<Signature>;
+
+    /// Outcome of a processed transaction (`None` if not processed yet)
+    fn get_signature_status(&self, signature: &Signature) -> Result<Option<transaction::Result<()>>>;
+
+    /// Statuses from the recent status cache, in `signatures` order
+    fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>>;
+
+    /// Statuses searching the ledger history, in `signatures` order
+    fn get_signature_statuses_with_history(&self, signatures: &[Signature])
+        -> Result<Vec<Option<TransactionStatus>>>;
+
+    fn get_latest_blockhash(&self) -> Result<Hash>;
+
+    /// Latest blockhash, with the last block height at which transactions
+    /// using it are accepted
+    fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)>;
+
+    /// Whether transactions using `blockhash` are still accepted
+    fn is_blockhash_valid(&self, blockhash: &Hash) -> Result<bool>;
+
+    /// Current block height, at the client's commitment
+    fn get_block_height(&self) -> Result<u64>;
+
+    /// Estimated Unix time of the block produced in `slot`
+    fn get_block_time(&self, slot: u64) -> Result<i64>;
+
+    fn get_health(&self) -> Result<()>;
+
+    /// Current slot, at the client's commitment
+    fn get_slot(&self) -> Result<u64>;
+}
+
+impl SolanaRpc for RpcClient {
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.get_account_with_commitment(pubkey, self.commitment())?.value)
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(RpcClient::get_multiple_accounts(self, pubkeys)?)
+    }
+
+    fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        Ok(RpcClient::send_transaction(self, transaction)?)
+    }
+
+    fn get_signature_status(&self, signature: &Signature) -> Result<Option<transaction::Result<()>>> {
+        Ok(RpcClient::get_signature_status(self, signature)?)
+    }
+
+    fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>> {
+        Ok(RpcClient::get_signature_statuses(self, signatures)?.value)
+    }
+
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        Ok(RpcClient::get_signature_statuses_with_history(self, signatures)?.value)
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(RpcClient::get_latest_blockhash(self)?)
+    }
+
+    fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        Ok(self.get_latest_blockhash_with_commitment(self.commitment())?)
+    }
+
+    fn is_blockhash_valid(&self, blockhash: &Hash) -> Result<bool> {
+        Ok(RpcClient::is_blockhash_valid(self, blockhash, self.commitment())?)
+    }
+
+    fn get_block_height(&self) -> Result<u64> {
+        Ok(RpcClient::get_block_height(self)?)
+    }
+
+    fn get_block_time(&self, slot: u64) -> Result<i64> {
+        Ok(RpcClient::get_block_time(self, slot)?)
+    }
+
+    fn get_health(&self) -> Result<()> {
+        Ok(RpcClient::get_health(self)?)
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        Ok(RpcClient::get_slot(self)?)
+    }
+}
+
+/// [`SolanaRpc`] recording every call in [`AppMetrics`]
+///
+/// Counts calls (`x402_rpc_calls_total`) and their duration
+/// (`x402_rpc_duration_seconds`) by RPC method, and failures by method and
+/// [`error_type`] (`x402_rpc_errors_total`). The facilitator's shared RPC
+/// client is wrapped in one, so verification, settlement and the health
+/// monitor are all counted.
+pub struct InstrumentedRpc {
+    inner: Arc<dyn SolanaRpc>,
+    metrics: AppMetrics,
+}
+
+impl InstrumentedRpc {
+    pub fn new(inner: Arc<dyn SolanaRpc>, metrics: AppMetrics) -> Self {
+        Self { inner, metrics }
+    }
+
+    fn observe<T>(&self, method: &str, call: impl FnOnce() -> Result<T>) -> Result<T> {
+        let start = Instant::now();
+        let result = call();
+        self.metrics
+            .record_rpc_call(method, start.elapsed(), result.as_ref().err().map(error_type));
+        result
+    }
+}
+
+impl SolanaRpc for InstrumentedRpc {
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        self.observe("getAccountInfo", || self.inner.get_account(pubkey))
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.observe("getMultipleAccounts", || self.inner.get_multiple_accounts(pubkeys))
+    }
+
+    fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        self.observe("sendTransaction", || self.inner.send_transaction(transaction))
+    }
+
+    fn get_signature_status(&self, signature: &Signature) -> Result<Option<transaction::Result<()>>> {
+        self.observe("getSignatureStatuses", || self.inner.get_signature_status(signature))
+    }
+
+    fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>> {
+        self.observe("getSignatureStatuses", || self.inner.get_signature_statuses(signatures))
+    }
+
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        self.observe("getSignatureStatuses", || {
+            self.inner.get_signature_statuses_with_history(signatures)
+        })
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.observe("getLatestBlockhash", || self.inner.get_latest_blockhash())
+    }
+
+    fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        self.observe("getLatestBlockhash", || self.inner.get_latest_blockhash_with_expiry())
+    }
+
+    fn is_blockhash_valid(&self, blockhash: &Hash) -> Result<bool> {
+        self.observe("isBlockhashValid", || self.inner.is_blockhash_valid(blockhash))
+    }
+
+    fn get_block_height(&self) -> Result<u64> {
+        self.observe("getBlockHeight", || self.inner.get_block_height())
+    }
+
+    fn get_block_time(&self, slot: u64) -> Result<i64> {
+        self.observe("getBlockTime", || self.inner.get_block_time(slot))
+    }
+
+    fn get_health(&self) -> Result<()> {
+        self.observe("getHealth", || self.inner.get_health())
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        self.observe("getSlot", || self.inner.get_slot())
+    }
+}
+
+/// Latest blockhash and how many blocks old it is
+///
+/// A healthy node serves a blockhash a block or two old. Transactions built
+/// on one older than `MAX_PROCESSING_AGE` blocks are rejected, so a growing
+/// age means settlements are about to fail.
+pub fn latest_blockhash_age(rpc: &dyn SolanaRpc) -> Result<(Hash, u64)> {
+    let (blockhash, last_valid_block_height) = rpc.get_latest_blockhash_with_expiry()?;
+    let block_height = rpc.get_block_height()?;
+    let age = (block_height + MAX_PROCESSING_AGE as u64).saturating_sub(last_valid_block_height);
+    Ok((blockhash, age))
+}
+
+/// Class of an RPC failure, the `error_type` label of `x402_rpc_errors_total`
+///
+/// `timeout`, `http` and `io` are transport failures (the provider may be
+/// down), `rpc` an error returned by the node, `transaction` a rejected
+/// transaction; `other` covers the rest.
+pub fn error_type(error: &anyhow::Error) -> &'static str {
+    let Some(error) = error.downcast_ref::<ClientError>() else {
+        return "other";
+    };
+    match error.kind() {
+        ClientErrorKind::Reqwest(e) if e.is_timeout() => "timeout",
+        ClientErrorKind::Reqwest(_) => "http",
+        ClientErrorKind::Io(_) => "io",
+        ClientErrorKind::RpcError(_) => "rpc",
+        ClientErrorKind::SerdeJson(_) => "parse",
+        ClientErrorKind::TransactionError(_) => "transaction",
+        _ => "other",
+    }
+}
+
+/// In-memory [`SolanaRpc`] for tests
+///
+/// Serves the accounts it is given. Sent transactions are recorded and
+/// confirmed immediately (each in a new slot) unless sends are set to fail
+/// or their blockhash has expired.
+#[derive(Debug)]
+pub struct MockRpc {
+    state: Mutex<MockState>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    accounts: HashMap<Pubkey, Account>,
+    statuses: HashMap<Signature, TransactionStatus>,
+    sent: Vec<Transaction>,
+    send_error: Option<String>,
+    health_error: Option<String>,
+    blockhash: Hash,
+    blockhash_age: u64,
+    expired_blockhashes: HashSet<Hash>,
+    slot: u64,
+    /// Unix time of the slots transactions were processed in
+    block_times: HashMap<u64, i64>,
+}
+
+impl Default for MockRpc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockRpc {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(MockState {
+                accounts: HashMap::new(),
+                statuses: HashMap::new(),
+                sent: Vec::new(),
+                send_error: None,
+                health_error: None,
+                blockhash: Hash::new_unique(),
+                blockhash_age: 0,
+                expired_blockhashes: HashSet::new(),
+                slot: 1,
+                block_times: HashMap::new(),
+            }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_account(&self, pubkey: Pubkey, account: Account) {
+        self.state().accounts.insert(pubkey, account);
+    }
+
+    pub fn remove_account(&self, pubkey: &Pubkey) {
+        self.state().accounts.remove(pubkey);
+    }
+
+    /// Make `send_transaction` fail with `error` (`None` to succeed again)
+    pub fn fail_sends(&self, error: Option<&str>) {
+        self.state().send_error = error.map(str::to_string);
+    }
+
+    /// Make `get_health` fail with `error` (`None` to be healthy again)
+    pub fn fail_health(&self, error: Option<&str>) {
+        self.state().health_error = error.map(str::to_string);
+    }
+
+    /// Set the current slot (advanced by each processed transaction)
+    pub fn set_slot(&self, slot: u64) {
+        self.state().slot = slot;
+    }
+
+    /// Serve a latest blockhash `blocks` behind the block height, as a
+    /// lagging node would
+    pub fn set_blockhash_age(&self, blocks: u64) {
+        self.state().blockhash_age = blocks;
+    }
+
+    /// Refuse transactions using `blockhash` as `BlockhashNotFound`, serving
+    /// a new latest blockhash if it was the latest
+    pub fn expire_blockhash(&self, blockhash: Hash) {
+        let mut state = self.state();
+        state.expired_blockhashes.insert(blockhash);
+        if state.blockhash == blockhash {
+            state.blockhash = Hash::new_unique();
+        }
+    }
+
+    /// Record `signature` as processed, e.g. landed through another facilitator
+    pub fn set_signature_status(&self, signature: Signature, status: transaction::Result<()>) {
+        let mut state = self.state();
+        state.slot += 1;
+        let (slot, now) = (state.slot, chrono::Utc::now().timestamp());
+        state.block_times.insert(slot, now);
+        let status = TransactionStatus {
+            slot: state.slot,
+            confirmations: None,
+            err: status.clone().err(),
+            status,
+            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+        };
+        state.statuses.insert(signature, status);
+    }
+
+    /// Transactions sent so far, in order
+    pub fn sent_transactions(&self) -> Vec<Transaction> {
+        self.state().sent.clone()
+    }
+}
+
+impl SolanaRpc for MockRpc {
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.state().accounts.get(pubkey).cloned())
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let state = self.state();
+        Ok(pubkeys.iter().map(|pubkey| state.accounts.get(pubkey).cloned()).collect())
+    }
+
+    fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        if let Some(error) = self.state().send_error.clone() {
+            return Err(anyhow!(error));
+        }
+        if self.state().expired_blockhashes.contains(&transaction.message.recent_blockhash) {
+            return Err(anyhow!("Transaction simulation failed: Blockhash not found"));
+        }
+
+        let signature = transaction.signatures.first().copied().unwrap_or_default();
+        self.state().sent.push(transaction.clone());
+        self.set_signature_status(signature, Ok(()));
+        Ok(signature)
+    }
+
+    fn get_signature_status(&self, signature: &Signature) -> Result<Option<transaction::Result<()>>> {
+        Ok(self.state().statuses.get(signature).map(|status| status.status.clone()))
+    }
+
+    fn get_signature_statuses(&self, signatures: &[Signature]) -> Result<Vec<Option<TransactionStatus>>> {
+        let state = self.state();
+        Ok(signatures.iter().map(|signature| state.statuses.get(signature).cloned()).collect())
+    }
+
+    fn get_signature_statuses_with_history(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        self.get_signature_statuses(signatures)
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.state().blockhash)
+    }
+
+    /// The block height is the slot
+    fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        let state = self.state();
+        let expiry = (state.slot + MAX_PROCESSING_AGE as u64).saturating_sub(state.blockhash_age);
+        Ok((state.blockhash, expiry))
+    }
+
+    fn is_blockhash_valid(&self, blockhash: &Hash) -> Result<bool> {
+        Ok(!self.state().expired_blockhashes.contains(blockhash))
+    }
+
+    fn get_block_height(&self) -> Result<u64> {
+        Ok(self.state().slot)
+    }
+
+    /// Known for slots a transaction was processed in
+    fn get_block_time(&self, slot: u64) -> Result<i64> {
+        self.state()
+            .block_times
+            .get(&slot)
+            .copied()
+            .ok_or_else(|| anyhow!("Block not available for slot {}", slot))
+    }
+
+    fn get_health(&self) -> Result<()> {
+        match self.state().health_error.clone() {
+            Some(error) => Err(anyhow!(error)),
+            None => Ok(()),
+        }
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        Ok(self.state().slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{message::Message, signature::Keypair, signer::Signer, system_instruction};
+
+    #[test]
+    fn test_latest_blockhash_age() {
+        let rpc = MockRpc::new();
+        rpc.set_slot(1_000);
+        let (blockhash, age) = latest_blockhash_age(&rpc).unwrap();
+        assert_eq!(blockhash, rpc.get_latest_blockhash().unwrap());
+        assert_eq!(age, 0);
+
+        rpc.set_blockhash_age(40);
+        assert_eq!(latest_blockhash_age(&rpc).unwrap().1, 40);
+    }
+
+    #[test]
+    fn test_mock_rpc_accounts_and_sends() {
+        let rpc = MockRpc::new();
+        let pubkey = Pubkey::new_unique();
+        assert!(rpc.get_account(&pubkey).unwrap().is_none());
+
+        rpc.set_account(pubkey, Account { lamports: 42, ..Account::default() });
+        assert_eq!(rpc.get_account(&pubkey).unwrap().unwrap().lamports, 42);
+        let accounts = rpc.get_multiple_accounts(&[pubkey, Pubkey::new_unique()]).unwrap();
+        assert!(accounts[0].is_some() && accounts[1].is_none());
+
+        let payer = Keypair::new();
+        let message = Message::new(
+            &[system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1)],
+            Some(&payer.pubkey()),
+        );
+        let transaction = Transaction::new(&[&payer], message, rpc.get_latest_blockhash().unwrap());
+
+        rpc.fail_sends(Some("blockhash not found"));
+        assert!(rpc.send_transaction(&transaction).is_err());
+        assert!(rpc.sent_transactions().is_empty());
+
+        rpc.fail_sends(None);
+        let signature = rpc.send_transaction(&transaction).unwrap();
+        assert_eq!(rpc.get_signature_status(&signature).unwrap(), Some(Ok(())));
+        assert!(rpc.get_signature_statuses(&[signature]).unwrap()[0].is_some());
+        assert_eq!(rpc.sent_transactions().len(), 1);
+    }
+
+    #[test]
+    fn test_instrumented_rpc_counts_calls_and_errors() {
+        let mock = Arc::new(MockRpc::new());
+        let metrics = AppMetrics::new();
+        let rpc = InstrumentedRpc::new(mock.clone(), metrics.clone());
+        let calls = metrics.rpc_calls.with_label_values(&["getHealth"]).get();
+        let errors = metrics.rpc_errors.with_label_values(&["getHealth", "other"]).get();
+
+        rpc.get_health().unwrap();
+        mock.fail_health(Some("Node is unhealthy"));
+        assert!(rpc.get_health().is_err());
+
+        assert_eq!(metrics.rpc_calls.with_label_values(&["getHealth"]).get(), calls + 2);
+        assert_eq!(metrics.rpc_errors.with_label_values(&["getHealth", "other"]).get(), errors + 1);
+        assert_eq!(rpc.get_slot().unwrap(), mock.get_slot().unwrap());
+    }
+}
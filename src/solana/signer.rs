@@ -1,10 +1,273 @@
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use solana_sdk::{
-    signature::{Keypair, Signer as SolanaSigner},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer as SolanaSigner},
     transaction::Transaction,
 };
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use bs58;
 
+use super::kms::{AwsKmsSigner, GcpKmsSigner};
+use crate::audit::AuditLogger;
+
+/// Signing backend for the facilitator's fee payer key
+///
+/// Settlement only needs a public key and the ability to sign a message, so
+/// the key itself can live in a local keypair or stay inside a KMS/HSM.
+#[async_trait]
+pub trait FeePayerSigner: Send + Sync {
+    /// Public key of the fee payer
+    fn pubkey(&self) -> Pubkey;
+
+    /// Sign a serialized transaction message
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature>;
+
+    /// Short backend name for logs and diagnostics
+    fn backend(&self) -> &'static str;
+
+    /// Swap in a new keypair (only supported by in-process backends)
+    fn rotate(&self, _keypair: Keypair) -> Result<()> {
+        Err(anyhow!("{} signer does not support key rotation", self.backend()))
+    }
+}
+
+/// Signer backed by an in-process keypair
+pub struct LocalKeypairSigner {
+    keypair: RwLock<Arc<Keypair>>,
+}
+
+impl LocalKeypairSigner {
+    /// Create a signer from an existing keypair
+    pub fn new(keypair: Keypair) -> Self {
+        Self {
+            keypair: RwLock::new(Arc::new(keypair)),
+        }
+    }
+
+    fn keypair(&self) -> Arc<Keypair> {
+        self.keypair.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Create a signer from a base58-encoded private key
+    pub fn from_base58(private_key: &str) -> Result<Self> {
+        Ok(Self::new(load_keypair_from_base58(private_key)?))
+    }
+}
+
+#[async_trait]
+impl FeePayerSigner for LocalKeypairSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair().pubkey()
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.keypair().sign_message(message))
+    }
+
+    fn backend(&self) -> &'static str {
+        "local"
+    }
+
+    fn rotate(&self, keypair: Keypair) -> Result<()> {
+        *self.keypair.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(keypair);
+        Ok(())
+    }
+}
+
+/// Signer backed by a Solana CLI JSON keypair file that can be rotated
+///
+/// The file is re-read periodically; when its contents change the new
+/// keypair is swapped in atomically, so in-flight signing either uses the
+/// old or the new key, never a partially written one.
+pub struct FileKeypairSigner {
+    path: PathBuf,
+    current: RwLock<Arc<Keypair>>,
+    fingerprint: RwLock<[u8; 32]>,
+}
+
+impl FileKeypairSigner {
+    /// Load the signer from a Solana CLI JSON keypair file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let (keypair, fingerprint) = read_keypair_file_with_fingerprint(&path)?;
+
+        Ok(Self {
+            path,
+            current: RwLock::new(Arc::new(keypair)),
+            fingerprint: RwLock::new(fingerprint),
+        })
+    }
+
+    fn keypair(&self) -> Arc<Keypair> {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Re-read the keypair file and swap keys if it changed
+    ///
+    /// Returns the new public key when a rotation happened. A file that fails
+    /// to parse (e.g. mid-write) leaves the current key in place.
+    pub fn reload_if_changed(&self) -> Result<Option<Pubkey>> {
+        let (keypair, fingerprint) = read_keypair_file_with_fingerprint(&self.path)?;
+
+        if *self.fingerprint.read().unwrap_or_else(|e| e.into_inner()) == fingerprint {
+            return Ok(None);
+        }
+
+        let pubkey = keypair.pubkey();
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(keypair);
+        *self.fingerprint.write().unwrap_or_else(|e| e.into_inner()) = fingerprint;
+
+        Ok(Some(pubkey))
+    }
+
+    /// Spawn a background task that polls the keypair file for rotations
+    pub fn spawn_reload_watcher(self: &Arc<Self>, interval: Duration, audit_logger: AuditLogger) {
+        let signer = Arc::downgrade(self);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+
+                // Stop watching once the signer has been dropped
+                let Some(signer) = signer.upgrade() else { break };
+
+                match signer.reload_if_changed() {
+                    Ok(Some(pubkey)) => {
+                        tracing::info!("🔄 Fee payer keypair rotated: {}", pubkey);
+                        audit_logger.log_config_changed(
+                            "fee_payer_keypair",
+                            serde_json::json!({
+                                "path": signer.path.display().to_string(),
+                                "pubkey": pubkey.to_string(),
+                            }),
+                        );
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::warn!("⚠️  Failed to reload fee payer keypair, keeping current key: {}", e);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl FeePayerSigner for FileKeypairSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair().pubkey()
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(self.keypair().sign_message(message))
+    }
+
+    fn backend(&self) -> &'static str {
+        "file"
+    }
+}
+
+/// Read a JSON keypair file along with a digest of its contents
+fn read_keypair_file_with_fingerprint(path: &Path) -> Result<(Keypair, [u8; 32])> {
+    let contents = std::fs::read(path)
+        .map_err(|e| anyhow!("Failed to read keypair file {}: {}", path.display(), e))?;
+
+    let bytes: Vec<u8> = serde_json::from_slice(&contents)
+        .map_err(|e| anyhow!("Invalid keypair file {}: {}", path.display(), e))?;
+
+    let keypair = Keypair::from_bytes(&bytes)
+        .map_err(|e| anyhow!("Invalid keypair in {}: {}", path.display(), e))?;
+
+    Ok((keypair, Sha256::digest(&contents).into()))
+}
+
+/// Build the fee payer signer selected by `FEE_PAYER_SIGNER`
+///
+/// Supported backends:
+/// * `local` (default) - keypair file from `FEE_PAYER_KEYPAIR_PATH` (watched
+///   for rotation every `FEE_PAYER_KEYPAIR_RELOAD_SECONDS`), or a base58
+///   keypair from `FEE_PAYER_PRIVATE_KEY`
+/// * `aws-kms` - Ed25519 key in AWS KMS (`AWS_KMS_KEY_ID`)
+/// * `gcp-kms` - Ed25519 key version in Google Cloud KMS (`GCP_KMS_KEY_VERSION`)
+///
+/// Remote backends require `FEE_PAYER_PUBKEY` so the public key is known
+/// without a round trip to the key service.
+pub fn signer_from_env(audit_logger: &AuditLogger) -> Result<Arc<dyn FeePayerSigner>> {
+    let backend = std::env::var("FEE_PAYER_SIGNER").unwrap_or_else(|_| "local".to_string());
+
+    let signer: Arc<dyn FeePayerSigner> = match backend.as_str() {
+        "local" => match std::env::var("FEE_PAYER_KEYPAIR_PATH") {
+            Ok(path) => {
+                let reload_seconds = std::env::var("FEE_PAYER_KEYPAIR_RELOAD_SECONDS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(5);
+
+                let signer = Arc::new(FileKeypairSigner::from_file(&path)?);
+                signer.spawn_reload_watcher(Duration::from_secs(reload_seconds), audit_logger.clone());
+                signer
+            }
+            Err(_) => {
+                let private_key = std::env::var("FEE_PAYER_PRIVATE_KEY").map_err(|_| {
+                    anyhow!("FEE_PAYER_PRIVATE_KEY or FEE_PAYER_KEYPAIR_PATH must be set for the local signer")
+                })?;
+                Arc::new(LocalKeypairSigner::from_base58(&private_key)?)
+            }
+        },
+        "aws-kms" => Arc::new(AwsKmsSigner::from_env(fee_payer_pubkey_from_env()?)?),
+        "gcp-kms" => Arc::new(GcpKmsSigner::from_env(fee_payer_pubkey_from_env()?)?),
+        other => anyhow::bail!(
+            "Invalid FEE_PAYER_SIGNER: {} (must be one of: local, aws-kms, gcp-kms)",
+            other
+        ),
+    };
+
+    tracing::info!(
+        "🔑 Fee payer signer: {} ({})",
+        signer.backend(),
+        signer.pubkey()
+    );
+
+    Ok(signer)
+}
+
+/// Sign a probe message and check it verifies against the signer's public key
+///
+/// Run once at startup so a KMS key that is unreachable or doesn't match
+/// `FEE_PAYER_PUBKEY` fails fast instead of on the first settlement.
+pub async fn check_signer(signer: &dyn FeePayerSigner) -> Result<()> {
+    let message = b"x402 facilitator fee payer signer check";
+    let signature = signer
+        .sign_message(message)
+        .await
+        .map_err(|e| anyhow!("{} signer failed to sign: {}", signer.backend(), e))?;
+
+    if !signature.verify(signer.pubkey().as_ref(), message) {
+        return Err(anyhow!(
+            "{} signer produced a signature that doesn't verify against {}",
+            signer.backend(),
+            signer.pubkey()
+        ));
+    }
+    Ok(())
+}
+
+/// Read the fee payer public key for remote signers
+fn fee_payer_pubkey_from_env() -> Result<Pubkey> {
+    let pubkey = std::env::var("FEE_PAYER_PUBKEY")
+        .map_err(|_| anyhow!("FEE_PAYER_PUBKEY must be set when using a remote signer"))?;
+
+    Pubkey::from_str(&pubkey).map_err(|e| anyhow!("Invalid FEE_PAYER_PUBKEY: {}", e))
+}
+
 /// Load keypair from base58-encoded private key
 pub fn load_keypair_from_base58(private_key: &str) -> Result<Keypair> {
     let decoded = bs58::decode(private_key)
@@ -15,10 +278,10 @@ pub fn load_keypair_from_base58(private_key: &str) -> Result<Keypair> {
         .map_err(|e| anyhow!("Failed to create keypair from bytes: {}", e))
 }
 
-/// Sign a transaction with the fee payer keypair
-pub fn sign_transaction_as_fee_payer(
+/// Sign a transaction as the fee payer using the configured signer
+pub async fn sign_transaction_as_fee_payer(
     transaction: &mut Transaction,
-    fee_payer: &Keypair,
+    fee_payer: &dyn FeePayerSigner,
 ) -> Result<()> {
     // The fee payer should be the first signer
     // Client has already signed (second+ signers)
@@ -27,7 +290,7 @@ pub fn sign_transaction_as_fee_payer(
     let message = &transaction.message;
     
     // Sign the transaction
-    let signature = fee_payer.sign_message(message.serialize().as_slice());
+    let signature = fee_payer.sign_message(message.serialize().as_slice()).await?;
     
     // Set the fee payer signature (first position)
     if transaction.signatures.is_empty() {
@@ -51,12 +314,13 @@ pub fn is_transaction_fully_signed(transaction: &Transaction) -> bool {
     transaction.signatures
         .iter()
         .take(num_required)
-        .all(|sig| sig.as_ref() != &[0u8; 64])
+        .all(|sig| sig.as_ref() != [0u8; 64])
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use solana_sdk::{message::Message, system_instruction};
 
     #[test]
     fn test_load_keypair() {
@@ -69,5 +333,90 @@ mod tests {
         
         assert_eq!(keypair.pubkey(), loaded.pubkey());
     }
-}
 
+    fn write_keypair_json(path: &Path, keypair: &Keypair) {
+        std::fs::write(path, serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_file_signer_reload() {
+        let path = std::env::temp_dir().join(format!("x402-fee-payer-{}.json", uuid::Uuid::new_v4()));
+        let first = Keypair::new();
+        write_keypair_json(&path, &first);
+
+        let signer = FileKeypairSigner::from_file(&path).unwrap();
+        assert_eq!(signer.pubkey(), first.pubkey());
+        assert_eq!(signer.backend(), "file");
+
+        // Unchanged file is a no-op
+        assert_eq!(signer.reload_if_changed().unwrap(), None);
+
+        // Rotated file swaps the key
+        let second = Keypair::new();
+        write_keypair_json(&path, &second);
+        assert_eq!(signer.reload_if_changed().unwrap(), Some(second.pubkey()));
+        assert_eq!(signer.pubkey(), second.pubkey());
+
+        // A corrupt file keeps the current key
+        std::fs::write(&path, "[1, 2, 3").unwrap();
+        assert!(signer.reload_if_changed().is_err());
+        assert_eq!(signer.pubkey(), second.pubkey());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_local_signer_signs_fee_payer_slot() {
+        let fee_payer = Keypair::new();
+        let client = Keypair::new();
+        let instruction = system_instruction::transfer(&client.pubkey(), &Pubkey::new_unique(), 1);
+        let message = Message::new(&[instruction], Some(&fee_payer.pubkey()));
+        let mut tx = Transaction::new_unsigned(message);
+
+        let signer = LocalKeypairSigner::new(fee_payer.insecure_clone());
+        assert_eq!(signer.pubkey(), fee_payer.pubkey());
+        assert_eq!(signer.backend(), "local");
+
+        sign_transaction_as_fee_payer(&mut tx, &signer).await.unwrap();
+
+        let message_bytes = tx.message.serialize();
+        assert!(tx.signatures[0].verify(fee_payer.pubkey().as_ref(), &message_bytes));
+    }
+
+    struct MismatchedSigner(LocalKeypairSigner);
+
+    #[async_trait]
+    impl FeePayerSigner for MismatchedSigner {
+        fn pubkey(&self) -> Pubkey {
+            Pubkey::new_unique()
+        }
+
+        async fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+            self.0.sign_message(message).await
+        }
+
+        fn backend(&self) -> &'static str {
+            "mismatched"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_signer() {
+        assert!(check_signer(&LocalKeypairSigner::new(Keypair::new())).await.is_ok());
+
+        let err = check_signer(&MismatchedSigner(LocalKeypairSigner::new(Keypair::new())))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("doesn't verify"));
+    }
+
+    #[test]
+    fn test_local_signer_rotate() {
+        let signer = LocalKeypairSigner::new(Keypair::new());
+        let next = Keypair::new();
+        let next_pubkey = next.pubkey();
+
+        signer.rotate(next).unwrap();
+        assert_eq!(signer.pubkey(), next_pubkey);
+    }
+}
@@ -1,22 +1,65 @@
 use anyhow::{anyhow, Result};
-use solana_client::rpc_client::RpcClient;
+use solana_client::client_error::ClientError;
+use solana_transaction_status::TransactionStatus;
 use solana_sdk::{
+    hash::Hash,
     signature::Signature,
-    transaction::Transaction,
+    transaction::{self, Transaction, TransactionError},
 };
 use std::str::FromStr;
 use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use crate::solana::rpc::SolanaRpc;
+
+/// The transaction's blockhash expired before it landed
+///
+/// Resubmitting it can't succeed: the cluster refuses it from now on.
+#[derive(Debug, Error)]
+#[error("Blockhash {0} of the transaction has expired")]
+pub struct BlockhashExpired(pub Hash);
+
+/// Whether `error` is the cluster refusing a transaction's blockhash
+/// (`BlockhashNotFound`, from preflight or the transaction itself)
+pub fn is_blockhash_not_found(error: &anyhow::Error) -> bool {
+    let from_client = error
+        .downcast_ref::<ClientError>()
+        .and_then(|error| error.get_transaction_error())
+        .is_some_and(|error| error == TransactionError::BlockhashNotFound);
+    from_client || format!("{:#}", error).to_lowercase().contains("blockhash not found")
+}
+
+/// Whether the transaction uses a durable nonce instead of a recent
+/// blockhash (its first instruction advances a nonce account), so it
+/// doesn't expire
+pub fn uses_durable_nonce(tx: &Transaction) -> bool {
+    transaction::uses_durable_nonce(tx).is_some()
+}
+
+/// Whether a failed attempt failed for good because the transaction's
+/// blockhash expired
+///
+/// Checks the blockhash with the cluster when the error doesn't say so,
+/// e.g. after a confirmation timeout.
+fn blockhash_expired(rpc_client: &dyn SolanaRpc, transaction: &Transaction, error: &anyhow::Error) -> bool {
+    if uses_durable_nonce(transaction) {
+        return false;
+    }
+    is_blockhash_not_found(error)
+        || matches!(rpc_client.is_blockhash_valid(&transaction.message.recent_blockhash), Ok(false))
+}
 
 /// Submit a signed transaction and wait for confirmation
 pub async fn submit_and_confirm_transaction(
-    rpc_client: &RpcClient,
+    rpc_client: &dyn SolanaRpc,
     transaction: &Transaction,
     timeout_seconds: u64,
 ) -> Result<Signature> {
     // Send the transaction
-    let signature = rpc_client
-        .send_transaction(transaction)
-        .map_err(|e| anyhow!("Failed to send transaction: {}", e))?;
+    let signature = rpc_client.send_transaction(transaction).map_err(|e| {
+        tracing::error!(rpc_method = "sendTransaction", "RPC error sending transaction: {}", e);
+        anyhow!("Failed to send transaction: {}", e)
+    })?;
 
     tracing::info!("Transaction sent: {}", signature);
 
@@ -51,12 +94,32 @@ pub async fn submit_and_confirm_transaction(
     }
 }
 
+/// Outcome of one submission attempt, reported to the caller
+#[derive(Debug, Clone)]
+pub struct SubmissionAttempt {
+    /// 1-based attempt number
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// Signature of the confirmed transaction (on success)
+    pub signature: Option<Signature>,
+    /// RPC or confirmation error (on failure)
+    pub error: Option<String>,
+    /// Delay before the next attempt (`None` if this was the last one)
+    pub backoff: Option<Duration>,
+}
+
 /// Submit transaction with retries
+///
+/// `on_attempt` is called after every attempt, e.g. to audit RPC failures.
+/// Stops with [`BlockhashExpired`] once the transaction's blockhash has
+/// expired, rather than retrying a transaction the cluster won't take;
+/// transactions using a durable nonce are retried as usual.
 pub async fn submit_transaction_with_retries(
-    rpc_client: &RpcClient,
+    rpc_client: &dyn SolanaRpc,
     transaction: &Transaction,
     max_retries: u32,
     timeout_seconds: u64,
+    on_attempt: &mut (dyn FnMut(&SubmissionAttempt) + Send),
 ) -> Result<Signature> {
     let mut last_error = None;
 
@@ -64,14 +127,37 @@ pub async fn submit_transaction_with_retries(
         tracing::info!("Submission attempt {}/{}", attempt, max_retries);
 
         match submit_and_confirm_transaction(rpc_client, transaction, timeout_seconds).await {
-            Ok(signature) => return Ok(signature),
+            Ok(signature) => {
+                on_attempt(&SubmissionAttempt {
+                    attempt,
+                    max_attempts: max_retries,
+                    signature: Some(signature),
+                    error: None,
+                    backoff: None,
+                });
+                return Ok(signature);
+            }
             Err(e) => {
                 tracing::warn!("Attempt {} failed: {}", attempt, e);
+                let expired = blockhash_expired(rpc_client, transaction, &e);
+
+                // Exponential backoff before the next attempt
+                let backoff =
+                    (attempt < max_retries && !expired).then(|| Duration::from_secs(2u64.pow(attempt - 1)));
+                on_attempt(&SubmissionAttempt {
+                    attempt,
+                    max_attempts: max_retries,
+                    signature: None,
+                    error: Some(e.to_string()),
+                    backoff,
+                });
+                if expired {
+                    tracing::warn!("⏳ Blockhash {} expired; not retrying", transaction.message.recent_blockhash);
+                    return Err(BlockhashExpired(transaction.message.recent_blockhash).into());
+                }
                 last_error = Some(e);
-                
-                if attempt < max_retries {
-                    // Wait before retry (exponential backoff)
-                    let backoff = Duration::from_secs(2u64.pow(attempt - 1));
+
+                if let Some(backoff) = backoff {
                     tokio::time::sleep(backoff).await;
                 }
             }
@@ -81,6 +167,41 @@ pub async fn submit_transaction_with_retries(
     Err(last_error.unwrap_or_else(|| anyhow!("All retry attempts failed")))
 }
 
+/// Status of a confirmed transaction, with the slot it landed in (`None`
+/// if unknown or on RPC errors)
+pub fn confirmed_status(rpc_client: &dyn SolanaRpc, signature: &Signature) -> Option<TransactionStatus> {
+    match rpc_client.get_signature_statuses(&[*signature]) {
+        Ok(statuses) => statuses.into_iter().next().flatten(),
+        Err(e) => {
+            tracing::warn!(rpc_method = "getSignatureStatuses", "Failed to look up slot of {}: {}", signature, e);
+            None
+        }
+    }
+}
+
+/// Estimated Unix time of the block in `slot` (`None` if the node doesn't
+/// have it, e.g. not yet rooted on some providers)
+pub fn block_time(rpc_client: &dyn SolanaRpc, slot: u64) -> Option<i64> {
+    match rpc_client.get_block_time(slot) {
+        Ok(time) => Some(time),
+        Err(e) => {
+            tracing::debug!(rpc_method = "getBlockTime", "No block time for slot {}: {}", slot, e);
+            None
+        }
+    }
+}
+
+/// Status of a transaction, searching the ledger history beyond the
+/// recent status cache (`None` if the cluster doesn't know it)
+pub fn signature_status(rpc_client: &dyn SolanaRpc, signature: &Signature) -> Result<Option<TransactionStatus>> {
+    let statuses = rpc_client.get_signature_statuses_with_history(&[*signature]).map_err(|e| {
+        tracing::warn!(rpc_method = "getSignatureStatuses", "Failed to look up status of {}: {}", signature, e);
+        anyhow!("Failed to look up transaction status: {}", e)
+    })?;
+
+    Ok(statuses.into_iter().next().flatten())
+}
+
 /// Get transaction signature as string
 pub fn signature_to_string(signature: &Signature) -> String {
     signature.to_string()
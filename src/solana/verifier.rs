@@ -1,15 +1,20 @@
 use anyhow::Result;
-use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
+    account::Account,
     instruction::CompiledInstruction,
     message::Message,
     pubkey::Pubkey,
     transaction::Transaction,
 };
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::collections::HashMap;
 
-use crate::cache::AccountCache;
+use crate::account_watch::AccountWatcher;
+use crate::cache::{AccountCache, MintCache, MintInfo};
+use crate::config::Config;
 use crate::error::VerificationError;
+use crate::metrics::AppMetrics;
+use crate::solana::rpc::SolanaRpc;
 use crate::types::requests::PaymentRequirements;
 
 /// Verify that the transaction has the correct number of instructions (3 or 4)
@@ -53,6 +58,9 @@ pub fn verify_compute_limit_instruction(
     Ok(())
 }
 
+/// Highest compute unit price accepted, in micro-lamports (5 lamports)
+pub const MAX_COMPUTE_UNIT_PRICE: u64 = 5_000_000;
+
 /// Verify that the compute price instruction is valid and not too high
 pub fn verify_compute_price_instruction(
     instruction: &CompiledInstruction,
@@ -83,13 +91,86 @@ pub fn verify_compute_price_instruction(
 
     // Check max price: 5 lamports = 5_000_000 micro-lamports
     // This protects the facilitator from gas price abuse
-    if micro_lamports > 5_000_000 {
+    if micro_lamports > MAX_COMPUTE_UNIT_PRICE {
         return Err(VerificationError::ComputePriceTooHigh);
     }
 
     Ok(())
 }
 
+/// Base fee per signature
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Fee the fee payer pays for `tx`: the base fee of its signatures plus
+/// the priority fee its compute budget instructions set
+pub fn transaction_fee_lamports(tx: &Transaction) -> u64 {
+    let message = &tx.message;
+    let compute_budget_id = compute_budget_program_id();
+    let (mut unit_limit, mut micro_lamports) = (0u64, 0u64);
+
+    for instruction in &message.instructions {
+        if message.account_keys.get(instruction.program_id_index as usize) != Some(&compute_budget_id) {
+            continue;
+        }
+        match instruction.data.split_first() {
+            Some((2, limit)) => {
+                if let Some(limit) = limit.get(..4).and_then(|bytes| bytes.try_into().ok()) {
+                    unit_limit = u32::from_le_bytes(limit) as u64;
+                }
+            }
+            Some((3, price)) => {
+                if let Some(price) = price.get(..8).and_then(|bytes| bytes.try_into().ok()) {
+                    micro_lamports = u64::from_le_bytes(price);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    estimate_fee_lamports(message.header.num_required_signatures, unit_limit, micro_lamports)
+}
+
+/// Fee of a transaction with `signatures` signatures requesting
+/// `unit_limit` compute units at `micro_lamports` each
+pub fn estimate_fee_lamports(signatures: u8, unit_limit: u64, micro_lamports: u64) -> u64 {
+    let priority_fee = (unit_limit as u128 * micro_lamports as u128).div_ceil(1_000_000) as u64;
+    LAMPORTS_PER_SIGNATURE * signatures as u64 + priority_fee
+}
+
+/// Signatures an `exact` payment requires: the fee payer's and the payer's
+pub const DEFAULT_REQUIRED_SIGNERS: u8 = 2;
+
+/// Verify that the message header is consistent with the transaction: at
+/// least one signer, a writable fee payer, account counts within the keys
+/// and one signature slot per required signer
+pub fn verify_message_header(tx: &Transaction) -> Result<(), VerificationError> {
+    let header = &tx.message.header;
+    let keys = tx.message.account_keys.len();
+    let signers = header.num_required_signatures as usize;
+
+    let consistent = signers > 0
+        && signers <= keys
+        && (header.num_readonly_signed_accounts as usize) < signers
+        && (header.num_readonly_unsigned_accounts as usize) <= keys - signers
+        && tx.signatures.len() == signers;
+    if !consistent {
+        return Err(VerificationError::InvalidMessageHeader);
+    }
+
+    Ok(())
+}
+
+/// Verify that the transaction requires exactly `required` signatures
+///
+/// The facilitator must not co-sign alongside signers it doesn't know of.
+pub fn verify_signer_count(tx: &Transaction, required: u8) -> Result<(), VerificationError> {
+    if tx.message.header.num_required_signatures != required {
+        return Err(VerificationError::UnexpectedSigners);
+    }
+
+    Ok(())
+}
+
 /// Verify that the fee payer is not included in any instruction's accounts
 /// This is critical for security - prevents the facilitator from being tricked
 /// into transferring their own funds
@@ -97,11 +178,33 @@ pub fn verify_fee_payer_safety(
     tx: &Transaction,
     fee_payer: &Pubkey,
 ) -> Result<(), VerificationError> {
-    for instruction in &tx.message.instructions {
+    check_fee_payer_references(tx, fee_payer, false)
+}
+
+/// [`verify_fee_payer_safety`], except that the fee payer may fund a
+/// CreateATA instruction (what that costs is capped by the rent budget)
+pub fn verify_fee_payer_safety_funding_ata(
+    tx: &Transaction,
+    fee_payer: &Pubkey,
+) -> Result<(), VerificationError> {
+    check_fee_payer_references(tx, fee_payer, true)
+}
+
+fn check_fee_payer_references(
+    tx: &Transaction,
+    fee_payer: &Pubkey,
+    allow_ata_funding: bool,
+) -> Result<(), VerificationError> {
+    let message = &tx.message;
+    for instruction in &message.instructions {
+        let funds_ata = allow_ata_funding
+            && message.account_keys.get(instruction.program_id_index as usize)
+                == Some(&spl_associated_token_account::ID);
+
         // Check all account indices in this instruction
-        for account_index in &instruction.accounts {
-            let account = &tx.message.account_keys[*account_index as usize];
-            if account == fee_payer {
+        for (position, account_index) in instruction.accounts.iter().enumerate() {
+            let account = &message.account_keys[*account_index as usize];
+            if account == fee_payer && !(funds_ata && position == 0) {
                 return Err(VerificationError::FeePayerInInstructionAccounts);
             }
         }
@@ -110,6 +213,23 @@ pub fn verify_fee_payer_safety(
     Ok(())
 }
 
+/// Verify that the fee payer is the transaction's fee payer: the first
+/// account key, which the cluster charges as the first required signer
+///
+/// Otherwise the facilitator would sign a transaction someone else pays
+/// for, or be made to pay for one it doesn't sign as fee payer.
+pub fn verify_fee_payer_position(
+    tx: &Transaction,
+    fee_payer: &Pubkey,
+) -> Result<(), VerificationError> {
+    let message = &tx.message;
+    if message.header.num_required_signatures == 0 || message.account_keys.first() != Some(fee_payer) {
+        return Err(VerificationError::FeePayerNotFirstSigner);
+    }
+
+    Ok(())
+}
+
 /// Get SPL Token program ID
 pub fn spl_token_program_id() -> Pubkey {
     spl_token::ID
@@ -120,40 +240,160 @@ pub fn spl_token_2022_program_id() -> Pubkey {
     spl_token_2022::ID
 }
 
-/// Check if an account exists (with caching)
-pub async fn check_account_exists(
-    rpc_client: &RpcClient,
-    cache: &AccountCache,
-    pubkey: &Pubkey,
-) -> Result<bool, VerificationError> {
-    // Try cache first
-    if let Some(_account) = cache.get(pubkey).await {
-        tracing::debug!("✅ Cache HIT for account: {}", pubkey);
-        return Ok(true);
-    }
-    
-    tracing::debug!("❌ Cache MISS for account: {}, checking RPC", pubkey);
-    
-    // Fallback to RPC
-    match rpc_client.get_account(pubkey) {
-        Ok(account) => {
-            // Cache the result
-            cache.insert(*pubkey, account).await;
-            Ok(true)
+/// Accounts looked up during verification (the cache metrics label)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountKind {
+    SourceAta,
+    DestinationAta,
+    Mint,
+}
+
+impl AccountKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountKind::SourceAta => "source_ata",
+            AccountKind::DestinationAta => "destination_ata",
+            AccountKind::Mint => "mint",
         }
-        Err(_) => Ok(false),
     }
 }
 
-/// Verify transfer instruction
-pub fn verify_transfer_instruction(
+/// Accounts fetched ahead of verification (`None` for missing accounts)
+pub type PrefetchedAccounts = HashMap<Pubkey, Option<Account>>;
+
+/// Account lookups through the shared [`AccountCache`], recording cache
+/// hits and misses by [`AccountKind`]
+pub struct AccountLookup<'a> {
+    pub rpc_client: &'a dyn SolanaRpc,
+    pub cache: &'a AccountCache,
+    pub mints: &'a MintCache,
+    pub metrics: &'a AppMetrics,
+    /// Subscribes to accounts that are looked up often
+    pub watcher: Option<&'a AccountWatcher>,
+    /// Consulted on cache misses before RPC (batch verification fetches
+    /// the accounts of all its payments together)
+    pub prefetched: Option<&'a PrefetchedAccounts>,
+}
+
+impl<'a> AccountLookup<'a> {
+    pub fn from_config(config: &'a Config) -> Self {
+        Self {
+            rpc_client: config.rpc_client.as_ref(),
+            cache: &config.account_cache,
+            mints: &config.mint_cache,
+            metrics: &config.metrics,
+            watcher: config.account_watcher.as_ref(),
+            prefetched: None,
+        }
+    }
+
+    /// Serve cache misses from `prefetched` when it has the account
+    pub fn with_prefetched(mut self, prefetched: &'a PrefetchedAccounts) -> Self {
+        self.prefetched = Some(prefetched);
+        self
+    }
+
+    /// `pubkey` from the prefetched accounts, else over RPC
+    fn fetch(&self, pubkey: &Pubkey) -> Option<Account> {
+        if let Some(account) = self.prefetched.and_then(|prefetched| prefetched.get(pubkey)) {
+            return account.clone();
+        }
+        self.rpc_client.get_account(pubkey).ok()?
+    }
+
+    /// Check if an account exists (with caching)
+    ///
+    /// Only existing accounts are cached; missing ones are re-checked over RPC
+    /// (unless prefetched).
+    pub fn exists(&self, kind: AccountKind, pubkey: &Pubkey) -> bool {
+        self.account(kind, pubkey).is_some()
+    }
+
+    /// An account (with caching, like [`Self::exists`])
+    pub fn account(&self, kind: AccountKind, pubkey: &Pubkey) -> Option<Account> {
+        if let Some(watcher) = self.watcher {
+            watcher.observe(pubkey);
+        }
+
+        if let Some(account) = self.cache.lookup(pubkey) {
+            tracing::debug!("✅ Cache HIT for {} account: {}", kind.as_str(), pubkey);
+            self.metrics.record_cache_hit(kind.as_str());
+            return Some(account);
+        }
+
+        tracing::debug!("❌ Cache MISS for {} account: {}, checking RPC", kind.as_str(), pubkey);
+        self.metrics.record_cache_miss(kind.as_str());
+
+        let account = self.fetch(pubkey)?;
+        self.cache.store(*pubkey, account.clone());
+        Some(account)
+    }
+
+    /// Mint of a source token account (`None` if it isn't a token account)
+    pub fn token_account_mint(&self, pubkey: &Pubkey) -> Option<Pubkey> {
+        let account = self.account(AccountKind::SourceAta, pubkey)?;
+        if account.owner != spl_token_program_id() && account.owner != spl_token_2022_program_id() {
+            return None;
+        }
+        // Token accounts of both programs start with their mint
+        let mint: [u8; 32] = account.data.get(..32)?.try_into().ok()?;
+        Some(Pubkey::new_from_array(mint))
+    }
+
+    /// Decimals and extensions of a mint (`None` if it isn't a mint)
+    ///
+    /// Served from the long-lived [`MintCache`].
+    pub fn mint(&self, mint: &Pubkey) -> Option<MintInfo> {
+        let kind = AccountKind::Mint.as_str();
+        if let Some(info) = self.mints.lookup(mint) {
+            self.metrics.record_cache_hit(kind);
+            return Some(info);
+        }
+
+        tracing::debug!("❌ Cache MISS for mint: {}, checking RPC", mint);
+        self.metrics.record_cache_miss(kind);
+
+        let info = MintInfo::from_account(&self.fetch(mint)?).ok()?;
+        self.mints.store(*mint, info.clone());
+        Some(info)
+    }
+}
+
+/// Accounts and decimals of a transfer instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferAccounts {
+    pub source: Pubkey,
+    /// The `payTo` associated token account for the asset
+    pub destination: Pubkey,
+    pub mint: Pubkey,
+    /// Wallet signing the transfer (the payer)
+    pub authority: Pubkey,
+    /// Amount in the asset's base units
+    pub amount: u64,
+    /// Decimals the transfer claims for the mint (`None` for a plain
+    /// `Transfer`, whose asset is the source account's mint)
+    pub decimals: Option<u8>,
+}
+
+/// SPL Token `Transfer` instruction
+const TRANSFER_DISCRIMINATOR: u8 = 3;
+
+/// SPL Token `TransferChecked` instruction
+const TRANSFER_CHECKED_DISCRIMINATOR: u8 = 12;
+
+/// Check a transfer instruction against the requirements, without any
+/// account lookups
+///
+/// Shared by the server and the WASM verifier, which fetch the returned
+/// accounts in their own way. A plain SPL Token `Transfer` is only accepted
+/// with `allow_unchecked` (`ALLOW_UNCHECKED_TRANSFER`).
+pub fn parse_transfer_instruction(
     instruction: &CompiledInstruction,
     message: &Message,
     requirements: &PaymentRequirements,
     fee_payer: &Pubkey,
-    has_create_ata: bool,
-    rpc_client: &RpcClient,
-) -> Result<(), VerificationError> {
+    allow_unchecked: bool,
+) -> Result<TransferAccounts, VerificationError> {
     // Check if it's a token transfer instruction
     let program_id = &message.account_keys[instruction.program_id_index as usize];
     let token_program = spl_token_program_id();
@@ -163,11 +403,27 @@ pub fn verify_transfer_instruction(
         return Err(VerificationError::NotATransferInstruction);
     }
 
-    // Parse transfer instruction
-    // TransferChecked format: discriminator(1) + amount(8) + decimals(1)
-    if instruction.data.len() < 10 || instruction.data[0] != 12 {
-        return Err(VerificationError::NotATransferInstruction);
-    }
+    // Parse transfer instruction, with the positions of its
+    // [source, destination, authority] accounts
+    let (decimals, [source_pos, destination_pos, authority_pos]) = match instruction.data.first() {
+        // TransferChecked: discriminator(1) + amount(8) + decimals(1),
+        // accounts [source, mint, destination, authority, ...]
+        Some(&TRANSFER_CHECKED_DISCRIMINATOR) if instruction.data.len() >= 10 && instruction.accounts.len() >= 4 => {
+            (Some(instruction.data[9]), [0, 2, 3])
+        }
+        // Transfer (SPL Token only; Token-2022 mints may need the mint's
+        // extensions): discriminator(1) + amount(8),
+        // accounts [source, destination, authority, ...]
+        Some(&TRANSFER_DISCRIMINATOR)
+            if allow_unchecked
+                && program_id == &token_program
+                && instruction.data.len() >= 9
+                && instruction.accounts.len() >= 3 =>
+        {
+            (None, [0, 1, 2])
+        }
+        _ => return Err(VerificationError::NotATransferInstruction),
+    };
 
     // Get amount from instruction
     let amount_bytes: [u8; 8] = instruction.data[1..9]
@@ -186,14 +442,9 @@ pub fn verify_transfer_instruction(
     }
 
     // Get accounts from transfer instruction
-    // TransferChecked accounts: [source, mint, destination, authority, ...]
-    if instruction.accounts.len() < 4 {
-        return Err(VerificationError::NotATransferInstruction);
-    }
-
-    let source_idx = instruction.accounts[0] as usize;
-    let destination_idx = instruction.accounts[2] as usize;
-    let authority_idx = instruction.accounts[3] as usize;
+    let source_idx = instruction.accounts[source_pos] as usize;
+    let destination_idx = instruction.accounts[destination_pos] as usize;
+    let authority_idx = instruction.accounts[authority_pos] as usize;
 
     let source = &message.account_keys[source_idx];
     let destination = &message.account_keys[destination_idx];
@@ -204,6 +455,11 @@ pub fn verify_transfer_instruction(
         return Err(VerificationError::FeePayerTransferringFunds);
     }
 
+    // The authority is the payer, so it must sign
+    if !message.is_signer(authority_idx) {
+        return Err(VerificationError::TransferAuthorityNotSigner);
+    }
+
     // Calculate expected destination ATA
     let pay_to: Pubkey = requirements
         .pay_to
@@ -214,28 +470,119 @@ pub fn verify_transfer_instruction(
         .parse()
         .map_err(|_| VerificationError::TransferToIncorrectATA)?;
 
-    let expected_destination = get_associated_token_address(&pay_to, &asset);
+    // Token-2022 mints have their associated accounts under that program
+    let expected_destination = get_associated_token_address_with_program_id(&pay_to, &asset, program_id);
 
     // Verify destination is correct ATA
     if destination != &expected_destination {
         return Err(VerificationError::TransferToIncorrectATA);
     }
 
+    Ok(TransferAccounts {
+        source: *source,
+        destination: expected_destination,
+        mint: asset,
+        authority: *authority,
+        amount,
+        decimals,
+    })
+}
+
+/// Verify transfer instruction
+pub fn verify_transfer_instruction(
+    instruction: &CompiledInstruction,
+    message: &Message,
+    requirements: &PaymentRequirements,
+    fee_payer: &Pubkey,
+    has_create_ata: bool,
+    allow_unchecked: bool,
+    accounts: &AccountLookup,
+) -> Result<TransferAccounts, VerificationError> {
+    let transfer = parse_transfer_instruction(instruction, message, requirements, fee_payer, allow_unchecked)?;
+
     // Check account existence
+    // The asset mint must exist, with the decimals the transfer claims
+    let mint = accounts.mint(&transfer.mint).ok_or(VerificationError::MintNotFound)?;
+    if transfer.decimals.is_some_and(|decimals| decimals != mint.decimals) {
+        return Err(VerificationError::DecimalsMismatch);
+    }
+    if !mint.extensions.is_empty() {
+        tracing::debug!("Mint {} has extensions: {:?}", transfer.mint, mint.extensions);
+    }
+
     // Source ATA must exist
-    if rpc_client.get_account(source).is_err() {
+    if !accounts.exists(AccountKind::SourceAta, &transfer.source) {
         return Err(VerificationError::SenderATANotFound);
     }
 
+    // A plain Transfer names no mint: the source account must hold the asset
+    if transfer.decimals.is_none() && accounts.token_account_mint(&transfer.source) != Some(transfer.mint) {
+        return Err(VerificationError::SourceMintMismatch);
+    }
+
     // Destination ATA must exist if no CreateATA instruction
-    if !has_create_ata && rpc_client.get_account(&expected_destination).is_err() {
+    if !has_create_ata && !accounts.exists(AccountKind::DestinationAta, &transfer.destination) {
         return Err(VerificationError::ReceiverATANotFound);
     }
 
-    Ok(())
+    Ok(transfer)
 }
 
-/// Verify CreateATA instruction (if present)
+/// Wallet paying for a transaction: the signing authority of its last
+/// instruction, a TransferChecked (or Transfer)
+///
+/// Key order says nothing about who pays (a CreateATA funded by another
+/// wallet comes before the authority), so the payer is taken from the
+/// transfer itself. Checks nothing else of the transfer.
+pub fn transfer_authority(message: &Message) -> Result<Pubkey, VerificationError> {
+    let instruction = message.instructions.last().ok_or(VerificationError::NotATransferInstruction)?;
+    let program_id = message
+        .account_keys
+        .get(instruction.program_id_index as usize)
+        .ok_or(VerificationError::NotATransferInstruction)?;
+    if program_id != &spl_token_program_id() && program_id != &spl_token_2022_program_id() {
+        return Err(VerificationError::NotATransferInstruction);
+    }
+    let authority_pos = match instruction.data.first() {
+        Some(&TRANSFER_CHECKED_DISCRIMINATOR) => 3,
+        Some(&TRANSFER_DISCRIMINATOR) => 2,
+        _ => return Err(VerificationError::NotATransferInstruction),
+    };
+
+    let authority_idx = *instruction
+        .accounts
+        .get(authority_pos)
+        .ok_or(VerificationError::NotATransferInstruction)? as usize;
+    let authority = message
+        .account_keys
+        .get(authority_idx)
+        .ok_or(VerificationError::NotATransferInstruction)?;
+    if !message.is_signer(authority_idx) {
+        return Err(VerificationError::TransferAuthorityNotSigner);
+    }
+    Ok(*authority)
+}
+
+/// Checks of [`verify_transfer_instruction`] that need RPC, in the order they run
+///
+/// Reported as `checksSkipped` when verifying offline, where only
+/// [`parse_transfer_instruction`] runs. A plain `Transfer` (`checked` false)
+/// has its source account's mint checked instead of its decimals.
+pub fn account_checks(has_create_ata: bool, checked: bool) -> Vec<String> {
+    let mut checks = vec!["mint_exists"];
+    if checked {
+        checks.extend(["mint_decimals", "source_ata_exists"]);
+    } else {
+        checks.extend(["source_ata_exists", "source_mint"]);
+    }
+    if !has_create_ata {
+        checks.push("destination_ata_exists");
+    }
+    checks.into_iter().map(str::to_string).collect()
+}
+
+/// Verify CreateATA instruction (if present): `Create` or `CreateIdempotent`
+/// of payTo's associated token account for the asset
 pub fn verify_create_ata_instruction(
     instruction: &CompiledInstruction,
     message: &Message,
@@ -249,17 +596,37 @@ pub fn verify_create_ata_instruction(
         return Err(VerificationError::InvalidCreateATAInstruction);
     }
 
-    // CreateATA has no data (instruction discriminator is in program)
-    // Accounts: [payer, ata, owner, mint, system_program, token_program]
+    // Create (no data, or [0]) or CreateIdempotent ([1]); RecoverNested
+    // and any other instruction of the program are rejected
+    if !matches!(instruction.data.as_slice(), [] | [0] | [1]) {
+        return Err(VerificationError::InvalidCreateATAInstruction);
+    }
+
+    // Accounts: [funder, ata, owner, mint, system_program, token_program]
     if instruction.accounts.len() < 6 {
         return Err(VerificationError::InvalidCreateATAInstruction);
     }
 
-    let owner_idx = instruction.accounts[2] as usize;
-    let mint_idx = instruction.accounts[3] as usize;
+    let funder_idx = instruction.accounts[0] as usize;
+    let ata = &message.account_keys[instruction.accounts[1] as usize];
+    let owner = &message.account_keys[instruction.accounts[2] as usize];
+    let mint = &message.account_keys[instruction.accounts[3] as usize];
+    let system_program = &message.account_keys[instruction.accounts[4] as usize];
+    let token_program = &message.account_keys[instruction.accounts[5] as usize];
+
+    // The funder pays the new account's rent, so it must be a writable
+    // signer (the fee payer appears in no instruction at all)
+    let header = &message.header;
+    let writable_signers = header.num_required_signatures.saturating_sub(header.num_readonly_signed_accounts);
+    if funder_idx >= writable_signers as usize {
+        return Err(VerificationError::CreateATAInvalidFunder);
+    }
 
-    let owner = &message.account_keys[owner_idx];
-    let mint = &message.account_keys[mint_idx];
+    if system_program != &solana_sdk::system_program::ID
+        || (token_program != &spl_token_program_id() && token_program != &spl_token_2022_program_id())
+    {
+        return Err(VerificationError::InvalidCreateATAInstruction);
+    }
 
     // Verify owner matches pay_to
     let pay_to: Pubkey = requirements
@@ -281,9 +648,90 @@ pub fn verify_create_ata_instruction(
         return Err(VerificationError::CreateATAIncorrectAsset);
     }
 
+    // The account created is the associated one of payTo for the asset
+    if ata != &get_associated_token_address_with_program_id(&pay_to, &asset, token_program) {
+        return Err(VerificationError::InvalidCreateATAInstruction);
+    }
+
     Ok(())
 }
 
+/// Who pays the rent of the account a CreateATA instruction creates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtaFunder {
+    /// The wallet authorizing the transfer
+    Payer,
+    /// The facilitator, within its rent budget
+    FeePayer,
+}
+
+/// Verify the funding account of a CreateATA instruction: the payer, or
+/// the fee payer if `fee_payer_may_fund` (`FEE_PAYER_ATA_RENT_BUDGET_LAMPORTS`)
+///
+/// Anyone else would be an unrelated wallet paying rent for the payment.
+pub fn verify_create_ata_funder(
+    instruction: &CompiledInstruction,
+    message: &Message,
+    fee_payer: &Pubkey,
+    fee_payer_may_fund: bool,
+) -> Result<AtaFunder, VerificationError> {
+    let funder = instruction
+        .accounts
+        .first()
+        .and_then(|index| message.account_keys.get(*index as usize))
+        .ok_or(VerificationError::InvalidCreateATAInstruction)?;
+
+    if funder == fee_payer {
+        return if fee_payer_may_fund {
+            Ok(AtaFunder::FeePayer)
+        } else {
+            Err(VerificationError::CreateATAInvalidFunder)
+        };
+    }
+    if transfer_authority(message).ok().as_ref() != Some(funder) {
+        return Err(VerificationError::CreateATAInvalidFunder);
+    }
+
+    Ok(AtaFunder::Payer)
+}
+
+/// Size of an associated token account of SPL Token
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Size of an associated token account of Token-2022, with its
+/// ImmutableOwner extension (account type and extension header)
+const TOKEN_2022_ACCOUNT_LEN: usize = TOKEN_ACCOUNT_LEN + 1 + 4;
+
+/// Rent-exempt balance of a new associated token account of `token_program`
+///
+/// Token-2022 mints whose extensions add account extensions make the
+/// account somewhat larger.
+pub fn ata_rent_lamports(token_program: &Pubkey) -> u64 {
+    let len = if token_program == &spl_token_2022_program_id() {
+        TOKEN_2022_ACCOUNT_LEN
+    } else {
+        TOKEN_ACCOUNT_LEN
+    };
+    solana_sdk::rent::Rent::default().minimum_balance(len)
+}
+
+/// Rent of the associated token account `fee_payer` funds in `tx`, if any
+///
+/// Charged even when a CreateIdempotent finds the account already there.
+pub fn sponsored_ata_rent(tx: &Transaction, fee_payer: &Pubkey) -> Option<u64> {
+    let message = &tx.message;
+    let key = |index: Option<&u8>| index.and_then(|index| message.account_keys.get(*index as usize));
+
+    message
+        .instructions
+        .iter()
+        .filter(|instruction| {
+            message.account_keys.get(instruction.program_id_index as usize) == Some(&spl_associated_token_account::ID)
+        })
+        .find(|instruction| key(instruction.accounts.first()) == Some(fee_payer))
+        .map(|instruction| ata_rent_lamports(key(instruction.accounts.get(5)).unwrap_or(&spl_token_program_id())))
+}
+
 // Include comprehensive unit tests
 #[cfg(test)]
 #[path = "verifier_tests.rs"]
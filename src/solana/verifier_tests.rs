@@ -35,7 +35,7 @@ mod tests {
 
         let result = verify_instruction_count(&tx);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), false); // No CreateATA
+        assert!(!result.unwrap()); // No CreateATA
     }
 
     #[test]
@@ -52,7 +52,7 @@ mod tests {
 
         let result = verify_instruction_count(&tx);
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), true); // Has CreateATA
+        assert!(result.unwrap()); // Has CreateATA
     }
 
     #[test]
@@ -174,7 +174,7 @@ mod tests {
     #[test]
     fn test_compute_limit_wrong_discriminator() {
         // Wrong discriminator should fail
-        let mut data = vec![99u8]; // Wrong discriminator
+        let data = vec![99u8]; // Wrong discriminator
 
         let instruction = CompiledInstruction {
             program_id_index: 0,
@@ -271,6 +271,85 @@ mod tests {
         assert!(matches!(result, Err(VerificationError::FeePayerInInstructionAccounts)));
     }
 
+    #[test]
+    fn test_fee_payer_position() {
+        let fee_payer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let instructions = vec![Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new_readonly(payer, true)],
+        )];
+
+        let tx = Transaction::new_unsigned(Message::new(&instructions, Some(&fee_payer)));
+        assert!(verify_fee_payer_position(&tx, &fee_payer).is_ok());
+
+        // Someone else pays the fees, the facilitator only signs
+        let tx = Transaction::new_unsigned(Message::new(&instructions, Some(&payer)));
+        assert!(matches!(
+            verify_fee_payer_position(&tx, &fee_payer),
+            Err(VerificationError::FeePayerNotFirstSigner)
+        ));
+
+        // No signers at all
+        let mut tx = Transaction::new_unsigned(Message::new(&instructions, Some(&fee_payer)));
+        tx.message.header.num_required_signatures = 0;
+        assert!(matches!(
+            verify_fee_payer_position(&tx, &fee_payer),
+            Err(VerificationError::FeePayerNotFirstSigner)
+        ));
+    }
+
+    #[test]
+    fn test_signer_count() {
+        let fee_payer = Pubkey::new_unique();
+        let signers = |count: usize| {
+            let accounts = (1..count).map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), true)).collect();
+            let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], accounts);
+            Transaction::new_unsigned(Message::new(&[instruction], Some(&fee_payer)))
+        };
+
+        assert!(verify_signer_count(&signers(2), DEFAULT_REQUIRED_SIGNERS).is_ok());
+        assert!(matches!(
+            verify_signer_count(&signers(3), DEFAULT_REQUIRED_SIGNERS),
+            Err(VerificationError::UnexpectedSigners)
+        ));
+        assert!(matches!(
+            verify_signer_count(&signers(1), DEFAULT_REQUIRED_SIGNERS),
+            Err(VerificationError::UnexpectedSigners)
+        ));
+        // Overridden for e.g. a sponsor co-signing
+        assert!(verify_signer_count(&signers(3), 3).is_ok());
+    }
+
+    #[test]
+    fn test_message_header() {
+        let fee_payer = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new_readonly(Pubkey::new_unique(), true)],
+        );
+        let tx = Transaction::new_unsigned(Message::new(&[instruction], Some(&fee_payer)));
+        assert!(verify_message_header(&tx).is_ok());
+
+        let broken = |edit: &dyn Fn(&mut Transaction)| {
+            let mut tx = tx.clone();
+            edit(&mut tx);
+            matches!(verify_message_header(&tx), Err(VerificationError::InvalidMessageHeader))
+        };
+        // Read-only fee payer
+        assert!(broken(&|tx| tx.message.header.num_readonly_signed_accounts = 2));
+        // More signers than keys
+        assert!(broken(&|tx| tx.message.header.num_required_signatures = 10));
+        assert!(broken(&|tx| tx.message.header.num_required_signatures = 0));
+        assert!(broken(&|tx| tx.message.header.num_readonly_unsigned_accounts = 10));
+        // A signature slot missing
+        assert!(broken(&|tx| {
+            tx.signatures.pop();
+        }));
+    }
+
     #[test]
     fn test_compute_budget_program_id() {
         let id = compute_budget_program_id();
@@ -290,5 +369,322 @@ mod tests {
         assert_ne!(token_id, Pubkey::default());
         assert_ne!(token_2022_id, Pubkey::default());
     }
-}
 
+    #[test]
+    fn test_account_lookup_served_from_cache() {
+        use crate::solana::rpc::MockRpc;
+
+        // Empty RPC: only cached accounts can be found
+        let rpc_client = MockRpc::new();
+        let cache = AccountCache::new(100, 30);
+        let mints = MintCache::new(10, 3600);
+        let metrics = AppMetrics::new();
+        let accounts = AccountLookup {
+            rpc_client: &rpc_client,
+            cache: &cache,
+            mints: &mints,
+            metrics: &metrics,
+            watcher: None,
+            prefetched: None,
+        };
+
+        let ata = Pubkey::new_unique();
+        cache.store(ata, solana_sdk::account::Account::default());
+
+        let hits = metrics.cache_hits.with_label_values(&["destination_ata"]).get();
+        assert!(accounts.exists(AccountKind::DestinationAta, &ata));
+        assert_eq!(metrics.cache_hits.with_label_values(&["destination_ata"]).get(), hits + 1);
+
+        let mint = Pubkey::new_unique();
+        let info = MintInfo { decimals: 6, token_program: spl_token_program_id(), extensions: vec![] };
+        mints.store(mint, info.clone());
+        assert_eq!(accounts.mint(&mint), Some(info));
+        assert!(accounts.mint(&Pubkey::new_unique()).is_none());
+
+        let misses = metrics.cache_misses.with_label_values(&["source_ata"]).get();
+        assert!(!accounts.exists(AccountKind::SourceAta, &Pubkey::new_unique()));
+        assert_eq!(metrics.cache_misses.with_label_values(&["source_ata"]).get(), misses + 1);
+    }
+
+    /// TransferChecked of 1000 from `payer` to `payTo`, after a CreateATA
+    /// funded by `funder`, with the requirements it pays
+    fn sponsored_transfer(
+        fee_payer: &Pubkey,
+        funder: &Pubkey,
+        payer: &Pubkey,
+    ) -> (Vec<Instruction>, crate::types::requests::PaymentRequirements) {
+        use crate::types::requests::{ExtraFields, PaymentRequirements};
+        use spl_associated_token_account::{
+            get_associated_token_address_with_program_id, instruction::create_associated_token_account_idempotent,
+        };
+
+        let mint = Pubkey::new_unique();
+        let pay_to = Pubkey::new_unique();
+        let token_program = spl_token_program_id();
+        let requirements = PaymentRequirements {
+            scheme: "exact".to_string(),
+            network: "solana-devnet".to_string(),
+            max_amount_required: "1000".to_string(),
+            asset: mint.to_string(),
+            pay_to: pay_to.to_string(),
+            resource: "/premium".to_string(),
+            description: "Premium".to_string(),
+            mime_type: "application/json".to_string(),
+            max_timeout_seconds: 60,
+            output_schema: None,
+            extra: ExtraFields::new(fee_payer.to_string()),
+        };
+
+        let instructions = vec![
+            create_associated_token_account_idempotent(funder, &pay_to, &mint, &token_program),
+            spl_token_2022::instruction::transfer_checked(
+                &token_program,
+                &get_associated_token_address_with_program_id(payer, &mint, &token_program),
+                &mint,
+                &get_associated_token_address_with_program_id(&pay_to, &mint, &token_program),
+                payer,
+                &[],
+                1000,
+                6,
+            )
+            .unwrap(),
+        ];
+        (instructions, requirements)
+    }
+
+    #[test]
+    fn test_payer_is_transfer_authority_not_second_key() {
+        let fee_payer = Pubkey::new_unique();
+        let funder = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let (instructions, requirements) = sponsored_transfer(&fee_payer, &funder, &payer);
+        let message = Message::new(&instructions, Some(&fee_payer));
+
+        // The writable funder sorts before the read-only authority
+        assert_eq!(message.account_keys[1], funder);
+
+        assert_eq!(transfer_authority(&message).unwrap(), payer);
+        let transfer = parse_transfer_instruction(&message.instructions[1], &message, &requirements, &fee_payer, false).unwrap();
+        assert_eq!(transfer.authority, payer);
+    }
+
+    #[test]
+    fn test_unsigned_transfer_authority_rejected() {
+        let fee_payer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let (mut instructions, requirements) = sponsored_transfer(&fee_payer, &Pubkey::new_unique(), &payer);
+        instructions[1].accounts[3].is_signer = false;
+        let message = Message::new(&instructions, Some(&fee_payer));
+
+        assert!(matches!(
+            transfer_authority(&message),
+            Err(VerificationError::TransferAuthorityNotSigner)
+        ));
+        assert!(matches!(
+            parse_transfer_instruction(&message.instructions[1], &message, &requirements, &fee_payer, false),
+            Err(VerificationError::TransferAuthorityNotSigner)
+        ));
+    }
+
+    #[test]
+    fn test_transfer_authority_needs_transfer_last() {
+        let message = create_mock_message();
+        assert!(matches!(
+            transfer_authority(&message),
+            Err(VerificationError::NotATransferInstruction)
+        ));
+    }
+
+    #[test]
+    fn test_plain_transfer_only_when_allowed() {
+        use crate::cache::{AccountCache, MintCache, MintInfo};
+        use crate::metrics::AppMetrics;
+        use crate::solana::rpc::MockRpc;
+        use crate::types::requests::{ExtraFields, PaymentRequirements};
+        use solana_sdk::account::Account;
+        use spl_associated_token_account::get_associated_token_address;
+
+        let fee_payer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let pay_to = Pubkey::new_unique();
+        let requirements = PaymentRequirements {
+            scheme: "exact".to_string(),
+            network: "solana-devnet".to_string(),
+            max_amount_required: "1000".to_string(),
+            asset: mint.to_string(),
+            pay_to: pay_to.to_string(),
+            resource: "/premium".to_string(),
+            description: "Premium".to_string(),
+            mime_type: "application/json".to_string(),
+            max_timeout_seconds: 60,
+            output_schema: None,
+            extra: ExtraFields::new(fee_payer.to_string()),
+        };
+
+        let source = get_associated_token_address(&payer, &mint);
+        let destination = get_associated_token_address(&pay_to, &mint);
+        let instruction =
+            spl_token::instruction::transfer(&spl_token_program_id(), &source, &destination, &payer, &[], 1000).unwrap();
+        let message = Message::new(&[instruction], Some(&fee_payer));
+        let transfer_ix = &message.instructions[0];
+
+        assert!(matches!(
+            parse_transfer_instruction(transfer_ix, &message, &requirements, &fee_payer, false),
+            Err(VerificationError::NotATransferInstruction)
+        ));
+        let transfer = parse_transfer_instruction(transfer_ix, &message, &requirements, &fee_payer, true).unwrap();
+        assert_eq!(transfer.source, source);
+        assert_eq!(transfer.destination, destination);
+        assert_eq!(transfer.authority, payer);
+        assert_eq!(transfer.decimals, None);
+        assert_eq!(transfer_authority(&message).unwrap(), payer);
+        assert_eq!(
+            account_checks(true, false),
+            vec!["mint_exists", "source_ata_exists", "source_mint"]
+        );
+
+        // The asset is checked against the source account's mint
+        let rpc_client = MockRpc::new();
+        let cache = AccountCache::new(100, 30);
+        let mints = MintCache::new(10, 3600);
+        let metrics = AppMetrics::new();
+        let accounts = AccountLookup {
+            rpc_client: &rpc_client,
+            cache: &cache,
+            mints: &mints,
+            metrics: &metrics,
+            watcher: None,
+            prefetched: None,
+        };
+        mints.store(mint, MintInfo { decimals: 6, token_program: spl_token_program_id(), extensions: vec![] });
+        cache.store(destination, Account::default());
+        let token_account = |mint: &Pubkey| Account {
+            owner: spl_token_program_id(),
+            data: [mint.to_bytes().to_vec(), vec![0; 133]].concat(),
+            ..Account::default()
+        };
+        let verify = || {
+            verify_transfer_instruction(transfer_ix, &message, &requirements, &fee_payer, false, true, &accounts)
+        };
+
+        cache.store(source, token_account(&Pubkey::new_unique()));
+        assert!(matches!(verify(), Err(VerificationError::SourceMintMismatch)));
+
+        cache.store(source, token_account(&mint));
+        assert_eq!(verify().unwrap().authority, payer);
+    }
+
+    #[test]
+    fn test_create_ata_and_create_idempotent_accepted() {
+        use spl_associated_token_account::instruction::{
+            create_associated_token_account, create_associated_token_account_idempotent,
+        };
+
+        let fee_payer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let (_, requirements) = sponsored_transfer(&fee_payer, &payer, &payer);
+        let pay_to: Pubkey = requirements.pay_to.parse().unwrap();
+        let mint: Pubkey = requirements.asset.parse().unwrap();
+
+        let check = |instruction: Instruction| {
+            let message = Message::new(&[instruction], Some(&fee_payer));
+            verify_create_ata_instruction(&message.instructions[0], &message, &requirements)
+        };
+
+        for token_program in [spl_token_program_id(), spl_token_2022_program_id()] {
+            let create = create_associated_token_account(&payer, &pay_to, &mint, &token_program);
+            let idempotent = create_associated_token_account_idempotent(&payer, &pay_to, &mint, &token_program);
+            assert_eq!(idempotent.data, vec![1]);
+            assert!(check(create.clone()).is_ok());
+            assert!(check(idempotent).is_ok());
+
+            // RecoverNested and unknown instructions of the program
+            let mut other = create.clone();
+            other.data = vec![2];
+            assert!(matches!(check(other), Err(VerificationError::InvalidCreateATAInstruction)));
+
+            // The funder must sign
+            let mut unsigned = create.clone();
+            unsigned.accounts[0].is_signer = false;
+            assert!(matches!(check(unsigned), Err(VerificationError::CreateATAInvalidFunder)));
+
+            // Another account than payTo's associated one
+            let mut wrong_ata = create;
+            wrong_ata.accounts[1].pubkey = Pubkey::new_unique();
+            assert!(matches!(check(wrong_ata), Err(VerificationError::InvalidCreateATAInstruction)));
+        }
+    }
+
+    #[test]
+    fn test_create_ata_funder() {
+        let fee_payer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let funder = |funder: &Pubkey| {
+            let (instructions, _) = sponsored_transfer(&fee_payer, funder, &payer);
+            let message = Message::new(&instructions, Some(&fee_payer));
+            (verify_create_ata_funder(&message.instructions[0], &message, &fee_payer, false), message)
+        };
+
+        assert_eq!(funder(&payer).0.unwrap(), AtaFunder::Payer);
+        // An unrelated wallet paying rent for the payment
+        assert!(matches!(funder(&Pubkey::new_unique()).0, Err(VerificationError::CreateATAInvalidFunder)));
+
+        // The fee payer only with a rent budget
+        let (result, message) = funder(&fee_payer);
+        assert!(matches!(result, Err(VerificationError::CreateATAInvalidFunder)));
+        assert_eq!(
+            verify_create_ata_funder(&message.instructions[0], &message, &fee_payer, true).unwrap(),
+            AtaFunder::FeePayer
+        );
+    }
+
+    #[test]
+    fn test_fee_payer_funding_ata() {
+        let fee_payer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+
+        let (instructions, _) = sponsored_transfer(&fee_payer, &fee_payer, &payer);
+        let tx = Transaction::new_unsigned(Message::new(&instructions, Some(&fee_payer)));
+        assert!(matches!(
+            verify_fee_payer_safety(&tx, &fee_payer),
+            Err(VerificationError::FeePayerInInstructionAccounts)
+        ));
+        assert!(verify_fee_payer_safety_funding_ata(&tx, &fee_payer).is_ok());
+        assert_eq!(sponsored_ata_rent(&tx, &fee_payer), Some(2_039_280));
+
+        // Funding only: the fee payer as the transfer authority is still rejected
+        let (instructions, _) = sponsored_transfer(&fee_payer, &fee_payer, &fee_payer);
+        let tx = Transaction::new_unsigned(Message::new(&instructions, Some(&fee_payer)));
+        assert!(matches!(
+            verify_fee_payer_safety_funding_ata(&tx, &fee_payer),
+            Err(VerificationError::FeePayerInInstructionAccounts)
+        ));
+
+        // Funded by the payer: nothing charged to the budget
+        let (instructions, _) = sponsored_transfer(&fee_payer, &payer, &payer);
+        let tx = Transaction::new_unsigned(Message::new(&instructions, Some(&fee_payer)));
+        assert_eq!(sponsored_ata_rent(&tx, &fee_payer), None);
+        assert!(ata_rent_lamports(&spl_token_2022_program_id()) > ata_rent_lamports(&spl_token_program_id()));
+    }
+
+    #[test]
+    fn test_transaction_fee() {
+        let fee_payer = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let compute_budget = |data: Vec<u8>| Instruction::new_with_bytes(compute_budget_program_id(), &data, vec![]);
+
+        let (mut instructions, _) = sponsored_transfer(&fee_payer, &payer, &payer);
+        let tx = Transaction::new_unsigned(Message::new(&instructions, Some(&fee_payer)));
+        assert_eq!(transaction_fee_lamports(&tx), 10_000);
+
+        // 200_000 units at 1.5 lamports each, rounded up
+        let mut limit = vec![2u8];
+        limit.extend_from_slice(&200_000u32.to_le_bytes());
+        let mut price = vec![3u8];
+        price.extend_from_slice(&1_500_001u64.to_le_bytes());
+        instructions.splice(0..0, [compute_budget(limit), compute_budget(price)]);
+        let tx = Transaction::new_unsigned(Message::new(&instructions, Some(&fee_payer)));
+        assert_eq!(transaction_fee_lamports(&tx), 10_000 + 300_001);
+    }
+}
@@ -0,0 +1,138 @@
+//! Fee payer spending
+//!
+//! Every settlement records what it cost the fee payer: the transaction fee
+//! and any token account rent it funded (see [`crate::rent_budget`]). With
+//! `FEE_PAYER_DAILY_BUDGET_LAMPORTS` set, settlements are refused with
+//! `fee_budget_exhausted` once the fee payer has spent that much in the
+//! current UTC day.
+
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// What the fee payer spent in one UTC day
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DailySpend {
+    /// Days since the Unix epoch
+    pub day: u64,
+    pub fee_lamports: u64,
+    pub rent_lamports: u64,
+    pub settlements: u64,
+}
+
+impl DailySpend {
+    pub fn total_lamports(&self) -> u64 {
+        self.fee_lamports.saturating_add(self.rent_lamports)
+    }
+}
+
+/// Fee payer spending of the current day, against an optional budget
+#[derive(Debug, Clone, Default)]
+pub struct SpendTracker {
+    daily_budget_lamports: Option<u64>,
+    today: Arc<Mutex<DailySpend>>,
+}
+
+impl SpendTracker {
+    pub fn new(daily_budget_lamports: Option<u64>) -> Self {
+        Self { daily_budget_lamports, today: Arc::default() }
+    }
+
+    /// Tracker with the budget of `FEE_PAYER_DAILY_BUDGET_LAMPORTS` (none if unset)
+    pub fn from_env() -> Self {
+        let daily_budget_lamports = std::env::var("FEE_PAYER_DAILY_BUDGET_LAMPORTS")
+            .ok()
+            .and_then(|value| value.parse().ok());
+        if let Some(lamports) = daily_budget_lamports {
+            tracing::info!("💸 Fee payer daily budget: {} lamports", lamports);
+        }
+        Self::new(daily_budget_lamports)
+    }
+
+    pub fn daily_budget_lamports(&self) -> Option<u64> {
+        self.daily_budget_lamports
+    }
+
+    /// Spending so far today
+    pub fn today(&self) -> DailySpend {
+        self.on(today())
+    }
+
+    /// Whether today's budget is used up, so no settlement may start
+    pub fn is_exhausted(&self) -> bool {
+        self.is_exhausted_on(today())
+    }
+
+    /// Record a settlement's transaction fee and the rent it funded,
+    /// returning the day's spending with it
+    pub fn record(&self, fee_lamports: u64, rent_lamports: u64) -> DailySpend {
+        self.record_on(today(), fee_lamports, rent_lamports)
+    }
+
+    fn on(&self, day: u64) -> DailySpend {
+        let spend = *self.today.lock().unwrap();
+        if spend.day == day {
+            spend
+        } else {
+            DailySpend { day, ..DailySpend::default() }
+        }
+    }
+
+    fn is_exhausted_on(&self, day: u64) -> bool {
+        self.daily_budget_lamports
+            .is_some_and(|budget| self.on(day).total_lamports() >= budget)
+    }
+
+    fn record_on(&self, day: u64, fee_lamports: u64, rent_lamports: u64) -> DailySpend {
+        let mut spend = self.today.lock().unwrap();
+        if spend.day != day {
+            *spend = DailySpend { day, ..DailySpend::default() };
+        }
+        spend.fee_lamports = spend.fee_lamports.saturating_add(fee_lamports);
+        spend.rent_lamports = spend.rent_lamports.saturating_add(rent_lamports);
+        spend.settlements += 1;
+        *spend
+    }
+}
+
+/// Days since the Unix epoch (UTC)
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_exhausted_until_next_day() {
+        let tracker = SpendTracker::new(Some(20_000));
+        assert!(!tracker.is_exhausted_on(1));
+
+        let spend = tracker.record_on(1, 10_005, 0);
+        assert_eq!(spend.total_lamports(), 10_005);
+        assert!(!tracker.is_exhausted_on(1));
+
+        let spend = tracker.record_on(1, 10_005, 2_039_280);
+        assert_eq!(spend, DailySpend { day: 1, fee_lamports: 20_010, rent_lamports: 2_039_280, settlements: 2 });
+        assert!(tracker.is_exhausted_on(1));
+
+        // A new day starts over
+        assert!(!tracker.is_exhausted_on(2));
+        assert_eq!(tracker.on(2).settlements, 0);
+        assert_eq!(tracker.record_on(2, 10_005, 0).settlements, 1);
+    }
+
+    #[test]
+    fn test_no_budget_never_exhausted() {
+        let tracker = SpendTracker::default();
+        tracker.record(u64::MAX, u64::MAX);
+        assert!(!tracker.is_exhausted());
+        assert_eq!(tracker.today().total_lamports(), u64::MAX);
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use prometheus::proto::{MetricFamily, MetricType};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+/// Largest datagram we send; stays under a typical 1500 byte MTU
+const MAX_DATAGRAM_BYTES: usize = 1432;
+
+/// Push-based metrics export to a StatsD / DogStatsD agent
+///
+/// For platforms that can't scrape `/metrics`. The exporter reads the same
+/// Prometheus registry that `/metrics` serves (i.e. the [`crate::metrics::AppMetrics`]
+/// counters), so there is only one set of metrics to maintain.
+#[derive(Debug, Clone)]
+pub struct StatsdConfig {
+    /// Agent address, e.g. `127.0.0.1:8125`
+    pub addr: String,
+    /// Prepended to every metric name (e.g. `x402.`)
+    pub prefix: String,
+    /// Tags added to every metric, in `key:value` form
+    pub tags: Vec<String>,
+    pub interval: Duration,
+}
+
+impl StatsdConfig {
+    /// Load StatsD settings from environment
+    ///
+    /// Returns `None` when `STATSD_ADDR` is not set.
+    pub fn from_env() -> Option<Self> {
+        let addr = std::env::var("STATSD_ADDR").ok().filter(|addr| !addr.is_empty())?;
+
+        let tags = std::env::var("STATSD_TAGS")
+            .map(|tags| {
+                tags.split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            addr,
+            prefix: std::env::var("STATSD_PREFIX").unwrap_or_default(),
+            tags,
+            interval: Duration::from_secs(
+                std::env::var("STATSD_INTERVAL_SECONDS")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(10),
+            ),
+        })
+    }
+}
+
+/// Converts Prometheus snapshots into StatsD lines
+///
+/// Prometheus counters are cumulative while StatsD counters are increments,
+/// so the exporter remembers the last value of each series and sends deltas.
+#[derive(Debug)]
+pub struct StatsdExporter {
+    config: StatsdConfig,
+    previous: HashMap<String, f64>,
+}
+
+impl StatsdExporter {
+    pub fn new(config: StatsdConfig) -> Self {
+        Self {
+            config,
+            previous: HashMap::new(),
+        }
+    }
+
+    /// Render one push worth of StatsD lines
+    pub fn render(&mut self, families: &[MetricFamily]) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for family in families {
+            let name = format!("{}{}", self.config.prefix, family.name());
+
+            for metric in family.get_metric() {
+                let mut tags = self.config.tags.clone();
+                tags.extend(
+                    metric
+                        .get_label()
+                        .iter()
+                        .map(|label| format!("{}:{}", label.name(), label.value())),
+                );
+
+                match family.get_field_type() {
+                    MetricType::COUNTER => {
+                        let value = metric.get_counter().value();
+                        self.push_delta(&mut lines, &name, value, &tags);
+                    }
+                    MetricType::GAUGE => {
+                        lines.push(format_line(&name, metric.get_gauge().value(), "g", &tags));
+                    }
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        self.push_delta(&mut lines, &format!("{}_count", name), histogram.get_sample_count() as f64, &tags);
+                        self.push_delta(&mut lines, &format!("{}_sum", name), histogram.get_sample_sum(), &tags);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        lines
+    }
+
+    fn push_delta(&mut self, lines: &mut Vec<String>, name: &str, value: f64, tags: &[String]) {
+        let series = format!("{}|{}", name, tags.join(","));
+        let previous = self.previous.insert(series, value).unwrap_or(0.0);
+
+        // A drop means the counter was reset; its whole value is new
+        let delta = if value >= previous { value - previous } else { value };
+        if delta > 0.0 {
+            lines.push(format_line(name, delta, "c", tags));
+        }
+    }
+}
+
+fn format_line(name: &str, value: f64, kind: &str, tags: &[String]) -> String {
+    if tags.is_empty() {
+        format!("{}:{}|{}", name, value, kind)
+    } else {
+        format!("{}:{}|{}|#{}", name, value, kind, tags.join(","))
+    }
+}
+
+/// Pack lines into newline-separated datagrams
+fn datagrams(lines: &[String]) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut current = String::new();
+
+    for line in lines {
+        if !current.is_empty() && current.len() + 1 + line.len() > MAX_DATAGRAM_BYTES {
+            packets.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+
+    if !current.is_empty() {
+        packets.push(current);
+    }
+    packets
+}
+
+/// Spawn a background task pushing metrics to the StatsD agent
+pub async fn spawn_exporter(config: StatsdConfig) -> Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .await
+        .context("Failed to bind StatsD socket")?;
+    socket
+        .connect(&config.addr)
+        .await
+        .with_context(|| format!("Invalid STATSD_ADDR: {}", config.addr))?;
+
+    tracing::info!(
+        "📤 Pushing metrics to StatsD at {} every {}s",
+        config.addr,
+        config.interval.as_secs()
+    );
+
+    let interval = config.interval;
+    let mut exporter = StatsdExporter::new(config);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            let lines = exporter.render(&prometheus::gather());
+            for packet in datagrams(&lines) {
+                if let Err(e) = socket.send(packet.as_bytes()).await {
+                    tracing::warn!("⚠️  Failed to push metrics to StatsD: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::{IntCounterVec, IntGauge, Opts, Registry};
+
+    fn exporter() -> StatsdExporter {
+        StatsdExporter::new(StatsdConfig {
+            addr: "127.0.0.1:8125".to_string(),
+            prefix: "x402.".to_string(),
+            tags: vec!["env:test".to_string()],
+            interval: Duration::from_secs(10),
+        })
+    }
+
+    #[test]
+    fn test_counters_sent_as_deltas() {
+        let registry = Registry::new();
+        let counter = IntCounterVec::new(Opts::new("settles_total", "help"), &["status"]).unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+
+        let mut exporter = exporter();
+        counter.with_label_values(&["success"]).inc_by(3);
+        assert_eq!(
+            exporter.render(&registry.gather()),
+            vec!["x402.settles_total:3|c|#env:test,status:success"]
+        );
+
+        // Unchanged counters are not resent
+        assert!(exporter.render(&registry.gather()).is_empty());
+
+        counter.with_label_values(&["success"]).inc_by(2);
+        assert_eq!(
+            exporter.render(&registry.gather()),
+            vec!["x402.settles_total:2|c|#env:test,status:success"]
+        );
+    }
+
+    #[test]
+    fn test_gauges_sent_as_values() {
+        let registry = Registry::new();
+        let gauge = IntGauge::new("cache_size", "help").unwrap();
+        registry.register(Box::new(gauge.clone())).unwrap();
+
+        gauge.set(42);
+        let mut exporter = exporter();
+        assert_eq!(exporter.render(&registry.gather()), vec!["x402.cache_size:42|g|#env:test"]);
+        assert_eq!(exporter.render(&registry.gather()), vec!["x402.cache_size:42|g|#env:test"]);
+    }
+
+    #[test]
+    fn test_datagrams_respect_size_limit() {
+        let lines: Vec<String> = (0..200).map(|i| format!("x402.metric_{}:1|c", i)).collect();
+        let packets = datagrams(&lines);
+
+        assert!(packets.len() > 1);
+        assert!(packets.iter().all(|p| p.len() <= MAX_DATAGRAM_BYTES));
+        assert_eq!(packets.iter().map(|p| p.lines().count()).sum::<usize>(), 200);
+    }
+}
@@ -0,0 +1,290 @@
+//! Billing windows of subscriptions
+//!
+//! The `subscription` scheme (see [`crate::schemes::SubscriptionSvmVerifier`])
+//! accepts one payment per payer and resource in each billing window. The
+//! ledger keeps the last window each pair paid, plus the windows claimed by
+//! settlements being submitted, so a second payment for the window is
+//! refused even while the first is in flight. With `SUBSCRIPTIONS_PATH` set,
+//! it's written to that file after every settlement (off the request path)
+//! and loaded on startup.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::error::VerificationError;
+
+/// One billing period of a subscription, `[start, start + period_seconds)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BillingWindow {
+    pub start: u64,
+    pub period_seconds: u64,
+}
+
+impl BillingWindow {
+    /// Window containing `time`, of periods counted from `anchor` (`None`
+    /// before the first period)
+    pub fn at(time: u64, period_seconds: u64, anchor: u64) -> Option<Self> {
+        if period_seconds == 0 || time < anchor {
+            return None;
+        }
+        let start = anchor + (time - anchor) / period_seconds * period_seconds;
+        Some(Self { start, period_seconds })
+    }
+
+    pub fn end(&self) -> u64 {
+        self.start.saturating_add(self.period_seconds)
+    }
+
+    pub fn contains(&self, time: u64) -> bool {
+        (self.start..self.end()).contains(&time)
+    }
+}
+
+type SubscriptionKey = (String, String);
+
+#[derive(Debug, Default)]
+struct LedgerState {
+    /// Start of the billing window each (payer, resource) last paid
+    paid: HashMap<SubscriptionKey, u64>,
+    /// Start of the window claimed by each pair's settlement being submitted
+    pending: HashMap<SubscriptionKey, u64>,
+}
+
+/// Entry of the ledger file
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LedgerEntry {
+    payer: String,
+    resource: String,
+    /// Any time within the window works, so files of settlement times load
+    #[serde(alias = "settledAt")]
+    window_start: u64,
+}
+
+/// Last paid billing window of each subscription
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionLedger {
+    path: Option<Arc<PathBuf>>,
+    state: Arc<Mutex<LedgerState>>,
+    /// Held while writing the file, so writes land in order
+    write_lock: Arc<Mutex<()>>,
+}
+
+impl SubscriptionLedger {
+    /// In-memory ledger
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ledger persisted to `SUBSCRIPTIONS_PATH` (in memory if unset)
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("SUBSCRIPTIONS_PATH").ok().filter(|path| !path.is_empty()) {
+            Some(path) => {
+                let ledger = Self::open(PathBuf::from(path))?;
+                tracing::info!("🔁 Loaded {} subscription(s) from {}", ledger.len(), ledger.path_display());
+                Ok(ledger)
+            }
+            None => Ok(Self::new()),
+        }
+    }
+
+    /// Ledger kept in `path`, loading it if it exists
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let mut state = LedgerState::default();
+        match std::fs::read(&path) {
+            Ok(data) => {
+                let entries: Vec<LedgerEntry> = serde_json::from_slice(&data)
+                    .with_context(|| format!("Invalid subscriptions file {}", path.display()))?;
+                state.paid = entries
+                    .into_iter()
+                    .map(|entry| ((entry.payer, entry.resource), entry.window_start))
+                    .collect();
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+        Ok(Self {
+            path: Some(Arc::new(path)),
+            state: Arc::new(Mutex::new(state)),
+            write_lock: Arc::default(),
+        })
+    }
+
+    /// Number of subscriptions with a settled payment
+    pub fn len(&self) -> usize {
+        self.lock().paid.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Start of the last billing window `payer` paid for `resource`
+    pub fn last_paid_window(&self, payer: &str, resource: &str) -> Option<u64> {
+        self.lock().paid.get(&key(payer, resource)).copied()
+    }
+
+    /// Whether `window` is still unpaid for `payer` and `resource`
+    pub fn check(&self, payer: &str, resource: &str, window: &BillingWindow) -> Result<(), VerificationError> {
+        check_unpaid(&self.lock(), &key(payer, resource), window)
+    }
+
+    /// Claim `window` for a settlement about to be submitted; pair with
+    /// [`Self::finish`]
+    pub fn begin(&self, payer: &str, resource: &str, window: &BillingWindow) -> Result<(), VerificationError> {
+        let mut state = self.lock();
+        let key = key(payer, resource);
+        check_unpaid(&state, &key, window)?;
+        state.pending.insert(key, window.start);
+        Ok(())
+    }
+
+    /// Release a claimed window, recording it as paid if the settlement
+    /// went through
+    ///
+    /// The window is the one claimed by [`Self::begin`], even if the
+    /// settlement confirmed after it ended.
+    pub fn finish(&self, payer: &str, resource: &str, settled: bool) {
+        {
+            let mut state = self.lock();
+            let key = key(payer, resource);
+            let Some(window_start) = state.pending.remove(&key) else { return };
+            if !settled {
+                return;
+            }
+            state.paid.insert(key, window_start);
+        }
+
+        if self.path.is_none() {
+            return;
+        }
+        // Written on the blocking pool when there's a runtime, not under
+        // the ledger's lock
+        let ledger = self.clone();
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn_blocking(move || ledger.persist());
+            }
+            Err(_) => ledger.persist(),
+        }
+    }
+
+    /// Write the current state to the ledger file
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let _writing = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        // Snapshot taken under the write lock: the last write has the latest state
+        let paid = self.lock().paid.clone();
+        if let Err(e) = save(path, &paid) {
+            tracing::error!("Failed to write subscriptions: {:#}", e);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LedgerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn path_display(&self) -> String {
+        self.path.as_ref().map(|path| path.display().to_string()).unwrap_or_default()
+    }
+}
+
+fn key(payer: &str, resource: &str) -> SubscriptionKey {
+    (payer.to_string(), resource.to_string())
+}
+
+fn check_unpaid(state: &LedgerState, key: &SubscriptionKey, window: &BillingWindow) -> Result<(), VerificationError> {
+    let paid = state.paid.get(key).is_some_and(|window_start| window.contains(*window_start));
+    if paid || state.pending.contains_key(key) {
+        return Err(VerificationError::SubscriptionAlreadyPaid);
+    }
+    Ok(())
+}
+
+/// Write the paid windows to `path` (atomically, via a temporary file)
+fn save(path: &Path, paid: &HashMap<SubscriptionKey, u64>) -> Result<()> {
+    let entries: Vec<LedgerEntry> = paid
+        .iter()
+        .map(|((payer, resource), window_start)| LedgerEntry {
+            payer: payer.clone(),
+            resource: resource.clone(),
+            window_start: *window_start,
+        })
+        .collect();
+
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec(&entries)?).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_billing_window() {
+        let window = BillingWindow::at(1_000, 300, 100).unwrap();
+        assert_eq!(window, BillingWindow { start: 1_000, period_seconds: 300 });
+        assert!(window.contains(1_299));
+        assert!(!window.contains(1_300));
+        assert_eq!(BillingWindow::at(1_299, 300, 100), Some(window));
+        assert_eq!(BillingWindow::at(99, 300, 100), None);
+    }
+
+    #[test]
+    fn test_one_payment_per_window() {
+        let ledger = SubscriptionLedger::new();
+        let window = BillingWindow::at(1_000, 300, 0).unwrap();
+
+        ledger.begin("payer", "/feed", &window).unwrap();
+        // In flight: a second payment is refused
+        assert!(matches!(
+            ledger.check("payer", "/feed", &window),
+            Err(VerificationError::SubscriptionAlreadyPaid)
+        ));
+        // A failed settlement releases the window
+        ledger.finish("payer", "/feed", false);
+        assert!(ledger.check("payer", "/feed", &window).is_ok());
+
+        ledger.begin("payer", "/feed", &window).unwrap();
+        ledger.finish("payer", "/feed", true);
+        assert!(ledger.begin("payer", "/feed", &window).is_err());
+        assert!(ledger.check("payer", "/other", &window).is_ok());
+
+        // Next window
+        let next = BillingWindow::at(1_300, 300, 0).unwrap();
+        assert!(ledger.check("payer", "/feed", &next).is_ok());
+    }
+
+    #[test]
+    fn test_ledger_persists() {
+        let path = std::env::temp_dir().join(format!("x402-subscriptions-{}.json", uuid::Uuid::new_v4()));
+        let ledger = SubscriptionLedger::open(path.clone()).unwrap();
+        let window = BillingWindow::at(1_000, 300, 0).unwrap();
+        ledger.begin("payer", "/feed", &window).unwrap();
+        ledger.finish("payer", "/feed", true);
+
+        let reopened = SubscriptionLedger::open(path.clone()).unwrap();
+        assert_eq!(reopened.last_paid_window("payer", "/feed"), Some(window.start));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_late_settlement_pays_claimed_window() {
+        let ledger = SubscriptionLedger::new();
+        let window = BillingWindow::at(1_299, 300, 100).unwrap();
+        let next = BillingWindow::at(1_300, 300, 100).unwrap();
+
+        // Claimed at the end of a window, confirmed after it ended: the
+        // claimed window is paid, not the next one
+        ledger.begin("payer", "/feed", &window).unwrap();
+        ledger.finish("payer", "/feed", true);
+        assert_eq!(ledger.last_paid_window("payer", "/feed"), Some(window.start));
+        assert!(ledger.check("payer", "/feed", &window).is_err());
+        assert!(ledger.check("payer", "/feed", &next).is_ok());
+    }
+}
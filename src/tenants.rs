@@ -0,0 +1,276 @@
+//! Merchants sharing one facilitator
+//!
+//! With `TENANTS_FILE` set, payment requests must carry the `X-API-Key` of a
+//! tenant (see [`crate::middleware::tenant`]), and are held to that tenant's
+//! policy on top of the facilitator's: accepted mints and `payTo` addresses,
+//! its own rate limits, webhook endpoint and, optionally, a dedicated fee
+//! payer. Metrics and audit events of its requests carry the tenant's id.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::error::VerificationError;
+use crate::middleware::rate_limit::{RateLimitQuota, RateLimitState};
+use crate::solana::signer::{FeePayerSigner, FileKeypairSigner};
+use crate::types::requests::PaymentRequirements;
+use crate::webhooks::{WebhookEndpoint, WebhookEndpointSpec};
+
+/// A tenant as configured in the tenants file
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TenantSpec {
+    /// Identifies the tenant in metrics and audit events
+    pub id: String,
+    /// Keys the tenant's requests carry as `X-API-Key`
+    pub api_keys: Vec<String>,
+    /// Mints the tenant accepts (empty: any the facilitator accepts)
+    #[serde(default)]
+    pub allowed_mints: Vec<String>,
+    /// Addresses the tenant's payments may go to (empty: any)
+    #[serde(default)]
+    pub pay_to: Vec<String>,
+    /// Quotas replacing the facilitator's for this tenant
+    #[serde(default)]
+    pub rate_limits: Option<TenantRateLimits>,
+    /// Endpoint receiving the events of the tenant's requests
+    #[serde(default)]
+    pub webhook: Option<WebhookEndpointSpec>,
+    /// Solana CLI keypair file of a fee payer used only for this tenant
+    #[serde(default)]
+    pub fee_payer_keypair_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TenantRateLimits {
+    pub verify: RateLimitQuota,
+    pub settle: RateLimitQuota,
+}
+
+/// A merchant and its policy
+pub struct Tenant {
+    pub id: String,
+    pub allowed_mints: Vec<String>,
+    pub pay_to: Vec<String>,
+    /// Limiter of the tenant's own quotas (`None`: the facilitator's)
+    pub rate_limiter: Option<RateLimitState>,
+    pub webhook: Option<WebhookEndpoint>,
+    /// Dedicated fee payer (`None`: the facilitator's)
+    pub fee_payer: Option<Arc<dyn FeePayerSigner>>,
+}
+
+impl std::fmt::Debug for Tenant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tenant")
+            .field("id", &self.id)
+            .field("allowed_mints", &self.allowed_mints)
+            .field("pay_to", &self.pay_to)
+            .field("rate_limits", &self.rate_limiter.is_some())
+            .field("webhook", &self.webhook.as_ref().map(|endpoint| &endpoint.name))
+            .field("fee_payer", &self.fee_payer.as_ref().map(|signer| signer.pubkey()))
+            .finish()
+    }
+}
+
+impl Tenant {
+    fn from_spec(spec: TenantSpec) -> Result<Self> {
+        let fee_payer = match &spec.fee_payer_keypair_file {
+            Some(path) => {
+                let signer = FileKeypairSigner::from_file(path)
+                    .with_context(|| format!("Invalid fee payer keypair of tenant '{}'", spec.id))?;
+                Some(Arc::new(signer) as Arc<dyn FeePayerSigner>)
+            }
+            None => None,
+        };
+        let webhook = spec
+            .webhook
+            .map(WebhookEndpoint::from_spec)
+            .transpose()
+            .with_context(|| format!("Invalid webhook of tenant '{}'", spec.id))?;
+
+        Ok(Self {
+            id: spec.id,
+            allowed_mints: spec.allowed_mints,
+            pay_to: spec.pay_to,
+            rate_limiter: spec.rate_limits.map(|limits| RateLimitState::new(limits.verify, limits.settle)),
+            webhook,
+            fee_payer,
+        })
+    }
+
+    /// Check a payment's requirements against the tenant's policy
+    pub fn check(&self, requirements: &PaymentRequirements) -> Result<(), VerificationError> {
+        if !self.allowed_mints.is_empty() && !self.allowed_mints.contains(&requirements.asset) {
+            return Err(VerificationError::UnexpectedError(anyhow::anyhow!(
+                "Asset {} is not accepted for tenant {}",
+                requirements.asset,
+                self.id
+            )));
+        }
+        if !self.pay_to.is_empty() && !self.pay_to.contains(&requirements.pay_to) {
+            return Err(VerificationError::PayToNotAllowed);
+        }
+        if let Some(fee_payer) = &self.fee_payer {
+            if requirements.extra.fee_payer != fee_payer.pubkey().to_string() {
+                return Err(VerificationError::FeePayerNotAllowed);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tenants by API key
+#[derive(Debug, Clone, Default)]
+pub struct Tenants {
+    /// Keyed by the SHA-256 of each API key, so lookups don't compare keys
+    by_key: Arc<HashMap<[u8; 32], Arc<Tenant>>>,
+    count: usize,
+}
+
+impl Tenants {
+    /// Build the tenants of `specs`, rejecting duplicate ids and keys
+    pub fn from_specs(specs: Vec<TenantSpec>) -> Result<Self> {
+        let mut by_key = HashMap::new();
+        let mut ids = Vec::with_capacity(specs.len());
+
+        for spec in specs {
+            if spec.id.is_empty() || ids.contains(&spec.id) {
+                anyhow::bail!("Tenant ids must be unique and non-empty ('{}')", spec.id);
+            }
+            if spec.api_keys.is_empty() || spec.api_keys.iter().any(|key| key.is_empty()) {
+                anyhow::bail!("Tenant '{}' needs at least one non-empty API key", spec.id);
+            }
+            ids.push(spec.id.clone());
+
+            let keys = spec.api_keys.clone();
+            let tenant = Arc::new(Tenant::from_spec(spec)?);
+            for key in keys {
+                if by_key.insert(key_digest(&key), tenant.clone()).is_some() {
+                    anyhow::bail!("API key of tenant '{}' is used by another tenant", tenant.id);
+                }
+            }
+        }
+
+        Ok(Self { by_key: Arc::new(by_key), count: ids.len() })
+    }
+
+    /// Tenants of `TENANTS_FILE`, a JSON array of [`TenantSpec`] (none if unset)
+    pub fn from_env() -> Result<Self> {
+        let Some(path) = std::env::var("TENANTS_FILE").ok().filter(|path| !path.is_empty()) else {
+            return Ok(Self::default());
+        };
+
+        let json = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path))?;
+        let specs: Vec<TenantSpec> = serde_json::from_str(&json).with_context(|| format!("Invalid {}", path))?;
+        let tenants = Self::from_specs(specs).with_context(|| format!("Invalid {}", path))?;
+        tracing::info!("🏢 {} tenants loaded; payment requests need a tenant API key", tenants.len());
+        Ok(tenants)
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether no tenants are configured (requests then need no API key)
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Tenant owning `api_key`
+    pub fn get(&self, api_key: &str) -> Option<&Arc<Tenant>> {
+        self.by_key.get(&key_digest(api_key))
+    }
+}
+
+fn key_digest(api_key: &str) -> [u8; 32] {
+    Sha256::digest(api_key.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs(json: serde_json::Value) -> Vec<TenantSpec> {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn requirements(asset: &str, pay_to: &str) -> PaymentRequirements {
+        serde_json::from_value(serde_json::json!({
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "maxAmountRequired": "1000",
+            "asset": asset,
+            "payTo": pay_to,
+            "resource": "/premium",
+            "description": "",
+            "mimeType": "application/json",
+            "maxTimeoutSeconds": 60,
+            "extra": { "feePayer": "11111111111111111111111111111111" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tenants_by_api_key() {
+        let tenants = Tenants::from_specs(specs(serde_json::json!([
+            { "id": "acme", "api_keys": ["acme-1", "acme-2"] },
+            { "id": "globex", "api_keys": ["globex-1"], "rate_limits": {
+                "verify": { "per_second": 5, "burst_size": 10 },
+                "settle": { "per_second": 1, "burst_size": 2 }
+            } }
+        ])))
+        .unwrap();
+
+        assert_eq!(tenants.len(), 2);
+        assert_eq!(tenants.get("acme-2").unwrap().id, "acme");
+        assert!(tenants.get("acme-2").unwrap().rate_limiter.is_none());
+        assert!(tenants.get("globex-1").unwrap().rate_limiter.is_some());
+        assert!(tenants.get("unknown").is_none());
+        assert!(Tenants::default().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_ids_and_keys_rejected() {
+        let duplicate_id = specs(serde_json::json!([
+            { "id": "acme", "api_keys": ["a"] },
+            { "id": "acme", "api_keys": ["b"] }
+        ]));
+        assert!(Tenants::from_specs(duplicate_id).is_err());
+
+        let shared_key = specs(serde_json::json!([
+            { "id": "acme", "api_keys": ["a"] },
+            { "id": "globex", "api_keys": ["a"] }
+        ]));
+        assert!(Tenants::from_specs(shared_key).is_err());
+
+        let no_key = specs(serde_json::json!([{ "id": "acme", "api_keys": [] }]));
+        assert!(Tenants::from_specs(no_key).is_err());
+    }
+
+    #[test]
+    fn test_policy() {
+        let tenants = Tenants::from_specs(specs(serde_json::json!([{
+            "id": "acme",
+            "api_keys": ["a"],
+            "allowed_mints": ["EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"],
+            "pay_to": ["9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin"]
+        }])))
+        .unwrap();
+        let tenant = tenants.get("a").unwrap();
+
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let merchant = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+        assert!(tenant.check(&requirements(usdc, merchant)).is_ok());
+        assert!(matches!(
+            tenant.check(&requirements("So11111111111111111111111111111111111111112", merchant)),
+            Err(VerificationError::UnexpectedError(_))
+        ));
+        assert!(matches!(
+            tenant.check(&requirements(usdc, "11111111111111111111111111111111")),
+            Err(VerificationError::PayToNotAllowed)
+        ));
+    }
+}
@@ -1,8 +1,16 @@
-use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 use utoipa::ToSchema;
 
+use crate::error::VerificationError;
+
+/// HTTP header carrying the base64-encoded JSON [`PaymentPayload`]
+pub const PAYMENT_HEADER: &str = "x-payment";
+
 /// Payment payload sent by client in X-PAYMENT header
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify_next::Tsify))]
 #[serde(rename_all = "camelCase")]
 pub struct PaymentPayload {
     /// x402 protocol version (currently 1)
@@ -21,21 +29,69 @@ pub struct PaymentPayload {
     pub payload: SvmPayload,
     
     /// Unix timestamp when payment was created (optional, for expiry validation)
+    #[cfg_attr(target_arch = "wasm32", tsify(optional))]
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schema(example = 1699000000)]
     pub timestamp: Option<u64>,
 }
 
+impl PaymentPayload {
+    /// Decode an `X-PAYMENT` header value (base64-encoded JSON)
+    pub fn from_header(value: &str) -> Result<Self, String> {
+        let bytes = general_purpose::STANDARD
+            .decode(value.trim())
+            .map_err(|_| "X-PAYMENT header is not valid base64".to_string())?;
+        serde_json::from_slice(&bytes).map_err(|e| format!("Invalid X-PAYMENT payload: {}", e))
+    }
+
+    /// Check the payload's `timestamp` at `now` (Unix seconds): at most
+    /// `max_age_seconds` old and not in the future, both give or take
+    /// `skew_seconds` for clocks that disagree. Passes without a timestamp.
+    pub fn check_timestamp(&self, now: u64, max_age_seconds: u64, skew_seconds: u64) -> Result<(), VerificationError> {
+        let Some(timestamp) = self.timestamp else {
+            return Ok(());
+        };
+
+        if timestamp > now.saturating_add(skew_seconds) {
+            return Err(VerificationError::TimestampInFuture);
+        }
+        if now.saturating_sub(timestamp) > max_age_seconds.saturating_add(skew_seconds) {
+            return Err(VerificationError::PaymentExpired);
+        }
+        Ok(())
+    }
+}
+
+/// A [`PaymentPayload`] object, or the `X-PAYMENT` header value encoding one
+fn payload_or_header<'de, D: Deserializer<'de>>(deserializer: D) -> Result<PaymentPayload, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(header) => PaymentPayload::from_header(&header).map_err(D::Error::custom),
+        value => serde_json::from_value(value).map_err(D::Error::custom),
+    }
+}
+
+/// A transaction in standard base64, whatever encoding it was sent in
+///
+/// Undecodable strings are kept as is, for verification to reject.
+fn normalized_transaction<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    Ok(crate::solana::decoder::normalize_transaction_encoding(&encoded).unwrap_or(encoded))
+}
+
 /// Solana-specific payload containing the partially-signed transaction
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify_next::Tsify))]
 pub struct SvmPayload {
-    /// Base64-encoded partially-signed Solana transaction
+    /// Base64-encoded partially-signed Solana transaction (base64url and
+    /// base58 are accepted and converted to base64)
+    #[serde(deserialize_with = "normalized_transaction")]
     #[schema(example = "AQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABAAEDArczbMia1tLmq7zz4DinMNN0pJ1JtLdqIJPUw3YrGCzYAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABAgIAAQwCAAAAKgAAAAAAAAA=")]
     pub transaction: String,
 }
 
 /// Payment requirements sent by resource server
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify_next::Tsify))]
 #[serde(rename_all = "camelCase")]
 pub struct PaymentRequirements {
     /// Payment scheme (must match payload scheme)
@@ -75,6 +131,7 @@ pub struct PaymentRequirements {
     pub max_timeout_seconds: u64,
     
     /// Optional output schema
+    #[cfg_attr(target_arch = "wasm32", tsify(optional, type = "unknown"))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_schema: Option<serde_json::Value>,
     
@@ -82,31 +139,178 @@ pub struct PaymentRequirements {
     pub extra: ExtraFields,
 }
 
+impl PaymentRequirements {
+    /// Oldest payment accepted for these requirements, in seconds:
+    /// `max_timeout_seconds`, capped at the facilitator's `max_seconds`
+    /// (which also applies when the timeout is 0)
+    pub fn expiry_seconds(&self, max_seconds: u64) -> u64 {
+        match self.max_timeout_seconds {
+            0 => max_seconds,
+            timeout => timeout.min(max_seconds),
+        }
+    }
+}
+
 /// Extra fields in payment requirements (contains fee payer)
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, ToSchema)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify_next::Tsify))]
 #[serde(rename_all = "camelCase")]
 pub struct ExtraFields {
     /// Fee payer public key
     #[schema(example = "FeePayerPublicKeyHere123456789")]
     pub fee_payer: String,
+
+    /// Any other keys, for the scheme's verifier (e.g. a nonce or order id)
+    #[serde(flatten)]
+    pub other: BTreeMap<String, serde_json::Value>,
+}
+
+impl ExtraFields {
+    pub fn new(fee_payer: impl Into<String>) -> Self {
+        Self { fee_payer: fee_payer.into(), other: BTreeMap::new() }
+    }
+
+    /// Value of a key besides `feePayer`
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.other.get(key)
+    }
+
+    /// A string the scheme requires, as `invalid_extra` if missing
+    pub fn require_str(&self, key: &str) -> Result<&str, VerificationError> {
+        self.get(key)
+            .and_then(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| VerificationError::InvalidExtra(format!("Set extra.{} to a non-empty string", key)))
+    }
 }
 
 /// Request to /verify endpoint
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct VerifyRequest {
-    /// Payment payload from client
+    /// Payment payload from client (or the `X-PAYMENT` header value as is)
+    #[serde(deserialize_with = "payload_or_header")]
     pub payment_payload: PaymentPayload,
     
     /// Payment requirements from server
     pub payment_requirements: PaymentRequirements,
+
+    /// Verify without side effects: the transaction isn't marked as seen,
+    /// and no alerting metrics, audit events or webhooks are produced
+    #[serde(default, rename = "dryRun", skip_serializing_if = "std::ops::Not::not")]
+    #[schema(example = false)]
+    pub dry_run: bool,
+}
+
+/// Item of a /verify/batch or /verify/stream request
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct BatchVerifyRequest {
+    /// Caller's reference for the payment, echoed in its result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "order-1042")]
+    pub id: Option<String>,
+
+    #[serde(flatten)]
+    pub request: VerifyRequest,
+}
+
+impl From<VerifyRequest> for BatchVerifyRequest {
+    fn from(request: VerifyRequest) -> Self {
+        Self { id: None, request }
+    }
 }
 
 /// Request to /settle endpoint
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct SettleRequest {
-    /// Payment payload from client
+    /// Payment payload from client (or the `X-PAYMENT` header value as is)
+    #[serde(deserialize_with = "payload_or_header")]
     pub payment_payload: PaymentPayload,
     
     /// Payment requirements from server
     pub payment_requirements: PaymentRequirements,
 }
+
+/// Request to /verify/header endpoint
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct HeaderVerifyRequest {
+    /// `X-PAYMENT` header value; read from the request's own `X-PAYMENT`
+    /// header when omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "eyJ4NDAyVmVyc2lvbiI6MSwic2NoZW1lIjoiZXhhY3QiLC4uLn0=")]
+    pub payment_header: Option<String>,
+
+    /// Payment requirements from server
+    pub payment_requirements: PaymentRequirements,
+
+    /// Verify without side effects (see [`VerifyRequest::dry_run`])
+    #[serde(default, rename = "dryRun", skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_bounded_by_facilitator_max() {
+        let mut requirements: PaymentRequirements = serde_json::from_value(serde_json::json!({
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "maxAmountRequired": "1000",
+            "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "payTo": "11111111111111111111111111111111",
+            "resource": "/premium",
+            "description": "",
+            "mimeType": "application/json",
+            "maxTimeoutSeconds": 60,
+            "extra": { "feePayer": "11111111111111111111111111111111" }
+        }))
+        .unwrap();
+
+        assert_eq!(requirements.expiry_seconds(600), 60);
+        requirements.max_timeout_seconds = 3600;
+        assert_eq!(requirements.expiry_seconds(600), 600);
+        requirements.max_timeout_seconds = 0;
+        assert_eq!(requirements.expiry_seconds(600), 600);
+    }
+
+    #[test]
+    fn test_extra_keeps_unknown_keys() {
+        let extra: ExtraFields = serde_json::from_value(serde_json::json!({
+            "feePayer": "11111111111111111111111111111111",
+            "nonce": "7f3a",
+            "orderId": 1042
+        }))
+        .unwrap();
+
+        assert_eq!(extra.fee_payer, "11111111111111111111111111111111");
+        assert_eq!(extra.require_str("nonce").unwrap(), "7f3a");
+        assert!(matches!(extra.require_str("orderId"), Err(VerificationError::InvalidExtra(_))));
+        assert!(matches!(extra.require_str("missing"), Err(VerificationError::InvalidExtra(_))));
+
+        // Serialized back as they came
+        let json = serde_json::to_value(&extra).unwrap();
+        assert_eq!(json["nonce"], "7f3a");
+        assert_eq!(json["orderId"], 1042);
+    }
+
+    #[test]
+    fn test_timestamp_within_skew() {
+        let payload = |timestamp: Option<u64>| PaymentPayload {
+            x402_version: 1,
+            scheme: "exact".to_string(),
+            network: "solana-devnet".to_string(),
+            payload: SvmPayload { transaction: String::new() },
+            timestamp,
+        };
+        let check = |timestamp| payload(Some(timestamp)).check_timestamp(10_000, 60, 30);
+
+        assert!(payload(None).check_timestamp(10_000, 60, 30).is_ok());
+        assert!(check(10_000).is_ok());
+        // Up to the skew ahead, and the skew past expiry
+        assert!(check(10_030).is_ok());
+        assert!(matches!(check(10_031), Err(VerificationError::TimestampInFuture)));
+        assert!(check(9_910).is_ok());
+        assert!(matches!(check(9_909), Err(VerificationError::PaymentExpired)));
+    }
+}
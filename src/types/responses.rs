@@ -3,6 +3,7 @@ use utoipa::ToSchema;
 
 /// Response from /verify endpoint
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify_next::Tsify))]
 #[serde(rename_all = "camelCase")]
 pub struct VerifyResponse {
     /// Whether the payment is valid
@@ -10,14 +11,203 @@ pub struct VerifyResponse {
     pub is_valid: bool,
     
     /// Reason if invalid
+    #[cfg_attr(target_arch = "wasm32", tsify(optional))]
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schema(example = "invalid_amount")]
     pub invalid_reason: Option<String>,
     
     /// Payer public key if valid
+    #[cfg_attr(target_arch = "wasm32", tsify(optional))]
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schema(example = "PayerPublicKey123456789")]
     pub payer: Option<String>,
+
+    /// Checks not performed because the facilitator verifies offline
+    #[cfg_attr(target_arch = "wasm32", tsify(optional))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schema(example = json!(["mint_exists", "source_ata_exists"]))]
+    pub checks_skipped: Vec<String>,
+
+    /// Every failed check, in diagnostic mode (`?mode=diagnostic`)
+    #[cfg_attr(target_arch = "wasm32", tsify(optional))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<CheckFailure>,
+
+    /// What the transaction pays, if valid
+    #[cfg_attr(target_arch = "wasm32", tsify(optional))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<PaymentDetails>,
+}
+
+/// A valid payment, as parsed from its transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify_next::Tsify))]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentDetails {
+    /// Amount transferred, in the asset's base units
+    #[schema(example = "1000000")]
+    pub amount: String,
+
+    /// Mint of the asset
+    #[schema(example = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v")]
+    pub asset: String,
+
+    /// Token account receiving the payment (the `payTo` associated token account)
+    #[schema(example = "DestinationAta123456789")]
+    pub destination: String,
+
+    /// Wallet paying
+    #[schema(example = "PayerPublicKey123456789")]
+    pub payer: String,
+
+    /// Wallet paying the transaction fee
+    #[schema(example = "FeePayerPublicKey123456789")]
+    pub fee_payer: String,
+
+    /// Whether the transaction creates the destination token account
+    #[schema(example = false)]
+    pub creates_ata: bool,
+
+    /// Network fee of the transaction, in lamports
+    #[schema(example = 10000)]
+    pub estimated_fee_lamports: u64,
+}
+
+/// A check that failed in diagnostic verification
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify_next::Tsify))]
+pub struct CheckFailure {
+    /// Name of the check
+    #[schema(example = "compute_price")]
+    pub check: String,
+
+    /// Error code, as reported in `invalidReason`
+    #[schema(example = "invalid_exact_svm_payload_transaction_instructions_compute_price_instruction_too_high")]
+    pub code: String,
+
+    /// How to fix it
+    #[schema(example = "Lower the compute unit price to at most 5 lamports per compute unit")]
+    pub hint: String,
+}
+
+impl CheckFailure {
+    pub fn new(check: &str, error: &crate::error::VerificationError) -> Self {
+        Self {
+            check: check.to_string(),
+            code: error.as_str().to_string(),
+            hint: error.hint(),
+        }
+    }
+}
+
+/// One result line of /verify/stream
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StreamedVerifyResponse {
+    /// Position of the request in the stream (blank lines aren't counted)
+    #[schema(example = 0)]
+    pub index: usize,
+
+    /// `id` of the request, if it had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "order-1042")]
+    pub id: Option<String>,
+
+    #[serde(flatten)]
+    pub response: VerifyResponse,
+}
+
+/// Response from /verify/batch with `?envelope=true`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchVerifyResponse {
+    /// One result per request, in request order
+    pub results: Vec<BatchVerifyResult>,
+
+    pub summary: BatchSummary,
+}
+
+/// Result of one payment of a batch
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BatchVerifyResult {
+    /// Position of the request in the batch
+    #[schema(example = 0)]
+    pub index: usize,
+
+    /// `id` of the request, if it had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "order-1042")]
+    pub id: Option<String>,
+
+    #[serde(flatten)]
+    pub response: VerifyResponse,
+
+    /// Why the payment couldn't be verified (it's neither valid nor invalid)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "verification task panicked")]
+    pub error: Option<String>,
+}
+
+/// Outcome counts of a batch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct BatchSummary {
+    #[schema(example = 3)]
+    pub total: usize,
+    /// Valid payments
+    #[schema(example = 1)]
+    pub valid: usize,
+    /// Payments rejected by a check
+    #[schema(example = 1)]
+    pub invalid: usize,
+    /// Payments that couldn't be verified
+    #[schema(example = 1)]
+    pub errors: usize,
+}
+
+impl BatchSummary {
+    pub fn of(results: &[BatchVerifyResult]) -> Self {
+        let mut summary = Self { total: results.len(), ..Self::default() };
+        for result in results {
+            if result.error.is_some() {
+                summary.errors += 1;
+            } else if result.response.is_valid {
+                summary.valid += 1;
+            } else {
+                summary.invalid += 1;
+            }
+        }
+        summary
+    }
+}
+
+/// Response from /validate endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidateResponse {
+    /// Whether every field is valid
+    #[schema(example = false)]
+    pub is_valid: bool,
+
+    /// One entry per checked field, in the order they were checked
+    pub fields: Vec<FieldReport>,
+}
+
+/// Validation result of one field of a payment
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FieldReport {
+    /// Path of the field in the request
+    #[schema(example = "payment_payload.payload.transaction.instructions[1]")]
+    pub field: String,
+
+    pub valid: bool,
+
+    /// Error code if invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "invalid_exact_svm_payload_transaction_instructions_compute_price_instruction_too_high")]
+    pub code: Option<String>,
+
+    /// What's wrong and how to fix it, if invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "Lower the compute unit price to at most 5 lamports per compute unit")]
+    pub message: Option<String>,
 }
 
 /// Response from /settle endpoint
@@ -45,6 +235,27 @@ pub struct SettleResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schema(example = "transaction_failed")]
     pub error_reason: Option<String>,
+    
+    /// Slot the transaction landed in, if successful
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 312345678)]
+    pub slot: Option<u64>,
+
+    /// Unix time of the block the transaction landed in (the settlement
+    /// time), if the node reported it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = 1700000000)]
+    pub block_time: Option<i64>,
+
+    /// Commitment level the transaction had reached when confirmed
+    /// (`confirmed` or `finalized`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "confirmed")]
+    pub commitment: Option<String>,
+
+    /// Receipt signed by the facilitator if successful
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<crate::receipts::SignedReceipt>,
 }
 
 /// Response from /supported endpoint
@@ -65,3 +276,105 @@ pub struct SchemeSupport {
     #[schema(example = json!(["solana-devnet", "solana", "solana-testnet"]))]
     pub networks: Vec<String>,
 }
+
+/// Response from /quote
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteResponse {
+    #[schema(example = "exact")]
+    pub scheme: String,
+
+    #[schema(example = "solana-devnet")]
+    pub network: String,
+
+    /// Token mint to be paid in
+    #[schema(example = "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU")]
+    pub asset: String,
+
+    /// Amount quoted for, in the asset's base units
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "1000000")]
+    pub amount: Option<String>,
+
+    /// Fee payer to set as `extra.feePayer`
+    #[schema(example = "FeePayerPublicKey123456789")]
+    pub fee_payer: String,
+
+    /// Highest compute unit price accepted, in micro-lamports
+    #[schema(example = 5000000)]
+    pub max_compute_unit_price: u64,
+
+    /// Compute unit price the facilitator's own builder uses, in micro-lamports
+    #[schema(example = 1)]
+    pub compute_unit_price: u64,
+
+    /// Network fee of a plain transfer at that price, in lamports
+    #[schema(example = 10001)]
+    pub expected_fee_lamports: u64,
+
+    /// Network fee when the transaction also creates the recipient's token account
+    #[schema(example = 10001)]
+    pub expected_fee_with_ata_lamports: u64,
+
+    /// Whether the fee payer may currently fund the recipient's token account
+    #[schema(example = false)]
+    pub ata_creation_subsidized: bool,
+
+    /// Longest `maxTimeoutSeconds` honored (payments expire after the shorter)
+    #[schema(example = 600)]
+    pub max_timeout_seconds: u64,
+
+    /// Tolerance for client clocks on payment timestamps
+    #[schema(example = 30)]
+    pub clock_skew_seconds: u64,
+}
+
+/// Response from /pubkey
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PubkeyResponse {
+    /// Key signing responses and receipts (the facilitator's fee payer)
+    #[schema(example = "FeePayerPublicKey123456789")]
+    pub pubkey: String,
+
+    /// Whether verify and settle responses carry `X-Facilitator-Signature`
+    #[schema(example = true)]
+    pub signs_responses: bool,
+
+    /// Prefix of every signed response message, hex-encoded
+    #[schema(example = "ff7834303220726573706f6e73652076310a")]
+    pub response_domain: String,
+}
+
+/// Response from /settle/{signature}/status
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementStatusResponse {
+    /// Transaction signature
+    #[schema(example = "5j7s6NiJS3JAkvgkoc18WVAsiSaci2pxB2A6ueCJP4tprA2TFg9wSyTLeYouxPBJEMzJinENTkpA52YStRW5Dia7")]
+    pub signature: String,
+
+    /// On-chain commitment (`processed`, `confirmed` or `finalized`); absent
+    /// when the cluster doesn't know the transaction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "finalized")]
+    pub confirmation_status: Option<String>,
+
+    /// Slot the transaction landed in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 312345678)]
+    pub slot: Option<u64>,
+
+    /// Blocks confirming the transaction (absent once finalized)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmations: Option<usize>,
+
+    /// Transaction error, if it failed on chain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Settlement recorded by this facilitator, when history is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub settlement: Option<crate::settlements::SettlementRecord>,
+}
@@ -0,0 +1,391 @@
+// HashiCorp Vault secret backend
+// Fetches the fee payer key and webhook secret from a Vault KV secret so they
+// don't have to be stored as plaintext env vars in deployment manifests.
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+use crate::solana::signer::load_keypair_from_base58;
+
+/// How the facilitator authenticates to Vault
+#[derive(Clone, Debug)]
+pub enum VaultAuth {
+    /// Static token (`VAULT_TOKEN`)
+    Token(String),
+    /// AppRole login (`VAULT_ROLE_ID` + `VAULT_SECRET_ID`)
+    AppRole { role_id: String, secret_id: String },
+}
+
+/// Vault connection settings
+#[derive(Clone, Debug)]
+pub struct VaultConfig {
+    /// Vault address, e.g. https://vault.internal:8200
+    pub addr: String,
+    /// Authentication method
+    pub auth: VaultAuth,
+    /// Secret path below /v1/, e.g. `secret/data/x402-facilitator` (KV v2)
+    pub secret_path: String,
+    /// Optional Vault Enterprise namespace
+    pub namespace: Option<String>,
+    /// Field holding the base58 fee payer private key
+    pub fee_payer_key_field: String,
+    /// Field holding the webhook HMAC secret
+    pub webhook_secret_field: String,
+    /// How often to re-read secrets (0 disables refresh)
+    pub refresh_seconds: u64,
+}
+
+impl VaultConfig {
+    /// Load Vault configuration from environment
+    ///
+    /// Returns `None` when `VAULT_ADDR` is not set.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(addr) = std::env::var("VAULT_ADDR") else {
+            return Ok(None);
+        };
+
+        let auth = match (
+            std::env::var("VAULT_TOKEN"),
+            std::env::var("VAULT_ROLE_ID"),
+            std::env::var("VAULT_SECRET_ID"),
+        ) {
+            (Ok(token), _, _) => VaultAuth::Token(token),
+            (_, Ok(role_id), Ok(secret_id)) => VaultAuth::AppRole { role_id, secret_id },
+            _ => anyhow::bail!("VAULT_ADDR is set but neither VAULT_TOKEN nor VAULT_ROLE_ID/VAULT_SECRET_ID are"),
+        };
+
+        Ok(Some(Self {
+            addr: addr.trim_end_matches('/').to_string(),
+            auth,
+            secret_path: std::env::var("VAULT_SECRET_PATH")
+                .unwrap_or_else(|_| "secret/data/x402-facilitator".to_string()),
+            namespace: std::env::var("VAULT_NAMESPACE").ok(),
+            fee_payer_key_field: std::env::var("VAULT_FEE_PAYER_KEY_FIELD")
+                .unwrap_or_else(|_| "fee_payer_private_key".to_string()),
+            webhook_secret_field: std::env::var("VAULT_WEBHOOK_SECRET_FIELD")
+                .unwrap_or_else(|_| "webhook_secret".to_string()),
+            refresh_seconds: std::env::var("VAULT_REFRESH_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300),
+        }))
+    }
+}
+
+/// Secrets resolved from Vault
+#[derive(Clone, Default, PartialEq)]
+pub struct VaultSecrets {
+    pub fee_payer_private_key: Option<String>,
+    pub webhook_secret: Option<String>,
+}
+
+impl std::fmt::Debug for VaultSecrets {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultSecrets")
+            .field("fee_payer_private_key", &self.fee_payer_private_key.as_ref().map(|_| "[REDACTED]"))
+            .field("webhook_secret", &self.webhook_secret.as_ref().map(|_| "[REDACTED]"))
+            .finish()
+    }
+}
+
+/// Minimal Vault HTTP client (KV v1/v2 reads, token and AppRole auth)
+pub struct VaultClient {
+    config: VaultConfig,
+    client: Client,
+    token: Mutex<Option<String>>,
+}
+
+impl VaultClient {
+    pub fn new(config: VaultConfig) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| anyhow!("Failed to build Vault HTTP client: {}", e))?;
+
+        let token = match &config.auth {
+            VaultAuth::Token(token) => Some(token.clone()),
+            VaultAuth::AppRole { .. } => None,
+        };
+
+        Ok(Self {
+            config,
+            client,
+            token: Mutex::new(token),
+        })
+    }
+
+    pub fn config(&self) -> &VaultConfig {
+        &self.config
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let mut request = self
+            .client
+            .request(method, format!("{}/v1/{}", self.config.addr, path.trim_start_matches('/')));
+
+        if let Some(namespace) = &self.config.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+
+        request
+    }
+
+    /// Log in via AppRole and return a client token
+    async fn approle_login(&self, role_id: &str, secret_id: &str) -> Result<String> {
+        let response: Value = self
+            .request(reqwest::Method::POST, "auth/approle/login")
+            .json(&json!({ "role_id": role_id, "secret_id": secret_id }))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Vault AppRole login failed: {}", e))?
+            .error_for_status()
+            .map_err(|e| anyhow!("Vault AppRole login rejected: {}", e))?
+            .json()
+            .await
+            .map_err(|e| anyhow!("Invalid Vault login response: {}", e))?;
+
+        response["auth"]["client_token"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Vault login response missing auth.client_token"))
+    }
+
+    /// Current token, logging in first if needed
+    async fn token(&self, force_login: bool) -> Result<String> {
+        let mut token = self.token.lock().await;
+
+        if let VaultAuth::AppRole { role_id, secret_id } = &self.config.auth {
+            if force_login || token.is_none() {
+                *token = Some(self.approle_login(role_id, secret_id).await?);
+            }
+        }
+
+        token
+            .clone()
+            .ok_or_else(|| anyhow!("No Vault token available"))
+    }
+
+    /// Read the configured secret and extract facilitator secrets
+    pub async fn fetch_secrets(&self) -> Result<VaultSecrets> {
+        let mut response = self.read_secret(false).await?;
+
+        // AppRole tokens expire; log in again once on permission errors
+        if response.status() == reqwest::StatusCode::FORBIDDEN
+            && matches!(self.config.auth, VaultAuth::AppRole { .. })
+        {
+            response = self.read_secret(true).await?;
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("Vault read of {} failed: HTTP {}", self.config.secret_path, status);
+        }
+
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Invalid Vault secret response: {}", e))?;
+
+        let data = extract_secret_data(&body);
+
+        Ok(VaultSecrets {
+            fee_payer_private_key: data.get(&self.config.fee_payer_key_field).cloned(),
+            webhook_secret: data.get(&self.config.webhook_secret_field).cloned(),
+        })
+    }
+
+    async fn read_secret(&self, force_login: bool) -> Result<reqwest::Response> {
+        let token = self.token(force_login).await?;
+
+        self.request(reqwest::Method::GET, &self.config.secret_path)
+            .header("X-Vault-Token", token)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Vault request failed: {}", e))
+    }
+
+    /// Fetch secrets and export them as the env vars `Config::from_env` reads
+    ///
+    /// Must be called before `Config::from_env`.
+    pub async fn load_into_env(&self) -> Result<VaultSecrets> {
+        let secrets = self.fetch_secrets().await?;
+
+        if let Some(key) = &secrets.fee_payer_private_key {
+            std::env::set_var("FEE_PAYER_PRIVATE_KEY", key);
+        }
+        if let Some(secret) = &secrets.webhook_secret {
+            std::env::set_var("WEBHOOK_SECRET", secret);
+        }
+
+        tracing::info!(
+            "🔐 Loaded secrets from Vault ({}): fee_payer_key={}, webhook_secret={}",
+            self.config.secret_path,
+            secrets.fee_payer_private_key.is_some(),
+            secrets.webhook_secret.is_some()
+        );
+
+        Ok(secrets)
+    }
+}
+
+/// Extract string fields from a KV v2 (`data.data`) or KV v1 (`data`) response
+fn extract_secret_data(body: &Value) -> HashMap<String, String> {
+    let data = match body["data"].get("data") {
+        Some(inner) if inner.is_object() => inner,
+        _ => &body["data"],
+    };
+
+    data.as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Apply refreshed secrets to the running configuration
+///
+/// Returns the names of settings that changed.
+pub fn apply_secrets(config: &Config, previous: &VaultSecrets, current: &VaultSecrets) -> Result<Vec<&'static str>> {
+    let mut changed = Vec::new();
+
+    if current.fee_payer_private_key != previous.fee_payer_private_key {
+        if let Some(key) = &current.fee_payer_private_key {
+            config.fee_payer_signer.rotate(load_keypair_from_base58(key)?)?;
+            changed.push("fee_payer_private_key");
+        }
+    }
+
+    if current.webhook_secret != previous.webhook_secret {
+        if let (Some(secret), Some(webhook)) = (&current.webhook_secret, &config.webhook) {
+            webhook.secret.set(secret.clone());
+            changed.push("webhook_secret");
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Periodically re-read secrets from Vault and rotate them in place
+pub fn spawn_refresh(client: VaultClient, initial: VaultSecrets, config: Config) {
+    let interval = client.config().refresh_seconds;
+    if interval == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        ticker.tick().await;
+        let mut previous = initial;
+
+        loop {
+            ticker.tick().await;
+
+            let current = match client.fetch_secrets().await {
+                Ok(secrets) => secrets,
+                Err(e) => {
+                    tracing::warn!("⚠️  Vault refresh failed, keeping current secrets: {}", e);
+                    continue;
+                }
+            };
+
+            match apply_secrets(&config, &previous, &current) {
+                Ok(changed) => {
+                    for setting in changed {
+                        tracing::info!("🔄 Rotated {} from Vault", setting);
+                        config.audit_logger.log_config_changed(
+                            setting,
+                            json!({ "source": "vault", "path": client.config().secret_path }),
+                        );
+                    }
+                    previous = current;
+                }
+                Err(e) => tracing::error!("❌ Failed to apply Vault secrets: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+
+    fn test_config(addr: String, auth: VaultAuth) -> VaultConfig {
+        VaultConfig {
+            addr,
+            auth,
+            secret_path: "secret/data/x402".to_string(),
+            namespace: None,
+            fee_payer_key_field: "fee_payer_private_key".to_string(),
+            webhook_secret_field: "webhook_secret".to_string(),
+            refresh_seconds: 0,
+        }
+    }
+
+    #[test]
+    fn test_extract_kv_v2_and_v1() {
+        let v2 = json!({ "data": { "data": { "webhook_secret": "s2" }, "metadata": { "version": 3 } } });
+        assert_eq!(extract_secret_data(&v2).get("webhook_secret").unwrap(), "s2");
+
+        let v1 = json!({ "data": { "webhook_secret": "s1", "ttl": 60 } });
+        let fields = extract_secret_data(&v1);
+        assert_eq!(fields.get("webhook_secret").unwrap(), "s1");
+        assert!(!fields.contains_key("ttl"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_secrets_with_token() {
+        let server = MockServer::start_async().await;
+        let mock = server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v1/secret/data/x402")
+                    .header("X-Vault-Token", "root");
+                then.status(200).json_body(json!({
+                    "data": { "data": { "fee_payer_private_key": "abc", "webhook_secret": "whsec" } }
+                }));
+            })
+            .await;
+
+        let client = VaultClient::new(test_config(server.base_url(), VaultAuth::Token("root".into()))).unwrap();
+        let secrets = client.fetch_secrets().await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(secrets.fee_payer_private_key.as_deref(), Some("abc"));
+        assert_eq!(secrets.webhook_secret.as_deref(), Some("whsec"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_secrets_with_approle() {
+        let server = MockServer::start_async().await;
+        server
+            .mock_async(|when, then| {
+                when.method(POST).path("/v1/auth/approle/login");
+                then.status(200).json_body(json!({ "auth": { "client_token": "approle-token" } }));
+            })
+            .await;
+        server
+            .mock_async(|when, then| {
+                when.method(GET)
+                    .path("/v1/secret/data/x402")
+                    .header("X-Vault-Token", "approle-token");
+                then.status(200).json_body(json!({ "data": { "data": { "webhook_secret": "whsec" } } }));
+            })
+            .await;
+
+        let auth = VaultAuth::AppRole { role_id: "role".into(), secret_id: "secret".into() };
+        let client = VaultClient::new(test_config(server.base_url(), auth)).unwrap();
+        let secrets = client.fetch_secrets().await.unwrap();
+
+        assert_eq!(secrets.fee_payer_private_key, None);
+        assert_eq!(secrets.webhook_secret.as_deref(), Some("whsec"));
+    }
+}
@@ -3,9 +3,54 @@
 
 #![cfg(target_arch = "wasm32")]
 
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{de::DeserializeOwned, Deserialize};
+use solana_sdk::{account::Account, hash::Hash, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::str::FromStr;
 use wasm_bindgen::prelude::*;
-use crate::types::{requests::PaymentPayload, requests::PaymentRequirements, responses::VerifyResponse};
-use crate::solana::decoder::decode_transaction_from_base64;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use crate::cache::MintInfo;
+use crate::error::VerificationError;
+use crate::types::requests::{PaymentPayload, PaymentRequirements, SvmPayload};
+use crate::types::responses::VerifyResponse;
+use crate::solana::builder::{build_payment_transaction, PaymentTransaction};
+use crate::solana::decoder::{
+    decode_versioned_transaction_from_base64, encode_transaction_to_base64, to_legacy_transaction,
+};
+use crate::solana::verifier::{
+    account_checks, parse_transfer_instruction, spl_token_2022_program_id, spl_token_program_id, verify_compute_limit_instruction,
+    verify_compute_price_instruction, verify_create_ata_funder, verify_create_ata_instruction, verify_fee_payer_position,
+    verify_fee_payer_safety, verify_instruction_count, verify_message_header, verify_signer_count, TransferAccounts,
+    DEFAULT_REQUIRED_SIGNERS,
+};
+
+// TypeScript names for the JS values crossing the boundary; the interfaces
+// themselves are generated from the Rust types by tsify
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "PaymentPayload")]
+    pub type JsPaymentPayload;
+
+    #[wasm_bindgen(typescript_type = "PaymentPayload[]")]
+    pub type JsPaymentPayloadArray;
+
+    #[wasm_bindgen(typescript_type = "PaymentRequirements")]
+    pub type JsPaymentRequirements;
+
+    #[wasm_bindgen(typescript_type = "VerifyResponse")]
+    pub type JsVerifyResponse;
+
+    #[wasm_bindgen(typescript_type = "VerifyResponse[]")]
+    pub type JsVerifyResponseArray;
+
+    #[wasm_bindgen(typescript_type = "Promise<VerifyResponse>")]
+    pub type JsVerifyResponsePromise;
+
+    #[wasm_bindgen(typescript_type = "Promise<string>")]
+    pub type JsStringPromise;
+}
 
 /// Initialize panic hook for better debugging in the browser
 #[wasm_bindgen(start)]
@@ -31,7 +76,7 @@ pub fn init_wasm() {
 /// const verifier = WasmVerifier.new();
 /// 
 /// const result = verifier.verify(payment, requirements);
-/// console.log(result.is_valid);
+/// console.log(result.isValid);
 /// ```
 #[wasm_bindgen]
 pub struct WasmVerifier {
@@ -49,19 +94,21 @@ impl WasmVerifier {
     /// Verify a payment from JavaScript objects
     /// 
     /// This performs client-side verification without any network calls.
-    /// Suitable for basic validation before submitting to a facilitator.
+    /// Runs the server's instruction checks (compute budget, fee payer
+    /// safety, transfer layout) on legacy and v0 transactions; `payer` is
+    /// the authority of the transfer.
     /// 
     /// # Parameters
     /// - `payment_js`: JavaScript object matching PaymentPayload schema
     /// - `requirements_js`: JavaScript object matching PaymentRequirements schema
     /// 
     /// # Returns
-    /// JavaScript object with `is_valid`, `invalid_reason`, and `payer` fields
+    /// JavaScript object with `isValid`, `invalidReason`, and `payer` fields
     /// 
     /// # Example
     /// ```javascript
     /// const payment = {
-    ///   x402_version: 1,
+    ///   x402Version: 1,
     ///   scheme: "exact",
     ///   network: "solana-devnet",
     ///   payload: { transaction: "base64..." }
@@ -70,47 +117,103 @@ impl WasmVerifier {
     /// const requirements = {
     ///   scheme: "exact",
     ///   network: "solana-devnet",
-    ///   max_amount_required: "1000000",
+    ///   maxAmountRequired: "1000000",
     ///   // ... other fields
     /// };
     /// 
     /// const result = verifier.verify(payment, requirements);
-    /// if (result.is_valid) {
+    /// if (result.isValid) {
     ///   console.log(`Payer: ${result.payer}`);
     /// }
     /// ```
     #[wasm_bindgen]
-    pub fn verify(&self, payment_js: JsValue, requirements_js: JsValue) -> JsValue {
-        // Convert JS values to Rust types
-        let payment: PaymentPayload = match serde_wasm_bindgen::from_value(payment_js) {
-            Ok(p) => p,
-            Err(e) => {
-                return serde_wasm_bindgen::to_value(&VerifyResponse {
-                    is_valid: false,
-                    invalid_reason: Some(format!("Invalid payment format: {}", e)),
-                    payer: None,
-                })
-                .unwrap();
-            }
+    pub fn verify(&self, payment_js: JsPaymentPayload, requirements_js: JsPaymentRequirements) -> JsVerifyResponse {
+        // Convert JS values to Rust types, then perform WASM-safe verification
+        let result = match parse_request(payment_js.into(), requirements_js.into()) {
+            Ok((payment, requirements)) => verify_wasm_safe(&payment, &requirements),
+            Err(response) => response,
         };
 
-        let requirements: PaymentRequirements = match serde_wasm_bindgen::from_value(requirements_js) {
-            Ok(r) => r,
-            Err(e) => {
-                return serde_wasm_bindgen::to_value(&VerifyResponse {
-                    is_valid: false,
-                    invalid_reason: Some(format!("Invalid requirements format: {}", e)),
-                    payer: None,
-                })
-                .unwrap();
-            }
-        };
+        // Convert back to JS
+        serde_wasm_bindgen::to_value(&result).unwrap().unchecked_into()
+    }
+
+    /// Verify many payments against the same requirements in one call
+    /// 
+    /// Runs the same checks as `verify` for each payment, crossing the
+    /// JS/WASM boundary once for the whole batch.
+    /// 
+    /// # Parameters
+    /// - `payments_js`: Array of objects matching PaymentPayload schema
+    /// - `requirements_js`: JavaScript object matching PaymentRequirements schema
+    /// 
+    /// # Returns
+    /// Array of `{ isValid, invalidReason, payer }` objects, in payment order
+    /// 
+    /// # Example
+    /// ```javascript
+    /// const results = verifier.verify_batch(payments, requirements);
+    /// const valid = results.filter(r => r.isValid).length;
+    /// ```
+    #[wasm_bindgen]
+    pub fn verify_batch(&self, payments_js: JsPaymentPayloadArray, requirements_js: JsPaymentRequirements) -> JsVerifyResponseArray {
+        let requirements: Result<PaymentRequirements, _> = serde_wasm_bindgen::from_value(requirements_js.into())
+            .map_err(|e| format!("Invalid requirements format: {}", e));
 
-        // Perform WASM-safe verification
-        let result = verify_wasm_safe(&payment, &requirements);
+        let results: Vec<VerifyResponse> = js_sys::Array::from(&payments_js)
+            .iter()
+            .map(|payment_js| match (&requirements, serde_wasm_bindgen::from_value(payment_js)) {
+                (Err(reason), _) => invalid(reason.clone()),
+                (_, Err(e)) => invalid(format!("Invalid payment format: {}", e)),
+                (Ok(requirements), Ok(payment)) => verify_wasm_safe(&payment, requirements),
+            })
+            .collect();
 
-        // Convert back to JS
-        serde_wasm_bindgen::to_value(&result).unwrap()
+        serde_wasm_bindgen::to_value(&results).unwrap().unchecked_into()
+    }
+
+    /// Verify a payment including on-chain account checks
+    /// 
+    /// Runs the same checks as the server's `/verify` (except replay
+    /// detection and the mint allowlist): instruction layout, fee payer
+    /// safety and destination ATA, then - over Solana JSON-RPC with
+    /// `fetch` - that the mint exists with the transfer's decimals and that
+    /// the source (and, without a CreateATA instruction, destination) token
+    /// accounts exist.
+    /// 
+    /// # Parameters
+    /// - `payment_js`: JavaScript object matching PaymentPayload schema
+    /// - `requirements_js`: JavaScript object matching PaymentRequirements schema
+    /// - `rpc_url`: Solana JSON-RPC endpoint (must allow CORS)
+    /// 
+    /// # Returns
+    /// Promise resolving to an object with `isValid`, `invalidReason`, and
+    /// `payer` fields; RPC failures resolve with `isValid: false`
+    /// 
+    /// # Example
+    /// ```javascript
+    /// const result = await verifier.verify_full(payment, requirements, "https://api.devnet.solana.com");
+    /// if (!result.isValid) {
+    ///   console.log(`Rejected: ${result.invalidReason}`);
+    /// }
+    /// ```
+    #[wasm_bindgen]
+    pub fn verify_full(
+        &self,
+        payment_js: JsPaymentPayload,
+        requirements_js: JsPaymentRequirements,
+        rpc_url: String,
+    ) -> JsVerifyResponsePromise {
+        let request = parse_request(payment_js.into(), requirements_js.into());
+
+        future_to_promise(async move {
+            let result = match request {
+                Ok((payment, requirements)) => verify_with_rpc(&payment, &requirements, &rpc_url).await,
+                Err(response) => response,
+            };
+            Ok(serde_wasm_bindgen::to_value(&result)?)
+        })
+        .unchecked_into()
     }
 
     /// Get the library version
@@ -132,6 +235,399 @@ impl WasmVerifier {
     }
 }
 
+/// Builds x402 payment transactions for browser wallets
+/// 
+/// The transactions follow the layout the facilitator verifies (compute
+/// budget, optional CreateATA, TransferChecked), so a payload built here
+/// passes `/verify` once the wallet has signed it.
+/// 
+/// # Example (JavaScript, with @solana/web3.js and wallet-adapter)
+/// ```javascript
+/// const builder = new WasmPaymentBuilder(requirements);
+/// const unsigned = await builder.build_with_rpc(wallet.publicKey.toBase58(), rpcUrl);
+/// 
+/// const tx = Transaction.from(Buffer.from(unsigned, "base64"));
+/// const signed = await wallet.signTransaction(tx);
+/// const encoded = signed.serialize({ requireAllSignatures: false }).toString("base64");
+/// 
+/// const payment = builder.payment_payload(encoded);
+/// ```
+#[wasm_bindgen]
+pub struct WasmPaymentBuilder {
+    requirements: PaymentRequirements,
+}
+
+#[wasm_bindgen]
+impl WasmPaymentBuilder {
+    /// Create a builder for a JavaScript object matching PaymentRequirements
+    #[wasm_bindgen(constructor)]
+    pub fn new(requirements_js: JsPaymentRequirements) -> Result<WasmPaymentBuilder, JsError> {
+        let requirements: PaymentRequirements = serde_wasm_bindgen::from_value(requirements_js.into())
+            .map_err(|e| JsError::new(&format!("Invalid requirements format: {}", e)))?;
+        if requirements.scheme != "exact" {
+            return Err(JsError::new(&format!("Unsupported scheme: '{}'", requirements.scheme)));
+        }
+
+        Ok(Self { requirements })
+    }
+
+    /// Build the unsigned transaction (base64) from known mint details
+    /// 
+    /// # Parameters
+    /// - `payer`: Wallet address paying
+    /// - `recent_blockhash`: Base58 blockhash
+    /// - `decimals`: Decimals of the asset mint
+    /// - `token_2022`: Whether the asset is a Token-2022 mint
+    /// - `create_ata`: Whether the recipient's token account must be created
+    #[wasm_bindgen]
+    pub fn build(
+        &self,
+        payer: String,
+        recent_blockhash: String,
+        decimals: u8,
+        token_2022: bool,
+        create_ata: bool,
+    ) -> Result<String, JsError> {
+        let token_program = if token_2022 { spl_token_2022_program_id() } else { spl_token_program_id() };
+        let recent_blockhash = Hash::from_str(&recent_blockhash).map_err(|e| JsError::new(&format!("Invalid blockhash: {}", e)))?;
+
+        build_base64(&self.requirements, &payer, decimals, token_program, create_ata, recent_blockhash)
+            .map_err(|e| JsError::new(&e))
+    }
+
+    /// Build the unsigned transaction (base64), looking up the blockhash,
+    /// the mint and the recipient's token account over Solana JSON-RPC
+    /// 
+    /// # Returns
+    /// Promise resolving to the base64 transaction
+    #[wasm_bindgen]
+    pub fn build_with_rpc(&self, payer: String, rpc_url: String) -> JsStringPromise {
+        let requirements = self.requirements.clone();
+
+        future_to_promise(async move {
+            build_with_rpc(&requirements, &payer, &RpcFetch::new(&rpc_url))
+                .await
+                .map(JsValue::from)
+                .map_err(|e| JsError::new(&e).into())
+        })
+        .unchecked_into()
+    }
+
+    /// Wrap a wallet-signed transaction (base64) as the PaymentPayload for
+    /// the `X-PAYMENT` header
+    #[wasm_bindgen]
+    pub fn payment_payload(&self, signed_transaction: String) -> Result<JsPaymentPayload, JsError> {
+        let payload = PaymentPayload {
+            x402_version: 1,
+            scheme: self.requirements.scheme.clone(),
+            network: self.requirements.network.clone(),
+            payload: SvmPayload { transaction: signed_transaction },
+            timestamp: Some((js_sys::Date::now() / 1000.0) as u64),
+        };
+
+        serde_wasm_bindgen::to_value(&payload)
+            .map(JsCast::unchecked_into)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+}
+
+fn build_base64(
+    requirements: &PaymentRequirements,
+    payer: &str,
+    decimals: u8,
+    token_program: Pubkey,
+    create_destination_ata: bool,
+    recent_blockhash: Hash,
+) -> Result<String, String> {
+    let payer = Pubkey::from_str(payer).map_err(|e| format!("Invalid payer pubkey: {}", e))?;
+    let transaction = build_payment_transaction(&PaymentTransaction {
+        requirements,
+        payer,
+        decimals,
+        token_program,
+        create_destination_ata,
+        recent_blockhash,
+    })
+    .map_err(|e| format!("{:#}", e))?;
+
+    encode_transaction_to_base64(&transaction).map_err(|e| e.to_string())
+}
+
+async fn build_with_rpc(requirements: &PaymentRequirements, payer: &str, rpc: &RpcFetch<'_>) -> Result<String, String> {
+    let mint = Pubkey::from_str(&requirements.asset).map_err(|e| format!("Invalid asset pubkey: {}", e))?;
+    let pay_to = Pubkey::from_str(&requirements.pay_to).map_err(|e| format!("Invalid payTo pubkey: {}", e))?;
+
+    // The mint's owner decides which program the token accounts live under
+    let mint_account = rpc
+        .get_multiple_accounts(&[mint])
+        .await?
+        .pop()
+        .flatten()
+        .ok_or_else(|| format!("Mint {} not found", mint))?;
+    let token_program = mint_account.owner;
+    let decimals = MintInfo::from_account(&mint_account)
+        .map_err(|e| format!("Asset {} is not a token mint: {:#}", mint, e))?
+        .decimals;
+
+    let destination = get_associated_token_address_with_program_id(&pay_to, &mint, &token_program);
+    let create_ata = rpc.get_multiple_accounts(&[destination]).await?.pop().flatten().is_none();
+    let recent_blockhash = rpc.get_latest_blockhash().await?;
+
+    build_base64(requirements, payer, decimals, token_program, create_ata, recent_blockhash)
+}
+
+/// Convert the JS arguments, or the response rejecting them
+fn parse_request(
+    payment_js: JsValue,
+    requirements_js: JsValue,
+) -> Result<(PaymentPayload, PaymentRequirements), VerifyResponse> {
+    let payment = serde_wasm_bindgen::from_value(payment_js)
+        .map_err(|e| invalid(format!("Invalid payment format: {}", e)))?;
+    let requirements = serde_wasm_bindgen::from_value(requirements_js)
+        .map_err(|e| invalid(format!("Invalid requirements format: {}", e)))?;
+
+    Ok((payment, requirements))
+}
+
+fn invalid(reason: String) -> VerifyResponse {
+    VerifyResponse {
+        is_valid: false,
+        invalid_reason: Some(reason),
+        payer: None,
+        checks_skipped: Vec::new(),
+        failures: Vec::new(),
+        details: None,
+    }
+}
+
+/// Full verification: structural checks, then the server's instruction and
+/// account checks
+async fn verify_with_rpc(
+    payment: &PaymentPayload,
+    requirements: &PaymentRequirements,
+    rpc_url: &str,
+) -> VerifyResponse {
+    // No point fetching accounts for a malformed payment
+    let (transfer, has_create_ata) = match verify_offline(payment, requirements) {
+        Ok(checked) => checked,
+        Err(reason) => return invalid(reason),
+    };
+
+    if let Err(reason) = verify_accounts(&RpcFetch::new(rpc_url), &transfer, has_create_ata).await {
+        return invalid(reason);
+    }
+
+    VerifyResponse {
+        is_valid: true,
+        invalid_reason: None,
+        payer: Some(transfer.authority.to_string()),
+        checks_skipped: Vec::new(),
+        failures: Vec::new(),
+        details: None,
+    }
+}
+
+/// Instruction checks of the server's `/verify`, in the same order
+///
+/// Accepts legacy and v0 transactions; v0 messages are checked through
+/// their legacy equivalent, which needs every account to be a static key.
+fn verify_instructions(
+    payment: &PaymentPayload,
+    requirements: &PaymentRequirements,
+) -> Result<(TransferAccounts, bool), String> {
+    let transaction = decode_versioned_transaction_from_base64(&payment.payload.transaction)
+        .and_then(|tx| to_legacy_transaction(&tx))
+        .map_err(|e| format!("Failed to decode transaction: {}", e))?;
+    let reason = |e: VerificationError| e.as_str().to_string();
+
+    let fee_payer = Pubkey::from_str(&requirements.extra.fee_payer)
+        .map_err(|_| reason(VerificationError::UnexpectedError(anyhow::anyhow!("Invalid fee payer pubkey"))))?;
+    let message = &transaction.message;
+
+    verify_message_header(&transaction).map_err(reason)?;
+    verify_signer_count(&transaction, DEFAULT_REQUIRED_SIGNERS).map_err(reason)?;
+    let has_create_ata = verify_instruction_count(&transaction).map_err(reason)?;
+    verify_compute_limit_instruction(&message.instructions[0], message).map_err(reason)?;
+    verify_compute_price_instruction(&message.instructions[1], message).map_err(reason)?;
+    verify_fee_payer_position(&transaction, &fee_payer).map_err(reason)?;
+    verify_fee_payer_safety(&transaction, &fee_payer).map_err(reason)?;
+
+    if has_create_ata {
+        verify_create_ata_instruction(&message.instructions[2], message, requirements).map_err(reason)?;
+        verify_create_ata_funder(&message.instructions[2], message, &fee_payer, false).map_err(reason)?;
+    }
+
+    // The payer is the wallet authorizing the transfer
+    let transfer_idx = if has_create_ata { 3 } else { 2 };
+    let transfer =
+        parse_transfer_instruction(&message.instructions[transfer_idx], message, requirements, &fee_payer, false)
+            .map_err(reason)?;
+
+    Ok((transfer, has_create_ata))
+}
+
+/// Account checks of the server's `/verify`, with one `getMultipleAccounts` call
+async fn verify_accounts(rpc: &RpcFetch, transfer: &TransferAccounts, has_create_ata: bool) -> Result<(), String> {
+    let mut pubkeys = vec![transfer.mint, transfer.source];
+    if !has_create_ata {
+        pubkeys.push(transfer.destination);
+    }
+
+    let accounts = rpc
+        .get_multiple_accounts(&pubkeys)
+        .await
+        .map_err(|e| format!("RPC error: {}", e))?;
+    let reason = |e: VerificationError| e.as_str().to_string();
+
+    // The asset mint must exist, with the decimals the transfer claims
+    let mint = accounts[0]
+        .as_ref()
+        .and_then(|account| MintInfo::from_account(account).ok())
+        .ok_or_else(|| reason(VerificationError::MintNotFound))?;
+    if transfer.decimals != Some(mint.decimals) {
+        return Err(reason(VerificationError::DecimalsMismatch));
+    }
+
+    if accounts[1].is_none() {
+        return Err(reason(VerificationError::SenderATANotFound));
+    }
+
+    if !has_create_ata && accounts[2].is_none() {
+        return Err(reason(VerificationError::ReceiverATANotFound));
+    }
+
+    Ok(())
+}
+
+/// Minimal Solana JSON-RPC client over the browser's `fetch`
+struct RpcFetch<'a> {
+    url: &'a str,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RpcContextValue<T> {
+    value: T,
+}
+
+/// Account as returned with `"encoding": "base64"`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RpcAccount {
+    lamports: u64,
+    /// `[data, "base64"]`
+    data: (String, String),
+    owner: String,
+    executable: bool,
+    rent_epoch: u64,
+}
+
+#[derive(Deserialize)]
+struct RpcBlockhash {
+    blockhash: String,
+}
+
+impl RpcAccount {
+    fn into_account(self) -> Result<Account, String> {
+        Ok(Account {
+            lamports: self.lamports,
+            data: STANDARD.decode(&self.data.0).map_err(|e| format!("Invalid account data: {}", e))?,
+            owner: Pubkey::from_str(&self.owner).map_err(|e| format!("Invalid account owner: {}", e))?,
+            executable: self.executable,
+            rent_epoch: self.rent_epoch,
+        })
+    }
+}
+
+impl<'a> RpcFetch<'a> {
+    fn new(url: &'a str) -> Self {
+        Self { url }
+    }
+
+    /// `getMultipleAccounts`: the accounts in order, `None` where missing
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>, String> {
+        let pubkeys: Vec<String> = pubkeys.iter().map(Pubkey::to_string).collect();
+        let params = serde_json::json!([pubkeys, { "encoding": "base64", "commitment": "confirmed" }]);
+        let response: RpcContextValue<Vec<Option<RpcAccount>>> = self.call("getMultipleAccounts", params).await?;
+        response
+            .value
+            .into_iter()
+            .map(|account| account.map(RpcAccount::into_account).transpose())
+            .collect()
+    }
+
+    /// `getLatestBlockhash`
+    async fn get_latest_blockhash(&self) -> Result<Hash, String> {
+        let params = serde_json::json!([{ "commitment": "confirmed" }]);
+        let response: RpcContextValue<RpcBlockhash> = self.call("getLatestBlockhash", params).await?;
+        Hash::from_str(&response.value.blockhash).map_err(|e| format!("Invalid blockhash: {}", e))
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: serde_json::Value) -> Result<T, String> {
+        let body = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+
+        let init = web_sys::RequestInit::new();
+        init.set_method("POST");
+        init.set_body(&JsValue::from_str(&body.to_string()));
+        let request = web_sys::Request::new_with_str_and_init(self.url, &init).map_err(js_error)?;
+        request.headers().set("Content-Type", "application/json").map_err(js_error)?;
+
+        let response: web_sys::Response = JsFuture::from(fetch(&request)?)
+            .await
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(js_error)?;
+        if !response.ok() {
+            return Err(format!("{} returned HTTP {}", method, response.status()));
+        }
+
+        let text = JsFuture::from(response.text().map_err(js_error)?)
+            .await
+            .map_err(js_error)?
+            .as_string()
+            .ok_or_else(|| format!("{} returned a non-text body", method))?;
+        parse_rpc_response(method, &text)
+    }
+}
+
+fn parse_rpc_response<T: DeserializeOwned>(method: &str, text: &str) -> Result<T, String> {
+    let response: RpcResponse<T> =
+        serde_json::from_str(text).map_err(|e| format!("Invalid {} response: {}", method, e))?;
+
+    match (response.result, response.error) {
+        (_, Some(error)) => Err(format!("{} failed ({}): {}", method, error.code, error.message)),
+        (Some(result), None) => Ok(result),
+        (None, None) => Err(format!("{} returned no result", method)),
+    }
+}
+
+/// `fetch` from the window or, in a Web Worker, the worker scope
+fn fetch(request: &web_sys::Request) -> Result<js_sys::Promise, String> {
+    let global = js_sys::global();
+    if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+        return Ok(window.fetch_with_request(request));
+    }
+    if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+        return Ok(worker.fetch_with_request(request));
+    }
+    Err("fetch is not available in this environment".to_string())
+}
+
+fn js_error(value: JsValue) -> String {
+    value.as_string().unwrap_or_else(|| format!("{:?}", value))
+}
+
 /// WASM-safe verification logic
 /// 
 /// This performs verification without any I/O operations:
@@ -145,113 +641,66 @@ fn verify_wasm_safe(
     payment: &PaymentPayload,
     requirements: &PaymentRequirements,
 ) -> VerifyResponse {
+    match verify_offline(payment, requirements) {
+        Ok((transfer, has_create_ata)) => VerifyResponse {
+            is_valid: true,
+            invalid_reason: None,
+            payer: Some(transfer.authority.to_string()),
+            checks_skipped: account_checks(has_create_ata, true),
+            failures: Vec::new(),
+            details: None,
+        },
+        Err(reason) => invalid(reason),
+    }
+}
+
+/// All checks that need no RPC, returning the checked transfer and whether
+/// the transaction creates the recipient's token account
+fn verify_offline(
+    payment: &PaymentPayload,
+    requirements: &PaymentRequirements,
+) -> Result<(TransferAccounts, bool), String> {
     // 1. Verify scheme match
     if payment.scheme != requirements.scheme {
-        return VerifyResponse {
-            is_valid: false,
-            invalid_reason: Some(format!(
-                "Scheme mismatch: payment uses '{}', requirements specify '{}'",
-                payment.scheme, requirements.scheme
-            )),
-            payer: None,
-        };
+        return Err(format!(
+            "Scheme mismatch: payment uses '{}', requirements specify '{}'",
+            payment.scheme, requirements.scheme
+        ));
     }
 
     // 2. Verify scheme is supported
     if payment.scheme != "exact" {
-        return VerifyResponse {
-            is_valid: false,
-            invalid_reason: Some(format!(
-                "Unsupported scheme: '{}'. Only 'exact' is supported.",
-                payment.scheme
-            )),
-            payer: None,
-        };
+        return Err(format!(
+            "Unsupported scheme: '{}'. Only 'exact' is supported.",
+            payment.scheme
+        ));
     }
 
     // 3. Verify network match
     if payment.network != requirements.network {
-        return VerifyResponse {
-            is_valid: false,
-            invalid_reason: Some(format!(
-                "Network mismatch: payment uses '{}', requirements specify '{}'",
-                payment.network, requirements.network
-            )),
-            payer: None,
-        };
+        return Err(format!(
+            "Network mismatch: payment uses '{}', requirements specify '{}'",
+            payment.network, requirements.network
+        ));
     }
 
     // 4. Verify network is supported
     if !payment.network.starts_with("solana") {
-        return VerifyResponse {
-            is_valid: false,
-            invalid_reason: Some(format!(
-                "Unsupported network: '{}'. Only Solana networks are supported.",
-                payment.network
-            )),
-            payer: None,
-        };
-    }
-
-    // 5. Verify timestamp (if present)
-    if let Some(timestamp) = payment.timestamp {
-        // Get current time (WASM-compatible)
-        let current_time = js_sys::Date::now() / 1000.0;
-        let age_seconds = current_time as u64 - timestamp;
-        
-        // Default expiry: 10 minutes
-        let max_age = 600;
-        
-        if age_seconds > max_age {
-            return VerifyResponse {
-                is_valid: false,
-                invalid_reason: Some(format!(
-                    "Payment expired: age {} seconds exceeds maximum {} seconds",
-                    age_seconds, max_age
-                )),
-                payer: None,
-            };
-        }
+        return Err(format!(
+            "Unsupported network: '{}'. Only Solana networks are supported.",
+            payment.network
+        ));
     }
 
-    // 6. Decode transaction to extract payer
-    let transaction_base64 = &payment.payload.transaction;
-    
-    match decode_transaction_from_base64(transaction_base64) {
-        Ok(tx) => {
-            // Extract payer (second account key, index 1)
-            let payer = if let Some(payer_key) = tx.message.account_keys.get(1) {
-                payer_key.to_string()
-            } else {
-                "unknown".to_string()
-            };
+    // 5. Verify timestamp (if present): the requirements' timeout, up to
+    // the server's default of 10 minutes, with its default clock skew
+    let current_time = (js_sys::Date::now() / 1000.0) as u64;
+    payment
+        .check_timestamp(current_time, requirements.expiry_seconds(600), 30)
+        .map_err(|e| e.as_str().to_string())?;
 
-            // Basic instruction count check
-            let instruction_count = tx.message.instructions.len();
-            if instruction_count < 3 || instruction_count > 4 {
-                return VerifyResponse {
-                    is_valid: false,
-                    invalid_reason: Some(format!(
-                        "Invalid instruction count: expected 3 or 4, got {}",
-                        instruction_count
-                    )),
-                    payer: None,
-                };
-            }
-
-            // Success!
-            VerifyResponse {
-                is_valid: true,
-                invalid_reason: None,
-                payer: Some(payer),
-            }
-        }
-        Err(e) => VerifyResponse {
-            is_valid: false,
-            invalid_reason: Some(format!("Failed to decode transaction: {}", e)),
-            payer: None,
-        },
-    }
+    // 6. Decode the transaction and run the server's instruction checks
+    verify_instructions(payment, requirements)
 }
 
 #[cfg(test)]
@@ -278,5 +727,34 @@ mod tests {
         assert!(verifier.supports_network("solana-devnet".to_string()));
         assert!(!verifier.supports_network("ethereum".to_string()));
     }
-}
 
+    #[test]
+    fn test_parse_rpc_accounts() {
+        let owner = Pubkey::new_unique();
+        let text = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"result":{{"context":{{"slot":1}},"value":[
+                null,
+                {{"data":["AQID","base64"],"executable":false,"lamports":42,"owner":"{}","rentEpoch":18446744073709551615,"space":3}}
+            ]}}}}"#,
+            owner
+        );
+
+        let response: RpcContextValue<Vec<Option<RpcAccount>>> =
+            parse_rpc_response("getMultipleAccounts", &text).unwrap();
+        let accounts: Vec<Option<Account>> = response
+            .value
+            .into_iter()
+            .map(|account| account.map(|a| a.into_account().unwrap()))
+            .collect();
+
+        assert!(accounts[0].is_none());
+        let account = accounts[1].as_ref().unwrap();
+        assert_eq!(account.data, vec![1, 2, 3]);
+        assert_eq!(account.owner, owner);
+        assert_eq!(account.lamports, 42);
+
+        let error = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32602,"message":"Invalid param"}}"#;
+        let result: Result<RpcContextValue<Vec<Option<RpcAccount>>>, _> = parse_rpc_response("getMultipleAccounts", error);
+        assert!(result.unwrap_err().contains("Invalid param"));
+    }
+}
@@ -0,0 +1,448 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::Serialize;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions, SqliteRow};
+use sqlx::Row;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, OnceCell};
+
+use crate::webhooks::{send_webhook_request, WebhookConfig, WebhookPayload};
+
+/// Deliveries attempted per dispatcher pass
+const DISPATCH_BATCH_SIZE: i64 = 32;
+
+/// Longest the dispatcher sleeps without re-checking the queue
+const MAX_IDLE: Duration = Duration::from_secs(60);
+
+const SCHEMA: [&str; 2] = [
+    r#"CREATE TABLE IF NOT EXISTS webhook_deliveries (
+        id TEXT PRIMARY KEY,
+        endpoint TEXT NOT NULL,
+        event TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        next_attempt_at INTEGER NOT NULL,
+        last_error TEXT,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    )"#,
+    "CREATE INDEX IF NOT EXISTS webhook_deliveries_due_idx ON webhook_deliveries (status, next_attempt_at)",
+];
+
+/// A queued webhook delivery
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    /// Name of the [`crate::webhooks::WebhookEndpoint`] to deliver to
+    pub endpoint: String,
+    pub event: String,
+    /// Serialized [`WebhookPayload`], sent as-is
+    pub payload: String,
+    pub attempts: u32,
+    /// Unix time (ms) of the next attempt, or when it was dead-lettered
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    /// Unix time (ms) the delivery was queued
+    pub created_at: i64,
+}
+
+/// Persistent webhook delivery queue (SQLite)
+///
+/// Deliveries survive restarts: a dedicated dispatcher task sends due
+/// entries, reschedules failures with exponential backoff, and moves
+/// entries that exhausted `max_attempts` to a dead-letter list
+/// (`GET /admin/webhooks/dlq`).
+///
+/// Configuration:
+/// * `WEBHOOK_QUEUE_PATH` - SQLite file (enables the queue)
+/// * `WEBHOOK_QUEUE_MAX_ATTEMPTS` - attempts before dead-lettering (default: 10)
+/// * `WEBHOOK_QUEUE_BACKOFF_SECONDS` - first retry delay, doubled per attempt (default: 5)
+/// * `WEBHOOK_QUEUE_MAX_BACKOFF_SECONDS` - retry delay cap (default: 3600)
+#[derive(Clone)]
+pub struct WebhookQueue {
+    pool: SqlitePool,
+    schema: Arc<OnceCell<()>>,
+    notify: Arc<Notify>,
+    pub max_attempts: u32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl std::fmt::Debug for WebhookQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookQueue")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_backoff", &self.base_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebhookQueue {
+    /// Open (creating if needed) the queue database at `path`
+    ///
+    /// The file is opened lazily on first use.
+    pub fn open(path: &str) -> Self {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+
+        Self {
+            pool: SqlitePoolOptions::new().max_connections(1).connect_lazy_with(options),
+            schema: Arc::new(OnceCell::new()),
+            notify: Arc::new(Notify::new()),
+            max_attempts: 10,
+            base_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(3600),
+        }
+    }
+
+    /// Load queue settings from environment
+    ///
+    /// Returns `None` when `WEBHOOK_QUEUE_PATH` is not set.
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("WEBHOOK_QUEUE_PATH").ok().filter(|path| !path.is_empty())?;
+        let mut queue = Self::open(&path);
+
+        let env_u64 = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        if let Some(max_attempts) = std::env::var("WEBHOOK_QUEUE_MAX_ATTEMPTS").ok().and_then(|v| v.parse().ok()) {
+            queue.max_attempts = max_attempts;
+        }
+        if let Some(seconds) = env_u64("WEBHOOK_QUEUE_BACKOFF_SECONDS") {
+            queue.base_backoff = Duration::from_secs(seconds);
+        }
+        if let Some(seconds) = env_u64("WEBHOOK_QUEUE_MAX_BACKOFF_SECONDS") {
+            queue.max_backoff = Duration::from_secs(seconds);
+        }
+
+        tracing::info!("📬 Durable webhook queue at {}", path);
+        Some(queue)
+    }
+
+    async fn ensure_schema(&self) -> Result<()> {
+        self.schema
+            .get_or_try_init(|| async {
+                for statement in SCHEMA {
+                    sqlx::query(statement)
+                        .execute(&self.pool)
+                        .await
+                        .context("Failed to create webhook queue schema")?;
+                }
+                Ok::<_, anyhow::Error>(())
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Queue a payload for delivery to `endpoint` as soon as possible
+    pub async fn enqueue(&self, endpoint: &str, payload: &WebhookPayload) -> Result<String> {
+        self.ensure_schema().await?;
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = now_millis();
+
+        sqlx::query(
+            "INSERT INTO webhook_deliveries (id, endpoint, event, payload, next_attempt_at, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(&id)
+        .bind(endpoint)
+        .bind(&payload.event)
+        .bind(serde_json::to_string(payload)?)
+        .bind(now)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .context("Failed to queue webhook delivery")?;
+
+        self.notify.notify_one();
+        Ok(id)
+    }
+
+    /// Pending deliveries whose next attempt is due at `now` (unix ms)
+    pub async fn due(&self, now: i64) -> Result<Vec<WebhookDelivery>> {
+        self.ensure_schema().await?;
+
+        let rows = sqlx::query(
+            "SELECT * FROM webhook_deliveries WHERE status = 'pending' AND next_attempt_at <= $1 \
+             ORDER BY next_attempt_at LIMIT $2",
+        )
+        .bind(now)
+        .bind(DISPATCH_BATCH_SIZE)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read webhook queue")?;
+
+        Ok(rows.iter().map(delivery_from_row).collect())
+    }
+
+    /// Unix time (ms) of the earliest pending attempt, if any
+    pub async fn next_due(&self) -> Result<Option<i64>> {
+        self.ensure_schema().await?;
+
+        let next: Option<i64> = sqlx::query_scalar(
+            "SELECT MIN(next_attempt_at) FROM webhook_deliveries WHERE status = 'pending'",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to read webhook queue")?;
+
+        Ok(next)
+    }
+
+    /// Remove a delivered entry
+    pub async fn mark_delivered(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM webhook_deliveries WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .context("Failed to update webhook queue")?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt, rescheduling or dead-lettering the entry
+    ///
+    /// Returns `true` if the entry was dead-lettered.
+    pub async fn mark_failed(&self, delivery: &WebhookDelivery, error: &str) -> Result<bool> {
+        let attempts = delivery.attempts + 1;
+        let now = now_millis();
+        let dead = attempts >= self.max_attempts;
+
+        let (status, next_attempt_at) = if dead {
+            ("dead", now)
+        } else {
+            ("pending", now + self.backoff(attempts).as_millis() as i64)
+        };
+
+        sqlx::query(
+            "UPDATE webhook_deliveries SET status = $1, attempts = $2, next_attempt_at = $3, \
+             last_error = $4, updated_at = $5 WHERE id = $6",
+        )
+        .bind(status)
+        .bind(attempts as i64)
+        .bind(next_attempt_at)
+        .bind(error)
+        .bind(now)
+        .bind(&delivery.id)
+        .execute(&self.pool)
+        .await
+        .context("Failed to update webhook queue")?;
+
+        Ok(dead)
+    }
+
+    /// Dead-lettered deliveries, most recent first
+    pub async fn dead_letters(&self, limit: u32, offset: u64) -> Result<Vec<WebhookDelivery>> {
+        self.ensure_schema().await?;
+
+        let rows = sqlx::query(
+            "SELECT * FROM webhook_deliveries WHERE status = 'dead' \
+             ORDER BY next_attempt_at DESC, id LIMIT $1 OFFSET $2",
+        )
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read webhook dead-letter list")?;
+
+        Ok(rows.iter().map(delivery_from_row).collect())
+    }
+
+    /// Delay before the attempt following the `attempts`-th failure
+    fn backoff(&self, attempts: u32) -> Duration {
+        self.base_backoff
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+}
+
+fn delivery_from_row(row: &SqliteRow) -> WebhookDelivery {
+    WebhookDelivery {
+        id: row.get("id"),
+        endpoint: row.get("endpoint"),
+        event: row.get("event"),
+        payload: row.get("payload"),
+        attempts: row.get::<i64, _>("attempts") as u32,
+        next_attempt_at: row.get("next_attempt_at"),
+        last_error: row.get("last_error"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Attempt one delivery, updating the queue with the outcome
+async fn deliver(queue: &WebhookQueue, config: &WebhookConfig, client: &Client, delivery: &WebhookDelivery) -> Result<()> {
+    let Some(endpoint) = config.endpoint(&delivery.endpoint) else {
+        // Endpoint removed from config since the delivery was queued
+        tracing::warn!("⚠️  Webhook {} targets unknown endpoint '{}'", delivery.id, delivery.endpoint);
+        queue
+            .mark_failed(delivery, &format!("Unknown webhook endpoint '{}'", delivery.endpoint))
+            .await?;
+        return Ok(());
+    };
+
+    // Sign with the current secret, so rotations apply to queued deliveries
+    let url = endpoint.url.get();
+
+    // Endpoints with their own CA or client certificate need their own client
+    let custom_client;
+    let client = match &endpoint.tls {
+        Some(_) => match config.client(&endpoint) {
+            Ok(built) => {
+                custom_client = built;
+                &custom_client
+            }
+            Err(e) => {
+                // e.g. certificate files missing; retried with backoff like a failed delivery
+                tracing::warn!("⚠️  Failed to build HTTP client for webhook endpoint '{}': {:#}", endpoint.name, e);
+                queue.mark_failed(delivery, &format!("{:#}", e)).await?;
+                return Ok(());
+            }
+        },
+        None => client,
+    };
+
+    let started = Instant::now();
+    let result = send_webhook_request(client, &url, &delivery.payload, &endpoint.secret.get()).await;
+    config.status.record(
+        &endpoint.name,
+        delivery.attempts > 0,
+        started.elapsed(),
+        result.as_ref().err().map(|e| e.to_string()),
+    );
+
+    match result {
+        Ok(()) => {
+            tracing::info!("✅ Webhook delivered successfully: {} to {}", delivery.event, url);
+            queue.mark_delivered(&delivery.id).await
+        }
+        Err(e) => {
+            let dead = queue.mark_failed(delivery, &e.to_string()).await?;
+            if dead {
+                tracing::error!(
+                    "❌ Webhook {} dead-lettered after {} attempts: {}",
+                    delivery.id,
+                    delivery.attempts + 1,
+                    e
+                );
+            } else {
+                tracing::warn!(
+                    "⚠️  Webhook delivery failed (attempt {}/{}), will retry: {}",
+                    delivery.attempts + 1,
+                    queue.max_attempts,
+                    e
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Spawn the task delivering queued webhooks
+pub fn spawn_dispatcher(queue: WebhookQueue, config: WebhookConfig) {
+    tokio::spawn(async move {
+        let client = match Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!("Failed to build webhook HTTP client: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            let due = match queue.due(now_millis()).await {
+                Ok(due) => due,
+                Err(e) => {
+                    tracing::error!("Webhook queue unavailable: {:#}", e);
+                    tokio::time::sleep(MAX_IDLE).await;
+                    continue;
+                }
+            };
+
+            for delivery in &due {
+                if let Err(e) = deliver(&queue, &config, &client, delivery).await {
+                    tracing::error!("Failed to update webhook queue: {:#}", e);
+                }
+            }
+
+            if due.len() as i64 == DISPATCH_BATCH_SIZE {
+                continue;
+            }
+
+            // Sleep until the next retry is due or a new delivery is queued
+            let idle = match queue.next_due().await {
+                Ok(Some(next)) => Duration::from_millis((next - now_millis()).max(0) as u64).min(MAX_IDLE),
+                _ => MAX_IDLE,
+            };
+            tokio::select! {
+                _ = queue.notify.notified() => {}
+                _ = tokio::time::sleep(idle) => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhooks::WebhookEvent;
+
+    fn test_queue() -> (WebhookQueue, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("webhooks-{}.db", uuid::Uuid::new_v4()));
+        let mut queue = WebhookQueue::open(path.to_str().unwrap());
+        queue.max_attempts = 2;
+        (queue, path)
+    }
+
+    #[tokio::test]
+    async fn test_backoff_doubles_up_to_cap() {
+        let (mut queue, _) = test_queue();
+        queue.base_backoff = Duration::from_secs(5);
+        queue.max_backoff = Duration::from_secs(30);
+
+        assert_eq!(queue.backoff(1), Duration::from_secs(5));
+        assert_eq!(queue.backoff(2), Duration::from_secs(10));
+        assert_eq!(queue.backoff(3), Duration::from_secs(20));
+        assert_eq!(queue.backoff(4), Duration::from_secs(30));
+        assert_eq!(queue.backoff(40), Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_failed_deliveries_are_rescheduled_then_dead_lettered() {
+        let (queue, path) = test_queue();
+        let payload = WebhookPayload::new(WebhookEvent::SettlementSuccess, serde_json::json!({}));
+        let id = queue.enqueue("default", &payload).await.unwrap();
+
+        let due = queue.due(now_millis()).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, id);
+
+        // First failure: rescheduled with backoff, not due right away
+        assert!(!queue.mark_failed(&due[0], "HTTP 500").await.unwrap());
+        assert!(queue.due(now_millis()).await.unwrap().is_empty());
+        assert!(queue.next_due().await.unwrap().is_some());
+
+        // Second failure exhausts max_attempts
+        let retry = queue.due(now_millis() + 60_000).await.unwrap();
+        assert!(queue.mark_failed(&retry[0], "HTTP 503").await.unwrap());
+
+        let dead = queue.dead_letters(10, 0).await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, 2);
+        assert_eq!(dead[0].last_error.as_deref(), Some("HTTP 503"));
+        assert_eq!(queue.next_due().await.unwrap(), None);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -1,36 +1,508 @@
+use anyhow::Context;
+use arc_swap::ArcSwap;
 use hmac::{Hmac, Mac};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::metrics::WebhookMetrics;
+use crate::middleware::in_flight::InFlightCounter;
+use crate::tenants::Tenant;
 
 type HmacSha256 = Hmac<Sha256>;
 
-/// Webhook configuration
+/// Webhook signing secret that can be rotated at runtime (e.g. from Vault)
+#[derive(Clone, Default)]
+pub struct WebhookSecret(Arc<RwLock<String>>);
+
+impl WebhookSecret {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(Arc::new(RwLock::new(secret.into())))
+    }
+
+    /// Current secret value
+    pub fn get(&self) -> String {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the secret for all clones of this handle
+    pub fn set(&self, secret: impl Into<String>) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = secret.into();
+    }
+}
+
+impl std::fmt::Debug for WebhookSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+/// Webhook endpoint that can be changed at runtime (config reload)
 #[derive(Clone, Debug)]
-pub struct WebhookConfig {
+pub struct WebhookUrl(Arc<ArcSwap<String>>);
+
+impl WebhookUrl {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self(Arc::new(ArcSwap::from_pointee(url.into())))
+    }
+
+    /// Current endpoint
+    pub fn get(&self) -> String {
+        self.0.load().as_ref().clone()
+    }
+
+    /// Replace the endpoint for all clones of this handle
+    pub fn set(&self, url: impl Into<String>) {
+        self.0.store(Arc::new(url.into()));
+    }
+}
+
+/// Event types an endpoint subscribes to
+///
+/// Patterns are exact event names (`settlement.success`), a prefix
+/// wildcard (`settlement.*`) or `*`. An empty filter matches every event.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WebhookEventFilter(Vec<String>);
+
+impl WebhookEventFilter {
+    pub fn new(patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self(patterns.into_iter().map(Into::into).collect())
+    }
+
+    /// Parse a comma-separated list of patterns
+    pub fn parse(patterns: &str) -> Self {
+        Self::new(patterns.split(',').map(str::trim).filter(|p| !p.is_empty()))
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.0
+    }
+
+    pub fn matches(&self, event: &str) -> bool {
+        self.0.is_empty()
+            || self.0.iter().any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => event.starts_with(prefix),
+                None => pattern == event,
+            })
+    }
+}
+
+/// TLS settings for reaching a webhook endpoint, as PEM files
+///
+/// For receivers behind a private CA (`ca_file`) or requiring client
+/// certificates (`client_cert_file` with a PKCS#8 `client_key_file`).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookTls {
+    /// Root certificate bundle trusted in addition to the system roots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key_file: Option<PathBuf>,
+}
+
+impl WebhookTls {
+    /// TLS settings of the default endpoint
+    /// (`WEBHOOK_CA_FILE`, `WEBHOOK_CLIENT_CERT_FILE`, `WEBHOOK_CLIENT_KEY_FILE`)
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let path = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty()).map(PathBuf::from);
+        let tls = Self {
+            ca_file: path("WEBHOOK_CA_FILE"),
+            client_cert_file: path("WEBHOOK_CLIENT_CERT_FILE"),
+            client_key_file: path("WEBHOOK_CLIENT_KEY_FILE"),
+        };
+
+        if tls == Self::default() {
+            return Ok(None);
+        }
+        // Fail at startup on unreadable or invalid files
+        let _ = tls.configure(Client::builder()).context("Invalid webhook TLS settings")?;
+        Ok(Some(tls))
+    }
+
+    /// Add the CA bundle and client identity to `builder`
+    ///
+    /// Files are read on every call, so renewed certificates are picked up
+    /// without a restart.
+    pub fn configure(&self, mut builder: reqwest::ClientBuilder) -> anyhow::Result<reqwest::ClientBuilder> {
+        if let Some(ca_file) = &self.ca_file {
+            let pem = std::fs::read(ca_file).with_context(|| format!("Failed to read {}", ca_file.display()))?;
+            let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("Invalid CA bundle {}", ca_file.display()))?;
+            if certificates.is_empty() {
+                anyhow::bail!("No certificates in CA bundle {}", ca_file.display());
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        match (&self.client_cert_file, &self.client_key_file) {
+            (Some(cert_file), Some(key_file)) => {
+                let cert = std::fs::read(cert_file).with_context(|| format!("Failed to read {}", cert_file.display()))?;
+                let key = std::fs::read(key_file).with_context(|| format!("Failed to read {}", key_file.display()))?;
+                let identity = reqwest::Identity::from_pkcs8_pem(&cert, &key)
+                    .with_context(|| format!("Invalid client certificate {}", cert_file.display()))?;
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => anyhow::bail!("client_cert_file and client_key_file must be set together"),
+        }
+
+        Ok(builder)
+    }
+}
+
+/// A webhook receiver with its own secret and event filter
+#[derive(Clone, Debug)]
+pub struct WebhookEndpoint {
+    /// Identifies the endpoint in logs and queued deliveries
+    pub name: String,
+    pub url: WebhookUrl,
+    pub secret: WebhookSecret,
+    pub events: WebhookEventFilter,
+    /// Disabled endpoints receive no events
+    pub enabled: bool,
+    /// Custom CA / client certificate; system roots and no client cert when `None`
+    pub tls: Option<WebhookTls>,
+}
+
+impl WebhookEndpoint {
+    /// Validate and build an endpoint from its configuration
+    pub fn from_spec(spec: WebhookEndpointSpec) -> anyhow::Result<Self> {
+        validate_endpoint_name(&spec.name)?;
+        validate_endpoint_url(&spec.url)?;
+        if spec.secret.is_empty() {
+            anyhow::bail!("Webhook endpoint '{}' needs a secret", spec.name);
+        }
+        if let Some(tls) = &spec.tls {
+            let _ = tls
+                .configure(Client::builder())
+                .with_context(|| format!("Invalid TLS settings for webhook endpoint '{}'", spec.name))?;
+        }
+
+        Ok(Self {
+            name: spec.name,
+            url: WebhookUrl::new(spec.url),
+            secret: WebhookSecret::new(spec.secret),
+            events: WebhookEventFilter::new(spec.events),
+            enabled: spec.enabled,
+            tls: spec.tls,
+        })
+    }
+
+    fn to_spec(&self) -> WebhookEndpointSpec {
+        WebhookEndpointSpec {
+            name: self.name.clone(),
+            url: self.url.get(),
+            secret: self.secret.get(),
+            events: self.events.patterns().to_vec(),
+            enabled: self.enabled,
+            tls: self.tls.clone(),
+        }
+    }
+}
+
+/// Endpoint configuration, as in `WEBHOOK_ENDPOINTS` and the endpoints file
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WebhookEndpointSpec {
+    pub name: String,
     pub url: String,
     pub secret: String,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<WebhookTls>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Endpoint names appear in admin URLs and queued deliveries
+fn validate_endpoint_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty()
+        || name.len() > 64
+        || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!("Invalid webhook endpoint name '{}' (use 1-64 letters, digits, '-' or '_')", name);
+    }
+    if matches!(name, DEFAULT_ENDPOINT | "dlq" | "status") {
+        anyhow::bail!("Webhook endpoint name '{}' is reserved", name);
+    }
+    Ok(())
+}
+
+fn validate_endpoint_url(url: &str) -> anyhow::Result<()> {
+    let parsed = reqwest::Url::parse(url).with_context(|| format!("Invalid webhook URL '{}'", url))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        anyhow::bail!("Webhook URL must use http or https: '{}'", url);
+    }
+    Ok(())
+}
+
+/// Additional webhook endpoints, changeable at runtime via `/admin/webhooks`
+///
+/// Changes are saved to the endpoints file (`WEBHOOK_ENDPOINTS_FILE`) so they
+/// survive restarts; without one, the set is fixed to `WEBHOOK_ENDPOINTS`.
+#[derive(Clone, Debug, Default)]
+pub struct WebhookEndpoints {
+    current: Arc<ArcSwap<Vec<WebhookEndpoint>>>,
+    path: Option<PathBuf>,
+    /// Serializes read-modify-write updates and file saves
+    update_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl WebhookEndpoints {
+    pub fn new(endpoints: Vec<WebhookEndpoint>, path: Option<PathBuf>) -> Self {
+        Self {
+            current: Arc::new(ArcSwap::from_pointee(endpoints)),
+            path,
+            update_lock: Arc::default(),
+        }
+    }
+
+    /// Current endpoints
+    pub fn get(&self) -> Arc<Vec<WebhookEndpoint>> {
+        self.current.load_full()
+    }
+
+    /// Whether runtime changes are persisted (and therefore allowed)
+    pub fn is_persistent(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Apply `change` to a copy of the endpoints, save it, then publish it
+    ///
+    /// Nothing is saved or published if `change` fails.
+    pub async fn update<T, E: From<anyhow::Error>>(
+        &self,
+        change: impl FnOnce(&mut Vec<WebhookEndpoint>) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let Some(path) = &self.path else {
+            return Err(anyhow::anyhow!("WEBHOOK_ENDPOINTS_FILE is not set; endpoints can't be changed at runtime").into());
+        };
+
+        let _lock = self.update_lock.lock().await;
+        let mut endpoints = self.get().as_ref().clone();
+        let result = change(&mut endpoints)?;
+
+        save_endpoints(path, &endpoints).await?;
+        self.current.store(Arc::new(endpoints));
+        Ok(result)
+    }
+}
+
+/// Write the endpoints file atomically, readable by the owner only
+async fn save_endpoints(path: &Path, endpoints: &[WebhookEndpoint]) -> anyhow::Result<()> {
+    let specs: Vec<_> = endpoints.iter().map(WebhookEndpoint::to_spec).collect();
+    let json = serde_json::to_vec_pretty(&specs)?;
+
+    let partial = path.with_extension("partial");
+    tokio::fs::write(&partial, json)
+        .await
+        .with_context(|| format!("Failed to write {}", partial.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o600)).await?;
+    }
+
+    tokio::fs::rename(&partial, path)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// Recent delivery outcomes for one endpoint
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EndpointDeliveryStatus {
+    /// Successful attempts since startup
+    pub delivered: u64,
+    /// Failed attempts since startup
+    pub failed: u64,
+    /// Failed attempts since the last success
+    pub consecutive_failures: u64,
+    /// Unix timestamps (seconds)
+    pub last_attempt_at: Option<i64>,
+    pub last_success_at: Option<i64>,
+    pub last_failure_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_duration_ms: Option<u64>,
+}
+
+/// Per-endpoint delivery outcomes, shared by all delivery paths
+///
+/// Also feeds the `x402_webhook_*` Prometheus metrics.
+#[derive(Clone, Default)]
+pub struct WebhookDeliveryStatus {
+    endpoints: Arc<Mutex<HashMap<String, EndpointDeliveryStatus>>>,
+    metrics: WebhookMetrics,
+}
+
+impl std::fmt::Debug for WebhookDeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookDeliveryStatus").finish_non_exhaustive()
+    }
+}
+
+impl WebhookDeliveryStatus {
+    /// Record the outcome of one delivery attempt to `endpoint`
+    ///
+    /// `retry` marks attempts that follow an earlier failure of the same delivery.
+    pub fn record(&self, endpoint: &str, retry: bool, duration: Duration, error: Option<String>) {
+        self.metrics.record_delivery(endpoint, error.is_none(), duration);
+        if retry {
+            self.metrics.record_retry(endpoint);
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        let status = endpoints.entry(endpoint.to_string()).or_default();
+        status.last_attempt_at = Some(now);
+        status.last_duration_ms = Some(duration.as_millis() as u64);
+        match error {
+            None => {
+                status.delivered += 1;
+                status.consecutive_failures = 0;
+                status.last_success_at = Some(now);
+            }
+            Some(error) => {
+                status.failed += 1;
+                status.consecutive_failures += 1;
+                status.last_failure_at = Some(now);
+                status.last_error = Some(error);
+            }
+        }
+    }
+
+    /// Status of `endpoint` (all zero if nothing was sent to it yet)
+    pub fn get(&self, endpoint: &str) -> EndpointDeliveryStatus {
+        let endpoints = self.endpoints.lock().unwrap_or_else(|e| e.into_inner());
+        endpoints.get(endpoint).cloned().unwrap_or_default()
+    }
+}
+
+/// Suppresses repeats of an operational event about the same subject
+///
+/// Incidents (a low balance, a replayed transaction) recur on every request
+/// until they're resolved; one notification per cooldown is enough.
+#[derive(Clone)]
+pub struct AlertThrottle {
+    sent: moka::sync::Cache<String, ()>,
+}
+
+impl std::fmt::Debug for AlertThrottle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlertThrottle").finish_non_exhaustive()
+    }
+}
+
+impl Default for AlertThrottle {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(300))
+    }
+}
+
+impl AlertThrottle {
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            sent: moka::sync::Cache::builder()
+                .max_capacity(10_000)
+                .time_to_live(cooldown)
+                .build(),
+        }
+    }
+
+    /// Whether `event` about `subject` may be sent now (false within the
+    /// cooldown of the last one)
+    pub fn allow(&self, event: &str, subject: &str) -> bool {
+        self.sent.entry(format!("{}:{}", event, subject)).or_insert(()).is_fresh()
+    }
+}
+
+/// Name of the endpoint configured by `WEBHOOK_URL` / `WEBHOOK_SECRET`
+pub const DEFAULT_ENDPOINT: &str = "default";
+
+/// Webhook configuration
+///
+/// `url` and `secret` describe the default endpoint (reloadable and
+/// rotatable at runtime), which receives the events in `events`.
+/// `endpoints` lists further receivers, each with its own secret and filter.
+#[derive(Clone, Debug)]
+pub struct WebhookConfig {
+    pub url: WebhookUrl,
+    pub secret: WebhookSecret,
+    /// Events sent to the default endpoint (`WEBHOOK_EVENTS`)
+    pub events: WebhookEventFilter,
+    /// TLS settings of the default endpoint
+    pub tls: Option<WebhookTls>,
+    /// Additional endpoints (`WEBHOOK_ENDPOINTS` / `WEBHOOK_ENDPOINTS_FILE`)
+    pub endpoints: WebhookEndpoints,
     pub enabled: bool,
     pub timeout_seconds: u64,
     pub retry_attempts: u32,
+    /// Deliveries still being attempted (or queued) in the background
+    pub pending: InFlightCounter,
+    /// Last delivery outcome per endpoint
+    pub status: WebhookDeliveryStatus,
+    /// Repeats of operational events suppressed (`WEBHOOK_ALERT_COOLDOWN_SECONDS`)
+    pub alerts: AlertThrottle,
+    /// Durable delivery queue; deliveries are sent in-process when `None`
+    #[cfg(feature = "webhook-queue")]
+    pub queue: Option<crate::webhook_queue::WebhookQueue>,
 }
 
 impl WebhookConfig {
     /// Load webhook configuration from environment
-    pub fn from_env() -> Option<Self> {
+    ///
+    /// Returns `Ok(None)` unless `WEBHOOK_ENABLED=true` and the default
+    /// endpoint (`WEBHOOK_URL`, `WEBHOOK_SECRET`) is set. Further endpoints
+    /// come from `WEBHOOK_ENDPOINTS`, a JSON array such as
+    /// `[{"name": "billing", "url": "...", "secret": "...", "events": ["settlement.*"]}]`,
+    /// or from `WEBHOOK_ENDPOINTS_FILE` (same format) once it exists.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
         let enabled = std::env::var("WEBHOOK_ENABLED")
             .ok()
             .and_then(|v| v.parse().ok())
             .unwrap_or(false);
 
         if !enabled {
-            return None;
+            return Ok(None);
         }
 
-        Some(Self {
-            url: std::env::var("WEBHOOK_URL").ok()?,
-            secret: std::env::var("WEBHOOK_SECRET").ok()?,
+        let (Ok(url), Ok(secret)) = (std::env::var("WEBHOOK_URL"), std::env::var("WEBHOOK_SECRET")) else {
+            return Ok(None);
+        };
+
+        let path = std::env::var("WEBHOOK_ENDPOINTS_FILE").ok().filter(|path| !path.is_empty()).map(PathBuf::from);
+        let endpoints = match &path {
+            // Endpoints managed at runtime take precedence over the seed list
+            Some(path) if path.exists() => {
+                let json = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                parse_endpoints(&json).with_context(|| format!("Invalid {}", path.display()))?
+            }
+            _ => match std::env::var("WEBHOOK_ENDPOINTS") {
+                Ok(json) if !json.trim().is_empty() => parse_endpoints(&json).context("Invalid WEBHOOK_ENDPOINTS")?,
+                _ => Vec::new(),
+            },
+        };
+
+        Ok(Some(Self {
+            url: WebhookUrl::new(url),
+            secret: WebhookSecret::new(secret),
+            events: WebhookEventFilter::parse(&std::env::var("WEBHOOK_EVENTS").unwrap_or_default()),
+            tls: WebhookTls::from_env()?,
+            endpoints: WebhookEndpoints::new(endpoints, path),
             enabled: true,
             timeout_seconds: std::env::var("WEBHOOK_TIMEOUT_SECONDS")
                 .ok()
@@ -40,7 +512,61 @@ impl WebhookConfig {
                 .ok()
                 .and_then(|v| v.parse().ok())
                 .unwrap_or(3),
-        })
+            pending: InFlightCounter::new(),
+            status: WebhookDeliveryStatus::default(),
+            alerts: AlertThrottle::new(Duration::from_secs(
+                std::env::var("WEBHOOK_ALERT_COOLDOWN_SECONDS")
+                    .ok()
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(300),
+            )),
+            #[cfg(feature = "webhook-queue")]
+            queue: crate::webhook_queue::WebhookQueue::from_env(),
+        }))
+    }
+
+    /// The default endpoint (`WEBHOOK_URL`), sharing the reloadable URL and secret
+    pub fn default_endpoint(&self) -> WebhookEndpoint {
+        WebhookEndpoint {
+            name: DEFAULT_ENDPOINT.to_string(),
+            url: self.url.clone(),
+            secret: self.secret.clone(),
+            events: self.events.clone(),
+            enabled: true,
+            tls: self.tls.clone(),
+        }
+    }
+
+    /// HTTP client for deliveries to `endpoint`, honouring its TLS settings
+    pub fn client(&self, endpoint: &WebhookEndpoint) -> anyhow::Result<Client> {
+        let mut builder = Client::builder().timeout(Duration::from_secs(self.timeout_seconds));
+        if let Some(tls) = &endpoint.tls {
+            builder = tls.configure(builder)?;
+        }
+        Ok(builder.build()?)
+    }
+
+    /// Look up an endpoint by name
+    pub fn endpoint(&self, name: &str) -> Option<WebhookEndpoint> {
+        if name == DEFAULT_ENDPOINT {
+            return Some(self.default_endpoint());
+        }
+        self.endpoints.get().iter().find(|endpoint| endpoint.name == name).cloned()
+    }
+
+    /// Enabled endpoints subscribed to `event`
+    pub fn endpoints_for(&self, event: &str) -> Vec<WebhookEndpoint> {
+        std::iter::once(self.default_endpoint())
+            .chain(self.endpoints.get().iter().cloned())
+            .filter(|endpoint| endpoint.enabled && endpoint.events.matches(event))
+            .collect()
+    }
+
+    /// Wait for background deliveries to finish, up to `timeout`
+    ///
+    /// Returns `false` if deliveries were still pending when the timeout hit.
+    pub async fn flush(&self, timeout: Duration) -> bool {
+        self.pending.wait_idle(timeout).await
     }
 }
 
@@ -52,6 +578,16 @@ pub enum WebhookEvent {
     VerificationFailure,
     SettlementSuccess,
     SettlementFailure,
+    /// Sent on request via `POST /admin/webhooks/{name}/test`
+    Test,
+    /// The fee payer's balance is below `MIN_FEE_PAYER_BALANCE_LAMPORTS`
+    FeePayerLowBalance,
+    /// The RPC endpoint started failing its health checks
+    RpcUnhealthy,
+    /// A client was rate limited
+    RateLimitExceeded,
+    /// An already seen transaction was submitted again
+    ReplayDetected,
 }
 
 impl WebhookEvent {
@@ -61,6 +597,31 @@ impl WebhookEvent {
             WebhookEvent::VerificationFailure => "verification.failure",
             WebhookEvent::SettlementSuccess => "settlement.success",
             WebhookEvent::SettlementFailure => "settlement.failure",
+            WebhookEvent::Test => "webhook.test",
+            WebhookEvent::FeePayerLowBalance => "fee_payer.low_balance",
+            WebhookEvent::RpcUnhealthy => "rpc.unhealthy",
+            WebhookEvent::RateLimitExceeded => "rate_limit.exceeded",
+            WebhookEvent::ReplayDetected => "dedup.replay_detected",
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEvent {
+    type Err = anyhow::Error;
+
+    /// Parse an event name as sent in payloads (e.g. `settlement.success`)
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "verification.success" => Ok(WebhookEvent::VerificationSuccess),
+            "verification.failure" => Ok(WebhookEvent::VerificationFailure),
+            "settlement.success" => Ok(WebhookEvent::SettlementSuccess),
+            "settlement.failure" => Ok(WebhookEvent::SettlementFailure),
+            "webhook.test" => Ok(WebhookEvent::Test),
+            "fee_payer.low_balance" => Ok(WebhookEvent::FeePayerLowBalance),
+            "rpc.unhealthy" => Ok(WebhookEvent::RpcUnhealthy),
+            "rate_limit.exceeded" => Ok(WebhookEvent::RateLimitExceeded),
+            "dedup.replay_detected" => Ok(WebhookEvent::ReplayDetected),
+            other => anyhow::bail!("Unknown webhook event '{}'", other),
         }
     }
 }
@@ -83,40 +644,133 @@ impl WebhookPayload {
     }
 }
 
-/// Send a webhook notification with retries
+/// Validate and build endpoints from a JSON array of [`WebhookEndpointSpec`]
+fn parse_endpoints(json: &str) -> anyhow::Result<Vec<WebhookEndpoint>> {
+    let specs: Vec<WebhookEndpointSpec> = serde_json::from_str(json)?;
+
+    let mut endpoints: Vec<WebhookEndpoint> = Vec::with_capacity(specs.len());
+    for spec in specs {
+        if endpoints.iter().any(|endpoint| endpoint.name == spec.name) {
+            anyhow::bail!("Duplicate webhook endpoint name '{}'", spec.name);
+        }
+        endpoints.push(WebhookEndpoint::from_spec(spec)?);
+    }
+
+    Ok(endpoints)
+}
+
+/// Send a webhook notification in the background
+///
+/// Fans out to every endpoint subscribed to the event. Delivery (including
+/// retries) is tracked in `config.pending` so shutdown can wait for it via
+/// [`WebhookConfig::flush`]. With a durable queue configured the payload is
+/// only queued here, and delivered by the queue's dispatcher task.
+pub fn dispatch_webhook(config: &WebhookConfig, payload: WebhookPayload) {
+    for endpoint in config.endpoints_for(&payload.event) {
+        let config = config.clone();
+        let payload = payload.clone();
+        let guard = config.pending.track();
+
+        #[cfg(feature = "webhook-queue")]
+        if let Some(queue) = config.queue.clone() {
+            tokio::spawn(async move {
+                let _guard = guard;
+                if let Err(e) = queue.enqueue(&endpoint.name, &payload).await {
+                    tracing::error!("❌ Failed to queue webhook {} for {}: {:#}", payload.event, endpoint.name, e);
+                }
+            });
+            continue;
+        }
+
+        tokio::spawn(async move {
+            let _guard = guard;
+            let _ = send_webhook(&config, &endpoint, &payload).await;
+        });
+    }
+}
+
+/// [`dispatch_webhook`], plus the endpoint of the tenant whose request
+/// caused the event
+///
+/// Tenant endpoints are always sent to in-process: the durable queue only
+/// knows the facilitator's own endpoints.
+pub fn dispatch_webhook_for(config: &WebhookConfig, tenant: Option<&Tenant>, payload: WebhookPayload) {
+    let tenant_endpoint = tenant
+        .and_then(|tenant| tenant.webhook.clone())
+        .filter(|endpoint| endpoint.enabled && endpoint.events.matches(&payload.event));
+    if let Some(endpoint) = tenant_endpoint {
+        let config = config.clone();
+        let payload = payload.clone();
+        let guard = config.pending.track();
+        tokio::spawn(async move {
+            let _guard = guard;
+            let _ = send_webhook(&config, &endpoint, &payload).await;
+        });
+    }
+
+    dispatch_webhook(config, payload);
+}
+
+/// Send an operational event (a [`WebhookEvent`] about an incident rather
+/// than a payment), unless one about the same `subject` went out within the
+/// cooldown
+pub fn dispatch_alert(
+    config: &WebhookConfig,
+    tenant: Option<&Tenant>,
+    event: WebhookEvent,
+    subject: &str,
+    data: serde_json::Value,
+) {
+    if !config.alerts.allow(event.as_str(), subject) {
+        return;
+    }
+    tracing::info!("📣 Sending {} webhook for {}", event.as_str(), subject);
+    dispatch_webhook_for(config, tenant, WebhookPayload::new(event, data));
+}
+
+/// Send a webhook notification to one endpoint with retries
 pub async fn send_webhook(
     config: &WebhookConfig,
+    endpoint: &WebhookEndpoint,
     payload: &WebhookPayload,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     if !config.enabled {
         return Ok(());
     }
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(config.timeout_seconds))
-        .build()?;
+    let client = config.client(endpoint)?;
 
     let payload_json = serde_json::to_string(payload)?;
+    let secret = endpoint.secret.get();
 
-    // Generate HMAC signature
-    let signature = generate_signature(&config.secret, &payload_json)?;
+    // Retries go to the endpoint current when delivery started
+    let url = endpoint.url.get();
 
     // Attempt delivery with retries
     let mut last_error = None;
     for attempt in 1..=config.retry_attempts {
         tracing::debug!(
             "Sending webhook to {} (attempt {}/{})",
-            config.url,
+            url,
             attempt,
             config.retry_attempts
         );
 
-        match send_webhook_request(&client, &config.url, &payload_json, &signature).await {
+        let started = Instant::now();
+        let result = send_webhook_request(&client, &url, &payload_json, &secret).await;
+        config.status.record(
+            &endpoint.name,
+            attempt > 1,
+            started.elapsed(),
+            result.as_ref().err().map(|e| e.to_string()),
+        );
+
+        match result {
             Ok(_) => {
                 tracing::info!(
                     "✅ Webhook delivered successfully: {} to {}",
                     payload.event,
-                    config.url
+                    url
                 );
                 return Ok(());
             }
@@ -151,29 +805,87 @@ pub async fn send_webhook(
     Ok(())
 }
 
-/// Generate HMAC-SHA256 signature
-fn generate_signature(secret: &str, payload: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+/// Send a single `webhook.test` delivery to `endpoint`, without retries
+///
+/// Ignores the endpoint's event filter and enabled flag, so receivers can be
+/// checked before they are switched on.
+pub async fn send_test_webhook(
+    config: &WebhookConfig,
+    endpoint: &WebhookEndpoint,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let client = config.client(endpoint)?;
+
+    let payload = WebhookPayload::new(
+        WebhookEvent::Test,
+        serde_json::json!({ "endpoint": endpoint.name }),
+    );
+    let payload_json = serde_json::to_string(&payload)?;
+
+    let started = Instant::now();
+    let result = send_webhook_request(&client, &endpoint.url.get(), &payload_json, &endpoint.secret.get()).await;
+    config.status.record(
+        &endpoint.name,
+        false,
+        started.elapsed(),
+        result.as_ref().err().map(|e| e.to_string()),
+    );
+    result
+}
+
+/// How far `X-Webhook-Timestamp` may be from the receiver's clock by default
+pub const DEFAULT_SIGNATURE_TOLERANCE: Duration = Duration::from_secs(300);
+
+fn signature_mac(secret: &str, timestamp: i64, payload: &str) -> Result<HmacSha256, Box<dyn std::error::Error + Send + Sync>> {
     let mut mac = HmacSha256::new_from_slice(secret.as_bytes())?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
     mac.update(payload.as_bytes());
-    let result = mac.finalize();
+    Ok(mac)
+}
+
+/// Generate HMAC-SHA256 signature over `{timestamp}.{payload}`
+pub(crate) fn generate_signature(
+    secret: &str,
+    timestamp: i64,
+    payload: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let result = signature_mac(secret, timestamp, payload)?.finalize();
     Ok(hex::encode(result.into_bytes()))
 }
 
-/// Send the actual HTTP request
-async fn send_webhook_request(
+/// Build a delivery request signed with the current timestamp
+///
+/// Deliveries are sent exactly like this; also used by
+/// `facilitator-cli webhook-test`.
+pub fn signed_request(
     client: &Client,
     url: &str,
     payload: &str,
-    signature: &str,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let response = client
+    secret: &str,
+) -> Result<reqwest::RequestBuilder, Box<dyn std::error::Error + Send + Sync>> {
+    let timestamp = chrono::Utc::now().timestamp();
+    let signature = generate_signature(secret, timestamp, payload)?;
+
+    Ok(client
         .post(url)
         .header("Content-Type", "application/json")
+        .header("X-Webhook-Timestamp", timestamp.to_string())
         .header("X-Webhook-Signature", signature)
         .header("User-Agent", "x402-facilitator/2.0")
-        .body(payload.to_string())
-        .send()
-        .await?;
+        .body(payload.to_string()))
+}
+
+/// Send the actual HTTP request
+///
+/// Each attempt is signed with a fresh timestamp, so retries aren't rejected
+/// by receivers enforcing a tolerance window.
+pub(crate) async fn send_webhook_request(
+    client: &Client,
+    url: &str,
+    payload: &str,
+    secret: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let response = signed_request(client, url, payload, secret)?.send().await?;
 
     if !response.status().is_success() {
         return Err(format!("HTTP {}: {}", response.status(), response.text().await?).into());
@@ -183,9 +895,36 @@ async fn send_webhook_request(
 }
 
 /// Verify webhook signature (for webhook receivers)
-pub fn verify_signature(secret: &str, payload: &str, signature: &str) -> bool {
-    match generate_signature(secret, payload) {
-        Ok(expected) => expected == signature,
+///
+/// `timestamp` and `signature` are the `X-Webhook-Timestamp` and
+/// `X-Webhook-Signature` headers. Deliveries whose timestamp is more than
+/// `tolerance` away from now are rejected, so captured requests can't be
+/// replayed later.
+pub fn verify_signature(secret: &str, payload: &str, timestamp: &str, signature: &str, tolerance: Duration) -> bool {
+    verify_signature_at(secret, payload, timestamp, signature, tolerance, chrono::Utc::now().timestamp())
+}
+
+fn verify_signature_at(
+    secret: &str,
+    payload: &str,
+    timestamp: &str,
+    signature: &str,
+    tolerance: Duration,
+    now: i64,
+) -> bool {
+    let Ok(timestamp) = timestamp.trim().parse::<i64>() else {
+        return false;
+    };
+    if now.abs_diff(timestamp) > tolerance.as_secs() {
+        return false;
+    }
+
+    let Ok(signature) = hex::decode(signature.trim()) else {
+        return false;
+    };
+    match signature_mac(secret, timestamp, payload) {
+        // Constant-time comparison
+        Ok(mac) => mac.verify_slice(&signature).is_ok(),
         Err(_) => false,
     }
 }
@@ -199,31 +938,83 @@ mod tests {
         let secret = "test_secret";
         let payload = r#"{"event":"test","timestamp":1234567890,"data":{}}"#;
 
-        let sig1 = generate_signature(secret, payload).unwrap();
-        let sig2 = generate_signature(secret, payload).unwrap();
+        let sig1 = generate_signature(secret, 1234567890, payload).unwrap();
+        let sig2 = generate_signature(secret, 1234567890, payload).unwrap();
 
         // Same input should produce same signature
         assert_eq!(sig1, sig2);
 
         // Signature should be hex-encoded 64 chars (SHA256)
         assert_eq!(sig1.len(), 64);
+
+        // The timestamp is part of the signed content
+        assert_ne!(sig1, generate_signature(secret, 1234567891, payload).unwrap());
+    }
+
+    #[test]
+    fn test_webhook_event_round_trip() {
+        for event in [
+            WebhookEvent::VerificationSuccess,
+            WebhookEvent::VerificationFailure,
+            WebhookEvent::SettlementSuccess,
+            WebhookEvent::SettlementFailure,
+            WebhookEvent::Test,
+            WebhookEvent::FeePayerLowBalance,
+            WebhookEvent::RpcUnhealthy,
+            WebhookEvent::RateLimitExceeded,
+            WebhookEvent::ReplayDetected,
+        ] {
+            let parsed: WebhookEvent = event.as_str().parse().unwrap();
+            assert_eq!(parsed.as_str(), event.as_str());
+        }
+        assert!("settlement".parse::<WebhookEvent>().is_err());
     }
 
     #[test]
     fn test_verify_signature() {
         let secret = "test_secret";
         let payload = r#"{"event":"test","timestamp":1234567890,"data":{}}"#;
+        let timestamp = chrono::Utc::now().timestamp();
 
-        let signature = generate_signature(secret, payload).unwrap();
+        let signature = generate_signature(secret, timestamp, payload).unwrap();
+        let header = timestamp.to_string();
 
         // Valid signature
-        assert!(verify_signature(secret, payload, &signature));
+        assert!(verify_signature(secret, payload, &header, &signature, DEFAULT_SIGNATURE_TOLERANCE));
 
         // Invalid signature
-        assert!(!verify_signature(secret, payload, "invalid_signature"));
+        assert!(!verify_signature(secret, payload, &header, "invalid_signature", DEFAULT_SIGNATURE_TOLERANCE));
 
         // Wrong secret
-        assert!(!verify_signature("wrong_secret", payload, &signature));
+        assert!(!verify_signature("wrong_secret", payload, &header, &signature, DEFAULT_SIGNATURE_TOLERANCE));
+
+        // Timestamp header doesn't match the signed one
+        let other = (timestamp - 1).to_string();
+        assert!(!verify_signature(secret, payload, &other, &signature, DEFAULT_SIGNATURE_TOLERANCE));
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_replays() {
+        let secret = "test_secret";
+        let payload = r#"{"event":"test","timestamp":1234567890,"data":{}}"#;
+        let signature = generate_signature(secret, 1_000, payload).unwrap();
+        let tolerance = Duration::from_secs(300);
+
+        assert!(verify_signature_at(secret, payload, "1000", &signature, tolerance, 1_300));
+        assert!(verify_signature_at(secret, payload, "1000", &signature, tolerance, 700));
+        assert!(!verify_signature_at(secret, payload, "1000", &signature, tolerance, 1_301));
+        assert!(!verify_signature_at(secret, payload, "1000", &signature, tolerance, 699));
+        assert!(!verify_signature_at(secret, payload, "not-a-number", &signature, tolerance, 1_000));
+    }
+
+    #[test]
+    fn test_webhook_secret_rotation_shared() {
+        let secret = WebhookSecret::new("old");
+        let clone = secret.clone();
+
+        secret.set("new");
+        assert_eq!(clone.get(), "new");
+        assert_eq!(format!("{:?}", clone), "[REDACTED]");
     }
 
     #[test]
@@ -235,6 +1026,16 @@ mod tests {
         assert_eq!(event.as_str(), "settlement.failure");
     }
 
+    #[test]
+    fn test_alert_throttle_suppresses_repeats() {
+        let throttle = AlertThrottle::new(Duration::from_secs(300));
+        assert!(throttle.allow("rpc.unhealthy", "rpc.example"));
+        assert!(!throttle.allow("rpc.unhealthy", "rpc.example"));
+        // Other subjects and events aren't held back
+        assert!(throttle.allow("rpc.unhealthy", "backup.example"));
+        assert!(throttle.allow("fee_payer.low_balance", "rpc.example"));
+    }
+
     #[test]
     fn test_webhook_payload_creation() {
         let data = serde_json::json!({"transaction": "abc123"});
@@ -244,5 +1045,200 @@ mod tests {
         assert_eq!(payload.data, data);
         assert!(payload.timestamp > 0);
     }
-}
 
+    #[test]
+    fn test_event_filter_matching() {
+        let filter = WebhookEventFilter::parse("settlement.*, verification.failure");
+
+        assert!(filter.matches("settlement.success"));
+        assert!(filter.matches("settlement.failure"));
+        assert!(filter.matches("verification.failure"));
+        assert!(!filter.matches("verification.success"));
+
+        // No patterns means every event
+        assert!(WebhookEventFilter::default().matches("verification.success"));
+    }
+
+    #[test]
+    fn test_endpoints_for_fans_out_by_filter() {
+        let endpoints = parse_endpoints(
+            r#"[
+                {"name": "billing", "url": "https://billing.example/hook", "secret": "b", "events": ["settlement.*"]},
+                {"name": "fraud", "url": "https://fraud.example/hook", "secret": "f", "events": ["verification.failure"]}
+            ]"#,
+        )
+        .unwrap();
+
+        let config = WebhookConfig {
+            url: WebhookUrl::new("https://default.example/hook"),
+            secret: WebhookSecret::new("d"),
+            events: WebhookEventFilter::parse("verification.*"),
+            tls: None,
+            endpoints: WebhookEndpoints::new(endpoints, None),
+            enabled: true,
+            timeout_seconds: 1,
+            retry_attempts: 1,
+            pending: InFlightCounter::new(),
+            status: WebhookDeliveryStatus::default(),
+            alerts: AlertThrottle::default(),
+            #[cfg(feature = "webhook-queue")]
+            queue: None,
+        };
+
+        let names = |event: &str| -> Vec<String> {
+            config.endpoints_for(event).into_iter().map(|endpoint| endpoint.name).collect()
+        };
+        assert_eq!(names("settlement.success"), vec!["billing"]);
+        assert_eq!(names("verification.failure"), vec!["default", "fraud"]);
+        assert_eq!(config.endpoint("fraud").unwrap().secret.get(), "f");
+
+        // Names must be unique
+        let duplicate = r#"[{"name": "default", "url": "https://x.example", "secret": "x"}]"#;
+        assert!(parse_endpoints(duplicate).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_updates_are_persisted() {
+        let path = std::env::temp_dir().join(format!("webhook-endpoints-{}.json", uuid::Uuid::new_v4()));
+        let endpoints = WebhookEndpoints::new(Vec::new(), Some(path.clone()));
+
+        let spec = WebhookEndpointSpec {
+            name: "billing".to_string(),
+            url: "https://billing.example/hook".to_string(),
+            secret: "b".to_string(),
+            events: vec!["settlement.*".to_string()],
+            enabled: true,
+            tls: None,
+        };
+        endpoints
+            .update(|list| {
+                list.push(WebhookEndpoint::from_spec(spec)?);
+                anyhow::Ok(())
+            })
+            .await
+            .unwrap();
+
+        let saved = parse_endpoints(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].url.get(), "https://billing.example/hook");
+        assert_eq!(endpoints.get().len(), 1);
+
+        // Failed changes leave both memory and file untouched
+        assert!(endpoints.update(|_| -> anyhow::Result<()> { anyhow::bail!("nope") }).await.is_err());
+        assert_eq!(endpoints.get().len(), 1);
+
+        // Without a file, runtime changes are refused
+        assert!(WebhookEndpoints::default().update(|_| anyhow::Ok(())).await.is_err());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_endpoint_spec_validation() {
+        let spec = |name: &str, url: &str| WebhookEndpointSpec {
+            name: name.to_string(),
+            url: url.to_string(),
+            secret: "s".to_string(),
+            events: Vec::new(),
+            enabled: true,
+            tls: None,
+        };
+
+        assert!(WebhookEndpoint::from_spec(spec("billing", "https://billing.example/hook")).is_ok());
+        assert!(WebhookEndpoint::from_spec(spec("dlq", "https://billing.example/hook")).is_err());
+        assert!(WebhookEndpoint::from_spec(spec("bad name", "https://billing.example/hook")).is_err());
+        assert!(WebhookEndpoint::from_spec(spec("billing", "ftp://billing.example/hook")).is_err());
+    }
+
+    #[test]
+    fn test_webhook_tls_validation() {
+        // Certificate and key must come as a pair
+        let half = WebhookTls {
+            client_cert_file: Some(PathBuf::from("/nonexistent/client.pem")),
+            ..WebhookTls::default()
+        };
+        assert!(half.configure(Client::builder()).is_err());
+
+        let missing_ca = WebhookTls {
+            ca_file: Some(PathBuf::from("/nonexistent/ca.pem")),
+            ..WebhookTls::default()
+        };
+        assert!(missing_ca.configure(Client::builder()).is_err());
+
+        assert!(WebhookTls::default().configure(Client::builder()).is_ok());
+
+        let spec: WebhookEndpointSpec = serde_json::from_str(
+            r#"{"name": "internal", "url": "https://hooks.internal/x402", "secret": "s",
+                "tls": {"ca_file": "/nonexistent/ca.pem"}}"#,
+        )
+        .unwrap();
+        assert!(WebhookEndpoint::from_spec(spec).is_err());
+    }
+
+    #[test]
+    fn test_webhook_tls_client_identity() {
+        let fixture = |name: &str| Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls").join(name);
+        let tls = WebhookTls {
+            ca_file: Some(fixture("cert.pem")),
+            client_cert_file: Some(fixture("cert.pem")),
+            client_key_file: Some(fixture("key.pem")),
+        };
+        let builder = tls.configure(Client::builder()).unwrap();
+        assert!(builder.build().is_ok());
+
+        // A key that doesn't parse is reported
+        let bad_key = WebhookTls { client_key_file: Some(fixture("cert.pem")), ..tls };
+        assert!(bad_key.configure(Client::builder()).is_err());
+    }
+
+    #[test]
+    fn test_delivery_status_counts_consecutive_failures() {
+        let status = WebhookDeliveryStatus::default();
+        let name = format!("status-test-{}", uuid::Uuid::new_v4());
+
+        status.record(&name, false, Duration::from_millis(5), Some("HTTP 500".to_string()));
+        status.record(&name, true, Duration::from_millis(5), Some("HTTP 502".to_string()));
+        let failing = status.get(&name);
+        assert_eq!(failing.failed, 2);
+        assert_eq!(failing.consecutive_failures, 2);
+        assert_eq!(failing.last_error.as_deref(), Some("HTTP 502"));
+        assert!(failing.last_success_at.is_none());
+
+        status.record(&name, true, Duration::from_millis(5), None);
+        let recovered = status.get(&name);
+        assert_eq!(recovered.delivered, 1);
+        assert_eq!(recovered.consecutive_failures, 0);
+        assert!(recovered.last_success_at.is_some());
+
+        assert_eq!(status.get("unknown").delivered, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_tracked_until_delivered() {
+        let config = WebhookConfig {
+            url: WebhookUrl::new("http://127.0.0.1:1/webhook"),
+            secret: WebhookSecret::new("secret"),
+            events: WebhookEventFilter::default(),
+            tls: None,
+            endpoints: WebhookEndpoints::default(),
+            enabled: true,
+            timeout_seconds: 1,
+            retry_attempts: 2,
+            pending: InFlightCounter::new(),
+            status: WebhookDeliveryStatus::default(),
+            alerts: AlertThrottle::default(),
+            #[cfg(feature = "webhook-queue")]
+            queue: None,
+        };
+
+        dispatch_webhook(
+            &config,
+            WebhookPayload::new(WebhookEvent::SettlementSuccess, serde_json::json!({})),
+        );
+        assert_eq!(config.pending.count(), 1);
+
+        // Delivery fails (nothing listening) but still completes its retries
+        assert!(config.flush(Duration::from_secs(10)).await);
+        assert_eq!(config.pending.count(), 0);
+    }
+}
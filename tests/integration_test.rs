@@ -9,19 +9,21 @@ use std::sync::Arc;
 
 // Helper to create test config
 fn create_test_config() -> x402_facilitator::config::Config {
-    use solana_client::rpc_client::RpcClient;
-    use solana_sdk::commitment_config::CommitmentConfig;
     use std::sync::Arc;
     use x402_facilitator::cache::AccountCache;
     use x402_facilitator::metrics::AppMetrics;
+    use x402_facilitator::solana::rpc::MockRpc;
+    use x402_facilitator::solana::signer::LocalKeypairSigner;
+    use solana_sdk::signature::Keypair;
 
-    let rpc_url = std::env::var("SOLANA_RPC_URL")
-        .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
-
-    let rpc_client = Arc::new(RpcClient::new_with_commitment(
-        rpc_url.clone(),
-        CommitmentConfig::confirmed(),
-    ));
+    // In-memory RPC: no devnet needed
+    let rpc_url = "http://mock-rpc".to_string();
+    let rpc_client = Arc::new(MockRpc::new());
+    let rpc_health = x402_facilitator::rpc_health::RpcHealthMonitor::new(
+        &rpc_url,
+        rpc_client.clone(),
+        std::time::Duration::from_secs(15),
+    );
 
     // Create test cache, metrics, rate limiter, dedup, and audit logger
     let account_cache = AccountCache::new(100, 30);
@@ -32,16 +34,40 @@ fn create_test_config() -> x402_facilitator::config::Config {
     x402_facilitator::config::Config {
         solana_rpc_url: rpc_url,
         fee_payer_private_key: "test_key".to_string(),
+        fee_payer_signer: Arc::new(LocalKeypairSigner::new(Keypair::new())),
         network: "solana-devnet".to_string(),
         port: 3000,
         rpc_client,
+        rpc_health,
         account_cache,
+        mint_cache: x402_facilitator::cache::MintCache::new(100, 3600),
+        account_watcher: None,
         metrics,
         rate_limiter: None, // Disable rate limiting for tests
         webhook: None, // Disable webhooks for tests
         transaction_dedup,
-        payment_expiry_seconds: 600,
+        settings: x402_facilitator::reload::SharedSettings::default(),
+        max_body_bytes: 64 * 1024,
+        max_batch_body_bytes: 2 * 1024 * 1024,
+        max_batch_size: 100,
+        offline_verify: false,
+        allow_unchecked_transfer: false,
+        sign_responses: false,
+        ata_rent_budget: x402_facilitator::rent_budget::AtaRentBudget::default(),
+        price_oracle: x402_facilitator::pricing::PriceOracle::default(),
+        fee_spend: x402_facilitator::spend::SpendTracker::default(),
+        verify_pool: x402_facilitator::parallel::VerifyPool::new(4),
+        schemes: x402_facilitator::schemes::SchemeRegistry::default(),
+        networks: x402_facilitator::networks::NetworkAdapters::default(),
+        tenants: x402_facilitator::tenants::Tenants::default(),
+        tenant: None,
         audit_logger,
+        jwt_auth: None,
+        admin_auth: None,
+        in_flight: x402_facilitator::middleware::in_flight::InFlightRequests::new(),
+        events: x402_facilitator::events::EventBus::default(),
+        settlements: x402_facilitator::settlements::SettlementHistory::disabled(),
+        subscriptions: x402_facilitator::subscriptions::SubscriptionLedger::new(),
     }
 }
 
@@ -110,6 +136,90 @@ async fn test_supported_endpoint() {
     assert!(exact.networks.contains(&"solana-devnet".to_string()));
 }
 
+#[tokio::test]
+async fn test_quote_endpoint() {
+    use x402_facilitator::types::responses::QuoteResponse;
+
+    let config = create_test_config();
+    let fee_payer = config.fee_payer_signer.pubkey().to_string();
+    let app = x402_facilitator::server::create_router(config);
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = app.clone().oneshot(get("/v1/quote?network=solana-devnet&amount=1000000")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let quote: QuoteResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(quote.fee_payer, fee_payer);
+    assert_eq!(quote.asset, x402_facilitator::requirements::USDC_DEVNET);
+    assert_eq!(quote.amount.as_deref(), Some("1000000"));
+    assert_eq!(quote.max_compute_unit_price, 5_000_000);
+    // Two signatures and a rounded-up priority fee
+    assert_eq!(quote.expected_fee_lamports, 10_001);
+    assert!(!quote.ata_creation_subsidized);
+
+    for uri in ["/quote?network=base", "/quote?network=solana-devnet&asset=nope", "/quote?network=solana-devnet&amount=0"] {
+        let response = app.clone().oneshot(get(uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_versioned_routes_and_legacy_aliases() {
+    let config = create_test_config();
+    let app = x402_facilitator::server::create_router(config);
+
+    for uri in ["/v1/supported", "/supported"] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        assert_eq!(response.headers()["x-api-version"], "v1", "{}", uri);
+    }
+
+    // Unknown versions are not routed
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/v9/supported")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_responses_compressed_when_accepted() {
+    let config = create_test_config();
+    let app = x402_facilitator::server::create_router(config);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/supported")
+                .header("accept-encoding", "gzip")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-encoding"], "gzip");
+}
+
 #[tokio::test]
 async fn test_verify_endpoint_with_invalid_scheme() {
     // Create config and router
@@ -166,7 +276,7 @@ async fn test_verify_endpoint_with_invalid_scheme() {
         let verify: VerifyResponse = serde_json::from_slice(&body).unwrap();
 
         // Should be invalid
-        assert_eq!(verify.is_valid, false);
+        assert!(!verify.is_valid);
         assert!(verify.invalid_reason.is_some());
         assert_eq!(verify.invalid_reason.unwrap(), "unsupported_scheme");
     }
@@ -227,12 +337,74 @@ async fn test_verify_endpoint_with_network_mismatch() {
         let verify: VerifyResponse = serde_json::from_slice(&body).unwrap();
 
         // Should be invalid
-        assert_eq!(verify.is_valid, false);
+        assert!(!verify.is_valid);
         assert!(verify.invalid_reason.is_some());
         assert_eq!(verify.invalid_reason.unwrap(), "invalid_network");
     }
 }
 
+#[tokio::test]
+async fn test_verify_responses_signed_by_facilitator() {
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+    use x402_facilitator::middleware::response_signature::{verify_response_signature, SIGNATURE_HEADER};
+
+    let verify_request = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "invalid_scheme",
+            "network": "solana-devnet",
+            "payload": { "transaction": "test_transaction_base64" }
+        },
+        "payment_requirements": {
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "maxAmountRequired": "1000000",
+            "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "payTo": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS",
+            "resource": "/api/resource",
+            "description": "Test payment",
+            "mimeType": "application/json",
+            "maxTimeoutSeconds": 30,
+            "extra": { "feePayer": "FeePayerPublicKeyHere" }
+        }
+    });
+    let verify = |app: axum::Router| {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/v1/verify")
+            .header("content-type", "application/json")
+            .body(Body::from(verify_request.to_string()))
+            .unwrap();
+        app.oneshot(request)
+    };
+
+    // Unsigned by default
+    let response = verify(x402_facilitator::server::create_router(create_test_config())).await.unwrap();
+    assert!(response.headers().get(SIGNATURE_HEADER).is_none());
+
+    let mut config = create_test_config();
+    config.sign_responses = true;
+    let app = x402_facilitator::server::create_router(config);
+
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/pubkey").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let pubkey: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(pubkey["signsResponses"], true);
+    let facilitator = Pubkey::from_str(pubkey["pubkey"].as_str().unwrap()).unwrap();
+
+    let response = verify(app).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let signature = response.headers()[SIGNATURE_HEADER].to_str().unwrap().to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    verify_response_signature(&body, &signature, &facilitator).unwrap();
+    assert!(verify_response_signature(br#"{"isValid":true}"#, &signature, &facilitator).is_err());
+}
+
 #[tokio::test]
 async fn test_404_on_invalid_endpoint() {
     // Create config and router
@@ -257,7 +429,7 @@ async fn test_404_on_invalid_endpoint() {
 
 #[tokio::test]
 async fn test_connection_pooling_reuses_client() {
-    // This test verifies that the Arc<RpcClient> is properly shared
+    // This test verifies that the Arc<dyn SolanaRpc> is properly shared
     // We create multiple requests and ensure they all work
     let config = create_test_config();
     
@@ -273,3 +445,1923 @@ async fn test_connection_pooling_reuses_client() {
     assert!(Arc::ptr_eq(&config2.rpc_client, &config3.rpc_client));
 }
 
+
+#[tokio::test]
+async fn test_jwt_auth_requires_settle_scope() {
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use x402_facilitator::middleware::auth::JwtAuth;
+
+    let mut config = create_test_config();
+    config.jwt_auth = Some(JwtAuth::hs256("test-secret"));
+    let app = x402_facilitator::server::create_router(config);
+
+    // No token at all
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/settle")
+                .header("content-type", "application/json")
+                .body(Body::from("{}"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()["www-authenticate"], "Bearer");
+
+    // Token with only the verify scope
+    let claims = json!({
+        "sub": "merchant-1",
+        "scope": "verify",
+        "exp": jsonwebtoken::get_current_timestamp() + 300,
+    });
+    let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(b"test-secret")).unwrap();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/settle")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::from("{}"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("settle"));
+}
+
+#[tokio::test]
+async fn test_rate_limit_returns_429_with_retry_after() {
+    use x402_facilitator::middleware::rate_limit::{RateLimitQuota, RateLimitState};
+
+    let mut config = create_test_config();
+    let quota = RateLimitQuota { per_second: 1, burst_size: 1 };
+    config.rate_limiter = Some(RateLimitState::new(quota, quota));
+    let app = x402_facilitator::server::create_router(config);
+
+    let settle_request = || {
+        Request::builder()
+            .method(Method::POST)
+            .uri("/settle")
+            .header("content-type", "application/json")
+            .header("x-api-key", "merchant-key")
+            .body(Body::from("{}"))
+            .unwrap()
+    };
+
+    // First request is within quota (and fails later on the empty body)
+    let response = app.clone().oneshot(settle_request()).await.unwrap();
+    assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Second request in the same second is rejected
+    let response = app.clone().oneshot(settle_request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("retry-after"));
+
+    // Unlimited routes are unaffected
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_oversized_batch_rejected_with_413() {
+    let mut config = create_test_config();
+    config.max_batch_size = 2;
+    let app = x402_facilitator::server::create_router(config);
+
+    let verify_request = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "invalid_scheme",
+            "network": "solana-devnet",
+            "payload": {
+                "transaction": "test_transaction_base64"
+            }
+        },
+        "payment_requirements": {
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "maxAmountRequired": "1000000",
+            "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "payTo": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS",
+            "resource": "/api/resource",
+            "description": "Test payment",
+            "mimeType": "application/json",
+            "maxTimeoutSeconds": 30,
+            "extra": {
+                "feePayer": "FeePayerPublicKeyHere"
+            }
+        }
+    });
+    let batch = json!([verify_request.clone(), verify_request.clone(), verify_request]);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/verify/batch")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_vec(&batch).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("maximum is 2"));
+}
+
+#[tokio::test]
+async fn test_oversized_body_rejected_with_413() {
+    let mut config = create_test_config();
+    config.max_body_bytes = 1024;
+    let app = x402_facilitator::server::create_router(config);
+
+    let oversized = vec![b' '; 2048];
+
+    // Declared via Content-Length
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/verify")
+                .header("content-type", "application/json")
+                .header("content-length", oversized.len())
+                .body(Body::from(oversized.clone()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    // Streamed without a length
+    let stream = futures::stream::iter(
+        oversized
+            .chunks(256)
+            .map(|chunk| Ok::<_, std::io::Error>(chunk.to_vec()))
+            .collect::<Vec<_>>(),
+    );
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/settle")
+                .header("content-type", "application/json")
+                .body(Body::from_stream(stream))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error: Value = serde_json::from_slice(&body).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("1024 byte limit"));
+}
+
+#[tokio::test]
+async fn test_admin_reload_endpoint() {
+    let config = create_test_config();
+    let app = x402_facilitator::server::create_router(config);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/admin/reload")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let result: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(result["reloaded"], true);
+    assert!(result["changed"].is_array());
+}
+
+#[tokio::test]
+async fn test_admin_dedup_inspect_and_purge() {
+    use sha2::{Digest, Sha256};
+    use x402_facilitator::middleware::admin_auth::AdminAuth;
+
+    let mut config = create_test_config();
+    config.transaction_dedup.mark_seen("stuck_transaction");
+    config.admin_auth = Some(AdminAuth::default().with_api_key("admin-secret"));
+    let app = x402_facilitator::server::create_router(config);
+
+    let request = |method: Method, uri: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("x-admin-key", "admin-secret")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(request(Method::GET, "/admin/dedup/stats")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let stats: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(stats["backend"], "memory");
+
+    let hash = format!("{:x}", Sha256::digest(b"stuck_transaction"));
+    let uri = format!("/admin/dedup/{}", hash);
+    let response = app.clone().oneshot(request(Method::DELETE, &uri)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+    // Already released
+    let response = app.clone().oneshot(request(Method::DELETE, &uri)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app.clone().oneshot(request(Method::DELETE, "/admin/dedup/not-a-hash")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app.oneshot(request(Method::DELETE, "/admin/dedup")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_admin_mutations_require_admin_auth() {
+    let config = create_test_config();
+    config.transaction_dedup.mark_seen("stuck_transaction");
+    let app = x402_facilitator::server::create_router(config);
+
+    let request = |method: Method, uri: &str| Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+
+    // Without admin auth configured, webhook and dedup changes are refused...
+    for (method, uri) in [
+        (Method::DELETE, "/admin/dedup"),
+        (Method::DELETE, "/admin/dedup/abc"),
+        (Method::POST, "/admin/webhooks"),
+        (Method::PATCH, "/admin/webhooks/billing"),
+        (Method::DELETE, "/admin/webhooks/billing"),
+    ] {
+        let response = app.clone().oneshot(request(method.clone(), uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{} {}", method, uri);
+    }
+
+    // ...while inspecting them stays available
+    let response = app.clone().oneshot(request(Method::GET, "/admin/dedup/stats")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.oneshot(request(Method::GET, "/admin/webhooks")).await.unwrap();
+    assert_ne!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_admin_cache_invalidate_and_warm() {
+    use solana_sdk::{account::Account, pubkey::Pubkey};
+
+    let config = create_test_config();
+    let pubkey = Pubkey::new_unique();
+    config.account_cache.store(pubkey, Account::default());
+    let app = x402_facilitator::server::create_router(config.clone());
+
+    let post = |uri: &str, body: Value| {
+        Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(post("/admin/cache/invalidate", json!({ "pubkey": pubkey.to_string() })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let result: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(result["was_cached"], true);
+    assert!(!config.account_cache.contains(&pubkey));
+
+    let response = app
+        .clone()
+        .oneshot(post("/admin/cache/invalidate", json!({ "all": true })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Neither or both of pubkey/all
+    let response = app.clone().oneshot(post("/admin/cache/invalidate", json!({}))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Rejected before any RPC call
+    let response = app
+        .oneshot(post("/admin/cache/warm", json!({ "pubkeys": ["not-a-pubkey"] })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_admin_audit_requires_storage() {
+    let config = create_test_config();
+    let app = x402_facilitator::server::create_router(config);
+
+    let request = |uri: &str| Request::builder().method(Method::GET).uri(uri).body(Body::empty()).unwrap();
+
+    // The audit logger in tests has no database attached
+    let response = app.clone().oneshot(request("/admin/audit?event_type=settlement_success")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+
+    let response = app
+        .oneshot(request("/admin/audit?from=2024-02-01T00:00:00Z&to=2024-01-01T00:00:00Z"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_admin_webhook_dlq_requires_queue() {
+    let config = create_test_config();
+    let app = x402_facilitator::server::create_router(config);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/admin/webhooks/dlq")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    // Webhooks are disabled in tests, so there is no queue to inspect
+    assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+}
+
+#[tokio::test]
+async fn test_admin_webhook_management_requires_webhooks() {
+    use x402_facilitator::middleware::admin_auth::AdminAuth;
+
+    let mut config = create_test_config();
+    config.admin_auth = Some(AdminAuth::default().with_api_key("admin-secret"));
+    let app = x402_facilitator::server::create_router(config);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/admin/webhooks")
+                .header("content-type", "application/json")
+                .header("x-admin-key", "admin-secret")
+                .body(Body::from(
+                    json!({
+                        "name": "billing",
+                        "url": "https://billing.example.com/hooks",
+                        "secret": "test-secret"
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+}
+
+#[tokio::test]
+async fn test_admin_webhook_status_requires_webhooks() {
+    let config = create_test_config();
+    let app = x402_facilitator::server::create_router(config);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/admin/webhooks/status")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+}
+
+#[tokio::test]
+async fn test_settlement_status_rejects_invalid_signature() {
+    let config = create_test_config();
+    let app = x402_facilitator::server::create_router(config);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/v1/settle/not-a-signature/status")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_settlement_history_requires_storage() {
+    let config = create_test_config();
+    let app = x402_facilitator::server::create_router(config);
+
+    for uri in [
+        "/v1/settlements?payer=abc",
+        "/settlements/5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnb",
+        "/v1/receipt/5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnb",
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // No settlement history in tests
+        assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_mint_allowlist_rejects_other_assets() {
+    use x402_facilitator::reload::{RuntimeSettings, SharedSettings};
+
+    let mut config = create_test_config();
+    config.settings = SharedSettings::new(RuntimeSettings {
+        allowed_mints: Some(vec!["So11111111111111111111111111111111111111112".to_string()]),
+        ..RuntimeSettings::default()
+    });
+    let app = x402_facilitator::server::create_router(config);
+
+    let verify_request = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": {
+                "transaction": "test_transaction_base64"
+            }
+        },
+        "payment_requirements": {
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "maxAmountRequired": "1000000",
+            "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "payTo": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS",
+            "resource": "/api/resource",
+            "description": "Test payment",
+            "mimeType": "application/json",
+            "maxTimeoutSeconds": 30,
+            "extra": {
+                "feePayer": "FeePayerPublicKeyHere"
+            }
+        }
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/verify")
+                .header("content-type", "application/json")
+                .body(Body::from(verify_request.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let verify: VerifyResponse = serde_json::from_slice(&body).unwrap();
+    assert!(!verify.is_valid);
+    assert_eq!(verify.invalid_reason.unwrap(), "unexpected_verify_error");
+}
+
+#[tokio::test]
+async fn test_admin_routes_require_api_key() {
+    use x402_facilitator::middleware::admin_auth::AdminAuth;
+
+    let mut config = create_test_config();
+    config.admin_auth = Some(AdminAuth::default().with_api_key("admin-secret"));
+    let app = x402_facilitator::server::create_router(config);
+
+    let request = |key: Option<&str>| {
+        let builder = Request::builder().method(Method::GET).uri("/admin/config");
+        let builder = match key {
+            Some(key) => builder.header("x-admin-key", key),
+            None => builder,
+        };
+        builder.body(Body::empty()).unwrap()
+    };
+
+    let response = app.clone().oneshot(request(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app.clone().oneshot(request(Some("wrong"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app.clone().oneshot(request(Some("admin-secret"))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Public endpoints are unaffected
+    let response = app
+        .oneshot(Request::builder().uri("/health").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_admin_routes_enforce_ip_allowlist() {
+    use axum::extract::ConnectInfo;
+    use std::net::SocketAddr;
+    use x402_facilitator::middleware::admin_auth::AdminAuth;
+
+    let mut config = create_test_config();
+    config.admin_auth = Some(AdminAuth::default().with_allowed_networks(vec!["10.0.0.0/8".parse().unwrap()]));
+    let app = x402_facilitator::server::create_router(config);
+
+    let request = |peer: &str| {
+        let mut req = Request::builder().uri("/admin/stats").body(Body::empty()).unwrap();
+        req.extensions_mut().insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+        req
+    };
+
+    let response = app.clone().oneshot(request("203.0.113.9:4000")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = app.oneshot(request("10.1.2.3:4000")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_admin_dashboard_served_without_admin_key() {
+    use x402_facilitator::middleware::admin_auth::AdminAuth;
+
+    let mut config = create_test_config();
+    config.admin_auth = Some(AdminAuth::default().with_api_key("admin-secret"));
+    let app = x402_facilitator::server::create_router(config);
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = app.clone().oneshot(get("/admin/ui")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/html"));
+
+    let response = app.clone().oneshot(get("/admin/ui/app.js")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(get("/admin/ui/missing.js")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // The data it shows still needs the key
+    let response = app.oneshot(get("/admin/stats")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+/// An initialized SPL Token mint account
+fn mint_account(decimals: u8) -> solana_sdk::account::Account {
+    use solana_sdk::{program_option::COption, program_pack::Pack};
+
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::None,
+        supply: 0,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    solana_sdk::account::Account { lamports: 1, data, owner: spl_token::ID, ..Default::default() }
+}
+
+#[tokio::test]
+async fn test_settle_against_mock_rpc() {
+    use solana_sdk::{account::Account, hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use spl_associated_token_account::get_associated_token_address;
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+    use x402_facilitator::solana::rpc::{MockRpc, SolanaRpc};
+    use x402_facilitator::solana::verifier::transaction_fee_lamports;
+    use x402_facilitator::types::responses::SettleResponse;
+
+    let rpc = Arc::new(MockRpc::new());
+    let mut config = create_test_config();
+    config.rpc_client = rpc.clone();
+    // Any settlement uses up the day's fee budget
+    let fee_spend = x402_facilitator::spend::SpendTracker::new(Some(1));
+    config.fee_spend = fee_spend.clone();
+
+    let payer = Keypair::new();
+    let merchant = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+        .asset(mint)
+        .pay_to(merchant)
+        .amount(1_000_000)
+        .resource("/premium")
+        .build()
+        .unwrap();
+
+    // Mint and both token accounts exist
+    rpc.set_account(mint, mint_account(6));
+    for owner in [payer.pubkey(), merchant] {
+        rpc.set_account(get_associated_token_address(&owner, &mint), Account { lamports: 1, ..Account::default() });
+    }
+
+    let recent_blockhash = Hash::new_unique();
+    let mut transaction = build_payment_transaction(&PaymentTransaction {
+        requirements: &requirements,
+        payer: payer.pubkey(),
+        decimals: 6,
+        token_program: spl_token::ID,
+        create_destination_ata: false,
+        recent_blockhash,
+    })
+    .unwrap();
+    transaction.partial_sign(&[&payer], recent_blockhash);
+
+    let settle_request = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+        },
+        "payment_requirements": requirements,
+    });
+
+    let post = || {
+        Request::builder()
+            .method(Method::POST)
+            .uri("/v1/settle")
+            .header("content-type", "application/json")
+            .body(Body::from(settle_request.to_string()))
+            .unwrap()
+    };
+
+    let app = x402_facilitator::server::create_router(config);
+    let response = app.clone().oneshot(post()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let settled: SettleResponse = serde_json::from_slice(&body).unwrap();
+    assert!(settled.success, "{:?}", settled.error_reason);
+    assert_eq!(settled.payer, Some(payer.pubkey().to_string()));
+
+    // Where and when it landed, without another RPC query
+    assert_eq!(settled.slot, Some(rpc.get_slot().unwrap()));
+    assert!(settled.block_time.is_some_and(|time| (time - chrono::Utc::now().timestamp()).abs() < 60));
+    assert_eq!(settled.commitment.as_deref(), Some("confirmed"));
+
+    // Submitted with the facilitator's signature added
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].signatures[0].to_string(), settled.transaction);
+    assert!(sent[0].verify().is_ok());
+
+    // The fee is accounted for, and the budget now refuses settlements
+    let spent = fee_spend.today();
+    assert_eq!(spent.fee_lamports, transaction_fee_lamports(&sent[0]));
+    assert_eq!((spent.rent_lamports, spent.settlements), (0, 1));
+
+    let response = app.oneshot(post()).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let refused: SettleResponse = serde_json::from_slice(&body).unwrap();
+    assert!(!refused.success);
+    assert_eq!(refused.error_reason.as_deref(), Some("fee_budget_exhausted"));
+    assert_eq!(rpc.sent_transactions().len(), 1);
+}
+
+#[cfg(feature = "settlement-history")]
+#[tokio::test]
+async fn test_settlements_isolated_by_tenant() {
+    use x402_facilitator::settlement_store::SettlementStore;
+    use x402_facilitator::settlements::{SettlementHistory, SettlementRecord, SettlementStatus};
+    use x402_facilitator::tenants::Tenants;
+
+    let path = std::env::temp_dir().join(format!("settlements-{}.db", uuid::Uuid::new_v4()));
+    let store = SettlementStore::open(path.to_str().unwrap());
+    for (signature, tenant) in [("sig-acme", "acme"), ("sig-globex", "globex")] {
+        let record = SettlementRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            signature: Some(signature.to_string()),
+            network: "solana-devnet".to_string(),
+            payer: Some(format!("{}-payer", tenant)),
+            recipient: format!("{}-merchant", tenant),
+            amount: "1000000".to_string(),
+            mint: "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU".to_string(),
+            slot: Some(42),
+            status: SettlementStatus::Success,
+            error: None,
+            created_at: chrono::Utc::now(),
+            tenant: Some(tenant.to_string()),
+        };
+        store.insert(&record).await.unwrap();
+    }
+
+    let mut config = create_test_config();
+    config.settlements = SettlementHistory::with_store(store);
+    config.tenants = Tenants::from_specs(
+        serde_json::from_value(json!([
+            { "id": "acme", "api_keys": ["acme-key"] },
+            { "id": "globex", "api_keys": ["globex-key"] }
+        ]))
+        .unwrap(),
+    )
+    .unwrap();
+    let app = x402_facilitator::server::create_router(config);
+
+    let get = |uri: &str, api_key: &str| {
+        Request::builder().uri(uri).header("x-api-key", api_key).body(Body::empty()).unwrap()
+    };
+
+    // acme lists only its own settlement
+    let response = app.clone().oneshot(get("/v1/settlements", "acme-key")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let listed: Value = serde_json::from_slice(&body).unwrap();
+    let signatures: Vec<&str> = listed["settlements"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|settlement| settlement["signature"].as_str().unwrap())
+        .collect();
+    assert_eq!(signatures, vec!["sig-acme"]);
+
+    // globex's settlement and receipt don't exist for acme
+    for uri in ["/v1/settlements/sig-globex", "/v1/receipt/sig-globex"] {
+        let response = app.clone().oneshot(get(uri, "acme-key")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", uri);
+    }
+    for uri in ["/v1/settlements/sig-globex", "/v1/receipt/sig-globex"] {
+        let response = app.clone().oneshot(get(uri, "globex-key")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+    }
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn test_settle_stops_on_expired_blockhash() {
+    use solana_sdk::{account::Account, hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use spl_associated_token_account::get_associated_token_address;
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+    use x402_facilitator::solana::rpc::MockRpc;
+    use x402_facilitator::types::responses::SettleResponse;
+
+    let rpc = Arc::new(MockRpc::new());
+    let mut config = create_test_config();
+    config.rpc_client = rpc.clone();
+
+    let payer = Keypair::new();
+    let merchant = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+        .asset(mint)
+        .pay_to(merchant)
+        .amount(1_000_000)
+        .resource("/premium")
+        .build()
+        .unwrap();
+    rpc.set_account(mint, mint_account(6));
+    for owner in [payer.pubkey(), merchant] {
+        rpc.set_account(get_associated_token_address(&owner, &mint), Account { lamports: 1, ..Account::default() });
+    }
+
+    // Signed on a blockhash that expires before the facilitator submits it
+    let recent_blockhash = Hash::new_unique();
+    let mut transaction = build_payment_transaction(&PaymentTransaction {
+        requirements: &requirements,
+        payer: payer.pubkey(),
+        decimals: 6,
+        token_program: spl_token::ID,
+        create_destination_ata: false,
+        recent_blockhash,
+    })
+    .unwrap();
+    transaction.partial_sign(&[&payer], recent_blockhash);
+    rpc.expire_blockhash(recent_blockhash);
+
+    let settle_request = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+        },
+        "payment_requirements": requirements,
+    });
+
+    let started = std::time::Instant::now();
+    let app = x402_facilitator::server::create_router(config);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/v1/settle")
+                .header("content-type", "application/json")
+                .body(Body::from(settle_request.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let settled: SettleResponse = serde_json::from_slice(&body).unwrap();
+
+    // Failed on the first attempt, without backing off to retry
+    assert!(!settled.success);
+    assert_eq!(settled.error_reason.as_deref(), Some("blockhash_expired"));
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    assert!(rpc.sent_transactions().is_empty());
+}
+
+#[tokio::test]
+async fn test_tenant_policy() {
+    use solana_sdk::{account::Account, hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use spl_associated_token_account::get_associated_token_address;
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+    use x402_facilitator::solana::rpc::MockRpc;
+    use x402_facilitator::solana::verifier::transaction_fee_lamports;
+    use x402_facilitator::tenants::Tenants;
+
+    let rpc = Arc::new(MockRpc::new());
+    let mut config = create_test_config();
+    config.rpc_client = rpc.clone();
+
+    let payer = Keypair::new();
+    let merchant = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    config.tenants = Tenants::from_specs(
+        serde_json::from_value(json!([
+            { "id": "acme", "api_keys": ["acme-key"], "pay_to": [Pubkey::new_unique().to_string()] },
+            { "id": "globex", "api_keys": ["globex-key"], "pay_to": [merchant.to_string()] }
+        ]))
+        .unwrap(),
+    )
+    .unwrap();
+
+    rpc.set_account(mint, mint_account(6));
+    for owner in [payer.pubkey(), merchant] {
+        rpc.set_account(get_associated_token_address(&owner, &mint), Account { lamports: 1, ..Account::default() });
+    }
+
+    let requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+        .asset(mint)
+        .pay_to(merchant)
+        .amount(1_000_000)
+        .resource("/premium")
+        .build()
+        .unwrap();
+    let recent_blockhash = Hash::new_unique();
+    let mut transaction = build_payment_transaction(&PaymentTransaction {
+        requirements: &requirements,
+        payer: payer.pubkey(),
+        decimals: 6,
+        token_program: spl_token::ID,
+        create_destination_ata: false,
+        recent_blockhash,
+    })
+    .unwrap();
+    transaction.partial_sign(&[&payer], recent_blockhash);
+
+    let request_body = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+        },
+        "payment_requirements": requirements,
+    });
+    let post = |api_key: Option<&str>| {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri("/v1/verify")
+            .header("content-type", "application/json");
+        if let Some(api_key) = api_key {
+            request = request.header("x-api-key", api_key);
+        }
+        request.body(Body::from(request_body.to_string())).unwrap()
+    };
+
+    let app = x402_facilitator::server::create_router(config);
+
+    // Payment routes need a tenant's key; public ones don't
+    for api_key in [None, Some("unknown-key")] {
+        let response = app.clone().oneshot(post(api_key)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri("/supported").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Paying an address acme doesn't receive on
+    let response = app.clone().oneshot(post(Some("acme-key"))).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let verify: VerifyResponse = serde_json::from_slice(&body).unwrap();
+    assert!(!verify.is_valid);
+    assert_eq!(verify.invalid_reason.as_deref(), Some("pay_to_not_allowed"));
+
+    let response = app.oneshot(post(Some("globex-key"))).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let verify: VerifyResponse = serde_json::from_slice(&body).unwrap();
+    assert!(verify.is_valid, "{:?}", verify.invalid_reason);
+
+    // With what the transaction pays
+    let details = verify.details.unwrap();
+    assert_eq!(details.amount, "1000000");
+    assert_eq!(details.asset, mint.to_string());
+    assert_eq!(details.destination, get_associated_token_address(&merchant, &mint).to_string());
+    assert_eq!(details.payer, payer.pubkey().to_string());
+    assert_eq!(details.fee_payer, requirements.extra.fee_payer);
+    assert!(!details.creates_ata);
+    assert_eq!(details.estimated_fee_lamports, transaction_fee_lamports(&transaction));
+}
+
+#[tokio::test]
+async fn test_dry_run_verify_leaves_transaction_unmarked() {
+    use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+
+    // Offline, so the transaction needs no accounts
+    let mut config = create_test_config();
+    config.offline_verify = true;
+
+    let payer = Keypair::new();
+    let requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+        .asset(Pubkey::new_unique())
+        .pay_to(Pubkey::new_unique())
+        .amount(1_000_000)
+        .resource("/premium")
+        .build()
+        .unwrap();
+    let recent_blockhash = Hash::new_unique();
+    let mut transaction = build_payment_transaction(&PaymentTransaction {
+        requirements: &requirements,
+        payer: payer.pubkey(),
+        decimals: 6,
+        token_program: spl_token::ID,
+        create_destination_ata: false,
+        recent_blockhash,
+    })
+    .unwrap();
+    transaction.partial_sign(&[&payer], recent_blockhash);
+
+    let post = |dry_run: bool| {
+        let body = json!({
+            "payment_payload": {
+                "x402Version": 1,
+                "scheme": "exact",
+                "network": "solana-devnet",
+                "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+            },
+            "payment_requirements": requirements,
+            "dryRun": dry_run,
+        });
+        Request::builder()
+            .method(Method::POST)
+            .uri("/v1/verify")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let app = x402_facilitator::server::create_router(config);
+    let verify = |dry_run: bool| {
+        let app = app.clone();
+        let request = post(dry_run);
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<VerifyResponse>(&body).unwrap()
+        }
+    };
+
+    // Dry runs can be repeated: the transaction isn't marked as seen
+    for _ in 0..2 {
+        let response = verify(true).await;
+        assert!(response.is_valid, "{:?}", response.invalid_reason);
+        assert!(response.details.is_some());
+    }
+    assert!(verify(false).await.is_valid);
+
+    // ...but still report replays
+    let response = verify(true).await;
+    assert!(!response.is_valid);
+    assert_eq!(response.invalid_reason.as_deref(), Some("unexpected_verify_error"));
+}
+
+#[tokio::test]
+async fn test_offline_verify_skips_account_checks() {
+    use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+    use x402_facilitator::types::responses::SettleResponse;
+
+    // The mock RPC knows no accounts, so online verification would fail
+    let mut config = create_test_config();
+    config.offline_verify = true;
+
+    let payer = Keypair::new();
+    let requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+        .asset(Pubkey::new_unique())
+        .pay_to(Pubkey::new_unique())
+        .amount(1_000_000)
+        .resource("/premium")
+        .build()
+        .unwrap();
+
+    let recent_blockhash = Hash::new_unique();
+    let mut transaction = build_payment_transaction(&PaymentTransaction {
+        requirements: &requirements,
+        payer: payer.pubkey(),
+        decimals: 6,
+        token_program: spl_token::ID,
+        create_destination_ata: false,
+        recent_blockhash,
+    })
+    .unwrap();
+    transaction.partial_sign(&[&payer], recent_blockhash);
+
+    let request_body = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+        },
+        "payment_requirements": requirements,
+    });
+    let post = |uri: &str| {
+        Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(request_body.to_string()))
+            .unwrap()
+    };
+
+    let app = x402_facilitator::server::create_router(config);
+    let response = app.clone().oneshot(post("/v1/verify")).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let verify: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(verify["isValid"], true, "{}", verify);
+    assert_eq!(
+        verify["checksSkipped"],
+        json!(["mint_exists", "mint_decimals", "source_ata_exists", "destination_ata_exists"])
+    );
+
+    // Nothing is submitted without the account checks
+    let response = app.oneshot(post("/v1/settle")).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let settled: SettleResponse = serde_json::from_slice(&body).unwrap();
+    assert!(!settled.success);
+    assert_eq!(settled.error_reason.as_deref(), Some("offline_mode"));
+}
+
+#[tokio::test]
+async fn test_usd_priced_requirements() {
+    use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+
+    // Offline, so the transaction needs no accounts
+    let mut config = create_test_config();
+    config.offline_verify = true;
+
+    // One token (6 decimals) worth $2
+    let mint = Pubkey::new_unique();
+    config.price_oracle.set_price(&mint.to_string(), 2.0);
+    let payer = Keypair::new();
+    let verify = |usd_amount: &str, asset: Pubkey| {
+        let requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+            .asset(asset)
+            .pay_to(Pubkey::new_unique())
+            .amount(1_000_000)
+            .resource("/premium")
+            .extra("usdAmount", json!(usd_amount))
+            .build()
+            .unwrap();
+        let recent_blockhash = Hash::new_unique();
+        let mut transaction = build_payment_transaction(&PaymentTransaction {
+            requirements: &requirements,
+            payer: payer.pubkey(),
+            decimals: 6,
+            token_program: spl_token::ID,
+            create_destination_ata: false,
+            recent_blockhash,
+        })
+        .unwrap();
+        transaction.partial_sign(&[&payer], recent_blockhash);
+
+        let body = json!({
+            "payment_payload": {
+                "x402Version": 1,
+                "scheme": "exact",
+                "network": "solana-devnet",
+                "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+            },
+            "payment_requirements": requirements,
+        });
+        let app = x402_facilitator::server::create_router(config.clone());
+        async move {
+            let request = Request::builder()
+                .method(Method::POST)
+                .uri("/v1/verify")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<VerifyResponse>(&body).unwrap()
+        }
+    };
+
+    let response = verify("2.00", mint).await;
+    assert!(response.is_valid, "{:?}", response.invalid_reason);
+    assert!(verify("2.01", mint).await.is_valid);
+
+    let response = verify("3.00", mint).await;
+    assert_eq!(response.invalid_reason.as_deref(), Some("usd_amount_out_of_bounds"));
+
+    // No price for this asset
+    let response = verify("2.00", Pubkey::new_unique()).await;
+    assert_eq!(response.invalid_reason.as_deref(), Some("usd_price_unavailable"));
+
+    let response = verify("two", mint).await;
+    assert_eq!(response.invalid_reason.as_deref(), Some("invalid_extra"));
+}
+
+#[tokio::test]
+async fn test_subscription_scheme() {
+    use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+    use x402_facilitator::subscriptions::BillingWindow;
+
+    // Offline, so the transaction needs no accounts
+    let mut config = create_test_config();
+    config.offline_verify = true;
+
+    let payer = Keypair::new();
+    let verify = |subscriber: Pubkey| {
+        let mut requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+            .asset(Pubkey::new_unique())
+            .pay_to(Pubkey::new_unique())
+            .amount(1_000_000)
+            .resource("/feed")
+            .extra("subscriber", json!(subscriber.to_string()))
+            .extra("periodSeconds", json!(30 * 86_400))
+            .build()
+            .unwrap();
+        requirements.scheme = "subscription".to_string();
+        let recent_blockhash = Hash::new_unique();
+        let mut transaction = build_payment_transaction(&PaymentTransaction {
+            requirements: &requirements,
+            payer: payer.pubkey(),
+            decimals: 6,
+            token_program: spl_token::ID,
+            create_destination_ata: false,
+            recent_blockhash,
+        })
+        .unwrap();
+        transaction.partial_sign(&[&payer], recent_blockhash);
+
+        // Dry runs, so the window is only used up by the ledger below
+        let body = json!({
+            "payment_payload": {
+                "x402Version": 1,
+                "scheme": "subscription",
+                "network": "solana-devnet",
+                "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+            },
+            "payment_requirements": requirements,
+            "dryRun": true,
+        });
+        let app = x402_facilitator::server::create_router(config.clone());
+        async move {
+            let request = Request::builder()
+                .method(Method::POST)
+                .uri("/v1/verify")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let response = app.oneshot(request).await.unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<VerifyResponse>(&body).unwrap()
+        }
+    };
+
+    let response = verify(payer.pubkey()).await;
+    assert!(response.is_valid, "{:?}", response.invalid_reason);
+
+    let response = verify(Pubkey::new_unique()).await;
+    assert_eq!(response.invalid_reason.as_deref(), Some("subscription_subscriber_mismatch"));
+
+    // Settled in this window: the next payment waits for the next one
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let window = BillingWindow::at(now, 30 * 86_400, 0).unwrap();
+    let payer_key = payer.pubkey().to_string();
+    config.subscriptions.begin(&payer_key, "/feed", &window).unwrap();
+    config.subscriptions.finish(&payer_key, "/feed", true);
+    let response = verify(payer.pubkey()).await;
+    assert_eq!(response.invalid_reason.as_deref(), Some("subscription_already_paid"));
+}
+
+#[tokio::test]
+async fn test_diagnostic_verify_reports_all_failures() {
+    use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+
+    let config = create_test_config();
+    let payer = Keypair::new();
+    let builder = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+        .asset(Pubkey::new_unique())
+        .pay_to(Pubkey::new_unique())
+        .resource("/premium");
+    let paid = builder.clone().amount(1_000_000).build().unwrap();
+
+    let recent_blockhash = Hash::new_unique();
+    let mut transaction = build_payment_transaction(&PaymentTransaction {
+        requirements: &paid,
+        payer: payer.pubkey(),
+        decimals: 6,
+        token_program: spl_token::ID,
+        create_destination_ata: false,
+        recent_blockhash,
+    })
+    .unwrap();
+    transaction.partial_sign(&[&payer], recent_blockhash);
+
+    // Wrong network and a different amount than was paid
+    let mut requirements = builder.amount(2_000_000).build().unwrap();
+    requirements.network = "solana".to_string();
+    let request_body = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+        },
+        "payment_requirements": requirements,
+    });
+
+    let app = x402_facilitator::server::create_router(config);
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/v1/verify?mode=diagnostic")
+                    .header("content-type", "application/json")
+                    .body(Body::from(request_body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let verify: VerifyResponse = serde_json::from_slice(&body).unwrap();
+
+        // Not marked as seen, so the second run doesn't report a duplicate
+        assert!(!verify.is_valid);
+        let checks: Vec<&str> = verify.failures.iter().map(|failure| failure.check.as_str()).collect();
+        assert_eq!(checks, ["scheme_and_network", "transfer"]);
+        assert_eq!(verify.invalid_reason.as_deref(), Some("invalid_network"));
+        assert_eq!(verify.failures[1].code, "invalid_exact_svm_payload_transaction_amount_mismatch");
+        assert!(!verify.failures[1].hint.is_empty());
+        assert_eq!(verify.payer, Some(payer.pubkey().to_string()));
+    }
+}
+
+#[tokio::test]
+async fn test_validate_reports_fields_without_marking() {
+    use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+    use x402_facilitator::types::responses::ValidateResponse;
+
+    let config = create_test_config();
+    let payer = Keypair::new();
+    let requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+        .asset(Pubkey::new_unique())
+        .pay_to(Pubkey::new_unique())
+        .amount(1_000_000)
+        .resource("/premium")
+        .build()
+        .unwrap();
+
+    let recent_blockhash = Hash::new_unique();
+    let mut transaction = build_payment_transaction(&PaymentTransaction {
+        requirements: &requirements,
+        payer: payer.pubkey(),
+        decimals: 6,
+        token_program: spl_token::ID,
+        create_destination_ata: false,
+        recent_blockhash,
+    })
+    .unwrap();
+    transaction.partial_sign(&[&payer], recent_blockhash);
+    let encoded = encode_transaction_to_base64(&transaction).unwrap();
+
+    let app = x402_facilitator::server::create_router(config.clone());
+    let validate = |payload: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri("/v1/validate")
+                        .header("content-type", "application/json")
+                        .body(Body::from(payload.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<ValidateResponse>(&body).unwrap()
+        }
+    };
+
+    // Valid without any accounts on chain, and still unseen afterwards
+    let report = validate(json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": encoded }
+        },
+        "payment_requirements": requirements,
+    }))
+    .await;
+    assert!(report.is_valid, "{:?}", report.fields);
+    assert!(report.fields.iter().any(|field| field.field.ends_with("instructions[2]")));
+    assert!(!config.transaction_dedup.is_duplicate(&encoded));
+
+    // Every broken field is reported
+    let report = validate(json!({
+        "payment_payload": {
+            "x402Version": 2,
+            "scheme": "exact",
+            "network": "solana",
+            "payload": { "transaction": "not base64!" }
+        },
+        "payment_requirements": requirements,
+    }))
+    .await;
+    assert!(!report.is_valid);
+    let invalid: Vec<(&str, &str)> = report
+        .fields
+        .iter()
+        .filter(|field| !field.valid)
+        .map(|field| (field.field.as_str(), field.code.as_deref().unwrap()))
+        .collect();
+    assert_eq!(
+        invalid,
+        [
+            ("payment_payload.x402Version", "unsupported_version"),
+            ("payment_payload.network", "invalid_network"),
+            ("payment_payload.payload.transaction", "invalid_transaction_encoding"),
+        ]
+    );
+
+    // Schema errors name the object that doesn't parse
+    let report = validate(json!({ "payment_payload": { "scheme": "exact" } })).await;
+    assert!(!report.is_valid);
+    assert_eq!(report.fields[0].field, "payment_payload");
+    assert_eq!(report.fields[1].field, "payment_requirements");
+}
+
+#[tokio::test]
+async fn test_failed_verification_allows_retry() {
+    use solana_sdk::{account::Account, hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use spl_associated_token_account::get_associated_token_address;
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+    use x402_facilitator::solana::rpc::MockRpc;
+
+    let rpc = Arc::new(MockRpc::new());
+    let mut config = create_test_config();
+    config.rpc_client = rpc.clone();
+
+    let payer = Keypair::new();
+    let merchant = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+        .asset(mint)
+        .pay_to(merchant)
+        .amount(1_000_000)
+        .resource("/premium")
+        .build()
+        .unwrap();
+
+    // The merchant's token account doesn't exist yet
+    rpc.set_account(mint, mint_account(6));
+    rpc.set_account(get_associated_token_address(&payer.pubkey(), &mint), Account { lamports: 1, ..Account::default() });
+
+    let recent_blockhash = Hash::new_unique();
+    let mut transaction = build_payment_transaction(&PaymentTransaction {
+        requirements: &requirements,
+        payer: payer.pubkey(),
+        decimals: 6,
+        token_program: spl_token::ID,
+        create_destination_ata: false,
+        recent_blockhash,
+    })
+    .unwrap();
+    transaction.partial_sign(&[&payer], recent_blockhash);
+
+    let request = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+        },
+        "payment_requirements": requirements,
+    });
+    let app = x402_facilitator::server::create_router(config);
+    let post = |uri: &'static str, body: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri(uri)
+                        .header("content-type", "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<Value>(&body).unwrap()
+        }
+    };
+
+    // Rejected, in a batch and on its own, without consuming the replay window
+    let batch = post("/v1/verify/batch", json!([request])).await;
+    assert_eq!(batch[0]["invalidReason"], "invalid_exact_svm_payload_transaction_receiver_ata_not_found");
+    let verify = post("/v1/verify", request.clone()).await;
+    assert_eq!(verify["invalidReason"], "invalid_exact_svm_payload_transaction_receiver_ata_not_found");
+
+    // Once the account exists, the identical payload verifies
+    rpc.set_account(get_associated_token_address(&merchant, &mint), Account { lamports: 1, ..Account::default() });
+    let verify = post("/v1/verify", request.clone()).await;
+    assert_eq!(verify["isValid"], true, "{}", verify);
+
+    // ...and only then counts as seen
+    let verify = post("/v1/verify", request).await;
+    assert_eq!(verify["isValid"], false);
+    assert_eq!(verify["invalidReason"], "unexpected_verify_error");
+}
+
+#[tokio::test]
+async fn test_verify_payment_header() {
+    use base64::{engine::general_purpose, Engine as _};
+
+    let requirements = json!({
+        "scheme": "exact",
+        "network": "solana-devnet",
+        "maxAmountRequired": "1000000",
+        "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+        "payTo": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS",
+        "resource": "/premium",
+        "description": "",
+        "mimeType": "application/json",
+        "maxTimeoutSeconds": 60,
+        "extra": { "feePayer": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS" }
+    });
+    let header = general_purpose::STANDARD.encode(
+        json!({
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": "AQ==" }
+        })
+        .to_string(),
+    );
+
+    let app = x402_facilitator::server::create_router(create_test_config());
+    let post = |uri: &'static str, x_payment: Option<&str>, body: Value| {
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("content-type", "application/json");
+        if let Some(x_payment) = x_payment {
+            request = request.header("x-payment", x_payment);
+        }
+        let app = app.clone();
+        async move {
+            let response = app.oneshot(request.body(Body::from(body.to_string())).unwrap()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<VerifyResponse>(&body).unwrap()
+        }
+    };
+
+    // Decoded and verified: the envelope is fine, the transaction isn't
+    let forwarded = post("/v1/verify/header", Some(&header), json!({ "payment_requirements": requirements })).await;
+    assert_eq!(forwarded.invalid_reason.as_deref(), Some("unexpected_verify_error"));
+
+    let in_body = post(
+        "/v1/verify/header",
+        None,
+        json!({ "payment_header": header, "payment_requirements": requirements }),
+    )
+    .await;
+    assert_eq!(in_body.invalid_reason.as_deref(), Some("unexpected_verify_error"));
+
+    // /verify takes the header value in place of the payload object
+    let inline = post("/v1/verify", None, json!({ "payment_payload": header, "payment_requirements": requirements })).await;
+    assert_eq!(inline.invalid_reason.as_deref(), Some("unexpected_verify_error"));
+
+    let missing = post("/v1/verify/header", None, json!({ "payment_requirements": requirements })).await;
+    assert_eq!(missing.invalid_reason.as_deref(), Some("invalid_payment_header"));
+
+    let garbled = post("/v1/verify/header", Some("not base64!"), json!({ "payment_requirements": requirements })).await;
+    assert_eq!(garbled.invalid_reason.as_deref(), Some("invalid_payment_header"));
+}
+
+#[tokio::test]
+async fn test_batch_verify_msgpack() {
+    let request = json!([{
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": "AQ==" }
+        },
+        "payment_requirements": {
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "maxAmountRequired": "1000000",
+            "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "payTo": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS",
+            "resource": "/premium",
+            "description": "",
+            "mimeType": "application/json",
+            "maxTimeoutSeconds": 60,
+            "extra": { "feePayer": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS" }
+        }
+    }]);
+
+    let app = x402_facilitator::server::create_router(create_test_config());
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/v1/verify/batch")
+                .header("content-type", "application/msgpack")
+                .body(Body::from(rmp_serde::to_vec_named(&request).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/msgpack");
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let results: Vec<VerifyResponse> = rmp_serde::from_slice(&body).unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(!results[0].is_valid);
+}
+
+#[tokio::test]
+async fn test_verify_stream_ndjson() {
+    let request = json!({
+        "payment_payload": {
+            "x402Version": 1,
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "payload": { "transaction": "AQ==" }
+        },
+        "payment_requirements": {
+            "scheme": "exact",
+            "network": "solana-devnet",
+            "maxAmountRequired": "1000000",
+            "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "payTo": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS",
+            "resource": "/premium",
+            "description": "",
+            "mimeType": "application/json",
+            "maxTimeoutSeconds": 60,
+            "extra": { "feePayer": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS" }
+        }
+    });
+    let body = format!("{}\n\nnot json\n{}\n", request, request);
+
+    let app = x402_facilitator::server::create_router(create_test_config());
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/v1/verify/stream?concurrency=2")
+                .header("content-type", "application/x-ndjson")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-ndjson");
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let mut results: Vec<Value> = body
+        .split(|&b| b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_slice(line).unwrap())
+        .collect();
+    results.sort_by_key(|result| result["index"].as_u64());
+
+    // The blank line isn't counted; the bad one doesn't end the stream
+    assert_eq!(results.len(), 3);
+    assert_eq!(results.iter().map(|r| r["index"].as_u64().unwrap()).collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert!(results.iter().all(|r| r["isValid"] == false));
+    assert_eq!(results[1]["invalidReason"], "invalid_request");
+    assert_eq!(results[1]["failures"][0]["check"], "request");
+}
+
+#[tokio::test]
+async fn test_batch_verify_prefetches_accounts_in_order() {
+    use solana_sdk::{account::Account, hash::Hash, pubkey::Pubkey, signature::Keypair, signer::Signer};
+    use spl_associated_token_account::get_associated_token_address;
+    use x402_facilitator::requirements::RequirementsBuilder;
+    use x402_facilitator::solana::builder::{build_payment_transaction, PaymentTransaction};
+    use x402_facilitator::solana::decoder::encode_transaction_to_base64;
+    use x402_facilitator::solana::rpc::MockRpc;
+
+    let rpc = Arc::new(MockRpc::new());
+    let mut config = create_test_config();
+    config.rpc_client = rpc.clone();
+
+    let merchant = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let requirements = RequirementsBuilder::new("solana-devnet", config.fee_payer_signer.pubkey())
+        .asset(mint)
+        .pay_to(merchant)
+        .amount(1_000_000)
+        .resource("/premium")
+        .build()
+        .unwrap();
+    rpc.set_account(mint, mint_account(6));
+    rpc.set_account(get_associated_token_address(&merchant, &mint), Account { lamports: 1, ..Account::default() });
+
+    // Every payer but the second has a token account
+    let payers: Vec<Keypair> = (0..5).map(|_| Keypair::new()).collect();
+    let batch: Vec<Value> = payers
+        .iter()
+        .enumerate()
+        .map(|(i, payer)| {
+            if i != 1 {
+                rpc.set_account(
+                    get_associated_token_address(&payer.pubkey(), &mint),
+                    Account { lamports: 1, ..Account::default() },
+                );
+            }
+            let recent_blockhash = Hash::new_unique();
+            let mut transaction = build_payment_transaction(&PaymentTransaction {
+                requirements: &requirements,
+                payer: payer.pubkey(),
+                decimals: 6,
+                token_program: spl_token::ID,
+                create_destination_ata: false,
+                recent_blockhash,
+            })
+            .unwrap();
+            transaction.partial_sign(&[payer], recent_blockhash);
+            json!({
+                "payment_payload": {
+                    "x402Version": 1,
+                    "scheme": "exact",
+                    "network": "solana-devnet",
+                    "payload": { "transaction": encode_transaction_to_base64(&transaction).unwrap() }
+                },
+                "payment_requirements": requirements,
+            })
+        })
+        .collect();
+
+    let app = x402_facilitator::server::create_router(config.clone());
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/v1/verify/batch")
+                .header("content-type", "application/json")
+                .body(Body::from(Value::Array(batch).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let results: Vec<VerifyResponse> = serde_json::from_slice(&body).unwrap();
+
+    // Results follow the request order
+    assert_eq!(results.len(), 5);
+    for (i, result) in results.iter().enumerate() {
+        if i == 1 {
+            assert_eq!(
+                result.invalid_reason.as_deref(),
+                Some("invalid_exact_svm_payload_transaction_sender_ata_not_found")
+            );
+        } else {
+            assert!(result.is_valid, "{:?}", result);
+        }
+    }
+
+    // Prefetched accounts that exist end up in the caches
+    assert!(config.mint_cache.contains(&mint));
+    assert!(config.account_cache.contains(&get_associated_token_address(&payers[0].pubkey(), &mint)));
+    assert!(!config.account_cache.contains(&get_associated_token_address(&payers[1].pubkey(), &mint)));
+}
+
+#[tokio::test]
+async fn test_batch_verify_envelope() {
+    let item = |id: Option<&str>| {
+        let mut item = json!({
+            "payment_payload": {
+                "x402Version": 1,
+                "scheme": "exact",
+                "network": "solana-devnet",
+                "payload": { "transaction": "AQ==" }
+            },
+            "payment_requirements": {
+                "scheme": "exact",
+                "network": "solana-devnet",
+                "maxAmountRequired": "1000000",
+                "asset": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                "payTo": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS",
+                "resource": "/premium",
+                "description": "",
+                "mimeType": "application/json",
+                "maxTimeoutSeconds": 60,
+                "extra": { "feePayer": "8VzycpqZpqYXMqKSZqYXMqKSZqYXMqKS" }
+            }
+        });
+        if let Some(id) = id {
+            item["id"] = json!(id);
+        }
+        item
+    };
+    let batch = json!([item(Some("order-1")), item(None), item(Some("order-3"))]);
+
+    let app = x402_facilitator::server::create_router(create_test_config());
+    let post = |uri: &'static str| {
+        let app = app.clone();
+        let batch = batch.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method(Method::POST)
+                        .uri(uri)
+                        .header("content-type", "application/json")
+                        .body(Body::from(batch.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<Value>(&body).unwrap()
+        }
+    };
+
+    let envelope = post("/v1/verify/batch?envelope=true").await;
+    let results = envelope["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["index"], 0);
+    assert_eq!(results[0]["id"], "order-1");
+    assert!(results[1].get("id").is_none());
+    assert_eq!(results[2]["index"], 2);
+    assert_eq!(results[2]["id"], "order-3");
+    assert!(results.iter().all(|result| result.get("error").is_none()));
+    assert_eq!(envelope["summary"], json!({ "total": 3, "valid": 0, "invalid": 3, "errors": 0 }));
+
+    // Without the envelope, ids are accepted and results stay a bare array
+    let bare = post("/v1/verify/batch").await;
+    assert_eq!(bare.as_array().unwrap().len(), 3);
+    assert!(bare[0].get("index").is_none());
+}
+
+#[tokio::test]
+async fn test_admin_rpc_health() {
+    let config = create_test_config();
+    let app = x402_facilitator::server::create_router(config);
+
+    let response = app
+        .oneshot(Request::builder().uri("/admin/rpc").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let snapshot: Value = serde_json::from_slice(&body).unwrap();
+    let primary = &snapshot["endpoints"][0];
+    assert_eq!(primary["endpoint"], "mock-rpc");
+    assert_eq!(primary["primary"], true);
+    assert_eq!(primary["healthy"], true);
+    assert_eq!(primary["slot_lag"], 0);
+}
+
+#[tokio::test]
+async fn test_admin_health_reports_settle_readiness() {
+    use solana_sdk::account::Account;
+    use x402_facilitator::solana::rpc::MockRpc;
+
+    let rpc = Arc::new(MockRpc::new());
+    let mut config = create_test_config();
+    config.rpc_client = rpc.clone();
+    let fee_payer = config.fee_payer_signer.pubkey();
+
+    let health = |config: x402_facilitator::config::Config| async move {
+        let response = x402_facilitator::server::create_router(config)
+            .oneshot(Request::builder().uri("/admin/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<Value>(&body).unwrap()
+    };
+
+    // Unfunded fee payer
+    let detail = health(config.clone()).await;
+    assert_eq!(detail["can_settle"], false);
+    assert_eq!(detail["status"], "degraded");
+    assert_eq!(detail["fee_payer"]["pubkey"], fee_payer.to_string());
+    assert_eq!(detail["fee_payer"]["balance_lamports"], 0);
+    assert_eq!(detail["dedup"]["backend"], "memory");
+    assert!(detail["webhook_queue_depth"].is_null());
+
+    rpc.set_account(fee_payer, Account { lamports: 1_000_000_000, ..Account::default() });
+    let detail = health(config.clone()).await;
+    assert_eq!(detail["can_settle"], true);
+    assert_eq!(detail["status"], "ok");
+    assert_eq!(detail["fee_payer"]["balance_sol"], 1.0);
+    assert_eq!(detail["blockhash"]["age_blocks"], 0);
+
+    // A lagging node serves a blockhash about to expire
+    rpc.set_blockhash_age(140);
+    let detail = health(config).await;
+    assert_eq!(detail["can_settle"], false);
+    assert_eq!(detail["blockhash"]["age_blocks"], 140);
+}
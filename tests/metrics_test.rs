@@ -78,19 +78,81 @@ async fn test_metrics_are_recorded() {
     assert!(metrics_text.contains("x402_health_requests_total"));
 }
 
+#[tokio::test]
+async fn test_cache_and_dedup_gauges_exported() {
+    let config = create_test_config();
+    config.transaction_dedup.mark_seen("some_transaction");
+    let app = x402_facilitator::server::create_router(config);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/metrics")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let metrics_text = String::from_utf8(body.to_vec()).unwrap();
+
+    // Sampled at scrape time
+    assert!(metrics_text.contains("x402_dedup_entries"));
+    assert!(metrics_text.contains("x402_cache_hit_ratio"));
+}
+
+#[tokio::test]
+async fn test_request_duration_recorded_by_route() {
+    let app = x402_facilitator::server::create_router(create_test_config());
+
+    for uri in ["/v1/settlements/5abc", "/no-such-route", "/metrics"] {
+        let _ = app
+            .clone()
+            .oneshot(Request::builder().method(Method::GET).uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+    }
+
+    let response = app
+        .oneshot(Request::builder().method(Method::GET).uri("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let metrics_text = String::from_utf8(body.to_vec()).unwrap();
+
+    // Labelled by route template, not the concrete path
+    let recorded = |endpoint: &str, status: &str| {
+        metrics_text.lines().any(|line| {
+            line.starts_with("x402_request_duration_seconds_count")
+                && line.contains(&format!("endpoint=\"{}\"", endpoint))
+                && line.contains("method=\"GET\"")
+                && line.contains(&format!("status=\"{}\"", status))
+        })
+    };
+    assert!(metrics_text.contains("endpoint=\"/v1/settlements/:signature\""));
+    assert!(recorded("unmatched", "404"));
+    assert!(recorded("/metrics", "200"));
+    assert!(!metrics_text.contains("5abc"));
+}
+
 // Helper to create test config
 fn create_test_config() -> x402_facilitator::Config {
-    use solana_client::rpc_client::RpcClient;
-    use solana_sdk::commitment_config::CommitmentConfig;
     use std::sync::Arc;
     use x402_facilitator::cache::AccountCache;
     use x402_facilitator::metrics::AppMetrics;
+    use x402_facilitator::solana::rpc::MockRpc;
+    use x402_facilitator::solana::signer::LocalKeypairSigner;
+    use solana_sdk::signature::Keypair;
 
-    let rpc_url = "https://api.devnet.solana.com".to_string();
-    let rpc_client = Arc::new(RpcClient::new_with_commitment(
-        rpc_url.clone(),
-        CommitmentConfig::confirmed(),
-    ));
+    let rpc_url = "http://mock-rpc".to_string();
+    let rpc_client = Arc::new(MockRpc::new());
+    let rpc_health = x402_facilitator::rpc_health::RpcHealthMonitor::new(
+        &rpc_url,
+        rpc_client.clone(),
+        std::time::Duration::from_secs(15),
+    );
 
     let account_cache = AccountCache::new(100, 30);
     let metrics = AppMetrics::new();
@@ -100,16 +162,40 @@ fn create_test_config() -> x402_facilitator::Config {
     x402_facilitator::Config {
         solana_rpc_url: rpc_url,
         fee_payer_private_key: "test_key".to_string(),
+        fee_payer_signer: Arc::new(LocalKeypairSigner::new(Keypair::new())),
         network: "solana-devnet".to_string(),
         port: 3000,
         rpc_client,
+        rpc_health,
         account_cache,
+        mint_cache: x402_facilitator::cache::MintCache::new(100, 3600),
+        account_watcher: None,
         metrics,
         rate_limiter: None, // Disable rate limiting for tests
         webhook: None, // Disable webhooks for tests
         transaction_dedup,
-        payment_expiry_seconds: 600,
+        settings: x402_facilitator::reload::SharedSettings::default(),
+        max_body_bytes: 64 * 1024,
+        max_batch_body_bytes: 2 * 1024 * 1024,
+        max_batch_size: 100,
+        offline_verify: false,
+        allow_unchecked_transfer: false,
+        sign_responses: false,
+        ata_rent_budget: x402_facilitator::rent_budget::AtaRentBudget::default(),
+        price_oracle: x402_facilitator::pricing::PriceOracle::default(),
+        fee_spend: x402_facilitator::spend::SpendTracker::default(),
+        verify_pool: x402_facilitator::parallel::VerifyPool::new(4),
+        schemes: x402_facilitator::schemes::SchemeRegistry::default(),
+        networks: x402_facilitator::networks::NetworkAdapters::default(),
+        tenants: x402_facilitator::tenants::Tenants::default(),
+        tenant: None,
         audit_logger,
+        jwt_auth: None,
+        admin_auth: None,
+        in_flight: x402_facilitator::middleware::in_flight::InFlightRequests::new(),
+        events: x402_facilitator::events::EventBus::default(),
+        settlements: x402_facilitator::settlements::SettlementHistory::disabled(),
+        subscriptions: x402_facilitator::subscriptions::SubscriptionLedger::new(),
     }
 }
 
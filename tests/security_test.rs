@@ -1,27 +1,36 @@
 use x402_facilitator::{
     config::Config,
-    types::{
-        requests::{VerifyRequest, PaymentPayload, SvmPayload, PaymentRequirements, ExtraFields},
-        responses::VerifyResponse,
-    },
+    middleware::rate_limit::RateLimitRoute,
+    reload,
+    types::requests::{VerifyRequest, PaymentPayload, SvmPayload, PaymentRequirements, ExtraFields},
 };
-use axum::{
-    body::Body,
-    http::{Request, StatusCode},
-    Router,
-};
-use tower::ServiceExt;
+use solana_sdk::signature::Keypair;
+use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Serializes environment mutation across concurrently running tests
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
 /// Helper to create a test config with security features enabled
 fn create_test_config() -> Config {
+    create_test_config_with(&[])
+}
+
+/// Helper to create a test config with specific environment overrides
+fn create_test_config_with(overrides: &[(&str, &str)]) -> Config {
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
     std::env::set_var("SOLANA_RPC_URL", "https://api.devnet.solana.com");
-    std::env::set_var("FEE_PAYER_PRIVATE_KEY", "test_key_12345678901234567890123456789012");
+    std::env::set_var("FEE_PAYER_PRIVATE_KEY", bs58::encode(Keypair::new().to_bytes()).into_string());
     std::env::set_var("NETWORK", "solana-devnet");
     std::env::set_var("PORT", "3000");
     std::env::set_var("DEDUP_MAX_ENTRIES", "100");
     std::env::set_var("DEDUP_WINDOW_SECONDS", "300");
     std::env::set_var("PAYMENT_EXPIRY_SECONDS", "600");
+
+    for (key, value) in overrides {
+        std::env::set_var(key, value);
+    }
     
     Config::from_env().expect("Failed to create test config")
 }
@@ -49,14 +58,13 @@ fn create_test_verify_request(transaction_data: &str, timestamp: Option<u64>) ->
             mime_type: "application/json".to_string(),
             max_timeout_seconds: 30,
             output_schema: None,
-            extra: ExtraFields {
-                fee_payer: "FeePayerPublicKeyHere123456789".to_string(),
-            },
+            extra: ExtraFields::new("FeePayerPublicKeyHere123456789"),
         },
+        dry_run: false,
     }
 }
 
-#[tokio::test]
+#[tokio::test(flavor = "multi_thread")]
 async fn test_transaction_deduplication() {
     // Create config with dedup enabled
     let config = create_test_config();
@@ -78,9 +86,10 @@ async fn test_transaction_deduplication() {
     assert!(!is_dup_3, "Different transaction should not be duplicate");
 }
 
-#[tokio::test]
+#[tokio::test(flavor = "multi_thread")]
 async fn test_payment_expiry_validation() {
     let config = create_test_config();
+    let payment_expiry_seconds = config.settings.load().payment_expiry_seconds;
     
     let current_time = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -89,24 +98,23 @@ async fn test_payment_expiry_validation() {
     
     // Test 1: Recent payment (should pass)
     let recent_timestamp = current_time - 60; // 1 minute old
-    let tx_data = "RECENT_TX";
     
     // Simulate expiry check (would be done in verify handler)
     let age = current_time - recent_timestamp;
-    assert!(age <= config.payment_expiry_seconds, "Recent payment should not be expired");
+    assert!(age <= payment_expiry_seconds, "Recent payment should not be expired");
     
     // Test 2: Expired payment (should fail)
     let old_timestamp = current_time - 700; // 700 seconds old (> 600 second expiry)
     let age_old = current_time - old_timestamp;
-    assert!(age_old > config.payment_expiry_seconds, "Old payment should be expired");
+    assert!(age_old > payment_expiry_seconds, "Old payment should be expired");
     
     // Test 3: Edge case - exactly at expiry limit
-    let edge_timestamp = current_time - config.payment_expiry_seconds;
+    let edge_timestamp = current_time - payment_expiry_seconds;
     let age_edge = current_time - edge_timestamp;
-    assert!(age_edge <= config.payment_expiry_seconds, "Payment at exact expiry should still be valid");
+    assert!(age_edge <= payment_expiry_seconds, "Payment at exact expiry should still be valid");
 }
 
-#[tokio::test]
+#[tokio::test(flavor = "multi_thread")]
 async fn test_dedup_cache_stats() {
     let config = create_test_config();
     
@@ -120,27 +128,25 @@ async fn test_dedup_cache_stats() {
     assert_eq!(stats.window_seconds, 300, "Window should be 300 seconds");
 }
 
-#[tokio::test]
+#[tokio::test(flavor = "multi_thread")]
 async fn test_dedup_window_config() {
     // Test with custom window
-    std::env::set_var("DEDUP_WINDOW_SECONDS", "120");
-    let config = create_test_config();
+    let config = create_test_config_with(&[("DEDUP_WINDOW_SECONDS", "120")]);
     
     let stats = config.transaction_dedup.stats();
     assert_eq!(stats.window_seconds, 120, "Custom window should be respected");
 }
 
-#[tokio::test]
+#[tokio::test(flavor = "multi_thread")]
 async fn test_payment_expiry_config() {
     // Test with custom expiry
-    std::env::set_var("PAYMENT_EXPIRY_SECONDS", "300");
-    let config = create_test_config();
+    let config = create_test_config_with(&[("PAYMENT_EXPIRY_SECONDS", "300")]);
     
-    assert_eq!(config.payment_expiry_seconds, 300, "Custom expiry should be respected");
+    assert_eq!(config.settings.load().payment_expiry_seconds, 300, "Custom expiry should be respected");
 }
 
-#[test]
-fn test_dedup_hash_consistency() {
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dedup_hash_consistency() {
     let config = create_test_config();
     
     let tx = "test_transaction_data";
@@ -152,8 +158,8 @@ fn test_dedup_hash_consistency() {
     assert!(config.transaction_dedup.is_duplicate(tx), "Should detect duplicate");
 }
 
-#[test]
-fn test_dedup_different_transactions() {
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dedup_different_transactions() {
     let config = create_test_config();
     
     config.transaction_dedup.mark_seen("tx1");
@@ -167,9 +173,9 @@ fn test_dedup_different_transactions() {
     assert!(config.transaction_dedup.is_duplicate("tx2"));
 }
 
-#[tokio::test]
+#[tokio::test(flavor = "multi_thread")]
 async fn test_timestamp_none_skips_expiry() {
-    let config = create_test_config();
+    let _config = create_test_config();
     
     // Request without timestamp should not fail expiry validation
     let request = create_test_verify_request("test_tx", None);
@@ -179,7 +185,7 @@ async fn test_timestamp_none_skips_expiry() {
     // This test just confirms the structure allows None
 }
 
-#[tokio::test]
+#[tokio::test(flavor = "multi_thread")]
 async fn test_timestamp_some_enables_expiry() {
     let current_time = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -192,13 +198,46 @@ async fn test_timestamp_some_enables_expiry() {
     assert_eq!(request.payment_payload.timestamp.unwrap(), current_time);
 }
 
-#[tokio::test]
+#[tokio::test(flavor = "multi_thread")]
 async fn test_config_validation_includes_security() {
     let config = create_test_config();
     
     // Config should have security features initialized
     let dedup_stats = config.transaction_dedup.stats();
-    assert!(dedup_stats.entry_count >= 0, "Dedup should be initialized");
-    assert!(config.payment_expiry_seconds > 0, "Payment expiry should be configured");
+    assert!(dedup_stats.window_seconds > 0, "Dedup should be initialized");
+    assert!(config.settings.load().payment_expiry_seconds > 0, "Payment expiry should be configured");
+}
+
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reload_applies_runtime_settings() {
+    let config = create_test_config();
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    std::env::set_var("PAYMENT_EXPIRY_SECONDS", "120");
+    std::env::set_var("RATE_LIMIT_SETTLE_BURST_SIZE", "9");
+    let changed = reload::reload(&config);
+    std::env::set_var("PAYMENT_EXPIRY_SECONDS", "600");
+    std::env::remove_var("RATE_LIMIT_SETTLE_BURST_SIZE");
+
+    assert_eq!(changed.unwrap(), vec!["payment_expiry_seconds", "settle_quota"]);
+    assert_eq!(config.settings.load().payment_expiry_seconds, 120, "Clones see the new expiry");
+
+    let limiter = config.rate_limiter.as_ref().expect("rate limiting enabled by default");
+    assert_eq!(limiter.quota(RateLimitRoute::Settle).burst_size, 9, "Rate limiter picks up new quota");
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reload_rejects_invalid_settings() {
+    let config = create_test_config();
+    let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    std::env::set_var("PAYMENT_EXPIRY_SECONDS", "120");
+    std::env::set_var("ALLOWED_MINTS", "not-a-mint");
+    let result = reload::reload(&config);
+    std::env::set_var("PAYMENT_EXPIRY_SECONDS", "600");
+    std::env::remove_var("ALLOWED_MINTS");
+
+    assert!(result.is_err(), "Invalid mint should fail the reload");
+    assert_eq!(config.settings.load().payment_expiry_seconds, 600, "Settings left unchanged");
+}